@@ -0,0 +1,1811 @@
+(** Generated by `charon --generate-ml` from the definitions of the Rust AST: do not edit. *)
+
+open Yojson.Basic
+open OfJsonBasic
+open GeneratedTypes
+
+let unit_of_json (js : json) : (unit, string) result =
+  match js with `Null -> Ok () | _ -> Error "unit_of_json"
+
+let json_of_json (js : json) : (json, string) result = Ok js
+let block_id_of_json = int_of_json
+let const_generic_var_id_of_json = int_of_json
+let disambiguator_of_json = int_of_json
+let field_id_of_json = int_of_json
+let file_id_of_json = int_of_json
+let fun_decl_id_of_json = int_of_json
+let global_decl_id_of_json = int_of_json
+let loan_id_of_json = int_of_json
+let loop_id_of_json = int_of_json
+let macro_expansion_id_of_json = int_of_json
+let module_id_of_json = int_of_json
+let promoted_id_of_json = int_of_json
+let region_id_of_json = int_of_json
+let scope_id_of_json = int_of_json
+let trait_clause_id_of_json = int_of_json
+let trait_decl_id_of_json = int_of_json
+let trait_impl_id_of_json = int_of_json
+let type_alias_id_of_json = int_of_json
+let type_decl_id_of_json = int_of_json
+let type_var_id_of_json = int_of_json
+let var_id_of_json = int_of_json
+let variant_id_of_json = int_of_json
+
+let rec loc_of_json (js : json) : (loc, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("line", line); ("col", col) ] ->
+        let* line = int_of_json line in
+        let* col = int_of_json col in
+        Ok ({ line; col } : loc)
+    | _ -> Error "")
+
+and span_of_json (js : json) : (span, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("file_id", file_id); ("beg", beg); ("end", end_) ] ->
+        let* file_id = file_id_of_json file_id in
+        let* beg = loc_of_json beg in
+        let* end_ = loc_of_json end_ in
+        Ok ({ file_id; beg; end_ } : span)
+    | _ -> Error "")
+
+and meta_of_json (js : json) : (meta, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("span", span); ("generated_from_span", generated_from_span); ("expansion", expansion); ("in_unsafe_block", in_unsafe_block) ] ->
+        let* span = span_of_json span in
+        let* generated_from_span = (option_of_json span_of_json) generated_from_span in
+        let* expansion = (option_of_json macro_expansion_id_of_json) expansion in
+        let* in_unsafe_block = bool_of_json in_unsafe_block in
+        Ok ({ span; generated_from_span; expansion; in_unsafe_block } : meta)
+    | _ -> Error "")
+
+and macro_expansion_of_json (js : json) : (macro_expansion, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("macro_name", macro_name); ("call_site", call_site); ("def_site", def_site); ("parent", parent) ] ->
+        let* macro_name = string_of_json macro_name in
+        let* call_site = span_of_json call_site in
+        let* def_site = (option_of_json span_of_json) def_site in
+        let* parent = (option_of_json macro_expansion_id_of_json) parent in
+        Ok ({ macro_name; call_site; def_site; parent } : macro_expansion)
+    | _ -> Error "")
+
+and attribute_of_json (js : json) : (attribute, string) result =
+  string_of_json js
+
+and inline_attr_of_json (js : json) : (inline_attr, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `String "Hint" -> Ok (Hint : inline_attr)
+    | `String "Never" -> Ok (Never : inline_attr)
+    | `String "Always" -> Ok (Always : inline_attr)
+    | _ -> Error "")
+
+and visibility_of_json (js : json) : (visibility, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `String "Public" -> Ok (Public : visibility)
+    | `Assoc [ ("Restricted", x0) ] ->
+        let* x0 = name_of_json x0 in
+        Ok (Restricted x0 : visibility)
+    | _ -> Error "")
+
+and item_meta_of_json (js : json) : (item_meta, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("meta", meta); ("attributes", attributes); ("cfg", cfg); ("inline", inline); ("public", public); ("visibility", visibility); ("stable_id", stable_id); ("hash", hash) ] ->
+        let* meta = meta_of_json meta in
+        let* attributes = (list_of_json attribute_of_json) attributes in
+        let* cfg = (list_of_json string_of_json) cfg in
+        let* inline = (option_of_json inline_attr_of_json) inline in
+        let* public = bool_of_json public in
+        let* visibility = (option_of_json visibility_of_json) visibility in
+        let* stable_id = string_of_json stable_id in
+        let* hash = string_of_json hash in
+        Ok ({ meta; attributes; cfg; inline; public; visibility; stable_id; hash } : item_meta)
+    | _ -> Error "")
+
+and file_info_of_json (js : json) : (file_info, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [] -> Ok ()
+    | _ -> Error "")
+
+and file_name_of_json (js : json) : (file_name, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("Virtual", x0) ] ->
+        let* x0 = string_of_json x0 in
+        Ok (Virtual x0 : file_name)
+    | `Assoc [ ("Local", x0) ] ->
+        let* x0 = string_of_json x0 in
+        Ok (Local x0 : file_name)
+    | `Assoc [ ("NotReal", x0) ] ->
+        let* x0 = string_of_json x0 in
+        Ok (NotReal x0 : file_name)
+    | _ -> Error "")
+
+and path_elem_of_json (js : json) : (path_elem, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("Ident", `List [ x0; x1 ]) ] ->
+        let* x0 = symbol_of_json x0 in
+        let* x1 = disambiguator_of_json x1 in
+        Ok (Ident (x0, x1) : path_elem)
+    | `Assoc [ ("Impl", x0) ] ->
+        let* x0 = impl_elem_of_json x0 in
+        Ok (Impl x0 : path_elem)
+    | _ -> Error "")
+
+and impl_elem_of_json (js : json) : (impl_elem, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("disambiguator", disambiguator); ("generics", generics); ("preds", preds); ("kind", kind) ] ->
+        let* disambiguator = disambiguator_of_json disambiguator in
+        let* generics = generic_params_of_json generics in
+        let* preds = predicates_of_json preds in
+        let* kind = impl_elem_kind_of_json kind in
+        Ok ({ disambiguator; generics; preds; kind } : impl_elem)
+    | _ -> Error "")
+
+and impl_elem_kind_of_json (js : json) : (impl_elem_kind, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("Ty", x0) ] ->
+        let* x0 = ty_of_json x0 in
+        Ok (Ty x0 : impl_elem_kind)
+    | `Assoc [ ("Trait", x0) ] ->
+        let* x0 = trait_decl_ref_of_json x0 in
+        Ok (Trait x0 : impl_elem_kind)
+    | _ -> Error "")
+
+and name_of_json (js : json) : (name, string) result =
+  (list_of_json path_elem_of_json) js
+
+and symbol_of_json (js : json) : (symbol, string) result =
+  int_of_json js
+
+and string_table_of_json (js : json) : (string_table, string) result =
+  (list_of_json string_of_json) js
+
+and field_name_of_json (js : json) : (field_name, string) result =
+  string_of_json js
+
+and type_var_of_json (js : json) : (type_var, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("index", index); ("name", name); ("is_sized", is_sized); ("default", default) ] ->
+        let* index = type_var_id_of_json index in
+        let* name = string_of_json name in
+        let* is_sized = bool_of_json is_sized in
+        let* default = (option_of_json ty_of_json) default in
+        Ok ({ index; name; is_sized; default } : type_var)
+    | _ -> Error "")
+
+and region_var_of_json (js : json) : (region_var, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("index", index); ("name", name) ] ->
+        let* index = region_id_of_json index in
+        let* name = (option_of_json string_of_json) name in
+        Ok ({ index; name } : region_var)
+    | _ -> Error "")
+
+and const_generic_var_of_json (js : json) : (const_generic_var, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("index", index); ("name", name); ("ty", ty); ("default", default) ] ->
+        let* index = const_generic_var_id_of_json index in
+        let* name = string_of_json name in
+        let* ty = literal_ty_of_json ty in
+        let* default = (option_of_json const_generic_of_json) default in
+        Ok ({ index; name; ty; default } : const_generic_var)
+    | _ -> Error "")
+
+and de_bruijn_id_of_json (js : json) : (de_bruijn_id, string) result =
+  int_of_json js
+
+and region_of_json (js : json) : (region, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `String "Static" -> Ok (Static : region)
+    | `Assoc [ ("BVar", `List [ x0; x1 ]) ] ->
+        let* x0 = de_bruijn_id_of_json x0 in
+        let* x1 = region_id_of_json x1 in
+        Ok (BVar (x0, x1) : region)
+    | `Assoc [ ("Body", x0) ] ->
+        let* x0 = region_id_of_json x0 in
+        Ok (Body x0 : region)
+    | `String "Erased" -> Ok (Erased : region)
+    | `String "Unknown" -> Ok (Unknown : region)
+    | _ -> Error "")
+
+and trait_instance_id_of_json (js : json) : (trait_instance_id, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("TraitImpl", x0) ] ->
+        let* x0 = trait_impl_id_of_json x0 in
+        Ok (TraitImpl x0 : trait_instance_id)
+    | `Assoc [ ("Specializable", `Assoc [ ("impl_id", impl_id); ("specializing_impls", specializing_impls) ]) ] ->
+        let* impl_id = trait_impl_id_of_json impl_id in
+        let* specializing_impls = (list_of_json trait_impl_id_of_json) specializing_impls in
+        Ok (Specializable { impl_id; specializing_impls } : trait_instance_id)
+    | `Assoc [ ("BuiltinOrAuto", x0) ] ->
+        let* x0 = trait_decl_id_of_json x0 in
+        Ok (BuiltinOrAuto x0 : trait_instance_id)
+    | `Assoc [ ("Clause", x0) ] ->
+        let* x0 = trait_clause_id_of_json x0 in
+        Ok (Clause x0 : trait_instance_id)
+    | `Assoc [ ("ParentClause", `List [ x0; x1; x2 ]) ] ->
+        let* x0 = trait_instance_id_of_json x0 in
+        let* x1 = trait_decl_id_of_json x1 in
+        let* x2 = trait_clause_id_of_json x2 in
+        Ok (ParentClause (x0, x1, x2) : trait_instance_id)
+    | `Assoc [ ("ItemClause", `List [ x0; x1; x2; x3 ]) ] ->
+        let* x0 = trait_instance_id_of_json x0 in
+        let* x1 = trait_decl_id_of_json x1 in
+        let* x2 = trait_item_name_of_json x2 in
+        let* x3 = trait_clause_id_of_json x3 in
+        Ok (ItemClause (x0, x1, x2, x3) : trait_instance_id)
+    | `Assoc [ ("FnPointer", x0) ] ->
+        let* x0 = ty_of_json x0 in
+        Ok (FnPointer x0 : trait_instance_id)
+    | `Assoc [ ("Closure", `List [ x0; x1 ]) ] ->
+        let* x0 = fun_decl_id_of_json x0 in
+        let* x1 = generic_args_of_json x1 in
+        Ok (Closure (x0, x1) : trait_instance_id)
+    | `String "SelfId" -> Ok (SelfId : trait_instance_id)
+    | `Assoc [ ("Unsolved", `List [ x0; x1 ]) ] ->
+        let* x0 = trait_decl_id_of_json x0 in
+        let* x1 = generic_args_of_json x1 in
+        Ok (Unsolved (x0, x1) : trait_instance_id)
+    | `Assoc [ ("Unknown", x0) ] ->
+        let* x0 = string_of_json x0 in
+        Ok (Unknown x0 : trait_instance_id)
+    | _ -> Error "")
+
+and trait_ref_of_json (js : json) : (trait_ref, string) result =
+  trait_ref_data_of_json js
+
+and trait_ref_data_of_json (js : json) : (trait_ref_data, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("trait_id", trait_id); ("generics", generics); ("trait_decl_ref", trait_decl_ref) ] ->
+        let* trait_id = trait_instance_id_of_json trait_id in
+        let* generics = generic_args_of_json generics in
+        let* trait_decl_ref = trait_decl_ref_of_json trait_decl_ref in
+        Ok ({ trait_id; generics; trait_decl_ref } : trait_ref_data)
+    | _ -> Error "")
+
+and trait_decl_ref_of_json (js : json) : (trait_decl_ref, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("trait_id", trait_id); ("generics", generics) ] ->
+        let* trait_id = trait_decl_id_of_json trait_id in
+        let* generics = generic_args_of_json generics in
+        Ok ({ trait_id; generics } : trait_decl_ref)
+    | _ -> Error "")
+
+and outlives_pred_of_json : 't 'u. (json -> ('t, string) result) -> (json -> ('u, string) result) -> json -> (('t, 'u) outlives_pred, string) result =
+ fun t_of_json u_of_json js ->
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `List [ x0; x1 ] ->
+        let* x0 = t_of_json x0 in
+        let* x1 = u_of_json x1 in
+        Ok ((x0, x1) : (_, _) outlives_pred)
+    | _ -> Error "")
+
+and region_outlives_of_json (js : json) : (region_outlives, string) result =
+  (outlives_pred_of_json region_of_json region_of_json) js
+
+and type_outlives_of_json (js : json) : (type_outlives, string) result =
+  (outlives_pred_of_json ty_of_json region_of_json) js
+
+and trait_type_constraint_of_json (js : json) : (trait_type_constraint, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("trait_ref", trait_ref); ("type_name", type_name); ("generics", generics); ("ty", ty) ] ->
+        let* trait_ref = trait_ref_of_json trait_ref in
+        let* type_name = trait_item_name_of_json type_name in
+        let* generics = generic_args_of_json generics in
+        let* ty = ty_of_json ty in
+        Ok ({ trait_ref; type_name; generics; ty } : trait_type_constraint)
+    | _ -> Error "")
+
+and predicates_of_json (js : json) : (predicates, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("regions_outlive", regions_outlive); ("types_outlive", types_outlive); ("trait_type_constraints", trait_type_constraints); ("regions_graph", regions_graph) ] ->
+        let* regions_outlive = (list_of_json region_outlives_of_json) regions_outlive in
+        let* types_outlive = (list_of_json type_outlives_of_json) types_outlive in
+        let* trait_type_constraints = (list_of_json trait_type_constraint_of_json) trait_type_constraints in
+        let* regions_graph = region_graph_of_json regions_graph in
+        Ok ({ regions_outlive; types_outlive; trait_type_constraints; regions_graph } : predicates)
+    | _ -> Error "")
+
+and region_graph_of_json (js : json) : (region_graph, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("regions", regions); ("types", types) ] ->
+        let* regions = (list_of_json (list_of_json region_of_json)) regions in
+        let* types = (list_of_json (list_of_json region_of_json)) types in
+        Ok ({ regions; types } : region_graph)
+    | _ -> Error "")
+
+and generic_args_of_json (js : json) : (generic_args, string) result =
+  generic_args_data_of_json js
+
+and generic_args_data_of_json (js : json) : (generic_args_data, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("regions", regions); ("types", types); ("const_generics", const_generics); ("trait_refs", trait_refs) ] ->
+        let* regions = (list_of_json region_of_json) regions in
+        let* types = (list_of_json ty_of_json) types in
+        let* const_generics = (list_of_json const_generic_of_json) const_generics in
+        let* trait_refs = (list_of_json trait_ref_of_json) trait_refs in
+        Ok ({ regions; types; const_generics; trait_refs } : generic_args_data)
+    | _ -> Error "")
+
+and generic_params_of_json (js : json) : (generic_params, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("regions", regions); ("types", types); ("const_generics", const_generics); ("trait_clauses", trait_clauses) ] ->
+        let* regions = (list_of_json region_var_of_json) regions in
+        let* types = (list_of_json type_var_of_json) types in
+        let* const_generics = (list_of_json const_generic_var_of_json) const_generics in
+        let* trait_clauses = (list_of_json trait_clause_of_json) trait_clauses in
+        Ok ({ regions; types; const_generics; trait_clauses } : generic_params)
+    | _ -> Error "")
+
+and trait_clause_of_json (js : json) : (trait_clause, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("clause_id", clause_id); ("meta", meta); ("trait_id", trait_id); ("bound_regions", bound_regions); ("generics", generics); ("is_const", is_const) ] ->
+        let* clause_id = trait_clause_id_of_json clause_id in
+        let* meta = (option_of_json meta_of_json) meta in
+        let* trait_id = trait_decl_id_of_json trait_id in
+        let* bound_regions = (list_of_json region_var_of_json) bound_regions in
+        let* generics = generic_args_of_json generics in
+        let* is_const = bool_of_json is_const in
+        Ok ({ clause_id; meta; trait_id; bound_regions; generics; is_const } : trait_clause)
+    | _ -> Error "")
+
+and type_decl_of_json (js : json) : (type_decl, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("def_id", def_id); ("item_meta", item_meta); ("is_local", is_local); ("name", name); ("generics", generics); ("preds", preds); ("kind", kind); ("auto_traits", auto_traits); ("variances", variances); ("rec_group", rec_group) ] ->
+        let* def_id = type_decl_id_of_json def_id in
+        let* item_meta = item_meta_of_json item_meta in
+        let* is_local = bool_of_json is_local in
+        let* name = name_of_json name in
+        let* generics = generic_params_of_json generics in
+        let* preds = predicates_of_json preds in
+        let* kind = type_decl_kind_of_json kind in
+        let* auto_traits = auto_traits_of_json auto_traits in
+        let* variances = variances_of_json variances in
+        let* rec_group = (option_of_json (list_of_json type_decl_id_of_json)) rec_group in
+        Ok ({ def_id; item_meta; is_local; name; generics; preds; kind; auto_traits; variances; rec_group } : type_decl)
+    | _ -> Error "")
+
+and type_alias_decl_of_json (js : json) : (type_alias_decl, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("def_id", def_id); ("item_meta", item_meta); ("name", name); ("generics", generics); ("ty", ty) ] ->
+        let* def_id = type_alias_id_of_json def_id in
+        let* item_meta = item_meta_of_json item_meta in
+        let* name = name_of_json name in
+        let* generics = generic_params_of_json generics in
+        let* ty = ty_of_json ty in
+        Ok ({ def_id; item_meta; name; generics; ty } : type_alias_decl)
+    | _ -> Error "")
+
+and type_alias_ref_of_json (js : json) : (type_alias_ref, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("alias_id", alias_id); ("generics", generics) ] ->
+        let* alias_id = type_alias_id_of_json alias_id in
+        let* generics = generic_args_of_json generics in
+        Ok ({ alias_id; generics } : type_alias_ref)
+    | _ -> Error "")
+
+and variance_of_json (js : json) : (variance, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `String "Covariant" -> Ok (Covariant : variance)
+    | `String "Invariant" -> Ok (Invariant : variance)
+    | `String "Contravariant" -> Ok (Contravariant : variance)
+    | `String "Bivariant" -> Ok (Bivariant : variance)
+    | _ -> Error "")
+
+and variances_of_json (js : json) : (variances, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("regions", regions); ("types", types) ] ->
+        let* regions = (list_of_json variance_of_json) regions in
+        let* types = (list_of_json variance_of_json) types in
+        Ok ({ regions; types } : variances)
+    | _ -> Error "")
+
+and auto_trait_impl_of_json (js : json) : (auto_trait_impl, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `String "Always" -> Ok (Always : auto_trait_impl)
+    | `String "IfParams" -> Ok (IfParams : auto_trait_impl)
+    | `String "Never" -> Ok (Never : auto_trait_impl)
+    | _ -> Error "")
+
+and auto_traits_of_json (js : json) : (auto_traits, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("send", send); ("sync", sync); ("unpin", unpin) ] ->
+        let* send = auto_trait_impl_of_json send in
+        let* sync = auto_trait_impl_of_json sync in
+        let* unpin = auto_trait_impl_of_json unpin in
+        Ok ({ send; sync; unpin } : auto_traits)
+    | _ -> Error "")
+
+and type_decl_kind_of_json (js : json) : (type_decl_kind, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("Struct", x0) ] ->
+        let* x0 = (list_of_json field_of_json) x0 in
+        Ok (Struct x0 : type_decl_kind)
+    | `Assoc [ ("Enum", `List [ x0; x1; x2 ]) ] ->
+        let* x0 = (list_of_json variant_of_json) x0 in
+        let* x1 = integer_ty_of_json x1 in
+        let* x2 = (option_of_json enum_layout_of_json) x2 in
+        Ok (Enum (x0, x1, x2) : type_decl_kind)
+    | `String "Opaque" -> Ok (Opaque : type_decl_kind)
+    | `Assoc [ ("ImplTrait", `Assoc [ ("bounds", bounds); ("hidden_ty", hidden_ty) ]) ] ->
+        let* bounds = (list_of_json impl_trait_bound_of_json) bounds in
+        let* hidden_ty = (option_of_json ty_of_json) hidden_ty in
+        Ok (ImplTrait { bounds; hidden_ty } : type_decl_kind)
+    | `Assoc [ ("Error", x0) ] ->
+        let* x0 = string_of_json x0 in
+        Ok (Error x0 : type_decl_kind)
+    | _ -> Error "")
+
+and impl_trait_bound_of_json (js : json) : (impl_trait_bound, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("trait_id", trait_id); ("generics", generics); ("type_constraints", type_constraints) ] ->
+        let* trait_id = trait_decl_id_of_json trait_id in
+        let* generics = generic_args_of_json generics in
+        let* type_constraints = (list_of_json (pair_of_json trait_item_name_of_json ty_of_json)) type_constraints in
+        Ok ({ trait_id; generics; type_constraints } : impl_trait_bound)
+    | _ -> Error "")
+
+and enum_layout_of_json (js : json) : (enum_layout, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `String "Single" -> Ok (Single : enum_layout)
+    | `String "Tagged" -> Ok (Tagged : enum_layout)
+    | `Assoc [ ("Niche", `Assoc [ ("untagged_variant", untagged_variant); ("field", field) ]) ] ->
+        let* untagged_variant = variant_id_of_json untagged_variant in
+        let* field = field_id_of_json field in
+        Ok (Niche { untagged_variant; field } : enum_layout)
+    | _ -> Error "")
+
+and variant_of_json (js : json) : (variant, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("meta", meta); ("name", name); ("fields", fields); ("discriminant", discriminant) ] ->
+        let* meta = meta_of_json meta in
+        let* name = symbol_of_json name in
+        let* fields = (list_of_json field_of_json) fields in
+        let* discriminant = scalar_value_of_json discriminant in
+        Ok ({ meta; name; fields; discriminant } : variant)
+    | _ -> Error "")
+
+and field_of_json (js : json) : (field, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("meta", meta); ("name", name); ("ty", ty); ("is_zst", is_zst); ("is_phantom_data", is_phantom_data); ("alias", alias) ] ->
+        let* meta = meta_of_json meta in
+        let* name = (option_of_json symbol_of_json) name in
+        let* ty = ty_of_json ty in
+        let* is_zst = bool_of_json is_zst in
+        let* is_phantom_data = bool_of_json is_phantom_data in
+        let* alias = (option_of_json type_alias_ref_of_json) alias in
+        Ok ({ meta; name; ty; is_zst; is_phantom_data; alias } : field)
+    | _ -> Error "")
+
+and integer_ty_of_json (js : json) : (integer_ty, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `String "Isize" -> Ok (Isize : integer_ty)
+    | `String "I8" -> Ok (I8 : integer_ty)
+    | `String "I16" -> Ok (I16 : integer_ty)
+    | `String "I32" -> Ok (I32 : integer_ty)
+    | `String "I64" -> Ok (I64 : integer_ty)
+    | `String "I128" -> Ok (I128 : integer_ty)
+    | `String "Usize" -> Ok (Usize : integer_ty)
+    | `String "U8" -> Ok (U8 : integer_ty)
+    | `String "U16" -> Ok (U16 : integer_ty)
+    | `String "U32" -> Ok (U32 : integer_ty)
+    | `String "U64" -> Ok (U64 : integer_ty)
+    | `String "U128" -> Ok (U128 : integer_ty)
+    | _ -> Error "")
+
+and target_info_of_json (js : json) : (target_info, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("target_triple", target_triple); ("pointer_width", pointer_width); ("is_little_endian", is_little_endian); ("c_int_width", c_int_width) ] ->
+        let* target_triple = string_of_json target_triple in
+        let* pointer_width = int_of_json pointer_width in
+        let* is_little_endian = bool_of_json is_little_endian in
+        let* c_int_width = int_of_json c_int_width in
+        Ok ({ target_triple; pointer_width; is_little_endian; c_int_width } : target_info)
+    | _ -> Error "")
+
+and ref_kind_of_json (js : json) : (ref_kind, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `String "Mut" -> Ok (Mut : ref_kind)
+    | `String "Shared" -> Ok (Shared : ref_kind)
+    | _ -> Error "")
+
+and type_id_of_json (js : json) : (type_id, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("Adt", x0) ] ->
+        let* x0 = type_decl_id_of_json x0 in
+        Ok (Adt x0 : type_id)
+    | `String "Tuple" -> Ok (Tuple : type_id)
+    | `Assoc [ ("Assumed", x0) ] ->
+        let* x0 = assumed_ty_of_json x0 in
+        Ok (Assumed x0 : type_id)
+    | _ -> Error "")
+
+and type_decls_of_json (js : json) : (type_decls, string) result =
+  (list_of_json (pair_of_json type_decl_id_of_json type_decl_of_json)) js
+
+and literal_ty_of_json (js : json) : (literal_ty, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("Integer", x0) ] ->
+        let* x0 = integer_ty_of_json x0 in
+        Ok (Integer x0 : literal_ty)
+    | `String "Bool" -> Ok (Bool : literal_ty)
+    | `String "Char" -> Ok (Char : literal_ty)
+    | _ -> Error "")
+
+and const_generic_of_json (js : json) : (const_generic, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("Global", x0) ] ->
+        let* x0 = global_decl_id_of_json x0 in
+        Ok (Global x0 : const_generic)
+    | `Assoc [ ("TraitConst", `List [ x0; x1 ]) ] ->
+        let* x0 = trait_ref_of_json x0 in
+        let* x1 = trait_item_name_of_json x1 in
+        Ok (TraitConst (x0, x1) : const_generic)
+    | `Assoc [ ("Var", x0) ] ->
+        let* x0 = const_generic_var_id_of_json x0 in
+        Ok (Var x0 : const_generic)
+    | `Assoc [ ("Value", x0) ] ->
+        let* x0 = literal_of_json x0 in
+        Ok (Value x0 : const_generic)
+    | _ -> Error "")
+
+and ty_of_json (js : json) : (ty, string) result =
+  ty_kind_of_json js
+
+and ty_kind_of_json (js : json) : (ty_kind, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("Adt", `List [ x0; x1 ]) ] ->
+        let* x0 = type_id_of_json x0 in
+        let* x1 = generic_args_of_json x1 in
+        Ok (Adt (x0, x1) : ty_kind)
+    | `Assoc [ ("TypeVar", x0) ] ->
+        let* x0 = type_var_id_of_json x0 in
+        Ok (TypeVar x0 : ty_kind)
+    | `Assoc [ ("Literal", x0) ] ->
+        let* x0 = literal_ty_of_json x0 in
+        Ok (Literal x0 : ty_kind)
+    | `String "Never" -> Ok (Never : ty_kind)
+    | `Assoc [ ("Ref", `List [ x0; x1; x2 ]) ] ->
+        let* x0 = region_of_json x0 in
+        let* x1 = ty_of_json x1 in
+        let* x2 = ref_kind_of_json x2 in
+        Ok (Ref (x0, x1, x2) : ty_kind)
+    | `Assoc [ ("RawPtr", `List [ x0; x1 ]) ] ->
+        let* x0 = ty_of_json x0 in
+        let* x1 = ref_kind_of_json x1 in
+        Ok (RawPtr (x0, x1) : ty_kind)
+    | `Assoc [ ("TraitType", `List [ x0; x1; x2 ]) ] ->
+        let* x0 = trait_ref_of_json x0 in
+        let* x1 = trait_item_name_of_json x1 in
+        let* x2 = generic_args_of_json x2 in
+        Ok (TraitType (x0, x1, x2) : ty_kind)
+    | `Assoc [ ("Arrow", `List [ x0; x1; x2 ]) ] ->
+        let* x0 = (list_of_json region_var_of_json) x0 in
+        let* x1 = (list_of_json ty_of_json) x1 in
+        let* x2 = ty_of_json x2 in
+        Ok (Arrow (x0, x1, x2) : ty_kind)
+    | `Assoc [ ("FnDef", `List [ x0; x1 ]) ] ->
+        let* x0 = fun_decl_id_of_json x0 in
+        let* x1 = generic_args_of_json x1 in
+        Ok (FnDef (x0, x1) : ty_kind)
+    | _ -> Error "")
+
+and assumed_ty_of_json (js : json) : (assumed_ty, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `String "Box" -> Ok (Box : assumed_ty)
+    | `String "PtrUnique" -> Ok (PtrUnique : assumed_ty)
+    | `String "PtrNonNull" -> Ok (PtrNonNull : assumed_ty)
+    | `String "Array" -> Ok (Array : assumed_ty)
+    | `String "Slice" -> Ok (Slice : assumed_ty)
+    | `String "Str" -> Ok (Str : assumed_ty)
+    | _ -> Error "")
+
+and params_info_of_json (js : json) : (params_info, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("num_region_params", num_region_params); ("num_type_params", num_type_params); ("num_const_generic_params", num_const_generic_params); ("num_trait_clauses", num_trait_clauses); ("num_regions_outlive", num_regions_outlive); ("num_types_outlive", num_types_outlive); ("num_trait_type_constraints", num_trait_type_constraints) ] ->
+        let* num_region_params = int_of_json num_region_params in
+        let* num_type_params = int_of_json num_type_params in
+        let* num_const_generic_params = int_of_json num_const_generic_params in
+        let* num_trait_clauses = int_of_json num_trait_clauses in
+        let* num_regions_outlive = int_of_json num_regions_outlive in
+        let* num_types_outlive = int_of_json num_types_outlive in
+        let* num_trait_type_constraints = int_of_json num_trait_type_constraints in
+        Ok ({ num_region_params; num_type_params; num_const_generic_params; num_trait_clauses; num_regions_outlive; num_types_outlive; num_trait_type_constraints } : params_info)
+    | _ -> Error "")
+
+and closure_kind_of_json (js : json) : (closure_kind, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `String "Fn" -> Ok (Fn : closure_kind)
+    | `String "FnMut" -> Ok (FnMut : closure_kind)
+    | `String "FnOnce" -> Ok (FnOnce : closure_kind)
+    | _ -> Error "")
+
+and capture_mode_of_json (js : json) : (capture_mode, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `String "ByValue" -> Ok (ByValue : capture_mode)
+    | `Assoc [ ("ByRef", x0) ] ->
+        let* x0 = ref_kind_of_json x0 in
+        Ok (ByRef x0 : capture_mode)
+    | _ -> Error "")
+
+and capture_projection_of_json (js : json) : (capture_projection, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `String "Deref" -> Ok (Deref : capture_projection)
+    | `Assoc [ ("Field", x0) ] ->
+        let* x0 = field_id_of_json x0 in
+        Ok (Field x0 : capture_projection)
+    | _ -> Error "")
+
+and captured_place_of_json (js : json) : (captured_place, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("var_id", var_id); ("projection", projection); ("mode", mode) ] ->
+        let* var_id = var_id_of_json var_id in
+        let* projection = (list_of_json capture_projection_of_json) projection in
+        let* mode = capture_mode_of_json mode in
+        Ok ({ var_id; projection; mode } : captured_place)
+    | _ -> Error "")
+
+and closure_info_of_json (js : json) : (closure_info, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("kind", kind); ("state", state); ("captures", captures); ("is_move", is_move); ("call_mut_shim", call_mut_shim); ("call_once_shim", call_once_shim) ] ->
+        let* kind = closure_kind_of_json kind in
+        let* state = (list_of_json ty_of_json) state in
+        let* captures = (list_of_json captured_place_of_json) captures in
+        let* is_move = bool_of_json is_move in
+        let* call_mut_shim = (option_of_json fun_decl_id_of_json) call_mut_shim in
+        let* call_once_shim = (option_of_json fun_decl_id_of_json) call_once_shim in
+        Ok ({ kind; state; captures; is_move; call_mut_shim; call_once_shim } : closure_info)
+    | _ -> Error "")
+
+and fun_sig_of_json (js : json) : (fun_sig, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("is_unsafe", is_unsafe); ("is_const", is_const); ("is_closure", is_closure); ("closure_info", closure_info); ("generics", generics); ("preds", preds); ("parent_params_info", parent_params_info); ("inputs", inputs); ("output", output); ("input_aliases", input_aliases); ("output_alias", output_alias) ] ->
+        let* is_unsafe = bool_of_json is_unsafe in
+        let* is_const = bool_of_json is_const in
+        let* is_closure = bool_of_json is_closure in
+        let* closure_info = (option_of_json closure_info_of_json) closure_info in
+        let* generics = generic_params_of_json generics in
+        let* preds = predicates_of_json preds in
+        let* parent_params_info = (option_of_json params_info_of_json) parent_params_info in
+        let* inputs = (list_of_json ty_of_json) inputs in
+        let* output = ty_of_json output in
+        let* input_aliases = (list_of_json (option_of_json type_alias_ref_of_json)) input_aliases in
+        let* output_alias = (option_of_json type_alias_ref_of_json) output_alias in
+        Ok ({ is_unsafe; is_const; is_closure; closure_info; generics; preds; parent_params_info; inputs; output; input_aliases; output_alias } : fun_sig)
+    | _ -> Error "")
+
+and literal_of_json (js : json) : (literal, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("Scalar", x0) ] ->
+        let* x0 = scalar_value_of_json x0 in
+        Ok (Scalar x0 : literal)
+    | `Assoc [ ("Bool", x0) ] ->
+        let* x0 = bool_of_json x0 in
+        Ok (Bool x0 : literal)
+    | `Assoc [ ("Char", x0) ] ->
+        let* x0 = char_of_json x0 in
+        Ok (Char x0 : literal)
+    | _ -> Error "")
+
+and place_of_json (js : json) : (place, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("var_id", var_id); ("projection", projection) ] ->
+        let* var_id = var_id_of_json var_id in
+        let* projection = projection_of_json projection in
+        Ok ({ var_id; projection } : place)
+    | _ -> Error "")
+
+and projection_of_json (js : json) : (projection, string) result =
+  (list_of_json projection_elem_of_json) js
+
+and projection_elem_of_json (js : json) : (projection_elem, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `String "Deref" -> Ok (Deref : projection_elem)
+    | `String "DerefBox" -> Ok (DerefBox : projection_elem)
+    | `String "DerefRawPtr" -> Ok (DerefRawPtr : projection_elem)
+    | `Assoc [ ("Field", `List [ x0; x1 ]) ] ->
+        let* x0 = field_proj_kind_of_json x0 in
+        let* x1 = field_id_of_json x1 in
+        Ok (Field (x0, x1) : projection_elem)
+    | `Assoc [ ("Index", `List [ x0; x1 ]) ] ->
+        let* x0 = var_id_of_json x0 in
+        let* x1 = ty_of_json x1 in
+        Ok (Index (x0, x1) : projection_elem)
+    | `Assoc [ ("Subslice", `Assoc [ ("from", from); ("to", to_); ("from_end", from_end) ]) ] ->
+        let* from = int_of_json from in
+        let* to_ = int_of_json to_ in
+        let* from_end = bool_of_json from_end in
+        Ok (Subslice { from; to_; from_end } : projection_elem)
+    | _ -> Error "")
+
+and field_proj_kind_of_json (js : json) : (field_proj_kind, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("ProjAdt", `List [ x0; x1 ]) ] ->
+        let* x0 = type_decl_id_of_json x0 in
+        let* x1 = (option_of_json variant_id_of_json) x1 in
+        Ok (Adt (x0, x1) : field_proj_kind)
+    | `Assoc [ ("ProjTuple", x0) ] ->
+        let* x0 = int_of_json x0 in
+        Ok (Tuple x0 : field_proj_kind)
+    | `String "ProjClosureState" -> Ok (ClosureState : field_proj_kind)
+    | _ -> Error "")
+
+and borrow_kind_of_json (js : json) : (borrow_kind, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `String "Shared" -> Ok (Shared : borrow_kind)
+    | `String "Mut" -> Ok (Mut : borrow_kind)
+    | `String "TwoPhaseMut" -> Ok (TwoPhaseMut : borrow_kind)
+    | `String "Shallow" -> Ok (Shallow : borrow_kind)
+    | _ -> Error "")
+
+and un_op_of_json (js : json) : (un_op, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `String "Not" -> Ok (Not : un_op)
+    | `String "Neg" -> Ok (Neg : un_op)
+    | `Assoc [ ("Cast", x0) ] ->
+        let* x0 = cast_kind_of_json x0 in
+        Ok (Cast x0 : un_op)
+    | `Assoc [ ("ArrayToSlice", `List [ x0; x1; x2 ]) ] ->
+        let* x0 = ref_kind_of_json x0 in
+        let* x1 = ty_of_json x1 in
+        let* x2 = const_generic_of_json x2 in
+        Ok (ArrayToSlice (x0, x1, x2) : un_op)
+    | _ -> Error "")
+
+and null_op_of_json (js : json) : (null_op, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `String "SizeOf" -> Ok (SizeOf : null_op)
+    | `String "AlignOf" -> Ok (AlignOf : null_op)
+    | `Assoc [ ("OffsetOf", x0) ] ->
+        let* x0 = (list_of_json (pair_of_json field_proj_kind_of_json field_id_of_json)) x0 in
+        Ok (OffsetOf x0 : null_op)
+    | _ -> Error "")
+
+and cast_kind_of_json (js : json) : (cast_kind, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("Scalar", `List [ x0; x1 ]) ] ->
+        let* x0 = literal_ty_of_json x0 in
+        let* x1 = literal_ty_of_json x1 in
+        Ok (Scalar (x0, x1) : cast_kind)
+    | `Assoc [ ("FnPtr", `List [ x0; x1 ]) ] ->
+        let* x0 = ty_of_json x0 in
+        let* x1 = ty_of_json x1 in
+        Ok (FnPtr (x0, x1) : cast_kind)
+    | _ -> Error "")
+
+and bin_op_of_json (js : json) : (bin_op, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `String "BitXor" -> Ok (BitXor : bin_op)
+    | `String "BitAnd" -> Ok (BitAnd : bin_op)
+    | `String "BitOr" -> Ok (BitOr : bin_op)
+    | `String "Eq" -> Ok (Eq : bin_op)
+    | `String "Lt" -> Ok (Lt : bin_op)
+    | `String "Le" -> Ok (Le : bin_op)
+    | `String "Ne" -> Ok (Ne : bin_op)
+    | `String "Ge" -> Ok (Ge : bin_op)
+    | `String "Gt" -> Ok (Gt : bin_op)
+    | `String "Div" -> Ok (Div : bin_op)
+    | `String "Rem" -> Ok (Rem : bin_op)
+    | `String "Add" -> Ok (Add : bin_op)
+    | `String "Sub" -> Ok (Sub : bin_op)
+    | `String "Mul" -> Ok (Mul : bin_op)
+    | `String "Shl" -> Ok (Shl : bin_op)
+    | `String "Shr" -> Ok (Shr : bin_op)
+    | `String "Offset" -> Ok (Offset : bin_op)
+    | `String "WrappingOffset" -> Ok (WrappingOffset : bin_op)
+    | `String "PtrDiff" -> Ok (PtrDiff : bin_op)
+    | `String "CheckedAdd" -> Ok (CheckedAdd : bin_op)
+    | `String "CheckedSub" -> Ok (CheckedSub : bin_op)
+    | `String "CheckedMul" -> Ok (CheckedMul : bin_op)
+    | `String "WrappingAdd" -> Ok (WrappingAdd : bin_op)
+    | `String "WrappingSub" -> Ok (WrappingSub : bin_op)
+    | `String "WrappingMul" -> Ok (WrappingMul : bin_op)
+    | `String "OverflowingAdd" -> Ok (OverflowingAdd : bin_op)
+    | `String "OverflowingSub" -> Ok (OverflowingSub : bin_op)
+    | `String "OverflowingMul" -> Ok (OverflowingMul : bin_op)
+    | `String "SaturatingAdd" -> Ok (SaturatingAdd : bin_op)
+    | `String "SaturatingSub" -> Ok (SaturatingSub : bin_op)
+    | `String "SaturatingMul" -> Ok (SaturatingMul : bin_op)
+    | _ -> Error "")
+
+and operand_of_json (js : json) : (operand, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("Copy", x0) ] ->
+        let* x0 = place_of_json x0 in
+        Ok (Copy x0 : operand)
+    | `Assoc [ ("Move", x0) ] ->
+        let* x0 = place_of_json x0 in
+        Ok (Move x0 : operand)
+    | `Assoc [ ("Const", x0) ] ->
+        let* x0 = constant_expr_of_json x0 in
+        Ok (Const x0 : operand)
+    | `Assoc [ ("Expr", `List [ x0; x1 ]) ] ->
+        let* x0 = rvalue_of_json x0 in
+        let* x1 = ty_of_json x1 in
+        Ok (Expr (x0, x1) : operand)
+    | _ -> Error "")
+
+and fun_id_of_json (js : json) : (fun_id, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("Regular", x0) ] ->
+        let* x0 = fun_decl_id_of_json x0 in
+        Ok (Regular x0 : fun_id)
+    | `Assoc [ ("Assumed", x0) ] ->
+        let* x0 = assumed_fun_id_of_json x0 in
+        Ok (Assumed x0 : fun_id)
+    | _ -> Error "")
+
+and assumed_fun_id_of_json (js : json) : (assumed_fun_id, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `String "BoxNew" -> Ok (BoxNew : assumed_fun_id)
+    | `String "BoxFree" -> Ok (BoxFree : assumed_fun_id)
+    | `String "ArrayIndexShared" -> Ok (ArrayIndexShared : assumed_fun_id)
+    | `String "ArrayIndexMut" -> Ok (ArrayIndexMut : assumed_fun_id)
+    | `String "ArrayToSliceShared" -> Ok (ArrayToSliceShared : assumed_fun_id)
+    | `String "ArrayToSliceMut" -> Ok (ArrayToSliceMut : assumed_fun_id)
+    | `String "ArrayRepeat" -> Ok (ArrayRepeat : assumed_fun_id)
+    | `String "SliceIndexShared" -> Ok (SliceIndexShared : assumed_fun_id)
+    | `String "SliceIndexMut" -> Ok (SliceIndexMut : assumed_fun_id)
+    | _ -> Error "")
+
+and fun_id_or_trait_method_ref_of_json (js : json) : (fun_id_or_trait_method_ref, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("Fun", x0) ] ->
+        let* x0 = fun_id_of_json x0 in
+        Ok (Fun x0 : fun_id_or_trait_method_ref)
+    | `Assoc [ ("Trait", `List [ x0; x1; x2 ]) ] ->
+        let* x0 = trait_ref_of_json x0 in
+        let* x1 = trait_item_name_of_json x1 in
+        let* x2 = fun_decl_id_of_json x2 in
+        Ok (Trait (x0, x1, x2) : fun_id_or_trait_method_ref)
+    | _ -> Error "")
+
+and fn_ptr_of_json (js : json) : (fn_ptr, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("func", func); ("generics", generics) ] ->
+        let* func = fun_id_or_trait_method_ref_of_json func in
+        let* generics = generic_args_of_json generics in
+        Ok ({ func; generics } : fn_ptr)
+    | _ -> Error "")
+
+and raw_constant_expr_of_json (js : json) : (raw_constant_expr, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("Literal", x0) ] ->
+        let* x0 = literal_of_json x0 in
+        Ok (Literal x0 : raw_constant_expr)
+    | `Assoc [ ("Adt", `List [ x0; x1 ]) ] ->
+        let* x0 = (option_of_json variant_id_of_json) x0 in
+        let* x1 = (list_of_json constant_expr_of_json) x1 in
+        Ok (Adt (x0, x1) : raw_constant_expr)
+    | `Assoc [ ("Global", `List [ x0; x1 ]) ] ->
+        let* x0 = global_decl_id_of_json x0 in
+        let* x1 = generic_args_of_json x1 in
+        Ok (Global (x0, x1) : raw_constant_expr)
+    | `Assoc [ ("Promoted", `List [ x0; x1 ]) ] ->
+        let* x0 = promoted_id_of_json x0 in
+        let* x1 = generic_args_of_json x1 in
+        Ok (Promoted (x0, x1) : raw_constant_expr)
+    | `Assoc [ ("TraitConst", `List [ x0; x1 ]) ] ->
+        let* x0 = trait_ref_of_json x0 in
+        let* x1 = trait_item_name_of_json x1 in
+        Ok (TraitConst (x0, x1) : raw_constant_expr)
+    | `Assoc [ ("Ref", x0) ] ->
+        let* x0 = constant_expr_of_json x0 in
+        Ok (Ref x0 : raw_constant_expr)
+    | `Assoc [ ("Var", x0) ] ->
+        let* x0 = const_generic_var_id_of_json x0 in
+        Ok (Var x0 : raw_constant_expr)
+    | `Assoc [ ("FnPtr", x0) ] ->
+        let* x0 = fn_ptr_of_json x0 in
+        Ok (FnPtr x0 : raw_constant_expr)
+    | `Assoc [ ("MutStatic", x0) ] ->
+        let* x0 = global_decl_id_of_json x0 in
+        Ok (MutStatic x0 : raw_constant_expr)
+    | _ -> Error "")
+
+and constant_expr_of_json (js : json) : (constant_expr, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("value", value); ("ty", ty) ] ->
+        let* value = raw_constant_expr_of_json value in
+        let* ty = ty_of_json ty in
+        Ok ({ value; ty } : constant_expr)
+    | _ -> Error "")
+
+and rvalue_of_json (js : json) : (rvalue, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("Use", x0) ] ->
+        let* x0 = operand_of_json x0 in
+        Ok (Use x0 : rvalue)
+    | `Assoc [ ("Ref", `List [ x0; x1 ]) ] ->
+        let* x0 = place_of_json x0 in
+        let* x1 = borrow_kind_of_json x1 in
+        Ok (Ref (x0, x1) : rvalue)
+    | `Assoc [ ("UnaryOp", `List [ x0; x1 ]) ] ->
+        let* x0 = un_op_of_json x0 in
+        let* x1 = operand_of_json x1 in
+        Ok (UnaryOp (x0, x1) : rvalue)
+    | `Assoc [ ("BinaryOp", `List [ x0; x1; x2 ]) ] ->
+        let* x0 = bin_op_of_json x0 in
+        let* x1 = operand_of_json x1 in
+        let* x2 = operand_of_json x2 in
+        Ok (BinaryOp (x0, x1, x2) : rvalue)
+    | `Assoc [ ("Discriminant", `List [ x0; x1 ]) ] ->
+        let* x0 = place_of_json x0 in
+        let* x1 = type_decl_id_of_json x1 in
+        Ok (Discriminant (x0, x1) : rvalue)
+    | `Assoc [ ("Aggregate", `List [ x0; x1 ]) ] ->
+        let* x0 = aggregate_kind_of_json x0 in
+        let* x1 = (list_of_json operand_of_json) x1 in
+        Ok (Aggregate (x0, x1) : rvalue)
+    | `Assoc [ ("Global", `List [ x0; x1 ]) ] ->
+        let* x0 = global_decl_id_of_json x0 in
+        let* x1 = generic_args_of_json x1 in
+        Ok (Global (x0, x1) : rvalue)
+    | `Assoc [ ("Len", `List [ x0; x1; x2 ]) ] ->
+        let* x0 = place_of_json x0 in
+        let* x1 = ty_of_json x1 in
+        let* x2 = (option_of_json const_generic_of_json) x2 in
+        Ok (Len (x0, x1, x2) : rvalue)
+    | `Assoc [ ("Repeat", `List [ x0; x1; x2 ]) ] ->
+        let* x0 = operand_of_json x0 in
+        let* x1 = ty_of_json x1 in
+        let* x2 = const_generic_of_json x2 in
+        Ok (Repeat (x0, x1, x2) : rvalue)
+    | `Assoc [ ("NullaryOp", `List [ x0; x1 ]) ] ->
+        let* x0 = null_op_of_json x0 in
+        let* x1 = ty_of_json x1 in
+        Ok (NullaryOp (x0, x1) : rvalue)
+    | _ -> Error "")
+
+and aggregate_kind_of_json (js : json) : (aggregate_kind, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("Adt", `List [ x0; x1; x2 ]) ] ->
+        let* x0 = type_id_of_json x0 in
+        let* x1 = (option_of_json variant_id_of_json) x1 in
+        let* x2 = generic_args_of_json x2 in
+        Ok (Adt (x0, x1, x2) : aggregate_kind)
+    | `Assoc [ ("Array", `List [ x0; x1 ]) ] ->
+        let* x0 = ty_of_json x0 in
+        let* x1 = const_generic_of_json x1 in
+        Ok (Array (x0, x1) : aggregate_kind)
+    | `Assoc [ ("Closure", `List [ x0; x1 ]) ] ->
+        let* x0 = fun_decl_id_of_json x0 in
+        let* x1 = generic_args_of_json x1 in
+        Ok (Closure (x0, x1) : aggregate_kind)
+    | _ -> Error "")
+
+and var_of_json (js : json) : (var, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("index", index); ("name", name); ("ty", ty); ("debug_info", debug_info) ] ->
+        let* index = var_id_of_json index in
+        let* name = (option_of_json string_of_json) name in
+        let* ty = ty_of_json ty in
+        let* debug_info = (list_of_json var_debug_info_of_json) debug_info in
+        Ok ({ index; name; ty; debug_info } : var)
+    | _ -> Error "")
+
+and var_debug_info_of_json (js : json) : (var_debug_info, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("name", name); ("projection", projection); ("meta", meta) ] ->
+        let* name = string_of_json name in
+        let* projection = projection_of_json projection in
+        let* meta = meta_of_json meta in
+        Ok ({ name; projection; meta } : var_debug_info)
+    | _ -> Error "")
+
+and g_expr_body_of_json : 't. (json -> ('t, string) result) -> json -> ('t g_expr_body, string) result =
+ fun t_of_json js ->
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("meta", meta); ("arg_count", arg_count); ("locals", locals); ("regions", regions); ("borrowck_facts", borrowck_facts); ("scopes", scopes); ("promoted", promoted); ("body", body) ] ->
+        let* meta = meta_of_json meta in
+        let* arg_count = int_of_json arg_count in
+        let* locals = (list_of_json var_of_json) locals in
+        let* regions = body_regions_of_json regions in
+        let* borrowck_facts = (option_of_json borrowck_facts_of_json) borrowck_facts in
+        let* scopes = (list_of_json scope_of_json) scopes in
+        let* promoted = (list_of_json global_decl_id_of_json) promoted in
+        let* body = t_of_json body in
+        Ok ({ meta; arg_count; locals; regions; borrowck_facts; scopes; promoted; body } : _ g_expr_body)
+    | _ -> Error "")
+
+and scope_of_json (js : json) : (scope, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("index", index); ("parent", parent); ("meta", meta); ("inlined", inlined); ("locals", locals) ] ->
+        let* index = scope_id_of_json index in
+        let* parent = (option_of_json scope_id_of_json) parent in
+        let* meta = meta_of_json meta in
+        let* inlined = bool_of_json inlined in
+        let* locals = (list_of_json var_id_of_json) locals in
+        Ok ({ index; parent; meta; inlined; locals } : scope)
+    | _ -> Error "")
+
+and body_regions_of_json (js : json) : (body_regions, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("vars", vars); ("constraints", constraints) ] ->
+        let* vars = (list_of_json region_var_of_json) vars in
+        let* constraints = (list_of_json region_outlives_of_json) constraints in
+        Ok ({ vars; constraints } : body_regions)
+    | _ -> Error "")
+
+and location_of_json (js : json) : (location, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("block", block); ("statement_index", statement_index) ] ->
+        let* block = block_id_of_json block in
+        let* statement_index = int_of_json statement_index in
+        Ok ({ block; statement_index } : location)
+    | _ -> Error "")
+
+and loan_of_json (js : json) : (loan, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("index", index); ("region", region); ("location", location) ] ->
+        let* index = loan_id_of_json index in
+        let* region = region_of_json region in
+        let* location = location_of_json location in
+        Ok ({ index; region; location } : loan)
+    | _ -> Error "")
+
+and borrowck_facts_of_json (js : json) : (borrowck_facts, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("loans", loans); ("subset_constraints", subset_constraints); ("loans_invalidated_at", loans_invalidated_at); ("loans_killed_at", loans_killed_at); ("loans_out_of_scope_at", loans_out_of_scope_at) ] ->
+        let* loans = (list_of_json loan_of_json) loans in
+        let* subset_constraints = (list_of_json (triple_of_json region_of_json region_of_json location_of_json)) subset_constraints in
+        let* loans_invalidated_at = (list_of_json (pair_of_json loan_id_of_json location_of_json)) loans_invalidated_at in
+        let* loans_killed_at = (list_of_json (pair_of_json loan_id_of_json location_of_json)) loans_killed_at in
+        let* loans_out_of_scope_at = (list_of_json (pair_of_json location_of_json (list_of_json loan_id_of_json))) loans_out_of_scope_at in
+        Ok ({ loans; subset_constraints; loans_invalidated_at; loans_killed_at; loans_out_of_scope_at } : borrowck_facts)
+    | _ -> Error "")
+
+and item_kind_of_json (js : json) : (item_kind, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `String "Regular" -> Ok (Regular : item_kind)
+    | `Assoc [ ("TraitItemImpl", `Assoc [ ("impl_id", impl_id); ("trait_id", trait_id); ("item_name", item_name); ("provided", provided) ]) ] ->
+        let* impl_id = trait_impl_id_of_json impl_id in
+        let* trait_id = trait_decl_id_of_json trait_id in
+        let* item_name = trait_item_name_of_json item_name in
+        let* provided = bool_of_json provided in
+        Ok (TraitItemImpl { impl_id; trait_id; item_name; provided } : item_kind)
+    | `Assoc [ ("TraitItemDecl", `List [ x0; x1 ]) ] ->
+        let* x0 = trait_decl_id_of_json x0 in
+        let* x1 = trait_item_name_of_json x1 in
+        Ok (TraitItemDecl (x0, x1) : item_kind)
+    | `Assoc [ ("TraitItemProvided", `List [ x0; x1 ]) ] ->
+        let* x0 = trait_decl_id_of_json x0 in
+        let* x1 = trait_item_name_of_json x1 in
+        Ok (TraitItemProvided (x0, x1) : item_kind)
+    | _ -> Error "")
+
+and g_fun_decl_of_json : 't. (json -> ('t, string) result) -> json -> ('t g_fun_decl, string) result =
+ fun t_of_json js ->
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("def_id", def_id); ("item_meta", item_meta); ("is_local", is_local); ("name", name); ("lang_item", lang_item); ("signature", signature); ("kind", kind); ("rec_group", rec_group); ("body", body) ] ->
+        let* def_id = fun_decl_id_of_json def_id in
+        let* item_meta = item_meta_of_json item_meta in
+        let* is_local = bool_of_json is_local in
+        let* name = name_of_json name in
+        let* lang_item = (option_of_json string_of_json) lang_item in
+        let* signature = fun_sig_of_json signature in
+        let* kind = item_kind_of_json kind in
+        let* rec_group = (option_of_json (list_of_json fun_decl_id_of_json)) rec_group in
+        let* body = (option_of_json (g_expr_body_of_json t_of_json)) body in
+        Ok ({ def_id; item_meta; is_local; name; lang_item; signature; kind; rec_group; body } : _ g_fun_decl)
+    | _ -> Error "")
+
+and g_global_decl_of_json : 't. (json -> ('t, string) result) -> json -> ('t g_global_decl, string) result =
+ fun t_of_json js ->
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("def_id", def_id); ("item_meta", item_meta); ("is_local", is_local); ("name", name); ("generics", generics); ("preds", preds); ("ty", ty); ("is_mutable", is_mutable); ("is_thread_local", is_thread_local); ("is_interior_mutable", is_interior_mutable); ("kind", kind); ("promoted", promoted); ("init_deps", init_deps); ("body", body) ] ->
+        let* def_id = global_decl_id_of_json def_id in
+        let* item_meta = item_meta_of_json item_meta in
+        let* is_local = bool_of_json is_local in
+        let* name = name_of_json name in
+        let* generics = generic_params_of_json generics in
+        let* preds = predicates_of_json preds in
+        let* ty = ty_of_json ty in
+        let* is_mutable = bool_of_json is_mutable in
+        let* is_thread_local = bool_of_json is_thread_local in
+        let* is_interior_mutable = bool_of_json is_interior_mutable in
+        let* kind = item_kind_of_json kind in
+        let* promoted = (option_of_json promoted_info_of_json) promoted in
+        let* init_deps = (list_of_json global_decl_id_of_json) init_deps in
+        let* body = (option_of_json (g_expr_body_of_json t_of_json)) body in
+        Ok ({ def_id; item_meta; is_local; name; generics; preds; ty; is_mutable; is_thread_local; is_interior_mutable; kind; promoted; init_deps; body } : _ g_global_decl)
+    | _ -> Error "")
+
+and promoted_info_of_json (js : json) : (promoted_info, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("parent", parent); ("index", index) ] ->
+        let* parent = any_trans_id_of_json parent in
+        let* index = promoted_id_of_json index in
+        Ok ({ parent; index } : promoted_info)
+    | _ -> Error "")
+
+and trait_item_name_of_json (js : json) : (trait_item_name, string) result =
+  string_of_json js
+
+and trait_decl_of_json (js : json) : (trait_decl, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("def_id", def_id); ("is_local", is_local); ("item_meta", item_meta); ("name", name); ("lang_item", lang_item); ("generics", generics); ("preds", preds); ("parent_clauses", parent_clauses); ("consts", consts); ("types", types); ("required_methods", required_methods); ("provided_methods", provided_methods) ] ->
+        let* def_id = trait_decl_id_of_json def_id in
+        let* is_local = bool_of_json is_local in
+        let* item_meta = item_meta_of_json item_meta in
+        let* name = name_of_json name in
+        let* lang_item = (option_of_json string_of_json) lang_item in
+        let* generics = generic_params_of_json generics in
+        let* preds = predicates_of_json preds in
+        let* parent_clauses = (list_of_json trait_clause_of_json) parent_clauses in
+        let* consts = (list_of_json (pair_of_json trait_item_name_of_json (pair_of_json ty_of_json (option_of_json global_decl_id_of_json)))) consts in
+        let* types = (list_of_json (pair_of_json trait_item_name_of_json (triple_of_json generic_params_of_json (list_of_json trait_clause_of_json) (option_of_json ty_of_json)))) types in
+        let* required_methods = (list_of_json (pair_of_json trait_item_name_of_json fun_decl_id_of_json)) required_methods in
+        let* provided_methods = (list_of_json (pair_of_json trait_item_name_of_json (option_of_json fun_decl_id_of_json))) provided_methods in
+        Ok ({ def_id; is_local; item_meta; name; lang_item; generics; preds; parent_clauses; consts; types; required_methods; provided_methods } : trait_decl)
+    | _ -> Error "")
+
+and trait_impl_kind_of_json (js : json) : (trait_impl_kind, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `String "Regular" -> Ok (Regular : trait_impl_kind)
+    | `String "Blanket" -> Ok (Blanket : trait_impl_kind)
+    | `Assoc [ ("Derived", x0) ] ->
+        let* x0 = string_of_json x0 in
+        Ok (Derived x0 : trait_impl_kind)
+    | _ -> Error "")
+
+and trait_impl_of_json (js : json) : (trait_impl, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("def_id", def_id); ("is_local", is_local); ("name", name); ("item_meta", item_meta); ("impl_kind", impl_kind); ("is_negative", is_negative); ("is_const", is_const); ("impl_trait", impl_trait); ("generics", generics); ("preds", preds); ("parent_trait_refs", parent_trait_refs); ("consts", consts); ("types", types); ("required_methods", required_methods); ("provided_methods", provided_methods); ("default_items", default_items) ] ->
+        let* def_id = trait_impl_id_of_json def_id in
+        let* is_local = bool_of_json is_local in
+        let* name = name_of_json name in
+        let* item_meta = item_meta_of_json item_meta in
+        let* impl_kind = trait_impl_kind_of_json impl_kind in
+        let* is_negative = bool_of_json is_negative in
+        let* is_const = bool_of_json is_const in
+        let* impl_trait = trait_decl_ref_of_json impl_trait in
+        let* generics = generic_params_of_json generics in
+        let* preds = predicates_of_json preds in
+        let* parent_trait_refs = (list_of_json trait_ref_of_json) parent_trait_refs in
+        let* consts = (list_of_json (pair_of_json trait_item_name_of_json (pair_of_json ty_of_json global_decl_id_of_json))) consts in
+        let* types = (list_of_json (pair_of_json trait_item_name_of_json (triple_of_json generic_params_of_json (list_of_json trait_ref_of_json) ty_of_json))) types in
+        let* required_methods = (list_of_json (pair_of_json trait_item_name_of_json fun_decl_id_of_json)) required_methods in
+        let* provided_methods = (list_of_json (pair_of_json trait_item_name_of_json (pair_of_json fun_decl_id_of_json bool_of_json))) provided_methods in
+        let* default_items = (list_of_json trait_item_name_of_json) default_items in
+        Ok ({ def_id; is_local; name; item_meta; impl_kind; is_negative; is_const; impl_trait; generics; preds; parent_trait_refs; consts; types; required_methods; provided_methods; default_items } : trait_impl)
+    | _ -> Error "")
+
+and module__of_json (js : json) : (module_, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("module_id", module_id); ("item_meta", item_meta); ("name", name); ("parent", parent); ("submodules", submodules); ("items", items) ] ->
+        let* module_id = module_id_of_json module_id in
+        let* item_meta = item_meta_of_json item_meta in
+        let* name = name_of_json name in
+        let* parent = (option_of_json module_id_of_json) parent in
+        let* submodules = (list_of_json module_id_of_json) submodules in
+        let* items = (list_of_json any_trans_id_of_json) items in
+        Ok ({ module_id; item_meta; name; parent; submodules; items } : module_)
+    | _ -> Error "")
+
+and harness_of_json (js : json) : (harness, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("fun_id", fun_id); ("attribute", attribute); ("args", args) ] ->
+        let* fun_id = fun_decl_id_of_json fun_id in
+        let* attribute = string_of_json attribute in
+        let* args = (list_of_json string_of_json) args in
+        Ok ({ fun_id; attribute; args } : harness)
+    | _ -> Error "")
+
+and elaborated_clause_of_json (js : json) : (elaborated_clause, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("trait_id", trait_id); ("bound_regions", bound_regions); ("trait_decl_ref", trait_decl_ref) ] ->
+        let* trait_id = trait_instance_id_of_json trait_id in
+        let* bound_regions = (list_of_json region_var_of_json) bound_regions in
+        let* trait_decl_ref = trait_decl_ref_of_json trait_decl_ref in
+        Ok ({ trait_id; bound_regions; trait_decl_ref } : elaborated_clause)
+    | _ -> Error "")
+
+and clause_env_of_json (js : json) : (clause_env, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("item", item); ("clauses", clauses) ] ->
+        let* item = any_trans_id_of_json item in
+        let* clauses = (list_of_json elaborated_clause_of_json) clauses in
+        Ok ({ item; clauses } : clause_env)
+    | _ -> Error "")
+
+and fn_operand_of_json (js : json) : (fn_operand, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("Regular", x0) ] ->
+        let* x0 = fn_ptr_of_json x0 in
+        Ok (Regular x0 : fn_operand)
+    | `Assoc [ ("Move", x0) ] ->
+        let* x0 = place_of_json x0 in
+        Ok (Move x0 : fn_operand)
+    | _ -> Error "")
+
+and adjustment_of_json (js : json) : (adjustment, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `String "Deref" -> Ok (Deref : adjustment)
+    | `Assoc [ ("OverloadedDeref", x0) ] ->
+        let* x0 = ref_kind_of_json x0 in
+        Ok (OverloadedDeref x0 : adjustment)
+    | `Assoc [ ("Borrow", x0) ] ->
+        let* x0 = ref_kind_of_json x0 in
+        Ok (Borrow x0 : adjustment)
+    | `String "Unsize" -> Ok (Unsize : adjustment)
+    | _ -> Error "")
+
+and method_resolution_of_json (js : json) : (method_resolution, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `String "Inherent" -> Ok (Inherent : method_resolution)
+    | `Assoc [ ("TraitImpl", x0) ] ->
+        let* x0 = trait_impl_id_of_json x0 in
+        Ok (TraitImpl x0 : method_resolution)
+    | `Assoc [ ("DefaultMethod", x0) ] ->
+        let* x0 = trait_impl_id_of_json x0 in
+        Ok (DefaultMethod x0 : method_resolution)
+    | `Assoc [ ("BuiltinOrAuto", x0) ] ->
+        let* x0 = trait_decl_id_of_json x0 in
+        Ok (BuiltinOrAuto x0 : method_resolution)
+    | `String "Clause" -> Ok (Clause : method_resolution)
+    | _ -> Error "")
+
+and call_of_json (js : json) : (call, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("func", func); ("args", args); ("dest", dest); ("receiver_adjustments", receiver_adjustments); ("resolution", resolution); ("is_tail", is_tail); ("is_ghost", is_ghost) ] ->
+        let* func = fn_operand_of_json func in
+        let* args = (list_of_json operand_of_json) args in
+        let* dest = place_of_json dest in
+        let* receiver_adjustments = (list_of_json adjustment_of_json) receiver_adjustments in
+        let* resolution = (option_of_json method_resolution_of_json) resolution in
+        let* is_tail = bool_of_json is_tail in
+        let* is_ghost = bool_of_json is_ghost in
+        Ok ({ func; args; dest; receiver_adjustments; resolution; is_tail; is_ghost } : call)
+    | _ -> Error "")
+
+and mem_copy_of_json (js : json) : (mem_copy, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("src", src); ("dst", dst); ("count", count); ("nonoverlapping", nonoverlapping) ] ->
+        let* src = operand_of_json src in
+        let* dst = operand_of_json dst in
+        let* count = operand_of_json count in
+        let* nonoverlapping = bool_of_json nonoverlapping in
+        Ok ({ src; dst; count; nonoverlapping } : mem_copy)
+    | _ -> Error "")
+
+and write_bytes_of_json (js : json) : (write_bytes, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("dst", dst); ("val", val_); ("count", count) ] ->
+        let* dst = operand_of_json dst in
+        let* val_ = operand_of_json val_ in
+        let* count = operand_of_json count in
+        Ok ({ dst; val_; count } : write_bytes)
+    | _ -> Error "")
+
+and intrinsic_kind_of_json (js : json) : (intrinsic_kind, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `String "Assume" -> Ok (Assume : intrinsic_kind)
+    | `String "Assert" -> Ok (Assert : intrinsic_kind)
+    | `String "Havoc" -> Ok (Havoc : intrinsic_kind)
+    | `Assoc [ ("Custom", x0) ] ->
+        let* x0 = string_of_json x0 in
+        Ok (Custom x0 : intrinsic_kind)
+    | _ -> Error "")
+
+and intrinsic_of_json (js : json) : (intrinsic, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("kind", kind); ("args", args); ("dest", dest); ("message", message) ] ->
+        let* kind = intrinsic_kind_of_json kind in
+        let* args = (list_of_json operand_of_json) args in
+        let* dest = place_of_json dest in
+        let* message = (option_of_json string_of_json) message in
+        Ok ({ kind; args; dest; message } : intrinsic)
+    | _ -> Error "")
+
+and assert_kind_of_json (js : json) : (assert_kind, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `String "User" -> Ok (User : assert_kind)
+    | `String "Bounds" -> Ok (Bounds : assert_kind)
+    | `String "Overflow" -> Ok (Overflow : assert_kind)
+    | `String "DivisionByZero" -> Ok (DivisionByZero : assert_kind)
+    | _ -> Error "")
+
+and ullbc_expr_body_of_json (js : json) : (ullbc_expr_body, string) result =
+  (g_expr_body_of_json (list_of_json block_data_of_json)) js
+
+and ullbc_fun_decl_of_json (js : json) : (ullbc_fun_decl, string) result =
+  (g_fun_decl_of_json (list_of_json block_data_of_json)) js
+
+and ullbc_fun_decls_of_json (js : json) : (ullbc_fun_decls, string) result =
+  (list_of_json (pair_of_json fun_decl_id_of_json ullbc_fun_decl_of_json)) js
+
+and ullbc_global_decl_of_json (js : json) : (ullbc_global_decl, string) result =
+  (g_global_decl_of_json (list_of_json block_data_of_json)) js
+
+and ullbc_global_decls_of_json (js : json) : (ullbc_global_decls, string) result =
+  (list_of_json (pair_of_json global_decl_id_of_json ullbc_global_decl_of_json)) js
+
+and trait_decls_of_json (js : json) : (trait_decls, string) result =
+  (list_of_json (pair_of_json trait_decl_id_of_json trait_decl_of_json)) js
+
+and trait_impls_of_json (js : json) : (trait_impls, string) result =
+  (list_of_json (pair_of_json trait_impl_id_of_json trait_impl_of_json)) js
+
+and ullbc_raw_statement_of_json (js : json) : (ullbc_raw_statement, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("Assign", `List [ x0; x1 ]) ] ->
+        let* x0 = place_of_json x0 in
+        let* x1 = rvalue_of_json x1 in
+        Ok (Assign (x0, x1) : ullbc_raw_statement)
+    | `Assoc [ ("FakeRead", x0) ] ->
+        let* x0 = place_of_json x0 in
+        Ok (FakeRead x0 : ullbc_raw_statement)
+    | `Assoc [ ("SetDiscriminant", `List [ x0; x1 ]) ] ->
+        let* x0 = place_of_json x0 in
+        let* x1 = variant_id_of_json x1 in
+        Ok (SetDiscriminant (x0, x1) : ullbc_raw_statement)
+    | `Assoc [ ("StorageLive", x0) ] ->
+        let* x0 = var_id_of_json x0 in
+        Ok (StorageLive x0 : ullbc_raw_statement)
+    | `Assoc [ ("StorageDead", x0) ] ->
+        let* x0 = var_id_of_json x0 in
+        Ok (StorageDead x0 : ullbc_raw_statement)
+    | `Assoc [ ("Deinit", x0) ] ->
+        let* x0 = place_of_json x0 in
+        Ok (Deinit x0 : ullbc_raw_statement)
+    | `Assoc [ ("MemCopy", x0) ] ->
+        let* x0 = mem_copy_of_json x0 in
+        Ok (MemCopy x0 : ullbc_raw_statement)
+    | `Assoc [ ("WriteBytes", x0) ] ->
+        let* x0 = write_bytes_of_json x0 in
+        Ok (WriteBytes x0 : ullbc_raw_statement)
+    | `Assoc [ ("Intrinsic", x0) ] ->
+        let* x0 = intrinsic_of_json x0 in
+        Ok (Intrinsic x0 : ullbc_raw_statement)
+    | _ -> Error "")
+
+and ullbc_statement_of_json (js : json) : (ullbc_statement, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("meta", meta); ("content", content) ] ->
+        let* meta = meta_of_json meta in
+        let* content = ullbc_raw_statement_of_json content in
+        Ok ({ meta; content } : ullbc_statement)
+    | _ -> Error "")
+
+and switch_targets_of_json (js : json) : (switch_targets, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("If", `List [ x0; x1 ]) ] ->
+        let* x0 = block_id_of_json x0 in
+        let* x1 = block_id_of_json x1 in
+        Ok (If (x0, x1) : switch_targets)
+    | `Assoc [ ("SwitchInt", `List [ x0; x1; x2 ]) ] ->
+        let* x0 = integer_ty_of_json x0 in
+        let* x1 = (list_of_json (pair_of_json scalar_value_of_json block_id_of_json)) x1 in
+        let* x2 = block_id_of_json x2 in
+        Ok (SwitchInt (x0, x1, x2) : switch_targets)
+    | _ -> Error "")
+
+and raw_terminator_of_json (js : json) : (raw_terminator, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("Goto", `Assoc [ ("target", target) ]) ] ->
+        let* target = block_id_of_json target in
+        Ok (Goto { target } : raw_terminator)
+    | `Assoc [ ("Switch", `Assoc [ ("discr", discr); ("targets", targets) ]) ] ->
+        let* discr = operand_of_json discr in
+        let* targets = switch_targets_of_json targets in
+        Ok (Switch { discr; targets } : raw_terminator)
+    | `String "Panic" -> Ok (Panic : raw_terminator)
+    | `String "Return" -> Ok (Return : raw_terminator)
+    | `String "Unreachable" -> Ok (Unreachable : raw_terminator)
+    | `Assoc [ ("Drop", `Assoc [ ("place", place); ("target", target) ]) ] ->
+        let* place = place_of_json place in
+        let* target = block_id_of_json target in
+        Ok (Drop { place; target } : raw_terminator)
+    | `Assoc [ ("Call", `Assoc [ ("call", call); ("target", target) ]) ] ->
+        let* call = call_of_json call in
+        let* target = block_id_of_json target in
+        Ok (Call { call; target } : raw_terminator)
+    | `Assoc [ ("Assert", `Assoc [ ("cond", cond); ("expected", expected); ("kind", kind); ("target", target) ]) ] ->
+        let* cond = operand_of_json cond in
+        let* expected = bool_of_json expected in
+        let* kind = assert_kind_of_json kind in
+        let* target = block_id_of_json target in
+        Ok (Assert { cond; expected; kind; target } : raw_terminator)
+    | _ -> Error "")
+
+and terminator_of_json (js : json) : (terminator, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("meta", meta); ("content", content) ] ->
+        let* meta = meta_of_json meta in
+        let* content = raw_terminator_of_json content in
+        Ok ({ meta; content } : terminator)
+    | _ -> Error "")
+
+and block_data_of_json (js : json) : (block_data, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("statements", statements); ("terminator", terminator) ] ->
+        let* statements = (list_of_json ullbc_statement_of_json) statements in
+        let* terminator = terminator_of_json terminator in
+        Ok ({ statements; terminator } : block_data)
+    | _ -> Error "")
+
+and assert__of_json (js : json) : (assert_, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("cond", cond); ("expected", expected); ("kind", kind) ] ->
+        let* cond = operand_of_json cond in
+        let* expected = bool_of_json expected in
+        let* kind = assert_kind_of_json kind in
+        Ok ({ cond; expected; kind } : assert_)
+    | _ -> Error "")
+
+and llbc_raw_statement_of_json (js : json) : (llbc_raw_statement, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("Assign", `List [ x0; x1 ]) ] ->
+        let* x0 = place_of_json x0 in
+        let* x1 = rvalue_of_json x1 in
+        Ok (Assign (x0, x1) : llbc_raw_statement)
+    | `Assoc [ ("FakeRead", x0) ] ->
+        let* x0 = place_of_json x0 in
+        Ok (FakeRead x0 : llbc_raw_statement)
+    | `Assoc [ ("SetDiscriminant", `List [ x0; x1 ]) ] ->
+        let* x0 = place_of_json x0 in
+        let* x1 = variant_id_of_json x1 in
+        Ok (SetDiscriminant (x0, x1) : llbc_raw_statement)
+    | `Assoc [ ("Drop", x0) ] ->
+        let* x0 = place_of_json x0 in
+        Ok (Drop x0 : llbc_raw_statement)
+    | `Assoc [ ("StorageLive", x0) ] ->
+        let* x0 = var_id_of_json x0 in
+        Ok (StorageLive x0 : llbc_raw_statement)
+    | `Assoc [ ("StorageDead", x0) ] ->
+        let* x0 = var_id_of_json x0 in
+        Ok (StorageDead x0 : llbc_raw_statement)
+    | `Assoc [ ("Assert", x0) ] ->
+        let* x0 = assert__of_json x0 in
+        Ok (Assert x0 : llbc_raw_statement)
+    | `Assoc [ ("Call", x0) ] ->
+        let* x0 = call_of_json x0 in
+        Ok (Call x0 : llbc_raw_statement)
+    | `Assoc [ ("MemCopy", x0) ] ->
+        let* x0 = mem_copy_of_json x0 in
+        Ok (MemCopy x0 : llbc_raw_statement)
+    | `Assoc [ ("WriteBytes", x0) ] ->
+        let* x0 = write_bytes_of_json x0 in
+        Ok (WriteBytes x0 : llbc_raw_statement)
+    | `Assoc [ ("Intrinsic", x0) ] ->
+        let* x0 = intrinsic_of_json x0 in
+        Ok (Intrinsic x0 : llbc_raw_statement)
+    | `String "Panic" -> Ok (Panic : llbc_raw_statement)
+    | `String "Return" -> Ok (Return : llbc_raw_statement)
+    | `Assoc [ ("Break", `List [ x0; x1 ]) ] ->
+        let* x0 = int_of_json x0 in
+        let* x1 = (option_of_json string_of_json) x1 in
+        Ok (Break (x0, x1) : llbc_raw_statement)
+    | `Assoc [ ("Continue", `List [ x0; x1 ]) ] ->
+        let* x0 = int_of_json x0 in
+        let* x1 = (option_of_json string_of_json) x1 in
+        Ok (Continue (x0, x1) : llbc_raw_statement)
+    | `String "Nop" -> Ok (Nop : llbc_raw_statement)
+    | `Assoc [ ("Switch", x0) ] ->
+        let* x0 = switch_of_json x0 in
+        Ok (Switch x0 : llbc_raw_statement)
+    | `Assoc [ ("Loop", `List [ x0; x1 ]) ] ->
+        let* x0 = block_of_json x0 in
+        let* x1 = loop_info_of_json x1 in
+        Ok (Loop (x0, x1) : llbc_raw_statement)
+    | _ -> Error "")
+
+and loop_info_of_json (js : json) : (loop_info, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("id", id); ("label", label); ("depth", depth); ("breaks", breaks); ("continues", continues) ] ->
+        let* id = loop_id_of_json id in
+        let* label = string_of_json label in
+        let* depth = int_of_json depth in
+        let* breaks = (list_of_json loop_jump_of_json) breaks in
+        let* continues = (list_of_json loop_jump_of_json) continues in
+        Ok ({ id; label; depth; breaks; continues } : loop_info)
+    | _ -> Error "")
+
+and loop_jump_of_json (js : json) : (loop_jump, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("meta", meta); ("distance", distance) ] ->
+        let* meta = meta_of_json meta in
+        let* distance = int_of_json distance in
+        Ok ({ meta; distance } : loop_jump)
+    | _ -> Error "")
+
+and llbc_statement_of_json (js : json) : (llbc_statement, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("meta", meta); ("content", content) ] ->
+        let* meta = meta_of_json meta in
+        let* content = llbc_raw_statement_of_json content in
+        Ok ({ meta; content } : llbc_statement)
+    | _ -> Error "")
+
+and block_of_json (js : json) : (block, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("meta", meta); ("statements", statements) ] ->
+        let* meta = meta_of_json meta in
+        let* statements = (list_of_json llbc_statement_of_json) statements in
+        Ok ({ meta; statements } : block)
+    | _ -> Error "")
+
+and switch_of_json (js : json) : (switch, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("If", `List [ x0; x1; x2 ]) ] ->
+        let* x0 = operand_of_json x0 in
+        let* x1 = block_of_json x1 in
+        let* x2 = block_of_json x2 in
+        Ok (If (x0, x1, x2) : switch)
+    | `Assoc [ ("SwitchInt", `List [ x0; x1; x2; x3 ]) ] ->
+        let* x0 = operand_of_json x0 in
+        let* x1 = integer_ty_of_json x1 in
+        let* x2 = (list_of_json (pair_of_json (list_of_json scalar_value_of_json) block_of_json)) x2 in
+        let* x3 = block_of_json x3 in
+        Ok (SwitchInt (x0, x1, x2, x3) : switch)
+    | `Assoc [ ("Match", `List [ x0; x1; x2 ]) ] ->
+        let* x0 = place_of_json x0 in
+        let* x1 = (list_of_json (pair_of_json (list_of_json variant_id_of_json) block_of_json)) x1 in
+        let* x2 = (option_of_json block_of_json) x2 in
+        Ok (Match (x0, x1, x2) : switch)
+    | _ -> Error "")
+
+and llbc_expr_body_of_json (js : json) : (llbc_expr_body, string) result =
+  (g_expr_body_of_json block_of_json) js
+
+and llbc_fun_decl_of_json (js : json) : (llbc_fun_decl, string) result =
+  (g_fun_decl_of_json block_of_json) js
+
+and llbc_fun_decls_of_json (js : json) : (llbc_fun_decls, string) result =
+  (list_of_json (pair_of_json fun_decl_id_of_json llbc_fun_decl_of_json)) js
+
+and llbc_global_decl_of_json (js : json) : (llbc_global_decl, string) result =
+  (g_global_decl_of_json block_of_json) js
+
+and llbc_global_decls_of_json (js : json) : (llbc_global_decls, string) result =
+  (list_of_json (pair_of_json global_decl_id_of_json llbc_global_decl_of_json)) js
+
+and g_declaration_group_of_json : 'id. (json -> ('id, string) result) -> json -> ('id g_declaration_group, string) result =
+ fun id_of_json js ->
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("NonRec", x0) ] ->
+        let* x0 = id_of_json x0 in
+        Ok (NonRec x0 : _ g_declaration_group)
+    | `Assoc [ ("Rec", x0) ] ->
+        let* x0 = (list_of_json id_of_json) x0 in
+        Ok (Rec x0 : _ g_declaration_group)
+    | _ -> Error "")
+
+and declaration_group_of_json (js : json) : (declaration_group, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("Type", x0) ] ->
+        let* x0 = (g_declaration_group_of_json type_decl_id_of_json) x0 in
+        Ok (Type x0 : declaration_group)
+    | `Assoc [ ("Fun", x0) ] ->
+        let* x0 = (g_declaration_group_of_json fun_decl_id_of_json) x0 in
+        Ok (Fun x0 : declaration_group)
+    | `Assoc [ ("Global", x0) ] ->
+        let* x0 = (g_declaration_group_of_json global_decl_id_of_json) x0 in
+        Ok (Global x0 : declaration_group)
+    | `Assoc [ ("TraitDecl", x0) ] ->
+        let* x0 = (g_declaration_group_of_json trait_decl_id_of_json) x0 in
+        Ok (TraitDecl x0 : declaration_group)
+    | `Assoc [ ("TraitImpl", x0) ] ->
+        let* x0 = (g_declaration_group_of_json trait_impl_id_of_json) x0 in
+        Ok (TraitImpl x0 : declaration_group)
+    | _ -> Error "")
+
+and any_decl_id_of_json : 'type_id 'fun_id 'global_id 'trait_decl_id 'trait_impl_id. (json -> ('type_id, string) result) -> (json -> ('fun_id, string) result) -> (json -> ('global_id, string) result) -> (json -> ('trait_decl_id, string) result) -> (json -> ('trait_impl_id, string) result) -> json -> (('type_id, 'fun_id, 'global_id, 'trait_decl_id, 'trait_impl_id) any_decl_id, string) result =
+ fun type_id_of_json fun_id_of_json global_id_of_json trait_decl_id_of_json trait_impl_id_of_json js ->
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("Type", x0) ] ->
+        let* x0 = type_id_of_json x0 in
+        Ok (Type x0 : (_, _, _, _, _) any_decl_id)
+    | `Assoc [ ("Fun", x0) ] ->
+        let* x0 = fun_id_of_json x0 in
+        Ok (Fun x0 : (_, _, _, _, _) any_decl_id)
+    | `Assoc [ ("Global", x0) ] ->
+        let* x0 = global_id_of_json x0 in
+        Ok (Global x0 : (_, _, _, _, _) any_decl_id)
+    | `Assoc [ ("TraitDecl", x0) ] ->
+        let* x0 = trait_decl_id_of_json x0 in
+        Ok (TraitDecl x0 : (_, _, _, _, _) any_decl_id)
+    | `Assoc [ ("TraitImpl", x0) ] ->
+        let* x0 = trait_impl_id_of_json x0 in
+        Ok (TraitImpl x0 : (_, _, _, _, _) any_decl_id)
+    | _ -> Error "")
+
+and declarations_groups_of_json (js : json) : (declarations_groups, string) result =
+  (list_of_json declaration_group_of_json) js
+
+and any_trans_id_of_json (js : json) : (any_trans_id, string) result =
+  (any_decl_id_of_json type_decl_id_of_json fun_decl_id_of_json global_decl_id_of_json trait_decl_id_of_json trait_impl_id_of_json) js
+
+and g_crate_data_of_json : 'fd 'gd. (json -> ('fd, string) result) -> (json -> ('gd, string) result) -> json -> (('fd, 'gd) g_crate_data, string) result =
+ fun fd_of_json gd_of_json js ->
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("format_version", format_version); ("name", name); ("target_info", target_info); ("features", features); ("cargo_profile", cargo_profile); ("id_to_file", id_to_file); ("macro_expansions", macro_expansions); ("declarations", declarations); ("types", types); ("functions", functions); ("globals", globals); ("trait_decls", trait_decls); ("trait_impls", trait_impls); ("modules", modules); ("harnesses", harnesses); ("ghost_functions", ghost_functions); ("clause_envs", clause_envs); ("type_aliases", type_aliases); ("strings", strings) ] ->
+        let* format_version = int_of_json format_version in
+        let* name = string_of_json name in
+        let* target_info = target_info_of_json target_info in
+        let* features = (list_of_json string_of_json) features in
+        let* cargo_profile = string_of_json cargo_profile in
+        let* id_to_file = (list_of_json (triple_of_json file_id_of_json file_name_of_json (option_of_json string_of_json))) id_to_file in
+        let* macro_expansions = (list_of_json macro_expansion_of_json) macro_expansions in
+        let* declarations = (list_of_json declaration_group_of_json) declarations in
+        let* types = (list_of_json type_decl_of_json) types in
+        let* functions = (list_of_json fd_of_json) functions in
+        let* globals = (list_of_json gd_of_json) globals in
+        let* trait_decls = (list_of_json trait_decl_of_json) trait_decls in
+        let* trait_impls = (list_of_json trait_impl_of_json) trait_impls in
+        let* modules = (list_of_json module__of_json) modules in
+        let* harnesses = (list_of_json harness_of_json) harnesses in
+        let* ghost_functions = (list_of_json fun_decl_id_of_json) ghost_functions in
+        let* clause_envs = (list_of_json clause_env_of_json) clause_envs in
+        let* type_aliases = (list_of_json type_alias_decl_of_json) type_aliases in
+        let* strings = string_table_of_json strings in
+        Ok ({ format_version; name; target_info; features; cargo_profile; id_to_file; macro_expansions; declarations; types; functions; globals; trait_decls; trait_impls; modules; harnesses; ghost_functions; clause_envs; type_aliases; strings } : (_, _) g_crate_data)
+    | _ -> Error "")
+
+and group_file_of_json : 'fd 'gd. (json -> ('fd, string) result) -> (json -> ('gd, string) result) -> json -> (('fd, 'gd) group_file, string) result =
+ fun fd_of_json gd_of_json js ->
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("format_version", format_version); ("index", index); ("group", group); ("depends_on", depends_on); ("types", types); ("functions", functions); ("globals", globals); ("trait_decls", trait_decls); ("trait_impls", trait_impls) ] ->
+        let* format_version = int_of_json format_version in
+        let* index = int_of_json index in
+        let* group = declaration_group_of_json group in
+        let* depends_on = (list_of_json int_of_json) depends_on in
+        let* types = (list_of_json type_decl_of_json) types in
+        let* functions = (list_of_json fd_of_json) functions in
+        let* globals = (list_of_json gd_of_json) globals in
+        let* trait_decls = (list_of_json trait_decl_of_json) trait_decls in
+        let* trait_impls = (list_of_json trait_impl_of_json) trait_impls in
+        Ok ({ format_version; index; group; depends_on; types; functions; globals; trait_decls; trait_impls } : (_, _) group_file)
+    | _ -> Error "")
+
+and group_entry_of_json (js : json) : (group_entry, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("file", file); ("group", group); ("depends_on", depends_on); ("hash", hash) ] ->
+        let* file = string_of_json file in
+        let* group = declaration_group_of_json group in
+        let* depends_on = (list_of_json int_of_json) depends_on in
+        let* hash = string_of_json hash in
+        Ok ({ file; group; depends_on; hash } : group_entry)
+    | _ -> Error "")
+
+and crate_index_of_json (js : json) : (crate_index, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("format_version", format_version); ("name", name); ("target_info", target_info); ("features", features); ("cargo_profile", cargo_profile); ("id_to_file", id_to_file); ("macro_expansions", macro_expansions); ("modules", modules); ("harnesses", harnesses); ("ghost_functions", ghost_functions); ("clause_envs", clause_envs); ("type_aliases", type_aliases); ("groups", groups); ("strings", strings) ] ->
+        let* format_version = int_of_json format_version in
+        let* name = string_of_json name in
+        let* target_info = target_info_of_json target_info in
+        let* features = (list_of_json string_of_json) features in
+        let* cargo_profile = string_of_json cargo_profile in
+        let* id_to_file = (list_of_json (triple_of_json file_id_of_json file_name_of_json (option_of_json string_of_json))) id_to_file in
+        let* macro_expansions = (list_of_json macro_expansion_of_json) macro_expansions in
+        let* modules = (list_of_json module__of_json) modules in
+        let* harnesses = (list_of_json harness_of_json) harnesses in
+        let* ghost_functions = (list_of_json fun_decl_id_of_json) ghost_functions in
+        let* clause_envs = (list_of_json clause_env_of_json) clause_envs in
+        let* type_aliases = (list_of_json type_alias_decl_of_json) type_aliases in
+        let* groups = (list_of_json group_entry_of_json) groups in
+        let* strings = string_table_of_json strings in
+        Ok ({ format_version; name; target_info; features; cargo_profile; id_to_file; macro_expansions; modules; harnesses; ghost_functions; clause_envs; type_aliases; groups; strings } : crate_index)
+    | _ -> Error "")
+
+and scalar_value_of_json (js : json) : (scalar_value, string) result =
+  json_of_json js
+
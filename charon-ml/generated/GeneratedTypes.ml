@@ -0,0 +1,436 @@
+(** Generated by `charon --generate-ml` from the definitions of the Rust AST: do not edit. *)
+
+type json = Yojson.Basic.t
+type block_id = int
+type const_generic_var_id = int
+type disambiguator = int
+type field_id = int
+type file_id = int
+type fun_decl_id = int
+type global_decl_id = int
+type loan_id = int
+type loop_id = int
+type macro_expansion_id = int
+type module_id = int
+type promoted_id = int
+type region_id = int
+type scope_id = int
+type trait_clause_id = int
+type trait_decl_id = int
+type trait_impl_id = int
+type type_alias_id = int
+type type_decl_id = int
+type type_var_id = int
+type var_id = int
+type variant_id = int
+
+type loc = { line : int; col : int }
+and span = { file_id : file_id; beg : loc; end_ : loc }
+and meta = { span : span; generated_from_span : span option; expansion : macro_expansion_id option; in_unsafe_block : bool }
+and macro_expansion = { macro_name : string; call_site : span; def_site : span option; parent : macro_expansion_id option }
+and attribute = string
+and inline_attr =
+  | Hint
+  | Never
+  | Always
+and visibility =
+  | Public
+  | Restricted of name
+and item_meta = { meta : meta; attributes : attribute list; cfg : string list; inline : inline_attr option; public : bool; visibility : visibility option; stable_id : string; hash : string }
+and file_info = unit
+and file_name =
+  | Virtual of string
+  | Local of string
+  | NotReal of string
+and path_elem =
+  | Ident of symbol * disambiguator
+  | Impl of impl_elem
+and impl_elem = { disambiguator : disambiguator; generics : generic_params; preds : predicates; kind : impl_elem_kind }
+and impl_elem_kind =
+  | Ty of ty
+  | Trait of trait_decl_ref
+and name = path_elem list
+and symbol = int
+and string_table = string list
+and field_name = string
+and type_var = { index : type_var_id; name : string; is_sized : bool; default : ty option }
+and region_var = { index : region_id; name : string option }
+and const_generic_var = { index : const_generic_var_id; name : string; ty : literal_ty; default : const_generic option }
+and de_bruijn_id = int
+and region =
+  | Static
+  | BVar of de_bruijn_id * region_id
+  | Body of region_id
+  | Erased
+  | Unknown
+and trait_instance_id =
+  | TraitImpl of trait_impl_id
+  | Specializable of { impl_id : trait_impl_id; specializing_impls : trait_impl_id list }
+  | BuiltinOrAuto of trait_decl_id
+  | Clause of trait_clause_id
+  | ParentClause of trait_instance_id * trait_decl_id * trait_clause_id
+  | ItemClause of trait_instance_id * trait_decl_id * trait_item_name * trait_clause_id
+  | FnPointer of ty
+  | Closure of fun_decl_id * generic_args
+  | SelfId
+  | Unsolved of trait_decl_id * generic_args
+  | Unknown of string
+and trait_ref = trait_ref_data
+and trait_ref_data = { trait_id : trait_instance_id; generics : generic_args; trait_decl_ref : trait_decl_ref }
+and trait_decl_ref = { trait_id : trait_decl_id; generics : generic_args }
+and ('t, 'u) outlives_pred = ('t * 'u)
+and region_outlives = (region, region) outlives_pred
+and type_outlives = (ty, region) outlives_pred
+and trait_type_constraint = { trait_ref : trait_ref; type_name : trait_item_name; generics : generic_args; ty : ty }
+and predicates = { regions_outlive : region_outlives list; types_outlive : type_outlives list; trait_type_constraints : trait_type_constraint list; regions_graph : region_graph }
+and region_graph = { regions : region list list; types : region list list }
+and generic_args = generic_args_data
+and generic_args_data = { regions : region list; types : ty list; const_generics : const_generic list; trait_refs : trait_ref list }
+and generic_params = { regions : region_var list; types : type_var list; const_generics : const_generic_var list; trait_clauses : trait_clause list }
+and trait_clause = { clause_id : trait_clause_id; meta : meta option; trait_id : trait_decl_id; bound_regions : region_var list; generics : generic_args; is_const : bool }
+and type_decl = { def_id : type_decl_id; item_meta : item_meta; is_local : bool; name : name; generics : generic_params; preds : predicates; kind : type_decl_kind; auto_traits : auto_traits; variances : variances; rec_group : type_decl_id list option }
+and type_alias_decl = { def_id : type_alias_id; item_meta : item_meta; name : name; generics : generic_params; ty : ty }
+and type_alias_ref = { alias_id : type_alias_id; generics : generic_args }
+and variance =
+  | Covariant
+  | Invariant
+  | Contravariant
+  | Bivariant
+and variances = { regions : variance list; types : variance list }
+and auto_trait_impl =
+  | Always
+  | IfParams
+  | Never
+and auto_traits = { send : auto_trait_impl; sync : auto_trait_impl; unpin : auto_trait_impl }
+and type_decl_kind =
+  | Struct of field list
+  | Enum of variant list * integer_ty * enum_layout option
+  | Opaque
+  | ImplTrait of { bounds : impl_trait_bound list; hidden_ty : ty option }
+  | Error of string
+and impl_trait_bound = { trait_id : trait_decl_id; generics : generic_args; type_constraints : (trait_item_name * ty) list }
+and enum_layout =
+  | Single
+  | Tagged
+  | Niche of { untagged_variant : variant_id; field : field_id }
+and variant = { meta : meta; name : symbol; fields : field list; discriminant : scalar_value }
+and field = { meta : meta; name : symbol option; ty : ty; is_zst : bool; is_phantom_data : bool; alias : type_alias_ref option }
+and integer_ty =
+  | Isize
+  | I8
+  | I16
+  | I32
+  | I64
+  | I128
+  | Usize
+  | U8
+  | U16
+  | U32
+  | U64
+  | U128
+and target_info = { target_triple : string; pointer_width : int; is_little_endian : bool; c_int_width : int }
+and ref_kind =
+  | Mut
+  | Shared
+and type_id =
+  | Adt of type_decl_id
+  | Tuple
+  | Assumed of assumed_ty
+and type_decls = (type_decl_id * type_decl) list
+and literal_ty =
+  | Integer of integer_ty
+  | Bool
+  | Char
+and const_generic =
+  | Global of global_decl_id
+  | TraitConst of trait_ref * trait_item_name
+  | Var of const_generic_var_id
+  | Value of literal
+and ty = ty_kind
+and ty_kind =
+  | Adt of type_id * generic_args
+  | TypeVar of type_var_id
+  | Literal of literal_ty
+  | Never
+  | Ref of region * ty * ref_kind
+  | RawPtr of ty * ref_kind
+  | TraitType of trait_ref * trait_item_name * generic_args
+  | Arrow of region_var list * ty list * ty
+  | FnDef of fun_decl_id * generic_args
+and assumed_ty =
+  | Box
+  | PtrUnique
+  | PtrNonNull
+  | Array
+  | Slice
+  | Str
+and params_info = { num_region_params : int; num_type_params : int; num_const_generic_params : int; num_trait_clauses : int; num_regions_outlive : int; num_types_outlive : int; num_trait_type_constraints : int }
+and closure_kind =
+  | Fn
+  | FnMut
+  | FnOnce
+and capture_mode =
+  | ByValue
+  | ByRef of ref_kind
+and capture_projection =
+  | Deref
+  | Field of field_id
+and captured_place = { var_id : var_id; projection : capture_projection list; mode : capture_mode }
+and closure_info = { kind : closure_kind; state : ty list; captures : captured_place list; is_move : bool; call_mut_shim : fun_decl_id option; call_once_shim : fun_decl_id option }
+and fun_sig = { is_unsafe : bool; is_const : bool; is_closure : bool; closure_info : closure_info option; generics : generic_params; preds : predicates; parent_params_info : params_info option; inputs : ty list; output : ty; input_aliases : type_alias_ref option list; output_alias : type_alias_ref option }
+and literal =
+  | Scalar of scalar_value
+  | Bool of bool
+  | Char of char
+and place = { var_id : var_id; projection : projection }
+and projection = projection_elem list
+and projection_elem =
+  | Deref
+  | DerefBox
+  | DerefRawPtr
+  | Field of field_proj_kind * field_id
+  | Index of var_id * ty
+  | Subslice of { from : int; to_ : int; from_end : bool }
+and field_proj_kind =
+  | Adt of type_decl_id * variant_id option
+  | Tuple of int
+  | ClosureState
+and borrow_kind =
+  | Shared
+  | Mut
+  | TwoPhaseMut
+  | Shallow
+and un_op =
+  | Not
+  | Neg
+  | Cast of cast_kind
+  | ArrayToSlice of ref_kind * ty * const_generic
+and null_op =
+  | SizeOf
+  | AlignOf
+  | OffsetOf of (field_proj_kind * field_id) list
+and cast_kind =
+  | Scalar of literal_ty * literal_ty
+  | FnPtr of ty * ty
+and bin_op =
+  | BitXor
+  | BitAnd
+  | BitOr
+  | Eq
+  | Lt
+  | Le
+  | Ne
+  | Ge
+  | Gt
+  | Div
+  | Rem
+  | Add
+  | Sub
+  | Mul
+  | Shl
+  | Shr
+  | Offset
+  | WrappingOffset
+  | PtrDiff
+  | CheckedAdd
+  | CheckedSub
+  | CheckedMul
+  | WrappingAdd
+  | WrappingSub
+  | WrappingMul
+  | OverflowingAdd
+  | OverflowingSub
+  | OverflowingMul
+  | SaturatingAdd
+  | SaturatingSub
+  | SaturatingMul
+and operand =
+  | Copy of place
+  | Move of place
+  | Const of constant_expr
+  | Expr of rvalue * ty
+and fun_id =
+  | Regular of fun_decl_id
+  | Assumed of assumed_fun_id
+and assumed_fun_id =
+  | BoxNew
+  | BoxFree
+  | ArrayIndexShared
+  | ArrayIndexMut
+  | ArrayToSliceShared
+  | ArrayToSliceMut
+  | ArrayRepeat
+  | SliceIndexShared
+  | SliceIndexMut
+and fun_id_or_trait_method_ref =
+  | Fun of fun_id
+  | Trait of trait_ref * trait_item_name * fun_decl_id
+and fn_ptr = { func : fun_id_or_trait_method_ref; generics : generic_args }
+and raw_constant_expr =
+  | Literal of literal
+  | Adt of variant_id option * constant_expr list
+  | Global of global_decl_id * generic_args
+  | Promoted of promoted_id * generic_args
+  | TraitConst of trait_ref * trait_item_name
+  | Ref of constant_expr
+  | Var of const_generic_var_id
+  | FnPtr of fn_ptr
+  | MutStatic of global_decl_id
+and constant_expr = { value : raw_constant_expr; ty : ty }
+and rvalue =
+  | Use of operand
+  | Ref of place * borrow_kind
+  | UnaryOp of un_op * operand
+  | BinaryOp of bin_op * operand * operand
+  | Discriminant of place * type_decl_id
+  | Aggregate of aggregate_kind * operand list
+  | Global of global_decl_id * generic_args
+  | Len of place * ty * const_generic option
+  | Repeat of operand * ty * const_generic
+  | NullaryOp of null_op * ty
+and aggregate_kind =
+  | Adt of type_id * variant_id option * generic_args
+  | Array of ty * const_generic
+  | Closure of fun_decl_id * generic_args
+and var = { index : var_id; name : string option; ty : ty; debug_info : var_debug_info list }
+and var_debug_info = { name : string; projection : projection; meta : meta }
+and 't g_expr_body = { meta : meta; arg_count : int; locals : var list; regions : body_regions; borrowck_facts : borrowck_facts option; scopes : scope list; promoted : global_decl_id list; body : 't }
+and scope = { index : scope_id; parent : scope_id option; meta : meta; inlined : bool; locals : var_id list }
+and body_regions = { vars : region_var list; constraints : region_outlives list }
+and location = { block : block_id; statement_index : int }
+and loan = { index : loan_id; region : region; location : location }
+and borrowck_facts = { loans : loan list; subset_constraints : (region * region * location) list; loans_invalidated_at : (loan_id * location) list; loans_killed_at : (loan_id * location) list; loans_out_of_scope_at : (location * loan_id list) list }
+and item_kind =
+  | Regular
+  | TraitItemImpl of { impl_id : trait_impl_id; trait_id : trait_decl_id; item_name : trait_item_name; provided : bool }
+  | TraitItemDecl of trait_decl_id * trait_item_name
+  | TraitItemProvided of trait_decl_id * trait_item_name
+and 't g_fun_decl = { def_id : fun_decl_id; item_meta : item_meta; is_local : bool; name : name; lang_item : string option; signature : fun_sig; kind : item_kind; rec_group : fun_decl_id list option; body : 't g_expr_body option }
+and 't g_global_decl = { def_id : global_decl_id; item_meta : item_meta; is_local : bool; name : name; generics : generic_params; preds : predicates; ty : ty; is_mutable : bool; is_thread_local : bool; is_interior_mutable : bool; kind : item_kind; promoted : promoted_info option; init_deps : global_decl_id list; body : 't g_expr_body option }
+and promoted_info = { parent : any_trans_id; index : promoted_id }
+and trait_item_name = string
+and trait_decl = { def_id : trait_decl_id; is_local : bool; item_meta : item_meta; name : name; lang_item : string option; generics : generic_params; preds : predicates; parent_clauses : trait_clause list; consts : (trait_item_name * (ty * global_decl_id option)) list; types : (trait_item_name * (generic_params * trait_clause list * ty option)) list; required_methods : (trait_item_name * fun_decl_id) list; provided_methods : (trait_item_name * fun_decl_id option) list }
+and trait_impl_kind =
+  | Regular
+  | Blanket
+  | Derived of string
+and trait_impl = { def_id : trait_impl_id; is_local : bool; name : name; item_meta : item_meta; impl_kind : trait_impl_kind; is_negative : bool; is_const : bool; impl_trait : trait_decl_ref; generics : generic_params; preds : predicates; parent_trait_refs : trait_ref list; consts : (trait_item_name * (ty * global_decl_id)) list; types : (trait_item_name * (generic_params * trait_ref list * ty)) list; required_methods : (trait_item_name * fun_decl_id) list; provided_methods : (trait_item_name * (fun_decl_id * bool)) list; default_items : trait_item_name list }
+and module_ = { module_id : module_id; item_meta : item_meta; name : name; parent : module_id option; submodules : module_id list; items : any_trans_id list }
+and harness = { fun_id : fun_decl_id; attribute : string; args : string list }
+and elaborated_clause = { trait_id : trait_instance_id; bound_regions : region_var list; trait_decl_ref : trait_decl_ref }
+and clause_env = { item : any_trans_id; clauses : elaborated_clause list }
+and fn_operand =
+  | Regular of fn_ptr
+  | Move of place
+and adjustment =
+  | Deref
+  | OverloadedDeref of ref_kind
+  | Borrow of ref_kind
+  | Unsize
+and method_resolution =
+  | Inherent
+  | TraitImpl of trait_impl_id
+  | DefaultMethod of trait_impl_id
+  | BuiltinOrAuto of trait_decl_id
+  | Clause
+and call = { func : fn_operand; args : operand list; dest : place; receiver_adjustments : adjustment list; resolution : method_resolution option; is_tail : bool; is_ghost : bool }
+and mem_copy = { src : operand; dst : operand; count : operand; nonoverlapping : bool }
+and write_bytes = { dst : operand; val_ : operand; count : operand }
+and intrinsic_kind =
+  | Assume
+  | Assert
+  | Havoc
+  | Custom of string
+and intrinsic = { kind : intrinsic_kind; args : operand list; dest : place; message : string option }
+and assert_kind =
+  | User
+  | Bounds
+  | Overflow
+  | DivisionByZero
+and ullbc_expr_body = block_data list g_expr_body
+and ullbc_fun_decl = block_data list g_fun_decl
+and ullbc_fun_decls = (fun_decl_id * ullbc_fun_decl) list
+and ullbc_global_decl = block_data list g_global_decl
+and ullbc_global_decls = (global_decl_id * ullbc_global_decl) list
+and trait_decls = (trait_decl_id * trait_decl) list
+and trait_impls = (trait_impl_id * trait_impl) list
+and ullbc_raw_statement =
+  | Assign of place * rvalue
+  | FakeRead of place
+  | SetDiscriminant of place * variant_id
+  | StorageLive of var_id
+  | StorageDead of var_id
+  | Deinit of place
+  | MemCopy of mem_copy
+  | WriteBytes of write_bytes
+  | Intrinsic of intrinsic
+and ullbc_statement = { meta : meta; content : ullbc_raw_statement }
+and switch_targets =
+  | If of block_id * block_id
+  | SwitchInt of integer_ty * (scalar_value * block_id) list * block_id
+and raw_terminator =
+  | Goto of { target : block_id }
+  | Switch of { discr : operand; targets : switch_targets }
+  | Panic
+  | Return
+  | Unreachable
+  | Drop of { place : place; target : block_id }
+  | Call of { call : call; target : block_id }
+  | Assert of { cond : operand; expected : bool; kind : assert_kind; target : block_id }
+and terminator = { meta : meta; content : raw_terminator }
+and block_data = { statements : ullbc_statement list; terminator : terminator }
+and assert_ = { cond : operand; expected : bool; kind : assert_kind }
+and llbc_raw_statement =
+  | Assign of place * rvalue
+  | FakeRead of place
+  | SetDiscriminant of place * variant_id
+  | Drop of place
+  | StorageLive of var_id
+  | StorageDead of var_id
+  | Assert of assert_
+  | Call of call
+  | MemCopy of mem_copy
+  | WriteBytes of write_bytes
+  | Intrinsic of intrinsic
+  | Panic
+  | Return
+  | Break of int * string option
+  | Continue of int * string option
+  | Nop
+  | Switch of switch
+  | Loop of block * loop_info
+and loop_info = { id : loop_id; label : string; depth : int; breaks : loop_jump list; continues : loop_jump list }
+and loop_jump = { meta : meta; distance : int }
+and llbc_statement = { meta : meta; content : llbc_raw_statement }
+and block = { meta : meta; statements : llbc_statement list }
+and switch =
+  | If of operand * block * block
+  | SwitchInt of operand * integer_ty * (scalar_value list * block) list * block
+  | Match of place * (variant_id list * block) list * block option
+and llbc_expr_body = block g_expr_body
+and llbc_fun_decl = block g_fun_decl
+and llbc_fun_decls = (fun_decl_id * llbc_fun_decl) list
+and llbc_global_decl = block g_global_decl
+and llbc_global_decls = (global_decl_id * llbc_global_decl) list
+and 'id g_declaration_group =
+  | NonRec of 'id
+  | Rec of 'id list
+and declaration_group =
+  | Type of type_decl_id g_declaration_group
+  | Fun of fun_decl_id g_declaration_group
+  | Global of global_decl_id g_declaration_group
+  | TraitDecl of trait_decl_id g_declaration_group
+  | TraitImpl of trait_impl_id g_declaration_group
+and ('type_id, 'fun_id, 'global_id, 'trait_decl_id, 'trait_impl_id) any_decl_id =
+  | Type of 'type_id
+  | Fun of 'fun_id
+  | Global of 'global_id
+  | TraitDecl of 'trait_decl_id
+  | TraitImpl of 'trait_impl_id
+and declarations_groups = declaration_group list
+and any_trans_id = (type_decl_id, fun_decl_id, global_decl_id, trait_decl_id, trait_impl_id) any_decl_id
+and ('fd, 'gd) g_crate_data = { format_version : int; name : string; target_info : target_info; features : string list; cargo_profile : string; id_to_file : (file_id * file_name * string option) list; macro_expansions : macro_expansion list; declarations : declaration_group list; types : type_decl list; functions : 'fd list; globals : 'gd list; trait_decls : trait_decl list; trait_impls : trait_impl list; modules : module_ list; harnesses : harness list; ghost_functions : fun_decl_id list; clause_envs : clause_env list; type_aliases : type_alias_decl list; strings : string_table }
+and ('fd, 'gd) group_file = { format_version : int; index : int; group : declaration_group; depends_on : int list; types : type_decl list; functions : 'fd list; globals : 'gd list; trait_decls : trait_decl list; trait_impls : trait_impl list }
+and group_entry = { file : string; group : declaration_group; depends_on : int list; hash : string }
+and crate_index = { format_version : int; name : string; target_info : target_info; features : string list; cargo_profile : string; id_to_file : (file_id * file_name * string option) list; macro_expansions : macro_expansion list; modules : module_ list; harnesses : harness list; ghost_functions : fun_decl_id list; clause_envs : clause_env list; type_aliases : type_alias_decl list; groups : group_entry list; strings : string_table }
+and scalar_value = json
@@ -4,8 +4,10 @@ open Values
 module VarId = IdGen ()
 module GlobalDeclId = Types.GlobalDeclId
 module FunDeclId = Types.FunDeclId
+module PromotedId = IdGen ()
 
 type fun_decl_id = FunDeclId.id [@@deriving show, ord]
+type promoted_id = PromotedId.id [@@deriving show, ord]
 
 (** We define this type to control the name of the visitor functions
     (see e.g., {!Charon.UllbcAst.iter_statement_base}).
@@ -27,8 +29,7 @@ type assumed_fun_id =
 (** Ancestor the field_proj_kind iter visitor *)
 class ['self] iter_place_base =
   object (_self : 'self)
-    inherit [_] VisitorsRuntime.iter
-    method visit_type_decl_id : 'env -> type_decl_id -> unit = fun _ _ -> ()
+    inherit [_] iter_ty
     method visit_var_id : 'env -> var_id -> unit = fun _ _ -> ()
     method visit_variant_id : 'env -> variant_id -> unit = fun _ _ -> ()
     method visit_field_id : 'env -> field_id -> unit = fun _ _ -> ()
@@ -37,11 +38,7 @@ class ['self] iter_place_base =
 (** Ancestor the field_proj_kind map visitor *)
 class ['self] map_place_base =
   object (_self : 'self)
-    inherit [_] VisitorsRuntime.map
-
-    method visit_type_decl_id : 'env -> type_decl_id -> type_decl_id =
-      fun _ x -> x
-
+    inherit [_] map_ty
     method visit_var_id : 'env -> var_id -> var_id = fun _ x -> x
     method visit_variant_id : 'env -> variant_id -> variant_id = fun _ x -> x
     method visit_field_id : 'env -> field_id -> field_id = fun _ x -> x
@@ -51,8 +48,21 @@ type field_proj_kind =
   | ProjAdt of type_decl_id * variant_id option
   | ProjTuple of int  (** The integer gives the arity of the tuple *)
 
-(* Remark: no `Index` variant, as it is eliminated by a micro-pass *)
-and projection_elem = Deref | DerefBox | Field of field_proj_kind * field_id
+and projection_elem =
+  | Deref
+  | DerefBox
+  | DerefRawPtr
+  | Field of field_proj_kind * field_id
+  | Subslice of int * int * bool
+      (** [Subslice (from, to, from_end)] is the subslice of an array or of a
+          slice from index [from] (included) to index [to] (excluded). If
+          [from_end] is true, [to] is counted from the end. Those come from
+          the slice patterns with a subslice binding (ex.: [[first, rest @ ..]]).
+       *)
+  | Index of var_id * ty
+      (** Indexing of an array or of a slice (with the type of the array or of
+          the slice). Only present in the ULLBC: the LLBC replaces it with
+          calls to the index functions. *)
 and projection = projection_elem list
 
 and place = { var_id : var_id; projection : projection }
@@ -103,6 +113,31 @@ type binop =
   | Mul
   | Shl
   | Shr
+  | Offset
+      (** Offset a raw pointer by a number of elements ([p.offset(n)]) *)
+  | WrappingOffset
+      (** Same as [Offset], but the result may be out of bounds *)
+  | PtrDiff
+      (** The distance between two raw pointers, in number of elements
+          ([p.offset_from(q)]) *)
+  | CheckedAdd
+      (** [x.checked_add(y)]: returns [None] on overflow (and similarly for
+          the other checked operations) *)
+  | CheckedSub
+  | CheckedMul
+  | WrappingAdd  (** [x.wrapping_add(y)]: wraps around on overflow *)
+  | WrappingSub
+  | WrappingMul
+  | OverflowingAdd
+      (** [x.overflowing_add(y)]: returns the wrapped result and whether the
+          operation overflowed *)
+  | OverflowingSub
+  | OverflowingMul
+  | SaturatingAdd
+      (** [x.saturating_add(y)]: returns the bound of the integer type on
+          overflow *)
+  | SaturatingSub
+  | SaturatingMul
 [@@deriving show, ord]
 
 let all_binops =
@@ -123,6 +158,21 @@ let all_binops =
     Mul;
     Shl;
     Shr;
+    Offset;
+    WrappingOffset;
+    PtrDiff;
+    CheckedAdd;
+    CheckedSub;
+    CheckedMul;
+    WrappingAdd;
+    WrappingSub;
+    WrappingMul;
+    OverflowingAdd;
+    OverflowingSub;
+    OverflowingMul;
+    SaturatingAdd;
+    SaturatingSub;
+    SaturatingMul;
   ]
 
 (** Ancestor for the constant_expr iter visitor *)
@@ -131,6 +181,7 @@ class ['self] iter_constant_expr_base =
     inherit [_] iter_place
     inherit! [_] iter_ty
     method visit_assumed_fun_id : 'env -> assumed_fun_id -> unit = fun _ _ -> ()
+    method visit_promoted_id : 'env -> promoted_id -> unit = fun _ _ -> ()
   end
 
 (** Ancestor the constant_expr map visitor *)
@@ -141,24 +192,34 @@ class ['self] map_constant_expr_base =
 
     method visit_assumed_fun_id : 'env -> assumed_fun_id -> assumed_fun_id =
       fun _ x -> x
+
+    method visit_promoted_id : 'env -> promoted_id -> promoted_id = fun _ x -> x
   end
 
 type cast_kind =
   | CastScalar of literal_type * literal_type
   | CastFnPtr of ty * ty
 
-(* Remark: no `ArrayToSlice` variant: it gets eliminated in a micro-pass. *)
 and unop =
   | Not
   | Neg
   | Cast of cast_kind
       (** Cast an integer from a source type to a target type *)
+  | ArrayToSlice of ref_kind * ty * const_generic
+      (** Coercion from an array to a slice. Only present in the ULLBC: the
+          LLBC replaces it with calls to the conversion functions. *)
 
 and raw_constant_expr =
   | CLiteral of literal
   | CVar of const_generic_var_id
   | CTraitConst of trait_ref * string
   | CFnPtr of fn_ptr
+  | CMutStatic of global_decl_id
+      (** A raw pointer to a [static mut] (the accesses to the mutable statics
+          are made through such pointers, in unsafe code) *)
+  | CPromoted of promoted_id * generic_args
+      (** A reference to a promoted constant of the current body: the index
+          refers to the [promoted] list of the body *)
 
 and constant_expr = { value : raw_constant_expr; ty : ty }
 and fn_ptr = { func : fun_id_or_trait_method_ref; generics : generic_args }
@@ -206,7 +267,13 @@ class ['self] map_rvalue_base =
     method visit_borrow_kind : 'env -> borrow_kind -> borrow_kind = fun _ x -> x
   end
 
-type operand = Copy of place | Move of place | Constant of constant_expr
+type operand =
+  | Copy of place
+  | Move of place
+  | Constant of constant_expr
+  | Expr of rvalue * ty
+      (** A nested expression, with its type (introduced by the optional
+          reconstruction of the expressions) *)
 
 (** An aggregated ADT.
 
@@ -244,6 +311,23 @@ and rvalue =
   | Discriminant of place * type_decl_id
   | Aggregate of aggregate_kind * operand list
   | Global of global_decl_id * generic_args
+  | NullaryOp of null_op * ty
+      (** Nullary operation on a type, like [mem::size_of::<T>()] once
+          inlined. The result has type [usize]. *)
+  | Len of place * ty * const_generic option
+      (** The length of an array or of a slice (the const generic gives the
+          length of the arrays). Only present in the ULLBC: the LLBC removes
+          it together with the bounds checks. *)
+  | Repeat of operand * ty * const_generic
+      (** [Repeat (x, ty, n)] creates an array where [x] is copied [n] times.
+          Only present in the ULLBC: the LLBC replaces it with a function
+          call. *)
+
+and null_op =
+  | SizeOf
+  | AlignOf
+  | OffsetOf of (field_proj_kind * field_id) list
+      (** The path to the field, from the type given in the rvalue *)
 [@@deriving
   show,
     visitors
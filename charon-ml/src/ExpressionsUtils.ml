@@ -1,9 +1,13 @@
 open Expressions
 
 let unop_can_fail (unop : unop) : bool =
-  match unop with Neg | Cast _ -> true | Not -> false
+  match unop with Neg | Cast _ -> true | Not | ArrayToSlice _ -> false
 
 let binop_can_fail (binop : binop) : bool =
   match binop with
-  | BitXor | BitAnd | BitOr | Eq | Lt | Le | Ne | Ge | Gt -> false
-  | Div | Rem | Add | Sub | Mul | Shl | Shr -> true
+  | BitXor | BitAnd | BitOr | Eq | Lt | Le | Ne | Ge | Gt | WrappingOffset
+  | CheckedAdd | CheckedSub | CheckedMul | WrappingAdd | WrappingSub
+  | WrappingMul | OverflowingAdd | OverflowingSub | OverflowingMul
+  | SaturatingAdd | SaturatingSub | SaturatingMul ->
+      false
+  | Div | Rem | Add | Sub | Mul | Shl | Shr | Offset | PtrDiff -> true
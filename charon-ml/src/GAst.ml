@@ -8,6 +8,11 @@ module GlobalDeclId = Expressions.GlobalDeclId
 module TraitDeclId = Types.TraitDeclId
 module TraitImplId = Types.TraitImplId
 module TraitClauseId = Types.TraitClauseId
+module BlockId = Identifiers.IdGen ()
+module LoanId = Identifiers.IdGen ()
+module ModuleId = Identifiers.IdGen ()
+module PromotedId = Expressions.PromotedId
+module ScopeId = Identifiers.IdGen ()
 
 type fun_decl_id = FunDeclId.id [@@deriving show, ord]
 type assumed_fun_id = Expressions.assumed_fun_id [@@deriving show, ord]
@@ -16,6 +21,16 @@ type fun_id = Expressions.fun_id [@@deriving show, ord]
 type fun_id_or_trait_method_ref = Expressions.fun_id_or_trait_method_ref
 [@@deriving show, ord]
 
+(** A variable of the source code, stored in (a part of) a local *)
+type var_debug_info = {
+  name : string;
+  projection : projection;
+      (** The part of the local which stores the variable (empty if it is the
+          whole local) *)
+  meta : meta;
+}
+[@@deriving show]
+
 (** A variable, as used in a function definition *)
 type var = {
   index : VarId.id;  (** Unique variable identifier *)
@@ -24,6 +39,9 @@ type var = {
       (** The variable type - erased type, because variables are not used
        ** in function signatures: they are only used to declare the list of
        ** variables manipulated by a function body *)
+  debug_info : var_debug_info list;
+      (** The variables of the source code stored in this local, as given by
+          the debug information of the MIR *)
 }
 [@@deriving show]
 
@@ -43,10 +61,91 @@ class ['self] map_ast_base =
 
 (* Below: the types need not be mutually recursive, but it makes it easier
    to derive the visitors *)
-type assertion = { cond : operand; expected : bool }
+type assertion = { cond : operand; expected : bool; kind : assert_kind }
+
+(** The dynamic check performed by an assertion *)
+and assert_kind =
+  | AssertUser
+      (** An assertion reconstructed from an [if ... { panic!() }] *)
+  | AssertBounds  (** A bounds check of an array or slice access *)
+  | AssertOverflow  (** An overflow check of an arithmetic operation *)
+  | AssertDivisionByZero  (** A division by zero check *)
+
 and fn_operand = FnOpRegular of fn_ptr | FnOpMove of place
 
-and call = { func : fn_operand; args : operand list; dest : place }
+(** An adjustment the compiler inserted on the receiver of a method call (the
+    corresponding operations are already present in the body) *)
+and adjustment =
+  | AdjDeref  (** A built-in dereference *)
+  | AdjOverloadedDeref of ref_kind
+      (** A call to [Deref::deref] or to [DerefMut::deref_mut] *)
+  | AdjBorrow of ref_kind  (** A borrow (auto-ref) *)
+  | AdjUnsize  (** An unsizing coercion *)
+
+(** How the compiler resolved a method call *)
+and method_resolution =
+  | ResInherent  (** A method of an inherent impl *)
+  | ResTraitImpl of trait_impl_id
+      (** A method implemented by the given trait impl *)
+  | ResDefaultMethod of trait_impl_id
+      (** The default implementation of a method, which the given trait impl
+          doesn't override *)
+  | ResBuiltinOrAuto of trait_decl_id
+      (** A method of a builtin or auto trait implementation *)
+  | ResClause
+      (** A method of a trait implementation which comes from a clause *)
+
+and call = {
+  func : fn_operand;
+  args : operand list;
+  dest : place;
+  receiver_adjustments : adjustment list;
+      (** For method calls, the adjustments applied to the receiver *)
+  resolution : method_resolution option;
+      (** For method calls, how the method was resolved *)
+  is_tail : bool;
+      (** [true] if the call is in tail position: its result is returned, and
+          only drops happen between the call and the return *)
+  is_ghost : bool;
+      (** [true] if the call is ghost code (the callee is marked with
+          [#[charon::ghost]], or the call comes from a [ghost!] macro), which is
+          not part of the executable semantics *)
+}
+
+(** A copy of [count] values from the raw pointer [src] to the raw pointer
+    [dst] ([ptr::copy_nonoverlapping] if [nonoverlapping] is true, [ptr::copy]
+    otherwise) *)
+and mem_copy = {
+  src : operand;
+  dst : operand;
+  count : operand;
+  nonoverlapping : bool;
+}
+
+(** [ptr::write_bytes(dst, val, count)] *)
+and write_bytes = { dst : operand; val_ : operand; count : operand }
+
+(** The meaning of a domain-specific intrinsic *)
+and intrinsic_kind =
+  | IntrinsicAssume  (** Assume that the argument (a boolean) is true *)
+  | IntrinsicAssert  (** Check that the argument (a boolean) is true *)
+  | IntrinsicHavoc  (** Give an arbitrary value to the destination *)
+  | IntrinsicCustom of string
+      (** A statement whose meaning is only known to the consumer of the
+          crate, identified by its name *)
+
+(** A call to a function which the tool embedding Charon asked to translate to
+    a dedicated statement rather than to a call, or to a built-in of the
+    verifiers (like [kani::assume], [prusti::assert] or [kani::any]) *)
+and intrinsic = {
+  kind : intrinsic_kind;
+  args : operand list;
+  dest : place;
+  message : string option;
+      (** The message given by the user to an assertion, if it is a string
+          literal *)
+}
+
 [@@deriving
   show,
     visitors
@@ -90,11 +189,48 @@ type params_info = {
 [@@deriving show]
 
 type closure_kind = Fn | FnMut | FnOnce [@@deriving show]
-type closure_info = { kind : closure_kind; state : ty list } [@@deriving show]
+
+(** The way a closure captures a place (the unique immutable borrows of rustc
+    are translated as mutable borrows) *)
+type capture_mode = CaptureByValue | CaptureByRef of ref_kind
+[@@deriving show]
+
+type capture_projection = CaptureDeref | CaptureField of FieldId.id
+[@@deriving show]
+
+(** A place captured by a closure, given as a path from a local variable of
+    the parent function *)
+type captured_place = {
+  var_id : VarId.id;
+      (** The captured variable, in the body of the parent function (or of the
+          function or closure which declares it, for the nested closures) *)
+  projection : capture_projection list;
+  mode : capture_mode;
+}
+[@@deriving show]
+
+type closure_info = {
+  kind : closure_kind;
+  state : ty list;
+  captures : captured_place list;
+      (** The i-th place corresponds to the i-th field of the state. Empty for
+          the non-local closures. *)
+  is_move : bool;
+  call_mut_shim : FunDeclId.id option;
+      (** The function we generate for [call_mut] if the closure implements
+          [Fn] *)
+  call_once_shim : FunDeclId.id option;
+      (** The function we generate for [call_once] if the closure implements
+          [Fn] or [FnMut] *)
+}
+[@@deriving show]
 
 (** A function signature for function declarations *)
 type fun_sig = {
   is_unsafe : bool;
+  is_const : bool;
+      (** [true] for the [const fn] (this includes the methods of the [const]
+          trait impls) *)
   is_closure : bool;
   closure_info : closure_info option;
   generics : generic_params;
@@ -102,6 +238,12 @@ type fun_sig = {
   parent_params_info : params_info option;
   inputs : ty list;
   output : ty;
+  input_aliases : type_alias_ref option list;
+      (** The type aliases the user wrote for the types of the inputs. This is
+          empty if we don't know them (for the closures and the external
+          functions), otherwise there is one per input. *)
+  output_alias : type_alias_ref option;
+      (** The type alias the user wrote for the type of the output, if any *)
 }
 [@@deriving show]
 
@@ -124,6 +266,58 @@ type item_kind =
           default implementation at the same time *)
 [@@deriving show]
 
+(** The region variables introduced by the borrow checker in a body, and the
+    outlives constraints between them. This is empty if the regions of the
+    bodies are erased. *)
+type body_regions = {
+  body_region_vars : region_var list;
+  body_region_constraints : region_outlives list;
+}
+[@@deriving show]
+
+type loan_id = LoanId.id [@@deriving show]
+
+(** A location in a body: the point before the statement of index
+    [statement_index] in the block [block] (if the index is equal to the number
+    of statements, this is the point before the terminator).
+
+    The locations refer to the ULLBC bodies, as translated from MIR (before the
+    micro-passes). *)
+type location = { block : BlockId.id; statement_index : int } [@@deriving show]
+
+(** A loan, that is a borrow performed by the body *)
+type loan = {
+  loan_index : loan_id;
+  loan_region : region;  (** The region of the borrow *)
+  loan_location : location;
+      (** The location of the statement which performs the borrow *)
+}
+[@@deriving show]
+
+(** The facts computed by the borrow checker for a body. *)
+type borrowck_facts = {
+  loans : loan list;
+  subset_constraints : (region * region * location) list;
+      (** The outlives constraints between the regions, together with the
+          locations at which they hold *)
+  loans_invalidated_at : (loan_id * location) list;
+  loans_killed_at : (loan_id * location) list;
+  loans_out_of_scope_at : (location * loan_id list) list;
+}
+[@@deriving show]
+
+(** A lexical scope of a body: the scopes form a tree, whose root (the first
+    scope) is the scope of the whole body *)
+type scope = {
+  index : ScopeId.id;
+  parent : ScopeId.id option;  (** [None] for the root scope *)
+  meta : meta;
+  inlined : bool;
+      (** Whether the scope comes from a function inlined by rustc *)
+  locals : VarId.id list;  (** The locals declared in this scope *)
+}
+[@@deriving show]
+
 type 'body gexpr_body = {
   meta : meta;
   arg_count : int;
@@ -132,6 +326,14 @@ type 'body gexpr_body = {
 
           See {!Identifiers.Id.mapi} for instance.
        *)
+  regions : body_regions;
+  borrowck_facts : borrowck_facts option;
+  scopes : scope list;
+      (** The tree of the lexical scopes (empty for the bodies generated by
+          Charon) *)
+  promoted : GlobalDeclId.id list;
+      (** The globals translated from the constants promoted in this body,
+          indexed by {!PromotedId.id} *)
   body : 'body;
 }
 [@@deriving show]
@@ -141,8 +343,14 @@ type 'body gfun_decl = {
   item_meta : item_meta;
   is_local : bool;
   name : name;
+  lang_item : string option;
+      (** The lang item the function corresponds to, if any (for instance
+          [deref_method] for [Deref::deref]) *)
   signature : fun_sig;
   kind : item_kind;
+  rec_group : FunDeclId.id list option;
+      (** If the function is (mutually) recursive, the functions of its
+          recursive group, including itself *)
   body : 'body gexpr_body option;
   is_global_decl_body : bool;
 }
@@ -153,29 +361,55 @@ type trait_decl = {
   item_meta : item_meta;
   is_local : bool;
   name : name;
+  lang_item : string option;
+      (** The lang item the trait corresponds to, if any (for instance [add]
+          for [core::ops::Add], [eq] for [PartialEq]) *)
   generics : generic_params;
   preds : predicates;
   parent_clauses : trait_clause list;
   consts : (trait_item_name * (ty * global_decl_id option)) list;
-  types : (trait_item_name * (trait_clause list * ty option)) list;
+  types :
+    (trait_item_name * (generic_params * trait_clause list * ty option)) list;
+      (** The generics of an associated type include the generics of the
+          trait, followed by the parameters of the type itself *)
   required_methods : (trait_item_name * fun_decl_id) list;
   provided_methods : (trait_item_name * fun_decl_id option) list;
 }
 [@@deriving show]
 
+(** How a trait implementation was introduced *)
+type trait_impl_kind =
+  | RegularImpl  (** A hand-written impl *)
+  | BlanketImpl  (** An impl for a type parameter: [impl<T: Foo> Bar for T] *)
+  | DerivedImpl of string
+      (** An impl generated by a [#[derive(...)]] attribute: we store the name
+          of the derive macro ([Clone], [PartialEq], etc.) *)
+[@@deriving show]
+
 type trait_impl = {
   def_id : trait_impl_id;
   item_meta : item_meta;
   is_local : bool;
   name : name;
+  impl_kind : trait_impl_kind;
+  is_negative : bool;
+      (** [true] if this is a negative impl ([impl !Send for Foo {}]) *)
+  is_const : bool;
+      (** [true] if this is a [const] impl ([impl const Foo for Bar]) *)
   impl_trait : trait_decl_ref;
   generics : generic_params;
   preds : predicates;
   parent_trait_refs : trait_ref list;
   consts : (trait_item_name * (ty * global_decl_id)) list;
-  types : (trait_item_name * (trait_ref list * ty)) list;
+  types : (trait_item_name * (generic_params * trait_ref list * ty)) list;
   required_methods : (trait_item_name * fun_decl_id) list;
-  provided_methods : (trait_item_name * fun_decl_id) list;
+  provided_methods : (trait_item_name * (fun_decl_id * bool)) list;
+      (** The boolean is [true] if the method overrides the default
+          implementation, and [false] if the identifier is the one of the
+          default implementation given in the trait declaration. *)
+  default_items : trait_item_name list;
+      (** The items which can be specialized by other impls (the [default]
+          items) *)
 }
 [@@deriving show]
 
@@ -196,6 +430,70 @@ type declaration_group =
   | TraitImplGroup of TraitImplId.id
 [@@deriving show]
 
+(** The id of a declaration *)
+type any_decl_id =
+  | IdType of TypeDeclId.id
+  | IdFun of FunDeclId.id
+  | IdGlobal of GlobalDeclId.id
+  | IdTraitDecl of TraitDeclId.id
+  | IdTraitImpl of TraitImplId.id
+[@@deriving show]
+
+(** A module of the crate *)
+type module_decl = {
+  module_id : ModuleId.id;
+  item_meta : item_meta;
+  name : name;
+  parent : ModuleId.id option;  (** [None] for the crate root *)
+  submodules : ModuleId.id list;
+  items : any_decl_id list;
+      (** The translated items declared in the module (the methods are listed
+          in the module of their impl block or trait) *)
+}
+[@@deriving show]
+
+(** A verification entry point: a function marked with an attribute like
+    [#[kani::proof]] or [#[test]] *)
+type harness = {
+  fun_id : FunDeclId.id;
+  attribute : string;  (** The path of the attribute, like [kani::proof] *)
+  args : string list;  (** The arguments of the attribute *)
+}
+[@@deriving show]
+
+(** A trait clause in scope in a declaration (see [clause_env]) *)
+type elaborated_clause = {
+  clause_trait_id : trait_instance_id;
+      (** The path of the clause, as it appears in the trait references of
+          the declaration *)
+  elab_bound_regions : region_var list;
+  elab_trait_decl_ref : trait_decl_ref;
+      (** The trait the clause implements, instantiated with the generics of
+          the declaration *)
+}
+[@@deriving show]
+
+(** The trait clauses in scope in a declaration: its own clauses, and the
+    clauses we derive from them through the parent clauses and the item
+    clauses of the traits *)
+type clause_env = {
+  env_item : any_decl_id;
+  env_clauses : elaborated_clause list;
+}
+[@@deriving show]
+
+(** The constants which appear in the bodies (like [&3u32] in [f(&3u32)])
+    are promoted by rustc: we translate them as globals, which share the
+    generics of their parent declaration. *)
+type promoted_info = {
+  promoted_parent : any_decl_id;
+      (** The declaration whose body contains the constant *)
+  promoted_index : PromotedId.id;
+      (** The index of the constant among the promoted constants of the
+          parent *)
+}
+[@@deriving show]
+
 type 'body gglobal_decl = {
   def_id : GlobalDeclId.id;
   item_meta : item_meta;
@@ -204,7 +502,17 @@ type 'body gglobal_decl = {
   generics : generic_params;
   preds : predicates;
   ty : ty;
+  is_mutable : bool;  (** [true] for the [static mut] *)
+  is_thread_local : bool;  (** [true] for the [#[thread_local]] statics *)
+  is_interior_mutable : bool;
+      (** [true] if the type of the global has interior mutability (it
+          contains an [UnsafeCell]) *)
   kind : item_kind;
+  promoted : promoted_info option;
+      (** [Some] if the global is a promoted constant *)
+  init_deps : GlobalDeclId.id list;
+      (** The globals whose initializers must be executed before the
+          initializer of this global *)
   body : 'body;
 }
 [@@deriving show]
@@ -212,11 +520,24 @@ type 'body gglobal_decl = {
 (** A crate *)
 type ('fun_body, 'global_body) gcrate = {
   name : string;
+  target_info : target_info;
+  features : string list;  (** The features enabled for the extraction *)
+  cargo_profile : string;  (** The cargo profile the crate was compiled with *)
+  macro_expansions : macro_expansion list;
+      (** The macro expansions the code comes from (see [meta.expansion]). *)
   declarations : declaration_group list;
   type_decls : type_decl TypeDeclId.Map.t;
   fun_decls : 'fun_body gfun_decl FunDeclId.Map.t;
   global_decls : 'global_body gglobal_decl GlobalDeclId.Map.t;
   trait_decls : trait_decl TraitDeclId.Map.t;
   trait_impls : trait_impl TraitImplId.Map.t;
+  modules : module_decl list;  (** The module tree of the crate *)
+  harnesses : harness list;  (** The verification entry points of the crate *)
+  ghost_functions : FunDeclId.id list;
+      (** The functions marked with [#[charon::ghost]] *)
+  clause_envs : clause_env list;
+      (** The trait clauses in scope in the declarations *)
+  type_aliases : type_alias_decl TypeAliasId.Map.t;
+      (** The type aliases of the crate (see the [--type-aliases] option) *)
 }
 [@@deriving show]
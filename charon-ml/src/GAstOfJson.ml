@@ -77,12 +77,38 @@ let file_name_of_json (js : json) : (file_name, string) result =
   *)
 let id_to_file_of_json (js : json) : (id_to_file_map, string) result =
   combine_error_msgs js __FUNCTION__
-    ((* The map is stored as a list of pairs (key, value): we deserialize
-      * this list then convert it to a map *)
+    ((* The map is stored as a list of triples (key, value, content): we
+      * deserialize this list then convert it to a map (we ignore the content
+      * of the files, which is only exported with [--include-sources]) *)
      let* key_values =
-       list_of_json (pair_of_json file_id_of_json file_name_of_json) js
+       list_of_json
+         (triple_of_json file_id_of_json file_name_of_json
+            (option_of_json string_of_json))
+         js
      in
-     Ok (IdToFile.of_list key_values))
+     Ok
+       (IdToFile.of_list
+          (List.map (fun (id, name, _) -> (id, name)) key_values)))
+
+(** The string table of the crate we are deserializing.
+
+    The symbols (the identifiers of the names, and the names of the fields and
+    of the variants) are serialized as indices in a string table, which is
+    serialized once (see [charon/src/symbol.rs]). We set the table with
+    [string_table_of_json] before deserializing the declarations. *)
+let string_table : string array ref = ref [||]
+
+let string_table_of_json (js : json) : (unit, string) result =
+  combine_error_msgs js __FUNCTION__
+    (let* strings = list_of_json string_of_json js in
+     string_table := Array.of_list strings;
+     Ok ())
+
+let symbol_of_json (js : json) : (string, string) result =
+  combine_error_msgs js __FUNCTION__
+    (let* i = int_of_json js in
+     if 0 <= i && i < Array.length !string_table then Ok (!string_table).(i)
+     else Error ("Unknown symbol: " ^ string_of_int i))
 
 let loc_of_json (js : json) : (loc, string) result =
   combine_error_msgs js __FUNCTION__
@@ -109,12 +135,38 @@ let meta_of_json (id_to_file : id_to_file_map) (js : json) :
     (meta, string) result =
   combine_error_msgs js __FUNCTION__
     (match js with
-    | `Assoc [ ("span", span); ("generated_from_span", generated_from_span) ] ->
+    | `Assoc
+        [
+          ("span", span);
+          ("generated_from_span", generated_from_span);
+          ("expansion", expansion);
+          ("in_unsafe_block", in_unsafe_block);
+        ] ->
         let* span = span_of_json id_to_file span in
         let* generated_from_span =
           option_of_json (span_of_json id_to_file) generated_from_span
         in
-        Ok { span; generated_from_span }
+        let* expansion = option_of_json int_of_json expansion in
+        let* in_unsafe_block = bool_of_json in_unsafe_block in
+        Ok { span; generated_from_span; expansion; in_unsafe_block }
+    | _ -> Error "")
+
+let macro_expansion_of_json (id_to_file : id_to_file_map) (js : json) :
+    (macro_expansion, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc
+        [
+          ("macro_name", macro_name);
+          ("call_site", call_site);
+          ("def_site", def_site);
+          ("parent", parent);
+        ] ->
+        let* macro_name = string_of_json macro_name in
+        let* call_site = span_of_json id_to_file call_site in
+        let* def_site = option_of_json (span_of_json id_to_file) def_site in
+        let* parent = option_of_json int_of_json parent in
+        Ok { macro_name; call_site; def_site; parent }
     | _ -> Error "")
 
 let inline_attr_of_json (js : json) : (inline_attr, string) result =
@@ -125,6 +177,21 @@ let inline_attr_of_json (js : json) : (inline_attr, string) result =
     | `String "Always" -> Ok Always
     | _ -> Error "")
 
+let visibility_of_json (js : json) : (visibility, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `String "Public" -> Ok Public
+    | `Assoc [ ("Restricted", path) ] ->
+        (* The paths of the modules only contain identifiers *)
+        let ident_of_json (js : json) : (string, string) result =
+          match js with
+          | `Assoc [ ("Ident", `List [ name; _ ]) ] -> string_of_json name
+          | _ -> Error ""
+        in
+        let* path = list_of_json ident_of_json path in
+        Ok (Restricted path)
+    | _ -> Error "")
+
 let item_meta_of_json (id_to_file : id_to_file_map) (js : json) :
     (item_meta, string) result =
   combine_error_msgs js __FUNCTION__
@@ -133,23 +200,32 @@ let item_meta_of_json (id_to_file : id_to_file_map) (js : json) :
         [
           ("meta", meta);
           ("attributes", attributes);
+          ("cfg", cfg);
           ("inline", inline);
           ("public", public);
+          ("visibility", visibility);
+          ("stable_id", stable_id);
+          ("hash", hash);
         ] ->
         let* meta = meta_of_json id_to_file meta in
         let* attributes = list_of_json string_of_json attributes in
+        let* cfg = list_of_json string_of_json cfg in
         let* inline = option_of_json inline_attr_of_json inline in
         let* public = bool_of_json public in
-        Ok { meta; attributes; inline; public }
-    | _ -> Error "")
-
-let type_var_of_json (js : json) : (type_var, string) result =
-  combine_error_msgs js __FUNCTION__
-    (match js with
-    | `Assoc [ ("index", index); ("name", name) ] ->
-        let* index = TypeVarId.id_of_json index in
-        let* name = string_of_json name in
-        Ok { index; name }
+        let* visibility = option_of_json visibility_of_json visibility in
+        let* stable_id = string_of_json stable_id in
+        let* hash = string_of_json hash in
+        Ok
+          {
+            meta;
+            attributes;
+            cfg;
+            inline;
+            public;
+            visibility;
+            stable_id;
+            hash;
+          }
     | _ -> Error "")
 
 let region_var_of_json (js : json) : (region_var, string) result =
@@ -170,6 +246,9 @@ let region_of_json (js : json) : (region, string) result =
         let* dbid = int_of_json dbid in
         let* rid = RegionVarId.id_of_json rid in
         Ok (RBVar (dbid, rid) : region)
+    | `Assoc [ ("Body", rid) ] ->
+        let* rid = RegionVarId.id_of_json rid in
+        Ok (RBody rid : region)
     | _ -> Error "")
 
 let integer_type_of_json (js : json) : (integer_type, string) result =
@@ -198,16 +277,6 @@ let literal_type_of_json (js : json) : (literal_type, string) result =
     | `String "Char" -> Ok TChar
     | _ -> Error "")
 
-let const_generic_var_of_json (js : json) : (const_generic_var, string) result =
-  combine_error_msgs js __FUNCTION__
-    (match js with
-    | `Assoc [ ("index", index); ("name", name); ("ty", ty) ] ->
-        let* index = ConstGenericVarId.id_of_json index in
-        let* name = string_of_json name in
-        let* ty = literal_type_of_json ty in
-        Ok { index; name; ty }
-    | _ -> Error "")
-
 let ref_kind_of_json (js : json) : (ref_kind, string) result =
   match js with
   | `String "Mut" -> Ok RMut
@@ -312,20 +381,6 @@ let literal_of_json (js : json) : (literal, string) result =
         Ok (VChar v)
     | _ -> Error "")
 
-let const_generic_of_json (js : json) : (const_generic, string) result =
-  combine_error_msgs js __FUNCTION__
-    (match js with
-    | `Assoc [ ("Global", id) ] ->
-        let* id = GlobalDeclId.id_of_json id in
-        Ok (CgGlobal id)
-    | `Assoc [ ("Var", id) ] ->
-        let* id = ConstGenericVarId.id_of_json id in
-        Ok (CgVar id)
-    | `Assoc [ ("Value", lit) ] ->
-        let* lit = literal_of_json lit in
-        Ok (CgValue lit)
-    | _ -> Error "")
-
 let rec ty_of_json (js : json) : (ty, string) result =
   combine_error_msgs js __FUNCTION__
     (match js with
@@ -352,15 +407,38 @@ let rec ty_of_json (js : json) : (ty, string) result =
         let* ty = ty_of_json ty in
         let* ref_kind = ref_kind_of_json ref_kind in
         Ok (TRawPtr (ty, ref_kind))
-    | `Assoc [ ("TraitType", `List [ trait_ref; item_name ]) ] ->
+    | `Assoc [ ("TraitType", `List [ trait_ref; item_name; generics ]) ] ->
         let* trait_ref = trait_ref_of_json trait_ref in
         let* item_name = string_of_json item_name in
-        Ok (TTraitType (trait_ref, item_name))
+        let* generics = generic_args_of_json generics in
+        Ok (TTraitType (trait_ref, item_name, generics))
     | `Assoc [ ("Arrow", `List [ regions; inputs; output ]) ] ->
         let* regions = list_of_json region_var_of_json regions in
         let* inputs = list_of_json ty_of_json inputs in
         let* output = ty_of_json output in
         Ok (TArrow (regions, inputs, output))
+    | `Assoc [ ("FnDef", `List [ fid; generics ]) ] ->
+        let* fid = FunDeclId.id_of_json fid in
+        let* generics = generic_args_of_json generics in
+        Ok (TFnDef (fid, generics))
+    | _ -> Error "")
+
+and const_generic_of_json (js : json) : (const_generic, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("Global", id) ] ->
+        let* id = GlobalDeclId.id_of_json id in
+        Ok (CgGlobal id)
+    | `Assoc [ ("TraitConst", `List [ trait_ref; name ]) ] ->
+        let* trait_ref = trait_ref_of_json trait_ref in
+        let* name = string_of_json name in
+        Ok (CgTraitConst (trait_ref, name))
+    | `Assoc [ ("Var", id) ] ->
+        let* id = ConstGenericVarId.id_of_json id in
+        Ok (CgVar id)
+    | `Assoc [ ("Value", lit) ] ->
+        let* lit = literal_of_json lit in
+        Ok (CgValue lit)
     | _ -> Error "")
 
 and trait_ref_of_json (js : json) : (trait_ref, string) result =
@@ -413,6 +491,19 @@ and trait_instance_id_of_json (js : json) : (trait_instance_id, string) result =
     | `Assoc [ ("TraitImpl", id) ] ->
         let* id = TraitImplId.id_of_json id in
         Ok (TraitImpl id)
+    | `Assoc
+        [
+          ( "Specializable",
+            `Assoc
+              [
+                ("impl_id", impl_id); ("specializing_impls", specializing_impls);
+              ] );
+        ] ->
+        let* impl_id = TraitImplId.id_of_json impl_id in
+        let* specializing_impls =
+          list_of_json TraitImplId.id_of_json specializing_impls
+        in
+        Ok (Specializable (impl_id, specializing_impls))
     | `Assoc [ ("BuiltinOrAuto", id) ] ->
         let* id = TraitDeclId.id_of_json id in
         Ok (BuiltinOrAuto id)
@@ -440,26 +531,91 @@ and trait_instance_id_of_json (js : json) : (trait_instance_id, string) result =
         Ok (Closure (fid, generics))
     | _ -> Error "")
 
+let type_alias_ref_of_json (js : json) : (type_alias_ref, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("alias_id", alias_id); ("generics", generics) ] ->
+        let* alias_id = TypeAliasId.id_of_json alias_id in
+        let* generics = generic_args_of_json generics in
+        Ok { alias_id; alias_generics = generics }
+    | _ -> Error "")
+
 let field_of_json (id_to_file : id_to_file_map) (js : json) :
     (field, string) result =
   combine_error_msgs js __FUNCTION__
     (match js with
-    | `Assoc [ ("meta", meta); ("name", name); ("ty", ty) ] ->
+    | `Assoc
+        [
+          ("meta", meta);
+          ("name", name);
+          ("ty", ty);
+          ("is_zst", is_zst);
+          ("is_phantom_data", is_phantom_data);
+          ("alias", alias);
+        ] ->
         let* meta = meta_of_json id_to_file meta in
-        let* name = option_of_json string_of_json name in
+        let* name = option_of_json symbol_of_json name in
         let* ty = ty_of_json ty in
-        Ok { meta; field_name = name; field_ty = ty }
+        let* is_zst = bool_of_json is_zst in
+        let* is_phantom_data = bool_of_json is_phantom_data in
+        let* alias = option_of_json type_alias_ref_of_json alias in
+        Ok
+          {
+            meta;
+            field_name = name;
+            field_ty = ty;
+            field_is_zst = is_zst;
+            field_is_phantom_data = is_phantom_data;
+            field_alias = alias;
+          }
     | _ -> Error "")
 
 let variant_of_json (id_to_file : id_to_file_map) (js : json) :
     (variant, string) result =
   combine_error_msgs js __FUNCTION__
     (match js with
-    | `Assoc [ ("meta", meta); ("name", name); ("fields", fields) ] ->
+    | `Assoc
+        [
+          ("meta", meta);
+          ("name", name);
+          ("fields", fields);
+          ("discriminant", discriminant);
+        ] ->
         let* meta = meta_of_json id_to_file meta in
-        let* name = string_of_json name in
+        let* name = symbol_of_json name in
         let* fields = list_of_json (field_of_json id_to_file) fields in
-        Ok { meta; variant_name = name; fields }
+        let* discriminant = scalar_value_of_json discriminant in
+        Ok { meta; variant_name = name; fields; discriminant }
+    | _ -> Error "")
+
+let impl_trait_bound_of_json (js : json) : (impl_trait_bound, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc
+        [
+          ("trait_id", trait_id);
+          ("generics", generics);
+          ("type_constraints", type_constraints);
+        ] ->
+        let* bound_trait_id = TraitDeclId.id_of_json trait_id in
+        let* bound_generics = generic_args_of_json generics in
+        let* bound_type_constraints =
+          list_of_json (pair_of_json string_of_json ty_of_json) type_constraints
+        in
+        Ok { bound_trait_id; bound_generics; bound_type_constraints }
+    | _ -> Error "")
+
+let enum_layout_of_json (js : json) : (enum_layout, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `String "Single" -> Ok Single
+    | `String "Tagged" -> Ok Tagged
+    | `Assoc
+        [ ("Niche", `Assoc [ ("untagged_variant", variant); ("field", field) ]) ]
+      ->
+        let* variant = VariantId.id_of_json variant in
+        let* field = FieldId.id_of_json field in
+        Ok (Niche (variant, field))
     | _ -> Error "")
 
 let type_decl_kind_of_json (id_to_file : id_to_file_map) (js : json) :
@@ -469,10 +625,17 @@ let type_decl_kind_of_json (id_to_file : id_to_file_map) (js : json) :
     | `Assoc [ ("Struct", fields) ] ->
         let* fields = list_of_json (field_of_json id_to_file) fields in
         Ok (Struct fields)
-    | `Assoc [ ("Enum", variants) ] ->
+    | `Assoc [ ("Enum", `List [ variants; discr_ty; layout ]) ] ->
         let* variants = list_of_json (variant_of_json id_to_file) variants in
-        Ok (Enum variants)
+        let* discr_ty = integer_type_of_json discr_ty in
+        let* layout = option_of_json enum_layout_of_json layout in
+        Ok (Enum (variants, discr_ty, layout))
     | `String "Opaque" -> Ok Opaque
+    | `Assoc [ ("ImplTrait", `Assoc [ ("bounds", bounds); ("hidden_ty", ty) ]) ]
+      ->
+        let* bounds = list_of_json impl_trait_bound_of_json bounds in
+        let* ty = option_of_json ty_of_json ty in
+        Ok (ImplTrait (bounds, ty))
     | _ -> Error "")
 
 let region_var_group_of_json (js : json) : (region_var_group, string) result =
@@ -497,13 +660,58 @@ let trait_clause_of_json (id_to_file : id_to_file_map) (js : json) :
           ("clause_id", clause_id);
           ("meta", meta);
           ("trait_id", trait_id);
+          ("bound_regions", bound_regions);
           ("generics", generics);
+          ("is_const", is_const);
         ] ->
         let* clause_id = TraitClauseId.id_of_json clause_id in
         let* meta = option_of_json (meta_of_json id_to_file) meta in
         let* trait_id = TraitDeclId.id_of_json trait_id in
+        let* clause_bound_regions =
+          list_of_json region_var_of_json bound_regions
+        in
         let* clause_generics = generic_args_of_json generics in
-        Ok ({ clause_id; meta; trait_id; clause_generics } : trait_clause)
+        let* clause_is_const = bool_of_json is_const in
+        Ok
+          ({
+             clause_id;
+             meta;
+             trait_id;
+             clause_bound_regions;
+             clause_generics;
+             clause_is_const;
+           }
+            : trait_clause)
+    | _ -> Error "")
+
+let type_var_of_json (js : json) : (type_var, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc
+        [
+          ("index", index);
+          ("name", name);
+          ("is_sized", is_sized);
+          ("default", default);
+        ] ->
+        let* index = TypeVarId.id_of_json index in
+        let* name = string_of_json name in
+        let* is_sized = bool_of_json is_sized in
+        let* default = option_of_json ty_of_json default in
+        Ok ({ index; name; is_sized; default } : type_var)
+    | _ -> Error "")
+
+let const_generic_var_of_json (js : json) : (const_generic_var, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc
+        [ ("index", index); ("name", name); ("ty", ty); ("default", default) ]
+      ->
+        let* index = ConstGenericVarId.id_of_json index in
+        let* name = string_of_json name in
+        let* ty = literal_type_of_json ty in
+        let* default = option_of_json const_generic_of_json default in
+        Ok ({ index; name; ty; default } : const_generic_var)
     | _ -> Error "")
 
 let generic_params_of_json (id_to_file : id_to_file_map) (js : json) :
@@ -550,12 +758,29 @@ let trait_type_constraint_of_json (js : json) :
     (trait_type_constraint, string) result =
   combine_error_msgs js __FUNCTION__
     (match js with
-    | `Assoc [ ("trait_ref", trait_ref); ("type_name", type_name); ("ty", ty) ]
-      ->
+    | `Assoc
+        [
+          ("trait_ref", trait_ref);
+          ("type_name", type_name);
+          ("generics", generics);
+          ("ty", ty);
+        ] ->
         let* trait_ref = trait_ref_of_json trait_ref in
         let* type_name = string_of_json type_name in
+        let* generics = generic_args_of_json generics in
         let* ty = ty_of_json ty in
-        Ok ({ trait_ref; type_name; ty } : trait_type_constraint)
+        Ok ({ trait_ref; type_name; generics; ty } : trait_type_constraint)
+    | _ -> Error "")
+
+let region_graph_of_json (js : json) : (region_graph, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("regions", regions); ("types", types) ] ->
+        let* graph_regions =
+          list_of_json (list_of_json region_of_json) regions
+        in
+        let* graph_types = list_of_json (list_of_json region_of_json) types in
+        Ok { graph_regions; graph_types }
     | _ -> Error "")
 
 let predicates_of_json (js : json) : (predicates, string) result =
@@ -566,6 +791,7 @@ let predicates_of_json (js : json) : (predicates, string) result =
           ("regions_outlive", regions_outlive);
           ("types_outlive", types_outlive);
           ("trait_type_constraints", trait_type_constraints);
+          ("regions_graph", regions_graph);
         ] ->
         let* regions_outlive =
           list_of_json region_outlives_of_json regions_outlive
@@ -574,7 +800,14 @@ let predicates_of_json (js : json) : (predicates, string) result =
         let* trait_type_constraints =
           list_of_json trait_type_constraint_of_json trait_type_constraints
         in
-        Ok { regions_outlive; types_outlive; trait_type_constraints }
+        let* regions_graph = region_graph_of_json regions_graph in
+        Ok
+          {
+            regions_outlive;
+            types_outlive;
+            trait_type_constraints;
+            regions_graph;
+          }
     | _ -> Error "")
 
 let impl_elem_kind_of_json (js : json) : (impl_elem_kind, string) result =
@@ -611,7 +844,7 @@ let path_elem_of_json (id_to_file : id_to_file_map) (js : json) :
   combine_error_msgs js __FUNCTION__
     (match js with
     | `Assoc [ ("Ident", `List [ name; d ]) ] ->
-        let* name = string_of_json name in
+        let* name = symbol_of_json name in
         let* d = Disambiguator.id_of_json d in
         Ok (PeIdent (name, d))
     | `Assoc [ ("Impl", impl) ] ->
@@ -624,6 +857,42 @@ let name_of_json (id_to_file : id_to_file_map) (js : json) :
   combine_error_msgs js __FUNCTION__
     (list_of_json (path_elem_of_json id_to_file) js)
 
+let auto_trait_impl_of_json (js : json) : (auto_trait_impl, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `String "Always" -> Ok AutoAlways
+    | `String "IfParams" -> Ok AutoIfParams
+    | `String "Never" -> Ok AutoNever
+    | _ -> Error "")
+
+let auto_traits_of_json (js : json) : (auto_traits, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("send", send); ("sync", sync); ("unpin", unpin) ] ->
+        let* send = auto_trait_impl_of_json send in
+        let* sync = auto_trait_impl_of_json sync in
+        let* unpin = auto_trait_impl_of_json unpin in
+        Ok { send; sync; unpin }
+    | _ -> Error "")
+
+let variance_of_json (js : json) : (variance, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `String "Covariant" -> Ok Covariant
+    | `String "Invariant" -> Ok Invariant
+    | `String "Contravariant" -> Ok Contravariant
+    | `String "Bivariant" -> Ok Bivariant
+    | _ -> Error "")
+
+let variances_of_json (js : json) : (variances, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("regions", regions); ("types", types) ] ->
+        let* region_variances = list_of_json variance_of_json regions in
+        let* type_variances = list_of_json variance_of_json types in
+        Ok { region_variances; type_variances }
+    | _ -> Error "")
+
 let type_decl_of_json (id_to_file : id_to_file_map) (js : json) :
     (type_decl, string) result =
   combine_error_msgs js __FUNCTION__
@@ -637,6 +906,9 @@ let type_decl_of_json (id_to_file : id_to_file_map) (js : json) :
           ("generics", generics);
           ("preds", preds);
           ("kind", kind);
+          ("auto_traits", auto_traits);
+          ("variances", variances);
+          ("rec_group", rec_group);
         ] ->
         let* def_id = TypeDeclId.id_of_json def_id in
         let* item_meta = item_meta_of_json id_to_file item_meta in
@@ -645,17 +917,61 @@ let type_decl_of_json (id_to_file : id_to_file_map) (js : json) :
         let* generics = generic_params_of_json id_to_file generics in
         let* preds = predicates_of_json preds in
         let* kind = type_decl_kind_of_json id_to_file kind in
-        Ok { def_id; item_meta; is_local; name; generics; preds; kind }
+        let* auto_traits = auto_traits_of_json auto_traits in
+        let* variances = variances_of_json variances in
+        let* rec_group =
+          option_of_json (list_of_json TypeDeclId.id_of_json) rec_group
+        in
+        Ok
+          {
+            def_id;
+            item_meta;
+            is_local;
+            name;
+            generics;
+            preds;
+            kind;
+            auto_traits;
+            variances;
+            rec_group;
+          }
     | _ -> Error "")
 
-let var_of_json (js : json) : (var, string) result =
+let type_alias_decl_of_json (id_to_file : id_to_file_map) (js : json) :
+    (type_alias_decl, string) result =
   combine_error_msgs js __FUNCTION__
     (match js with
-    | `Assoc [ ("index", index); ("name", name); ("ty", ty) ] ->
-        let* index = VarId.id_of_json index in
-        let* name = string_option_of_json name in
-        let* var_ty = ty_of_json ty in
-        Ok { index; name; var_ty }
+    | `Assoc
+        [
+          ("def_id", def_id);
+          ("item_meta", item_meta);
+          ("name", name);
+          ("generics", generics);
+          ("ty", ty);
+        ] ->
+        let* def_id = TypeAliasId.id_of_json def_id in
+        let* item_meta = item_meta_of_json id_to_file item_meta in
+        let* name = name_of_json id_to_file name in
+        let* generics = generic_params_of_json id_to_file generics in
+        let* ty = ty_of_json ty in
+        Ok ({ def_id; item_meta; name; generics; ty } : type_alias_decl)
+    | _ -> Error "")
+
+let target_info_of_json (js : json) : (target_info, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc
+        [
+          ("target_triple", target_triple);
+          ("pointer_width", pointer_width);
+          ("is_little_endian", is_little_endian);
+          ("c_int_width", c_int_width);
+        ] ->
+        let* target_triple = string_of_json target_triple in
+        let* pointer_width = int_of_json pointer_width in
+        let* is_little_endian = bool_of_json is_little_endian in
+        let* c_int_width = int_of_json c_int_width in
+        Ok { target_triple; pointer_width; is_little_endian; c_int_width }
     | _ -> Error "")
 
 let field_proj_kind_of_json (js : json) : (field_proj_kind, string) result =
@@ -677,15 +993,60 @@ let projection_elem_of_json (js : json) : (projection_elem, string) result =
     (match js with
     | `String "Deref" -> Ok Deref
     | `String "DerefBox" -> Ok DerefBox
+    | `String "DerefRawPtr" -> Ok DerefRawPtr
     | `Assoc [ ("Field", `List [ proj_kind; field_id ]) ] ->
         let* proj_kind = field_proj_kind_of_json proj_kind in
         let* field_id = FieldId.id_of_json field_id in
         Ok (Field (proj_kind, field_id))
+    | `Assoc
+        [
+          ( "Subslice",
+            `Assoc [ ("from", from); ("to", to_); ("from_end", from_end) ] );
+        ] ->
+        let* from = int_of_json from in
+        let* to_ = int_of_json to_ in
+        let* from_end = bool_of_json from_end in
+        Ok (Subslice (from, to_, from_end))
+    | `Assoc [ ("Index", `List [ var_id; ty ]) ] ->
+        let* var_id = VarId.id_of_json var_id in
+        let* ty = ty_of_json ty in
+        Ok (Index (var_id, ty))
     | _ -> Error ("projection_elem_of_json failed on:" ^ show js))
 
 let projection_of_json (js : json) : (projection, string) result =
   combine_error_msgs js __FUNCTION__ (list_of_json projection_elem_of_json js)
 
+let var_debug_info_of_json (id_to_file : id_to_file_map) (js : json) :
+    (var_debug_info, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("name", name); ("projection", projection); ("meta", meta) ] ->
+        let* name = string_of_json name in
+        let* projection = projection_of_json projection in
+        let* meta = meta_of_json id_to_file meta in
+        Ok { name; projection; meta }
+    | _ -> Error "")
+
+let var_of_json (id_to_file : id_to_file_map) (js : json) : (var, string) result
+    =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc
+        [
+          ("index", index);
+          ("name", name);
+          ("ty", ty);
+          ("debug_info", debug_info);
+        ] ->
+        let* index = VarId.id_of_json index in
+        let* name = string_option_of_json name in
+        let* var_ty = ty_of_json ty in
+        let* debug_info =
+          list_of_json (var_debug_info_of_json id_to_file) debug_info
+        in
+        Ok { index; name; var_ty; debug_info }
+    | _ -> Error "")
+
 let place_of_json (js : json) : (place, string) result =
   combine_error_msgs js __FUNCTION__
     (match js with
@@ -724,6 +1085,11 @@ let unop_of_json (js : json) : (unop, string) result =
     | `Assoc [ ("Cast", cast_kind) ] ->
         let* cast_kind = cast_kind_of_json cast_kind in
         Ok (Cast cast_kind)
+    | `Assoc [ ("ArrayToSlice", `List [ ref_kind; ty; cg ]) ] ->
+        let* ref_kind = ref_kind_of_json ref_kind in
+        let* ty = ty_of_json ty in
+        let* cg = const_generic_of_json cg in
+        Ok (ArrayToSlice (ref_kind, ty, cg))
     | _ -> Error "")
 
 let binop_of_json (js : json) : (binop, string) result =
@@ -744,6 +1110,21 @@ let binop_of_json (js : json) : (binop, string) result =
   | `String "Mul" -> Ok Mul
   | `String "Shl" -> Ok Shl
   | `String "Shr" -> Ok Shr
+  | `String "Offset" -> Ok Offset
+  | `String "WrappingOffset" -> Ok WrappingOffset
+  | `String "PtrDiff" -> Ok PtrDiff
+  | `String "CheckedAdd" -> Ok CheckedAdd
+  | `String "CheckedSub" -> Ok CheckedSub
+  | `String "CheckedMul" -> Ok CheckedMul
+  | `String "WrappingAdd" -> Ok WrappingAdd
+  | `String "WrappingSub" -> Ok WrappingSub
+  | `String "WrappingMul" -> Ok WrappingMul
+  | `String "OverflowingAdd" -> Ok OverflowingAdd
+  | `String "OverflowingSub" -> Ok OverflowingSub
+  | `String "OverflowingMul" -> Ok OverflowingMul
+  | `String "SaturatingAdd" -> Ok SaturatingAdd
+  | `String "SaturatingSub" -> Ok SaturatingSub
+  | `String "SaturatingMul" -> Ok SaturatingMul
   | _ -> Error ("binop_of_json failed on:" ^ show js)
 
 let literal_of_json (js : json) : (literal, string) result =
@@ -843,20 +1224,13 @@ and raw_constant_expr_of_json (js : json) : (raw_constant_expr, string) result =
     | `Assoc [ ("FnPtr", fn_ptr) ] ->
         let* fn_ptr = fn_ptr_of_json fn_ptr in
         Ok (CFnPtr fn_ptr)
-    | _ -> Error "")
-
-let operand_of_json (js : json) : (operand, string) result =
-  combine_error_msgs js __FUNCTION__
-    (match js with
-    | `Assoc [ ("Copy", place) ] ->
-        let* place = place_of_json place in
-        Ok (Copy place)
-    | `Assoc [ ("Move", place) ] ->
-        let* place = place_of_json place in
-        Ok (Move place)
-    | `Assoc [ ("Const", cv) ] ->
-        let* cv = constant_expr_of_json cv in
-        Ok (Constant cv)
+    | `Assoc [ ("MutStatic", id) ] ->
+        let* id = GlobalDeclId.id_of_json id in
+        Ok (CMutStatic id)
+    | `Assoc [ ("Promoted", `List [ id; generics ]) ] ->
+        let* id = PromotedId.id_of_json id in
+        let* generics = generic_args_of_json generics in
+        Ok (CPromoted (id, generics))
     | _ -> Error "")
 
 let aggregate_kind_of_json (js : json) : (aggregate_kind, string) result =
@@ -879,7 +1253,39 @@ let aggregate_kind_of_json (js : json) : (aggregate_kind, string) result =
         Ok (AggregatedClosure (fid, generics))
     | _ -> Error "")
 
-let rvalue_of_json (js : json) : (rvalue, string) result =
+let null_op_of_json (js : json) : (null_op, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `String "SizeOf" -> Ok SizeOf
+    | `String "AlignOf" -> Ok AlignOf
+    | `Assoc [ ("OffsetOf", path) ] ->
+        let* path =
+          list_of_json
+            (pair_of_json field_proj_kind_of_json FieldId.id_of_json)
+            path
+        in
+        Ok (OffsetOf path)
+    | _ -> Error "")
+
+let rec operand_of_json (js : json) : (operand, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("Copy", place) ] ->
+        let* place = place_of_json place in
+        Ok (Copy place)
+    | `Assoc [ ("Move", place) ] ->
+        let* place = place_of_json place in
+        Ok (Move place)
+    | `Assoc [ ("Const", cv) ] ->
+        let* cv = constant_expr_of_json cv in
+        Ok (Constant cv)
+    | `Assoc [ ("Expr", `List [ rv; ty ]) ] ->
+        let* rv = rvalue_of_json rv in
+        let* ty = ty_of_json ty in
+        Ok (Expr (rv, ty))
+    | _ -> Error "")
+
+and rvalue_of_json (js : json) : (rvalue, string) result =
   combine_error_msgs js __FUNCTION__
     (match js with
     | `Assoc [ ("Use", op) ] ->
@@ -910,6 +1316,20 @@ let rvalue_of_json (js : json) : (rvalue, string) result =
         let* aggregate_kind = aggregate_kind_of_json aggregate_kind in
         let* ops = list_of_json operand_of_json ops in
         Ok (Aggregate (aggregate_kind, ops))
+    | `Assoc [ ("NullaryOp", `List [ op; ty ]) ] ->
+        let* op = null_op_of_json op in
+        let* ty = ty_of_json ty in
+        Ok (NullaryOp (op, ty))
+    | `Assoc [ ("Len", `List [ place; ty; cg ]) ] ->
+        let* place = place_of_json place in
+        let* ty = ty_of_json ty in
+        let* cg = option_of_json const_generic_of_json cg in
+        Ok (Len (place, ty, cg))
+    | `Assoc [ ("Repeat", `List [ op; ty; cg ]) ] ->
+        let* op = operand_of_json op in
+        let* ty = ty_of_json ty in
+        let* cg = const_generic_of_json cg in
+        Ok (Repeat (op, ty, cg))
     | _ -> Error "")
 
 let params_info_of_json (js : json) : (params_info, string) result =
@@ -954,13 +1374,59 @@ let closure_kind_of_json (js : json) : (closure_kind, string) result =
     | `String "FnOnce" -> Ok FnOnce
     | _ -> Error "")
 
+let capture_mode_of_json (js : json) : (capture_mode, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `String "ByValue" -> Ok CaptureByValue
+    | `Assoc [ ("ByRef", kind) ] ->
+        let* kind = ref_kind_of_json kind in
+        Ok (CaptureByRef kind)
+    | _ -> Error "")
+
+let capture_projection_of_json (js : json) : (capture_projection, string) result
+    =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `String "Deref" -> Ok CaptureDeref
+    | `Assoc [ ("Field", field_id) ] ->
+        let* field_id = FieldId.id_of_json field_id in
+        Ok (CaptureField field_id)
+    | _ -> Error "")
+
+let captured_place_of_json (js : json) : (captured_place, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("var_id", var_id); ("projection", projection); ("mode", mode) ]
+      ->
+        let* var_id = VarId.id_of_json var_id in
+        let* projection = list_of_json capture_projection_of_json projection in
+        let* mode = capture_mode_of_json mode in
+        Ok { var_id; projection; mode }
+    | _ -> Error "")
+
 let closure_info_of_json (js : json) : (closure_info, string) result =
   combine_error_msgs js __FUNCTION__
     (match js with
-    | `Assoc [ ("kind", kind); ("state", state) ] ->
+    | `Assoc
+        [
+          ("kind", kind);
+          ("state", state);
+          ("captures", captures);
+          ("is_move", is_move);
+          ("call_mut_shim", call_mut_shim);
+          ("call_once_shim", call_once_shim);
+        ] ->
         let* kind = closure_kind_of_json kind in
         let* state = list_of_json ty_of_json state in
-        Ok { kind; state }
+        let* captures = list_of_json captured_place_of_json captures in
+        let* is_move = bool_of_json is_move in
+        let* call_mut_shim =
+          option_of_json FunDeclId.id_of_json call_mut_shim
+        in
+        let* call_once_shim =
+          option_of_json FunDeclId.id_of_json call_once_shim
+        in
+        Ok { kind; state; captures; is_move; call_mut_shim; call_once_shim }
     | _ -> Error "")
 
 let fun_sig_of_json (id_to_file : id_to_file_map) (js : json) :
@@ -970,6 +1436,7 @@ let fun_sig_of_json (id_to_file : id_to_file_map) (js : json) :
     | `Assoc
         [
           ("is_unsafe", is_unsafe);
+          ("is_const", is_const);
           ("is_closure", is_closure);
           ("closure_info", closure_info);
           ("generics", generics);
@@ -977,8 +1444,11 @@ let fun_sig_of_json (id_to_file : id_to_file_map) (js : json) :
           ("parent_params_info", parent_params_info);
           ("inputs", inputs);
           ("output", output);
+          ("input_aliases", input_aliases);
+          ("output_alias", output_alias);
         ] ->
         let* is_unsafe = bool_of_json is_unsafe in
+        let* is_const = bool_of_json is_const in
         let* is_closure = bool_of_json is_closure in
         let* closure_info = option_of_json closure_info_of_json closure_info in
 
@@ -989,9 +1459,16 @@ let fun_sig_of_json (id_to_file : id_to_file_map) (js : json) :
         in
         let* inputs = list_of_json ty_of_json inputs in
         let* output = ty_of_json output in
+        let* input_aliases =
+          list_of_json (option_of_json type_alias_ref_of_json) input_aliases
+        in
+        let* output_alias =
+          option_of_json type_alias_ref_of_json output_alias
+        in
         Ok
           {
             is_unsafe;
+            is_const;
             is_closure;
             closure_info;
             generics;
@@ -999,17 +1476,225 @@ let fun_sig_of_json (id_to_file : id_to_file_map) (js : json) :
             parent_params_info;
             inputs;
             output;
+            input_aliases;
+            output_alias;
           }
     | _ -> Error "")
 
+let adjustment_of_json (js : json) : (adjustment, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `String "Deref" -> Ok AdjDeref
+    | `Assoc [ ("OverloadedDeref", kind) ] ->
+        let* kind = ref_kind_of_json kind in
+        Ok (AdjOverloadedDeref kind)
+    | `Assoc [ ("Borrow", kind) ] ->
+        let* kind = ref_kind_of_json kind in
+        Ok (AdjBorrow kind)
+    | `String "Unsize" -> Ok AdjUnsize
+    | _ -> Error "")
+
+let method_resolution_of_json (js : json) :
+    (method_resolution, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `String "Inherent" -> Ok ResInherent
+    | `Assoc [ ("TraitImpl", id) ] ->
+        let* id = TraitImplId.id_of_json id in
+        Ok (ResTraitImpl id)
+    | `Assoc [ ("DefaultMethod", id) ] ->
+        let* id = TraitImplId.id_of_json id in
+        Ok (ResDefaultMethod id)
+    | `Assoc [ ("BuiltinOrAuto", id) ] ->
+        let* id = TraitDeclId.id_of_json id in
+        Ok (ResBuiltinOrAuto id)
+    | `String "Clause" -> Ok ResClause
+    | _ -> Error "")
+
 let call_of_json (js : json) : (call, string) result =
   combine_error_msgs js __FUNCTION__
     (match js with
-    | `Assoc [ ("func", func); ("args", args); ("dest", dest) ] ->
+    | `Assoc
+        [
+          ("func", func);
+          ("args", args);
+          ("dest", dest);
+          ("receiver_adjustments", receiver_adjustments);
+          ("resolution", resolution);
+          ("is_tail", is_tail);
+          ("is_ghost", is_ghost);
+        ] ->
         let* func = fn_operand_of_json func in
         let* args = list_of_json operand_of_json args in
         let* dest = place_of_json dest in
-        Ok { func; args; dest }
+        let* receiver_adjustments =
+          list_of_json adjustment_of_json receiver_adjustments
+        in
+        let* resolution = option_of_json method_resolution_of_json resolution in
+        let* is_tail = bool_of_json is_tail in
+        let* is_ghost = bool_of_json is_ghost in
+        Ok
+          {
+            func;
+            args;
+            dest;
+            receiver_adjustments;
+            resolution;
+            is_tail;
+            is_ghost;
+          }
+    | _ -> Error "")
+
+let assert_kind_of_json (js : json) : (assert_kind, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `String "User" -> Ok AssertUser
+    | `String "Bounds" -> Ok AssertBounds
+    | `String "Overflow" -> Ok AssertOverflow
+    | `String "DivisionByZero" -> Ok AssertDivisionByZero
+    | _ -> Error "")
+
+let mem_copy_of_json (js : json) : (mem_copy, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc
+        [
+          ("src", src);
+          ("dst", dst);
+          ("count", count);
+          ("nonoverlapping", nonoverlapping);
+        ] ->
+        let* src = operand_of_json src in
+        let* dst = operand_of_json dst in
+        let* count = operand_of_json count in
+        let* nonoverlapping = bool_of_json nonoverlapping in
+        Ok { src; dst; count; nonoverlapping }
+    | _ -> Error "")
+
+let write_bytes_of_json (js : json) : (write_bytes, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("dst", dst); ("val", val_); ("count", count) ] ->
+        let* dst = operand_of_json dst in
+        let* val_ = operand_of_json val_ in
+        let* count = operand_of_json count in
+        Ok ({ dst; val_; count } : write_bytes)
+    | _ -> Error "")
+
+let intrinsic_kind_of_json (js : json) : (intrinsic_kind, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `String "Assume" -> Ok IntrinsicAssume
+    | `String "Assert" -> Ok IntrinsicAssert
+    | `String "Havoc" -> Ok IntrinsicHavoc
+    | `Assoc [ ("Custom", name) ] ->
+        let* name = string_of_json name in
+        Ok (IntrinsicCustom name)
+    | _ -> Error "")
+
+let intrinsic_of_json (js : json) : (intrinsic, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc
+        [ ("kind", kind); ("args", args); ("dest", dest); ("message", message) ]
+      ->
+        let* kind = intrinsic_kind_of_json kind in
+        let* args = list_of_json operand_of_json args in
+        let* dest = place_of_json dest in
+        let* message = option_of_json string_of_json message in
+        Ok ({ kind; args; dest; message } : intrinsic)
+    | _ -> Error "")
+
+let body_regions_of_json (js : json) : (body_regions, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("vars", vars); ("constraints", constraints) ] ->
+        let* body_region_vars = list_of_json region_var_of_json vars in
+        let* body_region_constraints =
+          list_of_json region_outlives_of_json constraints
+        in
+        Ok { body_region_vars; body_region_constraints }
+    | _ -> Error "")
+
+let location_of_json (js : json) : (location, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("block", block); ("statement_index", statement_index) ] ->
+        let* block = BlockId.id_of_json block in
+        let* statement_index = int_of_json statement_index in
+        Ok { block; statement_index }
+    | _ -> Error "")
+
+let loan_of_json (js : json) : (loan, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("index", index); ("region", region); ("location", location) ]
+      ->
+        let* loan_index = LoanId.id_of_json index in
+        let* loan_region = region_of_json region in
+        let* loan_location = location_of_json location in
+        Ok { loan_index; loan_region; loan_location }
+    | _ -> Error "")
+
+let borrowck_facts_of_json (js : json) : (borrowck_facts, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc
+        [
+          ("loans", loans);
+          ("subset_constraints", subset_constraints);
+          ("loans_invalidated_at", loans_invalidated_at);
+          ("loans_killed_at", loans_killed_at);
+          ("loans_out_of_scope_at", loans_out_of_scope_at);
+        ] ->
+        let loan_location_of_json =
+          pair_of_json LoanId.id_of_json location_of_json
+        in
+        let* loans = list_of_json loan_of_json loans in
+        let* subset_constraints =
+          list_of_json
+            (triple_of_json region_of_json region_of_json location_of_json)
+            subset_constraints
+        in
+        let* loans_invalidated_at =
+          list_of_json loan_location_of_json loans_invalidated_at
+        in
+        let* loans_killed_at =
+          list_of_json loan_location_of_json loans_killed_at
+        in
+        let* loans_out_of_scope_at =
+          list_of_json
+            (pair_of_json location_of_json (list_of_json LoanId.id_of_json))
+            loans_out_of_scope_at
+        in
+        Ok
+          {
+            loans;
+            subset_constraints;
+            loans_invalidated_at;
+            loans_killed_at;
+            loans_out_of_scope_at;
+          }
+    | _ -> Error "")
+
+let scope_of_json (id_to_file : id_to_file_map) (js : json) :
+    (scope, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc
+        [
+          ("index", index);
+          ("parent", parent);
+          ("meta", meta);
+          ("inlined", inlined);
+          ("locals", locals);
+        ] ->
+        let* index = ScopeId.id_of_json index in
+        let* parent = option_of_json ScopeId.id_of_json parent in
+        let* meta = meta_of_json id_to_file meta in
+        let* inlined = bool_of_json inlined in
+        let* locals = list_of_json VarId.id_of_json locals in
+        Ok { index; parent; meta; inlined; locals }
     | _ -> Error "")
 
 let gexpr_body_of_json (body_of_json : json -> ('body, string) result)
@@ -1022,13 +1707,33 @@ let gexpr_body_of_json (body_of_json : json -> ('body, string) result)
           ("meta", meta);
           ("arg_count", arg_count);
           ("locals", locals);
+          ("regions", regions);
+          ("borrowck_facts", borrowck_facts);
+          ("scopes", scopes);
+          ("promoted", promoted);
           ("body", body);
         ] ->
         let* meta = meta_of_json id_to_file meta in
         let* arg_count = int_of_json arg_count in
-        let* locals = list_of_json var_of_json locals in
+        let* locals = list_of_json (var_of_json id_to_file) locals in
+        let* regions = body_regions_of_json regions in
+        let* borrowck_facts =
+          option_of_json borrowck_facts_of_json borrowck_facts
+        in
+        let* scopes = list_of_json (scope_of_json id_to_file) scopes in
+        let* promoted = list_of_json GlobalDeclId.id_of_json promoted in
         let* body = body_of_json body in
-        Ok { meta; arg_count; locals; body }
+        Ok
+          {
+            meta;
+            arg_count;
+            locals;
+            regions;
+            borrowck_facts;
+            scopes;
+            promoted;
+            body;
+          }
     | _ -> Error "")
 
 let item_kind_of_json (js : json) : (item_kind, string) result =
@@ -1061,6 +1766,25 @@ let item_kind_of_json (js : json) : (item_kind, string) result =
         Ok (TraitItemProvided (trait_id, item_name))
     | _ -> Error "")
 
+(** The versions of the formats of the ULLBC and LLBC files generated by
+    Charon that we can deserialize (see [ULLBC_FORMAT_VERSION] and
+    [LLBC_FORMAT_VERSION] in [charon/src/export.rs]). They are bumped
+    independently: only the version of the format whose shape changed is
+    bumped. *)
+let ullbc_format_version : int = 48
+
+let llbc_format_version : int = 47
+
+let format_version_of_json (expected : int) (js : json) : (unit, string) result
+    =
+  combine_error_msgs js __FUNCTION__
+    (let* version = int_of_json js in
+     if version = expected then Ok ()
+     else
+       Error
+         ("Unsupported format version: " ^ string_of_int version
+        ^ " (expected " ^ string_of_int expected ^ ")"))
+
 let gfun_decl_of_json (body_of_json : json -> ('body, string) result)
     (id_to_file : id_to_file_map) (js : json) : ('body gfun_decl, string) result
     =
@@ -1072,16 +1796,22 @@ let gfun_decl_of_json (body_of_json : json -> ('body, string) result)
           ("item_meta", item_meta);
           ("is_local", is_local);
           ("name", name);
+          ("lang_item", lang_item);
           ("signature", signature);
           ("kind", kind);
+          ("rec_group", rec_group);
           ("body", body);
         ] ->
         let* def_id = FunDeclId.id_of_json def_id in
         let* item_meta = item_meta_of_json id_to_file item_meta in
         let* is_local = bool_of_json is_local in
         let* name = name_of_json id_to_file name in
+        let* lang_item = option_of_json string_of_json lang_item in
         let* signature = fun_sig_of_json id_to_file signature in
         let* kind = item_kind_of_json kind in
+        let* rec_group =
+          option_of_json (list_of_json FunDeclId.id_of_json) rec_group
+        in
         let* body =
           option_of_json (gexpr_body_of_json body_of_json id_to_file) body
         in
@@ -1091,13 +1821,44 @@ let gfun_decl_of_json (body_of_json : json -> ('body, string) result)
             item_meta;
             is_local;
             name;
+            lang_item;
             signature;
             kind;
+            rec_group;
             body;
             is_global_decl_body = false;
           }
     | _ -> Error "")
 
+let any_decl_id_of_json (js : json) : (any_decl_id, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("Type", id) ] ->
+        let* id = TypeDeclId.id_of_json id in
+        Ok (IdType id)
+    | `Assoc [ ("Fun", id) ] ->
+        let* id = FunDeclId.id_of_json id in
+        Ok (IdFun id)
+    | `Assoc [ ("Global", id) ] ->
+        let* id = GlobalDeclId.id_of_json id in
+        Ok (IdGlobal id)
+    | `Assoc [ ("TraitDecl", id) ] ->
+        let* id = TraitDeclId.id_of_json id in
+        Ok (IdTraitDecl id)
+    | `Assoc [ ("TraitImpl", id) ] ->
+        let* id = TraitImplId.id_of_json id in
+        Ok (IdTraitImpl id)
+    | _ -> Error "")
+
+let promoted_info_of_json (js : json) : (promoted_info, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("parent", parent); ("index", index) ] ->
+        let* promoted_parent = any_decl_id_of_json parent in
+        let* promoted_index = PromotedId.id_of_json index in
+        Ok { promoted_parent; promoted_index }
+    | _ -> Error "")
+
 let gglobal_decl_of_json (body_of_json : json -> ('body, string) result)
     (id_to_file : id_to_file_map) (js : json) :
     ('body gexpr_body option gglobal_decl, string) result =
@@ -1112,7 +1873,12 @@ let gglobal_decl_of_json (body_of_json : json -> ('body, string) result)
           ("generics", generics);
           ("preds", preds);
           ("ty", ty);
+          ("is_mutable", is_mutable);
+          ("is_thread_local", is_thread_local);
+          ("is_interior_mutable", is_interior_mutable);
           ("kind", kind);
+          ("promoted", promoted);
+          ("init_deps", init_deps);
           ("body", body);
         ] ->
         let* global_id = GlobalDeclId.id_of_json def_id in
@@ -1125,7 +1891,12 @@ let gglobal_decl_of_json (body_of_json : json -> ('body, string) result)
         let* body =
           option_of_json (gexpr_body_of_json body_of_json id_to_file) body
         in
+        let* is_mutable = bool_of_json is_mutable in
+        let* is_thread_local = bool_of_json is_thread_local in
+        let* is_interior_mutable = bool_of_json is_interior_mutable in
         let* kind = item_kind_of_json kind in
+        let* promoted = option_of_json promoted_info_of_json promoted in
+        let* init_deps = list_of_json GlobalDeclId.id_of_json init_deps in
         let global =
           {
             def_id = global_id;
@@ -1136,7 +1907,12 @@ let gglobal_decl_of_json (body_of_json : json -> ('body, string) result)
             generics;
             preds;
             ty;
+            is_mutable;
+            is_thread_local;
+            is_interior_mutable;
             kind;
+            promoted;
+            init_deps;
           }
         in
         Ok global
@@ -1152,6 +1928,7 @@ let trait_decl_of_json (id_to_file : id_to_file_map) (js : json) :
           ("is_local", is_local);
           ("item_meta", item_meta);
           ("name", name);
+          ("lang_item", lang_item);
           ("generics", generics);
           ("preds", preds);
           ("parent_clauses", parent_clauses);
@@ -1164,6 +1941,7 @@ let trait_decl_of_json (id_to_file : id_to_file_map) (js : json) :
         let* is_local = bool_of_json is_local in
         let* name = name_of_json id_to_file name in
         let* item_meta = item_meta_of_json id_to_file item_meta in
+        let* lang_item = option_of_json string_of_json lang_item in
         let* generics = generic_params_of_json id_to_file generics in
         let* preds = predicates_of_json preds in
         let* parent_clauses =
@@ -1179,7 +1957,8 @@ let trait_decl_of_json (id_to_file : id_to_file_map) (js : json) :
         let* types =
           list_of_json
             (pair_of_json string_of_json
-               (pair_of_json
+               (triple_of_json
+                  (generic_params_of_json id_to_file)
                   (list_of_json (trait_clause_of_json id_to_file))
                   (option_of_json ty_of_json)))
             types
@@ -1200,6 +1979,7 @@ let trait_decl_of_json (id_to_file : id_to_file_map) (js : json) :
             item_meta;
             is_local;
             name;
+            lang_item;
             generics;
             preds;
             parent_clauses;
@@ -1210,6 +1990,16 @@ let trait_decl_of_json (id_to_file : id_to_file_map) (js : json) :
           }
     | _ -> Error "")
 
+let trait_impl_kind_of_json (js : json) : (trait_impl_kind, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `String "Regular" -> Ok RegularImpl
+    | `String "Blanket" -> Ok BlanketImpl
+    | `Assoc [ ("Derived", macro_name) ] ->
+        let* macro_name = string_of_json macro_name in
+        Ok (DerivedImpl macro_name)
+    | _ -> Error "")
+
 let trait_impl_of_json (id_to_file : id_to_file_map) (js : json) :
     (trait_impl, string) result =
   combine_error_msgs js __FUNCTION__
@@ -1220,6 +2010,9 @@ let trait_impl_of_json (id_to_file : id_to_file_map) (js : json) :
           ("is_local", is_local);
           ("name", name);
           ("item_meta", item_meta);
+          ("impl_kind", impl_kind);
+          ("is_negative", is_negative);
+          ("is_const", is_const);
           ("impl_trait", impl_trait);
           ("generics", generics);
           ("preds", preds);
@@ -1228,11 +2021,15 @@ let trait_impl_of_json (id_to_file : id_to_file_map) (js : json) :
           ("types", types);
           ("required_methods", required_methods);
           ("provided_methods", provided_methods);
+          ("default_items", default_items);
         ] ->
         let* def_id = TraitImplId.id_of_json def_id in
         let* item_meta = item_meta_of_json id_to_file item_meta in
         let* is_local = bool_of_json is_local in
         let* name = name_of_json id_to_file name in
+        let* impl_kind = trait_impl_kind_of_json impl_kind in
+        let* is_negative = bool_of_json is_negative in
+        let* is_const = bool_of_json is_const in
         let* impl_trait = trait_decl_ref_of_json impl_trait in
         let* generics = generic_params_of_json id_to_file generics in
         let* preds = predicates_of_json preds in
@@ -1248,20 +2045,32 @@ let trait_impl_of_json (id_to_file : id_to_file_map) (js : json) :
         let* types =
           list_of_json
             (pair_of_json string_of_json
-               (pair_of_json (list_of_json trait_ref_of_json) ty_of_json))
+               (triple_of_json
+                  (generic_params_of_json id_to_file)
+                  (list_of_json trait_ref_of_json)
+                  ty_of_json))
             types
         in
         let methods_of_json =
           list_of_json (pair_of_json string_of_json FunDeclId.id_of_json)
         in
         let* required_methods = methods_of_json required_methods in
-        let* provided_methods = methods_of_json provided_methods in
+        let* provided_methods =
+          list_of_json
+            (pair_of_json string_of_json
+               (pair_of_json FunDeclId.id_of_json bool_of_json))
+            provided_methods
+        in
+        let* default_items = list_of_json string_of_json default_items in
         Ok
           ({
              def_id;
              item_meta;
              is_local;
              name;
+             impl_kind;
+             is_negative;
+             is_const;
              impl_trait;
              generics;
              preds;
@@ -1270,6 +2079,7 @@ let trait_impl_of_json (id_to_file : id_to_file_map) (js : json) :
              types;
              required_methods;
              provided_methods;
+             default_items;
            }
             : trait_impl)
     | _ -> Error "")
@@ -1340,6 +2150,64 @@ let declaration_group_of_json (js : json) : (declaration_group, string) result =
         Ok (TraitImplGroup id)
     | _ -> Error "")
 
+let module_decl_of_json (id_to_file : id_to_file_map) (js : json) :
+    (module_decl, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc
+        [
+          ("module_id", module_id);
+          ("item_meta", item_meta);
+          ("name", name);
+          ("parent", parent);
+          ("submodules", submodules);
+          ("items", items);
+        ] ->
+        let* module_id = ModuleId.id_of_json module_id in
+        let* item_meta = item_meta_of_json id_to_file item_meta in
+        let* name = name_of_json id_to_file name in
+        let* parent = option_of_json ModuleId.id_of_json parent in
+        let* submodules = list_of_json ModuleId.id_of_json submodules in
+        let* items = list_of_json any_decl_id_of_json items in
+        Ok { module_id; item_meta; name; parent; submodules; items }
+    | _ -> Error "")
+
+let harness_of_json (js : json) : (harness, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("fun_id", fun_id); ("attribute", attribute); ("args", args) ] ->
+        let* fun_id = FunDeclId.id_of_json fun_id in
+        let* attribute = string_of_json attribute in
+        let* args = list_of_json string_of_json args in
+        Ok ({ fun_id; attribute; args } : harness)
+    | _ -> Error "")
+
+let elaborated_clause_of_json (js : json) : (elaborated_clause, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc
+        [
+          ("trait_id", trait_id);
+          ("bound_regions", bound_regions);
+          ("trait_decl_ref", trait_decl_ref);
+        ] ->
+        let* clause_trait_id = trait_instance_id_of_json trait_id in
+        let* elab_bound_regions =
+          list_of_json region_var_of_json bound_regions
+        in
+        let* elab_trait_decl_ref = trait_decl_ref_of_json trait_decl_ref in
+        Ok { clause_trait_id; elab_bound_regions; elab_trait_decl_ref }
+    | _ -> Error "")
+
+let clause_env_of_json (js : json) : (clause_env, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("item", item); ("clauses", clauses) ] ->
+        let* env_item = any_decl_id_of_json item in
+        let* env_clauses = list_of_json elaborated_clause_of_json clauses in
+        Ok { env_item; env_clauses }
+    | _ -> Error "")
+
 let length_of_json_list (js : json) : (int, string) result =
   combine_error_msgs js __FUNCTION__
     (match js with
@@ -3,8 +3,34 @@ open Types
 open Values
 open Expressions
 open Meta
+module LoopId = Identifiers.IdGen ()
 
-type statement = {
+(** We define this type to control the name of the visitor functions
+    (see {!LlbcAst.iter_statement_base}). *)
+type loop_id = LoopId.id [@@deriving show, ord]
+
+(** Ancestor for {!LlbcAst.statement} iter visitor *)
+class ['self] iter_statement_base =
+  object (_self : 'self)
+    inherit [_] GAst.iter_statement_base
+    method visit_loop_id : 'env -> loop_id -> unit = fun _ _ -> ()
+  end
+
+(** Ancestor for {!LlbcAst.statement} map visitor *)
+class ['self] map_statement_base =
+  object (_self : 'self)
+    inherit [_] GAst.map_statement_base
+    method visit_loop_id : 'env -> loop_id -> loop_id = fun _ x -> x
+  end
+
+(** A block of statements, which are evaluated in sequence. Remark: a block is
+    never empty (we insert a {!Nop} if necessary). *)
+type block = {
+  meta : meta;  (** The block meta-data *)
+  statements : statement list;
+}
+
+and statement = {
   meta : meta;  (** The statement meta-data *)
   content : raw_statement;  (** The statement itself *)
 }
@@ -14,36 +40,68 @@ and raw_statement =
   | FakeRead of place
   | SetDiscriminant of place * variant_id
   | Drop of place
+  | StorageLive of var_id
+      (** Only present if the option [--keep-storage-statements] is set *)
+  | StorageDead of var_id
+      (** Only present if the option [--keep-storage-statements] is set *)
   | Assert of assertion
   | Call of call
+  | MemCopy of mem_copy
+  | WriteBytes of write_bytes
+  | Intrinsic of intrinsic
   | Panic
   | Return
-  | Break of int
+  | Break of int * string option
       (** Break to (outer) loop. The [int] identifies the loop to break to:
           * 0: break to the first outer loop (the current loop)
           * 1: break to the second outer loop
           * ...
+
+          The label is the one of the loop we break to (see {!loop_info}).
           *)
-  | Continue of int
-      (** Continue to (outer) loop. The loop identifier works
+  | Continue of int * string option
+      (** Continue to (outer) loop. The loop identifier and the label work
           the same way as for {!Break} *)
   | Nop
-  | Sequence of statement * statement
   | Switch of switch
-  | Loop of statement
+  | Loop of block * loop_info
+
+(** Information about a loop *)
+and loop_info = {
+  loop_id : loop_id;
+      (** The id of the loop, unique in the body (the loops are numbered in
+          the order in which they appear in the body) *)
+  label : string;
+      (** A fresh label for the loop, derived from its id (used to print
+          the loop and the statements which target it) *)
+  depth : int;
+      (** The number of loops which contain this loop (0 for the outermost
+          loops) *)
+  breaks : loop_jump list;  (** The {!Break} statements which exit this loop *)
+  continues : loop_jump list;
+      (** The {!Continue} statements which go back to the start of this loop *)
+}
+
+(** A {!Break} or a {!Continue} statement which targets a loop *)
+and loop_jump = {
+  jump_meta : meta;
+  distance : int;
+      (** The number of loops between the statement and the loop it targets
+          (this is the index given by the statement) *)
+}
 
 and switch =
-  | If of operand * statement * statement
+  | If of operand * block * block
   | SwitchInt of
-      operand * integer_type * (scalar_value list * statement) list * statement
+      operand * integer_type * (scalar_value list * block) list * block
       (** The targets for a switch over an integer are:
-          - the list [(matched values, statement to execute)]
+          - the list [(matched values, block to execute)]
             We need a list for the matched values in case we do something like this:
             [switch n { 0 | 1 => ..., _ => ... }]
-          - the "otherwise" statement
+          - the "otherwise" block
           Also note that we precise the type of the integer (uint32, int64, etc.)
           which we switch on. *)
-  | Match of place * (variant_id list * statement) list * statement option
+  | Match of place * (variant_id list * block) list * block option
       (** A match over an ADT.
 
           Similar comments as for {!SwitchInt}. Note that the "otherwise" branch
@@ -68,11 +126,11 @@ and switch =
         concrete = true;
       }]
 
-type fun_body = statement gexpr_body [@@deriving show]
-type fun_decl = statement gfun_decl [@@deriving show]
+type fun_body = block gexpr_body [@@deriving show]
+type fun_decl = block gfun_decl [@@deriving show]
 
 (* TODO: the function id should be an option *)
 type global_decl = FunDeclId.id gglobal_decl [@@deriving show]
 
 (** LLBC crate *)
-type crate = (statement, FunDeclId.id) gcrate
+type crate = (block, FunDeclId.id) gcrate
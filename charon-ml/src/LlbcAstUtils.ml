@@ -2,70 +2,89 @@ include GAstUtils
 open LlbcAst
 open Utils
 
-(** Check if a {!type:Charon.LlbcAst.statement} contains loops *)
-let statement_has_loops (st : statement) : bool =
+(** Check if a {!type:Charon.LlbcAst.block} contains loops *)
+let block_has_loops (b : block) : bool =
   let obj =
     object
       inherit [_] iter_statement
-      method! visit_Loop _ _ = raise Found
+      method! visit_Loop _ _ _ = raise Found
     end
   in
   try
-    obj#visit_statement () st;
+    obj#visit_block () b;
     false
   with Found -> true
 
 (** Check if a {!type:Charon.LlbcAst.fun_decl} contains loops *)
 let fun_decl_has_loops (fd : fun_decl) : bool =
   match fd.body with
-  | Some body -> statement_has_loops body.body
+  | Some body -> block_has_loops body.body
   | None -> false
 
-(** Create a sequence *)
-let mk_sequence (st1 : statement) (st2 : statement) : statement =
-  let meta = MetaUtils.combine_meta st1.meta st2.meta in
-  let content = Sequence (st1, st2) in
-  { meta; content }
+(** Create a block from a (non-empty) list of statements *)
+let mk_block (statements : statement list) : block =
+  match statements with
+  | [] -> raise (Failure "A block can't be empty")
+  | st :: sts ->
+      let meta =
+        List.fold_left
+          (fun meta (st : statement) -> MetaUtils.combine_meta meta st.meta)
+          st.meta sts
+      in
+      { meta; statements }
 
 (** Chain two statements into a sequence, by pushing the second statement
-    at the end of the first one (diving into sequences, switches, etc.).
+    at the end of the first one (diving into switches, etc.).
  *)
-let rec chain_statements (st1 : statement) (st2 : statement) : statement =
+let rec chain_statements (st1 : statement) (st2 : statement) : statement list =
   match st1.content with
-  | SetDiscriminant _ | Assert _ | Call _ | Assign _ | FakeRead _ | Drop _
+  | SetDiscriminant _ | Assert _ | Call _ | MemCopy _ | WriteBytes _
+  | Intrinsic _ | Assign _ | FakeRead _ | Drop _ | StorageLive _ | StorageDead _
   | Loop _ ->
       (* Simply create a sequence *)
-      mk_sequence st1 st2
-  | Nop -> (* Ignore the nop *) st2
+      [ st1; st2 ]
+  | Nop -> (* Ignore the nop *) [ st2 ]
   | Break _ | Continue _ | Panic | Return ->
-      (* Ignore the second statement, which won't be evaluated *) st1
+      (* Ignore the second statement, which won't be evaluated *) [ st1 ]
   | Switch switch ->
       (* Insert inside the switch *)
       let meta = MetaUtils.combine_meta st1.meta st2.meta in
       let content = Switch (chain_statements_in_switch switch st2) in
-      { meta; content }
-  | Sequence (st3, st4) ->
-      (* Insert at the end of the statement *)
-      mk_sequence st3 (chain_statements st4 st2)
+      [ { meta; content } ]
+
+(** Push a statement at the end of a block (diving into the last statement
+    of the block) *)
+and chain_statement_in_block (b : block) (st : statement) : block =
+  let rec chain (sts : statement list) : statement list =
+    match sts with
+    | [] -> [ st ]
+    | [ st1 ] -> chain_statements st1 st
+    | st1 :: sts -> st1 :: chain sts
+  in
+  mk_block (chain b.statements)
 
 and chain_statements_in_switch (switch : switch) (st : statement) : switch =
   match switch with
-  | If (op, st0, st1) ->
-      If (op, chain_statements st0 st, chain_statements st1 st)
+  | If (op, b0, b1) ->
+      If (op, chain_statement_in_block b0 st, chain_statement_in_block b1 st)
   | SwitchInt (op, int_ty, branches, otherwise) ->
       let branches =
-        List.map (fun (svl, br) -> (svl, chain_statements br st)) branches
+        List.map
+          (fun (svl, br) -> (svl, chain_statement_in_block br st))
+          branches
       in
-      let otherwise = chain_statements otherwise st in
+      let otherwise = chain_statement_in_block otherwise st in
       SwitchInt (op, int_ty, branches, otherwise)
   | Match (op, branches, otherwise) ->
       let branches =
-        List.map (fun (svl, br) -> (svl, chain_statements br st)) branches
+        List.map
+          (fun (svl, br) -> (svl, chain_statement_in_block br st))
+          branches
       in
       let otherwise =
         match otherwise with
         | None -> None
-        | Some otherwise -> Some (chain_statements otherwise st)
+        | Some otherwise -> Some (chain_statement_in_block otherwise st)
       in
       Match (op, branches, otherwise)
 
@@ -11,13 +11,26 @@ open LlbcAst
 let assertion_of_json (js : json) : (assertion, string) result =
   combine_error_msgs js __FUNCTION__
     (match js with
-    | `Assoc [ ("cond", cond); ("expected", expected) ] ->
+    | `Assoc [ ("cond", cond); ("expected", expected); ("kind", kind) ] ->
         let* cond = operand_of_json cond in
         let* expected = bool_of_json expected in
-        Ok { cond; expected }
+        let* kind = assert_kind_of_json kind in
+        Ok { cond; expected; kind }
     | _ -> Error "")
 
-let rec statement_of_json (id_to_file : id_to_file_map) (js : json) :
+let rec block_of_json (id_to_file : id_to_file_map) (js : json) :
+    (block, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("meta", meta); ("statements", statements) ] ->
+        let* meta = meta_of_json id_to_file meta in
+        let* statements =
+          list_of_json (statement_of_json id_to_file) statements
+        in
+        Ok { meta; statements }
+    | _ -> Error "")
+
+and statement_of_json (id_to_file : id_to_file_map) (js : json) :
     (statement, string) result =
   combine_error_msgs js __FUNCTION__
     (match js with
@@ -45,31 +58,77 @@ and raw_statement_of_json (id_to_file : id_to_file_map) (js : json) :
     | `Assoc [ ("Drop", place) ] ->
         let* place = place_of_json place in
         Ok (Drop place)
+    | `Assoc [ ("StorageLive", var_id) ] ->
+        let* var_id = VarId.id_of_json var_id in
+        Ok (StorageLive var_id)
+    | `Assoc [ ("StorageDead", var_id) ] ->
+        let* var_id = VarId.id_of_json var_id in
+        Ok (StorageDead var_id)
     | `Assoc [ ("Assert", assertion) ] ->
         let* assertion = assertion_of_json assertion in
         Ok (Assert assertion)
     | `Assoc [ ("Call", call) ] ->
         let* call = call_of_json call in
         Ok (Call call)
+    | `Assoc [ ("MemCopy", copy) ] ->
+        let* copy = mem_copy_of_json copy in
+        Ok (MemCopy copy)
+    | `Assoc [ ("WriteBytes", write) ] ->
+        let* write = write_bytes_of_json write in
+        Ok (WriteBytes write)
+    | `Assoc [ ("Intrinsic", intrinsic) ] ->
+        let* intrinsic = intrinsic_of_json intrinsic in
+        Ok (Intrinsic intrinsic)
     | `String "Panic" -> Ok Panic
     | `String "Return" -> Ok Return
-    | `Assoc [ ("Break", i) ] ->
+    | `Assoc [ ("Break", `List [ i; label ]) ] ->
         let* i = int_of_json i in
-        Ok (Break i)
-    | `Assoc [ ("Continue", i) ] ->
+        let* label = string_option_of_json label in
+        Ok (Break (i, label))
+    | `Assoc [ ("Continue", `List [ i; label ]) ] ->
         let* i = int_of_json i in
-        Ok (Continue i)
+        let* label = string_option_of_json label in
+        Ok (Continue (i, label))
     | `String "Nop" -> Ok Nop
-    | `Assoc [ ("Sequence", `List [ st1; st2 ]) ] ->
-        let* st1 = statement_of_json id_to_file st1 in
-        let* st2 = statement_of_json id_to_file st2 in
-        Ok (Sequence (st1, st2))
     | `Assoc [ ("Switch", tgt) ] ->
         let* switch = switch_of_json id_to_file tgt in
         Ok (Switch switch)
-    | `Assoc [ ("Loop", st) ] ->
-        let* st = statement_of_json id_to_file st in
-        Ok (Loop st)
+    | `Assoc [ ("Loop", `List [ b; info ]) ] ->
+        let* b = block_of_json id_to_file b in
+        let* info = loop_info_of_json id_to_file info in
+        Ok (Loop (b, info))
+    | _ -> Error "")
+
+and loop_info_of_json (id_to_file : id_to_file_map) (js : json) :
+    (loop_info, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc
+        [
+          ("id", id);
+          ("label", label);
+          ("depth", depth);
+          ("breaks", breaks);
+          ("continues", continues);
+        ] ->
+        let* loop_id = LoopId.id_of_json id in
+        let* label = string_of_json label in
+        let* depth = int_of_json depth in
+        let* breaks = list_of_json (loop_jump_of_json id_to_file) breaks in
+        let* continues =
+          list_of_json (loop_jump_of_json id_to_file) continues
+        in
+        Ok { loop_id; label; depth; breaks; continues }
+    | _ -> Error "")
+
+and loop_jump_of_json (id_to_file : id_to_file_map) (js : json) :
+    (loop_jump, string) result =
+  combine_error_msgs js __FUNCTION__
+    (match js with
+    | `Assoc [ ("meta", meta); ("distance", distance) ] ->
+        let* jump_meta = meta_of_json id_to_file meta in
+        let* distance = int_of_json distance in
+        Ok { jump_meta; distance }
     | _ -> Error "")
 
 and switch_of_json (id_to_file : id_to_file_map) (js : json) :
@@ -78,8 +137,8 @@ and switch_of_json (id_to_file : id_to_file_map) (js : json) :
     (match js with
     | `Assoc [ ("If", `List [ op; st1; st2 ]) ] ->
         let* op = operand_of_json op in
-        let* st1 = statement_of_json id_to_file st1 in
-        let* st2 = statement_of_json id_to_file st2 in
+        let* st1 = block_of_json id_to_file st1 in
+        let* st2 = block_of_json id_to_file st2 in
         Ok (If (op, st1, st2))
     | `Assoc [ ("SwitchInt", `List [ op; int_ty; tgts; otherwise ]) ] ->
         let* op = operand_of_json op in
@@ -88,10 +147,10 @@ and switch_of_json (id_to_file : id_to_file_map) (js : json) :
           list_of_json
             (pair_of_json
                (list_of_json scalar_value_of_json)
-               (statement_of_json id_to_file))
+               (block_of_json id_to_file))
             tgts
         in
-        let* otherwise = statement_of_json id_to_file otherwise in
+        let* otherwise = block_of_json id_to_file otherwise in
         Ok (SwitchInt (op, int_ty, tgts, otherwise))
     | `Assoc [ ("Match", `List [ p; tgts; otherwise ]) ] ->
         let* p = place_of_json p in
@@ -99,11 +158,11 @@ and switch_of_json (id_to_file : id_to_file_map) (js : json) :
           list_of_json
             (pair_of_json
                (list_of_json VariantId.id_of_json)
-               (statement_of_json id_to_file))
+               (block_of_json id_to_file))
             tgts
         in
         let* otherwise =
-          option_of_json (statement_of_json id_to_file) otherwise
+          option_of_json (block_of_json id_to_file) otherwise
         in
         Ok (Match (p, tgts, otherwise))
     | _ -> Error "")
@@ -111,7 +170,7 @@ and switch_of_json (id_to_file : id_to_file_map) (js : json) :
 let fun_decl_of_json (id_to_file : id_to_file_map) (js : json) :
     (fun_decl, string) result =
   combine_error_msgs js __FUNCTION__
-    (gfun_decl_of_json (statement_of_json id_to_file) id_to_file js)
+    (gfun_decl_of_json (block_of_json id_to_file) id_to_file js)
 
 (** Strict type for the number of function declarations (see {!global_to_fun_id} below) *)
 type global_id_converter = { fun_count : int } [@@deriving show]
@@ -132,7 +191,7 @@ let global_decl_of_json (id_to_file : id_to_file_map) (js : json)
   combine_error_msgs js __FUNCTION__
     ((* Deserialize the global declaration *)
      let* global =
-       gglobal_decl_of_json (statement_of_json id_to_file) id_to_file js
+       gglobal_decl_of_json (block_of_json id_to_file) id_to_file js
      in
      let {
        def_id = global_id;
@@ -143,7 +202,12 @@ let global_decl_of_json (id_to_file : id_to_file_map) (js : json)
        generics;
        preds;
        ty;
+       is_mutable;
+       is_thread_local;
+       is_interior_mutable;
        kind;
+       promoted;
+       init_deps;
      } =
        global
      in
@@ -153,6 +217,7 @@ let global_decl_of_json (id_to_file : id_to_file_map) (js : json)
        {
          (* Not sure about `is_unsafe` actually *)
          is_unsafe = false;
+         is_const = false;
          is_closure = false;
          closure_info = None;
          generics;
@@ -160,6 +225,8 @@ let global_decl_of_json (id_to_file : id_to_file_map) (js : json)
          parent_params_info = None;
          inputs = [];
          output = ty;
+         input_aliases = [];
+         output_alias = None;
        }
      in
      let global_decl : global_decl =
@@ -172,7 +239,12 @@ let global_decl_of_json (id_to_file : id_to_file_map) (js : json)
          generics;
          preds;
          ty;
+         is_mutable;
+         is_thread_local;
+         is_interior_mutable;
          kind;
+         promoted;
+         init_deps;
        }
      in
      let fun_decl : fun_decl =
@@ -181,8 +253,10 @@ let global_decl_of_json (id_to_file : id_to_file_map) (js : json)
          item_meta;
          is_local;
          name;
+         lang_item = None;
          signature;
          kind = RegularKind;
+         rec_group = None;
          body;
          is_global_decl_body = true;
        }
@@ -194,19 +268,40 @@ let crate_of_json (js : json) : (crate, string) result =
     (match js with
     | `Assoc
         [
+          ("format_version", format_version);
           ("name", name);
+          ("target_info", target_info);
+          ("features", features);
+          ("cargo_profile", cargo_profile);
           ("id_to_file", id_to_file);
+          ("macro_expansions", macro_expansions);
           ("declarations", declarations);
           ("types", types);
           ("functions", functions);
           ("globals", globals);
           ("trait_decls", trait_decls);
           ("trait_impls", trait_impls);
+          ("modules", modules);
+          ("harnesses", harnesses);
+          ("ghost_functions", ghost_functions);
+          ("clause_envs", clause_envs);
+          ("type_aliases", type_aliases);
+          ("strings", strings);
         ] ->
         (* We first deserialize the declaration groups (which simply contain ids)
          * and all the declarations *butù* the globals *)
+        let* () =
+          format_version_of_json llbc_format_version format_version
+        in
         let* name = string_of_json name in
+        let* target_info = target_info_of_json target_info in
+        let* features = list_of_json string_of_json features in
+        let* cargo_profile = string_of_json cargo_profile in
         let* id_to_file = id_to_file_of_json id_to_file in
+        let* () = string_table_of_json strings in
+        let* macro_expansions =
+          list_of_json (macro_expansion_of_json id_to_file) macro_expansions
+        in
         let* declarations =
           list_of_json declaration_group_of_json declarations
         in
@@ -246,6 +341,15 @@ let crate_of_json (js : json) : (crate, string) result =
         let* trait_impls =
           list_of_json (trait_impl_of_json id_to_file) trait_impls
         in
+        let* modules = list_of_json (module_decl_of_json id_to_file) modules in
+        let* harnesses = list_of_json harness_of_json harnesses in
+        let* ghost_functions =
+          list_of_json FunDeclId.id_of_json ghost_functions
+        in
+        let* clause_envs = list_of_json clause_env_of_json clause_envs in
+        let* type_aliases =
+          list_of_json (type_alias_decl_of_json id_to_file) type_aliases
+        in
         let trait_decls =
           TraitDeclId.Map.of_list
             (List.map (fun (d : trait_decl) -> (d.def_id, d)) trait_decls)
@@ -254,14 +358,27 @@ let crate_of_json (js : json) : (crate, string) result =
           TraitImplId.Map.of_list
             (List.map (fun (d : trait_impl) -> (d.def_id, d)) trait_impls)
         in
+        let type_aliases =
+          TypeAliasId.Map.of_list
+            (List.map (fun (d : type_alias_decl) -> (d.def_id, d)) type_aliases)
+        in
         Ok
           {
             name;
+            target_info;
+            features;
+            cargo_profile;
+            macro_expansions;
             declarations;
             type_decls;
             fun_decls;
             global_decls;
             trait_decls;
             trait_impls;
+            modules;
+            harnesses;
+            ghost_functions;
+            clause_envs;
+            type_aliases;
           }
     | _ -> Error "")
@@ -41,6 +41,22 @@ type meta = {
        *)
   generated_from_span : span option;
       (** Where the code actually comes from, in case of macro expansion/inlining/etc. *)
+  expansion : int option;
+      (** The innermost macro expansion the code comes from, if any (this is
+          an index in the [macro_expansions] table of the crate). *)
+  in_unsafe_block : bool;
+      (** [true] if the code is inside an [unsafe { ... }] block *)
+}
+[@@deriving show, ord]
+
+(** A macro expansion *)
+type macro_expansion = {
+  macro_name : string;  (** The name of the macro (for instance, `vec`). *)
+  call_site : span;  (** The span of the macro call. *)
+  def_site : span option;  (** The span of the definition of the macro. *)
+  parent : int option;
+      (** The expansion in which the macro is called, if the call itself
+          comes from a macro expansion. *)
 }
 [@@deriving show, ord]
 
@@ -50,10 +66,31 @@ type inline_attr =
   | Always  (** `#[inline(always)]` **)
 [@@deriving show, ord]
 
+(** The declared visibility of an item *)
+type visibility =
+  | Public
+  | Restricted of string list
+      (** The item is only visible in the given module (we give its path)
+          and its descendants. **)
+[@@deriving show, ord]
+
 type item_meta = {
   meta : meta;
   attributes : string list;  (** Attributes (`#[...]`). **)
+  cfg : string list;
+      (** The conditions of the `#[cfg(...)]` attributes of the item and of
+          its parents (for instance `feature = "std"`). **)
   inline : inline_attr option;
   public : bool;
+  visibility : visibility option;
+      (** The declared visibility ([None] for impl blocks and closures). **)
+  stable_id : string;
+      (** An identifier of the item which doesn't change between two
+          extractions (contrary to its numeric id), if its path doesn't
+          change. **)
+  hash : string;
+      (** A hash of the content of the item, which ignores the spans: it
+          only changes if the item (or the name of an item it refers to)
+          changes. **)
 }
 [@@deriving show, ord]
@@ -20,4 +20,8 @@ let combine_meta (m0 : meta) (m1 : meta) : meta =
       end_loc = loc_max m0.span.end_loc m1.span.end_loc;
     }
   in
-  { span; generated_from_span = None }
+  let expansion =
+    if m0.expansion = m1.expansion then m0.expansion else None
+  in
+  let in_unsafe_block = m0.in_unsafe_block && m1.in_unsafe_block in
+  { span; generated_from_span = None; expansion; in_unsafe_block }
@@ -301,7 +301,7 @@ let update_map (find_opt : 'a -> 'm -> 'b option) (add : 'a -> 'b -> 'm -> 'm)
 let update_rmap (c : match_config) (m : maps) (id : var) (v : T.region) : bool =
   (* When it comes to matching, we treat erased regions like variables. *)
   let is_var =
-    match v with RBVar _ | RErased | RFVar _ -> true | _ -> false
+    match v with RBVar _ | RErased | RFVar _ | RBody _ -> true | _ -> false
   in
   if c.map_vars_to_vars && not is_var then false
   else
@@ -371,7 +371,7 @@ let match_region (c : match_config) (m : maps) (id : region) (v : T.region) :
     bool =
   match (id, v) with
   | RStatic, RStatic -> true
-  | RVar id, (RBVar _ | RFVar _ | RErased) ->
+  | RVar id, (RBVar _ | RFVar _ | RBody _ | RErased) ->
       (* When it comes to matching, we treat erased regions like variables *)
       opt_update_rmap c m id v
   | RVar id, _ -> if c.map_vars_to_vars then false else opt_update_rmap c m id v
@@ -483,8 +483,8 @@ and match_expr_with_ty (ctx : ctx) (c : match_config) (m : maps) (pty : expr)
       && match_expr_with_ty ctx c m pty ty
       && match_ref_kind prk rk
   | EVar v, _ -> opt_update_tmap c m v ty
-  | EComp pid, TTraitType (trait_ref, type_name) ->
-      match_trait_type ctx c pid trait_ref type_name
+  | EComp pid, TTraitType (trait_ref, type_name, generics) ->
+      match_trait_type ctx c pid trait_ref type_name generics
   | EArrow (pinputs, pout), TArrow (_, inputs, out) -> (
       (* Push a region group in the map *)
       let m = maps_push_bound_regions_group m in
@@ -535,8 +535,9 @@ and match_trait_ref_item (ctx : ctx) (c : match_config) (pid : pattern)
   else raise (Failure "Unimplemented")
 
 and match_trait_type (ctx : ctx) (c : match_config) (pid : pattern)
-    (tr : T.trait_ref) (type_name : string) : bool =
-  match_trait_ref_item ctx c pid tr type_name TypesUtils.empty_generic_args
+    (tr : T.trait_ref) (type_name : string) (generics : T.generic_args) : bool
+    =
+  match_trait_ref_item ctx c pid tr type_name generics
 
 and match_generic_args (ctx : ctx) (c : match_config) (m : maps)
     (pgenerics : generic_args) (generics : T.generic_args) : bool =
@@ -584,6 +585,9 @@ and match_expr_with_const_generic (ctx : ctx) (c : match_config) (m : maps)
       (* Lookup the decl and match the name *)
       let d = T.GlobalDeclId.Map.find gid ctx.global_decls in
       match_name ctx c pat d.name
+  | EComp pid, CgTraitConst (trait_ref, const_name) ->
+      match_trait_ref_item ctx c pid trait_ref const_name
+        TypesUtils.empty_generic_args
   | _ -> false
 
 let assumed_fun_id_to_string (fid : E.assumed_fun_id) : string =
@@ -692,6 +696,10 @@ let region_to_pattern (m : constraints) (r : T.region) : region =
          in the patterns.
          Note that they should be used only in the symbolic execution *)
       RVar None
+  | RBody _ ->
+      (* Same as for the free region variables: those should only appear in
+         the bodies *)
+      RVar None
   | RStatic -> RStatic
   | RErased ->
       (* We do get there when converting function pointers (when we try to
@@ -833,10 +841,10 @@ and ty_to_pattern_aux (ctx : ctx) (c : to_pat_config) (m : constraints)
         ( region_to_pattern m r,
           ty_to_pattern_aux ctx c m ty,
           ref_kind_to_pattern rk )
-  | TTraitType (trait_ref, type_name) ->
+  | TTraitType (trait_ref, type_name, generics) ->
       let name =
         trait_ref_item_with_generics_to_pattern ctx c m trait_ref type_name
-          TypesUtils.empty_generic_args
+          generics
       in
       EComp name
   | TArrow (regions, inputs, out) ->
@@ -850,6 +858,7 @@ and ty_to_pattern_aux (ctx : ctx) (c : to_pat_config) (m : constraints)
   | TRawPtr (ty, RMut) -> ERawPtr (Mut, ty_to_pattern_aux ctx c m ty)
   | TRawPtr (ty, RShared) -> ERawPtr (Not, ty_to_pattern_aux ctx c m ty)
   | TNever -> raise (Failure "Unimplemented: Never")
+  | TFnDef _ -> raise (Failure "Unimplemented: FnDef")
 
 and trait_ref_item_with_generics_to_pattern (ctx : ctx) (c : to_pat_config)
     (m : constraints) (trait_ref : T.trait_ref) (item_name : string)
@@ -882,6 +891,12 @@ and const_generic_to_pattern (ctx : ctx) (c : to_pat_config) (m : constraints)
       let d = T.GlobalDeclId.Map.find gid ctx.global_decls in
       let n = name_to_pattern_aux ctx c d.name in
       GExpr (EComp n)
+  | CgTraitConst (trait_ref, const_name) ->
+      let n =
+        trait_ref_item_with_generics_to_pattern ctx c m trait_ref const_name
+          TypesUtils.empty_generic_args
+      in
+      GExpr (EComp n)
 
 and generic_args_to_pattern (ctx : ctx) (c : to_pat_config) (m : constraints)
     (generics : T.generic_args) : generic_args =
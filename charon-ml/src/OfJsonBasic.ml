@@ -49,6 +49,18 @@ let pair_of_json (a_of_json : json -> ('a, string) result)
       Ok (a, b)
   | _ -> Error ("pair_of_json failed on: " ^ show js)
 
+let triple_of_json (a_of_json : json -> ('a, string) result)
+    (b_of_json : json -> ('b, string) result)
+    (c_of_json : json -> ('c, string) result) (js : json) :
+    ('a * 'b * 'c, string) result =
+  match js with
+  | `List [ a; b; c ] ->
+      let* a = a_of_json a in
+      let* b = b_of_json b in
+      let* c = c_of_json c in
+      Ok (a, b, c)
+  | _ -> Error ("triple_of_json failed on: " ^ show js)
+
 let list_of_json (a_of_json : json -> ('a, string) result) (js : json) :
     ('a list, string) result =
   combine_error_msgs js "list_of_json"
@@ -73,3 +85,32 @@ let option_of_json (a_of_json : json -> ('a, string) result) (js : json) :
 let string_option_of_json (js : json) : (string option, string) result =
   combine_error_msgs js "string_option_of_json"
     (option_of_json string_of_json js)
+
+(** The magic number at the beginning of the zstd frames: the files exported
+    with [charon --compress] start with it. *)
+let zstd_magic = "\x28\xb5\x2f\xfd"
+
+(** Read a file exported by Charon. We transparently decompress the files
+    compressed with [--compress], by calling the [zstd] command-line tool. *)
+let json_of_file (path : string) : (json, string) result =
+  try
+    let ic = open_in_bin path in
+    let magic =
+      try really_input_string ic (String.length zstd_magic)
+      with End_of_file -> ""
+    in
+    close_in ic;
+    if magic <> zstd_magic then Ok (from_file path)
+    else
+      let ic =
+        Unix.open_process_args_in "zstd" [| "zstd"; "-dc"; "--"; path |]
+      in
+      let json =
+        try Ok (from_channel ~fname:path ic)
+        with Yojson.Json_error msg -> Error msg
+      in
+      match Unix.close_process_in ic with
+      | Unix.WEXITED 0 -> json
+      | _ ->
+          Error ("json_of_file: could not decompress " ^ path ^ " with zstd")
+  with Sys_error msg | Yojson.Json_error msg -> Error ("json_of_file: " ^ msg)
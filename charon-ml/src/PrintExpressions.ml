@@ -31,6 +31,7 @@ let rec projection_to_string (env : ('a, 'b) fmt_env) (s : string)
         match pe with
         | Deref -> "*(" ^ s ^ ")"
         | DerefBox -> "deref_box(" ^ s ^ ")"
+        | DerefRawPtr -> "deref_raw_ptr(" ^ s ^ ")"
         | Field (ProjTuple _, fid) -> "(" ^ s ^ ")." ^ FieldId.to_string fid
         | Field (ProjAdt (adt_id, opt_variant_id), fid) -> (
             let field_name =
@@ -45,6 +46,12 @@ let rec projection_to_string (env : ('a, 'b) fmt_env) (s : string)
                   adt_variant_to_string env adt_id variant_id
                 in
                 "(" ^ s ^ " as " ^ variant_name ^ ")." ^ field_name)
+        | Subslice (from, to_, from_end) ->
+            let to_ =
+              if from_end then "-" ^ string_of_int to_ else string_of_int to_
+            in
+            "(" ^ s ^ ")[" ^ string_of_int from ^ ".." ^ to_ ^ "]"
+        | Index (i, _) -> "(" ^ s ^ ")[" ^ var_id_to_string env i ^ "]"
       in
       projection_to_string env s p'
 
@@ -65,6 +72,9 @@ let unop_to_string (env : ('a, 'b) fmt_env) (unop : unop) : string =
   | Not -> "¬"
   | Neg -> "-"
   | Cast cast_kind -> cast_kind_to_string env cast_kind
+  | ArrayToSlice (rk, ty, cg) ->
+      "array_to_slice<" ^ ref_kind_to_string rk ^ "," ^ ty_to_string env ty
+      ^ "," ^ const_generic_to_string env cg ^ ">"
 
 let binop_to_string (binop : binop) : string =
   match binop with
@@ -84,6 +94,21 @@ let binop_to_string (binop : binop) : string =
   | Mul -> "*"
   | Shl -> "<<"
   | Shr -> ">>"
+  | Offset -> "offset"
+  | WrappingOffset -> "wrapping_offset"
+  | PtrDiff -> "ptr_diff"
+  | CheckedAdd -> "checked_add"
+  | CheckedSub -> "checked_sub"
+  | CheckedMul -> "checked_mul"
+  | WrappingAdd -> "wrapping_add"
+  | WrappingSub -> "wrapping_sub"
+  | WrappingMul -> "wrapping_mul"
+  | OverflowingAdd -> "overflowing_add"
+  | OverflowingSub -> "overflowing_sub"
+  | OverflowingMul -> "overflowing_mul"
+  | SaturatingAdd -> "saturating_add"
+  | SaturatingSub -> "saturating_sub"
+  | SaturatingMul -> "saturating_mul"
 
 let assumed_fun_id_to_string (aid : assumed_fun_id) : string =
   match aid with
@@ -123,14 +148,19 @@ let constant_expr_to_string (env : ('a, 'b) fmt_env) (cv : constant_expr) :
       let trait_ref = trait_ref_to_string env trait_ref in
       trait_ref ^ const_name
   | CFnPtr fn_ptr -> fn_ptr_to_string env fn_ptr
+  | CMutStatic id -> "&raw mut " ^ global_decl_id_to_string env id
+  | CPromoted (id, generics) ->
+      "promoted[" ^ PromotedId.to_string id ^ "]"
+      ^ generic_args_to_string env generics
 
-let operand_to_string (env : ('a, 'b) fmt_env) (op : operand) : string =
+let rec operand_to_string (env : ('a, 'b) fmt_env) (op : operand) : string =
   match op with
   | Copy p -> "copy " ^ place_to_string env p
   | Move p -> "move " ^ place_to_string env p
   | Constant cv -> constant_expr_to_string env cv
+  | Expr (rv, _) -> "(" ^ rvalue_to_string env rv ^ ")"
 
-let rvalue_to_string (env : ('a, 'b) fmt_env) (rv : rvalue) : string =
+and rvalue_to_string (env : ('a, 'b) fmt_env) (rv : rvalue) : string =
   match rv with
   | Use op -> operand_to_string env op
   | RvRef (p, bk) -> (
@@ -146,6 +176,28 @@ let rvalue_to_string (env : ('a, 'b) fmt_env) (rv : rvalue) : string =
       operand_to_string env op1 ^ " " ^ binop_to_string binop ^ " "
       ^ operand_to_string env op2
   | Discriminant (p, _) -> "discriminant(" ^ place_to_string env p ^ ")"
+  | Len (p, _, _) -> "len(" ^ place_to_string env p ^ ")"
+  | Repeat (op, _, cg) ->
+      "[" ^ operand_to_string env op ^ ";" ^ const_generic_to_string env cg
+      ^ "]"
+  | NullaryOp (op, ty) -> (
+      let ty = ty_to_string env ty in
+      match op with
+      | SizeOf -> "size_of<" ^ ty ^ ">"
+      | AlignOf -> "align_of<" ^ ty ^ ">"
+      | OffsetOf path ->
+          let path =
+            List.map
+              (fun (proj_kind, fid) ->
+                match proj_kind with
+                | ProjTuple _ -> FieldId.to_string fid
+                | ProjAdt (adt_id, opt_variant_id) -> (
+                    match adt_field_to_string env adt_id opt_variant_id fid with
+                    | Some field_name -> field_name
+                    | None -> FieldId.to_string fid))
+              path
+          in
+          "offset_of<" ^ ty ^ ">(" ^ String.concat "." path ^ ")")
   | Global (gid, generics) ->
       let generics = generic_args_to_string env generics in
       "global " ^ global_decl_id_to_string env gid ^ generics
@@ -18,7 +18,39 @@ let call_to_string (env : ('a, 'b) fmt_env) (indent : string) (call : call) :
   let args = List.map (operand_to_string env) call.args in
   let args = "(" ^ String.concat ", " args ^ ")" in
   let dest = place_to_string env call.dest in
-  indent ^ dest ^ " := move " ^ func ^ args
+  let ghost = if call.is_ghost then "ghost " else "" in
+  indent ^ dest ^ " := move " ^ ghost ^ func ^ args
+
+let mem_copy_to_string (env : ('a, 'b) fmt_env) (indent : string)
+    (copy : mem_copy) : string =
+  let name = if copy.nonoverlapping then "copy_nonoverlapping" else "copy" in
+  let args = List.map (operand_to_string env) [ copy.src; copy.dst; copy.count ] in
+  indent ^ name ^ "(" ^ String.concat ", " args ^ ")"
+
+let write_bytes_to_string (env : ('a, 'b) fmt_env) (indent : string)
+    (write : write_bytes) : string =
+  let args =
+    List.map (operand_to_string env) [ write.dst; write.val_; write.count ]
+  in
+  indent ^ "write_bytes(" ^ String.concat ", " args ^ ")"
+
+let intrinsic_to_string (env : ('a, 'b) fmt_env) (indent : string)
+    (intrinsic : intrinsic) : string =
+  let name =
+    match intrinsic.kind with
+    | IntrinsicAssume -> "assume"
+    | IntrinsicAssert -> "assert"
+    | IntrinsicHavoc -> "havoc"
+    | IntrinsicCustom name -> name
+  in
+  let args = List.map (operand_to_string env) intrinsic.args in
+  let args =
+    match intrinsic.message with
+    | None -> args
+    | Some message -> args @ [ "\"" ^ String.escaped message ^ "\"" ]
+  in
+  let dest = place_to_string env intrinsic.dest in
+  indent ^ dest ^ " := @" ^ name ^ "(" ^ String.concat ", " args ^ ")"
 
 let assertion_to_string (env : ('a, 'b) fmt_env) (indent : string)
     (a : assertion) : string =
@@ -32,7 +64,8 @@ let fun_sig_with_name_to_string (env : ('a, 'b) fmt_env) (indent : string)
     (args : var list option) (sg : fun_sig) : string =
   let ty_to_string = ty_to_string env in
 
-  (* Unsafe keyword *)
+  (* Const and unsafe keywords *)
+  let const = if sg.is_const then "const " else "" in
   let unsafe = if sg.is_unsafe then "unsafe " else "" in
 
   (* Generics and predicates *)
@@ -68,8 +101,8 @@ let fun_sig_with_name_to_string (env : ('a, 'b) fmt_env) (indent : string)
   (* Put everything together *)
   let attribute = match attribute with None -> "" | Some attr -> attr ^ " " in
   let name = match name with None -> "" | Some name -> " " ^ name in
-  indent ^ attribute ^ unsafe ^ "fn" ^ name ^ params ^ "(" ^ args ^ ")" ^ ret_ty
-  ^ clauses
+  indent ^ attribute ^ const ^ unsafe ^ "fn" ^ name ^ params ^ "(" ^ args ^ ")"
+  ^ ret_ty ^ clauses
 
 let fun_sig_to_string (env : ('a, 'b) fmt_env) (indent : string)
     (indent_incr : string) (sg : fun_sig) : string =
@@ -173,14 +206,19 @@ let trait_decl_to_string (env : ('a, 'b) fmt_env) (indent : string)
     in
     let types =
       List.map
-        (fun (name, (clauses, opt_ty)) ->
+        (fun (name, (generics, clauses, opt_ty)) ->
+          let params = own_generic_params_to_string def.generics generics in
+          let env =
+            fmt_env_update_generics_and_preds env generics empty_predicates
+          in
           let clauses = List.map (trait_clause_to_string env) clauses in
           let clauses = clauses_to_string indent1 indent_incr 0 clauses in
           match opt_ty with
-          | None -> indent1 ^ "type " ^ name ^ clauses ^ "\n"
+          | None -> indent1 ^ "type " ^ name ^ params ^ clauses ^ "\n"
           | Some ty ->
-              indent1 ^ "type " ^ name ^ " = " ^ ty_to_string ty ^ clauses
-              ^ "\n")
+              indent1 ^ "type " ^ name ^ params ^ " = "
+              ^ PrintTypes.ty_to_string env ty
+              ^ clauses ^ "\n")
         def.types
     in
     let required_methods =
@@ -259,7 +297,11 @@ let trait_impl_to_string (env : ('a, 'b) fmt_env) (indent : string)
     in
     let types =
       List.map
-        (fun (name, (trait_refs, ty)) ->
+        (fun (name, (generics, trait_refs, ty)) ->
+          let params = own_generic_params_to_string def.generics generics in
+          let env =
+            fmt_env_update_generics_and_preds env generics empty_predicates
+          in
           let trait_refs =
             if trait_refs <> [] then
               " where ["
@@ -268,14 +310,23 @@ let trait_impl_to_string (env : ('a, 'b) fmt_env) (indent : string)
               ^ "]"
             else ""
           in
-          indent1 ^ "type " ^ name ^ " = " ^ ty_to_string ty ^ trait_refs ^ "\n")
+          indent1 ^ "type " ^ name ^ params ^ " = "
+          ^ PrintTypes.ty_to_string env ty
+          ^ trait_refs ^ "\n")
         def.types
     in
     let env_method (name, f) =
       indent1 ^ "fn " ^ name ^ " : " ^ fun_decl_id_to_string env f ^ "\n"
     in
     let required_methods = List.map env_method def.required_methods in
-    let provided_methods = List.map env_method def.provided_methods in
+    let provided_methods =
+      List.map
+        (fun (name, (f, overrides)) ->
+          let default = if overrides then "" else " (default)" in
+          indent1 ^ "fn " ^ name ^ " : " ^ fun_decl_id_to_string env f ^ default
+          ^ "\n")
+        def.provided_methods
+    in
     let methods =
       if required_methods <> [] || provided_methods <> [] then
         List.concat
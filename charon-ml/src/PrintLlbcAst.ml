@@ -6,13 +6,18 @@ open PrintTypes
 open PrintValues
 open PrintExpressions
 
-type fmt_env = (statement, FunDeclId.id) PrintUtils.fmt_env
+type fmt_env = (block, FunDeclId.id) PrintUtils.fmt_env
 
 (** Pretty-printing for LLBC AST (generic functions) *)
 module Ast = struct
   include PrintGAst
 
-  let rec statement_to_string (env : fmt_env) (indent : string)
+  let rec block_to_string (env : fmt_env) (indent : string)
+      (indent_incr : string) (b : block) : string =
+    String.concat ";\n"
+      (List.map (statement_to_string env indent indent_incr) b.statements)
+
+  and statement_to_string (env : fmt_env) (indent : string)
       (indent_incr : string) (st : statement) : string =
     raw_statement_to_string env indent indent_incr st.content
 
@@ -28,24 +33,29 @@ module Ast = struct
         ^ VariantId.to_string variant_id
         ^ ")"
     | Drop p -> indent ^ "drop " ^ place_to_string env p
+    | StorageLive var_id ->
+        indent ^ "storage_live " ^ var_id_to_string env var_id
+    | StorageDead var_id ->
+        indent ^ "storage_dead " ^ var_id_to_string env var_id
     | Assert a -> assertion_to_string env indent a
     | Call call -> call_to_string env indent call
+    | MemCopy copy -> mem_copy_to_string env indent copy
+    | WriteBytes write -> write_bytes_to_string env indent write
+    | Intrinsic intrinsic -> intrinsic_to_string env indent intrinsic
     | Panic -> indent ^ "panic"
     | Return -> indent ^ "return"
-    | Break i -> indent ^ "break " ^ string_of_int i
-    | Continue i -> indent ^ "continue " ^ string_of_int i
+    | Break (_, Some label) -> indent ^ "break '" ^ label
+    | Break (i, None) -> indent ^ "break " ^ string_of_int i
+    | Continue (_, Some label) -> indent ^ "continue '" ^ label
+    | Continue (i, None) -> indent ^ "continue " ^ string_of_int i
     | Nop -> indent ^ "nop"
-    | Sequence (st1, st2) ->
-        statement_to_string env indent indent_incr st1
-        ^ ";\n"
-        ^ statement_to_string env indent indent_incr st2
     | Switch switch -> (
         match switch with
         | If (op, true_st, false_st) ->
             let op = operand_to_string env op in
             let inner_indent = indent ^ indent_incr in
             let inner_to_string =
-              statement_to_string env inner_indent indent_incr
+              block_to_string env inner_indent indent_incr
             in
             let true_st = inner_to_string true_st in
             let false_st = inner_to_string false_st in
@@ -55,8 +65,7 @@ module Ast = struct
             let op = operand_to_string env op in
             let indent1 = indent ^ indent_incr in
             let indent2 = indent1 ^ indent_incr in
-            let inner_to_string2 =
-              statement_to_string env indent2 indent_incr
+            let inner_to_string2 = block_to_string env indent2 indent_incr
             in
             let branches =
               List.map
@@ -79,8 +88,7 @@ module Ast = struct
             let p = place_to_string env p in
             let indent1 = indent ^ indent_incr in
             let indent2 = indent1 ^ indent_incr in
-            let inner_to_string2 =
-              statement_to_string env indent2 indent_incr
+            let inner_to_string2 = block_to_string env indent2 indent_incr
             in
             let branches =
               List.map
@@ -103,9 +111,10 @@ module Ast = struct
             in
             let branches = branches ^ otherwise in
             indent ^ "match (" ^ p ^ ") {\n" ^ branches ^ "\n" ^ indent ^ "}")
-    | Loop loop_st ->
-        indent ^ "loop {\n"
-        ^ statement_to_string env (indent ^ indent_incr) indent_incr loop_st
+    | Loop (loop_st, info) ->
+        let label = if info.label = "" then "" else "'" ^ info.label ^ ": " in
+        indent ^ label ^ "loop {\n"
+        ^ block_to_string env (indent ^ indent_incr) indent_incr loop_st
         ^ "\n" ^ indent ^ "}"
 
   let fun_sig_to_string (env : fmt_env) (indent : string) (indent_incr : string)
@@ -114,7 +123,7 @@ module Ast = struct
 
   let fun_decl_to_string (env : fmt_env) (indent : string)
       (indent_incr : string) (def : fun_decl) : string =
-    gfun_decl_to_string env indent indent_incr statement_to_string def
+    gfun_decl_to_string env indent indent_incr block_to_string def
 
   let global_decl_to_string (env : fmt_env) (indent : string)
       (_indent_incr : string) (def : global_decl) : string =
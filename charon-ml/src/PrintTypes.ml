@@ -89,6 +89,7 @@ let region_to_string (env : ('a, 'b) fmt_env) (r : region) : string =
   | RErased -> "'_"
   | RBVar (db, rid) -> region_var_id_to_string env db rid
   | RFVar rid -> region_id_to_pretty_string rid
+  | RBody rid -> "'?" ^ RegionVarId.to_string rid
 
 let trait_clause_id_to_string _ id = trait_clause_id_to_pretty_string id
 
@@ -134,6 +135,8 @@ and const_generic_to_string (env : ('a, 'b) fmt_env) (cg : const_generic) :
     string =
   match cg with
   | CgGlobal id -> global_decl_id_to_string env id
+  | CgTraitConst (trait_ref, const_name) ->
+      trait_ref_to_string env trait_ref ^ "::" ^ const_name
   | CgVar id -> const_generic_var_id_to_string env id
   | CgValue lit -> literal_to_string lit
 
@@ -146,9 +149,10 @@ and ty_to_string (env : ('a, 'b) fmt_env) (ty : ty) : string =
   | TVar tv -> type_var_id_to_string env tv
   | TNever -> "!"
   | TLiteral lit_ty -> literal_type_to_string lit_ty
-  | TTraitType (trait_ref, type_name) ->
+  | TTraitType (trait_ref, type_name, generics) ->
       let trait_ref = trait_ref_to_string env trait_ref in
-      trait_ref ^ "::" ^ type_name
+      let params = params_to_string env false generics in
+      trait_ref ^ "::" ^ type_name ^ params
   | TRef (r, rty, ref_kind) -> (
       match ref_kind with
       | RMut ->
@@ -165,6 +169,11 @@ and ty_to_string (env : ('a, 'b) fmt_env) (ty : ty) : string =
         "(" ^ String.concat ", " (List.map (ty_to_string env) inputs) ^ ") -> "
       in
       inputs ^ ty_to_string env output
+  | TFnDef (fid, generics) ->
+      "{"
+      ^ fun_decl_id_to_string env fid
+      ^ generic_args_to_string env generics
+      ^ "}"
 
 and params_to_string (env : ('a, 'b) fmt_env) (is_tuple : bool)
     (generics : generic_args) : string =
@@ -220,6 +229,10 @@ and trait_instance_id_to_string (env : ('a, 'b) fmt_env)
   match id with
   | Self -> "Self"
   | TraitImpl id -> trait_impl_id_to_string env id
+  | Specializable (id, specializing_impls) ->
+      let impls = List.map (trait_impl_id_to_string env) specializing_impls in
+      trait_impl_id_to_string env id
+      ^ "(specialized by: [" ^ String.concat ", " impls ^ "])"
   | BuiltinOrAuto id -> trait_decl_id_to_string env id
   | Clause id -> trait_clause_id_to_string env id
   | ParentClause (inst_id, _decl_id, clause_id) ->
@@ -281,8 +294,16 @@ let trait_clause_to_string (env : ('a, 'b) fmt_env) (clause : trait_clause) :
     string =
   let clause_id = trait_clause_id_to_string env clause.clause_id in
   let trait_id = trait_decl_id_to_string env clause.trait_id in
+  let bound_regions, env =
+    if clause.clause_bound_regions = [] then ("", env)
+    else
+      let regions = List.map region_var_to_string clause.clause_bound_regions in
+      ( "for<" ^ String.concat ", " regions ^ "> ",
+        { env with regions = clause.clause_bound_regions :: env.regions } )
+  in
   let generics = generic_args_to_string env clause.clause_generics in
-  "[" ^ clause_id ^ "]: " ^ trait_id ^ generics
+  let constness = if clause.clause_is_const then "~const " else "" in
+  "[" ^ clause_id ^ "]: " ^ bound_regions ^ constness ^ trait_id ^ generics
 
 let generic_params_to_strings (env : ('a, 'b) fmt_env)
     (generics : generic_params) : string list * string list =
@@ -296,6 +317,26 @@ let generic_params_to_strings (env : ('a, 'b) fmt_env)
   let trait_clauses = List.map (trait_clause_to_string env) trait_clauses in
   (params, trait_clauses)
 
+(** Format the parameters which are not inherited from [parent] (for instance,
+    the parameters of a generic associated type).
+    We ignore the trait clauses. *)
+let own_generic_params_to_string (parent : generic_params)
+    (generics : generic_params) : string =
+  let drop = Collections.List.drop in
+  let regions =
+    List.map region_var_to_string
+      (drop (List.length parent.regions) generics.regions)
+  in
+  let types =
+    List.map type_var_to_string (drop (List.length parent.types) generics.types)
+  in
+  let cgs =
+    List.map const_generic_var_to_string
+      (drop (List.length parent.const_generics) generics.const_generics)
+  in
+  let params = List.flatten [ regions; types; cgs ] in
+  if params = [] then "" else "<" ^ String.concat ", " params ^ ">"
+
 let field_to_string env (f : field) : string =
   match f.field_name with
   | Some field_name -> field_name ^ " : " ^ ty_to_string env f.field_ty
@@ -308,10 +349,11 @@ let variant_to_string env (v : variant) : string =
 
 let trait_type_constraint_to_string (env : ('a, 'b) fmt_env)
     (ttc : trait_type_constraint) : string =
-  let { trait_ref; type_name; ty } = ttc in
+  let { trait_ref; type_name; generics; ty } = ttc in
   let trait_ref = trait_ref_to_string env trait_ref in
+  let params = params_to_string env false generics in
   let ty = ty_to_string env ty in
-  trait_ref ^ "::" ^ type_name ^ " = " ^ ty
+  trait_ref ^ "::" ^ type_name ^ params ^ " = " ^ ty
 
 (** Helper to format "where" clauses *)
 let clauses_to_string (indent : string) (indent_incr : string)
@@ -339,7 +381,7 @@ let clauses_to_string (indent : string) (indent_incr : string)
 let predicates_and_trait_clauses_to_string (env : ('a, 'b) fmt_env)
     (indent : string) (indent_incr : string) (params_info : params_info option)
     (trait_clauses : string list) (preds : predicates) : string =
-  let { regions_outlive; types_outlive; trait_type_constraints } = preds in
+  let { regions_outlive; types_outlive; trait_type_constraints; _ } = preds in
   let region_to_string = region_to_string env in
   let regions_outlive =
     List.map
@@ -376,6 +418,20 @@ let predicates_and_trait_clauses_to_string (env : ('a, 'b) fmt_env)
       let clauses = List.append inherited local in
       clauses_to_string indent indent_incr num_inherited clauses
 
+let impl_trait_bound_to_string (env : ('a, 'b) fmt_env) (b : impl_trait_bound)
+    : string =
+  let params, _ = generic_args_to_strings env b.bound_generics in
+  let constraints =
+    List.map
+      (fun (name, ty) -> name ^ " = " ^ ty_to_string env ty)
+      b.bound_type_constraints
+  in
+  let params = List.append params constraints in
+  let params =
+    if params = [] then "" else "<" ^ String.concat ", " params ^ ">"
+  in
+  trait_decl_id_to_string env b.bound_trait_id ^ params
+
 let type_decl_to_string (env : ('a, 'b) fmt_env) (def : type_decl) : string =
   (* Locally update the generics and the predicates *)
   let env = fmt_env_update_generics_and_preds env def.generics def.preds in
@@ -397,13 +453,21 @@ let type_decl_to_string (env : ('a, 'b) fmt_env) (def : type_decl) : string =
         in
         "struct " ^ name ^ params ^ clauses ^ "\n{" ^ fields ^ "\n}"
       else "struct " ^ name ^ params ^ clauses ^ "{}"
-  | Enum variants ->
+  | Enum (variants, _, _) ->
       let variants =
         List.map (fun v -> "|  " ^ variant_to_string env v) variants
       in
       let variants = String.concat "\n" variants in
       "enum " ^ name ^ params ^ clauses ^ "\n  =\n" ^ variants
   | Opaque -> "opaque type " ^ name ^ params ^ clauses
+  | ImplTrait (bounds, hidden_ty) ->
+      let bounds = List.map (impl_trait_bound_to_string env) bounds in
+      let hidden_ty =
+        match hidden_ty with None -> "" | Some ty -> " = " ^ ty_to_string env ty
+      in
+      "opaque type " ^ name ^ params ^ clauses ^ " : impl "
+      ^ String.concat " + " bounds
+      ^ hidden_ty
 
 let adt_variant_to_string (env : ('a, 'b) fmt_env) (def_id : TypeDeclId.id)
     (variant_id : VariantId.id) : string =
@@ -414,8 +478,8 @@ let adt_variant_to_string (env : ('a, 'b) fmt_env) (def_id : TypeDeclId.id)
       ^ variant_id_to_pretty_string variant_id
   | Some def -> (
       match def.kind with
-      | Struct _ | Opaque -> raise (Failure "Unreachable")
-      | Enum variants ->
+      | Struct _ | Opaque | ImplTrait _ -> raise (Failure "Unreachable")
+      | Enum (variants, _, _) ->
           let variant = VariantId.nth variants variant_id in
           name_to_string env def.name ^ "::" ^ variant.variant_name)
 
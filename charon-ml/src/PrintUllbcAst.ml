@@ -28,9 +28,14 @@ module Ast = struct
         indent ^ "set_discriminant(" ^ place_to_string env p ^ ", "
         ^ variant_id_to_pretty_string variant_id
         ^ ")"
+    | StorageLive var_id ->
+        indent ^ "storage_live " ^ var_id_to_string env var_id
     | StorageDead var_id ->
         indent ^ "storage_dead " ^ var_id_to_string env var_id
     | Deinit p -> indent ^ "deinit " ^ place_to_string env p
+    | MemCopy copy -> mem_copy_to_string env indent copy
+    | WriteBytes write -> write_bytes_to_string env indent write
+    | Intrinsic intrinsic -> intrinsic_to_string env indent intrinsic
 
   let switch_to_string (indent : string) (tgt : switch) : string =
     match tgt with
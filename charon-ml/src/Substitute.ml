@@ -135,7 +135,7 @@ let make_region_subst (var_ids : RegionVarId.id list) (regions : region list) :
   in
   fun r ->
     match r with
-    | RStatic | RErased -> r
+    | RStatic | RErased | RBody _ -> r
     | RFVar _ -> raise (Failure "Unexpected")
     | RBVar (bdid, id) ->
         (* Only substitute the bound regions with DeBruijn index equal to 0 *)
@@ -229,10 +229,10 @@ let type_decl_get_instantiated_variants_fields_types (def : type_decl)
   let subst = make_subst_from_generics def.generics generics tr_self in
   let (variants_fields : (VariantId.id option * field list) list) =
     match def.kind with
-    | Enum variants ->
+    | Enum (variants, _, _) ->
         List.mapi (fun i v -> (Some (VariantId.of_int i), v.fields)) variants
     | Struct fields -> [ (None, fields) ]
-    | Opaque ->
+    | Opaque | ImplTrait _ ->
         raise
           (Failure
              ("Can't retrieve the variants of an opaque type: "
@@ -299,11 +299,12 @@ let fun_body_substitute_in_body (subst : subst) (body : fun_body) :
 
 let trait_type_constraint_substitute (subst : subst)
     (ttc : trait_type_constraint) : trait_type_constraint =
-  let { trait_ref; type_name; ty } = ttc in
+  let { trait_ref; type_name; generics; ty } = ttc in
   let visitor = st_substitute_visitor subst in
   let trait_ref = visitor#visit_trait_ref subst trait_ref in
+  let generics = visitor#visit_generic_args subst generics in
   let ty = visitor#visit_ty subst ty in
-  { trait_ref; type_name; ty }
+  { trait_ref; type_name; generics; ty }
 
 (** Substitute variable identifiers in a type *)
 let statement_substitute_ids (ty_subst : TypeVarId.id -> TypeVarId.id)
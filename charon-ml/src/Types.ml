@@ -3,6 +3,7 @@ open Meta
 open Values
 module TypeVarId = IdGen ()
 module TypeDeclId = IdGen ()
+module TypeAliasId = IdGen ()
 module VariantId = IdGen ()
 module FieldId = IdGen ()
 module GlobalDeclId = IdGen ()
@@ -68,20 +69,11 @@ type ('id, 'name) indexed_var = {
 }
 [@@deriving show, ord]
 
-type type_var = (TypeVarId.id, string) indexed_var [@@deriving show, ord]
-
 type region_var = (RegionVarId.id, string option) indexed_var
 [@@deriving show, ord]
 
 type literal_type = Values.literal_type [@@deriving show, ord]
 
-type const_generic_var = {
-  index : ConstGenericVarId.id;
-  name : string;
-  ty : literal_type;
-}
-[@@deriving show, ord]
-
 let all_signed_int_types = [ Isize; I8; I16; I32; I64; I128 ]
 let all_unsigned_int_types = [ Usize; U8; U16; U32; U64; U128 ]
 let all_int_types = List.append all_signed_int_types all_unsigned_int_types
@@ -153,56 +145,12 @@ class virtual ['self] mapreduce_const_generic_base =
       fun _ x -> (x, self#zero)
   end
 
-(** Remark: we have to use long names because otherwise we have collisions in
-    the functions derived for the visitors. *)
-type const_generic =
-  | CgGlobal of global_decl_id
-  | CgVar of const_generic_var_id
-  | CgValue of literal
-[@@deriving
-  show,
-    ord,
-    visitors
-      {
-        name = "iter_const_generic";
-        variety = "iter";
-        ancestors = [ "iter_const_generic_base" ];
-        nude = true (* Don't inherit {!VisitorsRuntime.iter} *);
-        concrete = true;
-        polymorphic = false;
-      },
-    visitors
-      {
-        name = "map_const_generic";
-        variety = "map";
-        ancestors = [ "map_const_generic_base" ];
-        nude = true (* Don't inherit {!VisitorsRuntime.map} *);
-        concrete = true;
-        polymorphic = false;
-      },
-    visitors
-      {
-        name = "reduce_const_generic";
-        variety = "reduce";
-        ancestors = [ "reduce_const_generic_base" ];
-        nude = true (* Don't inherit {!VisitorsRuntime.reduce} *);
-        polymorphic = false;
-      },
-    visitors
-      {
-        name = "mapreduce_const_generic";
-        variety = "mapreduce";
-        ancestors = [ "mapreduce_const_generic_base" ];
-        nude = true (* Don't inherit {!VisitorsRuntime.mapreduce} *);
-        polymorphic = false;
-      }]
-
 type trait_item_name = string [@@deriving show, ord]
 
 (** Ancestor for iter visitor for {!type: Types.ty} *)
 class ['self] iter_ty_base =
   object (self : 'self)
-    inherit [_] iter_const_generic
+    inherit [_] iter_const_generic_base
     method visit_region_db_id : 'env -> region_db_id -> unit = fun _ _ -> ()
     method visit_region_var_id : 'env -> region_var_id -> unit = fun _ _ -> ()
     method visit_region_id : 'env -> region_id -> unit = fun _ _ -> ()
@@ -229,7 +177,7 @@ class ['self] iter_ty_base =
 (** Ancestor for map visitor for {!type: Types.ty} *)
 class virtual ['self] map_ty_base =
   object (self : 'self)
-    inherit [_] map_const_generic
+    inherit [_] map_const_generic_base
 
     method visit_region_db_id : 'env -> region_db_id -> region_db_id =
       fun _ id -> id
@@ -266,6 +214,86 @@ class virtual ['self] map_ty_base =
         { index; name }
   end
 
+(** Ancestor for reduce visitor for {!type: Types.ty} *)
+class virtual ['self] reduce_ty_base =
+  object (self : 'self)
+    inherit [_] reduce_const_generic_base
+    method visit_region_db_id : 'env -> region_db_id -> 'a = fun _ _ -> self#zero
+
+    method visit_region_var_id : 'env -> region_var_id -> 'a =
+      fun _ _ -> self#zero
+
+    method visit_region_id : 'env -> region_id -> 'a = fun _ _ -> self#zero
+    method visit_type_var_id : 'env -> type_var_id -> 'a = fun _ _ -> self#zero
+    method visit_ref_kind : 'env -> ref_kind -> 'a = fun _ _ -> self#zero
+
+    method visit_trait_item_name : 'env -> trait_item_name -> 'a =
+      fun _ _ -> self#zero
+
+    method visit_fun_decl_id : 'env -> fun_decl_id -> 'a = fun _ _ -> self#zero
+
+    method visit_trait_decl_id : 'env -> trait_decl_id -> 'a =
+      fun _ _ -> self#zero
+
+    method visit_trait_impl_id : 'env -> trait_impl_id -> 'a =
+      fun _ _ -> self#zero
+
+    method visit_trait_clause_id : 'env -> trait_clause_id -> 'a =
+      fun _ _ -> self#zero
+
+    method visit_region_var : 'env -> region_var -> 'a =
+      fun env x ->
+        let { index; name } : region_var = x in
+        let index = self#visit_region_var_id env index in
+        let name = self#visit_option self#visit_string env name in
+        self#plus index name
+  end
+
+(** Ancestor for mapreduce visitor for {!type: Types.ty} *)
+class virtual ['self] mapreduce_ty_base =
+  object (self : 'self)
+    inherit [_] mapreduce_const_generic_base
+
+    method visit_region_db_id : 'env -> region_db_id -> region_db_id * 'a =
+      fun _ x -> (x, self#zero)
+
+    method visit_region_var_id : 'env -> region_var_id -> region_var_id * 'a =
+      fun _ x -> (x, self#zero)
+
+    method visit_region_id : 'env -> region_id -> region_id * 'a =
+      fun _ x -> (x, self#zero)
+
+    method visit_type_var_id : 'env -> type_var_id -> type_var_id * 'a =
+      fun _ x -> (x, self#zero)
+
+    method visit_ref_kind : 'env -> ref_kind -> ref_kind * 'a =
+      fun _ x -> (x, self#zero)
+
+    method visit_trait_item_name
+        : 'env -> trait_item_name -> trait_item_name * 'a =
+      fun _ x -> (x, self#zero)
+
+    method visit_fun_decl_id : 'env -> fun_decl_id -> fun_decl_id * 'a =
+      fun _ x -> (x, self#zero)
+
+    method visit_trait_decl_id : 'env -> trait_decl_id -> trait_decl_id * 'a =
+      fun _ x -> (x, self#zero)
+
+    method visit_trait_impl_id : 'env -> trait_impl_id -> trait_impl_id * 'a =
+      fun _ x -> (x, self#zero)
+
+    method visit_trait_clause_id
+        : 'env -> trait_clause_id -> trait_clause_id * 'a =
+      fun _ x -> (x, self#zero)
+
+    method visit_region_var : 'env -> region_var -> region_var * 'a =
+      fun env x ->
+        let { index; name } : region_var = x in
+        let index, s0 = self#visit_region_var_id env index in
+        let name, s1 = self#visit_option self#visit_string env name in
+        ({ index; name }, self#plus s0 s1)
+  end
+
 (* TODO: Str should be a literal *)
 type assumed_ty = TBox | TArray | TSlice | TStr
 
@@ -284,9 +312,14 @@ and ty =
   | TNever
   | TRef of region * ty * ref_kind
   | TRawPtr of ty * ref_kind
-  | TTraitType of trait_ref * string
-      (** The string is for the name of the associated type *)
+  | TTraitType of trait_ref * string * generic_args
+      (** The string is for the name of the associated type. The generic
+          arguments are the arguments of the associated type itself (for
+          generic associated types), not the arguments of the trait. *)
   | TArrow of region_var list * ty list * ty
+  | TFnDef of fun_decl_id * generic_args
+      (** The type of a (local) function item: the id of the function and its
+          generic arguments *)
 
 and trait_ref = {
   trait_id : trait_instance_id;
@@ -306,11 +339,26 @@ and generic_args = {
   trait_refs : trait_ref list;
 }
 
+(** Remark: we have to use long names because otherwise we have collisions in
+    the functions derived for the visitors. *)
+and const_generic =
+  | CgGlobal of global_decl_id
+  | CgTraitConst of trait_ref * trait_item_name
+      (** A trait associated constant (ex.: [T::LEN]) *)
+  | CgVar of const_generic_var_id
+  | CgValue of literal
+
 (** Identifier of a trait instance. *)
 and trait_instance_id =
   | Self
       (** Reference to *self*, in case of trait declarations/implementations *)
   | TraitImpl of trait_impl_id  (** A specific implementation *)
+  | Specializable of trait_impl_id * trait_impl_id list
+      (** A specific implementation which contains items that can be
+          specialized, together with the impls which specialize it. An impl
+          comes before the impls it specializes in the list: the first impl
+          which applies is the one used at runtime (we fall back to the first
+          impl if none applies). *)
   | BuiltinOrAuto of trait_decl_id
   | Clause of trait_clause_id
   | ParentClause of trait_instance_id * trait_decl_id * trait_clause_id
@@ -349,6 +397,10 @@ and region =
       (** Bound region. We use those in function signatures, type definitions, etc. *)
   | RFVar of region_id
       (** Free region. We use those during the symbolic execution. *)
+  | RBody of region_var_id
+      (** Region variable of a function body, introduced by the borrow checker.
+          Those only appear in the bodies, if they were extracted with the
+          [--body-regions] option. *)
   | RErased  (** Erased region *)
 [@@deriving
   show,
@@ -370,8 +422,70 @@ and region =
         nude = true (* Don't inherit {!VisitorsRuntime.map} *);
         concrete = false;
         polymorphic = false;
+      },
+    visitors
+      {
+        name = "reduce_ty";
+        variety = "reduce";
+        ancestors = [ "reduce_ty_base" ];
+        nude = true (* Don't inherit {!VisitorsRuntime.reduce} *);
+        polymorphic = false;
+      },
+    visitors
+      {
+        name = "mapreduce_ty";
+        variety = "mapreduce";
+        ancestors = [ "mapreduce_ty_base" ];
+        nude = true (* Don't inherit {!VisitorsRuntime.mapreduce} *);
+        polymorphic = false;
       }]
 
+(** The const generics contain trait references: their visitors are the
+    visitors of {!type: Types.ty}. *)
+class ['self] iter_const_generic =
+  object (_self : 'self)
+    inherit [_] iter_ty
+  end
+
+class virtual ['self] map_const_generic =
+  object (_self : 'self)
+    inherit [_] map_ty
+  end
+
+class virtual ['self] reduce_const_generic =
+  object (_self : 'self)
+    inherit [_] reduce_ty
+  end
+
+class virtual ['self] mapreduce_const_generic =
+  object (_self : 'self)
+    inherit [_] mapreduce_ty
+  end
+
+(** We define the type variables after the types, because of their default
+    values *)
+type type_var = {
+  index : TypeVarId.id;  (** Unique index identifying the variable *)
+  name : string;  (** Variable name *)
+  is_sized : bool;
+      (** [false] if the type parameter may be instantiated with a dynamically
+          sized type (it has a [?Sized] bound for instance) *)
+  default : ty option;
+      (** The default value of the parameter, like [u32] in
+          [struct Foo<T = u32>] *)
+}
+[@@deriving show, ord]
+
+type const_generic_var = {
+  index : ConstGenericVarId.id;
+  name : string;
+  ty : literal_type;
+  default : const_generic option;
+      (** The default value of the parameter, like [4] in
+          [struct Foo<const N: usize = 4>] *)
+}
+[@@deriving show, ord]
+
 (** Ancestor for iter visitor for {!type: Types.predicates} *)
 class ['self] iter_predicates_base =
   object (self : 'self)
@@ -380,16 +494,19 @@ class ['self] iter_predicates_base =
 
     method visit_type_var : 'env -> type_var -> unit =
       fun env x ->
-        let { index; name } : type_var = x in
+        let { index; name; is_sized; default } : type_var = x in
         self#visit_type_var_id env index;
-        self#visit_string env name
+        self#visit_string env name;
+        self#visit_bool env is_sized;
+        self#visit_option self#visit_ty env default
 
     method visit_const_generic_var : 'env -> const_generic_var -> unit =
       fun env x ->
-        let { index; name; ty } : const_generic_var = x in
+        let { index; name; ty; default } : const_generic_var = x in
         self#visit_const_generic_var_id env index;
         self#visit_string env name;
-        self#visit_literal_type env ty
+        self#visit_literal_type env ty;
+        self#visit_option self#visit_const_generic env default
   end
 
 (** Ancestor for map visitor for {!type: Types.ty} *)
@@ -400,19 +517,22 @@ class virtual ['self] map_predicates_base =
 
     method visit_type_var : 'env -> type_var -> type_var =
       fun env x ->
-        let { index; name } : type_var = x in
+        let { index; name; is_sized; default } : type_var = x in
         let index = self#visit_type_var_id env index in
         let name = self#visit_string env name in
-        { index; name }
+        let is_sized = self#visit_bool env is_sized in
+        let default = self#visit_option self#visit_ty env default in
+        { index; name; is_sized; default }
 
     method visit_const_generic_var
         : 'env -> const_generic_var -> const_generic_var =
       fun env x ->
-        let { index; name; ty } : const_generic_var = x in
+        let { index; name; ty; default } : const_generic_var = x in
         let index = self#visit_const_generic_var_id env index in
         let name = self#visit_string env name in
         let ty = self#visit_literal_type env ty in
-        { index; name; ty }
+        let default = self#visit_option self#visit_const_generic env default in
+        { index; name; ty; default }
   end
 
 (** Type with erased regions (this only has an informative purpose) *)
@@ -425,7 +545,13 @@ and trait_clause = {
   clause_id : trait_clause_id;
   meta : meta option;
   trait_id : trait_decl_id;
+  clause_bound_regions : region_var list;
+      (** The regions bound by the clause itself, for clauses like
+          [F : for<'a> Fn(&'a u32) -> &'a u32]. They are bound with
+          De Bruijn index 0 inside [clause_generics]. *)
   clause_generics : generic_args;
+  clause_is_const : bool;
+      (** [true] if this is a [~const] bound ([T : ~const Foo]) *)
 }
 
 and generic_params = {
@@ -458,13 +584,26 @@ and type_outlives = ty * region
 and trait_type_constraint = {
   trait_ref : trait_ref;
   type_name : trait_item_name;
+  generics : generic_args;
   ty : ty;
 }
 
+(** The transitive closure of the outlives relation between the region and
+    type parameters of a declaration, including the implied bounds.
+
+    The lists of regions are sorted, with ['static] first. *)
+and region_graph = {
+  graph_regions : region list list;
+      (** For every region parameter, the regions it outlives *)
+  graph_types : region list list;
+      (** For every type parameter, the regions it outlives *)
+}
+
 and predicates = {
   regions_outlive : region_outlives list;
   types_outlive : type_outlives list;
   trait_type_constraints : trait_type_constraint list;
+  regions_graph : region_graph;
 }
 [@@deriving
   show,
@@ -528,7 +667,29 @@ type region_var_group = (RegionVarId.id, RegionGroupId.id) g_region_group
 
 type region_var_groups = region_var_group list [@@deriving show]
 
-type field = { meta : meta; field_name : string option; field_ty : ty }
+(** A reference to a type alias, which records the alias the user wrote for a
+    type (for instance, [Result<u32>] for [std::result::Result<u32, MyError>]).
+    We only record the aliases written at the top of the types of the fields
+    and of the signatures. *)
+type type_alias_ref = {
+  alias_id : TypeAliasId.id;
+  alias_generics : generic_args;  (** The regions are erased *)
+}
+[@@deriving show]
+
+type field = {
+  meta : meta;
+  field_name : string option;
+  field_ty : ty;
+  field_is_zst : bool;
+      (** [true] if the type of the field is zero-sized, like [()] or
+          [PhantomData<T>]. This is [false] if the size of the field depends
+          on the type parameters. *)
+  field_is_phantom_data : bool;
+      (** [true] if the type of the field is [PhantomData<T>] *)
+  field_alias : type_alias_ref option;
+      (** The type alias the user wrote for the type of the field, if any *)
+}
 [@@deriving show]
 
 type variant = {
@@ -539,22 +700,110 @@ type variant = {
 
           See {!Identifiers.Id.mapi} for instance.
        *)
+  discriminant : scalar_value;
+      (** The discriminant used at runtime. Its type is the discriminant type
+          of the enumeration. *)
 }
 [@@deriving show]
 
+(** A bound on an [impl Trait] type, for instance [Iterator<Item = u32>] in
+    [impl Iterator<Item = u32>]. *)
+type impl_trait_bound = {
+  bound_trait_id : trait_decl_id;
+  bound_generics : generic_args;
+      (** The arguments of the trait. They don't contain the self type (which
+          is the opaque type itself), and the trait refs are not resolved. *)
+  bound_type_constraints : (trait_item_name * ty) list;
+      (** The constraints on the associated types (ex.: [Item = u32]) *)
+}
+[@@deriving show]
+
+(** The layout chosen by rustc for an enumeration, which tells how the
+    discriminant is represented in memory. *)
+type enum_layout =
+  | Single
+      (** No discriminant is stored, because the enumeration has at most one
+          inhabited variant *)
+  | Tagged  (** The discriminant is stored in a dedicated field (the tag) *)
+  | Niche of variant_id * field_id
+      (** The discriminant is encoded in the invalid values (the niche) of a
+          field of one of the variants, the untagged variant: the other
+          variants are represented by invalid values of this field. For
+          instance, the [None] variant of [Option<&T>] is represented by the
+          null pointer.
+
+          We give the untagged variant, and its field which contains the
+          niche (the niche may be nested in this field).
+       *)
+[@@deriving show]
+
 type type_decl_kind =
   | Struct of field list
       (** The fields of the structure can be indexed with {!FieldId.id}.
 
           See {!Identifiers.Id.mapi} for instance.
        *)
-  | Enum of variant list
+  | Enum of variant list * integer_type * enum_layout option
       (** The variants of the enumeration can be indexed with {!VariantId.id}.
 
           See {!Identifiers.Id.mapi} for instance.
+
+          The integer type is the type of the discriminant: [isize], unless the
+          enumeration has a [#[repr(...)]] attribute giving another type. The
+          layout is [None] if it depends on the type parameters.
        *)
   | Opaque
       (** An opaque type: either a local type marked as opaque, or an external type *)
+  | ImplTrait of impl_trait_bound list * ty option
+      (** An opaque type introduced by an [impl Trait] in return position.
+          We give its bounds, and its hidden type if we know it. *)
+[@@deriving show]
+
+(** Whether a type implements an auto trait *)
+type auto_trait_impl =
+  | AutoAlways  (** The type implements the trait *)
+  | AutoIfParams
+      (** The type implements the trait if its type parameters implement it
+          (for instance, [Vec<T>] is [Send] if [T] is [Send]) *)
+  | AutoNever  (** The type doesn't implement the trait *)
+[@@deriving show]
+
+(** The auto traits implemented by a type declaration (this takes into account
+    the negative impls) *)
+type auto_traits = {
+  send : auto_trait_impl;
+  sync : auto_trait_impl;
+  unpin : auto_trait_impl;
+}
+[@@deriving show]
+
+(** The variance of a parameter of a type declaration *)
+type variance =
+  | Covariant
+  | Invariant
+  | Contravariant
+  | Bivariant  (** The parameter isn't used by the type *)
+[@@deriving show]
+
+(** The variances of the parameters of a type declaration, as computed by
+    rustc. Those don't depend on the fields we keep (see the
+    [--remove-phantom-data] option). *)
+type variances = {
+  region_variances : variance list;
+  type_variances : variance list;
+}
+[@@deriving show]
+
+(** A type alias, like [type Result<T> = std::result::Result<T, MyError>].
+    We only translate the local type aliases, with the [--type-aliases]
+    option. *)
+type type_alias_decl = {
+  def_id : TypeAliasId.id;
+  item_meta : item_meta;
+  name : name;
+  generics : generic_params;
+  ty : ty;  (** The type the alias stands for *)
+}
 [@@deriving show]
 
 type type_decl = {
@@ -565,5 +814,19 @@ type type_decl = {
   generics : generic_params;
   preds : predicates;
   kind : type_decl_kind;
+  auto_traits : auto_traits;
+  variances : variances;
+  rec_group : TypeDeclId.id list option;
+      (** If the type is (mutually) recursive, the types of its recursive
+          group, including itself *)
+}
+[@@deriving show]
+
+(** Information about the target the crate was compiled for *)
+type target_info = {
+  target_triple : string;
+  pointer_width : int;  (** The size of the pointers, in bits *)
+  is_little_endian : bool;
+  c_int_width : int;  (** The size of the C [int] type, in bits *)
 }
 [@@deriving show]
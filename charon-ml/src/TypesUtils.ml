@@ -21,7 +21,7 @@ let as_ident (e : path_elem) : string =
   match e with PeIdent (s, _) -> s | _ -> raise (Failure "Unexpected")
 
 let type_decl_is_opaque (d : type_decl) : bool =
-  match d.kind with Struct _ | Enum _ -> false | Opaque -> true
+  match d.kind with Struct _ | Enum _ -> false | Opaque | ImplTrait _ -> true
 
 (** Retrieve the list of fields for the given variant of a {!Charon.Types.type_decl}.
 
@@ -30,7 +30,8 @@ let type_decl_is_opaque (d : type_decl) : bool =
 let type_decl_get_fields (def : type_decl)
     (opt_variant_id : VariantId.id option) : field list =
   match (def.kind, opt_variant_id) with
-  | Enum variants, Some variant_id -> (VariantId.nth variants variant_id).fields
+  | Enum (variants, _, _), Some variant_id ->
+      (VariantId.nth variants variant_id).fields
   | Struct fields, None -> fields
   | _ ->
       let opt_variant_id =
@@ -44,7 +45,9 @@ let type_decl_get_fields (def : type_decl)
           ^ opt_variant_id))
 
 let type_decl_is_enum (def : type_decl) : bool =
-  match def.kind with Struct _ -> false | Enum _ -> true | Opaque -> false
+  match def.kind with
+  | Struct _ | Opaque | ImplTrait _ -> false
+  | Enum _ -> true
 
 (** Return [true] if a {!type:Charon.Types.ty} is actually [unit] *)
 let ty_is_unit (ty : ty) : bool =
@@ -99,8 +102,15 @@ let mk_generic_args_from_types (types : ty list) : generic_args =
 let empty_generic_params : generic_params =
   { regions = []; types = []; const_generics = []; trait_clauses = [] }
 
+let empty_region_graph : region_graph = { graph_regions = []; graph_types = [] }
+
 let empty_predicates : predicates =
-  { regions_outlive = []; types_outlive = []; trait_type_constraints = [] }
+  {
+    regions_outlive = [];
+    types_outlive = [];
+    trait_type_constraints = [];
+    regions_graph = empty_region_graph;
+  }
 
 let merge_generic_args (g1 : generic_args) (g2 : generic_args) : generic_args =
   let { regions = r1; types = tys1; const_generics = cgs1; trait_refs = tr1 } =
@@ -155,6 +165,8 @@ let region_in_set (r : region) (rset : RegionId.Set.t) : bool =
       raise (Failure "region_in_set shouldn't be called on erased regions")
   | RBVar _ ->
       raise (Failure "region_in_set shouldn't be called on bound regions")
+  | RBody _ ->
+      raise (Failure "region_in_set shouldn't be called on body regions")
   | RFVar id -> RegionId.Set.mem id rset
 
 (** Return the set of regions in an type - TODO: add static?
@@ -171,6 +183,8 @@ let ty_regions (ty : ty) : RegionId.Set.t =
         raise (Failure "ty_regions shouldn't be called on erased regions")
     | RBVar _ ->
         raise (Failure "region_in_set shouldn't be called on bound regions")
+    | RBody _ ->
+        raise (Failure "ty_regions shouldn't be called on body regions")
     | RFVar rid -> s := RegionId.Set.add rid !s
   in
   let obj =
@@ -4,7 +4,6 @@ open Values
 open Expressions
 open Meta
 open Identifiers
-module BlockId = IdGen ()
 
 (** We define this type to control the name of the visitor functions
     (see e.g., {!UllbcAst.iter_statement_base} and {!switch}).
@@ -34,8 +33,13 @@ and raw_statement =
   | Assign of place * rvalue
   | FakeRead of place
   | SetDiscriminant of place * variant_id
+  | StorageLive of var_id
+      (** Only present if the option [--keep-storage-statements] is set *)
   | StorageDead of var_id
   | Deinit of place
+  | MemCopy of mem_copy
+  | WriteBytes of write_bytes
+  | Intrinsic of intrinsic
 [@@deriving
   show,
     visitors
@@ -33,12 +33,24 @@ and raw_statement_of_json (js : json) : (raw_statement, string) result =
         let* place = place_of_json place in
         let* variant_id = VariantId.id_of_json variant_id in
         Ok (SetDiscriminant (place, variant_id))
+    | `Assoc [ ("StorageLive", var_id) ] ->
+        let* var_id = VarId.id_of_json var_id in
+        Ok (StorageLive var_id)
     | `Assoc [ ("StorageDead", var_id) ] ->
         let* var_id = VarId.id_of_json var_id in
         Ok (StorageDead var_id)
     | `Assoc [ ("Deinit", place) ] ->
         let* place = place_of_json place in
         Ok (Deinit place)
+    | `Assoc [ ("MemCopy", copy) ] ->
+        let* copy = mem_copy_of_json copy in
+        Ok (MemCopy copy)
+    | `Assoc [ ("WriteBytes", write) ] ->
+        let* write = write_bytes_of_json write in
+        Ok (WriteBytes write)
+    | `Assoc [ ("Intrinsic", intrinsic) ] ->
+        let* intrinsic = intrinsic_of_json intrinsic in
+        Ok (Intrinsic intrinsic)
     | _ -> Error "")
 
 let switch_of_json (js : json) : (switch, string) result =
@@ -102,12 +114,18 @@ and raw_terminator_of_json (js : json) : (raw_terminator, string) result =
         [
           ( "Assert",
             `Assoc
-              [ ("cond", cond); ("expected", expected); ("target", target) ] );
+              [
+                ("cond", cond);
+                ("expected", expected);
+                ("kind", kind);
+                ("target", target);
+              ] );
         ] ->
         let* cond = operand_of_json cond in
         let* expected = bool_of_json expected in
+        let* kind = assert_kind_of_json kind in
         let* target = BlockId.id_of_json target in
-        Ok (Assert ({ cond; expected }, target))
+        Ok (Assert ({ cond; expected; kind }, target))
     | _ -> Error "")
 
 let block_of_json (id_to_file : id_to_file_map) (js : json) :
@@ -142,17 +160,38 @@ let crate_of_json (js : json) : (crate, string) result =
     (match js with
     | `Assoc
         [
+          ("format_version", format_version);
           ("name", name);
+          ("target_info", target_info);
+          ("features", features);
+          ("cargo_profile", cargo_profile);
           ("id_to_file", id_to_file);
+          ("macro_expansions", macro_expansions);
           ("declarations", declarations);
           ("types", types);
           ("functions", functions);
           ("globals", globals);
           ("trait_decls", trait_decls);
           ("trait_impls", trait_impls);
+          ("modules", modules);
+          ("harnesses", harnesses);
+          ("ghost_functions", ghost_functions);
+          ("clause_envs", clause_envs);
+          ("type_aliases", type_aliases);
+          ("strings", strings);
         ] ->
+        let* () =
+          format_version_of_json ullbc_format_version format_version
+        in
         let* name = string_of_json name in
+        let* target_info = target_info_of_json target_info in
+        let* features = list_of_json string_of_json features in
+        let* cargo_profile = string_of_json cargo_profile in
         let* id_to_file = id_to_file_of_json id_to_file in
+        let* () = string_table_of_json strings in
+        let* macro_expansions =
+          list_of_json (macro_expansion_of_json id_to_file) macro_expansions
+        in
         let* declarations =
           list_of_json declaration_group_of_json declarations
         in
@@ -165,6 +204,15 @@ let crate_of_json (js : json) : (crate, string) result =
         let* trait_impls =
           list_of_json (trait_impl_of_json id_to_file) trait_impls
         in
+        let* modules = list_of_json (module_decl_of_json id_to_file) modules in
+        let* harnesses = list_of_json harness_of_json harnesses in
+        let* ghost_functions =
+          list_of_json FunDeclId.id_of_json ghost_functions
+        in
+        let* clause_envs = list_of_json clause_env_of_json clause_envs in
+        let* type_aliases =
+          list_of_json (type_alias_decl_of_json id_to_file) type_aliases
+        in
         let type_decls =
           TypeDeclId.Map.of_list
             (List.map (fun (d : type_decl) -> (d.def_id, d)) types)
@@ -185,14 +233,27 @@ let crate_of_json (js : json) : (crate, string) result =
           TraitImplId.Map.of_list
             (List.map (fun (d : trait_impl) -> (d.def_id, d)) trait_impls)
         in
+        let type_aliases =
+          TypeAliasId.Map.of_list
+            (List.map (fun (d : type_alias_decl) -> (d.def_id, d)) type_aliases)
+        in
         Ok
           {
             name;
+            target_info;
+            features;
+            cargo_profile;
+            macro_expansions;
             declarations;
             type_decls;
             fun_decls;
             global_decls;
             trait_decls;
             trait_impls;
+            modules;
+            harnesses;
+            ghost_functions;
+            clause_envs;
+            type_aliases;
           }
     | _ -> Error "")
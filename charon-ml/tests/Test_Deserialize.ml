@@ -12,13 +12,33 @@ let run_tests (folder : string) : unit =
     |> List.filter (fun file -> Filename.check_suffix file suffix)
   in
 
-  (* List the LLBC files.
+  (* List the ULLBC and LLBC files (we also read the compressed files) *)
+  let ullbc_files =
+    get_files_with_suffix folder ".ullbc"
+    @ get_files_with_suffix folder ".ullbc.zst"
+  in
+  let llbc_files =
+    get_files_with_suffix folder ".llbc"
+    @ get_files_with_suffix folder ".llbc.zst"
+  in
 
-     Remark: we do not deserialize the ULLBC files anymore: we rely on some
-     micro-passes which are only performed on LLBC to remove some unwanted
-     variants/types.
-  *)
-  let llbc_files = get_files_with_suffix folder ".llbc" in
+  (* Deserialize ULLBC *)
+  let () =
+    List.iter
+      (fun file ->
+        log#ldebug (lazy ("Deserializing ULLBC file: " ^ file));
+        (* Load the module *)
+        let json = OfJsonBasic.json_of_file (folder ^ "/" ^ file) in
+        match Result.bind json UllbcOfJson.crate_of_json with
+        | Error s ->
+            log#error "Error when deserializing file %s: %s\n" file s;
+            exit 1
+        | Ok m ->
+            log#linfo (lazy ("Deserialized: " ^ file));
+            log#ldebug
+              (lazy ("\n" ^ PrintUllbcAst.Crate.crate_to_string m ^ "\n")))
+      ullbc_files
+  in
 
   (* Deserialize LLBC *)
   let () =
@@ -26,8 +46,8 @@ let run_tests (folder : string) : unit =
       (fun file ->
         log#ldebug (lazy ("Deserializing LLBC file: " ^ file));
         (* Load the module *)
-        let json = Yojson.Basic.from_file (folder ^ "/" ^ file) in
-        match LlbcOfJson.crate_of_json json with
+        let json = OfJsonBasic.json_of_file (folder ^ "/" ^ file) in
+        match Result.bind json LlbcOfJson.crate_of_json with
         | Error s ->
             log#error "Error when deserializing file %s: %s\n" file s;
             exit 1
@@ -0,0 +1,48 @@
+open Charon
+open Types
+open Logging
+
+let log = main_log
+let _ = log#linfo (lazy "Visitor tests: starting")
+
+(* The const generics may contain trait references *)
+let const_generic_tests () =
+  let trait_decl_id = TraitDeclId.of_int 1 in
+  let trait_ref : trait_ref =
+    {
+      trait_id = Clause (TraitClauseId.of_int 0);
+      generics = TypesUtils.empty_generic_args;
+      trait_decl_ref =
+        { trait_decl_id; decl_generics = TypesUtils.empty_generic_args };
+    }
+  in
+  let cg = CgTraitConst (trait_ref, "LEN") in
+
+  (* Collect the trait declarations *)
+  let reduce_visitor =
+    object
+      inherit [_] reduce_const_generic
+      method zero = TraitDeclId.Set.empty
+      method plus = TraitDeclId.Set.union
+      method! visit_trait_decl_id _ id = TraitDeclId.Set.singleton id
+    end
+  in
+  let ids = reduce_visitor#visit_const_generic () cg in
+  assert (TraitDeclId.Set.elements ids = [ trait_decl_id ]);
+
+  (* Rename the trait items while counting them *)
+  let mapreduce_visitor =
+    object
+      inherit [_] mapreduce_const_generic
+      method zero = 0
+      method plus = ( + )
+      method! visit_trait_item_name _ name = (name ^ "_", 1)
+    end
+  in
+  let cg, count = mapreduce_visitor#visit_const_generic () cg in
+  assert (count = 1);
+  assert (cg = CgTraitConst (trait_ref, "LEN_"))
+
+let run_tests () =
+  const_generic_tests ();
+  log#linfo (lazy "Visitor tests: success")
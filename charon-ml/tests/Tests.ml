@@ -14,3 +14,4 @@ let () =
 (* Call the tests *)
 let () = Test_Deserialize.run_tests "../../../tests/serialized"
 let () = Test_NameMatcher.run_tests ()
+let () = Test_Visitors.run_tests ()
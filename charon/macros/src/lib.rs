@@ -1030,10 +1030,14 @@ fn generic_expr_to_mut(e: &mut Expr) {
             e.args.iter_mut().for_each(|a| generic_expr_to_mut(a));
 
             // IMPORTANT: check the name of the method: if it is `iter` change
-            // to `iter_mut`
+            // to `iter_mut` (and similarly for `with_kind` and `with_data`)
             let id = e.method.to_string();
             if id == "iter" {
                 e.method = Ident::new("iter_mut", Span::call_site().into());
+            } else if id == "with_kind" {
+                e.method = Ident::new("with_kind_mut", Span::call_site().into());
+            } else if id == "with_data" {
+                e.method = Ident::new("with_data_mut", Span::call_site().into());
             }
         }
         Expr::Paren(e) => {
@@ -5,20 +5,22 @@
 //! we ignore the disambiguators (see [crate::names] and [crate::names_utils]).
 // TODO: rename to "primitive"
 
+use crate::cli_options::CliOpts;
+use crate::expressions::BinOp;
+use crate::gast::IntrinsicKind;
 use crate::names::*;
 use crate::types::*;
 use crate::ullbc_ast;
 use macros::EnumIsA;
 
-/// Ignore the builtin/auto traits like [core::marker::Sized] or [core::marker::Sync].
-pub const IGNORE_BUILTIN_MARKER_TRAITS: bool = true;
-
-// Ignored traits (includes marker traits, and others)
+// Traits ignored by default (includes marker traits, and others).
+// This can be configured with the `--ignore-trait`, `--keep-trait` and
+// `--keep-all-trait-clauses` options.
 pub static MARKER_SIZED_NAME: [&str; 3] = ["core", "marker", "Sized"];
 pub static MARKER_TUPLE_NAME: [&str; 3] = ["core", "marker", "Tuple"];
-pub static SYNC_NAME: [&str; 3] = ["core", "marker", "SYNC"];
-pub static SEND_NAME: [&str; 3] = ["core", "marker", "SEND"];
-pub static UNPIN_NAME: [&str; 3] = ["core", "marker", "UNPIN"];
+pub static SYNC_NAME: [&str; 3] = ["core", "marker", "Sync"];
+pub static SEND_NAME: [&str; 3] = ["core", "marker", "Send"];
+pub static UNPIN_NAME: [&str; 3] = ["core", "marker", "Unpin"];
 pub static ALLOC_ALLOCATOR: [&str; 3] = ["core", "alloc", "Allocator"];
 pub static IGNORED_TRAITS_NAMES: [&[&str]; 6] = [
     &MARKER_SIZED_NAME,
@@ -29,6 +31,15 @@ pub static IGNORED_TRAITS_NAMES: [&[&str]; 6] = [
     &ALLOC_ALLOCATOR,
 ];
 
+// The attributes of the verification entry points (see [crate::gast::Harness]). This can be
+// extended with the `--harness-attribute` option.
+pub static HARNESS_ATTRIBUTES: [&str; 4] = [
+    "test",
+    "kani::proof",
+    "kanitool::proof",
+    "kani::proof_for_contract",
+];
+
 // Assumed types
 pub static BOX_NAME: [&str; 3] = ["alloc", "boxed", "Box"];
 
@@ -47,6 +58,46 @@ pub static BOX_FREE_NAME: [&str; 3] = ["alloc", "alloc", "box_free"];
 pub static PTR_UNIQUE_NAME: [&str; 3] = ["core", "ptr", "Unique"];
 pub static PTR_NON_NULL_NAME: [&str; 3] = ["core", "ptr", "NonNull"];
 
+// Pointer arithmetic: the intrinsics, and the methods of `*const T` and `*mut T`
+// (we ignore the `impl` path elements when comparing the names).
+pub static PTR_EQ_NAME: [&str; 3] = ["core", "ptr", "eq"];
+pub static INTRINSICS_OFFSET_NAME: [&str; 3] = ["core", "intrinsics", "offset"];
+pub static INTRINSICS_ARITH_OFFSET_NAME: [&str; 3] = ["core", "intrinsics", "arith_offset"];
+pub static INTRINSICS_PTR_OFFSET_FROM_NAME: [&str; 3] = ["core", "intrinsics", "ptr_offset_from"];
+pub static PTR_MODULES: [&str; 2] = ["const_ptr", "mut_ptr"];
+
+// Copies of raw memory: the intrinsics (`ptr::copy` and the others are re-exports of
+// those), and the methods of `*const T` and `*mut T`.
+pub static INTRINSICS_COPY_NAME: [&str; 3] = ["core", "intrinsics", "copy"];
+pub static INTRINSICS_COPY_NONOVERLAPPING_NAME: [&str; 3] =
+    ["core", "intrinsics", "copy_nonoverlapping"];
+pub static INTRINSICS_WRITE_BYTES_NAME: [&str; 3] = ["core", "intrinsics", "write_bytes"];
+
+// The built-ins of the verifiers, in the verification frontends and in the `charon` facade
+// crate. This can be extended with the `--verifier-assume`, `--verifier-assert` and
+// `--verifier-nondet` options.
+pub static VERIFIER_ASSUME_NAMES: [&str; 3] = ["kani::assume", "prusti::assume", "charon::assume"];
+pub static VERIFIER_ASSERT_NAMES: [&str; 3] = ["kani::assert", "prusti::assert", "charon::assert"];
+pub static VERIFIER_NONDET_NAMES: [&str; 2] = ["kani::any", "charon::nondet"];
+
+// Lang items. When possible, we identify the assumed items with their lang items
+// rather than with their names: the names depend on the crates which define the
+// items, which are not the same for the crates which depend on `std` and for the
+// `#![no_std]` crates (for instance, `begin_panic` is only defined in `std`). We
+// still compare the names for the items which are not lang items.
+pub static BOX_LANG_ITEM: &str = "owned_box";
+pub static BOX_FREE_LANG_ITEM: &str = "box_free";
+/// The functions which panic, like `core::panicking::panic_fmt` (which the
+/// `panic!` macros of `core` and `std` call), or `core::panicking::panic_bounds_check`.
+pub static PANIC_LANG_ITEMS: [&str; 6] = [
+    "panic",
+    "panic_fmt",
+    "panic_display",
+    "panic_bounds_check",
+    "panic_nounwind",
+    "begin_panic",
+];
+
 /// We redefine identifiers for assumed functions here, instead of reusing the
 /// identifiers from [ullbc_ast], because:
 /// - some of the functions (the panic functions) will actually not be translated
@@ -63,17 +114,46 @@ enum FunId {
     BoxFree,
 }
 
-pub fn is_marker_trait(name: &Name) -> bool {
-    for n in IGNORED_TRAITS_NAMES {
-        if name.equals_ref_name(n) {
+/// Compute the names of the traits whose clauses we filter, given the
+/// command-line options.
+pub fn get_ignored_traits(options: &CliOpts) -> Vec<Vec<String>> {
+    if options.keep_all_trait_clauses {
+        return Vec::new();
+    }
+    let to_name = |n: &str| -> Vec<String> { n.split("::").map(|s| s.to_string()).collect() };
+    let kept: Vec<Vec<String>> = options.kept_traits.iter().map(|n| to_name(n)).collect();
+    IGNORED_TRAITS_NAMES
+        .iter()
+        .map(|n| n.iter().map(|s| s.to_string()).collect::<Vec<String>>())
+        .filter(|n| !kept.contains(n))
+        .chain(options.ignored_traits.iter().map(|n| to_name(n)))
+        .collect()
+}
+
+/// Compute the paths of the attributes which mark the verification entry points, given the
+/// command-line options.
+pub fn get_harness_attributes(options: &CliOpts) -> Vec<Vec<String>> {
+    let to_path = |n: &str| -> Vec<String> { n.split("::").map(|s| s.to_string()).collect() };
+    HARNESS_ATTRIBUTES
+        .iter()
+        .map(|n| to_path(n))
+        .chain(options.harness_attributes.iter().map(|n| to_path(n)))
+        .collect()
+}
+
+pub fn is_marker_trait(name: &Name, ignored_traits: &[Vec<String>]) -> bool {
+    for n in ignored_traits {
+        let n: Vec<&str> = n.iter().map(|s| s.as_str()).collect();
+        if name.equals_ref_name(&n) {
             return true;
         }
     }
     false
 }
 
-pub fn get_type_id_from_name(name: &Name) -> Option<AssumedTy> {
-    if name.equals_ref_name(&BOX_NAME) {
+/// The assumed type corresponding to a type, given its name and its lang item
+pub fn get_type_id_from_name(name: &Name, lang_item: Option<&str>) -> Option<AssumedTy> {
+    if lang_item == Some(BOX_LANG_ITEM) || name.equals_ref_name(&BOX_NAME) {
         Option::Some(AssumedTy::Box)
     } else if name.equals_ref_name(&PTR_UNIQUE_NAME) {
         Option::Some(AssumedTy::PtrUnique)
@@ -95,12 +175,26 @@ pub fn get_name_from_type_id(id: AssumedTy) -> Vec<String> {
     }
 }
 
-fn get_fun_id_from_name_full(name: &Name) -> Option<FunId> {
+/// Whether a function panics, given its name and its lang item: we translate the
+/// calls to those functions to panics.
+pub fn is_panic_fun(name: &Name, lang_item: Option<&str>) -> bool {
+    lang_item.is_some_and(|item| PANIC_LANG_ITEMS.contains(&item))
+        || name.equals_ref_name(&PANIC_NAME)
+        || name.equals_ref_name(&BEGIN_PANIC_NAME)
+        || name.equals_ref_name(&ASSERT_FAILED_NAME)
+}
+
+/// Whether a function is `alloc::alloc::box_free`, given its name and its lang item
+pub fn is_box_free_fun(name: &Name, lang_item: Option<&str>) -> bool {
+    lang_item == Some(BOX_FREE_LANG_ITEM) || name.equals_ref_name(&BOX_FREE_NAME)
+}
+
+fn get_fun_id_from_name_full(name: &Name, lang_item: Option<&str>) -> Option<FunId> {
     if name.equals_ref_name(&PANIC_NAME) {
         Option::Some(FunId::Panic)
     } else if name.equals_ref_name(&BEGIN_PANIC_NAME) {
         Option::Some(FunId::BeginPanic)
-    } else if name.equals_ref_name(&BOX_FREE_NAME) {
+    } else if is_box_free_fun(name, lang_item) {
         Option::Some(FunId::BoxFree)
     } else {
         // Box::new is peculiar because there is an impl block
@@ -109,26 +203,29 @@ fn get_fun_id_from_name_full(name: &Name) -> Option<FunId> {
             [Ident(alloc, _), Ident(boxed, _), Impl(impl_elem), Ident(new, _)] => {
                 if alloc == "alloc" && boxed == "boxed" && new == "new" {
                     match &impl_elem.kind {
-                        ImplElemKind::Ty(Ty::Adt(TypeId::Assumed(AssumedTy::Box), generics)) => {
-                            let GenericArgs {
-                                regions,
-                                types,
-                                const_generics,
-                                trait_refs,
-                            } = generics;
-                            if regions.is_empty()
-                                && types.len() == 1
-                                && const_generics.is_empty()
-                                && trait_refs.is_empty()
-                            {
-                                match types.as_slice() {
-                                    [Ty::TypeVar(_)] => Option::Some(FunId::BoxNew),
-                                    _ => Option::None,
+                        ImplElemKind::Ty(ty) => match ty.kind() {
+                            TyKind::Adt(TypeId::Assumed(AssumedTy::Box), generics) => {
+                                let GenericArgsData {
+                                    regions,
+                                    types,
+                                    const_generics,
+                                    trait_refs,
+                                } = generics.data();
+                                if regions.is_empty()
+                                    && types.len() == 1
+                                    && const_generics.is_empty()
+                                    && trait_refs.is_empty()
+                                {
+                                    match types.as_slice() {
+                                        [ty] if ty.is_type_var() => Option::Some(FunId::BoxNew),
+                                        _ => Option::None,
+                                    }
+                                } else {
+                                    Option::None
                                 }
-                            } else {
-                                Option::None
                             }
-                        }
+                            _ => Option::None,
+                        },
                         _ => Option::None,
                     }
                 } else {
@@ -140,8 +237,11 @@ fn get_fun_id_from_name_full(name: &Name) -> Option<FunId> {
     }
 }
 
-pub fn get_fun_id_from_name(name: &Name) -> Option<ullbc_ast::AssumedFunId> {
-    match get_fun_id_from_name_full(name) {
+pub fn get_fun_id_from_name(
+    name: &Name,
+    lang_item: Option<&str>,
+) -> Option<ullbc_ast::AssumedFunId> {
+    match get_fun_id_from_name_full(name, lang_item) {
         Option::Some(id) => {
             let id = match id {
                 FunId::Panic | FunId::BeginPanic => unreachable!(),
@@ -154,13 +254,152 @@ pub fn get_fun_id_from_name(name: &Name) -> Option<ullbc_ast::AssumedFunId> {
     }
 }
 
+/// The functions on raw pointers which we translate to binary operations, like
+/// `<*const T>::offset` (see [BinOp::Offset]).
+pub fn get_ptr_binop_from_name(name: &Name) -> Option<BinOp> {
+    if name.equals_ref_name(&PTR_EQ_NAME) {
+        return Some(BinOp::Eq);
+    } else if name.equals_ref_name(&INTRINSICS_OFFSET_NAME) {
+        return Some(BinOp::Offset);
+    } else if name.equals_ref_name(&INTRINSICS_ARITH_OFFSET_NAME) {
+        return Some(BinOp::WrappingOffset);
+    } else if name.equals_ref_name(&INTRINSICS_PTR_OFFSET_FROM_NAME) {
+        return Some(BinOp::PtrDiff);
+    }
+    for module in PTR_MODULES {
+        let method = |m: &str| name.equals_ref_name(&["core", "ptr", module, m]);
+        if method("offset") || method("add") {
+            return Some(BinOp::Offset);
+        } else if method("wrapping_offset") || method("wrapping_add") {
+            return Some(BinOp::WrappingOffset);
+        } else if method("offset_from") {
+            return Some(BinOp::PtrDiff);
+        }
+    }
+    None
+}
+
+/// The copies of raw memory (see [get_mem_op_from_name]).
+#[derive(Debug, Clone, Copy)]
+pub enum MemOp {
+    /// A copy, translated to [ullbc_ast::RawStatement::MemCopy]. The arguments are the source,
+    /// the destination and the count, except if `dst_first` is true (`p.copy_from(src, count)`).
+    Copy {
+        nonoverlapping: bool,
+        dst_first: bool,
+    },
+    /// Translated to [ullbc_ast::RawStatement::WriteBytes]: the arguments are the destination,
+    /// the value and the count.
+    WriteBytes,
+}
+
+/// The functions which copy raw memory, like `ptr::copy_nonoverlapping` or `<*mut T>::copy_from`.
+pub fn get_mem_op_from_name(name: &Name) -> Option<MemOp> {
+    let copy = |nonoverlapping, dst_first| {
+        Some(MemOp::Copy {
+            nonoverlapping,
+            dst_first,
+        })
+    };
+    if name.equals_ref_name(&INTRINSICS_COPY_NAME) {
+        return copy(false, false);
+    } else if name.equals_ref_name(&INTRINSICS_COPY_NONOVERLAPPING_NAME) {
+        return copy(true, false);
+    } else if name.equals_ref_name(&INTRINSICS_WRITE_BYTES_NAME) {
+        return Some(MemOp::WriteBytes);
+    }
+    for module in PTR_MODULES {
+        let method = |m: &str| name.equals_ref_name(&["core", "ptr", module, m]);
+        if method("copy_to") {
+            return copy(false, false);
+        } else if method("copy_to_nonoverlapping") {
+            return copy(true, false);
+        } else if method("copy_from") {
+            return copy(false, true);
+        } else if method("copy_from_nonoverlapping") {
+            return copy(true, true);
+        } else if method("write_bytes") {
+            return Some(MemOp::WriteBytes);
+        }
+    }
+    None
+}
+
+/// Compute the patterns of the functions which we translate to the built-ins of the
+/// verifiers, given the command-line options. The patterns have the syntax of the
+/// `--extract-external-bodies` option. We translate the built-ins to intrinsics (see
+/// [crate::gast::Intrinsic]): the assertions take the condition and, optionally, the
+/// message, and the nondeterministic values are havocs of the destination.
+pub fn get_verifier_primitives(options: &CliOpts) -> Vec<(Vec<String>, IntrinsicKind)> {
+    let to_pattern = |n: &str| -> Vec<String> { n.split("::").map(|s| s.to_string()).collect() };
+    let mut primitives = Vec::new();
+    for kind in [
+        IntrinsicKind::Assume,
+        IntrinsicKind::Assert,
+        IntrinsicKind::Havoc,
+    ] {
+        let (defaults, patterns): (&[&str], _) = match kind {
+            IntrinsicKind::Assume => (&VERIFIER_ASSUME_NAMES, &options.verifier_assume),
+            IntrinsicKind::Assert => (&VERIFIER_ASSERT_NAMES, &options.verifier_assert),
+            IntrinsicKind::Havoc => (&VERIFIER_NONDET_NAMES, &options.verifier_nondet),
+            IntrinsicKind::Custom(_) => unreachable!(),
+        };
+        let patterns = patterns.iter().map(|n| n.as_str());
+        for n in defaults.iter().copied().chain(patterns) {
+            primitives.push((to_pattern(n), kind.clone()));
+        }
+    }
+    primitives
+}
+
+/// The methods of the integer types with an explicit behavior on overflow (we ignore the
+/// `impl` path elements when comparing the names).
+pub static INT_ARITH_METHODS: [(&str, BinOp); 12] = [
+    ("checked_add", BinOp::CheckedAdd),
+    ("checked_sub", BinOp::CheckedSub),
+    ("checked_mul", BinOp::CheckedMul),
+    ("wrapping_add", BinOp::WrappingAdd),
+    ("wrapping_sub", BinOp::WrappingSub),
+    ("wrapping_mul", BinOp::WrappingMul),
+    ("overflowing_add", BinOp::OverflowingAdd),
+    ("overflowing_sub", BinOp::OverflowingSub),
+    ("overflowing_mul", BinOp::OverflowingMul),
+    ("saturating_add", BinOp::SaturatingAdd),
+    ("saturating_sub", BinOp::SaturatingSub),
+    ("saturating_mul", BinOp::SaturatingMul),
+];
+
+/// The corresponding intrinsics.
+pub static INTRINSICS_ARITH: [(&str, BinOp); 8] = [
+    ("wrapping_add", BinOp::WrappingAdd),
+    ("wrapping_sub", BinOp::WrappingSub),
+    ("wrapping_mul", BinOp::WrappingMul),
+    ("add_with_overflow", BinOp::OverflowingAdd),
+    ("sub_with_overflow", BinOp::OverflowingSub),
+    ("mul_with_overflow", BinOp::OverflowingMul),
+    ("saturating_add", BinOp::SaturatingAdd),
+    ("saturating_sub", BinOp::SaturatingSub),
+];
+
+/// The functions which perform an arithmetic operation with an explicit behavior on overflow,
+/// like `u32::checked_add`, which we translate to binary operations (see [BinOp::CheckedAdd]).
+pub fn get_arith_binop_from_name(name: &Name) -> Option<BinOp> {
+    let find = |module: &str, table: &[(&str, BinOp)]| {
+        table
+            .iter()
+            .find(|(m, _)| name.equals_ref_name(&["core", module, m]))
+            .map(|(_, binop)| *binop)
+    };
+    find("num", &INT_ARITH_METHODS).or_else(|| find("intrinsics", &INTRINSICS_ARITH))
+}
+
 /// When translating from MIR to ULLBC, we ignore some type parameters for some
 /// assumed types.
 /// For instance, many types like box or vec are parameterized (in MIR) by an allocator
 /// (`std::alloc::Allocator`): we ignore it.
-pub fn type_to_used_params(name: &Name) -> Option<Vec<bool>> {
+pub fn type_to_used_params(name: &Name, lang_item: Option<&str>) -> Option<Vec<bool>> {
     trace!("{:?}", name);
-    match get_type_id_from_name(name) {
+    match get_type_id_from_name(name, lang_item) {
         Option::None => Option::None,
         Option::Some(id) => {
             let id = match id {
@@ -187,9 +426,9 @@ pub struct FunInfo {
 }
 
 /// See the comments for [type_to_used_params]
-pub fn function_to_info(name: &Name) -> Option<FunInfo> {
+pub fn function_to_info(name: &Name, lang_item: Option<&str>) -> Option<FunInfo> {
     trace!("{:?}", name);
-    match get_fun_id_from_name_full(name) {
+    match get_fun_id_from_name_full(name, lang_item) {
         Option::None => Option::None,
         Option::Some(id) => {
             let info = match id {
@@ -0,0 +1,476 @@
+//! Models of common functions of the standard library (this is optional, see
+//! the `--use-builtin-models` option).
+//!
+//! We don't extract the bodies of the external functions by default: the
+//! backends then have to axiomatize them. For a few common functions, we
+//! instead generate bodies written by hand, which are simpler than the
+//! implementations of the standard library (those often rely on unsafe code or
+//! intrinsics):
+//! - `Option::map` matches over the option, and calls the closure;
+//! - `Vec::len` computes the length of the slice given by `Vec::as_slice`;
+//! - `<[T]>::get` calls `SliceIndex::get`.
+//!
+//! We generate ULLBC bodies, which then go through the micro-passes like the
+//! other bodies. We only use a model if we would not extract the body of the
+//! function otherwise (see `--extract-opaque-bodies` and
+//! `--extract-external-bodies`). If the function doesn't have the signature we
+//! expect, we don't use the model.
+//!
+//! **IMPORTANT**:
+//! When checking whether names are equal to one of the reference names below,
+//! we ignore the disambiguators and the impl blocks (see [crate::names] and
+//! [crate::names_utils]).
+
+use crate::common::*;
+use crate::expressions::*;
+use crate::meta::Meta;
+use crate::names::Name;
+use crate::translate_ctx::*;
+use crate::types::*;
+use crate::ullbc_ast::*;
+use crate::values::*;
+use rustc_hir::def_id::DefId;
+
+pub static OPTION_MAP_NAME: [&str; 3] = ["core", "option", "map"];
+pub static VEC_LEN_NAME: [&str; 3] = ["alloc", "vec", "len"];
+pub static SLICE_GET_NAME: [&str; 3] = ["core", "slice", "get"];
+
+/// The functions for which we have a model
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuiltinModel {
+    OptionMap,
+    VecLen,
+    SliceGet,
+}
+
+pub fn get_model_from_name(name: &Name) -> Option<BuiltinModel> {
+    if name.equals_ref_name(&OPTION_MAP_NAME) {
+        Some(BuiltinModel::OptionMap)
+    } else if name.equals_ref_name(&VEC_LEN_NAME) {
+        Some(BuiltinModel::VecLen)
+    } else if name.equals_ref_name(&SLICE_GET_NAME) {
+        Some(BuiltinModel::SliceGet)
+    } else {
+        None
+    }
+}
+
+/// The discriminants of `Option::None` and `Option::Some` (this is the order
+/// of the variants in the declaration of `Option`).
+const OPTION_NONE_ID: usize = 0;
+const OPTION_SOME_ID: usize = 1;
+
+/// Helper to build a model body: the return value and the inputs are the first
+/// locals, and we add the blocks in order.
+struct BodyBuilder {
+    meta: Meta,
+    locals: VarId::Vector<Var>,
+    blocks: BlockId::Vector<BlockData>,
+}
+
+impl BodyBuilder {
+    fn new(meta: Meta, signature: &FunSig) -> Self {
+        let mut builder = BodyBuilder {
+            meta,
+            locals: VarId::Vector::new(),
+            blocks: BlockId::Vector::new(),
+        };
+        builder.new_var(signature.output.clone());
+        for ty in &signature.inputs {
+            builder.new_var(ty.clone());
+        }
+        builder
+    }
+
+    fn new_var(&mut self, ty: Ty) -> VarId::Id {
+        self.locals.push_with(|index| Var {
+            index,
+            name: None,
+            ty,
+            debug_info: Vec::new(),
+        })
+    }
+
+    /// The id of the block we push after `n` more blocks
+    fn next_block(&self, n: usize) -> BlockId::Id {
+        BlockId::Id::new(self.blocks.len() + n)
+    }
+
+    fn push_block(&mut self, statements: Vec<RawStatement>, terminator: RawTerminator) {
+        let meta = self.meta;
+        self.blocks.push(BlockData {
+            statements: statements
+                .into_iter()
+                .map(|content| Statement::new(meta, content))
+                .collect(),
+            terminator: Terminator::new(meta, terminator),
+        });
+    }
+
+    fn call(
+        func: FnPtr,
+        args: Vec<Operand>,
+        dest: VarId::Id,
+        target: BlockId::Id,
+    ) -> RawTerminator {
+        let call = Call {
+            func: FnOperand::Regular(func),
+            args,
+            dest: Place::new(dest),
+            receiver_adjustments: Vec::new(),
+            resolution: None,
+            is_tail: false,
+            is_ghost: false,
+        };
+        RawTerminator::Call { call, target }
+    }
+
+    fn build(self, arg_count: usize) -> ExprBody {
+        ExprBody {
+            meta: self.meta,
+            arg_count,
+            locals: self.locals,
+            regions: BodyRegions::default(),
+            borrowck_facts: None,
+            scopes: ScopeId::Vector::new(),
+            promoted: PromotedId::Vector::new(),
+            body: self.blocks,
+        }
+    }
+}
+
+/// A call to the method of a trait clause of the function
+fn clause_method_call(clause: &TraitClause, method: &str, fun_id: FunDeclId::Id) -> FnPtr {
+    let trait_ref = TraitRef::new(
+        TraitInstanceId::Clause(clause.clause_id),
+        clause.generics.clone(),
+        TraitDeclRef {
+            trait_id: clause.trait_id,
+            generics: clause.generics.clone(),
+        },
+    );
+    FnPtr {
+        func: FunIdOrTraitMethodRef::Trait(trait_ref, TraitItemName(method.to_string()), fun_id),
+        generics: clause.generics.clone(),
+    }
+}
+
+impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
+    /// Generate the model of a function, if we have one for this function and
+    /// we don't extract its body otherwise.
+    pub(crate) fn translate_builtin_model(
+        &mut self,
+        rust_id: DefId,
+        name: &Name,
+        signature: &FunSig,
+    ) -> Result<Option<ExprBody>, Error> {
+        if !self.t_ctx.use_builtin_models
+            || rust_id.is_local()
+            || self.t_ctx.extract_external_body(rust_id)
+        {
+            return Ok(None);
+        }
+        let Some(model) = get_model_from_name(name) else {
+            return Ok(None);
+        };
+        let meta = self.t_ctx.translate_meta_from_rid(rust_id);
+        let builder = BodyBuilder::new(meta, signature);
+        let builder = match model {
+            BuiltinModel::OptionMap => self.option_map_model(rust_id, signature, builder)?,
+            BuiltinModel::VecLen => self.vec_len_model(rust_id, signature, builder),
+            BuiltinModel::SliceGet => self.slice_get_model(rust_id, signature, builder)?,
+        };
+        Ok(builder.map(|builder| builder.build(signature.inputs.len())))
+    }
+
+    /// Find the trait clause of the function whose trait has a method called
+    /// `method`, and return it together with the id of this method.
+    fn find_clause_with_method(
+        &mut self,
+        rust_id: DefId,
+        signature: &FunSig,
+        method: &str,
+    ) -> Result<Option<(TraitClause, FunDeclId::Id)>, Error> {
+        use rustc_middle::ty::{AssocKind, Clause, PredicateKind};
+        let tcx = self.t_ctx.tcx;
+        let span = tcx.def_span(rust_id);
+        for (pred, _) in tcx.predicates_of(rust_id).predicates {
+            let PredicateKind::Clause(Clause::Trait(trait_pred)) = pred.kind().skip_binder()
+            else { continue };
+            let trait_id = trait_pred.def_id();
+            let Some(item) = tcx
+                .associated_items(trait_id)
+                .in_definition_order()
+                .find(|item| item.kind == AssocKind::Fn && item.name.as_str() == method)
+            else { continue };
+            // This returns `None` for the marker traits we ignore
+            let Some(trait_decl_id) = self.translate_trait_decl_id(span, trait_id)? else {
+                continue;
+            };
+            let clause = signature
+                .generics
+                .trait_clauses
+                .iter()
+                .find(|clause| clause.trait_id == trait_decl_id);
+            if let Some(clause) = clause {
+                let fun_id = self.translate_fun_decl_id(span, item.def_id);
+                return Ok(Some((clause.clone(), fun_id)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// The model of `Option::map`:
+    /// ```text
+    /// fn map<T, U, F: FnOnce(T) -> U>(self: Option<T>, f: F) -> Option<U> {
+    ///     match self {
+    ///         None => { drop(f); None }
+    ///         Some(x) => Some(FnOnce::call_once(move f, (move x,))),
+    ///     }
+    /// }
+    /// ```
+    fn option_map_model(
+        &mut self,
+        rust_id: DefId,
+        signature: &FunSig,
+        mut builder: BodyBuilder,
+    ) -> Result<Option<BodyBuilder>, Error> {
+        let [self_ty, _] = signature.inputs.as_slice() else {
+            return Ok(None);
+        };
+        let TyKind::Adt(TypeId::Adt(option_id), generics) = self_ty.kind() else {
+            return Ok(None);
+        };
+        let TyKind::Adt(_, output_generics) = signature.output.kind() else {
+            return Ok(None);
+        };
+        let (Some(elem_ty), Some(output_ty)) =
+            (generics.types.first(), output_generics.types.first())
+        else {
+            return Ok(None);
+        };
+        let Some((clause, call_once_id)) =
+            self.find_clause_with_method(rust_id, signature, "call_once")?
+        else {
+            return Ok(None);
+        };
+        let (option_id, elem_ty, output_ty) = (*option_id, elem_ty.clone(), output_ty.clone());
+
+        let (dest, opt, f) = (VarId::ZERO, VarId::Id::new(1), VarId::Id::new(2));
+        let discr =
+            builder.new_var(TyKind::Literal(LiteralTy::Integer(IntegerTy::Isize)).into_ty());
+        let x = builder.new_var(elem_ty.clone());
+        let args_ty = TyKind::Adt(
+            TypeId::Tuple,
+            GenericArgs::new_from_types(vec![elem_ty.clone()]),
+        );
+        let args = builder.new_var(args_ty.into_ty());
+        let y = builder.new_var(output_ty.clone());
+        let variant = |id: usize, ty: &Ty, ops: Vec<Operand>| {
+            let generics = GenericArgs::new_from_types(vec![ty.clone()]);
+            let kind = AggregateKind::Adt(
+                TypeId::Adt(option_id),
+                Some(VariantId::Id::new(id)),
+                generics,
+            );
+            Rvalue::Aggregate(kind, ops)
+        };
+
+        // bb0: switch over the discriminant
+        let (none_block, some_block, unreachable_block) = (
+            builder.next_block(1),
+            builder.next_block(3),
+            builder.next_block(5),
+        );
+        let targets = SwitchTargets::SwitchInt(
+            IntegerTy::Isize,
+            vec![
+                (ScalarValue::Isize(OPTION_NONE_ID as i64), none_block),
+                (ScalarValue::Isize(OPTION_SOME_ID as i64), some_block),
+            ],
+            unreachable_block,
+        );
+        builder.push_block(
+            vec![RawStatement::Assign(
+                Place::new(discr),
+                Rvalue::Discriminant(Place::new(opt), option_id),
+            )],
+            RawTerminator::Switch {
+                discr: Operand::Move(Place::new(discr)),
+                targets,
+            },
+        );
+
+        // bb1, bb2: the `None` case
+        let target = builder.next_block(1);
+        builder.push_block(
+            Vec::new(),
+            RawTerminator::Drop {
+                place: Place::new(f),
+                target,
+            },
+        );
+        builder.push_block(
+            vec![RawStatement::Assign(
+                Place::new(dest),
+                variant(OPTION_NONE_ID, &output_ty, Vec::new()),
+            )],
+            RawTerminator::Return,
+        );
+
+        // bb3, bb4: the `Some` case
+        let field = ProjectionElem::Field(
+            FieldProjKind::Adt(option_id, Some(VariantId::Id::new(OPTION_SOME_ID))),
+            FieldId::Id::new(0),
+        );
+        let tuple = AggregateKind::Adt(
+            TypeId::Tuple,
+            None,
+            GenericArgs::new_from_types(vec![elem_ty]),
+        );
+        let target = builder.next_block(1);
+        builder.push_block(
+            vec![
+                RawStatement::Assign(
+                    Place::new(x),
+                    Rvalue::Use(Operand::Move(Place {
+                        var_id: opt,
+                        projection: vec![field],
+                    })),
+                ),
+                RawStatement::Assign(
+                    Place::new(args),
+                    Rvalue::Aggregate(tuple, vec![Operand::Move(Place::new(x))]),
+                ),
+            ],
+            BodyBuilder::call(
+                clause_method_call(&clause, "call_once", call_once_id),
+                vec![
+                    Operand::Move(Place::new(f)),
+                    Operand::Move(Place::new(args)),
+                ],
+                y,
+                target,
+            ),
+        );
+        builder.push_block(
+            vec![RawStatement::Assign(
+                Place::new(dest),
+                variant(
+                    OPTION_SOME_ID,
+                    &output_ty,
+                    vec![Operand::Move(Place::new(y))],
+                ),
+            )],
+            RawTerminator::Return,
+        );
+
+        // bb5: the other discriminants
+        builder.push_block(Vec::new(), RawTerminator::Unreachable);
+        Ok(Some(builder))
+    }
+
+    /// The model of `Vec::len`:
+    /// ```text
+    /// fn len<T, A>(self: &Vec<T, A>) -> usize {
+    ///     let s = Vec::as_slice(copy self);
+    ///     len(*s)
+    /// }
+    /// ```
+    /// We only use this model if `Vec::as_slice` is in the same impl block as
+    /// `Vec::len`, so that they have the same generics.
+    fn vec_len_model(
+        &mut self,
+        rust_id: DefId,
+        signature: &FunSig,
+        mut builder: BodyBuilder,
+    ) -> Option<BodyBuilder> {
+        use rustc_middle::ty::AssocKind;
+        let tcx = self.t_ctx.tcx;
+        let [self_ty] = signature.inputs.as_slice() else {
+            return None;
+        };
+        let TyKind::Ref(_, vec_ty, _) = self_ty.kind() else {
+            return None;
+        };
+        let TyKind::Adt(_, generics) = vec_ty.kind() else {
+            return None;
+        };
+        let elem_ty = generics.types.first()?.clone();
+        let as_slice = tcx
+            .associated_items(tcx.parent(rust_id))
+            .in_definition_order()
+            .find(|item| item.kind == AssocKind::Fn && item.name.as_str() == "as_slice")?;
+        let as_slice_id = self.translate_fun_decl_id(tcx.def_span(rust_id), as_slice.def_id);
+
+        let slice_ty = TyKind::Adt(
+            TypeId::Assumed(AssumedTy::Slice),
+            GenericArgs::new_from_types(vec![elem_ty]),
+        )
+        .into_ty();
+        let s_ty = TyKind::Ref(Region::Erased, slice_ty.clone(), RefKind::Shared).into_ty();
+        let s = builder.new_var(s_ty);
+        let func = FnPtr {
+            func: FunIdOrTraitMethodRef::Fun(FunId::Regular(as_slice_id)),
+            generics: signature.generics.identity_args(),
+        };
+        let target = builder.next_block(1);
+        builder.push_block(
+            Vec::new(),
+            BodyBuilder::call(
+                func,
+                vec![Operand::Copy(Place::new(VarId::Id::new(1)))],
+                s,
+                target,
+            ),
+        );
+        let slice = Place {
+            var_id: s,
+            projection: vec![ProjectionElem::Deref],
+        };
+        builder.push_block(
+            vec![RawStatement::Assign(
+                Place::new(VarId::ZERO),
+                Rvalue::Len(slice, slice_ty, None),
+            )],
+            RawTerminator::Return,
+        );
+        Some(builder)
+    }
+
+    /// The model of `<[T]>::get`:
+    /// ```text
+    /// fn get<T, I: SliceIndex<[T]>>(self: &[T], index: I) -> Option<&I::Output> {
+    ///     SliceIndex::get(move index, copy self)
+    /// }
+    /// ```
+    fn slice_get_model(
+        &mut self,
+        rust_id: DefId,
+        signature: &FunSig,
+        mut builder: BodyBuilder,
+    ) -> Result<Option<BodyBuilder>, Error> {
+        if signature.inputs.len() != 2 {
+            return Ok(None);
+        }
+        let Some((clause, get_id)) = self.find_clause_with_method(rust_id, signature, "get")?
+        else {
+            return Ok(None);
+        };
+        let target = builder.next_block(1);
+        builder.push_block(
+            Vec::new(),
+            BodyBuilder::call(
+                clause_method_call(&clause, "get", get_id),
+                vec![
+                    Operand::Move(Place::new(VarId::Id::new(2))),
+                    Operand::Copy(Place::new(VarId::Id::new(1))),
+                ],
+                VarId::ZERO,
+                target,
+            ),
+        );
+        builder.push_block(Vec::new(), RawTerminator::Return);
+        Ok(Some(builder))
+    }
+}
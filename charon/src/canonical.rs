@@ -0,0 +1,424 @@
+//! The canonical export (see the `--canonical` option). The output of Charon
+//! is deterministic, but the numeric ids of the declarations and of the files
+//! depend on the order in which we translated them, while the spans refer to
+//! absolute paths: a small change in the crate (or moving it to another
+//! directory) can change the whole output file. In canonical mode, we renumber
+//! the declarations and the modules in the order of their names, the files in
+//! the order of their names, which we make relative to the directory containing the local
+//! files, and the macro expansions in the order of their contents. This makes
+//! the output easier to cache and to compare.
+//!
+//! We first compute the new ids (see [Canonical::new]), then put the serialized
+//! elements of the crate in canonical form one at a time, while we write them
+//! (see [crate::export]): we never hold the whole serialized crate in memory.
+//! We reuse the traversal of `charon diff` to find the references to the
+//! declarations (see [map_decl_refs]). The symbols are numbered in the order in
+//! which they appear in the canonical crate, because we write its elements in
+//! this order (see [crate::symbol]).
+//! Remark: we rely on the `preserve_order` feature of `serde_json`, so that
+//! the fields of the objects are serialized in the same order as usual.
+use crate::diff::{map_decl_refs, DeclKind};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// The key with which we sort the declarations: their names, then their stable
+/// ids (several trait implementations can have the same name).
+pub type SortKey = (String, String);
+
+/// Compute the key with which we sort a declaration. This must be called
+/// outside of [crate::symbol::with_string_table], so that we compare the
+/// strings of the names, and not their indices.
+pub fn sort_key(name: &impl Serialize, stable_id: &impl Serialize) -> SortKey {
+    // We don't use maps with non-string keys: the serialization can't fail.
+    (
+        serde_json::to_string(name).unwrap(),
+        serde_json::to_string(stable_id).unwrap(),
+    )
+}
+
+/// The positions of the elements of a list, sorted by their keys.
+fn sorted_positions<K: Ord>(keys: &[K]) -> Vec<usize> {
+    let mut positions: Vec<usize> = (0..keys.len()).collect();
+    positions.sort_by(|i, j| keys[*i].cmp(&keys[*j]));
+    positions
+}
+
+/// The key with which we sort the values of the shape `{ "Type": id }`, `{ "Fun": id }`, etc.
+fn tagged_key(value: &Value) -> Option<(String, Option<u64>)> {
+    value
+        .as_object()
+        .and_then(|value| value.iter().next())
+        .map(|(kind, id)| (kind.clone(), id.as_u64()))
+}
+
+/// Renumber the ids of a declaration group (all the numbers it contains are
+/// ids of declarations of the given kind). We sort the ids of the recursive
+/// groups.
+fn renumber_group_ids(value: &mut Value, kind: DeclKind, f: &dyn Fn(DeclKind, u64) -> Value) {
+    match value {
+        Value::Number(id) => {
+            if let Some(id) = id.as_u64() {
+                *value = f(kind, id)
+            }
+        }
+        Value::Array(ids) => {
+            for id in ids.iter_mut() {
+                renumber_group_ids(id, kind, f)
+            }
+            ids.sort_by_key(|id| id.as_u64());
+        }
+        Value::Object(fields) => {
+            for v in fields.values_mut() {
+                renumber_group_ids(v, kind, f)
+            }
+        }
+        Value::Null | Value::Bool(_) | Value::String(_) => (),
+    }
+}
+
+/// Renumber the ids of a value of the shape `{ "Type": ... }`, `{ "Fun": ... }`, etc. (the
+/// declaration groups and the items of the modules).
+fn renumber_tagged_ids(value: &mut Value, f: &dyn Fn(DeclKind, u64) -> Value) {
+    let Value::Object(fields) = value else { return };
+    for (variant, ids) in fields.iter_mut() {
+        let kind = match variant.as_str() {
+            "Type" => DeclKind::Type,
+            "Fun" => DeclKind::Fun,
+            "Global" => DeclKind::Global,
+            "TraitDecl" => DeclKind::TraitDecl,
+            "TraitImpl" => DeclKind::TraitImpl,
+            _ => continue,
+        };
+        renumber_group_ids(ids, kind, f);
+    }
+}
+
+/// The longest directory containing all the paths.
+fn common_dir(paths: &[PathBuf]) -> Option<PathBuf> {
+    let mut dir = paths.first()?.parent()?.to_path_buf();
+    while !paths.iter().all(|path| path.starts_with(&dir)) {
+        if !dir.pop() {
+            return None;
+        }
+    }
+    Some(dir)
+}
+
+/// Replace the ids in the fields with the given name (the file ids in the
+/// spans, the ids of the macro expansions in the meta information, etc.).
+fn renumber_ids(value: &mut Value, id_field: &str, new_ids: &HashMap<u64, u64>) {
+    match value {
+        Value::Array(values) => {
+            for v in values.iter_mut() {
+                renumber_ids(v, id_field, new_ids)
+            }
+        }
+        Value::Object(fields) => {
+            for (field, value) in fields.iter_mut() {
+                if field != id_field {
+                    renumber_ids(value, id_field, new_ids)
+                } else if let Some(new_id) = value.as_u64().and_then(|id| new_ids.get(&id)) {
+                    *value = Value::from(*new_id)
+                }
+            }
+        }
+        Value::Null | Value::Bool(_) | Value::Number(_) | Value::String(_) => (),
+    }
+}
+
+/// Normalize the names of the local files of the serialized `id_to_file` field,
+/// and renumber the files in the order of their names. We return the new ids.
+fn renumber_files(files: &mut Value) -> HashMap<u64, u64> {
+    let Value::Array(files) = files else {
+        return HashMap::new();
+    };
+    let local_name = |file: &Value| file.get(1)?.get("Local")?.as_str().map(PathBuf::from);
+    let local_paths: Vec<PathBuf> = files.iter().filter_map(local_name).collect();
+    if let Some(dir) = common_dir(&local_paths) {
+        for file in files.iter_mut() {
+            let Some(Value::String(name)) = file.get_mut(1).and_then(|f| f.get_mut("Local"))
+            else {
+                continue;
+            };
+            if let Ok(relative) = Path::new(name.as_str()).strip_prefix(&dir) {
+                *name = relative.to_string_lossy().into_owned();
+            }
+        }
+    }
+
+    files.sort_by_cached_key(|file| file.get(1).map(Value::to_string));
+    let mut new_ids = HashMap::new();
+    for (new_id, file) in files.iter_mut().enumerate() {
+        let Some(id) = file.get_mut(0) else { continue };
+        if let Some(old_id) = id.as_u64() {
+            new_ids.insert(old_id, new_id as u64);
+        }
+        *id = Value::from(new_id);
+    }
+    new_ids
+}
+
+/// Renumber the serialized `macro_expansions` field, given the new ids of the
+/// files. We sort the expansions by their contents, then by the contents of
+/// their parents (the calls of the same macro at the same place can come from
+/// different expansions). We return the new ids.
+fn renumber_expansions(expansions: &mut Value, file_ids: &HashMap<u64, u64>) -> HashMap<u64, u64> {
+    let Value::Array(expansions) = expansions else {
+        return HashMap::new();
+    };
+    for expansion in expansions.iter_mut() {
+        renumber_ids(expansion, "file_id", file_ids);
+    }
+    let contents: Vec<String> = expansions
+        .iter()
+        .map(|expansion| {
+            let mut expansion = expansion.clone();
+            if let Some(fields) = expansion.as_object_mut() {
+                fields.remove("parent");
+            }
+            expansion.to_string()
+        })
+        .collect();
+    // The ids of the expansions are their positions
+    let keys: Vec<Vec<&String>> = (0..expansions.len())
+        .map(|mut pos| {
+            let mut key = vec![&contents[pos]];
+            let parent = |pos: usize| expansions[pos].get("parent").and_then(Value::as_u64);
+            while let Some(parent) = parent(pos) {
+                pos = parent as usize;
+                if pos >= expansions.len() || key.len() > expansions.len() {
+                    break;
+                }
+                key.push(&contents[pos]);
+            }
+            key
+        })
+        .collect();
+    let order = sorted_positions(&keys);
+    let new_ids: HashMap<u64, u64> = order
+        .iter()
+        .enumerate()
+        .map(|(new_id, pos)| (*pos as u64, new_id as u64))
+        .collect();
+    let mut sorted: Vec<Value> = order.iter().map(|pos| expansions[*pos].take()).collect();
+    for expansion in sorted.iter_mut() {
+        renumber_ids(expansion, "parent", &new_ids);
+    }
+    *expansions = sorted;
+    new_ids
+}
+
+/// The new ids of the canonical form, and the order in which we write the
+/// elements of the crate.
+pub struct Canonical {
+    /// The serialized `id_to_file` field, in canonical form.
+    files: Value,
+    file_ids: HashMap<u64, u64>,
+    /// The serialized `macro_expansions` field, in canonical form.
+    expansions: Value,
+    expansion_ids: HashMap<u64, u64>,
+    decl_ids: HashMap<(DeclKind, u64), u64>,
+    num_decls: HashMap<DeclKind, u64>,
+    alias_ids: HashMap<u64, u64>,
+    module_ids: HashMap<u64, u64>,
+    /// For the fields of the crate whose elements we reorder, the positions of
+    /// the elements in the order in which we write them.
+    orders: HashMap<&'static str, Vec<usize>>,
+}
+
+impl Canonical {
+    /// Compute the new ids:
+    /// - `files` is the serialized `id_to_file` field;
+    /// - `expansions` is the serialized `macro_expansions` field;
+    /// - `decls` gives, for every kind of declarations, the ids and the keys
+    ///   (see [sort_key]) of the declarations, in the order of their field;
+    /// - `aliases` gives the ids and the keys of the type aliases;
+    /// - `modules` gives the ids and the keys of the modules;
+    /// - `clause_envs` gives the serialized items of the clause environments
+    ///   (see [crate::gast::ClauseEnv]).
+    pub fn new(
+        mut files: Value,
+        mut expansions: Value,
+        decls: HashMap<DeclKind, Vec<(u64, SortKey)>>,
+        aliases: Vec<(u64, SortKey)>,
+        modules: Vec<(u64, SortKey)>,
+        clause_envs: Vec<Value>,
+    ) -> Self {
+        let file_ids = renumber_files(&mut files);
+        let expansion_ids = renumber_expansions(&mut expansions, &file_ids);
+        let mut canonical = Canonical {
+            files,
+            file_ids,
+            expansions,
+            expansion_ids,
+            decl_ids: HashMap::new(),
+            num_decls: HashMap::new(),
+            alias_ids: HashMap::new(),
+            module_ids: HashMap::new(),
+            orders: HashMap::new(),
+        };
+
+        // Sort the declarations and compute their new ids
+        for (kind, decls) in decls {
+            let keys: Vec<&SortKey> = decls.iter().map(|(_, key)| key).collect();
+            let order = sorted_positions(&keys);
+            for (new_id, pos) in order.iter().enumerate() {
+                canonical
+                    .decl_ids
+                    .insert((kind, decls[*pos].0), new_id as u64);
+            }
+            canonical.num_decls.insert(kind, decls.len() as u64);
+            canonical.orders.insert(kind.field(), order);
+        }
+
+        // The type aliases aren't declarations: we renumber them separately
+        let keys: Vec<&SortKey> = aliases.iter().map(|(_, key)| key).collect();
+        let order = sorted_positions(&keys);
+        for (new_id, pos) in order.iter().enumerate() {
+            canonical.alias_ids.insert(aliases[*pos].0, new_id as u64);
+        }
+        canonical.orders.insert("type_aliases", order);
+
+        // We renumber the modules in the order of their names (we don't
+        // reorder the field: we sort the modules once renumbered)
+        let keys: Vec<&SortKey> = modules.iter().map(|(_, key)| key).collect();
+        for (new_id, pos) in sorted_positions(&keys).iter().enumerate() {
+            canonical.module_ids.insert(modules[*pos].0, new_id as u64);
+        }
+
+        // Sort the environments by kind, then by id (as the items of the modules)
+        let keys: Vec<_> = clause_envs
+            .into_iter()
+            .map(|mut item| {
+                renumber_tagged_ids(&mut item, &|kind, id| canonical.renumber(kind, id));
+                tagged_key(&item)
+            })
+            .collect();
+        canonical
+            .orders
+            .insert("clause_envs", sorted_positions(&keys));
+        canonical
+    }
+
+    /// The new id of a declaration. The declarations which are missing because
+    /// of errors are numbered after the others (so that the new ids are still
+    /// unique).
+    fn renumber(&self, kind: DeclKind, id: u64) -> Value {
+        let new_id = match self.decl_ids.get(&(kind, id)) {
+            Some(new_id) => *new_id,
+            None => self.num_decls.get(&kind).copied().unwrap_or_default() + id,
+        };
+        Value::from(new_id)
+    }
+
+    /// The serialized `id_to_file` field, in canonical form.
+    pub fn files(&self) -> &Value {
+        &self.files
+    }
+
+    /// The serialized `macro_expansions` field, in canonical form.
+    pub fn expansions(&self) -> &Value {
+        &self.expansions
+    }
+
+    /// The positions of the elements of a field of the crate, in the order in
+    /// which we write them (`None` if we don't reorder the field).
+    pub fn order(&self, field: &str) -> Option<&[usize]> {
+        self.orders.get(field).map(Vec::as_slice)
+    }
+
+    /// Put an element of a field of the crate in canonical form.
+    pub fn element(&self, field: &str, value: &mut Value) {
+        let renumber = |kind, id| self.renumber(kind, id);
+        renumber_ids(value, "file_id", &self.file_ids);
+        renumber_ids(value, "expansion", &self.expansion_ids);
+        renumber_ids(value, "alias_id", &self.alias_ids);
+        if let Some(kind) = DeclKind::ALL.into_iter().find(|kind| kind.field() == field) {
+            *value = map_decl_refs(value, &[], &renumber);
+            if let Some(def_id) = value.get_mut("def_id")
+                && let Some(id) = def_id.as_u64()
+            {
+                *def_id = renumber(kind, id);
+            }
+            // The recursive group of the declaration contains declarations of
+            // its own kind
+            if let Some(rec_group) = value.get_mut("rec_group") {
+                renumber_group_ids(rec_group, kind, &renumber);
+            }
+            return;
+        }
+        match field {
+            "declarations" => renumber_tagged_ids(value, &renumber),
+            "modules" => {
+                let renumber_module = |id: &mut Value| {
+                    if let Some(new_id) = id.as_u64().and_then(|id| self.module_ids.get(&id)) {
+                        *id = Value::from(*new_id)
+                    }
+                };
+                for field in ["module_id", "parent"] {
+                    if let Some(id) = value.get_mut(field) {
+                        renumber_module(id)
+                    }
+                }
+                if let Some(Value::Array(submodules)) = value.get_mut("submodules") {
+                    submodules.iter_mut().for_each(renumber_module);
+                    submodules.sort_by_key(Value::as_u64);
+                }
+                let Some(Value::Array(items)) = value.get_mut("items") else { return };
+                for item in items.iter_mut() {
+                    renumber_tagged_ids(item, &renumber);
+                }
+                // Sort the items by kind, then by id
+                items.sort_by_cached_key(tagged_key);
+            }
+            "harnesses" => {
+                if let Some(fun_id) = value.get_mut("fun_id")
+                    && let Some(id) = fun_id.as_u64()
+                {
+                    *fun_id = renumber(DeclKind::Fun, id);
+                }
+            }
+            "ghost_functions" => renumber_group_ids(value, DeclKind::Fun, &renumber),
+            "clause_envs" => {
+                if let Some(clauses) = value.get_mut("clauses") {
+                    *clauses = map_decl_refs(clauses, &[], &renumber);
+                }
+                if let Some(item) = value.get_mut("item") {
+                    renumber_tagged_ids(item, &renumber);
+                }
+            }
+            "type_aliases" => {
+                *value = map_decl_refs(value, &[], &renumber);
+                if let Some(def_id) = value.get_mut("def_id")
+                    && let Some(new_id) = def_id.as_u64().and_then(|id| self.alias_ids.get(&id))
+                {
+                    *def_id = Value::from(*new_id);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    /// Put a field of the crate which we serialize at once in canonical form
+    /// (see [Self::element] for the fields we serialize one element at a time).
+    pub fn field(&self, field: &str, value: &mut Value) {
+        let Value::Array(elems) = value else {
+            return self.element(field, value);
+        };
+        for elem in elems.iter_mut() {
+            self.element(field, elem);
+        }
+        match field {
+            "harnesses" => {
+                elems.sort_by_key(|harness| harness.get("fun_id").and_then(Value::as_u64))
+            }
+            "ghost_functions" => elems.sort_by_key(Value::as_u64),
+            "modules" => {
+                elems.sort_by_key(|module| module.get("module_id").and_then(Value::as_u64))
+            }
+            _ => (),
+        }
+    }
+}
@@ -2,10 +2,13 @@
 //! crate to LLBC.
 
 use charon_lib::cli_options;
+use charon_lib::common::CharonError;
 use charon_lib::driver::{
-    arg_value, get_args_crate_index, get_args_source_index, CharonCallbacks, CharonFailure,
+    arg_value, get_args_crate_index, get_args_source_index, is_build_crate, run_rustc,
+    CharonCallbacks,
 };
-use charon_lib::export::CrateData;
+use charon_lib::export::{CrateData, COMPRESSED_EXTENSION};
+use charon_lib::interp;
 use charon_lib::logger;
 use charon_lib::trace;
 
@@ -67,6 +70,31 @@ fn main() {
     if !has_sysroot_arg {
         compiler_args.extend(vec!["--sysroot".to_string(), sysroot]);
     }
+    // Cargo also calls the driver on the build scripts and the proc-macro
+    // crates of the workspace, which it needs to build the crate we extract: we
+    // simply compile them, unless the user asked to extract them as well.
+    if !options.extract_build_crates && is_build_crate(&compiler_args) {
+        let crate_name = arg_value(&compiler_args, "--crate-name", |_| true).unwrap_or_default();
+        log::info!(
+            "Compiling {crate_name} without extracting it (it is a build script or a proc-macro \
+             crate): use --extract-build-crates to extract it"
+        );
+        if let Err(error) = run_rustc(compiler_args) {
+            log::error!("{}", error);
+            std::process::exit(error.exit_code());
+        }
+        return;
+    }
+    // When called through cargo, the target is given by cargo (cargo-charon
+    // forwards the `--target` option to cargo). Otherwise, we have to add it.
+    if let Some(target) = &options.target {
+        if arg_value(&compiler_args, "--target", |_| true).is_none() {
+            compiler_args.extend(vec!["--target".to_string(), target.clone()]);
+        }
+    }
+    for cfg in &options.cfgs {
+        compiler_args.extend(vec!["--cfg".to_string(), cfg.clone()]);
+    }
     if options.use_polonius {
         compiler_args.push("-Zpolonius".to_string());
     }
@@ -138,9 +166,16 @@ fn main() {
     let mut callback = CharonCallbacks::new(options);
     let mut res = callback.run_compiler(compiler_args);
     if let Some(crate_data) = &callback.crate_data {
-        if !callback.options.no_serialize {
+        if !callback.options.no_serialize && callback.options.exec.is_none() {
             // # Final step: generate the files.
             res = res.and_then(|()| {
+                if callback.options.stdout {
+                    return crate_data.serialize_to_stdout(callback.options.compress);
+                }
+                if let Some(dir) = &callback.options.split_output {
+                    trace!("Target directory: {:?}", dir);
+                    return crate_data.serialize_split_to_dir(dir);
+                }
                 let dest_file = match callback.options.dest_file.clone() {
                     Some(f) => f,
                     None => {
@@ -154,12 +189,47 @@ fn main() {
                         target_filename
                     }
                 };
+                let dest_file = if callback.options.compress
+                    && dest_file.extension() != Some(COMPRESSED_EXTENSION.as_ref())
+                {
+                    let mut dest_file = dest_file.into_os_string();
+                    dest_file.push(format!(".{COMPRESSED_EXTENSION}"));
+                    dest_file.into()
+                } else {
+                    dest_file
+                };
                 trace!("Target file: {:?}", dest_file);
-                crate_data
-                    .serialize_to_file(&dest_file)
-                    .map_err(|()| CharonFailure::Serialize)
+                if callback.options.watch {
+                    crate_data.update_file(&dest_file)
+                } else {
+                    crate_data.serialize_to_file(&dest_file)
+                }
             });
+            if let Some(profile) = &mut callback.profile {
+                profile.end_phase("serialization");
+            }
         }
+        // Execute the function given to `--exec` with the LLBC interpreter
+        if let (Some(name), Ok(()), CrateData::LLBC(krate)) =
+            (&callback.options.exec, &res, crate_data)
+        {
+            match interp::exec(krate, name, &callback.options.exec_args) {
+                Ok(value) => println!("{value}"),
+                Err(error) => {
+                    log::error!("{}", error);
+                    std::process::exit(error.exit_code());
+                }
+            }
+        }
+    }
+    // We generate the report even if the extraction generated errors: this is
+    // precisely when it is useful.
+    if let (Some(report), Some(report_file)) = (&callback.report, &callback.options.report) {
+        let report_res = report.serialize_to_file(report_file);
+        res = res.and(report_res);
+    }
+    if let Some(profile) = &callback.profile {
+        log::info!("# Profile:\n\n{}", profile);
     }
 
     match res {
@@ -170,15 +240,19 @@ fn main() {
                 log::warn!("{}", msg);
             }
         }
-        Err(CharonFailure::Panic) => {
-            // Standard rust panic error code.
-            std::process::exit(101);
-        }
-        Err(CharonFailure::RustcError(_) | CharonFailure::Serialize) => {
+        Err(error @ CharonError::RustcError(_)) => {
             assert!(!errors_as_warnings);
             let msg = format!("The extraction encountered {} errors", callback.error_count);
             log::error!("{}", msg);
-            std::process::exit(1);
+            std::process::exit(error.exit_code());
+        }
+        Err(error @ CharonError::Internal(_)) => {
+            // The panic message has already been printed.
+            std::process::exit(error.exit_code());
+        }
+        Err(error) => {
+            log::error!("{}", error);
+            std::process::exit(error.exit_code());
         }
     }
 }
@@ -1,4 +1,4 @@
-use clap::Parser;
+use clap::{ArgEnum, Parser};
 /// The options received as input by cargo-charon
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
@@ -26,6 +26,35 @@ pub struct CliOpts {
     #[clap(long = "bin")]
     #[serde(default)]
     pub bin: Option<String>,
+    /// The target triple to compile the crate for (for instance:
+    /// `aarch64-unknown-linux-gnu`). Defaults to the host target. Information
+    /// about the target (pointer width, endianness, etc.) is recorded in the
+    /// exported crate data.
+    #[clap(long = "target")]
+    #[serde(default)]
+    pub target: Option<String>,
+    /// The features of the crate to activate (forwarded to cargo). Can be given
+    /// several times. The enabled features are recorded in the exported crate
+    /// data.
+    #[clap(long = "features")]
+    #[serde(default)]
+    pub features: Vec<String>,
+    /// Don't activate the default features of the crate (forwarded to cargo)
+    #[clap(long = "no-default-features")]
+    #[serde(default)]
+    pub no_default_features: bool,
+    /// Compile the crate with the release profile. This is the default: we
+    /// analyze the released code, in which rustc doesn't insert the overflow
+    /// checks and the debug assertions.
+    #[clap(long = "release")]
+    #[serde(default)]
+    pub release: bool,
+    /// Compile the crate with the given cargo profile (for instance: `dev`)
+    /// rather than with the release profile. The profile is recorded in the
+    /// exported crate data.
+    #[clap(long = "cargo-profile")]
+    #[serde(default)]
+    pub cargo_profile: Option<String>,
     /// Extract the promoted MIR instead of the built MIR
     #[clap(long = "mir_promoted")]
     #[serde(default)]
@@ -34,6 +63,20 @@ pub struct CliOpts {
     #[clap(long = "mir_optimized")]
     #[serde(default)]
     pub mir_optimized: bool,
+    /// Do not erase the regions in the function bodies: we retrieve the region
+    /// variables and the outlives constraints computed by the borrow checker.
+    /// As the borrow checker works on the promoted MIR, this implies
+    /// `--mir_promoted`. Note that we only do this for the local bodies.
+    #[clap(long = "body-regions")]
+    #[serde(default)]
+    pub body_regions: bool,
+    /// Export the facts computed by the borrow checker for the local bodies:
+    /// the loans, the constraints between the regions at every location, and
+    /// the locations where the loans are killed, invalidated or go out of
+    /// scope. This implies `--body-regions`.
+    #[clap(long = "borrowck-facts")]
+    #[serde(default)]
+    pub borrowck_facts: bool,
     /// Provide a custom name for the compiled crate (ignore the name computed
     /// by Cargo)
     #[clap(long = "crate")]
@@ -45,6 +88,34 @@ pub struct CliOpts {
     #[clap(long = "input", parse(from_os_str))]
     #[serde(default)]
     pub input_file: Option<PathBuf>,
+    /// A standalone Rust file to extract, instead of the crate in the current
+    /// directory. We then compile the file as a library by calling rustc
+    /// directly, without going through cargo (see `--edition` and `--cfg`):
+    /// this is convenient for the regression tests and the quick experiments.
+    #[clap(parse(from_os_str))]
+    #[serde(default)]
+    pub file: Option<PathBuf>,
+    /// The edition of the standalone file to extract (defaults to 2021).
+    #[clap(long = "edition")]
+    #[serde(default)]
+    pub edition: Option<String>,
+    /// A configuration option to set when compiling the crate (for instance:
+    /// `--cfg 'feature="std"'`), as with the `--cfg` option of rustc. Can be
+    /// given several times.
+    #[clap(long = "cfg")]
+    #[serde(default)]
+    pub cfgs: Vec<String>,
+    /// Also extract the build scripts and the proc-macro crates of the
+    /// workspace. By default, we compile those crates (cargo needs them to
+    /// build the crate we extract) without extracting them.
+    #[clap(long = "extract-build-crates")]
+    #[serde(default)]
+    pub extract_build_crates: bool,
+    /// Extract the crate, then extract it again every time one of its source
+    /// files changes. We only rewrite the output files whose content changed.
+    #[clap(long = "watch")]
+    #[serde(default)]
+    pub watch: bool,
     /// The destination directory. Files will be generated as `<dest_dir>/<crate_name>.{u}llbc`,
     /// unless `dest_file` is set. `dest_dir` defaults to the current directory.
     #[clap(long = "dest", parse(from_os_str))]
@@ -55,6 +126,66 @@ pub struct CliOpts {
     #[clap(long = "dest-file", parse(from_os_str))]
     #[serde(default)]
     pub dest_file: Option<PathBuf>,
+    /// Split the output: generate one file per declaration group in the given
+    /// directory, together with an index file (`index.json`). If this is set we
+    /// ignore `dest_dir` and `dest_file`.
+    #[clap(long = "split-output", parse(from_os_str))]
+    #[serde(default)]
+    pub split_output: Option<PathBuf>,
+    /// Compress the output file with zstd: we add the `.zst` extension to the
+    /// name of the output file (if it doesn't already have it).
+    #[clap(long = "compress")]
+    #[serde(default)]
+    pub compress: bool,
+    /// Write the crate data to the standard output instead of a file. The logs
+    /// are printed on the standard error, so this allows using Charon in a pipe.
+    /// If this is set we ignore `dest_dir` and `dest_file`.
+    #[clap(long = "stdout")]
+    #[serde(default)]
+    pub stdout: bool,
+    /// Include the content of the source files in the `id_to_file` table of the
+    /// output file, so that the consumers can quote the original code without
+    /// access to the sources (this is not available for the external crates).
+    #[clap(long = "include-sources")]
+    #[serde(default)]
+    pub include_sources: bool,
+    /// Export the crate in canonical form: we renumber the declarations in the
+    /// order of their names, and the files in the order of their names (which
+    /// we make relative to the directory containing the local files). This
+    /// makes the output easier to cache and to compare.
+    #[clap(long = "canonical")]
+    #[serde(default)]
+    pub canonical: bool,
+    /// The format of the output file: `json` (the default), `sexp` to export the
+    /// crate data as S-expressions (with the same structure as the JSON), or
+    /// `protobuf` (if Charon was built with the `protobuf` feature).
+    #[clap(long = "format", arg_enum, default_value = "json")]
+    #[serde(default)]
+    pub format: OutputFormat,
+    /// Generate the OCaml definitions of the AST, together with the functions
+    /// to deserialize them, in the given directory (we don't extract any crate).
+    /// This helps keeping `charon-ml` in sync with the Rust definitions.
+    #[clap(long = "generate-ml", parse(from_os_str))]
+    #[serde(default)]
+    pub generate_ml: Option<PathBuf>,
+    /// Generate the protobuf schema of the crate data, together with the updated
+    /// numbering of its fields, in the given directory (we don't extract any crate).
+    #[cfg(feature = "protobuf")]
+    #[clap(long = "generate-proto", parse(from_os_str))]
+    #[serde(default)]
+    pub generate_proto: Option<PathBuf>,
+    /// Generate a report listing all the items we encountered, together with the
+    /// outcome of their extraction (translated, opaque, errored or skipped).
+    #[clap(long = "report", parse(from_os_str))]
+    #[serde(default)]
+    pub report: Option<PathBuf>,
+    /// Print the time spent and the peak memory used in every phase of the
+    /// extraction (the translation of the items, every micro-pass, the
+    /// serialization), together with the items which take the longest to
+    /// translate.
+    #[clap(long = "profile")]
+    #[serde(default)]
+    pub profile: bool,
     /// If activated, use Polonius' non-lexical lifetimes (NLL) analysis.
     /// Otherwise, use the standard borrow checker.
     #[clap(long = "polonius")]
@@ -104,6 +235,76 @@ performs: `y := (x as E2).1`). Producing a better reconstruction is non-trivial.
     )]
     #[serde(default)]
     pub no_code_duplication: bool,
+    /// Keep the bounds checks of the array and slice accesses inserted by the
+    /// compiler, as `Assert` statements (by default, we remove them, see
+    /// [crate::remove_dynamic_checks]).
+    #[clap(long = "keep-bounds-checks")]
+    #[serde(default)]
+    pub keep_bounds_checks: bool,
+    /// Keep the overflow checks of the shifts and of the signed divisions of
+    /// `MIN` by `-1` inserted by the compiler, as `Assert` statements. Note that
+    /// the checks of the additions, subtractions and multiplications are always
+    /// removed, because those operations panic on overflow in the LLBC.
+    #[clap(long = "keep-overflow-checks")]
+    #[serde(default)]
+    pub keep_overflow_checks: bool,
+    /// Keep the division by zero checks of the divisions and remainders
+    /// inserted by the compiler, as `Assert` statements.
+    #[clap(long = "keep-div-checks")]
+    #[serde(default)]
+    pub keep_div_checks: bool,
+    /// Keep the `StorageLive` and `StorageDead` statements of the MIR, as
+    /// statements of the ULLBC and of the LLBC (by default, we ignore the
+    /// `StorageLive` and translate the `StorageDead` to drops in the LLBC).
+    #[clap(long = "keep-storage-statements")]
+    #[serde(default)]
+    pub keep_storage_statements: bool,
+    /// Keep the redundant reborrows and copies, like `tmp := &*x; f(move tmp)`
+    /// (by default, we replace it with `f(copy x)`, see
+    /// [crate::transform::remove_redundant_copies]).
+    #[clap(long = "keep-redundant-copies")]
+    #[serde(default)]
+    pub keep_redundant_copies: bool,
+    /// Don't replace the associated types of the traits with the types they
+    /// are equal to, when the constraints of the declarations or the trait
+    /// implementations determine them (see
+    /// [crate::transform::normalize_trait_types]).
+    #[clap(long = "no-normalize-trait-types")]
+    #[serde(default)]
+    pub no_normalize_trait_types: bool,
+    /// Remove the fields of type `PhantomData<T>` from the structures and the
+    /// enumerations (see [crate::transform::remove_phantom_data]). The
+    /// variances of the type parameters are still given by the type
+    /// declarations.
+    #[clap(long = "remove-phantom-data")]
+    #[serde(default)]
+    pub remove_phantom_data: bool,
+    /// Translate the local type aliases, which are otherwise inlined, and
+    /// record the aliases the user wrote for the types of the fields and of the
+    /// signatures (see [crate::types::TypeAliasDecl]).
+    #[clap(long = "type-aliases")]
+    #[serde(default)]
+    pub type_aliases: bool,
+    /// Reconstruct the nested expressions, by inlining the temporaries which
+    /// are used only once (see [crate::transform::reconstruct_expressions]).
+    /// For instance, `tmp := copy b + copy c; d := move tmp * const 2` becomes
+    /// `d := (copy b + copy c) * const 2`.
+    #[clap(long = "reconstruct-expressions")]
+    #[serde(default)]
+    pub reconstruct_expressions: bool,
+    /// Propagate the literal constants through the locals, fold the operations
+    /// on constants and simplify the switches on constants (see
+    /// [crate::transform::propagate_constants]).
+    #[clap(long = "propagate-constants")]
+    #[serde(default)]
+    pub propagate_constants: bool,
+    /// Inline the calls to the functions which are not recursive, and whose
+    /// bodies contain at most the given number of statements or which are
+    /// marked `#[inline(always)]` (see [crate::transform::inline_functions]).
+    /// This is ignored with `--body-regions`.
+    #[clap(long = "inline-threshold")]
+    #[serde(default)]
+    pub inline_threshold: Option<usize>,
     /// A list of modules of the extracted crate that we consider as opaque: we
     /// extract only the signature information, without the definition content
     /// (of the functions, types, etc.).
@@ -115,6 +316,69 @@ performs: `y := (x as E2).1`). Producing a better reconstruction is non-trivial.
     #[clap(long = "extract-opaque-bodies")]
     #[serde(default)]
     pub extract_opaque_bodies: bool,
+    /// A list of patterns of external items whose bodies we extract (for
+    /// instance: `--extract-external-bodies 'core::option::*'` or
+    /// `--extract-external-bodies alloc::vec::Vec::push`). A `*` matches any
+    /// path element, and a `*` at the end of a pattern matches any non-empty
+    /// suffix. The methods of an inherent impl are named after their type. We
+    /// only extract the bodies of the dependencies of those items if they also
+    /// match a pattern.
+    #[clap(long = "extract-external-bodies")]
+    #[serde(default)]
+    pub extract_external_bodies: Vec<String>,
+    /// Use the bodies written by hand that Charon provides for a few common
+    /// functions of the standard library, like `Option::map`, `Vec::len` or
+    /// `<[T]>::get`, rather than leaving them opaque (see
+    /// [crate::builtin_models]).
+    #[clap(long = "use-builtin-models")]
+    #[serde(default)]
+    pub use_builtin_models: bool,
+    /// A list of patterns of functions (with the syntax of
+    /// `--extract-external-bodies`) whose calls we translate to assumptions,
+    /// in addition to `kani::assume`, `prusti::assume` and `charon::assume`.
+    #[clap(long = "verifier-assume")]
+    #[serde(default)]
+    pub verifier_assume: Vec<String>,
+    /// A list of patterns of functions whose calls we translate to assertions,
+    /// in addition to `kani::assert`, `prusti::assert` and `charon::assert`.
+    /// The functions take a condition and, optionally, a message.
+    #[clap(long = "verifier-assert")]
+    #[serde(default)]
+    pub verifier_assert: Vec<String>,
+    /// A list of patterns of functions whose calls we translate to
+    /// nondeterministic values of their return types, in addition to
+    /// `kani::any` and `charon::nondet`.
+    #[clap(long = "verifier-nondet")]
+    #[serde(default)]
+    pub verifier_nondet: Vec<String>,
+    /// A list of attributes which mark the verification entry points (for
+    /// instance: `--harness-attribute my_verifier::harness`), in addition to
+    /// `#[test]` and `#[kani::proof]`. We export the functions marked with
+    /// those attributes in the `harnesses` field of the crate.
+    #[clap(long = "harness-attribute")]
+    #[serde(default)]
+    pub harness_attributes: Vec<String>,
+    /// A list of traits whose clauses we filter, in addition to the builtin
+    /// marker traits like `core::marker::Sized` or `core::marker::Send` (for
+    /// instance: `--ignore-trait core::marker::Copy`).
+    #[clap(long = "ignore-trait")]
+    #[serde(default)]
+    pub ignored_traits: Vec<String>,
+    /// A list of builtin marker traits whose clauses we do not filter (for
+    /// instance: `--keep-trait core::marker::Sized`).
+    #[clap(long = "keep-trait")]
+    #[serde(default)]
+    pub kept_traits: Vec<String>,
+    /// Do not filter any trait clause (not even the clauses about builtin
+    /// marker traits). The trait clauses then have contiguous indices.
+    #[clap(long = "keep-all-trait-clauses")]
+    #[serde(default)]
+    pub keep_all_trait_clauses: bool,
+    /// A TOML file mapping path patterns to new names, to rename the items
+    /// before exporting them (see `RenameTable` in `transform/rename_items.rs`).
+    #[clap(long = "rename-table", parse(from_os_str))]
+    #[serde(default)]
+    pub rename_table: Option<PathBuf>,
     /// Do not provide a Rust version argument to Cargo (e.g., `+nightly-2022-01-29`).
     /// This is for Nix: outside of Nix, we use Rustup to call the proper version
     /// of Cargo (and thus need this argument), but within Nix we build and call a very
@@ -167,6 +431,65 @@ Print the final LLBC (after all the cleaning micro-passes).
     )]
     #[serde(default)]
     pub print_llbc: bool,
+    #[clap(
+        long = "validate",
+        help = "
+Check that the bodies are well-formed after every micro-pass, and report an
+error otherwise (this is used to debug the micro-passes).
+"
+    )]
+    #[serde(default)]
+    pub validate: bool,
+    /// Execute the given function of the crate with the LLBC interpreter (see
+    /// `charon exec`), and print the value it returns. This implies
+    /// `--no-serialize`.
+    #[clap(long = "exec")]
+    #[serde(default)]
+    pub exec: Option<String>,
+    /// An argument for the function given to `--exec` (an integer, a boolean
+    /// or a character). Can be used several times.
+    #[clap(long = "exec-arg", allow_hyphen_values = true)]
+    #[serde(default)]
+    pub exec_args: Vec<String>,
+}
+
+/// The options of `charon exec`.
+#[derive(Debug, Parser)]
+#[clap(name = "charon exec")]
+pub struct ExecOpts {
+    /// The directory of the crate.
+    #[clap(parse(from_os_str))]
+    pub crate_dir: PathBuf,
+    /// The function to execute (for instance: `my_crate::module::f`).
+    pub function: String,
+    /// The arguments of the function (integers, booleans or characters).
+    #[clap(allow_hyphen_values = true)]
+    pub args: Vec<String>,
+}
+
+impl ExecOpts {
+    /// The options we give to the driver: we extract the LLBC of the crate,
+    /// without serializing it, then execute the function.
+    pub fn to_cli_opts(&self) -> CliOpts {
+        CliOpts {
+            exec: Some(self.function.clone()),
+            exec_args: self.args.clone(),
+            no_serialize: true,
+            ..CliOpts::default()
+        }
+    }
+}
+
+/// The format of the output file (see the `--format` option).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ArgEnum, Serialize, Deserialize)]
+pub enum OutputFormat {
+    #[default]
+    Json,
+    /// S-expressions, with the same structure as the JSON.
+    Sexp,
+    /// The protobuf format, following the schema generated with `--generate-proto`.
+    #[cfg(feature = "protobuf")]
+    Protobuf,
 }
 
 /// The name of the environment variable we use to save the serialized Cli options
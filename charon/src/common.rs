@@ -1,3 +1,5 @@
+pub use crate::errors::CharonError;
+
 pub static TAB_INCR: &str = "    ";
 
 /// Common error used during the translation.
@@ -7,6 +9,12 @@ pub struct Error {
     pub msg: String,
 }
 
+impl From<Error> for CharonError {
+    fn from(e: Error) -> Self {
+        CharonError::Unsupported(e.msg)
+    }
+}
+
 /// Custom function to pretty-print elements from an iterator
 /// The output format is:
 /// ```text
@@ -50,7 +50,7 @@ impl Graph {
     }
 }
 
-impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
+impl<'ctx> ErrorCtx<'ctx> {
     /// In case errors happened when extracting the definitions coming from
     /// the external dependencies, print a detailed report to explain
     /// to the user which dependencies were problematic, and where they
@@ -89,7 +89,7 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
         // We need to compute the reachability graph. An easy way is simply
         // to use Dijkstra on every external definition which triggered an
         // error.
-        for id in &self.decls_with_errors {
+        for id in self.decls_with_errors.keys() {
             if !id.is_local() {
                 let reachable = dijkstra(&graph.dgraph, Node::External(*id), None, &mut |_| 1);
                 trace!("id: {:?}\nreachable:\n{:?}", id, reachable);
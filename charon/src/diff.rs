@@ -0,0 +1,525 @@
+//! The `charon diff` subcommand: compare two crates exported by Charon, and
+//! report the items which were added, removed or changed (see [diff_crates]).
+//!
+//! We compare the JSON values directly. We match the declarations of the two
+//! crates with their stable ids (see the `stable_id` field of `ItemMeta`), and
+//! before comparing them we replace the references to the other declarations
+//! with those stable ids, so that we ignore the renumbering of the
+//! declarations (see [normalize_decl]). We also ignore the spans.
+//!
+//! Remark: this module is also compiled with the `charon` binary, which doesn't
+//! link with the rest of the library: we can only depend on external crates.
+use clap::Parser;
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// The options of `charon diff`.
+#[derive(Debug, Parser)]
+#[clap(name = "charon diff")]
+pub struct DiffOpts {
+    /// The old version of the crate (a `.llbc` or `.ullbc` file).
+    #[clap(parse(from_os_str))]
+    pub old: PathBuf,
+    /// The new version of the crate.
+    #[clap(parse(from_os_str))]
+    pub new: PathBuf,
+}
+
+/// The kinds of declarations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum DeclKind {
+    Type,
+    Fun,
+    Global,
+    TraitDecl,
+    TraitImpl,
+}
+
+impl DeclKind {
+    pub(crate) const ALL: [DeclKind; 5] = [
+        DeclKind::Type,
+        DeclKind::Fun,
+        DeclKind::Global,
+        DeclKind::TraitDecl,
+        DeclKind::TraitImpl,
+    ];
+
+    /// The field of the crate in which we store the declarations of this kind.
+    pub(crate) fn field(self) -> &'static str {
+        match self {
+            DeclKind::Type => "types",
+            DeclKind::Fun => "functions",
+            DeclKind::Global => "globals",
+            DeclKind::TraitDecl => "trait_decls",
+            DeclKind::TraitImpl => "trait_impls",
+        }
+    }
+}
+
+impl fmt::Display for DeclKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self {
+            DeclKind::Type => "type",
+            DeclKind::Fun => "function",
+            DeclKind::Global => "global",
+            DeclKind::TraitDecl => "trait",
+            DeclKind::TraitImpl => "trait impl",
+        };
+        write!(f, "{kind}")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    Added,
+    Removed,
+    /// The declaration changed: we list the fields which changed (`signature`,
+    /// `body`, etc.).
+    Changed(Vec<String>),
+}
+
+/// The difference between the two versions of a declaration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ItemDiff {
+    pub kind: DeclKind,
+    pub name: String,
+    pub change: Change,
+}
+
+impl fmt::Display for ItemDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.change {
+            Change::Added => write!(f, "added {} {}", self.kind, self.name),
+            Change::Removed => write!(f, "removed {} {}", self.kind, self.name),
+            Change::Changed(fields) => {
+                write!(
+                    f,
+                    "changed {} {} ({})",
+                    self.kind,
+                    self.name,
+                    fields.join(", ")
+                )
+            }
+        }
+    }
+}
+
+/// Format a name (a list of path elements).
+fn name_to_string(name: &Value) -> String {
+    let Some(elems) = name.as_array() else {
+        return "<unknown>".to_string();
+    };
+    let elem_to_string = |elem: &Value| -> String {
+        let Some(Value::Array(ident)) = elem.get("Ident") else {
+            return "{impl}".to_string();
+        };
+        match ident.as_slice() {
+            [Value::String(ident), d] if d.as_u64() == Some(0) => ident.clone(),
+            [Value::String(ident), d] => format!("{ident}#{d}"),
+            _ => "<unknown>".to_string(),
+        }
+    };
+    let elems: Vec<String> = elems.iter().map(elem_to_string).collect();
+    elems.join("::")
+}
+
+/// The declarations of a crate.
+struct Decls<'a> {
+    /// The keys of the declarations, indexed by their numeric ids. We use the
+    /// stable ids of the declarations, or their names if they don't have any.
+    keys: HashMap<(DeclKind, u64), String>,
+    /// The declarations, and their names.
+    decls: BTreeMap<(DeclKind, String), (String, &'a Map<String, Value>)>,
+}
+
+/// The fields we ignore when comparing the declarations: the spans, the ids of
+/// the declarations themselves, and their hashes (which are computed from the
+/// rest of the declarations).
+const IGNORED_FIELDS: [&str; 5] = ["meta", "span", "def_id", "stable_id", "hash"];
+
+/// If the variant of an enumeration (we use the default, externally tagged,
+/// representation) contains the id of a declaration, return its kind and its
+/// position in the fields of the variant (`None` if the id is the only field).
+fn variant_decl_ref(variant: &str, fields: &Value) -> Option<(DeclKind, Option<usize>)> {
+    let num_fields = fields.as_array().map(Vec::len);
+    let decl_ref = match (variant, num_fields) {
+        // The variants with a single field
+        ("Adt", None) => (DeclKind::Type, None),
+        ("Regular" | "Fun", None) => (DeclKind::Fun, None),
+        ("Global" | "MutStatic", None) => (DeclKind::Global, None),
+        ("TraitImpl" | "DefaultMethod", None) => (DeclKind::TraitImpl, None),
+        ("BuiltinOrAuto", None) => (DeclKind::TraitDecl, None),
+        // The variants with several fields
+        ("ProjAdt", Some(_)) => (DeclKind::Type, Some(0)),
+        ("Global", Some(_)) => (DeclKind::Global, Some(0)),
+        ("Closure" | "FnDef", Some(_)) => (DeclKind::Fun, Some(0)),
+        ("Unsolved" | "TraitItemDecl" | "TraitItemProvided", Some(_)) => {
+            (DeclKind::TraitDecl, Some(0))
+        }
+        ("ParentClause" | "ItemClause", Some(_)) => (DeclKind::TraitDecl, Some(1)),
+        ("Discriminant", Some(_)) => (DeclKind::Type, Some(1)),
+        // A call to a trait method: the trait ref, the name of the method and its id
+        ("Trait", Some(3)) => (DeclKind::Fun, Some(2)),
+        _ => return None,
+    };
+    Some(decl_ref)
+}
+
+impl<'a> Decls<'a> {
+    fn new(krate: &'a Value) -> Result<Self, String> {
+        let mut keys = HashMap::new();
+        let mut decls = BTreeMap::new();
+        for kind in DeclKind::ALL {
+            let Some(kind_decls) = krate.get(kind.field()).and_then(Value::as_array) else {
+                return Err(format!("Missing field: `{}`", kind.field()));
+            };
+            for decl in kind_decls {
+                let Some(decl) = decl.as_object() else {
+                    return Err(format!("Malformed declaration in `{}`", kind.field()));
+                };
+                let Some(id) = decl.get("def_id").and_then(Value::as_u64) else {
+                    return Err(format!("Missing id in `{}`", kind.field()));
+                };
+                let name = decl.get("name").map(name_to_string).unwrap_or_default();
+                let key = match decl.get("item_meta").and_then(|m| m.get("stable_id")) {
+                    Some(Value::String(stable_id)) => stable_id.clone(),
+                    _ => name.clone(),
+                };
+                keys.insert((kind, id), key.clone());
+                decls.insert((kind, key), (name, decl));
+            }
+        }
+        Ok(Decls { keys, decls })
+    }
+
+    fn normalize(&self, kind: DeclKind, decl: &Map<String, Value>) -> Value {
+        normalize_decl(kind, &Value::Object(decl.clone()), &self.keys)
+    }
+}
+
+/// Normalize a value, by removing the ignored fields and replacing the
+/// references to declarations with their keys (given by `keys`). We compare
+/// the normalized declarations, and also use them to compute the hashes of
+/// the declarations.
+pub fn normalize(value: &Value, keys: &HashMap<(DeclKind, u64), String>) -> Value {
+    map_decl_refs(value, &IGNORED_FIELDS, &|kind, id| decl_key(kind, id, keys))
+}
+
+/// The key of a declaration in the normalized values (see [normalize]).
+fn decl_key(kind: DeclKind, id: u64, keys: &HashMap<(DeclKind, u64), String>) -> Value {
+    match keys.get(&(kind, id)) {
+        Some(key) => Value::String(format!("{kind}:{key}")),
+        // This should not happen if the crate is well-formed
+        None => Value::String(format!("{kind}#{id}")),
+    }
+}
+
+/// Normalize a declaration of kind `kind` (see [normalize]). Its `rec_group`
+/// field lists declarations of the same kind as the declaration itself: we
+/// normalize it here, because the field doesn't tell their kind.
+pub fn normalize_decl(
+    kind: DeclKind,
+    value: &Value,
+    keys: &HashMap<(DeclKind, u64), String>,
+) -> Value {
+    let mut value = normalize(value, keys);
+    if let Some(Value::Array(ids)) = value.get_mut("rec_group") {
+        for id in ids.iter_mut() {
+            if let Some(n) = id.as_u64() {
+                *id = decl_key(kind, n, keys);
+            }
+        }
+        ids.sort_by(|id0, id1| id0.as_str().cmp(&id1.as_str()));
+    }
+    value
+}
+
+/// Rebuild a value, by replacing the references to declarations (given by
+/// their kinds and numeric ids) with the result of `f`, and by removing the
+/// `ignored` fields of the objects.
+pub fn map_decl_refs(value: &Value, ignored: &[&str], f: &dyn Fn(DeclKind, u64) -> Value) -> Value {
+    let map = |value: &Value| map_decl_refs(value, ignored, f);
+    let map_ref = |kind: DeclKind, id: &Value| match id.as_u64() {
+        Some(id) => f(kind, id),
+        None => map(id),
+    };
+    // Map a list of pairs `(name, x)`, by applying `g` to every `x`.
+    let map_pairs = |pairs: &Value, g: &dyn Fn(&Value) -> Value| match pairs.as_array() {
+        Some(pairs) => Value::Array(
+            pairs
+                .iter()
+                .map(|pair| match pair.as_array().map(Vec::as_slice) {
+                    Some([name, x]) => Value::Array(vec![name.clone(), g(x)]),
+                    _ => map(pair),
+                })
+                .collect(),
+        ),
+        None => map(pairs),
+    };
+    match value {
+        Value::Array(values) => Value::Array(values.iter().map(map).collect()),
+        Value::Object(fields) => {
+            // An enumeration variant which contains the id of a declaration
+            if fields.len() == 1 {
+                let (variant, variant_fields) = fields.iter().next().unwrap();
+                if let Some((kind, pos)) = variant_decl_ref(variant, variant_fields) {
+                    let variant_fields = match pos {
+                        None => map_ref(kind, variant_fields),
+                        Some(pos) => {
+                            let variant_fields = variant_fields.as_array().unwrap();
+                            let variant_fields =
+                                variant_fields.iter().enumerate().map(|(i, field)| {
+                                    if i == pos {
+                                        map_ref(kind, field)
+                                    } else {
+                                        map(field)
+                                    }
+                                });
+                            Value::Array(variant_fields.collect())
+                        }
+                    };
+                    let mut fields = Map::new();
+                    fields.insert(variant.clone(), variant_fields);
+                    return Value::Object(fields);
+                }
+            }
+
+            let mut mapped = Map::new();
+            for (field, value) in fields {
+                if ignored.contains(&field.as_str()) {
+                    continue;
+                }
+                let value = match field.as_str() {
+                    "trait_id" => map_ref(DeclKind::TraitDecl, value),
+                    "impl_id" => map_ref(DeclKind::TraitImpl, value),
+                    "specializing_impls" => match value.as_array() {
+                        Some(ids) => Value::Array(
+                            ids.iter()
+                                .map(|id| map_ref(DeclKind::TraitImpl, id))
+                                .collect(),
+                        ),
+                        None => map(value),
+                    },
+                    // The globals of the promoted constants of a body (the
+                    // globals themselves have a `promoted` field which is not
+                    // a list)
+                    "init_deps" | "promoted" => match value.as_array() {
+                        Some(ids) => Value::Array(
+                            ids.iter()
+                                .map(|id| map_ref(DeclKind::Global, id))
+                                .collect(),
+                        ),
+                        None => map(value),
+                    },
+                    "call_mut_shim" | "call_once_shim" => map_ref(DeclKind::Fun, value),
+                    // The items of the trait declarations and implementations
+                    "required_methods" => map_pairs(value, &|id| map_ref(DeclKind::Fun, id)),
+                    "provided_methods" => map_pairs(value, &|x| match x {
+                        // In the trait implementations, we also store a boolean
+                        Value::Array(x) if x.len() == 2 => {
+                            Value::Array(vec![map_ref(DeclKind::Fun, &x[0]), x[1].clone()])
+                        }
+                        _ => map_ref(DeclKind::Fun, x),
+                    }),
+                    "consts" => map_pairs(value, &|x| match x {
+                        Value::Array(x) if x.len() == 2 => {
+                            Value::Array(vec![map(&x[0]), map_ref(DeclKind::Global, &x[1])])
+                        }
+                        _ => map(x),
+                    }),
+                    _ => map(value),
+                };
+                mapped.insert(field.clone(), value);
+            }
+            Value::Object(mapped)
+        }
+        Value::Null | Value::Bool(_) | Value::Number(_) | Value::String(_) => value.clone(),
+    }
+}
+
+/// A step of a path in a serialized crate (see [map_symbols]).
+#[derive(Debug, Clone, Copy)]
+enum Step {
+    /// A field of a structure.
+    Field(&'static str),
+    /// The content of a variant of an enumeration (we use the default,
+    /// externally tagged, representation).
+    Variant(&'static str),
+    /// An element of a tuple.
+    Index(usize),
+    /// All the elements of a list.
+    Elements,
+}
+
+use Step::*;
+
+/// The fields of the crate which contain named declarations.
+const NAMED_DECLS: [&str; 7] = [
+    "types",
+    "functions",
+    "globals",
+    "trait_decls",
+    "trait_impls",
+    "modules",
+    "type_aliases",
+];
+
+/// Apply `f` to the values at the end of a path. We skip the `null` values
+/// (the names of the fields are optional).
+fn map_path(value: &mut Value, path: &[Step], f: &mut dyn FnMut(&mut Value)) {
+    let Some((step, path)) = path.split_first() else {
+        if !value.is_null() {
+            f(value)
+        }
+        return;
+    };
+    let next = match (step, value) {
+        (Field(field), Value::Object(fields)) => fields.get_mut(*field),
+        (Variant(variant), Value::Object(fields)) if fields.len() == 1 => fields.get_mut(*variant),
+        (Index(i), Value::Array(values)) => values.get_mut(*i),
+        (Elements, Value::Array(values)) => {
+            values.iter_mut().for_each(|v| map_path(v, path, f));
+            None
+        }
+        _ => None,
+    };
+    if let Some(next) = next {
+        map_path(next, path, f)
+    }
+}
+
+/// Apply `f` to the symbols of a serialized crate (see [crate::symbol]): the
+/// identifiers of the names (see [crate::names::PathElem]), and the names of
+/// the fields and of the variants (see [crate::types::Field] and
+/// [crate::types::Variant]). We follow the types which contain symbols, rather
+/// than guessing the symbols from the shapes of the values, which change with
+/// the types: the paths must be updated whenever we add symbols to the
+/// serialized types (the `string_table` test checks that we find all of them).
+fn map_symbols(krate: &mut Value, f: &mut dyn FnMut(&mut Value)) {
+    let name: &[Step] = &[Field("name"), Elements, Variant("Ident"), Index(0)];
+    for field in NAMED_DECLS {
+        map_path(krate, &[Field(field), Elements], &mut |decl| map_path(decl, name, f));
+    }
+    let kinds: &[Step] = &[Field("types"), Elements, Field("kind")];
+    let variants: &[Step] = &[Variant("Enum"), Index(0), Elements];
+    map_path(krate, kinds, &mut |kind| {
+        map_path(kind, &[Variant("Struct"), Elements, Field("name")], f);
+        map_path(kind, variants, &mut |variant| {
+            map_path(variant, &[Field("name")], f);
+            map_path(variant, &[Field("fields"), Elements, Field("name")], f);
+        });
+    });
+}
+
+/// Replace the symbols of a serialized crate (which are indices in its string
+/// table, see [crate::symbol]) with their strings, and remove the string table.
+pub fn resolve_symbols(krate: &mut Value) {
+    let Some(Value::Array(strings)) = krate.as_object_mut().and_then(|k| k.remove("strings"))
+    else {
+        return;
+    };
+    map_symbols(krate, &mut |symbol| {
+        if let Some(s) = symbol.as_u64().and_then(|i| strings.get(i as usize)) {
+            *symbol = s.clone()
+        }
+    })
+}
+
+/// Compare two exported crates, whose symbols have been resolved (see
+/// [resolve_symbols]).
+pub fn diff_crates(old: &Value, new: &Value) -> Result<Vec<ItemDiff>, String> {
+    if old.get("format_version") != new.get("format_version") {
+        return Err("The crates were exported by incompatible versions of Charon".to_string());
+    }
+    let old_decls = Decls::new(old)?;
+    let new_decls = Decls::new(new)?;
+
+    let mut diffs = Vec::new();
+    for ((kind, key), (name, old_decl)) in &old_decls.decls {
+        let Some((_, new_decl)) = new_decls.decls.get(&(*kind, key.clone())) else {
+            diffs.push(ItemDiff {
+                kind: *kind,
+                name: name.clone(),
+                change: Change::Removed,
+            });
+            continue;
+        };
+        // Compare the fields one by one, to report which parts of the
+        // declaration changed
+        let old_normalized = old_decls.normalize(*kind, old_decl);
+        let new_normalized = new_decls.normalize(*kind, new_decl);
+        let mut fields: Vec<&String> = old_decl.keys().chain(new_decl.keys()).collect();
+        fields.sort();
+        fields.dedup();
+        let changed: Vec<String> = fields
+            .into_iter()
+            .filter(|field| !IGNORED_FIELDS.contains(&field.as_str()))
+            .filter(|field| old_normalized.get(*field) != new_normalized.get(*field))
+            .cloned()
+            .collect();
+        if !changed.is_empty() {
+            diffs.push(ItemDiff {
+                kind: *kind,
+                name: name.clone(),
+                change: Change::Changed(changed),
+            });
+        }
+    }
+    for ((kind, key), (name, _)) in &new_decls.decls {
+        if !old_decls.decls.contains_key(&(*kind, key.clone())) {
+            diffs.push(ItemDiff {
+                kind: *kind,
+                name: name.clone(),
+                change: Change::Added,
+            });
+        }
+    }
+    diffs.sort_by(|d0, d1| (d0.kind, &d0.name).cmp(&(d1.kind, &d1.name)));
+    Ok(diffs)
+}
+
+/// The magic number at the beginning of the zstd frames.
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// Read an exported crate, and resolve its symbols. We transparently
+/// decompress the files compressed with `--compress`.
+fn read_crate(path: &Path) -> Result<Value, String> {
+    let error = |error: &dyn fmt::Display| format!("{}: {}", path.display(), error);
+    let content = std::fs::read(path).map_err(|e| error(&e))?;
+    let content = if content.starts_with(&ZSTD_MAGIC) {
+        zstd::decode_all(content.as_slice()).map_err(|e| error(&e))?
+    } else {
+        content
+    };
+    let mut krate = serde_json::from_slice(&content).map_err(|e| error(&e))?;
+    resolve_symbols(&mut krate);
+    Ok(krate)
+}
+
+/// Compare two exported crates, given their files.
+pub fn diff_files(old: &Path, new: &Path) -> Result<Vec<ItemDiff>, String> {
+    diff_crates(&read_crate(old)?, &read_crate(new)?)
+}
+
+/// Run `charon diff`, and return the exit code: like `diff`, we return 0 if
+/// the crates are the same, 1 if they are different and 2 if there was an error.
+pub fn run(options: &DiffOpts) -> i32 {
+    match diff_files(&options.old, &options.new) {
+        Ok(diffs) => {
+            for diff in &diffs {
+                println!("{diff}");
+            }
+            if diffs.is_empty() {
+                0
+            } else {
+                1
+            }
+        }
+        Err(msg) => {
+            log::error!("{}", msg);
+            2
+        }
+    }
+}
@@ -14,8 +14,8 @@ fn statement_diverges(divergent: &HashMap<ast::FunDeclId::Id, bool>, st: &llbc::
         | RawStatement::Assert(_)
         | RawStatement::Panic
         | RawStatement::Return
-        | RawStatement::Break(_)
-        | RawStatement::Continue(_)
+        | RawStatement::Break(..)
+        | RawStatement::Continue(..)
         | RawStatement::Nop => false,
         RawStatement::Call(call) => match &call.func {
             ast::FunId::Regular(id) => *divergent.get(id).unwrap(),
@@ -44,7 +44,7 @@ fn statement_diverges(divergent: &HashMap<ast::FunDeclId::Id, bool>, st: &llbc::
             let tgts = switch.get_targets();
             tgts.iter().any(|st| statement_diverges(divergent, st))
         }
-        RawStatement::Loop(_) => true,
+        RawStatement::Loop(..) => true,
     }
 }
 
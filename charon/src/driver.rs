@@ -1,22 +1,33 @@
 use crate::cli_options;
+use crate::common::CharonError;
 use crate::export;
+use crate::gast::IntrinsicKind;
 use crate::get_mir::MirLevel;
+use crate::llbc_ast;
+use crate::profile;
 use crate::reorder_decls;
+use crate::report;
+use crate::transform::llbc_passes::LlbcPass;
 use crate::transform::{
-    index_to_function_calls, insert_assign_return_unit, ops_to_function_calls, reconstruct_asserts,
-    remove_drop_never, remove_dynamic_checks, remove_nops, remove_read_discriminant,
-    remove_unused_locals, simplify_constants, update_closure_signatures,
+    compute_loop_info, compute_regions_graph, index_to_function_calls, inline_functions,
+    insert_assign_return_unit, insert_closure_shims, llbc_passes, make_cfg_reducible,
+    mark_tail_calls, normalize_trait_types, ops_to_function_calls, propagate_constants,
+    reconstruct_asserts, reconstruct_expressions, remove_dead_branches, remove_drop_never,
+    remove_dynamic_checks, remove_nops, remove_phantom_data, remove_read_discriminant,
+    remove_redundant_copies, remove_unit_locals, remove_unused_locals, rename_items,
+    simplify_constants, simplify_trait_refs, solve_trait_obligations, update_closure_signatures,
 };
 use crate::translate_crate_to_ullbc;
 use crate::translate_ctx;
 use crate::ullbc_to_llbc;
+use crate::validate;
 use regex::Regex;
 use rustc_driver::{Callbacks, Compilation};
 use rustc_interface::{interface::Compiler, Queries};
 use rustc_middle::ty::TyCtxt;
 use rustc_session::Session;
+use std::any::Any;
 use std::collections::HashSet;
-use std::fmt;
 use std::iter::FromIterator;
 use std::ops::Deref;
 use std::panic::{self, AssertUnwindSafe};
@@ -26,25 +37,29 @@ pub struct CharonCallbacks {
     pub options: cli_options::CliOpts,
     /// This is to be filled during the extraction
     pub crate_data: Option<export::CrateData>,
+    /// The extraction report, if the user asked for it (see [report::Report])
+    pub report: Option<report::Report>,
+    /// The profiling report, if the user asked for it (see [profile::Profile])
+    pub profile: Option<profile::Profile>,
     pub error_count: usize,
+    /// The error which made the translation fail, if any (we can't directly
+    /// return it from the callbacks).
+    pub error: Option<CharonError>,
+    /// The custom passes on the LLBC bodies (see [Self::register_llbc_pass])
+    pub llbc_passes: Vec<Box<dyn LlbcPass>>,
+    /// The patterns of the functions whose calls we translate to
+    /// domain-specific intrinsics (see [Self::register_intrinsic])
+    pub intrinsics: Vec<(String, IntrinsicKind)>,
 }
 
-pub enum CharonFailure {
-    RustcError(usize),
-    Panic,
-    Serialize,
-}
-
-impl fmt::Display for CharonFailure {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            CharonFailure::RustcError(error_count) => {
-                write!(f, "Compilation encountered {} errors", error_count)?
-            }
-            CharonFailure::Panic => write!(f, "Compilation panicked")?,
-            CharonFailure::Serialize => write!(f, "Could not serialize output file")?,
-        }
-        Ok(())
+/// Retrieve the message of a panic
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(msg) => *msg,
+        Err(payload) => match payload.downcast::<&str>() {
+            Ok(msg) => msg.to_string(),
+            Err(_) => "Compilation panicked".to_string(),
+        },
     }
 }
 
@@ -53,26 +68,87 @@ impl CharonCallbacks {
         Self {
             options,
             crate_data: None,
+            report: None,
+            profile: None,
             error_count: 0,
+            error: None,
+            llbc_passes: Vec::new(),
+            intrinsics: Vec::new(),
         }
     }
 
+    /// Translate the calls to the functions which match `pattern` (like
+    /// `my_dsl::assume`, or `verifier::*`: see `--extract-external-bodies` for
+    /// the syntax of the patterns) to [RawStatement::Intrinsic] statements of
+    /// the given kind, rather than to calls.
+    ///
+    /// [RawStatement::Intrinsic]: llbc_ast::RawStatement::Intrinsic
+    pub fn register_intrinsic(&mut self, pattern: &str, kind: IntrinsicKind) {
+        self.intrinsics.push((pattern.to_string(), kind));
+    }
+
+    /// Register a custom pass on the LLBC bodies (see [LlbcPass]). We run the
+    /// custom passes in the order in which they were registered.
+    pub fn register_llbc_pass(&mut self, pass: Box<dyn LlbcPass>) {
+        self.llbc_passes.push(pass);
+    }
+
     /// Run rustc with our custom callbacks. `args` is the arguments passed to `rustc`'s
     /// command-line.
-    pub fn run_compiler(&mut self, mut args: Vec<String>) -> Result<(), CharonFailure> {
+    pub fn run_compiler(&mut self, mut args: Vec<String>) -> Result<(), CharonError> {
         // Arguments list always start with the executable name. We put a silly value to ensure
         // it's not used for anything.
         args.insert(0, "__CHARON_MYSTERIOUS_FIRST_ARG__".to_string());
+        // Register the `charon` tool, so that rustc accepts the `#[charon::...]` attributes
+        // (see [translate_ctx::CharonAttr]).
+        args.push("-Zcrate-attr=feature(register_tool)".to_string());
+        args.push("-Zcrate-attr=register_tool(charon)".to_string());
         let mut this = AssertUnwindSafe(self);
         panic::catch_unwind(move || {
             let res = rustc_driver::RunCompiler::new(&args, *this).run();
-            res.map_err(|_| CharonFailure::RustcError(this.error_count))
+            if let Some(error) = this.error.take() {
+                return Err(error);
+            }
+            res.map_err(|_| CharonError::RustcError(this.error_count))
         })
-        .map_err(|_| CharonFailure::Panic)??;
+        .map_err(|payload| CharonError::Internal(panic_message(payload)))??;
         Ok(())
     }
 }
 
+/// The callbacks we use to compile the crates we don't extract (see
+/// [run_rustc]): we only count the errors.
+#[derive(Default)]
+struct CountErrors {
+    error_count: usize,
+}
+
+impl Callbacks for CountErrors {
+    /// Rustc doesn't call the callbacks after the analysis if it fails: we run
+    /// the analysis ourselves to count its errors. Its result is cached, so
+    /// rustc doesn't run it twice.
+    fn after_expansion<'tcx>(&mut self, c: &Compiler, queries: &'tcx Queries<'tcx>) -> Compilation {
+        queries.global_ctxt().unwrap().get_mut().enter(|tcx| {
+            let _ = tcx.analysis(());
+        });
+        self.error_count = c.session().err_count();
+        Compilation::Continue
+    }
+}
+
+/// Run rustc without our custom callbacks, i.e., simply compile the crate.
+/// `args` is the arguments passed to `rustc`'s command-line.
+pub fn run_rustc(mut args: Vec<String>) -> Result<(), CharonError> {
+    args.insert(0, "__CHARON_MYSTERIOUS_FIRST_ARG__".to_string());
+    let mut callbacks = CountErrors::default();
+    rustc_driver::RunCompiler::new(&args, &mut callbacks)
+        .run()
+        // If the compilation failed before the analysis (while parsing the
+        // crate) or after (while generating the code), rustc reported at
+        // least one error, which we didn't count
+        .map_err(|_| CharonError::RustcError(callbacks.error_count.max(1)))
+}
+
 impl Callbacks for CharonCallbacks {
     /// We have to be careful here: we can plug ourselves at several places
     /// (after parsing, after expansion, after analysis). However, the MIR is
@@ -85,17 +161,13 @@ impl Callbacks for CharonCallbacks {
     /// phases of the compilation process, we query the context as early as
     /// possible (i.e., after parsing). See [crate::get_mir].
     fn after_parsing<'tcx>(&mut self, c: &Compiler, queries: &'tcx Queries<'tcx>) -> Compilation {
-        queries
-            .global_ctxt()
-            .unwrap()
-            .get_mut()
-            .enter(|tcx| {
-                let session = c.session();
-                let crate_data = translate(session, tcx, self)?;
-                self.crate_data = Some(crate_data);
-                Ok::<(), ()>(())
-            })
-            .unwrap();
+        queries.global_ctxt().unwrap().get_mut().enter(|tcx| {
+            let session = c.session();
+            match translate(session, tcx, self) {
+                Ok(crate_data) => self.crate_data = Some(crate_data),
+                Err(error) => self.error = Some(error),
+            }
+        });
         Compilation::Stop
     }
 }
@@ -156,15 +228,25 @@ pub fn get_args_crate_index<T: Deref<Target = str>>(args: &[T]) -> Option<usize>
         })
 }
 
+/// Whether the arguments compile a build script or a proc-macro crate. Cargo
+/// calls the driver on those crates when they belong to the workspace: they are
+/// compiled (and executed, for the build scripts) before the crate we extract.
+pub fn is_build_crate<T: Deref<Target = str>>(args: &[T]) -> bool {
+    let is_proc_macro = arg_value(args, "--crate-type", |ty| ty == "proc-macro").is_some();
+    let is_build_script = arg_value(args, "--crate-name", |name| {
+        name.starts_with("build_script_")
+    });
+    is_proc_macro || is_build_script.is_some()
+}
+
 /// Translate a crate to LLBC (Low-Level Borrow Calculus).
 ///
 /// This function is a callback function for the Rust compiler.
-#[allow(clippy::result_unit_err)]
 pub fn translate(
     sess: &Session,
     tcx: TyCtxt,
     internal: &mut CharonCallbacks,
-) -> Result<export::CrateData, ()> {
+) -> Result<export::CrateData, CharonError> {
     trace!();
     let options = &internal.options;
 
@@ -182,12 +264,18 @@ pub fn translate(
     // Adjust the level of MIR we extract, depending on the options
     let mir_level = if options.mir_optimized {
         MirLevel::Optimized
-    } else if options.mir_promoted {
+    } else if options.mir_promoted || options.body_regions || options.borrowck_facts {
         MirLevel::Promoted
     } else {
         MirLevel::Built
     };
 
+    // Read the renaming table now, so that we fail early if it is malformed
+    let rename_table = match &options.rename_table {
+        Some(path) => Some(rename_items::RenameTable::from_file(path)?),
+        None => None,
+    };
+
     // Some important notes about crates and how to interact with rustc:
     // - when calling rustc, we should give it the root of the crate, for
     //   instance the "main.rs" file. From there, rustc will load all the
@@ -203,11 +291,9 @@ pub fn translate(
     // # Translate the declarations in the crate.
     // We translate the declarations in an ad-hoc order, and do not group
     // the mutually recursive groups - we do this in the next step.
+    let intrinsics = &internal.intrinsics;
     let mut ctx =
-        match translate_crate_to_ullbc::translate(crate_info, options, sess, tcx, mir_level) {
-            Ok(ctx) => ctx,
-            Err(_) => return Err(()),
-        };
+        translate_crate_to_ullbc::translate(crate_info, options, intrinsics, sess, tcx, mir_level)?;
 
     trace!("# After translation from MIR:\n\n{}\n", ctx);
 
@@ -215,12 +301,119 @@ pub fn translate(
         info!("# ULLBC after translation from MIR:\n\n{}\n", ctx);
     }
 
+    // # Micro-pass: the first local variable of closures is the
+    // closure itself. This is not consistent with the closure signature,
+    // which ignores this first variable. This micro-pass updates this.
+    update_closure_signatures::transform(&mut ctx.translated);
+    if let Some(profile) = &mut ctx.profile {
+        profile.end_phase("update_closure_signatures");
+    }
+
+    // # Generate the `call_mut` and `call_once` methods of the closures. We
+    // do this before reordering the declarations: those are new functions.
+    insert_closure_shims::transform(&mut ctx);
+    if let Some(profile) = &mut ctx.profile {
+        profile.end_phase("insert_closure_shims");
+    }
+
     // # Reorder the graph of dependencies and compute the strictly
     // connex components to:
     // - compute the order in which to extract the definitions
     // - find the recursive definitions
     // - group the mutually recursive definitions
     reorder_decls::reorder_declarations(&mut ctx);
+    if let Some(profile) = &mut ctx.profile {
+        profile.end_phase("reorder_decls");
+    }
+
+    // # Apply the micro-passes, and reconstruct the control-flow if the user
+    // wants the structured LLBC.
+    let custom_passes = &mut internal.llbc_passes;
+    let llbc_decls = transform(&mut ctx, options, rename_table.as_ref(), custom_passes);
+
+    // Update the error count
+    internal.error_count = ctx.errors.error_count;
+
+    // Compute the extraction report, if necessary. We must do this before
+    // building the crate data, which moves the declarations out of the context.
+    if internal.options.report.is_some() {
+        internal.report = Some(report::Report::new(&ctx));
+    }
+
+    let mut crate_data = match llbc_decls {
+        None => export::CrateData::new_ullbc(&mut ctx, crate_name),
+        Some((llbc_funs, llbc_globals)) => {
+            export::CrateData::new_llbc(&mut ctx, crate_name, llbc_funs, llbc_globals)
+        }
+    };
+    crate_data.set_canonical(options.canonical);
+    crate_data.set_format(options.format);
+    if options.include_sources {
+        crate_data.include_sources(&ctx);
+    }
+    if let Some(profile) = &mut ctx.profile {
+        profile.end_phase("construction of the crate data");
+    }
+    internal.profile = ctx.profile.take();
+    trace!("Done");
+
+    Ok(crate_data)
+}
+
+/// Apply the micro-passes to the translated crate. If the user asked for the
+/// structured LLBC, reconstruct the control-flow and return the LLBC bodies.
+///
+/// With `--validate`, we check that the bodies are well-formed after every
+/// pass (see [validate]). With `--profile`, we record the time spent in every
+/// pass (see [profile]). We also run the custom passes given in
+/// `custom_passes` (see [LlbcPass]).
+pub fn transform(
+    ctx: &mut translate_ctx::TransCtx,
+    options: &cli_options::CliOpts,
+    rename_table: Option<&rename_items::RenameTable>,
+    custom_passes: &mut [Box<dyn LlbcPass>],
+) -> Option<(llbc_ast::FunDecls, llbc_ast::GlobalDecls)> {
+    // We take the profile out of the context, as the passes borrow the context
+    let mut profile = ctx.profile.take();
+    let llbc_decls = transform_passes(ctx, options, rename_table, custom_passes, &mut profile);
+    ctx.profile = profile;
+    llbc_decls
+}
+
+fn transform_passes(
+    ctx: &mut translate_ctx::TransCtx,
+    options: &cli_options::CliOpts,
+    rename_table: Option<&rename_items::RenameTable>,
+    custom_passes: &mut [Box<dyn LlbcPass>],
+    profile: &mut Option<profile::Profile>,
+) -> Option<(llbc_ast::FunDecls, llbc_ast::GlobalDecls)> {
+    // Record the time spent in the last pass
+    macro_rules! profile_pass {
+        ($pass:expr) => {
+            if let Some(profile) = profile.as_mut() {
+                profile.end_phase($pass)
+            }
+        };
+    }
+    // Called after every pass: we don't count the validation in the time spent
+    // in the passes
+    macro_rules! validate_ullbc {
+        ($pass:expr) => {
+            profile_pass!($pass);
+            if options.validate {
+                validate::check_ullbc(ctx, $pass);
+                if let Some(profile) = profile.as_mut() {
+                    profile.restart()
+                }
+            }
+        };
+    }
+    if options.validate {
+        validate::check_ullbc(ctx, "the translation");
+        if let Some(profile) = profile.as_mut() {
+            profile.restart()
+        }
+    }
 
     //
     // =================
@@ -230,25 +423,71 @@ pub fn translate(
     // we simply apply some micro-passes to make the code cleaner, before
     // serializing the result.
 
+    // # Micro-pass: retry the trait obligations we failed to solve during the
+    // translation, now that all the items are translated, and report those
+    // we still fail to solve.
+    solve_trait_obligations::transform(ctx);
+    validate_ullbc!("solve_trait_obligations");
+
+    // # Micro-pass (optional): replace the associated types of the traits with
+    // the types they are equal to. This must happen after we solve the trait
+    // obligations (we need the trait references to find the implementations).
+    if !options.no_normalize_trait_types {
+        normalize_trait_types::transform(ctx);
+        validate_ullbc!("normalize_trait_types");
+    }
+
+    // # Micro-pass (optional): remove the fields of type `PhantomData<T>`.
+    if options.remove_phantom_data {
+        remove_phantom_data::transform(ctx);
+        validate_ullbc!("remove_phantom_data");
+    }
+
     // # Micro-pass: desugar the constants to other values/operands as much
     // as possible.
-    simplify_constants::transform(&mut ctx);
+    simplify_constants::transform(ctx);
+    validate_ullbc!("simplify_constants");
+
+    // # Micro-pass: compute the outlives relation between the generic
+    // parameters of the declarations (including the implied bounds).
+    compute_regions_graph::transform(&mut ctx.translated);
+    profile_pass!("compute_regions_graph");
+
+    // # Micro-pass: rename the declarations according to the renaming table.
+    // We do this before the control-flow reconstruction, which copies the
+    // names of the functions and the globals.
+    if let Some(rename_table) = rename_table {
+        rename_items::transform(rename_table, &mut ctx.translated);
+        profile_pass!("rename_items");
+    }
 
     // # There are two options:
     // - either the user wants the unstructured LLBC, in which case we stop there
     // - or they want the structured LLBC, in which case we reconstruct the
     //   control-flow and apply micro-passes
 
-    let crate_data = if options.ullbc {
-        export::CrateData::new_ullbc(&ctx, crate_name, &ctx.fun_decls, &ctx.global_decls)
+    if options.ullbc {
+        // # Micro-pass: mark the calls in tail position (the LLBC bodies are
+        // marked at the end of the micro-passes, see below).
+        mark_tail_calls::transform_ullbc(ctx);
+        validate_ullbc!("mark_tail_calls");
+
+        None
     } else {
+        // # Micro-pass: make the control-flow graphs reducible, by duplicating
+        // blocks if necessary. The control-flow reconstruction only handles the
+        // reducible graphs: we remove the bodies we fail to make reducible.
+        make_cfg_reducible::transform(ctx);
+        validate_ullbc!("make_cfg_reducible");
+
         // # Go from ULLBC to LLBC (Low-Level Borrow Calculus) by reconstructing
         // the control flow.
-        let (mut llbc_funs, mut llbc_globals) = ullbc_to_llbc::translate_functions(&ctx);
+        let (mut llbc_funs, mut llbc_globals) = ullbc_to_llbc::translate_functions(ctx);
+        profile_pass!("ullbc_to_llbc");
 
         if options.print_built_llbc {
             let llbc_ctx = crate::translate_ctx::LlbcTransCtx {
-                ctx: &ctx,
+                ctx: &ctx.translated,
                 llbc_globals: &llbc_globals,
                 llbc_funs: &llbc_funs,
             };
@@ -258,10 +497,27 @@ pub fn translate(
             );
         }
 
-        // # Micro-pass: the first local variable of closures is the
-        // closure itself. This is not consistent with the closure signature,
-        // which ignores this first variable. This micro-pass updates this.
-        update_closure_signatures::transform(&ctx, &mut llbc_funs);
+        // The micro-passes below only need to look up the translated
+        // declarations and to report errors
+        let mut t_ctx = ctx.transform_ctx();
+        macro_rules! validate_llbc {
+            ($pass:expr) => {
+                profile_pass!($pass);
+                if options.validate {
+                    validate::check_llbc(&mut t_ctx, &llbc_funs, &llbc_globals, $pass);
+                    if let Some(profile) = profile.as_mut() {
+                        profile.restart()
+                    }
+                }
+            };
+        }
+        if options.validate {
+            let pass = "the control-flow reconstruction";
+            validate::check_llbc(&mut t_ctx, &llbc_funs, &llbc_globals, pass);
+            if let Some(profile) = profile.as_mut() {
+                profile.restart()
+            }
+        }
 
         // # Micro-pass: remove the dynamic checks for array/slice bounds
         // and division by zero.
@@ -269,31 +525,36 @@ pub fn translate(
         // introduced by Rustc use a special "assert" construct. Because of
         // this, it must happen *before* the [reconstruct_asserts] pass.
         // See the comments in [crate::remove_dynamic_checks].
-        remove_dynamic_checks::transform(&mut ctx, &mut llbc_funs, &mut llbc_globals);
+        remove_dynamic_checks::transform(&mut t_ctx, options, &mut llbc_funs, &mut llbc_globals);
+        validate_llbc!("remove_dynamic_checks");
 
         // # Micro-pass: reconstruct the asserts
-        reconstruct_asserts::transform(&mut ctx, &mut llbc_funs, &mut llbc_globals);
+        reconstruct_asserts::transform(&mut t_ctx, &mut llbc_funs, &mut llbc_globals);
+        validate_llbc!("reconstruct_asserts");
 
-        // TODO: we should mostly use the TransCtx to format declarations
+        // TODO: we should mostly use the TranslatedCrate to format declarations
         use crate::formatter::{Formatter, IntoFormatter};
         for (_, def) in &llbc_funs {
             trace!(
                 "# After asserts reconstruction:\n{}\n",
-                ctx.into_fmt().format_object(def)
+                t_ctx.into_fmt().format_object(def)
             );
         }
 
         // # Micro-pass: replace some unops/binops and the array aggregates with
         // function calls (introduces: ArrayToSlice, etc.)
-        ops_to_function_calls::transform(&mut ctx, &mut llbc_funs, &mut llbc_globals);
+        ops_to_function_calls::transform(&mut t_ctx, &mut llbc_funs, &mut llbc_globals);
+        validate_llbc!("ops_to_function_calls");
 
         // # Micro-pass: replace the arrays/slices index operations with function
         // calls.
         // (introduces: ArrayIndexShared, ArrayIndexMut, etc.)
-        index_to_function_calls::transform(&mut ctx, &mut llbc_funs, &mut llbc_globals);
+        index_to_function_calls::transform(&mut t_ctx, &mut llbc_funs, &mut llbc_globals);
+        validate_llbc!("index_to_function_calls");
 
         // # Micro-pass: Remove the discriminant reads (merge them with the switches)
-        remove_read_discriminant::transform(&mut ctx, &mut llbc_funs, &mut llbc_globals);
+        remove_read_discriminant::transform(&mut t_ctx, &mut llbc_funs, &mut llbc_globals);
+        validate_llbc!("remove_read_discriminant");
 
         // # Micro-pass: add the missing assignments to the return value.
         // When the function return type is unit, the generated MIR doesn't
@@ -303,27 +564,106 @@ pub fn translate(
         // an extra assignment just before returning.
         // This also applies to globals (for checking or executing code before
         // the main or at compile-time).
-        insert_assign_return_unit::transform(&mut ctx, &mut llbc_funs, &mut llbc_globals);
+        insert_assign_return_unit::transform(&mut t_ctx, &mut llbc_funs, &mut llbc_globals);
+        validate_llbc!("insert_assign_return_unit");
 
         // # Micro-pass: remove the drops of locals whose type is `Never` (`!`). This
         // is in preparation of the next transformation.
-        remove_drop_never::transform(&mut ctx, &mut llbc_funs, &mut llbc_globals);
+        remove_drop_never::transform(&mut t_ctx, &mut llbc_funs, &mut llbc_globals);
+        validate_llbc!("remove_drop_never");
+
+        // # Micro-pass (optional): inline the calls to the small functions. We
+        // do this before the passes which clean up the bodies, so that they
+        // also clean up the inlined bodies. We don't inline when we compute the
+        // regions of the bodies: those are specific to every body.
+        if let Some(threshold) = options.inline_threshold
+            && !options.body_regions
+            && !options.borrowck_facts
+        {
+            inline_functions::transform(&mut t_ctx, threshold, &mut llbc_funs, &mut llbc_globals);
+            validate_llbc!("inline_functions");
+        }
+
+        // # Micro-pass: simplify the chains of parent clauses and item clauses in
+        // the trait references. This must happen after the inlining, which
+        // substitutes the clauses of the inlined functions.
+        simplify_trait_refs::transform(&mut t_ctx, &mut llbc_funs, &mut llbc_globals);
+        validate_llbc!("simplify_trait_refs");
+
+        // # Micro-pass: remove the dead branches of the switches on constants
+        // (coming from `cfg!(...)` for instance), and the unreachable statements.
+        remove_dead_branches::transform(&mut t_ctx, &mut llbc_funs, &mut llbc_globals);
+        validate_llbc!("remove_dead_branches");
+
+        // # Micro-pass: remove the redundant reborrows and copies, unless the
+        // options ask to keep them. This must happen before we remove the unused
+        // locals (the locals we remove the definitions of become unused).
+        if !options.keep_redundant_copies {
+            remove_redundant_copies::transform(&mut t_ctx, &mut llbc_funs, &mut llbc_globals);
+            validate_llbc!("remove_redundant_copies");
+        }
+
+        // # Micro-pass: remove the assignments of `()` to the locals of type unit,
+        // and the copies of those locals. This must happen after we insert the
+        // assignments of `()` to the return value, and before we remove the
+        // unused locals.
+        remove_unit_locals::transform(&mut t_ctx, &mut llbc_funs, &mut llbc_globals);
+        validate_llbc!("remove_unit_locals");
+
+        // # Micro-pass (optional): propagate the constants through the locals,
+        // fold the operations on constants and simplify the switches on
+        // constants. This must happen before we remove the unused locals (the
+        // locals whose constant we propagated become unused).
+        if options.propagate_constants {
+            propagate_constants::transform(&mut t_ctx, &mut llbc_funs, &mut llbc_globals);
+            validate_llbc!("propagate_constants");
+        }
+
+        // # Micro-pass (optional): inline the temporaries which are used only
+        // once, to reconstruct the nested expressions. This must happen before
+        // we remove the unused locals (the inlined temporaries become unused).
+        if options.reconstruct_expressions {
+            reconstruct_expressions::transform(&mut t_ctx, &mut llbc_funs, &mut llbc_globals);
+            validate_llbc!("reconstruct_expressions");
+        }
+
+        // # The custom passes registered by the tool which embeds Charon (see
+        // [LlbcPass]). This must happen before we remove the unused locals and
+        // the no-ops, so that those passes don't have to clean up after
+        // themselves.
+        for pass in custom_passes.iter_mut() {
+            llbc_passes::transform(pass.as_mut(), &mut t_ctx, &mut llbc_funs, &mut llbc_globals);
+            validate_llbc!(pass.name());
+        }
 
         // # Micro-pass: remove the locals which are never used. After doing so, we
         // check that there are no remaining locals with type `Never`.
-        remove_unused_locals::transform(&mut ctx, &mut llbc_funs, &mut llbc_globals);
+        remove_unused_locals::transform(&mut t_ctx, &mut llbc_funs, &mut llbc_globals);
+        validate_llbc!("remove_unused_locals");
 
         // # Micro-pass (not necessary, but good for cleaning): remove the
         // useless no-ops.
-        remove_nops::transform(&mut ctx, &mut llbc_funs, &mut llbc_globals);
+        remove_nops::transform(&mut t_ctx, &mut llbc_funs, &mut llbc_globals);
+        validate_llbc!("remove_nops");
+
+        // # Micro-pass: mark the calls in tail position. We do this last, as the
+        // previous passes change the statements which follow the calls.
+        mark_tail_calls::transform(&mut t_ctx, &mut llbc_funs, &mut llbc_globals);
+        validate_llbc!("mark_tail_calls");
+
+        // # Micro-pass: compute the information of the loops (ids, nesting depths,
+        // and the statements which exit them or go back to their start). We do
+        // this last, as the previous passes create, remove and move loops.
+        compute_loop_info::transform(&mut t_ctx, &mut llbc_funs, &mut llbc_globals);
+        validate_llbc!("compute_loop_info");
 
         trace!("# Final LLBC:\n");
         for (_, def) in &llbc_funs {
-            trace!("#{}\n", ctx.into_fmt().format_object(def));
+            trace!("#{}\n", t_ctx.into_fmt().format_object(def));
         }
 
         let llbc_ctx = crate::translate_ctx::LlbcTransCtx {
-            ctx: &ctx,
+            ctx: t_ctx.translated,
             llbc_globals: &llbc_globals,
             llbc_funs: &llbc_funs,
         };
@@ -333,14 +673,8 @@ pub fn translate(
         }
 
         // Display an error report about the external dependencies, if necessary
-        ctx.report_external_deps_errors();
-
-        export::CrateData::new_llbc(&ctx, crate_name, &llbc_funs, &llbc_globals)
-    };
-    trace!("Done");
-
-    // Update the error count
-    internal.error_count = ctx.error_count;
+        ctx.errors.report_external_deps_errors();
 
-    Ok(crate_data)
+        Some((llbc_funs, llbc_globals))
+    }
 }
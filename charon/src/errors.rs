@@ -0,0 +1,74 @@
+//! The errors returned by the entry points of Charon.
+
+/// The errors returned by the entry points of Charon (the driver, the export,
+/// and the `charon` binary).
+///
+/// This module doesn't depend on the compiler libraries, so that the `charon`
+/// binary can use it.
+#[derive(Debug)]
+pub enum CharonError {
+    /// We could not read or write a file.
+    Io {
+        path: std::path::PathBuf,
+        error: std::io::Error,
+    },
+    /// We could not serialize the translated crate.
+    Serialize(serde_json::Error),
+    /// A configuration file given on the command line (like the renaming
+    /// table) is malformed.
+    Config {
+        path: std::path::PathBuf,
+        msg: String,
+    },
+    /// The crate uses a feature we do not support. The details have been
+    /// reported to the user through the compiler session.
+    Unsupported(String),
+    /// We broke an internal invariant (this includes the panics).
+    Internal(String),
+    /// The compilation encountered some errors (which were reported through
+    /// the compiler session).
+    RustcError(usize),
+}
+
+impl CharonError {
+    /// The exit code of the process. We use 101 (the standard panic error code)
+    /// for the internal errors.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            CharonError::RustcError(_) | CharonError::Unsupported(_) => 1,
+            CharonError::Io { .. } | CharonError::Config { .. } => 2,
+            CharonError::Serialize(_) => 3,
+            CharonError::Internal(_) => 101,
+        }
+    }
+}
+
+impl std::fmt::Display for CharonError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CharonError::Io { path, error } => write!(f, "{}: {}", path.display(), error),
+            CharonError::Serialize(error) => {
+                write!(f, "Could not serialize the output file: {}", error)
+            }
+            CharonError::Config { path, msg } => write!(f, "{}: {}", path.display(), msg),
+            CharonError::Unsupported(msg) => write!(f, "Unsupported feature: {}", msg),
+            CharonError::Internal(msg) => write!(f, "Internal error: {}", msg),
+            CharonError::RustcError(error_count) => {
+                write!(f, "Compilation encountered {} errors", error_count)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CharonError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CharonError::Io { error, .. } => Some(error),
+            CharonError::Serialize(error) => Some(error),
+            CharonError::Config { .. }
+            | CharonError::Unsupported(_)
+            | CharonError::Internal(_)
+            | CharonError::RustcError(_) => None,
+        }
+    }
+}
@@ -1,102 +1,627 @@
+use crate::canonical::{self, Canonical};
+use crate::cli_options::OutputFormat;
+use crate::common::CharonError;
+use crate::diff::{self, DeclKind};
+use crate::gast::{ClauseEnv, GFunDecl, GGlobalDecl, Harness, Module, ModuleId};
 use crate::llbc_ast;
-use crate::meta::{FileId, FileName};
-use crate::reorder_decls::DeclarationGroup;
+use crate::meta::{FileId, FileName, MacroExpansion, MacroExpansionId};
+use crate::meta_utils;
+use crate::names::Name;
+#[cfg(feature = "protobuf")]
+use crate::protobuf;
+use crate::reorder_decls::{AnyTransId, DeclarationGroup, GDeclarationGroup};
+use crate::sexp;
+use crate::symbol::{self, StringTable, Symbol};
 use crate::translate_ctx::*;
 use crate::types::*;
 use crate::ullbc_ast;
 use crate::ullbc_ast::{FunDeclId, GlobalDeclId, TraitDecl, TraitImpl};
+use hax_frontend_exporter::SInto;
+use rustc_data_structures::fingerprint::Fingerprint;
+use rustc_data_structures::stable_hasher::StableHasher;
 use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
 use std::fs::File;
+use std::hash::Hasher;
+use std::io::{BufWriter, Write};
 use std::path::Path;
 
+/// The version of the format of the exported ULLBC crates: this must be
+/// bumped whenever we make a breaking change to the format, so that `charon-ml`
+/// can detect that it is reading a file generated by an incompatible version of
+/// Charon.
+///
+/// The ULLBC and the LLBC are versioned independently, and we only bump the
+/// version of the format whose serialized shape changed:
+/// - a change to the constructs which only appear in the ULLBC (the blocks and
+///   the terminators, the `Index` projections, the `Len` and `Repeat` rvalues,
+///   etc.) only bumps [ULLBC_FORMAT_VERSION];
+/// - a change to the constructs which only appear in the LLBC (the structured
+///   statements, the loops, etc.) only bumps [LLBC_FORMAT_VERSION];
+/// - a change to the parts they share (the types, the expressions, the
+///   declarations, the fields of the crate, etc.) bumps both versions.
+///
+/// The versions expected by `charon-ml` are in `GAstOfJson.ml`: they must be
+/// kept in sync.
+pub const ULLBC_FORMAT_VERSION: u32 = 48;
+
+/// The version of the format of the exported LLBC crates (see
+/// [ULLBC_FORMAT_VERSION]).
+pub const LLBC_FORMAT_VERSION: u32 = 47;
+
+/// The extension of the compressed files: we compress the output with zstd
+/// when the name of the target file has this extension.
+pub const COMPRESSED_EXTENSION: &str = "zst";
+
 /// The data of a generic crate. We serialize this to pass it to `charon-ml`, so this must be as
 /// stable as possible. This is used for both ULLBC and LLBC.
 #[derive(Serialize)]
 #[serde(rename = "Crate")]
 pub struct GCrateData<FD, GD> {
+    /// The version of the format (see [ULLBC_FORMAT_VERSION] and
+    /// [LLBC_FORMAT_VERSION]).
+    pub format_version: u32,
     pub name: String,
+    /// Information about the target the crate was compiled for (pointer width,
+    /// endianness, etc.).
+    pub target_info: TargetInfo,
+    /// The features enabled for the extraction (see [crate::meta::ItemMeta::cfg]).
+    pub features: Vec<String>,
+    /// The cargo profile the crate was compiled with (`release`, unless the
+    /// user gave `--cargo-profile`).
+    pub cargo_profile: String,
     /// The `id_to_file` map is serialized as a vector.
     /// We use this map for the spans: the spans only store the file ids, not
     /// the file names, in order to save space.
-    pub id_to_file: Vec<(FileId::Id, FileName)>,
+    /// The last component is the content of the file, that we only export
+    /// with `--include-sources` (see [GCrateData::include_sources]).
+    pub id_to_file: Vec<(FileId::Id, FileName, Option<String>)>,
+    /// The macro expansions the code comes from (see [crate::meta::Meta::expansion]).
+    pub macro_expansions: MacroExpansionId::Vector<MacroExpansion>,
     pub declarations: Vec<DeclarationGroup>,
     pub types: Vec<TypeDecl>,
     pub functions: Vec<FD>,
     pub globals: Vec<GD>,
     pub trait_decls: Vec<TraitDecl>,
     pub trait_impls: Vec<TraitImpl>,
+    /// The module tree of the crate (see [crate::gast::Module]).
+    pub modules: ModuleId::Vector<Module>,
+    /// The verification entry points of the crate (see [crate::gast::Harness]).
+    pub harnesses: Vec<Harness>,
+    /// The ghost functions of the crate (see [crate::gast::Call::is_ghost]).
+    pub ghost_functions: Vec<FunDeclId::Id>,
+    /// The trait clauses in scope in the declarations (see [crate::gast::ClauseEnv]). We omit
+    /// the declarations which don't have any.
+    pub clause_envs: Vec<ClauseEnv>,
+    /// The type aliases of the crate, if we translated them (see
+    /// [crate::types::TypeAliasDecl]).
+    pub type_aliases: Vec<TypeAliasDecl>,
+    /// The string table: the symbols (the identifiers of the names, and the
+    /// names of the fields and of the variants) are serialized as indices in
+    /// this table (see [crate::symbol]). This must be the last field.
+    pub strings: StringTable,
+    #[serde(skip_serializing)]
+    /// For every group of [Self::declarations], the indices of the groups it
+    /// depends on. We only export this when splitting the output (see
+    /// [GCrateData::serialize_split_to_dir]).
+    pub declarations_deps: Vec<Vec<usize>>,
+    #[serde(skip_serializing)]
+    /// The hashes of the exact serializations of the declarations (see
+    /// [content_hash]).
+    pub exact_hashes: HashMap<AnyTransId, String>,
+    #[serde(skip_serializing)]
+    /// Whether to put the serialized crate in canonical form (see [crate::canonical]).
+    pub canonical: bool,
+    #[serde(skip_serializing)]
+    /// Whether the bodies are structured, i.e., whether this is an LLBC crate.
+    pub is_llbc: bool,
+    #[serde(skip_serializing)]
+    /// The format of the output file.
+    pub format: OutputFormat,
     #[serde(skip_serializing)]
     /// If there were errors, this contains only a partial description of the input crate.
     pub has_errors: bool,
 }
 
-impl<FD: Serialize + Clone, GD: Serialize + Clone> GCrateData<FD, GD> {
+fn stable_hash(bytes: &[u8]) -> String {
+    let mut hasher = StableHasher::new();
+    hasher.write(bytes);
+    let hash: Fingerprint = hasher.finish();
+    hash.to_hex()
+}
+
+/// Compute the hash of the content of a declaration (see the `hash` field of
+/// [crate::meta::ItemMeta]): we hash its serialization, normalized as in
+/// `charon diff` (see [diff::normalize_decl]).
+///
+/// We also return the hash of the exact serialization of the declaration,
+/// which covers the spans and the numeric ids that the content hash ignores:
+/// we need both to decide whether to rewrite the group file of the declaration
+/// (see [GCrateData::serialize_split_to_dir]).
+fn content_hash(
+    kind: DeclKind,
+    decl: &impl Serialize,
+    keys: &HashMap<(DeclKind, u64), String>,
+) -> (String, String) {
+    // We don't use maps with non-string keys: the serialization can't fail.
+    // We are not inside [symbol::with_string_table]: the symbols are
+    // serialized as strings.
+    let value = serde_json::to_value(decl).unwrap();
+    let normalized = diff::normalize_decl(kind, &value, keys);
+    let content = stable_hash(normalized.to_string().as_bytes());
+    let exact = stable_hash(value.to_string().as_bytes());
+    (content, exact)
+}
+
+/// Compute the trait clauses in scope in the declarations (see [ClauseEnv]).
+fn clause_envs<T>(
+    translated: &TranslatedCrate,
+    fun_decls: &FunDeclId::Map<GFunDecl<T>>,
+    global_decls: &GlobalDeclId::Map<GGlobalDecl<T>>,
+) -> Vec<ClauseEnv> {
+    let trait_decls = &translated.trait_decls;
+    let env = |item, generics: &GenericParams| {
+        ClauseEnv::new(item, trait_decls, &generics.trait_clauses, None)
+    };
+    let types = translated
+        .type_decls
+        .iter()
+        .map(|d| env(AnyTransId::Type(d.def_id), &d.generics));
+    let funs = fun_decls
+        .iter()
+        .map(|d| env(AnyTransId::Fun(d.def_id), &d.signature.generics));
+    let globals = global_decls
+        .iter()
+        .map(|d| env(AnyTransId::Global(d.def_id), &d.generics));
+    // In a trait declaration, the trait itself is in scope as `Self`
+    let decls = trait_decls.iter().map(|d| {
+        let mut generics = d.generics.identity_args();
+        generics.with_data_mut(|generics| {
+            generics.regions = d
+                .generics
+                .regions
+                .iter()
+                .map(|r| Region::BVar(DeBruijnId::new(0), r.index))
+                .collect()
+        });
+        let self_ref = TraitDeclRef {
+            trait_id: d.def_id,
+            generics,
+        };
+        let item = AnyTransId::TraitDecl(d.def_id);
+        ClauseEnv::new(item, trait_decls, &d.generics.trait_clauses, Some(self_ref))
+    });
+    let impls = translated
+        .trait_impls
+        .iter()
+        .map(|d| env(AnyTransId::TraitImpl(d.def_id), &d.generics));
+    types
+        .chain(funs)
+        .chain(globals)
+        .chain(decls)
+        .chain(impls)
+        .filter(|env| !env.clauses.is_empty())
+        .collect()
+}
+
+/// A writer of the serialized crate, in one of the output formats. We write the
+/// crate one field at a time, and the long fields one element at a time (see
+/// [GCrateData::write_fields]): we never hold the whole serialized crate in
+/// memory.
+trait CrateWriter {
+    fn begin(&mut self) -> Result<(), CharonError>;
+    /// Write a field at once.
+    fn field(&mut self, name: &'static str, value: &Value) -> Result<(), CharonError>;
+    /// Start a field which is a list, whose elements we then write with
+    /// [Self::element].
+    fn begin_list(&mut self, name: &'static str) -> Result<(), CharonError>;
+    fn element(&mut self, value: &Value) -> Result<(), CharonError>;
+    fn end_list(&mut self) -> Result<(), CharonError>;
+    fn end(&mut self) -> Result<(), CharonError>;
+}
+
+/// Write the crate in JSON, in the same way as `serde_json::to_writer`.
+struct JsonWriter<'a> {
+    writer: &'a mut dyn Write,
+    dest: &'a Path,
+    /// Whether we wrote the first field of the crate, or the first element of
+    /// the current list.
+    first_field: bool,
+    first_element: bool,
+}
+
+impl JsonWriter<'_> {
+    fn write(&mut self, s: &str) -> Result<(), CharonError> {
+        self.writer
+            .write_all(s.as_bytes())
+            .map_err(|error| CharonError::Io {
+                path: self.dest.to_path_buf(),
+                error,
+            })
+    }
+
+    fn key(&mut self, name: &str) -> Result<(), CharonError> {
+        if !std::mem::replace(&mut self.first_field, false) {
+            self.write(",")?;
+        }
+        serde_json::to_writer(&mut *self.writer, name).map_err(CharonError::Serialize)?;
+        self.write(":")
+    }
+}
+
+impl CrateWriter for JsonWriter<'_> {
+    fn begin(&mut self) -> Result<(), CharonError> {
+        self.first_field = true;
+        self.write("{")
+    }
+
+    fn field(&mut self, name: &'static str, value: &Value) -> Result<(), CharonError> {
+        self.key(name)?;
+        serde_json::to_writer(&mut *self.writer, value).map_err(CharonError::Serialize)
+    }
+
+    fn begin_list(&mut self, name: &'static str) -> Result<(), CharonError> {
+        self.key(name)?;
+        self.first_element = true;
+        self.write("[")
+    }
+
+    fn element(&mut self, value: &Value) -> Result<(), CharonError> {
+        if !std::mem::replace(&mut self.first_element, false) {
+            self.write(",")?;
+        }
+        serde_json::to_writer(&mut *self.writer, value).map_err(CharonError::Serialize)
+    }
+
+    fn end_list(&mut self) -> Result<(), CharonError> {
+        self.write("]")
+    }
+
+    fn end(&mut self) -> Result<(), CharonError> {
+        self.write("}")
+    }
+}
+
+/// Write the crate as an S-expression, in the same way as [sexp::write_value].
+struct SexpWriter<'a> {
+    writer: &'a mut dyn Write,
+    dest: &'a Path,
+    /// Whether we wrote the first field of the crate, or the first element of
+    /// the current list.
+    first_field: bool,
+    first_element: bool,
+}
+
+impl SexpWriter<'_> {
+    fn io_error(&self, error: std::io::Error) -> CharonError {
+        CharonError::Io {
+            path: self.dest.to_path_buf(),
+            error,
+        }
+    }
+
+    fn write(&mut self, s: &str) -> Result<(), CharonError> {
+        self.writer
+            .write_all(s.as_bytes())
+            .map_err(|error| self.io_error(error))
+    }
+
+    fn key(&mut self, name: &str) -> Result<(), CharonError> {
+        if !std::mem::replace(&mut self.first_field, false) {
+            self.write(" ")?;
+        }
+        self.write("(")?;
+        sexp::write_value(&mut *self.writer, &Value::from(name))
+            .map_err(|error| self.io_error(error))?;
+        self.write(" . ")
+    }
+}
+
+impl CrateWriter for SexpWriter<'_> {
+    fn begin(&mut self) -> Result<(), CharonError> {
+        self.first_field = true;
+        self.write("(")
+    }
+
+    fn field(&mut self, name: &'static str, value: &Value) -> Result<(), CharonError> {
+        self.key(name)?;
+        sexp::write_value(&mut *self.writer, value).map_err(|error| self.io_error(error))?;
+        self.write(")")
+    }
+
+    fn begin_list(&mut self, name: &'static str) -> Result<(), CharonError> {
+        self.key(name)?;
+        self.first_element = true;
+        self.write("(")
+    }
+
+    fn element(&mut self, value: &Value) -> Result<(), CharonError> {
+        if !std::mem::replace(&mut self.first_element, false) {
+            self.write(" ")?;
+        }
+        sexp::write_value(&mut *self.writer, value).map_err(|error| self.io_error(error))
+    }
+
+    fn end_list(&mut self) -> Result<(), CharonError> {
+        self.write("))")
+    }
+
+    fn end(&mut self) -> Result<(), CharonError> {
+        self.write(")")
+    }
+}
+
+/// Write the crate as a protobuf message (see [protobuf::Schema::encode_record_field]).
+#[cfg(feature = "protobuf")]
+struct ProtoWriter<'a> {
+    writer: &'a mut dyn Write,
+    dest: &'a Path,
+    schema: protobuf::Schema,
+    /// The root message ([protobuf::LLBC_CRATE] or [protobuf::ULLBC_CRATE]).
+    root: &'static str,
+    /// The name of the current list.
+    list: &'static str,
+}
+
+#[cfg(feature = "protobuf")]
+impl ProtoWriter<'_> {
+    fn write(&mut self, bytes: Result<Vec<u8>, String>) -> Result<(), CharonError> {
+        let bytes = bytes.map_err(CharonError::Internal)?;
+        self.writer
+            .write_all(&bytes)
+            .map_err(|error| CharonError::Io {
+                path: self.dest.to_path_buf(),
+                error,
+            })
+    }
+}
+
+#[cfg(feature = "protobuf")]
+impl CrateWriter for ProtoWriter<'_> {
+    fn begin(&mut self) -> Result<(), CharonError> {
+        Ok(())
+    }
+
+    fn field(&mut self, name: &'static str, value: &Value) -> Result<(), CharonError> {
+        let bytes = self.schema.encode_record_field(self.root, name, value);
+        self.write(bytes)
+    }
+
+    fn begin_list(&mut self, name: &'static str) -> Result<(), CharonError> {
+        self.list = name;
+        Ok(())
+    }
+
+    fn element(&mut self, value: &Value) -> Result<(), CharonError> {
+        let bytes = self.schema.encode_record_elem(self.root, self.list, value);
+        self.write(bytes)
+    }
+
+    fn end_list(&mut self) -> Result<(), CharonError> {
+        Ok(())
+    }
+
+    fn end(&mut self) -> Result<(), CharonError> {
+        Ok(())
+    }
+}
+
+fn to_value(x: &impl Serialize) -> Result<Value, CharonError> {
+    serde_json::to_value(x).map_err(CharonError::Serialize)
+}
+
+/// Write a field of the crate at once (in canonical form in canonical mode).
+fn write_field(
+    out: &mut dyn CrateWriter,
+    canonical: Option<&Canonical>,
+    name: &'static str,
+    x: &impl Serialize,
+) -> Result<(), CharonError> {
+    let mut value = to_value(x)?;
+    if let Some(canonical) = canonical {
+        canonical.field(name, &mut value)
+    }
+    out.field(name, &value)
+}
+
+/// Write a field of the crate which is a list one element at a time (in
+/// canonical form and in the canonical order in canonical mode).
+fn write_list<X: Serialize>(
+    out: &mut dyn CrateWriter,
+    canonical: Option<&Canonical>,
+    name: &'static str,
+    elems: &[X],
+) -> Result<(), CharonError> {
+    out.begin_list(name)?;
+    let order = canonical.and_then(|canonical| canonical.order(name));
+    for i in 0..elems.len() {
+        let mut value = to_value(&elems[order.map_or(i, |order| order[i])])?;
+        if let Some(canonical) = canonical {
+            canonical.element(name, &mut value)
+        }
+        out.element(&value)?;
+    }
+    out.end_list()
+}
+
+impl<T: Serialize + Clone> GCrateData<GFunDecl<T>, GGlobalDecl<T>> {
+    /// Note that we move the declarations out of the context rather than
+    /// cloning them, so as not to have two copies of the whole crate in memory:
+    /// the context can't be used to look up the declarations afterwards.
     pub fn new(
-        ctx: &TransCtx,
+        ctx: &mut TransCtx,
         crate_name: String,
-        fun_decls: &FunDeclId::Map<FD>,
-        global_decls: &GlobalDeclId::Map<GD>,
+        fun_decls: FunDeclId::Map<GFunDecl<T>>,
+        global_decls: GlobalDeclId::Map<GGlobalDecl<T>>,
     ) -> Self {
         // Transform the map file id -> file into a vector.
         // Sort the vector to make the serialized file as stable as possible.
-        let id_to_file = &ctx.id_to_file;
+        let id_to_file = &ctx.translated.id_to_file;
         let mut file_ids: Vec<FileId::Id> = id_to_file.keys().copied().collect();
         file_ids.sort();
-        let id_to_file: Vec<(FileId::Id, FileName)> = file_ids
+        let id_to_file: Vec<(FileId::Id, FileName, Option<String>)> = file_ids
             .into_iter()
-            .map(|id| (id, id_to_file.get(&id).unwrap().clone()))
+            .map(|id| (id, id_to_file.get(&id).unwrap().clone(), None))
             .collect();
 
         // Note that we replace the maps with vectors (the declarations contain
         // their ids, so it is easy to reconstruct the maps from there).
-        let declarations = ctx.ordered_decls.clone().unwrap();
-        let types = ctx.type_decls.iter().cloned().collect();
-        let functions = fun_decls.iter().cloned().collect();
-        let globals = global_decls.iter().cloned().collect();
-        let trait_decls = ctx.trait_decls.iter().cloned().collect();
-        let trait_impls = ctx.trait_impls.iter().cloned().collect();
-        GCrateData {
+        let translated = &mut ctx.translated;
+        let declarations = translated.ordered_decls.take().unwrap();
+        let declarations_deps = std::mem::take(&mut translated.ordered_decls_deps);
+        let macro_expansions = std::mem::take(&mut translated.macro_expansions);
+        let modules = std::mem::take(&mut translated.modules);
+        let harnesses = std::mem::take(&mut translated.harnesses);
+        let ghost_functions = std::mem::take(&mut translated.ghost_functions);
+        let clause_envs = clause_envs(translated, &fun_decls, &global_decls);
+        let type_aliases = std::mem::take(&mut translated.type_aliases)
+            .into_values()
+            .collect();
+        let types = std::mem::take(&mut translated.type_decls)
+            .into_values()
+            .collect();
+        let functions = fun_decls.into_values().collect();
+        let globals = global_decls.into_values().collect();
+        let trait_decls = std::mem::take(&mut translated.trait_decls)
+            .into_values()
+            .collect();
+        let trait_impls = std::mem::take(&mut translated.trait_impls)
+            .into_values()
+            .collect();
+        let mut crate_data = GCrateData {
+            format_version: ULLBC_FORMAT_VERSION,
             name: crate_name,
+            target_info: ctx.target_info.clone(),
+            features: ctx.enabled_features(),
+            cargo_profile: ctx.cargo_profile.clone(),
             id_to_file,
+            macro_expansions,
             declarations,
             types,
             functions,
             globals,
             trait_decls,
             trait_impls,
-            has_errors: ctx.error_count > 0,
+            modules,
+            harnesses,
+            ghost_functions,
+            clause_envs,
+            type_aliases,
+            strings: StringTable,
+            declarations_deps,
+            exact_hashes: HashMap::new(),
+            canonical: false,
+            is_llbc: false,
+            format: OutputFormat::Json,
+            has_errors: ctx.errors.error_count > 0,
+        };
+        crate_data.compute_hashes();
+        crate_data
+    }
+
+    /// Compute the hashes of the contents of the declarations. We serialize the
+    /// declarations one at a time, so as not to hold the whole serialized crate
+    /// in memory.
+    fn compute_hashes(&mut self) {
+        // The references to the declarations are replaced with their stable ids
+        let mut keys = HashMap::new();
+        let mut insert_key = |kind, id: usize, meta: &crate::meta::ItemMeta| {
+            keys.insert((kind, id as u64), meta.stable_id.clone());
+        };
+        for d in &self.types {
+            insert_key(DeclKind::Type, d.def_id.index(), &d.item_meta);
+        }
+        for d in &self.functions {
+            insert_key(DeclKind::Fun, d.def_id.index(), &d.item_meta);
+        }
+        for d in &self.globals {
+            insert_key(DeclKind::Global, d.def_id.index(), &d.item_meta);
+        }
+        for d in &self.trait_decls {
+            insert_key(DeclKind::TraitDecl, d.def_id.index(), &d.item_meta);
+        }
+        for d in &self.trait_impls {
+            insert_key(DeclKind::TraitImpl, d.def_id.index(), &d.item_meta);
+        }
+
+        let exact_hashes = &mut self.exact_hashes;
+        for d in self.types.iter_mut() {
+            let (hash, exact) = content_hash(DeclKind::Type, &*d, &keys);
+            d.item_meta.hash = hash;
+            exact_hashes.insert(AnyTransId::Type(d.def_id), exact);
+        }
+        for d in self.functions.iter_mut() {
+            let (hash, exact) = content_hash(DeclKind::Fun, &*d, &keys);
+            d.item_meta.hash = hash;
+            exact_hashes.insert(AnyTransId::Fun(d.def_id), exact);
+        }
+        for d in self.globals.iter_mut() {
+            let (hash, exact) = content_hash(DeclKind::Global, &*d, &keys);
+            d.item_meta.hash = hash;
+            exact_hashes.insert(AnyTransId::Global(d.def_id), exact);
+        }
+        for d in self.trait_decls.iter_mut() {
+            let (hash, exact) = content_hash(DeclKind::TraitDecl, &*d, &keys);
+            d.item_meta.hash = hash;
+            exact_hashes.insert(AnyTransId::TraitDecl(d.def_id), exact);
+        }
+        for d in self.trait_impls.iter_mut() {
+            let (hash, exact) = content_hash(DeclKind::TraitImpl, &*d, &keys);
+            d.item_meta.hash = hash;
+            exact_hashes.insert(AnyTransId::TraitImpl(d.def_id), exact);
         }
     }
 
-    /// Export the translated definitions to a JSON file.
-    #[allow(clippy::result_unit_err)]
-    pub fn serialize_to_file(&self, target_filename: &Path) -> Result<(), ()> {
+    /// Include the content of the source files in [Self::id_to_file], so that
+    /// the consumers can quote the original code (with the spans) without
+    /// having access to the sources. We retrieve the content from the source
+    /// map: it is not available for the files of the external crates (from which
+    /// rustc only loads the metadata).
+    pub fn include_sources(&mut self, ctx: &TransCtx) {
+        let source_map = ctx.tcx.sess.source_map();
+        let contents: HashMap<FileName, String> = source_map
+            .files()
+            .iter()
+            .filter_map(|file| {
+                let src = file.src.as_ref()?;
+                let name = meta_utils::convert_filename(&file.name.sinto(&ctx.hax_state));
+                Some((name, src.to_string()))
+            })
+            .collect();
+        for (_, name, content) in &mut self.id_to_file {
+            *content = contents.get(name).cloned();
+        }
+    }
+
+    /// Export the translated definitions to a file (in JSON, unless we chose
+    /// another format).
+    pub fn serialize_to_file(&self, target_filename: &Path) -> Result<(), CharonError> {
         // Create the directory, if necessary (note that if the target directory
         // is not specified, there is no need to create it: otherwise we
         // couldn't have read the input file in the first place).
         let target_dir = target_filename.parent().unwrap();
-        match std::fs::create_dir_all(target_dir) {
-            std::result::Result::Ok(()) => (),
-            std::result::Result::Err(_) => {
-                error!("Could not create the directory: {:?}", target_dir);
-                return Err(());
-            }
-        };
+        std::fs::create_dir_all(target_dir).map_err(|error| CharonError::Io {
+            path: target_dir.to_path_buf(),
+            error,
+        })?;
 
         // Create the file.
-        let std::io::Result::Ok(outfile) = File::create(target_filename.clone()) else {
-            error!("Could not open: {:?}", target_filename);
-            return Err(())
-        };
+        let outfile = File::create(target_filename).map_err(|error| CharonError::Io {
+            path: target_filename.to_path_buf(),
+            error,
+        })?;
         // Write to the file.
-        let std::result::Result::Ok(()) = serde_json::to_writer(&outfile, self) else {
-            error!("Could not write to: {:?}", target_filename);
-            return Err(());
-        };
+        let compress = target_filename.extension() == Some(COMPRESSED_EXTENSION.as_ref());
+        self.serialize_to_writer(outfile, target_filename, compress)?;
 
         // We canonicalize (i.e., make absolute) the path before printing it; this makes it clearer
         // to the user where to find the file.
-        let target_filename = std::fs::canonicalize(target_filename).unwrap();
+        let target_filename =
+            std::fs::canonicalize(target_filename).map_err(|error| CharonError::Io {
+                path: target_filename.to_path_buf(),
+                error,
+            })?;
         if self.has_errors {
             info!(
                 "Generated the partial (because we encountered errors) file: {}",
@@ -107,6 +632,484 @@ impl<FD: Serialize + Clone, GD: Serialize + Clone> GCrateData<FD, GD> {
         }
         Ok(())
     }
+
+    /// Export the translated definitions to a file, unless the file already has
+    /// this content (see [write_if_changed]). Contrary to
+    /// [Self::serialize_to_file], we hold the whole serialized crate in memory.
+    pub fn update_file(&self, target_filename: &Path) -> Result<(), CharonError> {
+        let io_error = |error| CharonError::Io {
+            path: target_filename.to_path_buf(),
+            error,
+        };
+        let target_dir = target_filename.parent().unwrap();
+        std::fs::create_dir_all(target_dir).map_err(|error| CharonError::Io {
+            path: target_dir.to_path_buf(),
+            error,
+        })?;
+
+        let compress = target_filename.extension() == Some(COMPRESSED_EXTENSION.as_ref());
+        let mut content = Vec::new();
+        self.serialize_to_writer(&mut content, target_filename, compress)?;
+        write_if_changed(target_filename, &content).map_err(io_error)?;
+        info!("Updated the file: {}", target_filename.display());
+        Ok(())
+    }
+
+    /// Export the translated definitions to the standard output, so that we
+    /// can be used in a pipe (the logs are printed on the standard error).
+    pub fn serialize_to_stdout(&self, compress: bool) -> Result<(), CharonError> {
+        let stdout = std::io::stdout().lock();
+        self.serialize_to_writer(stdout, Path::new("<stdout>"), compress)
+    }
+
+    /// Compute the new ids of the canonical form (see [Canonical]). This must
+    /// be called outside of [symbol::with_string_table], so that we sort the
+    /// declarations by the strings of their names.
+    fn canonical_form(&self) -> Result<Canonical, CharonError> {
+        let key = |def_id: usize, name: &Name, meta: &crate::meta::ItemMeta| {
+            (def_id as u64, canonical::sort_key(name, &meta.stable_id))
+        };
+        let mut decls = HashMap::new();
+        decls.insert(
+            DeclKind::Type,
+            self.types
+                .iter()
+                .map(|d| key(d.def_id.index(), &d.name, &d.item_meta))
+                .collect(),
+        );
+        decls.insert(
+            DeclKind::Fun,
+            self.functions
+                .iter()
+                .map(|d| key(d.def_id.index(), &d.name, &d.item_meta))
+                .collect(),
+        );
+        decls.insert(
+            DeclKind::Global,
+            self.globals
+                .iter()
+                .map(|d| key(d.def_id.index(), &d.name, &d.item_meta))
+                .collect(),
+        );
+        decls.insert(
+            DeclKind::TraitDecl,
+            self.trait_decls
+                .iter()
+                .map(|d| key(d.def_id.index(), &d.name, &d.item_meta))
+                .collect(),
+        );
+        decls.insert(
+            DeclKind::TraitImpl,
+            self.trait_impls
+                .iter()
+                .map(|d| key(d.def_id.index(), &d.name, &d.item_meta))
+                .collect(),
+        );
+        let aliases = self
+            .type_aliases
+            .iter()
+            .map(|d| key(d.def_id.index(), &d.name, &d.item_meta))
+            .collect();
+        let modules = self
+            .modules
+            .iter()
+            .map(|m| key(m.module_id.index(), &m.name, &m.item_meta))
+            .collect();
+        let clause_envs = self
+            .clause_envs
+            .iter()
+            .map(|env| to_value(&env.item))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Canonical::new(
+            to_value(&self.id_to_file)?,
+            to_value(&self.macro_expansions)?,
+            decls,
+            aliases,
+            modules,
+            clause_envs,
+        ))
+    }
+
+    /// Write the fields of the crate, in the order of the declaration of
+    /// [GCrateData] (the string table is last). This must be called inside
+    /// [symbol::with_string_table].
+    fn write_fields(
+        &self,
+        out: &mut dyn CrateWriter,
+        canonical: Option<&Canonical>,
+    ) -> Result<(), CharonError> {
+        out.begin()?;
+        write_field(out, canonical, "format_version", &self.format_version)?;
+        write_field(out, canonical, "name", &self.name)?;
+        write_field(out, canonical, "target_info", &self.target_info)?;
+        write_field(out, canonical, "features", &self.features)?;
+        write_field(out, canonical, "cargo_profile", &self.cargo_profile)?;
+        match canonical {
+            Some(canonical) => out.field("id_to_file", canonical.files())?,
+            None => write_field(out, None, "id_to_file", &self.id_to_file)?,
+        }
+        match canonical {
+            Some(canonical) => out.field("macro_expansions", canonical.expansions())?,
+            None => write_field(out, None, "macro_expansions", &self.macro_expansions)?,
+        }
+        write_list(out, canonical, "declarations", &self.declarations)?;
+        write_list(out, canonical, "types", &self.types)?;
+        write_list(out, canonical, "functions", &self.functions)?;
+        write_list(out, canonical, "globals", &self.globals)?;
+        write_list(out, canonical, "trait_decls", &self.trait_decls)?;
+        write_list(out, canonical, "trait_impls", &self.trait_impls)?;
+        write_field(out, canonical, "modules", &self.modules)?;
+        write_field(out, canonical, "harnesses", &self.harnesses)?;
+        write_field(out, canonical, "ghost_functions", &self.ghost_functions)?;
+        write_list(out, canonical, "clause_envs", &self.clause_envs)?;
+        write_list(out, canonical, "type_aliases", &self.type_aliases)?;
+        out.field("strings", &to_value(&self.strings)?)?;
+        out.end()
+    }
+
+    /// Serialize the crate to a writer (`dest` is only used in the error
+    /// messages). We stream the serialized data to the writer (through the
+    /// encoder when compressing), so that we never hold the whole serialized
+    /// crate in memory: in canonical mode, and to use another format than JSON,
+    /// we serialize the crate one field or one element at a time (see
+    /// [Self::write_fields]).
+    fn serialize_to_writer<W: Write>(
+        &self,
+        writer: W,
+        dest: &Path,
+        compress: bool,
+    ) -> Result<(), CharonError> {
+        let io_error = |error| CharonError::Io {
+            path: dest.to_path_buf(),
+            error,
+        };
+        let canonical = if self.canonical {
+            Some(self.canonical_form()?)
+        } else {
+            None
+        };
+        let write = |writer: &mut dyn Write| {
+            if canonical.is_none() && self.format == OutputFormat::Json {
+                return symbol::with_string_table(|| serde_json::to_writer(writer, self))
+                    .map_err(CharonError::Serialize);
+            }
+            let mut out: Box<dyn CrateWriter + '_> = match self.format {
+                OutputFormat::Json => Box::new(JsonWriter {
+                    writer,
+                    dest,
+                    first_field: true,
+                    first_element: true,
+                }),
+                OutputFormat::Sexp => Box::new(SexpWriter {
+                    writer,
+                    dest,
+                    first_field: true,
+                    first_element: true,
+                }),
+                #[cfg(feature = "protobuf")]
+                OutputFormat::Protobuf => Box::new(ProtoWriter {
+                    writer,
+                    dest,
+                    schema: protobuf::Schema::registered().map_err(CharonError::Internal)?,
+                    root: if self.is_llbc {
+                        protobuf::LLBC_CRATE
+                    } else {
+                        protobuf::ULLBC_CRATE
+                    },
+                    list: "",
+                }),
+            };
+            symbol::with_string_table(|| self.write_fields(&mut *out, canonical.as_ref()))
+        };
+        let mut writer = BufWriter::new(writer);
+        let mut writer = if compress {
+            let mut encoder = zstd::Encoder::new(writer, 0).map_err(io_error)?;
+            write(&mut encoder)?;
+            encoder.finish().map_err(io_error)?
+        } else {
+            write(&mut writer)?;
+            writer
+        };
+        writer.flush().map_err(io_error)
+    }
+}
+
+/// The file we generate for a declaration group, when splitting the output
+/// (see [GCrateData::serialize_split_to_dir]).
+#[derive(Serialize)]
+#[serde(rename = "DeclarationGroupFile")]
+struct GroupFile<'a, FD, GD> {
+    format_version: u32,
+    /// The index of the group in the index file.
+    index: usize,
+    group: &'a DeclarationGroup,
+    /// The indices of the groups this group depends on: the declarations of
+    /// this file only refer to the declarations of this file and of those groups.
+    depends_on: &'a [usize],
+    types: Vec<&'a TypeDecl>,
+    functions: Vec<&'a FD>,
+    globals: Vec<&'a GD>,
+    trait_decls: Vec<&'a TraitDecl>,
+    trait_impls: Vec<&'a TraitImpl>,
+}
+
+/// An entry of the index file.
+#[derive(Serialize)]
+struct GroupEntry<'a> {
+    /// The name of the file containing the group, relative to the index file.
+    file: String,
+    group: &'a DeclarationGroup,
+    /// The indices of the groups this group depends on.
+    depends_on: &'a [usize],
+    /// The fingerprint of the group file (see [GCrateData::group_hash]): we
+    /// don't rewrite the group files whose fingerprint didn't change.
+    hash: String,
+}
+
+/// The index file we generate when splitting the output. It contains the
+/// information shared by all the groups (the spans of the group files refer to
+/// the file ids of `id_to_file`).
+#[derive(Serialize)]
+#[serde(rename = "CrateIndex")]
+struct CrateIndex<'a> {
+    format_version: u32,
+    name: &'a str,
+    target_info: &'a TargetInfo,
+    features: &'a [String],
+    cargo_profile: &'a str,
+    id_to_file: &'a [(FileId::Id, FileName, Option<String>)],
+    macro_expansions: &'a MacroExpansionId::Vector<MacroExpansion>,
+    modules: &'a ModuleId::Vector<Module>,
+    harnesses: &'a [Harness],
+    ghost_functions: &'a [FunDeclId::Id],
+    clause_envs: &'a [ClauseEnv],
+    type_aliases: &'a [TypeAliasDecl],
+    groups: Vec<GroupEntry<'a>>,
+    /// The string table of the index and of all the group files (we write
+    /// the index last). It starts with the string table of the previous index,
+    /// if there is one (see [PreviousIndex]).
+    strings: StringTable,
+}
+
+/// The index file of a previous extraction to the same directory. We don't
+/// rewrite the group files whose fingerprint didn't change: we keep the string
+/// table of the previous extraction (we add the new strings at the end) so that
+/// the symbols of those files keep their indices.
+struct PreviousIndex {
+    /// The fingerprints of the group files, indexed by the names of the files.
+    hashes: HashMap<String, String>,
+    strings: Vec<Symbol>,
+}
+
+impl PreviousIndex {
+    /// Read the index file in `target_dir`, if there is one. We ignore the
+    /// malformed index files (we then rewrite all the group files).
+    fn read(target_dir: &Path) -> Option<Self> {
+        let content = std::fs::read(target_dir.join(INDEX_FILE)).ok()?;
+        let index: serde_json::Value = serde_json::from_slice(&content).ok()?;
+        let hashes = index["groups"]
+            .as_array()?
+            .iter()
+            .map(|entry| {
+                let file = entry["file"].as_str()?.to_string();
+                let hash = entry["hash"].as_str()?.to_string();
+                Some((file, hash))
+            })
+            .collect::<Option<_>>()?;
+        let strings = index["strings"]
+            .as_array()?
+            .iter()
+            .map(|s| s.as_str().map(Symbol::intern))
+            .collect::<Option<_>>()?;
+        Some(PreviousIndex { hashes, strings })
+    }
+}
+
+/// The name of the index file, when splitting the output.
+pub const INDEX_FILE: &str = "index.json";
+
+fn write_json_file<T: Serialize>(path: &Path, value: &T) -> Result<(), CharonError> {
+    let io_error = |error| CharonError::Io {
+        path: path.to_path_buf(),
+        error,
+    };
+    let content = serde_json::to_vec(value).map_err(CharonError::Serialize)?;
+    write_if_changed(path, &content).map_err(io_error)
+}
+
+/// Write a file, unless it already has this content: this way, the tools which
+/// monitor the output (in watch mode for instance) only see the files which
+/// actually changed.
+fn write_if_changed(path: &Path, content: &[u8]) -> std::io::Result<()> {
+    if std::fs::read(path).is_ok_and(|old| old == content) {
+        trace!("Unchanged file: {}", path.display());
+        return Ok(());
+    }
+    std::fs::write(path, content)
+}
+
+/// Retrieve the declarations whose ids are in the group (we may not find all
+/// of them if there were errors).
+fn group_decls<'a, Id: Copy + Eq + std::hash::Hash, T>(
+    decls: &HashMap<Id, &'a T>,
+    group: &GDeclarationGroup<Id>,
+) -> Vec<&'a T> {
+    group
+        .get_ids()
+        .iter()
+        .filter_map(|id| decls.get(id).copied())
+        .collect()
+}
+
+impl<T: Serialize + Clone> GCrateData<GFunDecl<T>, GGlobalDecl<T>> {
+    /// The fingerprint of a group file. The group file is determined by the
+    /// group, its dependencies and the declarations it contains: we combine the
+    /// hashes of the contents of the declarations (see the `hash` field of
+    /// [crate::meta::ItemMeta]) with the hashes of their exact serializations,
+    /// which cover the spans and the numeric ids (see [content_hash]). The
+    /// symbols are serialized as strings in those hashes: the indices of the
+    /// symbols are preserved separately (see [PreviousIndex]).
+    fn group_hash(&self, file: &GroupFile<'_, GFunDecl<T>, GGlobalDecl<T>>) -> String {
+        let types = file
+            .types
+            .iter()
+            .map(|d| (AnyTransId::Type(d.def_id), &d.item_meta));
+        let funs = file
+            .functions
+            .iter()
+            .map(|d| (AnyTransId::Fun(d.def_id), &d.item_meta));
+        let globals = file
+            .globals
+            .iter()
+            .map(|d| (AnyTransId::Global(d.def_id), &d.item_meta));
+        let traits = file
+            .trait_decls
+            .iter()
+            .map(|d| (AnyTransId::TraitDecl(d.def_id), &d.item_meta));
+        let impls = file
+            .trait_impls
+            .iter()
+            .map(|d| (AnyTransId::TraitImpl(d.def_id), &d.item_meta));
+        let file_decls = types.chain(funs).chain(globals).chain(traits).chain(impls);
+
+        // The groups don't contain symbols
+        let header = (file.format_version, file.index, file.group, file.depends_on);
+        let mut content = serde_json::to_string(&header).unwrap();
+        for (id, item_meta) in file_decls {
+            content.push_str(&item_meta.hash);
+            content.push_str(self.exact_hashes.get(&id).map_or("", String::as_str));
+        }
+        stable_hash(content.as_bytes())
+    }
+
+    /// Write the group files and the index file (see
+    /// [GCrateData::serialize_split_to_dir]). We skip the group files whose
+    /// fingerprint is the same as in the `previous` index.
+    fn write_split_files(
+        &self,
+        target_dir: &Path,
+        previous: Option<&PreviousIndex>,
+    ) -> Result<(), CharonError> {
+        let types: HashMap<_, _> = self.types.iter().map(|d| (d.def_id, d)).collect();
+        let functions: HashMap<_, _> = self.functions.iter().map(|d| (d.def_id, d)).collect();
+        let globals: HashMap<_, _> = self.globals.iter().map(|d| (d.def_id, d)).collect();
+        let trait_decls: HashMap<_, _> = self.trait_decls.iter().map(|d| (d.def_id, d)).collect();
+        let trait_impls: HashMap<_, _> = self.trait_impls.iter().map(|d| (d.def_id, d)).collect();
+
+        let mut groups = Vec::new();
+        for (index, group) in self.declarations.iter().enumerate() {
+            let depends_on = self
+                .declarations_deps
+                .get(index)
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            let mut file = GroupFile {
+                format_version: self.format_version,
+                index,
+                group,
+                depends_on,
+                types: Vec::new(),
+                functions: Vec::new(),
+                globals: Vec::new(),
+                trait_decls: Vec::new(),
+                trait_impls: Vec::new(),
+            };
+            match group {
+                DeclarationGroup::Type(g) => file.types = group_decls(&types, g),
+                DeclarationGroup::Fun(g) => file.functions = group_decls(&functions, g),
+                DeclarationGroup::Global(g) => file.globals = group_decls(&globals, g),
+                DeclarationGroup::TraitDecl(g) => file.trait_decls = group_decls(&trait_decls, g),
+                DeclarationGroup::TraitImpl(g) => file.trait_impls = group_decls(&trait_impls, g),
+            }
+            let file_name = format!("group_{index}.json");
+            let path = target_dir.join(&file_name);
+            let hash = self.group_hash(&file);
+            let unchanged = previous.is_some_and(|p| p.hashes.get(&file_name) == Some(&hash));
+            if unchanged && path.exists() {
+                trace!("Unchanged group: {}", path.display());
+            } else {
+                write_json_file(&path, &file)?;
+            }
+            groups.push(GroupEntry {
+                file: file_name,
+                group,
+                depends_on,
+                hash,
+            });
+        }
+
+        let index = CrateIndex {
+            format_version: self.format_version,
+            name: &self.name,
+            target_info: &self.target_info,
+            features: &self.features,
+            cargo_profile: &self.cargo_profile,
+            id_to_file: &self.id_to_file,
+            macro_expansions: &self.macro_expansions,
+            modules: &self.modules,
+            harnesses: &self.harnesses,
+            ghost_functions: &self.ghost_functions,
+            clause_envs: &self.clause_envs,
+            type_aliases: &self.type_aliases,
+            groups,
+            strings: StringTable,
+        };
+        write_json_file(&target_dir.join(INDEX_FILE), &index)
+    }
+
+    /// Export the translated definitions to a directory, with one file per
+    /// declaration group (`group_<index>.json`) and an index file ([INDEX_FILE]).
+    /// This allows the consumers to only reload the groups which changed: if
+    /// the directory contains the output of a previous extraction, we only
+    /// rewrite the group files whose fingerprint changed (see
+    /// [GCrateData::group_hash]).
+    pub fn serialize_split_to_dir(&self, target_dir: &Path) -> Result<(), CharonError> {
+        std::fs::create_dir_all(target_dir).map_err(|error| CharonError::Io {
+            path: target_dir.to_path_buf(),
+            error,
+        })?;
+
+        // The symbols of all the files share the string table of the index
+        let previous = PreviousIndex::read(target_dir);
+        let strings = previous.as_ref().map(|p| p.strings.clone());
+        symbol::with_string_table_from(strings.unwrap_or_default(), || {
+            self.write_split_files(target_dir, previous.as_ref())
+        })?;
+
+        let target_dir = std::fs::canonicalize(target_dir).map_err(|error| CharonError::Io {
+            path: target_dir.to_path_buf(),
+            error,
+        })?;
+        if self.has_errors {
+            info!(
+                "Generated the partial (because we encountered errors) files in: {}",
+                target_dir.to_str().unwrap()
+            );
+        } else {
+            info!("Generated the files in: {}", target_dir.to_str().unwrap());
+        }
+        Ok(())
+    }
 }
 
 /// The two kinds of crate data we construct.
@@ -116,30 +1119,79 @@ pub enum CrateData {
 }
 
 impl CrateData {
-    pub fn new_ullbc(
-        ctx: &TransCtx,
-        crate_name: String,
-        fun_decls: &FunDeclId::Map<ullbc_ast::FunDecl>,
-        global_decls: &GlobalDeclId::Map<ullbc_ast::GlobalDecl>,
-    ) -> Self {
+    pub fn new_ullbc(ctx: &mut TransCtx, crate_name: String) -> Self {
+        let fun_decls = std::mem::take(&mut ctx.translated.fun_decls);
+        let global_decls = std::mem::take(&mut ctx.translated.global_decls);
         Self::ULLBC(GCrateData::new(ctx, crate_name, fun_decls, global_decls))
     }
 
     pub fn new_llbc(
-        ctx: &TransCtx,
+        ctx: &mut TransCtx,
         crate_name: String,
-        fun_decls: &FunDeclId::Map<llbc_ast::FunDecl>,
-        global_decls: &GlobalDeclId::Map<llbc_ast::GlobalDecl>,
+        fun_decls: FunDeclId::Map<llbc_ast::FunDecl>,
+        global_decls: GlobalDeclId::Map<llbc_ast::GlobalDecl>,
     ) -> Self {
-        Self::LLBC(GCrateData::new(ctx, crate_name, fun_decls, global_decls))
+        let mut crate_data = GCrateData::new(ctx, crate_name, fun_decls, global_decls);
+        crate_data.is_llbc = true;
+        crate_data.format_version = LLBC_FORMAT_VERSION;
+        Self::LLBC(crate_data)
     }
 
     /// Export the translated definitions to a JSON file.
-    #[allow(clippy::result_unit_err)]
-    pub fn serialize_to_file(&self, dest_file: &Path) -> Result<(), ()> {
+    pub fn serialize_to_file(&self, dest_file: &Path) -> Result<(), CharonError> {
         match self {
             CrateData::ULLBC(crate_data) => crate_data.serialize_to_file(dest_file),
             CrateData::LLBC(crate_data) => crate_data.serialize_to_file(dest_file),
         }
     }
+
+    /// Export the translated definitions to a file, unless the file already has
+    /// this content.
+    pub fn update_file(&self, dest_file: &Path) -> Result<(), CharonError> {
+        match self {
+            CrateData::ULLBC(crate_data) => crate_data.update_file(dest_file),
+            CrateData::LLBC(crate_data) => crate_data.update_file(dest_file),
+        }
+    }
+
+    /// Put the exported crate in canonical form (see [crate::canonical]).
+    pub fn set_canonical(&mut self, canonical: bool) {
+        match self {
+            CrateData::ULLBC(crate_data) => crate_data.canonical = canonical,
+            CrateData::LLBC(crate_data) => crate_data.canonical = canonical,
+        }
+    }
+
+    /// Include the content of the source files (see [GCrateData::include_sources]).
+    pub fn include_sources(&mut self, ctx: &TransCtx) {
+        match self {
+            CrateData::ULLBC(crate_data) => crate_data.include_sources(ctx),
+            CrateData::LLBC(crate_data) => crate_data.include_sources(ctx),
+        }
+    }
+
+    /// Set the format of the output file.
+    pub fn set_format(&mut self, format: OutputFormat) {
+        match self {
+            CrateData::ULLBC(crate_data) => crate_data.format = format,
+            CrateData::LLBC(crate_data) => crate_data.format = format,
+        }
+    }
+
+    /// Export the translated definitions to the standard output.
+    pub fn serialize_to_stdout(&self, compress: bool) -> Result<(), CharonError> {
+        match self {
+            CrateData::ULLBC(crate_data) => crate_data.serialize_to_stdout(compress),
+            CrateData::LLBC(crate_data) => crate_data.serialize_to_stdout(compress),
+        }
+    }
+
+    /// Export the translated definitions to a directory, with one file per
+    /// declaration group.
+    pub fn serialize_split_to_dir(&self, target_dir: &Path) -> Result<(), CharonError> {
+        match self {
+            CrateData::ULLBC(crate_data) => crate_data.serialize_split_to_dir(target_dir),
+            CrateData::LLBC(crate_data) => crate_data.serialize_split_to_dir(target_dir),
+        }
+    }
 }
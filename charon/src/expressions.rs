@@ -1,7 +1,7 @@
 //! Implements expressions: paths, operands, rvalues, lvalues
 
 pub use crate::expressions_utils::*;
-use crate::gast::{FunDeclId, TraitItemName};
+use crate::gast::{FunDeclId, PromotedId, TraitItemName};
 use crate::types::*;
 pub use crate::values::VarId;
 use crate::values::*;
@@ -39,7 +39,7 @@ pub enum ProjectionElem {
     /// during the translation.
     /// In rust, this comes from the `*` operator applied on boxes.
     DerefBox,
-    /// Dereference a raw pointer. See the comments for [crate::types::Ty::RawPtr].
+    /// Dereference a raw pointer. See the comments for [crate::types::TyKind::RawPtr].
     /// TODO: remove those (we would also need: `DerefPtrUnique`, `DerefPtrNonNull`, etc.)
     /// and only keep a single `Deref` variant?
     /// Or if we keep them, change to: `Deref(DerefKind)`?
@@ -59,6 +59,15 @@ pub enum ProjectionElem {
     /// (this is not necessary).
     /// We **eliminate** this variant in a micro-pass.
     Index(VarId::Id, Ty),
+    /// A subslice of an array or of a slice, which contains the elements from
+    /// index `from` (included) to index `to` (excluded). If `from_end` is true,
+    /// `to` is counted from the end: the subslice stops `to` elements before
+    /// the end (ex.: `x[1..len-2]` is `Subslice { from: 1, to: 2, from_end: true }`).
+    ///
+    /// Those come from the slice patterns with a subslice binding, like `rest`
+    /// in `[first, rest @ ..]`. Note that the range indexing (`x[2..]`) is a
+    /// call to the `Index` trait, and doesn't give a projection.
+    Subslice { from: u64, to: u64, from_end: bool },
 }
 
 #[derive(Debug, PartialEq, Eq, Copy, Clone, EnumIsA, EnumAsGetters, Serialize)]
@@ -70,7 +79,8 @@ pub enum FieldProjKind {
     Tuple(usize),
     #[serde(rename = "ProjClosureState")]
     /// Access to a field in a closure state.
-    /// We eliminate this in a micro-pass ([crate::update_closure_signatures]).
+    /// We eliminate this in a micro-pass ([crate::transform::update_closure_signatures]),
+    /// right after the translation.
     ClosureState,
 }
 
@@ -109,6 +119,17 @@ pub enum UnOp {
     ArrayToSlice(RefKind, Ty, ConstGeneric),
 }
 
+/// Nullary operation, which computes information about a type.
+#[derive(Debug, PartialEq, Eq, Clone, EnumIsA, VariantName, Serialize)]
+pub enum NullOp {
+    SizeOf,
+    AlignOf,
+    /// The offset of a field, which may be nested (ex.: `offset_of!(S, a.b)`).
+    /// We give the path from the type to the field, with the kind of every
+    /// projection.
+    OffsetOf(Vec<(FieldProjKind, FieldId::Id)>),
+}
+
 /// For all the variants: the first type gives the source type, the second one gives
 /// the destination type.
 #[derive(Debug, PartialEq, Eq, Clone, EnumIsA, VariantName, Serialize)]
@@ -116,6 +137,8 @@ pub enum CastKind {
     /// Conversion between types in {Integer, Bool}
     /// Remark: for now we don't support conversions with Char.
     Scalar(LiteralTy, LiteralTy),
+    /// Coercion to a function pointer: the source is a function item (see
+    /// [TyKind::FnDef]), a closure or a function pointer.
     FnPtr(Ty, Ty),
 }
 
@@ -145,7 +168,45 @@ pub enum BinOp {
     Shl,
     /// Can fail if the shift is too big
     Shr,
-    // No Offset binary operation: this is an operation on raw pointers
+    /// Offset a raw pointer by a number of elements (an `isize` or a `usize`), like
+    /// `p.offset(n)` or `p.add(n)`. Undefined behavior if the result is out of the bounds
+    /// of the allocation.
+    Offset,
+    /// Same as [BinOp::Offset], but the result may be out of bounds (it wraps around):
+    /// `p.wrapping_offset(n)` or `p.wrapping_add(n)`.
+    WrappingOffset,
+    /// The distance between two raw pointers to the same allocation, in number of elements
+    /// (`p.offset_from(q)`). The result has type `isize`.
+    ///
+    /// Note that the raw pointers are compared with [BinOp::Eq], [BinOp::Lt], etc. (which
+    /// compare the addresses, and the metadata of the fat pointers).
+    PtrDiff,
+    /// `x.checked_add(y)`: the result has type `Option<T>`, and is `None` if the
+    /// addition overflows.
+    CheckedAdd,
+    /// `x.checked_sub(y)`, see [BinOp::CheckedAdd].
+    CheckedSub,
+    /// `x.checked_mul(y)`, see [BinOp::CheckedAdd].
+    CheckedMul,
+    /// `x.wrapping_add(y)`: the result wraps around on overflow.
+    WrappingAdd,
+    /// `x.wrapping_sub(y)`, see [BinOp::WrappingAdd].
+    WrappingSub,
+    /// `x.wrapping_mul(y)`, see [BinOp::WrappingAdd].
+    WrappingMul,
+    /// `x.overflowing_add(y)`: the result has type `(T, bool)`, and gives the wrapped
+    /// result of the addition and whether it overflowed.
+    OverflowingAdd,
+    /// `x.overflowing_sub(y)`, see [BinOp::OverflowingAdd].
+    OverflowingSub,
+    /// `x.overflowing_mul(y)`, see [BinOp::OverflowingAdd].
+    OverflowingMul,
+    /// `x.saturating_add(y)`: the result is the bound of the integer type on overflow.
+    SaturatingAdd,
+    /// `x.saturating_sub(y)`, see [BinOp::SaturatingAdd].
+    SaturatingSub,
+    /// `x.saturating_mul(y)`, see [BinOp::SaturatingAdd].
+    SaturatingMul,
 }
 
 #[derive(
@@ -156,6 +217,11 @@ pub enum Operand {
     Move(Place),
     /// Constant value (including constant and static variables)
     Const(ConstantExpr),
+    /// A nested expression, together with its type. The MIR only has places
+    /// and constants as operands: we only introduce those in
+    /// [crate::transform::reconstruct_expressions], if the option
+    /// `--reconstruct-expressions` is set.
+    Expr(Box<Rvalue>, Ty),
 }
 
 /// A function identifier. See [crate::ullbc_ast::Terminator]
@@ -298,6 +364,13 @@ pub enum RawConstantExpr {
     /// ```
     Global(GlobalDeclId::Id, GenericArgs),
     ///
+    /// A promoted constant of the current declaration, like `&3u32` in
+    /// `f(&3u32)`, given by its index among the promoted constants of the
+    /// declaration. The promoted constants are translated as globals, which
+    /// the body lists (see [crate::gast::GExprBody::promoted] and
+    /// [crate::gast::PromotedInfo]). They have the generics of the declaration.
+    Promoted(PromotedId::Id, GenericArgs),
+    ///
     /// A trait constant.
     ///
     /// Ex.:
@@ -319,6 +392,11 @@ pub enum RawConstantExpr {
     Var(ConstGenericVarId::Id),
     /// Function pointer
     FnPtr(FnPtr),
+    /// A raw pointer to a `static mut` (the value has type `*mut T`). Rust
+    /// only gives access to the mutable statics through such pointers, and
+    /// only in unsafe code: we keep this case as a constant (contrary to
+    /// [RawConstantExpr::Global], we don't desugar it to a read of the global).
+    MutStatic(GlobalDeclId::Id),
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Serialize)]
@@ -329,7 +407,7 @@ pub struct ConstantExpr {
 
 /// TODO: we could factor out [Rvalue] and function calls (for LLBC, not ULLBC).
 /// We can also factor out the unops, binops with the function calls.
-#[derive(Debug, Clone, Serialize, EnumToGetters, EnumAsGetters, EnumIsA)]
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, EnumToGetters, EnumAsGetters, EnumIsA)]
 pub enum Rvalue {
     Use(Operand),
     Ref(Place, BorrowKind),
@@ -338,8 +416,9 @@ pub enum Rvalue {
     /// Binary operations (note that we merge "checked" and "unchecked" binops)
     BinaryOp(BinOp, Operand, Operand),
     /// Discriminant (for enumerations).
-    /// Note that discriminant values have type isize. We also store the identifier
-    /// of the type from which we read the discriminant.
+    /// Note that discriminant values have the discriminant type of the
+    /// enumeration (see [crate::types::TypeDeclKind::Enum]). We also store the
+    /// identifier of the type from which we read the discriminant.
     ///
     /// This case is filtered in [crate::remove_read_discriminant]
     Discriminant(Place, TypeDeclId::Id),
@@ -384,9 +463,12 @@ pub enum Rvalue {
     ///
     /// We desugar this to a function call.
     Repeat(Operand, Ty, ConstGeneric),
+    /// Nullary operation on a type, like `mem::size_of::<T>()` once inlined.
+    /// The result has type `usize`.
+    NullaryOp(NullOp, Ty),
 }
 
-#[derive(Debug, Clone, VariantIndexArity, Serialize)]
+#[derive(Debug, PartialEq, Eq, Clone, VariantIndexArity, Serialize)]
 pub enum AggregateKind {
     Adt(TypeId, Option<VariantId::Id>, GenericArgs),
     /// We don't put this with the ADT cas because this is the only assumed type
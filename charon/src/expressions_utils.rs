@@ -75,6 +75,21 @@ impl std::fmt::Display for BinOp {
             BinOp::Mul => write!(f, "*"),
             BinOp::Shl => write!(f, "<<"),
             BinOp::Shr => write!(f, ">>"),
+            BinOp::Offset => write!(f, "offset"),
+            BinOp::WrappingOffset => write!(f, "wrapping_offset"),
+            BinOp::PtrDiff => write!(f, "ptr_diff"),
+            BinOp::CheckedAdd => write!(f, "checked_add"),
+            BinOp::CheckedSub => write!(f, "checked_sub"),
+            BinOp::CheckedMul => write!(f, "checked_mul"),
+            BinOp::WrappingAdd => write!(f, "wrapping_add"),
+            BinOp::WrappingSub => write!(f, "wrapping_sub"),
+            BinOp::WrappingMul => write!(f, "wrapping_mul"),
+            BinOp::OverflowingAdd => write!(f, "overflowing_add"),
+            BinOp::OverflowingSub => write!(f, "overflowing_sub"),
+            BinOp::OverflowingMul => write!(f, "overflowing_mul"),
+            BinOp::SaturatingAdd => write!(f, "saturating_add"),
+            BinOp::SaturatingSub => write!(f, "saturating_sub"),
+            BinOp::SaturatingMul => write!(f, "saturating_mul"),
         }
     }
 }
@@ -114,6 +129,14 @@ impl Place {
                     }
                 },
                 ProjectionElem::Index(i, _) => out = format!("({out})[{}]", ctx.format_object(*i)),
+                ProjectionElem::Subslice { from, to, from_end } => {
+                    let to = if *from_end {
+                        format!("-{to}")
+                    } else {
+                        to.to_string()
+                    };
+                    out = format!("({out})[{from}..{to}]")
+                }
             }
         }
 
@@ -161,6 +184,12 @@ impl RawConstantExpr {
                     generics.fmt_with_ctx_split_trait_refs(ctx)
                 )
             }
+            RawConstantExpr::Promoted(id, generics) => {
+                format!(
+                    "promoted[{id}]{}",
+                    generics.fmt_with_ctx_split_trait_refs(ctx)
+                )
+            }
             RawConstantExpr::TraitConst(trait_ref, name) => {
                 format!("{}::{name}", trait_ref.fmt_with_ctx(ctx),)
             }
@@ -171,6 +200,7 @@ impl RawConstantExpr {
             RawConstantExpr::FnPtr(f) => {
                 format!("{}", f.fmt_with_ctx(ctx),)
             }
+            RawConstantExpr::MutStatic(id) => format!("&raw mut {}", ctx.format_object(*id)),
         }
     }
 }
@@ -204,6 +234,17 @@ impl std::fmt::Display for ConstantExpr {
 }
 
 impl Operand {
+    /// The literal of the operand, if it is a literal constant
+    pub fn as_literal(&self) -> Option<&Literal> {
+        match self {
+            Operand::Const(ConstantExpr {
+                value: RawConstantExpr::Literal(lit),
+                ..
+            }) => Some(lit),
+            _ => None,
+        }
+    }
+
     pub fn fmt_with_ctx<C>(&self, ctx: &C) -> String
     where
         C: AstFormatter,
@@ -212,6 +253,7 @@ impl Operand {
             Operand::Copy(p) => format!("copy ({})", p.fmt_with_ctx(ctx)),
             Operand::Move(p) => format!("move ({})", p.fmt_with_ctx(ctx)),
             Operand::Const(c) => format!("const ({})", c.fmt_with_ctx(ctx)),
+            Operand::Expr(rv, _) => format!("({})", rv.fmt_with_ctx(ctx)),
         }
     }
 }
@@ -299,6 +341,27 @@ impl Rvalue {
             Rvalue::Repeat(op, _ty, cg) => {
                 format!("[{}; {}]", op.fmt_with_ctx(ctx), cg.fmt_with_ctx(ctx))
             }
+            Rvalue::NullaryOp(op, ty) => {
+                let ty = ty.fmt_with_ctx(ctx);
+                match op {
+                    NullOp::SizeOf => format!("size_of<{ty}>"),
+                    NullOp::AlignOf => format!("align_of<{ty}>"),
+                    NullOp::OffsetOf(path) => {
+                        let path: Vec<String> = path
+                            .iter()
+                            .map(|(proj_kind, field_id)| match proj_kind {
+                                FieldProjKind::Adt(adt_id, variant_id) => {
+                                    ctx.format_object((*adt_id, *variant_id, *field_id))
+                                }
+                                FieldProjKind::Tuple(_) | FieldProjKind::ClosureState => {
+                                    field_id.to_string()
+                                }
+                            })
+                            .collect();
+                        format!("offset_of<{ty}>({})", path.join("."))
+                    }
+                }
+            }
         }
     }
 }
@@ -338,6 +401,7 @@ pub trait ExprVisitor: crate::types::TypeVisitor {
             ProjectionElem::DerefRawPtr => self.visit_deref_raw_ptr(),
             ProjectionElem::Field(proj_kind, fid) => self.visit_projection_field(proj_kind, fid),
             ProjectionElem::Index(i, _) => self.visit_var_id(i),
+            ProjectionElem::Subslice { .. } => self.visit_subslice(),
         }
     }
 
@@ -349,12 +413,14 @@ pub trait ExprVisitor: crate::types::TypeVisitor {
     fn visit_deref_box(&mut self) {}
     fn visit_deref_raw_ptr(&mut self) {}
     fn visit_projection_field(&mut self, _: &FieldProjKind, _: &FieldId::Id) {}
+    fn visit_subslice(&mut self) {}
 
     fn default_visit_operand(&mut self, o: &Operand) {
         match o {
             Operand::Copy(p) => self.visit_copy(p),
             Operand::Move(p) => self.visit_move(p),
             Operand::Const(cv) => self.visit_operand_const(cv),
+            Operand::Expr(rv, ty) => self.visit_operand_expr(rv, ty),
         }
     }
 
@@ -374,6 +440,11 @@ pub trait ExprVisitor: crate::types::TypeVisitor {
         self.visit_constant_expr(op);
     }
 
+    fn visit_operand_expr(&mut self, rv: &Rvalue, ty: &Ty) {
+        self.visit_rvalue(rv);
+        self.visit_ty(ty);
+    }
+
     fn visit_constant_expr(&mut self, expr: &ConstantExpr) {
         self.visit_ty(&expr.ty);
         self.visit_raw_constant_expr(&expr.value);
@@ -392,6 +463,7 @@ pub trait ExprVisitor: crate::types::TypeVisitor {
                 self.visit_global_decl_id(id);
                 self.visit_generic_args(generics);
             }
+            Promoted(_, generics) => self.visit_generic_args(generics),
             TraitConst(trait_ref, _name) => {
                 self.visit_trait_ref(trait_ref);
             }
@@ -400,6 +472,7 @@ pub trait ExprVisitor: crate::types::TypeVisitor {
             FnPtr(f) => {
                 self.visit_fn_ptr(f);
             }
+            MutStatic(id) => self.visit_global_decl_id(id),
         }
     }
 
@@ -423,6 +496,7 @@ pub trait ExprVisitor: crate::types::TypeVisitor {
             }
             Rvalue::Len(p, ty, cg) => self.visit_len(p, ty, cg),
             Rvalue::Repeat(op, ty, cg) => self.visit_repeat(op, ty, cg),
+            Rvalue::NullaryOp(op, ty) => self.visit_nullary_op(op, ty),
         }
     }
 
@@ -507,11 +581,24 @@ pub trait ExprVisitor: crate::types::TypeVisitor {
         self.visit_const_generic(cg);
     }
 
+    fn visit_nullary_op(&mut self, op: &NullOp, ty: &Ty) {
+        if let NullOp::OffsetOf(path) = op {
+            for (proj_kind, field_id) in path {
+                self.visit_projection_field(proj_kind, field_id)
+            }
+        }
+        self.visit_ty(ty);
+    }
+
     fn visit_call(&mut self, c: &Call) {
         let Call {
             func,
             args,
             dest,
+            receiver_adjustments: _,
+            resolution: _,
+            is_tail: _,
+            is_ghost: _,
         } = c;
         self.visit_fn_operand(func);
         for o in args {
@@ -204,7 +204,7 @@ pub trait AstFormatter = Formatter<TypeVarId::Id>
     + Formatter<(TypeDeclId::Id, VariantId::Id)>
     + Formatter<(TypeDeclId::Id, Option<VariantId::Id>, FieldId::Id)>
     + for<'a> Formatter<&'a ullbc_ast::BlockId::Vector<ullbc_ast::BlockData>>
-    + for<'a> Formatter<&'a llbc_ast::Statement>
+    + for<'a> Formatter<&'a llbc_ast::Block>
     + for<'a> SetGenerics<'a>
     + for<'a> SetLocals<'a>
     + for<'a> PushBoundRegions<'a>;
@@ -434,7 +434,7 @@ impl<'a> Formatter<(TypeDeclId::Id, Option<VariantId::Id>, FieldId::Id)> for Fmt
                         ),
                     },
                     Option::Some(gen_def) => match (&gen_def.kind, opt_variant_id) {
-                        (TypeDeclKind::Enum(variants), Some(variant_id)) => {
+                        (TypeDeclKind::Enum(variants, ..), Some(variant_id)) => {
                             let field = variants
                                 .get(variant_id)
                                 .unwrap()
@@ -442,14 +442,14 @@ impl<'a> Formatter<(TypeDeclId::Id, Option<VariantId::Id>, FieldId::Id)> for Fmt
                                 .get(field_id)
                                 .unwrap();
                             match &field.name {
-                                Option::Some(name) => name.clone(),
+                                Option::Some(name) => name.to_string(),
                                 Option::None => field_id.to_string(),
                             }
                         }
                         (TypeDeclKind::Struct(fields), None) => {
                             let field = fields.get(field_id).unwrap();
                             match &field.name {
-                                Option::Some(name) => name.clone(),
+                                Option::Some(name) => name.to_string(),
                                 Option::None => field_id.to_string(),
                             }
                         }
@@ -473,8 +473,8 @@ impl<'a> Formatter<VarId::Id> for FmtCtx<'a> {
     }
 }
 
-impl<'a> Formatter<&llbc_ast::Statement> for FmtCtx<'a> {
-    fn format_object(&self, x: &llbc_ast::Statement) -> String {
+impl<'a> Formatter<&llbc_ast::Block> for FmtCtx<'a> {
+    fn format_object(&self, x: &llbc_ast::Block) -> String {
         x.fmt_with_ctx(TAB_INCR, self)
     }
 }
@@ -4,15 +4,22 @@ pub use crate::gast_utils::*;
 use crate::generate_index_type;
 use crate::meta::{ItemMeta, Meta};
 use crate::names::Name;
+use crate::reorder_decls::AnyTransId;
 pub use crate::types::GlobalDeclId;
 pub use crate::types::TraitClauseId;
 use crate::types::*;
 pub use crate::types::{
     GenericArgs, GenericParams, TraitDeclId, TraitImplId, TraitInstanceId, TraitRef,
 };
+use crate::ullbc_ast::BlockId;
+use macros::EnumIsA;
 use serde::Serialize;
 
 generate_index_type!(FunDeclId);
+generate_index_type!(LoanId);
+generate_index_type!(ModuleId);
+generate_index_type!(PromotedId);
+generate_index_type!(ScopeId);
 
 /// A variable
 #[derive(Debug, Clone, Serialize)]
@@ -24,6 +31,23 @@ pub struct Var {
     pub name: Option<String>,
     /// The variable type
     pub ty: Ty,
+    /// The variables of the source code stored in this local, or in a part of
+    /// it, as given by the debug information of the MIR.
+    pub debug_info: Vec<VarDebugInfo>,
+}
+
+/// A variable of the source code, stored in (a part of) a local. For instance,
+/// the variables captured by a closure are fields of its state, and the
+/// variables of a function are sometimes merged by the MIR optimizations.
+#[derive(Debug, Clone, Serialize)]
+pub struct VarDebugInfo {
+    /// The name of the variable in the source code
+    pub name: String,
+    /// The part of the local which stores the variable: it is empty if the
+    /// variable is the whole local.
+    pub projection: Projection,
+    /// The location of the declaration of the variable
+    pub meta: Meta,
 }
 
 /// An expression body.
@@ -40,9 +64,105 @@ pub struct GExprBody<T> {
     /// - the input arguments
     /// - the remaining locals, used for the intermediate computations
     pub locals: VarId::Vector<Var>,
+    /// The region variables of the body. This is empty if we erase the
+    /// regions of the bodies (which is the default).
+    pub regions: BodyRegions,
+    /// The facts computed by the borrow checker. We only compute those if the
+    /// `--borrowck-facts` option is set.
+    pub borrowck_facts: Option<BorrowckFacts>,
+    /// The tree of the lexical scopes of the body (see [Scope]). This is empty
+    /// for the bodies we generate (like the closure shims).
+    pub scopes: ScopeId::Vector<Scope>,
+    /// The globals in which we translated the promoted constants of the
+    /// declaration (see [PromotedInfo]), which the body refers to with
+    /// [crate::expressions::RawConstantExpr::Promoted]. The bodies of the
+    /// promoted constants have the same list, because they may refer to each
+    /// other.
+    pub promoted: PromotedId::Vector<GlobalDeclId::Id>,
     pub body: T,
 }
 
+/// A lexical scope of a body, as given by the source scopes of the MIR. The
+/// scopes form a tree, whose root (the first scope) is the scope of the whole
+/// body: every block and every `let` introduces a new scope.
+///
+/// The locals we introduce in the micro-passes don't belong to any scope.
+#[derive(Debug, Clone, Serialize)]
+pub struct Scope {
+    pub index: ScopeId::Id,
+    /// The parent scope (`None` for the root scope)
+    pub parent: Option<ScopeId::Id>,
+    pub meta: Meta,
+    /// Whether the scope comes from a function inlined by rustc (this only
+    /// happens in the optimized MIR).
+    pub inlined: bool,
+    /// The locals declared in this scope
+    pub locals: Vec<VarId::Id>,
+}
+
+/// The region variables introduced by the borrow checker in a function body,
+/// together with the outlives constraints it computed. We only compute those
+/// if the `--body-regions` option is set.
+///
+/// In the body, the regions of the signature are referred to with
+/// [Region::Static] and [Region::BVar], while the other region variables are
+/// referred to with [Region::Body]. Note that some regions (for instance the
+/// regions of the references we introduce for the index operations) remain
+/// erased.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BodyRegions {
+    /// The region variables local to the body
+    pub vars: RegionId::Vector<RegionVar>,
+    /// The outlives constraints between the regions. In those constraints,
+    /// the regions of the signature use the De Bruijn index 0.
+    pub constraints: Vec<RegionOutlives>,
+}
+
+/// A location in a body: this is the point before the statement of index
+/// [statement_index] in the block [block] (if the index is equal to the number
+/// of statements, this is the point before the terminator).
+///
+/// The locations refer to the ULLBC bodies, as translated from MIR (i.e.,
+/// before we apply any micro-pass).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct Location {
+    pub block: BlockId::Id,
+    pub statement_index: usize,
+}
+
+/// A loan, that is a borrow performed by the body
+#[derive(Debug, Clone, Serialize)]
+pub struct Loan {
+    pub index: LoanId::Id,
+    /// The region of the borrow
+    pub region: Region,
+    /// The location of the statement which performs the borrow
+    pub location: Location,
+}
+
+/// The facts computed by the borrow checker for a body. Those mostly come
+/// from the input facts of Polonius. The regions are the regions of the body
+/// (see [BodyRegions]).
+///
+/// We ignore the facts about the blocks that we don't translate (for instance
+/// the unwinding blocks).
+#[derive(Debug, Clone, Serialize)]
+pub struct BorrowckFacts {
+    pub loans: LoanId::Vector<Loan>,
+    /// The outlives constraints between the regions, together with the
+    /// locations at which they hold (`subset_base` in Polonius)
+    pub subset_constraints: Vec<(Region, Region, Location)>,
+    /// The locations at which the loans are invalidated, because of an access
+    /// to the borrowed place (`loan_invalidated_at` in Polonius)
+    pub loans_invalidated_at: Vec<(LoanId::Id, Location)>,
+    /// The locations at which the loans are killed, because the borrowed place
+    /// is overwritten (`loan_killed_at` in Polonius)
+    pub loans_killed_at: Vec<(LoanId::Id, Location)>,
+    /// The loans which go out of scope at every location, as computed by the
+    /// non-lexical lifetimes analysis
+    pub loans_out_of_scope_at: Vec<(Location, Vec<LoanId::Id>)>,
+}
+
 /// Item kind kind: "regular" item (not linked to a trait), trait item declaration, etc.
 ///
 /// Example:
@@ -98,11 +218,17 @@ pub struct GFunDecl<T> {
     /// an external crate.
     pub is_local: bool,
     pub name: Name,
+    /// The lang item the function corresponds to, if any (for instance `deref_method` for
+    /// `Deref::deref`): the backends can identify those items without looking at their names.
+    pub lang_item: Option<String>,
     /// The signature contains the inputs/output types *with* non-erased regions.
     /// It also contains the list of region and type parameters.
     pub signature: FunSig,
     /// The function kind: "regular" function, trait method declaration, etc.
     pub kind: ItemKind,
+    /// If the function is (mutually) recursive, the functions of the recursive group it belongs
+    /// to, including itself (see [crate::reorder_decls::DeclarationGroup]).
+    pub rec_group: Option<Vec<FunDeclId::Id>>,
     /// The function body, in case the function is not opaque.
     /// Opaque functions are: external functions, or local functions tagged
     /// as opaque.
@@ -124,11 +250,40 @@ pub struct GGlobalDecl<T> {
     pub generics: GenericParams,
     pub preds: Predicates,
     pub ty: Ty,
+    /// [true] for the `static mut` (see [crate::expressions::RawConstantExpr::MutStatic]).
+    pub is_mutable: bool,
+    /// [true] for the `#[thread_local]` statics, which have one instance per
+    /// thread.
+    pub is_thread_local: bool,
+    /// [true] if the type of the global has interior mutability (it contains
+    /// an `UnsafeCell`): such a global can be modified even if it is not
+    /// mutable, e.g. `static COUNTER: AtomicUsize`.
+    pub is_interior_mutable: bool,
     /// The global kind: "regular" function, trait const declaration, etc.
     pub kind: ItemKind,
+    /// [Some] if the global is a promoted constant (see [PromotedInfo]).
+    pub promoted: Option<PromotedInfo>,
+    /// The globals whose initializers must be executed before the initializer
+    /// of this global: those its body refers to, directly or through the
+    /// functions it refers to. If the initializers are cyclic (which can only
+    /// happen with statics), the globals belong to a recursive group of
+    /// [crate::reorder_decls::DeclarationGroup].
+    pub init_deps: Vec<GlobalDeclId::Id>,
     pub body: Option<GExprBody<T>>,
 }
 
+/// The constants which appear in the bodies, like `&3u32` in `f(&3u32)`, are
+/// promoted by rustc: their values are computed in separate bodies, which the
+/// body of the function refers to. We translate those bodies as globals,
+/// which share the generics of their parent declaration.
+#[derive(Debug, Clone, Serialize)]
+pub struct PromotedInfo {
+    /// The declaration whose body contains the constant
+    pub parent: AnyTransId,
+    /// The index of the constant among the promoted constants of the parent
+    pub index: PromotedId::Id,
+}
+
 #[derive(Debug, Clone, Serialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct TraitItemName(pub String);
 
@@ -173,6 +328,9 @@ pub struct TraitDecl {
     pub is_local: bool,
     pub item_meta: ItemMeta,
     pub name: Name,
+    /// The lang item the trait corresponds to, if any (for instance `add` for `core::ops::Add`,
+    /// `eq` for `PartialEq`, `fn_once` for `FnOnce`).
+    pub lang_item: Option<String>,
     pub generics: GenericParams,
     pub preds: Predicates,
     /// The "parent" clauses: the supertraits.
@@ -193,7 +351,13 @@ pub struct TraitDecl {
     /// The optional id is for the default value.
     pub consts: Vec<(TraitItemName, (Ty, Option<GlobalDeclId::Id>))>,
     /// The associated types declared in the trait.
-    pub types: Vec<(TraitItemName, (Vec<TraitClause>, Option<Ty>))>,
+    ///
+    /// The generics are those of the associated type: they start with the
+    /// parameters of the trait, followed by the parameters which are specific
+    /// to the associated type if it is a generic associated type (for
+    /// instance: `type Item<'a>`). This is similar to what we do for the
+    /// methods.
+    pub types: Vec<(TraitItemName, (GenericParams, Vec<TraitClause>, Option<Ty>))>,
     /// The *required* methods.
     ///
     /// The required methods are the methods declared by the trait but with
@@ -214,6 +378,21 @@ pub struct TraitDecl {
     pub provided_methods: Vec<(TraitItemName, Option<FunDeclId::Id>)>,
 }
 
+/// How a trait implementation was introduced.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub enum TraitImplKind {
+    /// A hand-written impl.
+    Regular,
+    /// A blanket impl, that is an impl for a type parameter:
+    /// ```text
+    /// impl<T: Foo> Bar for T { ... }
+    /// ```
+    Blanket,
+    /// An impl generated by a `#[derive(...)]` attribute. We store the name of
+    /// the derive macro (`Clone`, `PartialEq`, etc.).
+    Derived(String),
+}
+
 /// A trait **implementation**.
 ///
 /// For instance:
@@ -228,10 +407,19 @@ pub struct TraitDecl {
 pub struct TraitImpl {
     pub def_id: TraitImplId::Id,
     /// [true] if the decl is a local decl, [false] if it comes from
-    /// an external crate.
+    /// an external crate (this is a foreign impl).
     pub is_local: bool,
     pub name: Name,
     pub item_meta: ItemMeta,
+    /// Whether the impl is hand-written, a blanket impl or a derived impl.
+    pub impl_kind: TraitImplKind,
+    /// [true] if this is a negative impl (`impl !Send for Foo {}`): such
+    /// impls don't have parent clauses nor items.
+    pub is_negative: bool,
+    /// [true] if this is a `const` impl (`impl const Foo for Bar`): its
+    /// methods can be called in a const context, in particular to satisfy
+    /// the `~const` clauses (see [TraitClause::is_const]).
+    pub is_const: bool,
     /// The information about the implemented trait.
     /// Note that this contains the instantiation of the "parent"
     /// clauses.
@@ -243,11 +431,77 @@ pub struct TraitImpl {
     /// The associated constants declared in the trait.
     pub consts: Vec<(TraitItemName, (Ty, GlobalDeclId::Id))>,
     /// The associated types declared in the trait.
-    pub types: Vec<(TraitItemName, (Vec<TraitRef>, Ty))>,
+    ///
+    /// As for [TraitDecl::types], the generics start with the parameters of
+    /// the impl, followed by the parameters which are specific to the
+    /// associated type.
+    pub types: Vec<(TraitItemName, (GenericParams, Vec<TraitRef>, Ty))>,
     /// The implemented required methods
     pub required_methods: Vec<(TraitItemName, FunDeclId::Id)>,
-    /// The re-implemented provided methods
-    pub provided_methods: Vec<(TraitItemName, FunDeclId::Id)>,
+    /// The provided methods.
+    ///
+    /// The boolean is [true] if the method is re-implemented by the trait impl,
+    /// and [false] if the identifier is the one of the default implementation
+    /// given in the trait declaration (see [TraitDecl::provided_methods]: we
+    /// only list the non-overridden methods if this identifier is known).
+    pub provided_methods: Vec<(TraitItemName, (FunDeclId::Id, bool))>,
+    /// The items which can be specialized by other impls (the items declared
+    /// with `default`, or all the items of a `default impl`), see
+    /// [TraitInstanceId::Specializable].
+    pub default_items: Vec<TraitItemName>,
+}
+
+/// A module of the crate. We export the module tree of the crate, so that the consumers which
+/// generate one file per module don't have to reconstruct it from the names of the items.
+#[derive(Debug, Clone, Serialize)]
+pub struct Module {
+    pub module_id: ModuleId::Id,
+    pub item_meta: ItemMeta,
+    pub name: Name,
+    /// The parent module (`None` for the crate root).
+    pub parent: Option<ModuleId::Id>,
+    pub submodules: Vec<ModuleId::Id>,
+    /// The translated items declared in the module. The methods and associated items are listed
+    /// in the module of their impl block or trait.
+    pub items: Vec<AnyTransId>,
+}
+
+/// A verification entry point: a function marked with an attribute like `#[kani::proof]` or
+/// `#[test]` (see [crate::assumed::get_harness_attributes]). We export the list of harnesses so
+/// that the drivers of the verifiers don't have to parse the attributes.
+#[derive(Debug, Clone, Serialize)]
+pub struct Harness {
+    pub fun_id: FunDeclId::Id,
+    /// The path of the attribute, like `kani::proof`.
+    pub attribute: String,
+    /// The arguments of the attribute, like `["f"]` for `#[kani::proof_for_contract(f)]`.
+    pub args: Vec<String>,
+}
+
+/// A trait clause in scope in an item (see [ClauseEnv]).
+#[derive(Debug, Clone, Serialize)]
+pub struct ElaboratedClause {
+    /// The path of the clause, as it appears in the trait references of the item: `Clause(i)`
+    /// for the clauses of the item, `SelfId` for the clause `Self: Trait` of a trait declaration,
+    /// and chains of [TraitInstanceId::ParentClause] and [TraitInstanceId::ItemClause] for the
+    /// clauses we derive from those.
+    pub trait_id: TraitInstanceId,
+    /// The regions universally quantified at the level of the clause (see
+    /// [TraitClause::bound_regions]).
+    pub bound_regions: RegionId::Vector<RegionVar>,
+    /// The trait the clause implements, instantiated with the generics of the item.
+    pub trait_decl_ref: TraitDeclRef,
+}
+
+/// The trait clauses in scope in an item: its own clauses, and the clauses we derive from them
+/// by following the parent clauses (the supertraits) and the item clauses (the bounds on the
+/// associated types) of the traits. We export those environments so that the consumers which
+/// build typing environments use the same paths as the trait references of the item, rather
+/// than elaborating the clauses themselves (see [ClauseEnv::new]).
+#[derive(Debug, Clone, Serialize)]
+pub struct ClauseEnv {
+    pub item: AnyTransId,
+    pub clauses: Vec<ElaboratedClause>,
 }
 
 /// A function operand is used in function calls.
@@ -261,9 +515,127 @@ pub enum FnOperand {
     Move(Place),
 }
 
+/// An adjustment the compiler inserted on the receiver of a method call (auto-ref, auto-deref,
+/// deref coercion). The corresponding operations are already present in the body, as regular
+/// statements: the adjustments allow the consumers to distinguish them from the borrows and
+/// dereferences written by the user.
+#[derive(Debug, Clone, Serialize)]
+pub enum Adjustment {
+    /// A built-in dereference (of a reference, a raw pointer or a box).
+    Deref,
+    /// A call to `Deref::deref`, or to `DerefMut::deref_mut` if the kind is [RefKind::Mut].
+    OverloadedDeref(RefKind),
+    /// A borrow (auto-ref).
+    Borrow(RefKind),
+    /// An unsizing coercion (for instance from `&[T; N]` to `&[T]`).
+    Unsize,
+}
+
+/// How the compiler resolved a method call: which implementation of the method is called. For
+/// the trait methods, this complements the [TraitInstanceId] of the trait reference, which
+/// doesn't tell if the impl overrides the method.
+#[derive(Debug, Clone, Serialize)]
+pub enum MethodResolution {
+    /// A method of an inherent impl (`impl Foo { ... }`).
+    Inherent,
+    /// A method implemented by the given trait impl.
+    TraitImpl(TraitImplId::Id),
+    /// The default implementation of a method, given in the trait declaration, which the given
+    /// trait impl doesn't override.
+    DefaultMethod(TraitImplId::Id),
+    /// A method of a builtin or auto trait implementation (see
+    /// [TraitInstanceId::BuiltinOrAuto]).
+    BuiltinOrAuto(TraitDeclId::Id),
+    /// A method of a trait implementation which comes from a clause: the implementation is only
+    /// known once the generics are instantiated.
+    Clause,
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct Call {
     pub func: FnOperand,
     pub args: Vec<Operand>,
     pub dest: Place,
+    /// If this is a method call, the adjustments the compiler applied to the receiver (the
+    /// first argument), in the order in which it applied them. This is empty for the other
+    /// calls, and for the calls in the bodies of external items.
+    pub receiver_adjustments: Vec<Adjustment>,
+    /// If this is a method call, how the method was resolved. This is `None` for the other
+    /// calls, and for the calls we generate.
+    pub resolution: Option<MethodResolution>,
+    /// [true] if the call is in tail position: its result is returned, and only drops happen
+    /// between the call and the return (see [crate::transform::mark_tail_calls]).
+    pub is_tail: bool,
+    /// [true] if the call is ghost code: the callee is marked with `#[charon::ghost]`, or the
+    /// call comes from the expansion of a `ghost!` macro. The ghost code only exists for the
+    /// specifications: it is not part of the executable semantics.
+    pub is_ghost: bool,
+}
+
+/// A copy of `count` values from the raw pointer `src` to the raw pointer `dst`, like
+/// `ptr::copy_nonoverlapping(src, dst, count)`. The values have the type of the pointees.
+#[derive(Debug, Clone, Serialize)]
+pub struct MemCopy {
+    pub src: Operand,
+    pub dst: Operand,
+    pub count: Operand,
+    /// `true` if the two memory areas must not overlap (`ptr::copy_nonoverlapping`),
+    /// `false` if they may overlap (`ptr::copy`).
+    pub nonoverlapping: bool,
+}
+
+/// Set the bytes of `count` values pointed to by the raw pointer `dst` to `val` (a `u8`), like
+/// `ptr::write_bytes(dst, val, count)`.
+#[derive(Debug, Clone, Serialize)]
+pub struct WriteBytes {
+    pub dst: Operand,
+    pub val: Operand,
+    pub count: Operand,
+}
+
+/// The meaning of a domain-specific intrinsic (see [Intrinsic]).
+#[derive(Debug, PartialEq, Eq, Clone, EnumIsA, Serialize)]
+pub enum IntrinsicKind {
+    /// Assume that the argument (a boolean) is true, like `my_dsl::assume(b)`.
+    Assume,
+    /// Check that the argument (a boolean) is true.
+    Assert,
+    /// Give an arbitrary value to the destination, like `verifier::nondet()`.
+    Havoc,
+    /// A statement whose meaning is only known to the consumer of the crate,
+    /// identified by its name.
+    Custom(String),
+}
+
+/// A call to a domain-specific intrinsic: a function which the tool embedding
+/// Charon asked us to translate to a dedicated statement rather than to a call
+/// (see [crate::driver::CharonCallbacks::register_intrinsic]). We also translate
+/// the built-ins of the verification frontends (`kani::assume`, `prusti::assert`,
+/// `kani::any`, etc.: see [crate::assumed::get_verifier_primitives]) to those
+/// statements, so that the consumers share the same encoding.
+#[derive(Debug, Clone, Serialize)]
+pub struct Intrinsic {
+    pub kind: IntrinsicKind,
+    pub args: Vec<Operand>,
+    /// The place receiving the result of the call (of type unit, unless the
+    /// intrinsic is [IntrinsicKind::Havoc] or [IntrinsicKind::Custom]).
+    pub dest: Place,
+    /// The message given by the user to an assertion, if it is a string
+    /// literal (we then don't keep it in the arguments).
+    pub message: Option<String>,
+}
+
+/// The dynamic check performed by an assertion.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, EnumIsA, Serialize)]
+pub enum AssertKind {
+    /// An assertion which comes from the code of the user, that we reconstructed from an
+    /// `if ... { panic!() }` (see [crate::reconstruct_asserts]).
+    User,
+    /// The check that an index is smaller than the length of the array or slice it accesses.
+    Bounds,
+    /// The check that an arithmetic operation (addition, negation, shift, signed division of
+    /// `MIN` by `-1`, etc.) doesn't overflow.
+    Overflow,
+    /// The check that the divisor of a division or a remainder is not zero.
+    DivisionByZero,
 }
@@ -4,6 +4,7 @@ use crate::common::TAB_INCR;
 use crate::formatter::{AstFormatter, Formatter, SetGenerics, SetLocals};
 use crate::gast::*;
 use crate::names::Name;
+use crate::reorder_decls::AnyTransId;
 use crate::types::*;
 use crate::values::*;
 use rustc_hir::def_id::DefId;
@@ -39,6 +40,7 @@ pub fn make_locals_generator(locals: &mut VarId::Vector<Var>) -> impl FnMut(Ty)
             index,
             name: None,
             ty,
+            debug_info: Vec::new(),
         })
     }
 }
@@ -99,24 +101,30 @@ impl TraitDecl {
                                 }
                             }
                         })
-                        .chain(self.types.iter().map(|(name, (trait_clauses, opt_ty))| {
-                            let trait_clauses: Vec<_> =
-                                trait_clauses.iter().map(|x| x.fmt_with_ctx(ctx)).collect();
-                            let clauses = fmt_where_clauses(
-                                &format!("{TAB_INCR}{TAB_INCR}"),
-                                0,
-                                trait_clauses,
-                            );
-                            match opt_ty {
-                                None => format!("{TAB_INCR}type {name}{clauses}\n"),
-                                Some(ty) => {
-                                    format!(
-                                        "{TAB_INCR}type {name} = {}{clauses}\n",
-                                        ty.fmt_with_ctx(ctx)
-                                    )
+                        .chain(self.types.iter().map(
+                            |(name, (generics, trait_clauses, opt_ty))| {
+                                // The generics of the associated type include the
+                                // generics of the trait: we only print its own params
+                                let params = generics.fmt_own_params(&self.generics);
+                                let ctx = &ctx.set_generics(generics);
+                                let trait_clauses: Vec<_> =
+                                    trait_clauses.iter().map(|x| x.fmt_with_ctx(ctx)).collect();
+                                let clauses = fmt_where_clauses(
+                                    &format!("{TAB_INCR}{TAB_INCR}"),
+                                    0,
+                                    trait_clauses,
+                                );
+                                match opt_ty {
+                                    None => format!("{TAB_INCR}type {name}{params}{clauses}\n"),
+                                    Some(ty) => {
+                                        format!(
+                                            "{TAB_INCR}type {name}{params} = {}{clauses}\n",
+                                            ty.fmt_with_ctx(ctx)
+                                        )
+                                    }
                                 }
-                            }
-                        }))
+                            },
+                        ))
                         .chain(self.required_methods.iter().map(|(name, f)| {
                             format!("{TAB_INCR}fn {name} : {}\n", ctx.format_object(*f))
                         }))
@@ -150,6 +158,14 @@ impl TraitImpl {
         let clauses = fmt_where_clauses_with_ctx(ctx, "", &None, trait_clauses, &self.preds);
 
         let items = {
+            // We mark the provided methods which are not overridden
+            let methods = self
+                .required_methods
+                .iter()
+                .map(|(name, f)| (name, f, ""))
+                .chain(self.provided_methods.iter().map(|(name, (f, overrides))| {
+                    (name, f, if *overrides { "" } else { " (default)" })
+                }));
             let items = self
                 .parent_trait_refs
                 .iter()
@@ -168,26 +184,23 @@ impl TraitImpl {
                         ctx.format_object(*id)
                     )
                 }))
-                .chain(self.types.iter().map(|(name, (trait_refs, ty))| {
+                .chain(self.types.iter().map(|(name, (generics, trait_refs, ty))| {
+                    let params = generics.fmt_own_params(&self.generics);
+                    let ctx = &ctx.set_generics(generics);
                     let trait_refs = trait_refs
                         .iter()
                         .map(|x| x.fmt_with_ctx(ctx))
                         .collect::<Vec<_>>()
                         .join(", ");
                     format!(
-                        "{TAB_INCR}type {name} = {} with [{}]\n",
+                        "{TAB_INCR}type {name}{params} = {} with [{}]\n",
                         ty.fmt_with_ctx(ctx),
                         trait_refs
                     )
                 }))
-                .chain(
-                    self.required_methods
-                        .iter()
-                        .chain(self.provided_methods.iter())
-                        .map(|(name, f)| {
-                            format!("{TAB_INCR}fn {name} = {}\n", ctx.format_object(*f))
-                        }),
-                )
+                .chain(methods.map(|(name, f, default)| {
+                    format!("{TAB_INCR}fn {name} = {}{default}\n", ctx.format_object(*f))
+                }))
                 .collect::<Vec<String>>();
             if items.is_empty() {
                 "".to_string()
@@ -201,6 +214,211 @@ impl TraitImpl {
     }
 }
 
+impl TraitInstanceId {
+    /// The trait implementation this instance refers to, if it is statically known. We follow
+    /// the chains of parent clauses and item clauses through the implementations: for instance,
+    /// `ParentClause(TraitImpl(i), _, 0)` resolves to the implementation given for the parent
+    /// clause 0 of `i`, if this implementation doesn't depend on the generics of `i`.
+    pub fn resolve_impl(
+        &self,
+        trait_impls: &TraitImplId::Map<TraitImpl>,
+    ) -> Option<TraitImplId::Id> {
+        match self {
+            TraitInstanceId::TraitImpl(id) => Some(*id),
+            TraitInstanceId::ParentClause(box parent, _, clause_id) => {
+                let imp = trait_impls.get(parent.resolve_impl(trait_impls)?)?;
+                let tr = imp.parent_trait_refs.get(*clause_id)?;
+                tr.trait_id.resolve_impl(trait_impls)
+            }
+            TraitInstanceId::ItemClause(box parent, _, item_name, clause_id) => {
+                let imp = trait_impls.get(parent.resolve_impl(trait_impls)?)?;
+                let (_, (_, trait_refs, _)) =
+                    imp.types.iter().find(|(name, _)| name == item_name)?;
+                let tr = trait_refs.get(clause_id.index())?;
+                tr.trait_id.resolve_impl(trait_impls)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Substitute the generics of a trait declaration in its parent clauses and its item clauses
+/// (see [ClauseEnv::new]).
+struct SubstClause<'a> {
+    /// The generics of the trait reference
+    generics: &'a GenericArgs,
+    /// The clause the trait reference comes from: this replaces `Self`
+    self_ref: &'a TraitRef,
+    /// The number of region groups we are in: the regions of the trait are bound at this level
+    depth: usize,
+}
+
+impl<'a> MutTypeVisitor for SubstClause<'a> {
+    fn enter_region_group(
+        &mut self,
+        _regions: &mut RegionId::Vector<RegionVar>,
+        visitor: &mut dyn FnMut(&mut Self),
+    ) {
+        self.depth += 1;
+        visitor(self);
+        self.depth -= 1;
+    }
+
+    fn visit_region(&mut self, r: &mut Region) {
+        if let Region::BVar(grid, id) = r
+            && grid.index == self.depth
+            && let Some(arg) = self.generics.regions.get(id.index())
+        {
+            *r = match arg {
+                // The regions of the arguments are bound outside of the groups we are in
+                Region::BVar(grid, id) => Region::BVar(DeBruijnId::new(grid.index + self.depth), *id),
+                arg => arg.clone(),
+            }
+        }
+    }
+
+    fn visit_ty(&mut self, ty: &mut Ty) {
+        if let TyKind::TypeVar(id) = ty.kind()
+            && let Some(arg) = self.generics.types.get(id.index())
+        {
+            *ty = arg.clone()
+        } else {
+            self.default_visit_ty(ty)
+        }
+    }
+
+    fn visit_const_generic(&mut self, cg: &mut ConstGeneric) {
+        match cg {
+            ConstGeneric::Var(id) => {
+                if let Some(arg) = self.generics.const_generics.get(id.index()) {
+                    *cg = arg.clone()
+                }
+            }
+            ConstGeneric::TraitConst(tr, _) => self.visit_trait_ref(tr),
+            ConstGeneric::Global(_) | ConstGeneric::Value(_) => (),
+        }
+    }
+
+    fn visit_trait_ref(&mut self, tr: &mut TraitRef) {
+        if let TraitInstanceId::SelfId = tr.trait_id {
+            *tr = self.self_ref.clone()
+        } else {
+            tr.with_data_mut(|tr| self.visit_trait_ref_data(tr))
+        }
+    }
+
+    fn visit_trait_instance_id(&mut self, id: &mut TraitInstanceId) {
+        if let TraitInstanceId::SelfId = id {
+            *id = self.self_ref.trait_id.clone()
+        } else {
+            self.default_visit_trait_instance_id(id)
+        }
+    }
+}
+
+impl ClauseEnv {
+    /// Compute the clauses in scope in an item, given its clauses. For a trait declaration, we
+    /// also give the reference to the trait itself, which is in scope as `Self`.
+    ///
+    /// We elaborate the clauses in a breadth-first manner, so that the clauses come after the
+    /// clauses they derive from. We don't elaborate the item clauses of the item clauses, which
+    /// may be infinite (`trait Foo { type A: Foo; }`), nor the higher-ranked clauses. We can't
+    /// elaborate the clauses of the traits which were not translated.
+    pub fn new(
+        item: AnyTransId,
+        trait_decls: &TraitDeclId::Map<TraitDecl>,
+        clauses: &[TraitClause],
+        self_ref: Option<TraitDeclRef>,
+    ) -> Self {
+        let self_clause = self_ref.map(|trait_decl_ref| ElaboratedClause {
+            trait_id: TraitInstanceId::SelfId,
+            bound_regions: RegionId::Vector::new(),
+            trait_decl_ref,
+        });
+        let local_clauses = clauses.iter().map(|clause| ElaboratedClause {
+            trait_id: TraitInstanceId::Clause(clause.clause_id),
+            bound_regions: clause.bound_regions.clone(),
+            trait_decl_ref: TraitDeclRef {
+                trait_id: clause.trait_id,
+                generics: clause.generics.clone(),
+            },
+        });
+        let mut clauses: Vec<ElaboratedClause> =
+            self_clause.into_iter().chain(local_clauses).collect();
+
+        // The index of the next clause to elaborate, and whether the clauses are item clauses
+        let mut next = 0;
+        let mut from_item = vec![false; clauses.len()];
+        while next < clauses.len() {
+            let clause = clauses[next].clone();
+            let is_item_clause = from_item[next];
+            next += 1;
+            let Some(decl) = trait_decls.get(clause.trait_decl_ref.trait_id) else {
+                continue;
+            };
+            if !clause.bound_regions.is_empty() {
+                continue;
+            }
+            let self_ref = TraitRef::new(
+                clause.trait_id.clone(),
+                GenericArgs::empty(),
+                clause.trait_decl_ref.clone(),
+            );
+            let subst = |c: &TraitClause, trait_id: TraitInstanceId| {
+                let mut generics = c.generics.clone();
+                let mut visitor = SubstClause {
+                    generics: &clause.trait_decl_ref.generics,
+                    self_ref: &self_ref,
+                    // The regions of the clause are bound inside its generics
+                    depth: if c.bound_regions.is_empty() { 0 } else { 1 },
+                };
+                visitor.visit_generic_args(&mut generics);
+                ElaboratedClause {
+                    trait_id,
+                    bound_regions: c.bound_regions.clone(),
+                    trait_decl_ref: TraitDeclRef {
+                        trait_id: c.trait_id,
+                        generics,
+                    },
+                }
+            };
+            for c in decl.parent_clauses.iter() {
+                let trait_id = TraitInstanceId::ParentClause(
+                    Box::new(clause.trait_id.clone()),
+                    decl.def_id,
+                    c.clause_id,
+                );
+                clauses.push(subst(c, trait_id));
+                from_item.push(is_item_clause);
+            }
+            if is_item_clause {
+                continue;
+            }
+            for (name, (params, item_clauses, _)) in decl.types.iter() {
+                // The clauses of the generic associated types are quantified over their own
+                // generics
+                if params.regions.len() > decl.generics.regions.len()
+                    || params.types.len() > decl.generics.types.len()
+                    || params.const_generics.len() > decl.generics.const_generics.len()
+                {
+                    continue;
+                }
+                for c in item_clauses.iter() {
+                    let trait_id = TraitInstanceId::ItemClause(
+                        Box::new(clause.trait_id.clone()),
+                        decl.def_id,
+                        name.clone(),
+                        c.clause_id,
+                    );
+                    clauses.push(subst(c, trait_id));
+                    from_item.push(true);
+                }
+            }
+        }
+        ClauseEnv { item, clauses }
+    }
+}
+
 impl FnOperand {
     pub fn fmt_with_ctx<C>(&self, ctx: &C) -> String
     where
@@ -222,7 +440,64 @@ where
     let args: Vec<String> = call.args.iter().map(|x| x.fmt_with_ctx(ctx)).collect();
     let args = args.join(", ");
     let f = call.func.fmt_with_ctx(ctx);
-    (format!("{f}({args})"), None)
+    let ghost = if call.is_ghost { "ghost " } else { "" };
+    (format!("{ghost}{f}({args})"), None)
+}
+
+impl MemCopy {
+    pub fn fmt_with_ctx<C>(&self, ctx: &C) -> String
+    where
+        C: AstFormatter,
+    {
+        let name = if self.nonoverlapping {
+            "copy_nonoverlapping"
+        } else {
+            "copy"
+        };
+        format!(
+            "@{name}({}, {}, {})",
+            self.src.fmt_with_ctx(ctx),
+            self.dst.fmt_with_ctx(ctx),
+            self.count.fmt_with_ctx(ctx)
+        )
+    }
+}
+
+impl Intrinsic {
+    pub fn fmt_with_ctx<C>(&self, ctx: &C) -> String
+    where
+        C: AstFormatter,
+    {
+        let name = match &self.kind {
+            IntrinsicKind::Assume => "assume",
+            IntrinsicKind::Assert => "assert",
+            IntrinsicKind::Havoc => "havoc",
+            IntrinsicKind::Custom(name) => name,
+        };
+        let mut args: Vec<String> = self.args.iter().map(|x| x.fmt_with_ctx(ctx)).collect();
+        if let Some(message) = &self.message {
+            args.push(format!("{message:?}"));
+        }
+        format!(
+            "{} := @{name}({})",
+            self.dest.fmt_with_ctx(ctx),
+            args.join(", ")
+        )
+    }
+}
+
+impl WriteBytes {
+    pub fn fmt_with_ctx<C>(&self, ctx: &C) -> String
+    where
+        C: AstFormatter,
+    {
+        format!(
+            "@write_bytes({}, {}, {})",
+            self.dst.fmt_with_ctx(ctx),
+            self.val.fmt_with_ctx(ctx),
+            self.count.fmt_with_ctx(ctx)
+        )
+    }
 }
 
 impl<T> GExprBody<T> {
@@ -273,6 +548,14 @@ impl<T> GExprBody<T> {
         }
 
         let mut locals = locals.join("");
+        // Format the constraints between the body regions
+        for OutlivesPred(r0, r1) in &self.regions.constraints {
+            locals.push_str(&format!(
+                "{tab}// {} : {}\n",
+                r0.fmt_with_ctx(ctx),
+                r1.fmt_with_ctx(ctx)
+            ));
+        }
         locals.push('\n');
 
         // Format the body blocks - TODO: we don't take the indentation
@@ -308,7 +591,12 @@ impl<T> GFunDecl<T> {
         // Update the context
         let ctx = &ctx.set_generics(&self.signature.generics);
 
-        // Unsafe keyword
+        // Const and unsafe keywords
+        let const_kw = if self.signature.is_const {
+            "const "
+        } else {
+            ""
+        };
         let unsafe_kw = if self.signature.is_unsafe {
             "unsafe ".to_string()
         } else {
@@ -354,7 +642,7 @@ impl<T> GFunDecl<T> {
         match &self.body {
             Option::None => {
                 // Put everything together
-                format!("{tab}{unsafe_kw}fn {name}{params}({args}){ret_ty}{preds}")
+                format!("{tab}{const_kw}{unsafe_kw}fn {name}{params}({args}){ret_ty}{preds}")
             }
             Option::Some(body) => {
                 // Body
@@ -363,7 +651,7 @@ impl<T> GFunDecl<T> {
 
                 // Put everything together
                 format!(
-                    "{tab}{unsafe_kw}fn {name}{params}({args}){ret_ty}{preds}\n{tab}{{\n{body}\n{tab}}}",
+                    "{tab}{const_kw}{unsafe_kw}fn {name}{params}({args}){ret_ty}{preds}\n{tab}{{\n{body}\n{tab}}}",
                 )
             }
         }
@@ -0,0 +1,824 @@
+//! Generate the OCaml definitions of the AST, together with the functions to
+//! deserialize them (see the `--generate-ml` option).
+//!
+//! We parse the source files which define the AST, and translate every type
+//! which derives `Serialize` (as well as the type aliases), following the
+//! representation chosen by serde (see [TypeDef]). The indices are represented
+//! with integers, and the types which are serialized with custom functions are
+//! left as JSON values. The generated deserializers rely on the helpers of
+//! `OfJsonBasic`: they are meant to help keeping `charon-ml` in sync with the
+//! Rust definitions (the hand-written deserializers of `charon-ml` also
+//! post-process the AST, for instance to replace the file ids with the files).
+//!
+//! Remark: this module is also compiled with the `charon` binary, which doesn't
+//! link with the rest of the library: we can only depend on external crates.
+use heck::SnakeCase;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Write;
+use std::path::Path;
+
+/// The source files of the AST, together with their module names.
+const SOURCES: [(&str, &str); 11] = [
+    ("meta", include_str!("meta.rs")),
+    ("names", include_str!("names.rs")),
+    ("symbol", include_str!("symbol.rs")),
+    ("types", include_str!("types.rs")),
+    ("values", include_str!("values.rs")),
+    ("expressions", include_str!("expressions.rs")),
+    ("gast", include_str!("gast.rs")),
+    ("ullbc_ast", include_str!("ullbc_ast.rs")),
+    ("llbc_ast", include_str!("llbc_ast.rs")),
+    ("reorder_decls", include_str!("reorder_decls.rs")),
+    ("export", include_str!("export.rs")),
+];
+
+/// The name of the generated file containing the type definitions.
+pub const TYPES_FILE: &str = "GeneratedTypes.ml";
+/// The name of the generated file containing the deserializers.
+pub const OF_JSON_FILE: &str = "GeneratedOfJson.ml";
+
+/// The OCaml keywords, and the names of the predefined types: we can't use
+/// them as type or field names.
+const RESERVED: [&str; 62] = [
+    "and",
+    "as",
+    "assert",
+    "asr",
+    "begin",
+    "class",
+    "constraint",
+    "do",
+    "done",
+    "downto",
+    "else",
+    "end",
+    "exception",
+    "external",
+    "false",
+    "for",
+    "fun",
+    "function",
+    "functor",
+    "if",
+    "in",
+    "include",
+    "inherit",
+    "initializer",
+    "land",
+    "lazy",
+    "let",
+    "lor",
+    "lsl",
+    "lsr",
+    "lxor",
+    "match",
+    "method",
+    "mod",
+    "module",
+    "mutable",
+    "new",
+    "nonrec",
+    "object",
+    "of",
+    "open",
+    "or",
+    "private",
+    "rec",
+    "sig",
+    "struct",
+    "then",
+    "to",
+    "true",
+    "try",
+    "type",
+    "val",
+    "virtual",
+    "when",
+    "while",
+    "with",
+    "int",
+    "string",
+    "bool",
+    "list",
+    "option",
+    "json",
+];
+
+/// Turn an identifier into a valid OCaml (type, field or variable) name.
+fn ml_ident(name: &str) -> String {
+    let name = name.trim_start_matches("r#").to_snake_case();
+    if RESERVED.contains(&name.as_str()) {
+        format!("{name}_")
+    } else {
+        name
+    }
+}
+
+/// An OCaml type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum MlTy {
+    /// A predefined type (`int`, `string`, etc.: we use `json` for the types we
+    /// leave as JSON values).
+    Base(&'static str),
+    /// A type parameter.
+    Param(String),
+    /// An index (we represent them with integers).
+    Id(String),
+    List(Box<MlTy>),
+    Option(Box<MlTy>),
+    /// A pair or a triple.
+    Tuple(Vec<MlTy>),
+    /// A type of the AST, with its arguments.
+    Adt(String, Vec<MlTy>),
+}
+
+impl MlTy {
+    fn to_ml(&self) -> String {
+        match self {
+            MlTy::Base(name) => name.to_string(),
+            MlTy::Param(name) => format!("'{name}"),
+            MlTy::Id(name) => name.clone(),
+            MlTy::List(ty) => format!("{} list", ty.to_ml()),
+            MlTy::Option(ty) => format!("{} option", ty.to_ml()),
+            MlTy::Tuple(tys) => {
+                let tys: Vec<String> = tys.iter().map(MlTy::to_ml).collect();
+                format!("({})", tys.join(" * "))
+            }
+            MlTy::Adt(name, args) => match args.as_slice() {
+                [] => name.clone(),
+                [arg] => format!("{} {name}", arg.to_ml()),
+                args => {
+                    let args: Vec<String> = args.iter().map(MlTy::to_ml).collect();
+                    format!("({}) {name}", args.join(", "))
+                }
+            },
+        }
+    }
+
+    /// The expression deserializing this type.
+    fn of_json(&self) -> String {
+        match self {
+            MlTy::Base(name) | MlTy::Param(name) | MlTy::Id(name) => format!("{name}_of_json"),
+            MlTy::List(ty) => format!("(list_of_json {})", ty.of_json()),
+            MlTy::Option(ty) => format!("(option_of_json {})", ty.of_json()),
+            MlTy::Tuple(tys) => {
+                let fun = if tys.len() == 2 { "pair" } else { "triple" };
+                let tys: Vec<String> = tys.iter().map(MlTy::of_json).collect();
+                format!("({fun}_of_json {})", tys.join(" "))
+            }
+            MlTy::Adt(name, args) if args.is_empty() => format!("{name}_of_json"),
+            MlTy::Adt(name, args) => {
+                let args: Vec<String> = args.iter().map(MlTy::of_json).collect();
+                format!("({name}_of_json {})", args.join(" "))
+            }
+        }
+    }
+}
+
+/// A field of a structure (or of a variant).
+#[derive(Debug, Clone)]
+pub(crate) struct Field {
+    /// The name of the field in the JSON value.
+    pub(crate) json_name: String,
+    pub(crate) ml_name: String,
+    pub(crate) ty: MlTy,
+}
+
+/// The fields of a structure or of a variant.
+#[derive(Debug, Clone)]
+pub(crate) enum Fields {
+    /// Serialized as an object, that we translate to a record.
+    Named(Vec<Field>),
+    /// Serialized as a list, that we translate to a tuple (the variants with
+    /// a single field are serialized as this field).
+    Unnamed(Vec<MlTy>),
+    Unit,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Variant {
+    pub(crate) name: String,
+    /// The name of the variant in the JSON values.
+    pub(crate) json_name: String,
+    pub(crate) fields: Fields,
+}
+
+/// The definition of a type.
+#[derive(Debug, Clone)]
+pub(crate) enum Def {
+    /// A type alias, the newtypes and the transparent structures.
+    Alias(MlTy),
+    /// A record (serialized as an object).
+    Record(Vec<Field>),
+    /// A tuple (serialized as a list).
+    Tuple(Vec<MlTy>),
+    /// A structure without fields: we use `unit`. We remember the pattern
+    /// matching the serialized value (`Null` or an empty object).
+    Unit(&'static str),
+    /// An enumeration, serialized with the default (externally tagged)
+    /// representation: the variants without fields are serialized as their
+    /// names, the others as objects with a single field (the name).
+    Variants(Vec<Variant>),
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct TypeDef {
+    pub(crate) name: String,
+    pub(crate) params: Vec<String>,
+    pub(crate) def: Def,
+}
+
+/// The serde attributes we support.
+#[derive(Debug, Default)]
+struct SerdeAttrs {
+    skip: bool,
+    transparent: bool,
+    rename: Option<String>,
+}
+
+fn serde_attrs(attrs: &[syn::Attribute]) -> SerdeAttrs {
+    let mut serde = SerdeAttrs::default();
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("serde")) {
+        let Ok(syn::Meta::List(list)) = attr.parse_meta() else { continue };
+        for nested in list.nested {
+            match nested {
+                syn::NestedMeta::Meta(syn::Meta::Path(path)) => {
+                    if path.is_ident("skip") || path.is_ident("skip_serializing") {
+                        serde.skip = true
+                    } else if path.is_ident("transparent") {
+                        serde.transparent = true
+                    }
+                }
+                syn::NestedMeta::Meta(syn::Meta::NameValue(nv)) if nv.path.is_ident("rename") => {
+                    if let syn::Lit::Str(name) = nv.lit {
+                        serde.rename = Some(name.value())
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+    serde
+}
+
+/// The OCaml types of the types of the sources whose serialization is
+/// implemented by hand: the symbols are serialized as indices in the string
+/// table, and the string table as the list of its strings (see [crate::symbol]).
+fn custom_serialization(ident: &syn::Ident) -> Option<MlTy> {
+    match ident.to_string().as_str() {
+        "Symbol" => Some(MlTy::Base("int")),
+        "StringTable" => Some(MlTy::List(Box::new(MlTy::Base("string")))),
+        _ => None,
+    }
+}
+
+fn derives_serialize(attrs: &[syn::Attribute]) -> bool {
+    attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("derive"))
+        .filter_map(|attr| match attr.parse_meta() {
+            Ok(syn::Meta::List(list)) => Some(list.nested),
+            _ => None,
+        })
+        .flatten()
+        .any(|nested| match nested {
+            syn::NestedMeta::Meta(syn::Meta::Path(path)) => path
+                .segments
+                .last()
+                .map_or(false, |s| s.ident == "Serialize"),
+            _ => false,
+        })
+}
+
+/// The generator: we first register the names of all the types of the AST, so
+/// as to resolve the references to them.
+struct Generator {
+    /// For every type name, the modules in which it is defined.
+    modules_of: HashMap<String, Vec<&'static str>>,
+    /// The indices we encountered.
+    ids: BTreeSet<String>,
+    /// The types we encountered but which are not defined by the AST (or which
+    /// have a custom serialization): we leave them as JSON values.
+    opaque: BTreeSet<String>,
+}
+
+impl Generator {
+    /// The OCaml name of a type of the AST. If several modules define a type
+    /// with the same name, we prefix it with the name of the module.
+    fn type_name(&self, module: &str, name: &str) -> String {
+        match self.modules_of.get(name) {
+            Some(modules) if modules.len() > 1 => {
+                let prefix = module.trim_end_matches("_ast");
+                ml_ident(&format!("{prefix}_{name}"))
+            }
+            _ => ml_ident(name),
+        }
+    }
+
+    /// Resolve the reference to a type, from the given module.
+    fn resolve(&mut self, module: &str, path: &syn::Path) -> Option<String> {
+        let segments: Vec<String> = path.segments.iter().map(|s| s.ident.to_string()).collect();
+        let name = segments.last()?;
+        let modules = self.modules_of.get(name)?;
+        // The path may give the module
+        let qualified = segments.len() >= 2 && modules.contains(&&*segments[segments.len() - 2]);
+        let module = if qualified {
+            segments[segments.len() - 2].clone()
+        } else if modules.contains(&module) {
+            module.to_string()
+        } else {
+            modules[0].to_string()
+        };
+        Some(self.type_name(&module, name))
+    }
+
+    fn translate_ty(&mut self, module: &str, params: &[String], ty: &syn::Type) -> MlTy {
+        match ty {
+            syn::Type::Reference(r) => self.translate_ty(module, params, &r.elem),
+            syn::Type::Paren(p) => self.translate_ty(module, params, &p.elem),
+            syn::Type::Group(g) => self.translate_ty(module, params, &g.elem),
+            syn::Type::Slice(s) => MlTy::List(Box::new(self.translate_ty(module, params, &s.elem))),
+            syn::Type::Array(a) => MlTy::List(Box::new(self.translate_ty(module, params, &a.elem))),
+            syn::Type::Tuple(t) => match t.elems.len() {
+                0 => MlTy::Base("unit"),
+                2 | 3 => MlTy::Tuple(
+                    t.elems
+                        .iter()
+                        .map(|ty| self.translate_ty(module, params, ty))
+                        .collect(),
+                ),
+                _ => MlTy::Base("json"),
+            },
+            syn::Type::Path(p) if p.qself.is_none() => self.translate_path(module, params, &p.path),
+            _ => MlTy::Base("json"),
+        }
+    }
+
+    fn translate_path(&mut self, module: &str, params: &[String], path: &syn::Path) -> MlTy {
+        let Some(last) = path.segments.last() else { return MlTy::Base("json") };
+        let args: Vec<MlTy> = match &last.arguments {
+            syn::PathArguments::AngleBracketed(args) => args
+                .args
+                .iter()
+                .filter_map(|arg| match arg {
+                    syn::GenericArgument::Type(ty) => Some(self.translate_ty(module, params, ty)),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        let name = last.ident.to_string();
+        // The index types are defined in modules generated by `generate_index_type`
+        let index_module = path
+            .segments
+            .iter()
+            .rev()
+            .nth(1)
+            .map(|s| ml_ident(&s.ident.to_string()));
+        let arg = |i: usize| Box::new(args.get(i).cloned().unwrap_or(MlTy::Base("json")));
+        match (name.as_str(), index_module) {
+            ("Id", Some(index)) => {
+                self.ids.insert(index.clone());
+                MlTy::Id(index)
+            }
+            ("Vector", Some(_)) => MlTy::List(arg(0)),
+            ("Map", Some(index)) => {
+                self.ids.insert(index.clone());
+                MlTy::List(Box::new(MlTy::Tuple(vec![MlTy::Id(index), *arg(0)])))
+            }
+            _ if path.segments.len() == 1 && params.contains(&name) => MlTy::Param(ml_ident(&name)),
+            ("String" | "str", _) => MlTy::Base("string"),
+            ("bool", _) => MlTy::Base("bool"),
+            ("char", _) => MlTy::Base("char"),
+            (
+                "u8" | "u16" | "u32" | "u64" | "u128" | "usize" | "i8" | "i16" | "i32" | "i64"
+                | "i128" | "isize",
+                _,
+            ) => MlTy::Base("int"),
+            ("Vec" | "VecDeque", _) => MlTy::List(arg(0)),
+            ("Box" | "Rc" | "Arc" | "HashConsed", _) => *arg(0),
+            ("Option", _) => MlTy::Option(arg(0)),
+            _ => match self.resolve(module, path) {
+                Some(name) => MlTy::Adt(name, args),
+                None => {
+                    let name = ml_ident(&name);
+                    self.opaque.insert(name.clone());
+                    MlTy::Adt(name, Vec::new())
+                }
+            },
+        }
+    }
+
+    fn translate_fields(
+        &mut self,
+        module: &str,
+        params: &[String],
+        fields: &syn::Fields,
+    ) -> Fields {
+        match fields {
+            syn::Fields::Named(fields) => Fields::Named(
+                fields
+                    .named
+                    .iter()
+                    .filter(|field| !serde_attrs(&field.attrs).skip)
+                    .map(|field| {
+                        let name = field.ident.as_ref().unwrap().to_string();
+                        let name = name.trim_start_matches("r#").to_string();
+                        Field {
+                            json_name: serde_attrs(&field.attrs).rename.unwrap_or(name.clone()),
+                            ml_name: ml_ident(&name),
+                            ty: self.translate_ty(module, params, &field.ty),
+                        }
+                    })
+                    .collect(),
+            ),
+            syn::Fields::Unnamed(fields) => Fields::Unnamed(
+                fields
+                    .unnamed
+                    .iter()
+                    .filter(|field| !serde_attrs(&field.attrs).skip)
+                    .map(|field| self.translate_ty(module, params, &field.ty))
+                    .collect(),
+            ),
+            syn::Fields::Unit => Fields::Unit,
+        }
+    }
+
+    fn translate_item(&mut self, module: &str, item: &syn::Item) -> Option<TypeDef> {
+        let (ident, generics) = match item {
+            syn::Item::Struct(s)
+                if derives_serialize(&s.attrs) || custom_serialization(&s.ident).is_some() =>
+            {
+                (&s.ident, &s.generics)
+            }
+            syn::Item::Enum(e) if derives_serialize(&e.attrs) => (&e.ident, &e.generics),
+            syn::Item::Type(t) => (&t.ident, &t.generics),
+            _ => return None,
+        };
+        let params: Vec<String> = generics
+            .type_params()
+            .map(|p| p.ident.to_string())
+            .collect();
+        let def = match item {
+            syn::Item::Struct(s) => {
+                if let Some(ty) = custom_serialization(&s.ident) {
+                    return Some(TypeDef {
+                        name: self.type_name(module, &ident.to_string()),
+                        params: Vec::new(),
+                        def: Def::Alias(ty),
+                    });
+                }
+                let transparent = serde_attrs(&s.attrs).transparent;
+                match self.translate_fields(module, &params, &s.fields) {
+                    Fields::Named(fields) if transparent && fields.len() == 1 => {
+                        Def::Alias(fields[0].ty.clone())
+                    }
+                    Fields::Named(fields) if fields.is_empty() => Def::Unit("`Assoc []"),
+                    Fields::Named(fields) => Def::Record(fields),
+                    Fields::Unnamed(mut tys) if tys.len() == 1 => Def::Alias(tys.remove(0)),
+                    Fields::Unnamed(tys) if tys.is_empty() => Def::Unit("`List []"),
+                    Fields::Unnamed(tys) => Def::Tuple(tys),
+                    Fields::Unit => Def::Unit("`Null"),
+                }
+            }
+            syn::Item::Enum(e) if e.variants.is_empty() => Def::Alias(MlTy::Base("json")),
+            syn::Item::Enum(e) => Def::Variants(
+                e.variants
+                    .iter()
+                    .filter(|v| !serde_attrs(&v.attrs).skip)
+                    .map(|v| {
+                        let name = v.ident.to_string();
+                        Variant {
+                            json_name: serde_attrs(&v.attrs).rename.unwrap_or(name.clone()),
+                            name,
+                            fields: self.translate_fields(module, &params, &v.fields),
+                        }
+                    })
+                    .collect(),
+            ),
+            syn::Item::Type(t) => Def::Alias(self.translate_ty(module, &params, &t.ty)),
+            _ => unreachable!(),
+        };
+        Some(TypeDef {
+            name: self.type_name(module, &ident.to_string()),
+            params: params.iter().map(|p| ml_ident(p)).collect(),
+            def,
+        })
+    }
+}
+
+/// The generated files.
+pub struct GeneratedMl {
+    /// The type definitions.
+    pub types: String,
+    /// The deserializers.
+    pub of_json: String,
+}
+
+/// Print the fields of a record (type).
+fn record_ty(fields: &[Field]) -> String {
+    let fields: Vec<String> = fields
+        .iter()
+        .map(|f| format!("{} : {}", f.ml_name, f.ty.to_ml()))
+        .collect();
+    format!("{{ {} }}", fields.join("; "))
+}
+
+/// The type of a definition, with wildcards for the parameters (we use it to
+/// annotate the values we build, to disambiguate the constructors and fields).
+fn annotation(def: &TypeDef) -> String {
+    match def.params.len() {
+        0 => def.name.clone(),
+        1 => format!("_ {}", def.name),
+        n => format!("({}) {}", vec!["_"; n].join(", "), def.name),
+    }
+}
+
+/// Print a case of a deserializer: the pattern matching the JSON value (given
+/// the pattern matching the fields), and the code building the OCaml value
+/// (given the constructor, if any).
+fn of_json_case(out: &mut String, pattern: impl Fn(&str) -> String, fields: &Fields, build: &str) {
+    let (fields_pattern, decode, value) = match fields {
+        Fields::Named(fields) => {
+            let pats: Vec<String> = fields
+                .iter()
+                .map(|f| format!("(\"{}\", {})", f.json_name, f.ml_name))
+                .collect();
+            let decode: Vec<(String, String)> = fields
+                .iter()
+                .map(|f| (f.ml_name.clone(), f.ty.of_json()))
+                .collect();
+            let names: Vec<&str> = fields.iter().map(|f| f.ml_name.as_str()).collect();
+            let value = format!("{{ {} }}", names.join("; "));
+            (format!("`Assoc [ {} ]", pats.join("; ")), decode, value)
+        }
+        Fields::Unnamed(tys) if tys.len() == 1 => {
+            let decode = vec![("x0".to_string(), tys[0].of_json())];
+            ("x0".to_string(), decode, "x0".to_string())
+        }
+        Fields::Unnamed(tys) => {
+            let names: Vec<String> = (0..tys.len()).map(|i| format!("x{i}")).collect();
+            let decode = names
+                .iter()
+                .cloned()
+                .zip(tys.iter().map(MlTy::of_json))
+                .collect();
+            let value = format!("({})", names.join(", "));
+            (format!("`List [ {} ]", names.join("; ")), decode, value)
+        }
+        Fields::Unit => (String::new(), Vec::new(), String::new()),
+    };
+    writeln!(out, "    | {} ->", pattern(&fields_pattern)).unwrap();
+    for (name, of_json) in decode {
+        writeln!(out, "        let* {name} = {of_json} {name} in").unwrap();
+    }
+    writeln!(out, "        Ok ({})", build.replace("{}", &value).trim()).unwrap();
+}
+
+impl TypeDef {
+    /// Print the definition of the type.
+    fn to_ml(&self, out: &mut String, keyword: &str) {
+        let params = match self.params.as_slice() {
+            [] => String::new(),
+            [param] => format!("'{param} "),
+            params => {
+                let params: Vec<String> = params.iter().map(|p| format!("'{p}")).collect();
+                format!("({}) ", params.join(", "))
+            }
+        };
+        write!(out, "{keyword} {params}{} =", self.name).unwrap();
+        match &self.def {
+            Def::Alias(ty) => writeln!(out, " {}", ty.to_ml()).unwrap(),
+            Def::Record(fields) => writeln!(out, " {}", record_ty(fields)).unwrap(),
+            Def::Tuple(tys) => writeln!(out, " {}", MlTy::Tuple(tys.clone()).to_ml()).unwrap(),
+            Def::Unit(_) => writeln!(out, " unit").unwrap(),
+            Def::Variants(variants) => {
+                writeln!(out).unwrap();
+                for v in variants {
+                    match &v.fields {
+                        Fields::Unit => writeln!(out, "  | {}", v.name),
+                        Fields::Named(fields) if fields.is_empty() => {
+                            writeln!(out, "  | {}", v.name)
+                        }
+                        Fields::Named(fields) => {
+                            writeln!(out, "  | {} of {}", v.name, record_ty(fields))
+                        }
+                        Fields::Unnamed(tys) => {
+                            let tys: Vec<String> = tys.iter().map(MlTy::to_ml).collect();
+                            writeln!(out, "  | {} of {}", v.name, tys.join(" * "))
+                        }
+                    }
+                    .unwrap()
+                }
+            }
+        }
+    }
+
+    /// Print the deserializer of the type.
+    fn of_json(&self, out: &mut String, keyword: &str) {
+        let name = &self.name;
+        let ty = match self.params.as_slice() {
+            [] => name.clone(),
+            [param] => format!("'{param} {name}"),
+            params => {
+                let params: Vec<String> = params.iter().map(|p| format!("'{p}")).collect();
+                format!("({}) {name}", params.join(", "))
+            }
+        };
+        if self.params.is_empty() {
+            writeln!(
+                out,
+                "{keyword} {name}_of_json (js : json) : ({ty}, string) result ="
+            )
+            .unwrap();
+        } else {
+            // The deserializers of the generic types take the deserializers of
+            // the parameters as arguments: we need explicit polymorphic
+            // annotations, as they may be used at different types in the group
+            let forall: Vec<String> = self.params.iter().map(|p| format!("'{p}")).collect();
+            let args: Vec<String> = self
+                .params
+                .iter()
+                .map(|p| format!("(json -> ('{p}, string) result)"))
+                .collect();
+            let arg_names: Vec<String> =
+                self.params.iter().map(|p| format!("{p}_of_json")).collect();
+            writeln!(
+                out,
+                "{keyword} {name}_of_json : {}. {} -> json -> ({ty}, string) result =",
+                forall.join(" "),
+                args.join(" -> ")
+            )
+            .unwrap();
+            writeln!(out, " fun {} js ->", arg_names.join(" ")).unwrap();
+        }
+
+        let annotation = annotation(self);
+        let cases = match &self.def {
+            Def::Alias(ty) => {
+                writeln!(out, "  {} js", ty.of_json()).unwrap();
+                return;
+            }
+            Def::Record(fields) => {
+                let mut cases = String::new();
+                let build = format!("{{}} : {annotation}");
+                of_json_case(
+                    &mut cases,
+                    |p| p.to_string(),
+                    &Fields::Named(fields.clone()),
+                    &build,
+                );
+                cases
+            }
+            Def::Tuple(tys) => {
+                let mut cases = String::new();
+                let build = format!("{{}} : {annotation}");
+                of_json_case(
+                    &mut cases,
+                    |p| p.to_string(),
+                    &Fields::Unnamed(tys.clone()),
+                    &build,
+                );
+                cases
+            }
+            Def::Unit(pattern) => format!("    | {pattern} -> Ok ()\n"),
+            Def::Variants(variants) => {
+                let mut cases = String::new();
+                for v in variants {
+                    let json_name = &v.json_name;
+                    let unit = match &v.fields {
+                        Fields::Unit => Some(format!("`String \"{json_name}\"")),
+                        Fields::Named(fields) if fields.is_empty() => {
+                            Some(format!("`Assoc [ (\"{json_name}\", `Assoc []) ]"))
+                        }
+                        _ => None,
+                    };
+                    match unit {
+                        Some(pattern) => {
+                            writeln!(cases, "    | {pattern} -> Ok ({} : {annotation})", v.name)
+                                .unwrap()
+                        }
+                        None => {
+                            let pattern = |p: &str| format!("`Assoc [ (\"{json_name}\", {p}) ]");
+                            let build = format!("{} {{}} : {annotation}", v.name);
+                            of_json_case(&mut cases, pattern, &v.fields, &build);
+                        }
+                    }
+                }
+                cases
+            }
+        };
+        writeln!(out, "  combine_error_msgs js __FUNCTION__").unwrap();
+        writeln!(out, "    (match js with").unwrap();
+        out.push_str(&cases);
+        writeln!(out, "    | _ -> Error \"\")").unwrap();
+    }
+}
+
+/// Translate the definitions of the AST, and return them together with the
+/// indices we encountered. We also use this for the protobuf schema.
+pub(crate) fn translate_ast() -> Result<(Vec<TypeDef>, BTreeSet<String>), String> {
+    let mut files = Vec::new();
+    for (module, source) in SOURCES {
+        let file = syn::parse_file(source).map_err(|error| format!("{module}.rs: {error}"))?;
+        files.push((module, file));
+    }
+    let mut gen = Generator {
+        modules_of: HashMap::new(),
+        ids: BTreeSet::new(),
+        opaque: BTreeSet::new(),
+    };
+    for (module, file) in &files {
+        for item in &file.items {
+            let ident = match item {
+                syn::Item::Struct(s)
+                    if derives_serialize(&s.attrs) || custom_serialization(&s.ident).is_some() =>
+                {
+                    &s.ident
+                }
+                syn::Item::Enum(e) if derives_serialize(&e.attrs) => &e.ident,
+                syn::Item::Type(t) => &t.ident,
+                _ => continue,
+            };
+            let modules = gen.modules_of.entry(ident.to_string()).or_default();
+            if !modules.contains(module) {
+                modules.push(*module);
+            }
+        }
+    }
+    let mut defs = Vec::new();
+    for (module, file) in &files {
+        defs.extend(
+            file.items
+                .iter()
+                .filter_map(|item| gen.translate_item(module, item)),
+        );
+    }
+    for name in &gen.opaque {
+        defs.push(TypeDef {
+            name: name.clone(),
+            params: Vec::new(),
+            def: Def::Alias(MlTy::Base("json")),
+        });
+    }
+    Ok((defs, gen.ids))
+}
+
+/// Generate the OCaml definitions from the sources of the AST.
+pub fn generate() -> Result<GeneratedMl, String> {
+    let (defs, ids) = translate_ast()?;
+    let header =
+        "(** Generated by `charon --generate-ml` from the definitions of the Rust AST: do \
+                  not edit. *)\n\n";
+    let mut types = header.to_string();
+    types.push_str("type json = Yojson.Basic.t\n");
+    for id in &ids {
+        writeln!(types, "type {id} = int").unwrap();
+    }
+    types.push('\n');
+    let mut of_json = header.to_string();
+    of_json.push_str("open Yojson.Basic\nopen OfJsonBasic\nopen GeneratedTypes\n\n");
+    of_json.push_str(
+        "let unit_of_json (js : json) : (unit, string) result =\n  \
+         match js with `Null -> Ok () | _ -> Error \"unit_of_json\"\n\n",
+    );
+    of_json.push_str("let json_of_json (js : json) : (json, string) result = Ok js\n");
+    for id in &ids {
+        writeln!(of_json, "let {id}_of_json = int_of_json").unwrap();
+    }
+    of_json.push('\n');
+    for (i, def) in defs.iter().enumerate() {
+        let (type_keyword, fun_keyword) = if i == 0 {
+            ("type", "let rec")
+        } else {
+            ("and", "and")
+        };
+        def.to_ml(&mut types, type_keyword);
+        def.of_json(&mut of_json, fun_keyword);
+        of_json.push('\n');
+    }
+    Ok(GeneratedMl { types, of_json })
+}
+
+/// Run `charon --generate-ml`, and return the exit code.
+pub fn run(dir: &Path) -> i32 {
+    let write = |file: &str, content: &str| {
+        let path = dir.join(file);
+        std::fs::write(&path, content).map_err(|error| format!("{}: {}", path.display(), error))
+    };
+    let res = generate().and_then(|generated| {
+        std::fs::create_dir_all(dir).map_err(|error| format!("{}: {}", dir.display(), error))?;
+        write(TYPES_FILE, &generated.types)?;
+        write(OF_JSON_FILE, &generated.of_json)
+    });
+    match res {
+        Ok(()) => {
+            log::info!("Generated the OCaml definitions in: {}", dir.display());
+            0
+        }
+        Err(msg) => {
+            log::error!("{}", msg);
+            2
+        }
+    }
+}
@@ -1,8 +1,10 @@
 //! Various utilities to load MIR.
 //! Allow to easily load the MIR code generated by a specific pass.
 
-use rustc_hir::def_id::DefId;
-use rustc_middle::mir::Body;
+use rustc_borrowck::consumers::{BodyWithBorrowckFacts, ConsumerOptions};
+use rustc_hir::def_id::{DefId, LocalDefId};
+use rustc_index::IndexVec;
+use rustc_middle::mir::{Body, Promoted};
 use rustc_middle::ty::TyCtxt;
 
 /// TODO: maybe we should always target MIR Built, this would make things
@@ -30,7 +32,7 @@ pub fn extract_constants_at_top_level(level: MirLevel) -> bool {
 }
 
 /// Are boxe manipulations desugared to very low-level code using raw pointers,
-/// unique and non-null pointers? See [crate::types::Ty::RawPtr] for detailed explanations.
+/// unique and non-null pointers? See [crate::types::TyKind::RawPtr] for detailed explanations.
 pub fn boxes_are_desugared(level: MirLevel) -> bool {
     match level {
         MirLevel::Built => false,
@@ -76,3 +78,43 @@ pub fn get_mir_for_def_id_and_level(
     };
     Some(body)
 }
+
+/// Query the promoted constants of a body at a specific level (see
+/// [get_mir_for_def_id_and_level]). The constants are promoted after the MIR
+/// is built: there are none at the [MirLevel::Built] level.
+pub fn get_promoted_mir_for_def_id_and_level(
+    tcx: TyCtxt<'_>,
+    def_id: DefId,
+    level: MirLevel,
+) -> IndexVec<Promoted, Body<'_>> {
+    if let Some(local_def_id) = def_id.as_local() {
+        match level {
+            MirLevel::Built => IndexVec::new(),
+            MirLevel::Promoted => {
+                let (_, promoted) = tcx.mir_promoted(local_def_id);
+                // We clone to be sure there are no problems with locked values
+                promoted.borrow().clone()
+            }
+            MirLevel::Optimized => tcx.promoted_mir(def_id).clone(),
+        }
+    } else if tcx.is_mir_available(def_id) {
+        tcx.promoted_mir(def_id).clone()
+    } else {
+        IndexVec::new()
+    }
+}
+
+/// Query the promoted MIR for a local function, as analyzed by the borrow
+/// checker. Contrary to the bodies returned by [get_mir_for_def_id_and_level],
+/// the regions in the returned body are not erased: they are the region variables
+/// of the borrow checker, which are described by the returned facts.
+///
+/// Remark: this panics if the body has already been stolen (as we query the
+/// MIR early, this shouldn't happen).
+pub fn get_mir_with_borrowck_facts(
+    tcx: TyCtxt<'_>,
+    def_id: LocalDefId,
+    options: ConsumerOptions,
+) -> BodyWithBorrowckFacts<'_> {
+    rustc_borrowck::consumers::get_body_with_borrowck_facts(tcx, def_id, options)
+}
@@ -0,0 +1,132 @@
+//! Hash-consing of values.
+//!
+//! We share the values which are structurally equal: a [HashConsed] value is
+//! a pointer to a unique representative, stored in a global table. Cloning a
+//! hash-consed value is thus cheap, and comparing two hash-consed values for
+//! equality is O(1) (we simply compare the pointers). We also compute the hash
+//! of the value once, when we hash-cons it: hashing a hash-consed value is O(1)
+//! too, and doesn't depend on the addresses of the values (which change from
+//! one run to the other).
+//!
+//! Remark: we never remove values from the tables. This is fine because Charon
+//! translates a single crate and then exits: the values we store in the tables
+//! would anyway live until the end of the translation.
+use serde::{Serialize, Serializer};
+use std::any::Any;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+use std::sync::{Arc, Mutex};
+
+lazy_static::lazy_static! {
+    /// The tables of the hash-consed values, indexed by the type of the values
+    /// (every table is a `HashSet<Arc<T>>`).
+    static ref TABLES: Mutex<HashMap<std::any::TypeId, Box<dyn Any + Send>>> =
+        Mutex::new(HashMap::new());
+}
+
+/// A hash-consed value, with the hash of the value.
+pub struct HashConsed<T>(Arc<T>, u64);
+
+impl<T> HashConsed<T>
+where
+    T: Hash + Eq + Clone + Send + Sync + 'static,
+{
+    /// Return the unique representative of the value
+    pub fn new(inner: T) -> Self {
+        // The default hasher is deterministic (its keys are fixed)
+        let mut hasher = DefaultHasher::new();
+        inner.hash(&mut hasher);
+        let hash = hasher.finish();
+        let mut tables = TABLES.lock().unwrap();
+        let table = tables
+            .entry(std::any::TypeId::of::<T>())
+            .or_insert_with(|| Box::new(HashSet::<Arc<T>>::new()))
+            .downcast_mut::<HashSet<Arc<T>>>()
+            .unwrap();
+        match table.get(&inner) {
+            Some(shared) => HashConsed(shared.clone(), hash),
+            None => {
+                let shared = Arc::new(inner);
+                table.insert(shared.clone());
+                HashConsed(shared, hash)
+            }
+        }
+    }
+
+    pub fn inner(&self) -> &T {
+        &self.0
+    }
+
+    /// Modify the value: we work on a copy of the value, which we hash-cons
+    /// once we are done.
+    pub fn with_inner_mut<R>(&mut self, f: impl FnOnce(&mut T) -> R) -> R {
+        let mut inner = self.inner().clone();
+        let res = f(&mut inner);
+        *self = HashConsed::new(inner);
+        res
+    }
+}
+
+impl<T> Clone for HashConsed<T> {
+    fn clone(&self) -> Self {
+        HashConsed(self.0.clone(), self.1)
+    }
+}
+
+impl<T> Deref for HashConsed<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+/// Two values are equal if and only if they have the same representative
+impl<T> PartialEq for HashConsed<T> {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl<T> Eq for HashConsed<T> {}
+
+/// We hash the value (and not the pointer) so that the hash is deterministic:
+/// it doesn't depend on the addresses of the values. The equal values have the
+/// same hash, as they have the same representative.
+impl<T> Hash for HashConsed<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.1.hash(state)
+    }
+}
+
+/// We compare the values themselves (and not the pointers) so that the order
+/// is deterministic.
+impl<T: Ord> PartialOrd for HashConsed<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: Ord> Ord for HashConsed<T> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        if Arc::ptr_eq(&self.0, &other.0) {
+            std::cmp::Ordering::Equal
+        } else {
+            self.0.cmp(&other.0)
+        }
+    }
+}
+
+impl<T: std::fmt::Debug> std::fmt::Debug for HashConsed<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// The hash-consing is transparent for the serialization
+impl<T: Serialize> Serialize for HashConsed<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
@@ -52,6 +52,10 @@ impl<K: Eq + Hash + Ord, I: Idx> MapGenerator<K, I> {
         self.map.get(k).map(|id| *id)
     }
 
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &I)> {
+        self.map.iter()
+    }
+
     // We may need to generate fresh ids without inserting a value in the map
     pub fn fresh_id(&mut self) -> I {
         self.counter.fresh_id()
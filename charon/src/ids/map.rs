@@ -16,7 +16,6 @@ pub struct Map<Id, T> {
 }
 
 impl<Id: std::cmp::Ord, T> Map<Id, T> {
-    #[allow(clippy::new_without_default)]
     pub fn new() -> Self {
         Map {
             map: std::collections::BTreeMap::new(),
@@ -43,6 +42,10 @@ impl<Id: std::cmp::Ord, T> Map<Id, T> {
         self.map.iter_mut().map(|(_, x)| x)
     }
 
+    pub fn into_values(self) -> impl Iterator<Item = T> {
+        self.map.into_values()
+    }
+
     pub fn iter_indexed(&self) -> impl Iterator<Item = (&Id, &T)> {
         self.map.iter()
     }
@@ -56,6 +59,12 @@ impl<Id: std::cmp::Ord, T> Map<Id, T> {
     }
 }
 
+impl<Id: std::cmp::Ord, T> Default for Map<Id, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<'a, Id, T> IntoIterator for &'a Map<Id, T>
 where
     T: Clone,
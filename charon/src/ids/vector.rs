@@ -57,6 +57,10 @@ where
         self.push(f(self.next_id()))
     }
 
+    pub fn pop(&mut self) -> Option<T> {
+        self.vector.pop()
+    }
+
     pub fn iter(&self) -> impl Iterator<Item = &T> {
         self.vector.iter()
     }
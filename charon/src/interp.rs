@@ -0,0 +1,1710 @@
+//! An interpreter for the LLBC: we execute the translated bodies on concrete
+//! values. This gives a reference semantics to the LLBC, which we use to test
+//! the translation (and the micro-passes) against the behavior of the compiled
+//! code, and which the backends can use for differential testing. It is
+//! available on the command line with `charon exec <crate> <function> [args]`.
+//!
+//! The memory is a set of allocations: every local variable of every frame,
+//! every boxed value, every global and every constant we take a reference to
+//! lives in its own allocation. The pointers (the references, the raw pointers
+//! and the boxes) are given by an allocation and a path inside the value it
+//! contains (see [Pointer]). The arrays and the slices are both represented
+//! by [Value::Array]: a reference to a slice is a pointer to an array.
+//!
+//! We follow the semantics of the LLBC rather than the one of the compiled
+//! code: the arithmetic operations fail if they overflow (even though we
+//! extract the code in release mode), and the use of an uninitialized value,
+//! of a dangling pointer, etc. is an error ([ExecError::UndefinedBehavior]).
+//! We don't execute the `Drop` implementations, and we can't execute the
+//! functions we don't have the body of (the external functions for instance):
+//! we only support the assumed functions (see [AssumedFunId]).
+use crate::export::GCrateData;
+use crate::generate_index_type;
+use crate::llbc_ast::*;
+use crate::names::{Name, PathElem};
+use crate::types::*;
+use crate::values::*;
+use std::collections::{HashMap, HashSet};
+
+generate_index_type!(AllocId);
+
+/// The crates we can execute.
+pub type LlbcCrate = GCrateData<FunDecl, GlobalDecl>;
+
+/// The maximal depth of the calls: we stop before overflowing our own stack.
+const MAX_CALL_DEPTH: usize = 1000;
+
+/// A step in the path of a [Pointer].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PtrElem {
+    /// A field of a structure, of a tuple or of an enumeration variant. We
+    /// also store the variant and the number of fields, so that we can
+    /// initialize a value field by field (as in the optimized MIR).
+    Field(Option<VariantId::Id>, FieldId::Id, usize),
+    /// An element of an array or of a slice.
+    Index(usize),
+}
+
+/// A pointer to a value, or to a part of a value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pointer {
+    pub alloc: AllocId::Id,
+    pub path: Vec<PtrElem>,
+}
+
+impl Pointer {
+    fn new(alloc: AllocId::Id) -> Self {
+        Pointer {
+            alloc,
+            path: Vec::new(),
+        }
+    }
+}
+
+/// A concrete value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    /// An uninitialized value, or a value which has been moved out.
+    Bottom,
+    Literal(Literal),
+    /// A structure, an enumeration variant, a tuple or the state of a closure.
+    Adt(Option<VariantId::Id>, Vec<Value>),
+    /// An array or a slice.
+    Array(Vec<Value>),
+    /// A reference or a raw pointer.
+    Ptr(Pointer),
+    /// A box: we store the boxed value in its own allocation.
+    Box(AllocId::Id),
+    /// A function pointer (or a function item). The generics don't refer to
+    /// the generic parameters of the function we execute.
+    FnPtr(FnPtr),
+}
+
+impl Value {
+    pub fn unit() -> Self {
+        Value::Adt(None, Vec::new())
+    }
+
+    fn as_usize(&self) -> Result<usize, ExecError> {
+        match self {
+            Value::Literal(Literal::Scalar(ScalarValue::Usize(v))) => Ok(*v as usize),
+            _ => Err(ExecError::UndefinedBehavior(
+                "expected a value of type usize".to_string(),
+            )),
+        }
+    }
+}
+
+/// The errors which stop the execution.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExecError {
+    /// The program panicked: explicit panic, failed assertion, arithmetic
+    /// overflow, out of bounds access, etc.
+    Panic(String),
+    /// The program did something forbidden by the semantics of the LLBC: use
+    /// of an uninitialized value, of a dangling pointer, etc.
+    UndefinedBehavior(String),
+    /// The program uses a feature we don't support (a function we don't have
+    /// the body of, for instance).
+    Unsupported(String),
+}
+
+impl ExecError {
+    /// The exit code of `charon exec`: we use 101 (the standard panic error
+    /// code) if the program panicked.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            ExecError::Panic(_) => 101,
+            ExecError::UndefinedBehavior(_) | ExecError::Unsupported(_) => 1,
+        }
+    }
+}
+
+impl std::fmt::Display for ExecError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExecError::Panic(msg) => write!(f, "The program panicked: {}", msg),
+            ExecError::UndefinedBehavior(msg) => write!(f, "Undefined behavior: {}", msg),
+            ExecError::Unsupported(msg) => write!(f, "Unsupported: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for ExecError {}
+
+type Result<T> = std::result::Result<T, ExecError>;
+
+fn ub<T>(msg: impl Into<String>) -> Result<T> {
+    Err(ExecError::UndefinedBehavior(msg.into()))
+}
+
+fn unsupported<T>(msg: impl Into<String>) -> Result<T> {
+    Err(ExecError::Unsupported(msg.into()))
+}
+
+fn out_of_bounds<T>(len: usize, index: usize) -> Result<T> {
+    Err(ExecError::Panic(format!(
+        "index out of bounds: the len is {len} but the index is {index}"
+    )))
+}
+
+/// Print a name without the generics of its impl blocks.
+pub fn name_to_string(name: &Name) -> String {
+    name.name
+        .iter()
+        .map(|elem| match elem {
+            PathElem::Ident(s, _) => s.as_str(),
+            PathElem::Impl(_) => "{impl}",
+        })
+        .collect::<Vec<_>>()
+        .join("::")
+}
+
+/// The declarations of the crate, indexed by their ids.
+struct Decls<'a> {
+    types: HashMap<TypeDeclId::Id, &'a TypeDecl>,
+    funs: HashMap<FunDeclId::Id, &'a FunDecl>,
+    globals: HashMap<GlobalDeclId::Id, &'a GlobalDecl>,
+    trait_impls: HashMap<TraitImplId::Id, &'a TraitImpl>,
+}
+
+impl<'a> Decls<'a> {
+    fn new(krate: &'a LlbcCrate) -> Self {
+        Decls {
+            types: krate.types.iter().map(|d| (d.def_id, d)).collect(),
+            funs: krate.functions.iter().map(|d| (d.def_id, d)).collect(),
+            globals: krate.globals.iter().map(|d| (d.def_id, d)).collect(),
+            trait_impls: krate.trait_impls.iter().map(|d| (d.def_id, d)).collect(),
+        }
+    }
+
+    /// The fields of a structure or of an enumeration variant.
+    fn fields(
+        &self,
+        id: TypeDeclId::Id,
+        variant: Option<VariantId::Id>,
+    ) -> Option<&'a FieldId::Vector<Field>> {
+        match (&self.types.get(&id)?.kind, variant) {
+            (TypeDeclKind::Struct(fields), None) => Some(fields),
+            (TypeDeclKind::Enum(variants, ..), Some(variant)) => {
+                Some(&variants.get(variant)?.fields)
+            }
+            _ => None,
+        }
+    }
+
+    fn num_fields(&self, id: TypeDeclId::Id, variant: Option<VariantId::Id>) -> Result<usize> {
+        match self.fields(id, variant) {
+            Some(fields) => Ok(fields.len()),
+            None => unsupported(format!("the fields of the type {id} are unknown")),
+        }
+    }
+
+    fn fun(&self, id: FunDeclId::Id) -> Result<&'a FunDecl> {
+        match self.funs.get(&id).copied() {
+            Some(d) => Ok(d),
+            None => unsupported(format!("the function {id} was not translated")),
+        }
+    }
+
+    fn trait_impl(&self, id: TraitImplId::Id) -> Result<&'a TraitImpl> {
+        match self.trait_impls.get(&id).copied() {
+            Some(d) => Ok(d),
+            None => unsupported(format!("the trait impl {id} was not translated")),
+        }
+    }
+
+    /// Select the impl which applies among the impls which specialize an impl
+    /// (see [TraitInstanceId::Specializable]): we look for the first one whose
+    /// implemented trait matches the trait reference. We don't support the
+    /// specializing impls which have trait clauses.
+    fn select_specializing_impl(
+        &self,
+        tr: &TraitRef,
+        impls: &[TraitImplId::Id],
+    ) -> Option<TraitRef> {
+        for id in impls {
+            let Some(imp) = self.trait_impls.get(id) else { continue };
+            if !imp.generics.trait_clauses.is_empty() {
+                continue;
+            }
+            let Ok(subst) = TySubst::unify_args_with_fixed(
+                std::iter::empty(),
+                std::iter::empty(),
+                &imp.impl_trait.generics,
+                &tr.trait_decl_ref.generics,
+            ) else {
+                continue;
+            };
+            let types: Option<Vec<Ty>> = imp
+                .generics
+                .types
+                .iter()
+                .map(|v| subst.type_vars_map.get(&v.index).cloned())
+                .collect();
+            let const_generics: Option<Vec<ConstGeneric>> = imp
+                .generics
+                .const_generics
+                .iter()
+                .map(|v| subst.const_generics_map.get(&v.index).cloned())
+                .collect();
+            if let (Some(types), Some(const_generics)) = (types, const_generics) {
+                let regions = vec![Region::Erased; imp.generics.regions.len()];
+                return Some(TraitRef::new(
+                    TraitInstanceId::TraitImpl(*id),
+                    GenericArgs::new(regions, types, const_generics, Vec::new()),
+                    tr.trait_decl_ref.clone(),
+                ));
+            }
+        }
+        None
+    }
+}
+
+/// The instantiation of the generic parameters of the item we execute. The
+/// generic arguments don't contain type variables nor local clauses.
+#[derive(Debug, Clone)]
+struct Instance {
+    generics: GenericArgs,
+    /// The trait references given for the trait clauses (note that the clause
+    /// ids are not necessarily contiguous, see [GenericParams::trait_clauses]).
+    clauses: HashMap<TraitClauseId::Id, TraitRef>,
+    /// The implementation of the trait when we execute a method: we use it
+    /// for [TraitInstanceId::SelfId].
+    self_ref: Option<TraitRef>,
+}
+
+impl Instance {
+    fn new(params: &GenericParams, generics: GenericArgs, self_ref: Option<TraitRef>) -> Self {
+        let clauses = params
+            .trait_clauses
+            .iter()
+            .map(|clause| clause.clause_id)
+            .zip(generics.trait_refs.iter().cloned())
+            .collect();
+        Instance {
+            generics,
+            clauses,
+            self_ref,
+        }
+    }
+
+    fn empty() -> Self {
+        Instance {
+            generics: GenericArgs::empty(),
+            clauses: HashMap::new(),
+            self_ref: None,
+        }
+    }
+}
+
+/// Substitute the generic parameters of the item we execute, and resolve the
+/// trait references as much as possible.
+struct Subst<'a, 'b> {
+    decls: &'b Decls<'a>,
+    inst: &'b Instance,
+}
+
+impl<'a, 'b> Subst<'a, 'b> {
+    fn subst_generic_args(&mut self, generics: &GenericArgs) -> GenericArgs {
+        let mut generics = generics.clone();
+        self.visit_generic_args(&mut generics);
+        generics
+    }
+
+    fn subst_const_generic(&mut self, cg: &ConstGeneric) -> ConstGeneric {
+        let mut cg = cg.clone();
+        self.visit_const_generic(&mut cg);
+        cg
+    }
+
+    fn subst_fn_ptr(&mut self, fn_ptr: &FnPtr) -> FnPtr {
+        let func = match &fn_ptr.func {
+            FunIdOrTraitMethodRef::Fun(id) => FunIdOrTraitMethodRef::Fun(id.clone()),
+            FunIdOrTraitMethodRef::Trait(tr, name, id) => {
+                FunIdOrTraitMethodRef::Trait(self.subst_trait_ref(tr), name.clone(), *id)
+            }
+        };
+        FnPtr {
+            func,
+            generics: self.subst_generic_args(&fn_ptr.generics),
+        }
+    }
+
+    /// Resolve a trait instance to a trait implementation (we use this for the
+    /// parent clauses and the item clauses).
+    fn resolve_impl(&mut self, id: &TraitInstanceId, decl_id: TraitDeclId::Id) -> Option<TraitRef> {
+        let tr = TraitRef::new(
+            id.clone(),
+            GenericArgs::empty(),
+            TraitDeclRef {
+                trait_id: decl_id,
+                generics: GenericArgs::empty(),
+            },
+        );
+        let tr = self.subst_trait_ref(&tr);
+        if matches!(tr.trait_id, TraitInstanceId::TraitImpl(_)) {
+            Some(tr)
+        } else {
+            None
+        }
+    }
+
+    fn subst_trait_ref(&mut self, tr: &TraitRef) -> TraitRef {
+        match &tr.trait_id {
+            TraitInstanceId::Clause(id) => {
+                if let Some(tr) = self.inst.clauses.get(id) {
+                    return tr.clone();
+                }
+            }
+            TraitInstanceId::SelfId => {
+                if let Some(tr) = &self.inst.self_ref {
+                    return tr.clone();
+                }
+            }
+            TraitInstanceId::ParentClause(box parent, decl_id, clause_id) => {
+                if let Some(parent) = self.resolve_impl(parent, *decl_id)
+                    && let TraitInstanceId::TraitImpl(impl_id) = &parent.trait_id
+                    && let Some(imp) = self.decls.trait_impls.get(impl_id)
+                    && let Some(tr) = imp.parent_trait_refs.get(*clause_id)
+                {
+                    let inst = Instance::new(&imp.generics, parent.generics.clone(), Some(parent));
+                    return Subst { decls: self.decls, inst: &inst }.subst_trait_ref(tr);
+                }
+            }
+            TraitInstanceId::ItemClause(box parent, decl_id, item_name, clause_id) => {
+                if let Some(parent) = self.resolve_impl(parent, *decl_id)
+                    && let TraitInstanceId::TraitImpl(impl_id) = &parent.trait_id
+                    && let Some(imp) = self.decls.trait_impls.get(impl_id)
+                    && let Some((_, (_, trait_refs, _))) =
+                        imp.types.iter().find(|(name, _)| name == item_name)
+                    && let Some(tr) = trait_refs.get(clause_id.index())
+                {
+                    let inst = Instance::new(&imp.generics, parent.generics.clone(), Some(parent));
+                    return Subst { decls: self.decls, inst: &inst }.subst_trait_ref(tr);
+                }
+            }
+            _ => (),
+        }
+
+        // Substitute the generics
+        let mut tr = tr.clone();
+        tr.with_data_mut(|tr| {
+            match &mut tr.trait_id {
+                TraitInstanceId::FnPointer(ty) => self.visit_ty(ty),
+                TraitInstanceId::Closure(_, generics) => self.visit_generic_args(generics),
+                _ => (),
+            }
+            self.visit_generic_args(&mut tr.generics);
+            self.visit_generic_args(&mut tr.trait_decl_ref.generics);
+        });
+        if let TraitInstanceId::Specializable {
+            impl_id,
+            specializing_impls,
+        } = &tr.trait_id
+        {
+            if let Some(tr) = self.decls.select_specializing_impl(&tr, specializing_impls) {
+                return tr;
+            }
+            let impl_id = *impl_id;
+            tr.with_data_mut(|tr| tr.trait_id = TraitInstanceId::TraitImpl(impl_id));
+        }
+        tr
+    }
+}
+
+impl<'a, 'b> MutTypeVisitor for Subst<'a, 'b> {
+    fn visit_ty(&mut self, ty: &mut Ty) {
+        if let TyKind::TypeVar(id) = ty.kind()
+            && let Some(arg) = self.inst.generics.types.get(id.index())
+        {
+            *ty = arg.clone()
+        } else {
+            self.default_visit_ty(ty)
+        }
+    }
+
+    fn visit_const_generic(&mut self, cg: &mut ConstGeneric) {
+        match cg {
+            ConstGeneric::Var(id) => {
+                if let Some(arg) = self.inst.generics.const_generics.get(id.index()) {
+                    *cg = arg.clone()
+                }
+            }
+            ConstGeneric::TraitConst(tr, _) => self.visit_trait_ref(tr),
+            ConstGeneric::Global(_) | ConstGeneric::Value(_) => (),
+        }
+    }
+
+    fn visit_trait_ref(&mut self, tr: &mut TraitRef) {
+        *tr = self.subst_trait_ref(tr)
+    }
+}
+
+/// Compute the generic arguments of a method, given the generic arguments of
+/// its parent (the trait impl or the trait declaration) and the generic
+/// arguments given at the call site, which end with the arguments of the
+/// method itself.
+fn method_generics(parent: &GenericArgs, call: &GenericArgs, sig: &FunSig) -> GenericArgs {
+    let Some(info) = &sig.parent_params_info else {
+        return call.clone();
+    };
+    fn own<T: Clone>(parent: &[T], call: &[T], num_params: usize, num_parent: usize) -> Vec<T> {
+        let num_own = num_params.saturating_sub(num_parent);
+        let own = &call[call.len().saturating_sub(num_own)..];
+        parent.iter().chain(own.iter()).cloned().collect()
+    }
+    GenericArgs::new(
+        vec![Region::Erased; sig.generics.regions.len()],
+        own(
+            &parent.types,
+            &call.types,
+            sig.generics.types.len(),
+            info.num_type_params,
+        ),
+        own(
+            &parent.const_generics,
+            &call.const_generics,
+            sig.generics.const_generics.len(),
+            info.num_const_generic_params,
+        ),
+        own(
+            &parent.trait_refs,
+            &call.trait_refs,
+            sig.generics.trait_clauses.len(),
+            info.num_trait_clauses,
+        ),
+    )
+}
+
+/// The way the execution of a statement ends.
+enum Outcome {
+    /// Go to the next statement
+    Normal,
+    Return,
+    Break(usize),
+    Continue(usize),
+}
+
+/// A function (or a global) being executed.
+struct Frame<'a> {
+    body: &'a ExprBody,
+    /// The allocations of the local variables
+    locals: Vec<AllocId::Id>,
+    inst: Instance,
+}
+
+/// The LLBC interpreter.
+pub struct Interpreter<'a> {
+    decls: Decls<'a>,
+    /// The allocations. We set an allocation to `None` once it is freed.
+    memory: Vec<Option<Value>>,
+    /// The allocations of the globals we initialized (we don't cache the
+    /// globals which have generics).
+    globals: HashMap<GlobalDeclId::Id, AllocId::Id>,
+    /// The globals whose initializers are being executed, to detect the cyclic
+    /// initializations (see [GGlobalDecl::init_deps]).
+    initializing: HashSet<GlobalDeclId::Id>,
+    /// The number of calls in the stack.
+    depth: usize,
+}
+
+impl<'a> Interpreter<'a> {
+    pub fn new(krate: &'a LlbcCrate) -> Self {
+        Interpreter {
+            decls: Decls::new(krate),
+            memory: Vec::new(),
+            globals: HashMap::new(),
+            initializing: HashSet::new(),
+            depth: 0,
+        }
+    }
+
+    /// Find a function from its name (for instance `my_crate::module::f`).
+    /// We ignore the impl blocks, and the disambiguators.
+    pub fn find_function(&self, name: &str) -> Option<FunDeclId::Id> {
+        let name: Vec<&str> = name.split("::").collect();
+        self.decls
+            .funs
+            .values()
+            .find(|d| d.name.equals_ref_name(&name))
+            .map(|d| d.def_id)
+    }
+
+    /// Call a function which doesn't have generic parameters.
+    pub fn call_function(&mut self, id: FunDeclId::Id, args: Vec<Value>) -> Result<Value> {
+        self.call_fun(id, GenericArgs::empty(), None, args)
+    }
+
+    /// Read the value a pointer points to.
+    pub fn read(&self, ptr: &Pointer) -> Result<&Value> {
+        let Some(Some(mut value)) = self.memory.get(ptr.alloc.index()).map(Option::as_ref) else {
+            return ub("use of a dangling pointer");
+        };
+        for elem in &ptr.path {
+            value = match (value, elem) {
+                (Value::Adt(variant, fields), PtrElem::Field(expected, field_id, _))
+                    if expected.is_none() || variant == expected =>
+                {
+                    match fields.get(field_id.index()) {
+                        Some(value) => value,
+                        None => return ub("invalid field projection"),
+                    }
+                }
+                (Value::Array(values), PtrElem::Index(i)) => match values.get(*i) {
+                    Some(value) => value,
+                    None => return out_of_bounds(values.len(), *i),
+                },
+                (Value::Bottom, _) => return ub("use of an uninitialized value"),
+                _ => return ub("invalid projection"),
+            }
+        }
+        Ok(value)
+    }
+
+    /// Access the value a pointer points to, to update it. If we go through
+    /// the field of an uninitialized value, or of another enumeration variant,
+    /// we initialize it with the proper variant.
+    fn write(&mut self, ptr: &Pointer) -> Result<&mut Value> {
+        let Some(Some(mut value)) = self.memory.get_mut(ptr.alloc.index()).map(Option::as_mut)
+        else {
+            return ub("use of a dangling pointer");
+        };
+        for elem in &ptr.path {
+            if let PtrElem::Field(variant, _, num_fields) = elem {
+                let init = match value {
+                    Value::Bottom => true,
+                    Value::Adt(current, _) => variant.is_some() && current != variant,
+                    _ => false,
+                };
+                if init {
+                    *value = Value::Adt(*variant, vec![Value::Bottom; *num_fields]);
+                }
+            }
+            value = match (value, elem) {
+                (Value::Adt(_, fields), PtrElem::Field(_, field_id, _)) => {
+                    match fields.get_mut(field_id.index()) {
+                        Some(value) => value,
+                        None => return ub("invalid field projection"),
+                    }
+                }
+                (Value::Array(values), PtrElem::Index(i)) => {
+                    let len = values.len();
+                    match values.get_mut(*i) {
+                        Some(value) => value,
+                        None => return out_of_bounds(len, *i),
+                    }
+                }
+                _ => return ub("invalid projection"),
+            }
+        }
+        Ok(value)
+    }
+
+    fn alloc(&mut self, value: Value) -> AllocId::Id {
+        self.memory.push(Some(value));
+        AllocId::Id::new(self.memory.len() - 1)
+    }
+
+    /// Free the boxes contained in a value we drop.
+    fn free_boxes(&mut self, value: Value) {
+        match value {
+            Value::Box(alloc) => {
+                if let Some(content) = self.memory.get_mut(alloc.index()).and_then(Option::take) {
+                    self.free_boxes(content)
+                }
+            }
+            Value::Adt(_, values) | Value::Array(values) => {
+                for value in values {
+                    self.free_boxes(value)
+                }
+            }
+            Value::Bottom | Value::Literal(_) | Value::Ptr(_) | Value::FnPtr(_) => (),
+        }
+    }
+
+    fn subst<'b>(&'b self, inst: &'b Instance) -> Subst<'a, 'b> {
+        Subst {
+            decls: &self.decls,
+            inst,
+        }
+    }
+
+    /// Execute a body. The arguments don't include the return value.
+    fn exec_body(&mut self, body: &'a ExprBody, inst: Instance, args: Vec<Value>) -> Result<Value> {
+        if args.len() != body.arg_count {
+            return ub(format!(
+                "expected {} arguments, got {}",
+                body.arg_count,
+                args.len()
+            ));
+        }
+        if self.depth >= MAX_CALL_DEPTH {
+            return unsupported("the maximal depth of the calls was reached");
+        }
+        let mut args = args.into_iter();
+        let locals = (0..body.locals.len())
+            .map(|i| {
+                let value = if i == 0 { None } else { args.next() };
+                self.alloc(value.unwrap_or(Value::Bottom))
+            })
+            .collect();
+        let frame = Frame { body, locals, inst };
+
+        self.depth += 1;
+        let outcome = self.exec_block(&frame, &body.body);
+        self.depth -= 1;
+        let result = match outcome {
+            Ok(Outcome::Return | Outcome::Normal) => match self.memory[frame.locals[0].index()]
+                .take()
+            {
+                Some(Value::Bottom) | None => ub("the return value is uninitialized"),
+                Some(value) => Ok(value),
+            },
+            Ok(Outcome::Break(_) | Outcome::Continue(_)) => ub("break or continue outside a loop"),
+            Err(error) => Err(error),
+        };
+        // Free the local variables
+        for alloc in frame.locals {
+            self.memory[alloc.index()] = None;
+        }
+        result
+    }
+
+    fn call_fun(
+        &mut self,
+        id: FunDeclId::Id,
+        generics: GenericArgs,
+        self_ref: Option<TraitRef>,
+        args: Vec<Value>,
+    ) -> Result<Value> {
+        let fun = self.decls.fun(id)?;
+        let Some(body) = &fun.body else {
+            return unsupported(format!(
+                "the function {} doesn't have a body",
+                name_to_string(&fun.name)
+            ));
+        };
+        let inst = Instance::new(&fun.signature.generics, generics, self_ref);
+        self.exec_body(body, inst, args)
+    }
+
+    /// Call a function pointer whose generics are concrete.
+    fn call_fn_ptr(&mut self, fn_ptr: &FnPtr, args: Vec<Value>) -> Result<Value> {
+        match &fn_ptr.func {
+            FunIdOrTraitMethodRef::Fun(FunId::Regular(id)) => {
+                self.call_fun(*id, fn_ptr.generics.clone(), None, args)
+            }
+            FunIdOrTraitMethodRef::Fun(FunId::Assumed(id)) => {
+                self.call_assumed(*id, &fn_ptr.generics, args)
+            }
+            FunIdOrTraitMethodRef::Trait(tr, name, _) => {
+                self.call_method(tr, name, &fn_ptr.generics, args)
+            }
+        }
+    }
+
+    fn call_method(
+        &mut self,
+        tr: &TraitRef,
+        name: &TraitItemName,
+        generics: &GenericArgs,
+        args: Vec<Value>,
+    ) -> Result<Value> {
+        match &tr.trait_id {
+            TraitInstanceId::TraitImpl(impl_id)
+            | TraitInstanceId::Specializable { impl_id, .. } => {
+                let imp = self.decls.trait_impl(*impl_id)?;
+                // If the method is a provided method which is not overriden by
+                // the impl, its generics start with those of the trait.
+                let method = imp
+                    .required_methods
+                    .iter()
+                    .map(|(n, id)| (n, *id, true))
+                    .chain(
+                        imp.provided_methods
+                            .iter()
+                            .map(|(n, (id, overriden))| (n, *id, *overriden)),
+                    )
+                    .find(|(n, _, _)| *n == name);
+                let Some((_, id, in_impl)) = method else {
+                    return unsupported(format!(
+                        "the method {} of {} is unknown",
+                        name.0,
+                        name_to_string(&imp.name)
+                    ));
+                };
+                let fun = self.decls.fun(id)?;
+                let parent = if in_impl {
+                    &tr.generics
+                } else {
+                    &tr.trait_decl_ref.generics
+                };
+                let generics = method_generics(parent, generics, &fun.signature);
+                self.call_fun(id, generics, Some(tr.clone()), args)
+            }
+            TraitInstanceId::FnPointer(_) | TraitInstanceId::Closure(..) => {
+                self.call_fn_trait_method(tr, name, args)
+            }
+            _ => unsupported(format!("could not resolve the method {}", name.0)),
+        }
+    }
+
+    /// Call the method of one of the `Fn` traits on a function pointer or on
+    /// a closure. The arguments are the function (or a reference to it) and a
+    /// tuple containing the arguments of the call.
+    fn call_fn_trait_method(
+        &mut self,
+        tr: &TraitRef,
+        name: &TraitItemName,
+        args: Vec<Value>,
+    ) -> Result<Value> {
+        let [f, Value::Adt(None, call_args)] = take_args::<2>(args)? else {
+            return ub("the arguments of a closure call should be a tuple");
+        };
+        let by_value = name.0 == "call_once";
+        match &tr.trait_id {
+            TraitInstanceId::FnPointer(_) => {
+                let f = match f {
+                    Value::Ptr(ptr) if !by_value => self.read(&ptr)?.clone(),
+                    f => f,
+                };
+                match f {
+                    Value::FnPtr(fn_ptr) => self.call_fn_ptr(&fn_ptr, call_args),
+                    _ => ub("calling a value which is not a function"),
+                }
+            }
+            TraitInstanceId::Closure(id, generics) => {
+                let fun = self.decls.fun(*id)?;
+                let Some(info) = &fun.signature.closure_info else {
+                    return ub("calling a function which is not a closure");
+                };
+                // The closures implementing `Fn` and `FnMut` take their state
+                // by reference: if we call them through `call_once`, we borrow
+                // the state.
+                let state = if by_value && info.kind != ClosureKind::FnOnce {
+                    Value::Ptr(Pointer::new(self.alloc(f)))
+                } else {
+                    f
+                };
+                let args = std::iter::once(state).chain(call_args).collect();
+                self.call_fun(*id, generics.clone(), None, args)
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn call_assumed(
+        &mut self,
+        id: AssumedFunId,
+        generics: &GenericArgs,
+        args: Vec<Value>,
+    ) -> Result<Value> {
+        match id {
+            AssumedFunId::BoxNew => {
+                let [value] = take_args::<1>(args)?;
+                Ok(Value::Box(self.alloc(value)))
+            }
+            AssumedFunId::BoxFree => {
+                // The content of the box has already been dropped
+                if let [Value::Box(alloc)] = take_args::<1>(args)?
+                    && let Some(slot) = self.memory.get_mut(alloc.index())
+                {
+                    *slot = None;
+                }
+                Ok(Value::unit())
+            }
+            AssumedFunId::ArrayIndexShared
+            | AssumedFunId::ArrayIndexMut
+            | AssumedFunId::SliceIndexShared
+            | AssumedFunId::SliceIndexMut => {
+                let [Value::Ptr(mut ptr), index] = take_args::<2>(args)? else {
+                    return ub("expected a reference to an array or a slice");
+                };
+                let index = index.as_usize()?;
+                let Value::Array(values) = self.read(&ptr)? else {
+                    return ub("expected a reference to an array or a slice");
+                };
+                if index >= values.len() {
+                    return out_of_bounds(values.len(), index);
+                }
+                ptr.path.push(PtrElem::Index(index));
+                Ok(Value::Ptr(ptr))
+            }
+            // The slices are represented as arrays
+            AssumedFunId::ArrayToSliceShared | AssumedFunId::ArrayToSliceMut => {
+                let [ptr] = take_args::<1>(args)?;
+                Ok(ptr)
+            }
+            AssumedFunId::ArrayRepeat => {
+                let [value] = take_args::<1>(args)?;
+                let Some(len) = generics.const_generics.get(0) else {
+                    return ub("missing length for an array repeat");
+                };
+                let len = self.eval_const_generic(len)?.as_usize()?;
+                Ok(Value::Array(vec![value; len]))
+            }
+            // We don't know the layouts of the types
+            Rvalue::NullaryOp(..) => unsupported("the nullary operations"),
+        }
+    }
+
+    /// Evaluate a global, and return the allocation which contains its value.
+    fn global_alloc(&mut self, id: GlobalDeclId::Id, generics: &GenericArgs) -> Result<AllocId::Id> {
+        if generics.is_empty()
+            && let Some(alloc) = self.globals.get(&id)
+        {
+            return Ok(*alloc);
+        }
+        let Some(global) = self.decls.globals.get(&id).copied() else {
+            return unsupported(format!("the global {id} was not translated"));
+        };
+        let Some(body) = &global.body else {
+            return unsupported(format!(
+                "the global {} doesn't have a body",
+                name_to_string(&global.name)
+            ));
+        };
+        if !self.initializing.insert(id) {
+            return ub(format!(
+                "cyclic initialization of {}",
+                name_to_string(&global.name)
+            ));
+        }
+        let inst = Instance::new(&global.generics, generics.clone(), None);
+        let value = self.exec_body(body, inst, Vec::new());
+        self.initializing.remove(&id);
+        let alloc = self.alloc(value?);
+        if generics.is_empty() {
+            self.globals.insert(id, alloc);
+        }
+        Ok(alloc)
+    }
+
+    fn read_global(&mut self, id: GlobalDeclId::Id, generics: &GenericArgs) -> Result<Value> {
+        let alloc = self.global_alloc(id, generics)?;
+        Ok(self.read(&Pointer::new(alloc))?.clone())
+    }
+
+    /// Evaluate an associated constant, given a concrete trait reference.
+    fn read_trait_const(&mut self, tr: &TraitRef, name: &TraitItemName) -> Result<Value> {
+        let (TraitInstanceId::TraitImpl(impl_id) | TraitInstanceId::Specializable { impl_id, .. }) =
+            &tr.trait_id
+        else {
+            return unsupported(format!("could not resolve the constant {}", name.0));
+        };
+        let imp = self.decls.trait_impl(*impl_id)?;
+        match imp.consts.iter().find(|(n, _)| n == name) {
+            Some((_, (_, id))) => self.read_global(*id, &tr.generics),
+            None => unsupported(format!(
+                "the constant {} of {} is unknown",
+                name.0,
+                name_to_string(&imp.name)
+            )),
+        }
+    }
+
+    /// Evaluate a concrete const generic.
+    fn eval_const_generic(&mut self, cg: &ConstGeneric) -> Result<Value> {
+        match cg {
+            ConstGeneric::Value(lit) => Ok(Value::Literal(lit.clone())),
+            ConstGeneric::Global(id) => self.read_global(*id, &GenericArgs::empty()),
+            ConstGeneric::TraitConst(tr, name) => self.read_trait_const(tr, name),
+            ConstGeneric::Var(_) => ub("unbound const generic"),
+        }
+    }
+
+    fn eval_constant(&mut self, frame: &Frame<'a>, cv: &ConstantExpr) -> Result<Value> {
+        let inst = &frame.inst;
+        match &cv.value {
+            RawConstantExpr::Literal(lit) => Ok(Value::Literal(lit.clone())),
+            RawConstantExpr::Adt(variant, fields) => {
+                let mut values = Vec::new();
+                for field in fields {
+                    values.push(self.eval_constant(frame, field)?);
+                }
+                Ok(Value::Adt(*variant, values))
+            }
+            RawConstantExpr::Global(id, generics) => {
+                let generics = self.subst(inst).subst_generic_args(generics);
+                self.read_global(*id, &generics)
+            }
+            RawConstantExpr::Promoted(id, generics) => match frame.body.promoted.get(*id) {
+                Some(global_id) => {
+                    let generics = self.subst(inst).subst_generic_args(generics);
+                    self.read_global(*global_id, &generics)
+                }
+                None => ub("unknown promoted constant"),
+            },
+            RawConstantExpr::TraitConst(tr, name) => {
+                let tr = self.subst(inst).subst_trait_ref(tr);
+                self.read_trait_const(&tr, name)
+            }
+            RawConstantExpr::Ref(cv) => {
+                let value = self.eval_constant(frame, cv)?;
+                Ok(Value::Ptr(Pointer::new(self.alloc(value))))
+            }
+            RawConstantExpr::Var(id) => match inst.generics.const_generics.get(id.index()) {
+                Some(cg) => self.eval_const_generic(cg),
+                None => ub("unbound const generic"),
+            },
+            RawConstantExpr::FnPtr(fn_ptr) => {
+                Ok(Value::FnPtr(self.subst(inst).subst_fn_ptr(fn_ptr)))
+            }
+            RawConstantExpr::MutStatic(id) => {
+                let alloc = self.global_alloc(*id, &GenericArgs::empty())?;
+                Ok(Value::Ptr(Pointer::new(alloc)))
+            }
+        }
+    }
+
+    fn eval_place(&mut self, frame: &Frame<'a>, place: &Place) -> Result<Pointer> {
+        let mut ptr = Pointer::new(frame.locals[place.var_id.index()]);
+        for elem in &place.projection {
+            match elem {
+                ProjectionElem::Deref | ProjectionElem::DerefRawPtr => match self.read(&ptr)? {
+                    Value::Ptr(target) => ptr = target.clone(),
+                    _ => return ub("dereferencing a value which is not a pointer"),
+                },
+                ProjectionElem::DerefBox => match self.read(&ptr)? {
+                    Value::Box(alloc) => ptr = Pointer::new(*alloc),
+                    _ => return ub("dereferencing a value which is not a box"),
+                },
+                ProjectionElem::Field(kind, field_id) => {
+                    let (variant, num_fields) = match kind {
+                        FieldProjKind::Adt(id, variant) => {
+                            (*variant, self.decls.num_fields(*id, *variant)?)
+                        }
+                        FieldProjKind::Tuple(arity) => (None, *arity),
+                        FieldProjKind::ClosureState => {
+                            return unsupported("access to the state of a closure")
+                        }
+                    };
+                    ptr.path
+                        .push(PtrElem::Field(variant, *field_id, num_fields));
+                }
+                ProjectionElem::Index(var_id, _) => {
+                    let index = self
+                        .read(&Pointer::new(frame.locals[var_id.index()]))?
+                        .as_usize()?;
+                    ptr.path.push(PtrElem::Index(index));
+                }
+                ProjectionElem::Subslice { .. } => return unsupported("subslice projections"),
+            }
+        }
+        Ok(ptr)
+    }
+
+    /// Compute the type declaration of a place, if it is an ADT.
+    fn place_type_decl(&self, frame: &Frame<'a>, place: &Place) -> Option<TypeDeclId::Id> {
+        let mut ty = frame.body.locals.get(place.var_id)?.ty.clone();
+        for elem in &place.projection {
+            ty = match (elem, ty.kind()) {
+                (
+                    ProjectionElem::Deref | ProjectionElem::DerefRawPtr,
+                    TyKind::Ref(_, ty, _) | TyKind::RawPtr(ty, _),
+                ) => ty.clone(),
+                (ProjectionElem::DerefBox, TyKind::Adt(TypeId::Assumed(AssumedTy::Box), args))
+                | (ProjectionElem::Index(..), TyKind::Adt(TypeId::Assumed(_), args)) => {
+                    args.types.get(0)?.clone()
+                }
+                (ProjectionElem::Field(FieldProjKind::Adt(id, variant), field_id), _) => {
+                    self.decls.fields(*id, *variant)?.get(*field_id)?.ty.clone()
+                }
+                (
+                    ProjectionElem::Field(FieldProjKind::Tuple(_), field_id),
+                    TyKind::Adt(TypeId::Tuple, args),
+                ) => args.types.get(field_id.index())?.clone(),
+                _ => return None,
+            }
+        }
+        match ty.kind() {
+            TyKind::Adt(TypeId::Adt(id), _) => Some(*id),
+            _ => None,
+        }
+    }
+
+    fn eval_operand(&mut self, frame: &Frame<'a>, op: &Operand) -> Result<Value> {
+        let value = match op {
+            Operand::Copy(place) => {
+                let ptr = self.eval_place(frame, place)?;
+                self.read(&ptr)?.clone()
+            }
+            Operand::Move(place) => {
+                let ptr = self.eval_place(frame, place)?;
+                std::mem::replace(self.write(&ptr)?, Value::Bottom)
+            }
+            Operand::Const(cv) => return self.eval_constant(frame, cv),
+            Operand::Expr(rv, _) => return self.eval_rvalue(frame, rv),
+        };
+        match value {
+            Value::Bottom => ub("use of an uninitialized value"),
+            value => Ok(value),
+        }
+    }
+
+    fn eval_rvalue(&mut self, frame: &Frame<'a>, rv: &Rvalue) -> Result<Value> {
+        match rv {
+            Rvalue::Use(op) => self.eval_operand(frame, op),
+            Rvalue::Ref(place, _) => Ok(Value::Ptr(self.eval_place(frame, place)?)),
+            Rvalue::UnaryOp(op, operand) => {
+                let value = self.eval_operand(frame, operand)?;
+                eval_unop(op, value)
+            }
+            Rvalue::BinaryOp(op, op1, op2) => {
+                let value1 = self.eval_operand(frame, op1)?;
+                let value2 = self.eval_operand(frame, op2)?;
+                eval_binop(*op, value1, value2)
+            }
+            Rvalue::Discriminant(place, id) => {
+                let ptr = self.eval_place(frame, place)?;
+                let Value::Adt(Some(variant_id), _) = self.read(&ptr)? else {
+                    return ub("reading the discriminant of a value which is not an enumeration");
+                };
+                let variant = match &self.decls.types.get(id).map(|d| &d.kind) {
+                    Some(TypeDeclKind::Enum(variants, ..)) => variants.get(*variant_id),
+                    _ => None,
+                };
+                match variant {
+                    Some(variant) => Ok(Value::Literal(Literal::Scalar(variant.discriminant))),
+                    None => unsupported(format!("the variants of the type {id} are unknown")),
+                }
+            }
+            Rvalue::Aggregate(kind, ops) => {
+                let mut values = Vec::new();
+                for op in ops {
+                    values.push(self.eval_operand(frame, op)?);
+                }
+                match kind {
+                    AggregateKind::Adt(_, variant, _) => Ok(Value::Adt(*variant, values)),
+                    AggregateKind::Array(..) => Ok(Value::Array(values)),
+                    // The closures are represented by their state
+                    AggregateKind::Closure(..) => Ok(Value::Adt(None, values)),
+                }
+            }
+            Rvalue::Global(id, generics) => {
+                let generics = self.subst(&frame.inst).subst_generic_args(generics);
+                self.read_global(*id, &generics)
+            }
+            Rvalue::Len(place, ..) => {
+                let ptr = self.eval_place(frame, place)?;
+                match self.read(&ptr)? {
+                    Value::Array(values) => Ok(Value::Literal(Literal::Scalar(
+                        ScalarValue::Usize(values.len() as u64),
+                    ))),
+                    _ => ub("computing the length of a value which is not an array"),
+                }
+            }
+            Rvalue::Repeat(op, _, len) => {
+                let value = self.eval_operand(frame, op)?;
+                let len = self.subst(&frame.inst).subst_const_generic(len);
+                let len = self.eval_const_generic(&len)?.as_usize()?;
+                Ok(Value::Array(vec![value; len]))
+            }
+        }
+    }
+
+    fn exec_call(&mut self, frame: &Frame<'a>, call: &'a Call) -> Result<()> {
+        // The ghost code is not part of the executable semantics
+        if call.is_ghost {
+            return Ok(());
+        }
+        let mut args = Vec::new();
+        for arg in &call.args {
+            args.push(self.eval_operand(frame, arg)?);
+        }
+        let value = match &call.func {
+            FnOperand::Regular(fn_ptr) => {
+                let fn_ptr = self.subst(&frame.inst).subst_fn_ptr(fn_ptr);
+                self.call_fn_ptr(&fn_ptr, args)?
+            }
+            FnOperand::Move(place) => match self.eval_operand(frame, &Operand::Move(place.clone()))? {
+                Value::FnPtr(fn_ptr) => self.call_fn_ptr(&fn_ptr, args)?,
+                _ => return ub("calling a value which is not a function"),
+            },
+        };
+        let ptr = self.eval_place(frame, &call.dest)?;
+        *self.write(&ptr)? = value;
+        Ok(())
+    }
+
+    fn exec_block(&mut self, frame: &Frame<'a>, block: &'a Block) -> Result<Outcome> {
+        for st in &block.statements {
+            match self.exec_statement(frame, st)? {
+                Outcome::Normal => (),
+                outcome => return Ok(outcome),
+            }
+        }
+        Ok(Outcome::Normal)
+    }
+
+    fn exec_statement(&mut self, frame: &Frame<'a>, st: &'a Statement) -> Result<Outcome> {
+        match &st.content {
+            RawStatement::Assign(place, rv) => {
+                let value = self.eval_rvalue(frame, rv)?;
+                let ptr = self.eval_place(frame, place)?;
+                *self.write(&ptr)? = value;
+            }
+            RawStatement::FakeRead(_) | RawStatement::StorageLive(_) | RawStatement::Nop => (),
+            RawStatement::MemCopy(_) | RawStatement::WriteBytes(_) => {
+                return unsupported("the copies of raw memory")
+            }
+            RawStatement::SetDiscriminant(place, variant_id) => {
+                let num_fields = match self.place_type_decl(frame, place) {
+                    Some(id) => self.decls.num_fields(id, Some(*variant_id))?,
+                    None => 0,
+                };
+                let ptr = self.eval_place(frame, place)?;
+                let value = self.write(&ptr)?;
+                if !matches!(value, Value::Adt(Some(id), _) if *id == *variant_id) {
+                    *value = Value::Adt(Some(*variant_id), vec![Value::Bottom; num_fields]);
+                }
+            }
+            RawStatement::Drop(place) => {
+                let ptr = self.eval_place(frame, place)?;
+                let value = std::mem::replace(self.write(&ptr)?, Value::Bottom);
+                self.free_boxes(value);
+            }
+            RawStatement::StorageDead(var_id) => {
+                // Same as dropping the variable
+                let ptr = self.eval_place(frame, &Place::new(*var_id))?;
+                let value = std::mem::replace(self.write(&ptr)?, Value::Bottom);
+                self.free_boxes(value);
+            }
+            RawStatement::Assert(assert) => match self.eval_operand(frame, &assert.cond)? {
+                Value::Literal(Literal::Bool(b)) if b == assert.expected => (),
+                Value::Literal(Literal::Bool(_)) => {
+                    let msg = match assert.kind {
+                        AssertKind::User => "assertion failed",
+                        AssertKind::Bounds => "index out of bounds",
+                        AssertKind::Overflow => "arithmetic overflow",
+                        AssertKind::DivisionByZero => "attempt to divide by zero",
+                    };
+                    return Err(ExecError::Panic(msg.to_string()));
+                }
+                _ => return ub("the condition of an assertion should be a boolean"),
+            },
+            RawStatement::Intrinsic(intrinsic) => match (&intrinsic.kind, &intrinsic.args[..]) {
+                (IntrinsicKind::Assume, [cond]) => match self.eval_operand(frame, cond)? {
+                    Value::Literal(Literal::Bool(true)) => (),
+                    Value::Literal(Literal::Bool(false)) => {
+                        return ub("the condition of an assumption should hold")
+                    }
+                    _ => return ub("the condition of an assumption should be a boolean"),
+                },
+                (IntrinsicKind::Assert, [cond, ..]) => match self.eval_operand(frame, cond)? {
+                    Value::Literal(Literal::Bool(true)) => (),
+                    Value::Literal(Literal::Bool(false)) => {
+                        let message = intrinsic.message.as_deref().unwrap_or("assertion failed");
+                        return Err(ExecError::Panic(message.to_string()));
+                    }
+                    _ => return ub("the condition of an assertion should be a boolean"),
+                },
+                (IntrinsicKind::Havoc, _) => return unsupported("the nondeterministic values"),
+                _ => return unsupported("the domain-specific intrinsics"),
+            },
+            RawStatement::Call(call) => self.exec_call(frame, call)?,
+            RawStatement::Panic => return Err(ExecError::Panic("explicit panic".to_string())),
+            RawStatement::Return => return Ok(Outcome::Return),
+            RawStatement::Break(i, _) => return Ok(Outcome::Break(*i)),
+            RawStatement::Continue(i, _) => return Ok(Outcome::Continue(*i)),
+            RawStatement::Switch(switch) => return self.exec_switch(frame, switch),
+            RawStatement::Loop(body, _) => loop {
+                match self.exec_block(frame, body)? {
+                    Outcome::Normal | Outcome::Continue(0) => (),
+                    Outcome::Break(0) => break,
+                    Outcome::Break(i) => return Ok(Outcome::Break(i - 1)),
+                    Outcome::Continue(i) => return Ok(Outcome::Continue(i - 1)),
+                    Outcome::Return => return Ok(Outcome::Return),
+                }
+            },
+        }
+        Ok(Outcome::Normal)
+    }
+
+    fn exec_switch(&mut self, frame: &Frame<'a>, switch: &'a Switch) -> Result<Outcome> {
+        match switch {
+            Switch::If(op, then_block, else_block) => match self.eval_operand(frame, op)? {
+                Value::Literal(Literal::Bool(true)) => self.exec_block(frame, then_block),
+                Value::Literal(Literal::Bool(false)) => self.exec_block(frame, else_block),
+                _ => ub("the condition of an if should be a boolean"),
+            },
+            Switch::SwitchInt(op, int_ty, targets, otherwise) => {
+                let Value::Literal(Literal::Scalar(value)) = self.eval_operand(frame, op)? else {
+                    return ub("the operand of a switch should be an integer");
+                };
+                // Compare the values with the integer type of the switch
+                let value = ScalarValue::from_le_bytes(*int_ty, value.to_bits().to_le_bytes());
+                match targets.iter().find(|(values, _)| values.contains(&value)) {
+                    Some((_, block)) => self.exec_block(frame, block),
+                    None => self.exec_block(frame, otherwise),
+                }
+            }
+            Switch::Match(place, targets, otherwise) => {
+                let ptr = self.eval_place(frame, place)?;
+                let Value::Adt(Some(variant_id), _) = self.read(&ptr)? else {
+                    return ub("matching on a value which is not an enumeration");
+                };
+                let variant_id = *variant_id;
+                match (
+                    targets.iter().find(|(ids, _)| ids.contains(&variant_id)),
+                    otherwise,
+                ) {
+                    (Some((_, block)), _) | (None, Some(block)) => self.exec_block(frame, block),
+                    (None, None) => ub("no branch of the match applies"),
+                }
+            }
+        }
+    }
+
+    /// Pretty-print a value of the given type. We print the values as the
+    /// derived `Debug` implementations do (in particular, we print the values
+    /// the references and the boxes point to), so that we can compare the
+    /// result with the one of the compiled code.
+    pub fn format_value(&self, value: &Value, ty: &Ty) -> String {
+        let format_list = |values: &[Value], tys: &mut dyn Iterator<Item = Ty>| {
+            values
+                .iter()
+                .map(|v| match tys.next() {
+                    Some(ty) => self.format_value(v, &ty),
+                    None => format!("{v:?}"),
+                })
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        match (value, ty.kind()) {
+            (Value::Bottom, _) => "⊥".to_string(),
+            (Value::Literal(lit), _) => literal_to_string(lit),
+            (Value::Box(alloc), TyKind::Adt(TypeId::Assumed(AssumedTy::Box), args)) => {
+                match self.read(&Pointer::new(*alloc)) {
+                    Ok(value) => self.format_value(value, &args.types[0]),
+                    Err(_) => "<dangling>".to_string(),
+                }
+            }
+            (Value::Ptr(ptr), TyKind::Ref(_, ty, _)) => match self.read(ptr) {
+                Ok(value) => self.format_value(value, ty),
+                Err(_) => "<dangling>".to_string(),
+            },
+            (Value::Ptr(_), TyKind::RawPtr(..)) => "<ptr>".to_string(),
+            (Value::Array(values), TyKind::Adt(TypeId::Assumed(_), args)) => {
+                let mut tys = std::iter::repeat(args.types[0].clone());
+                format!("[{}]", format_list(values, &mut tys))
+            }
+            (Value::Adt(None, values), TyKind::Adt(TypeId::Tuple, args)) => {
+                let values = format_list(values, &mut args.types.iter().cloned());
+                if args.types.len() == 1 {
+                    format!("({values},)")
+                } else {
+                    format!("({values})")
+                }
+            }
+            (Value::Adt(variant_id, values), TyKind::Adt(TypeId::Adt(id), args)) => {
+                let (Some(decl), Some(fields)) =
+                    (self.decls.types.get(id), self.decls.fields(*id, *variant_id))
+                else {
+                    return format!("{value:?}");
+                };
+                let name = match (&decl.kind, variant_id) {
+                    (TypeDeclKind::Enum(variants, ..), Some(variant_id)) => {
+                        variants[*variant_id].name.to_string()
+                    }
+                    _ => match decl.name.name.last() {
+                        Some(PathElem::Ident(name, _)) => name.to_string(),
+                        _ => name_to_string(&decl.name),
+                    },
+                };
+                if values.is_empty() {
+                    return name;
+                }
+                // Instantiate the types of the fields
+                let inst = Instance::new(&decl.generics, args.clone(), None);
+                let mut subst = self.subst(&inst);
+                let mut tys = fields.iter().map(|field| {
+                    let mut ty = field.ty.clone();
+                    subst.visit_ty(&mut ty);
+                    ty
+                });
+                if fields.iter().all(|field| field.name.is_some()) {
+                    let values = values
+                        .iter()
+                        .zip(fields.iter())
+                        .map(|(value, field)| match tys.next() {
+                            Some(ty) => format!(
+                                "{}: {}",
+                                field.name.as_ref().unwrap(),
+                                self.format_value(value, &ty)
+                            ),
+                            None => format!("{value:?}"),
+                        })
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    format!("{name} {{ {values} }}")
+                } else {
+                    format!("{name}({})", format_list(values, &mut tys))
+                }
+            }
+            (Value::FnPtr(_), _) => "<fn>".to_string(),
+            _ => format!("{value:?}"),
+        }
+    }
+}
+
+/// Retrieve the arguments of an assumed function.
+fn take_args<const N: usize>(args: Vec<Value>) -> Result<[Value; N]> {
+    match args.try_into() {
+        Ok(args) => Ok(args),
+        Err(args) => ub(format!("expected {N} arguments, got {}", args.len())),
+    }
+}
+
+fn literal_to_string(lit: &Literal) -> String {
+    match lit {
+        Literal::Scalar(v) if v.is_int() => v.as_int().unwrap().to_string(),
+        Literal::Scalar(v) => v.as_uint().unwrap().to_string(),
+        Literal::Bool(b) => b.to_string(),
+        Literal::Char(c) => format!("{c:?}"),
+    }
+}
+
+/// Parse an argument given on the command line. We only support the integers,
+/// the booleans and the characters.
+pub fn parse_arg(ty: &Ty, arg: &str) -> Result<Value> {
+    let lit = match ty.kind() {
+        TyKind::Literal(LiteralTy::Integer(int_ty)) => {
+            let value = if int_ty.is_signed() {
+                arg.parse::<i128>()
+                    .ok()
+                    .and_then(|v| ScalarValue::from_int(*int_ty, v).ok())
+            } else {
+                arg.parse::<u128>()
+                    .ok()
+                    .and_then(|v| ScalarValue::from_uint(*int_ty, v).ok())
+            };
+            value.map(Literal::Scalar)
+        }
+        TyKind::Literal(LiteralTy::Bool) => arg.parse::<bool>().ok().map(Literal::Bool),
+        TyKind::Literal(LiteralTy::Char) => arg.parse::<char>().ok().map(Literal::Char),
+        _ => {
+            return unsupported(
+                "only the integers, the booleans and the characters can be given as arguments",
+            )
+        }
+    };
+    match lit {
+        Some(lit) => Ok(Value::Literal(lit)),
+        None => unsupported(format!("invalid argument: {arg}")),
+    }
+}
+
+/// The message of the panics caused by the arithmetic overflows (we use the
+/// same messages as rustc).
+fn overflow<T>(op: &str) -> Result<T> {
+    Err(ExecError::Panic(format!("attempt to {op} with overflow")))
+}
+
+fn bool_value(b: bool) -> Value {
+    Value::Literal(Literal::Bool(b))
+}
+
+fn scalar_value(v: ScalarValue) -> Value {
+    Value::Literal(Literal::Scalar(v))
+}
+
+pub(crate) fn eval_unop(op: &UnOp, value: Value) -> Result<Value> {
+    let lit = match (op, value) {
+        // The slices are represented as arrays, and the function pointers as
+        // function items
+        (UnOp::ArrayToSlice(..) | UnOp::Cast(CastKind::FnPtr(..)), value) => return Ok(value),
+        (_, Value::Literal(lit)) => lit,
+        _ => return ub("unary operation on a value which is not a literal"),
+    };
+    match (op, lit) {
+        (UnOp::Not, Literal::Bool(b)) => Ok(bool_value(!b)),
+        (UnOp::Not, Literal::Scalar(v)) => Ok(scalar_value(ScalarValue::from_le_bytes(
+            v.get_integer_ty(),
+            (!v.to_bits()).to_le_bytes(),
+        ))),
+        (UnOp::Neg, Literal::Scalar(v)) if v.is_int() => {
+            match v
+                .as_int()
+                .unwrap()
+                .checked_neg()
+                .and_then(|r| ScalarValue::from_int(v.get_integer_ty(), r).ok())
+            {
+                Some(r) => Ok(scalar_value(r)),
+                None => overflow("negate"),
+            }
+        }
+        (UnOp::Cast(CastKind::Scalar(_, tgt)), lit) => {
+            let bits: i128 = match lit {
+                Literal::Scalar(v) if v.is_int() => v.as_int().unwrap(),
+                Literal::Scalar(v) => v.as_uint().unwrap() as i128,
+                Literal::Bool(b) => b as i128,
+                Literal::Char(c) => c as u32 as i128,
+            };
+            match tgt {
+                LiteralTy::Integer(int_ty) => Ok(scalar_value(ScalarValue::from_le_bytes(
+                    *int_ty,
+                    bits.to_le_bytes(),
+                ))),
+                LiteralTy::Char => match char::from_u32(bits as u32) {
+                    Some(c) => Ok(Value::Literal(Literal::Char(c))),
+                    None => ub("invalid character"),
+                },
+                LiteralTy::Bool => unsupported("cast to a boolean"),
+            }
+        }
+        _ => ub("ill-typed unary operation"),
+    }
+}
+
+pub(crate) fn eval_binop(op: BinOp, value1: Value, value2: Value) -> Result<Value> {
+    if let BinOp::Offset | BinOp::WrappingOffset | BinOp::PtrDiff = op {
+        return unsupported("the arithmetic on raw pointers");
+    }
+    let (Value::Literal(lit1), Value::Literal(lit2)) = (value1, value2) else {
+        return unsupported("binary operation on values which are not literals");
+    };
+    match (lit1, lit2) {
+        (Literal::Scalar(v1), Literal::Scalar(v2)) => match split_overflow_binop(op) {
+            Some((op, mode)) => eval_overflow_binop(op, mode, v1, v2),
+            None => eval_scalar_binop(op, v1, v2),
+        },
+        // The booleans and the characters are compared like the integers
+        (lit1 @ (Literal::Bool(_) | Literal::Char(_)), lit2) => {
+            let b = match op {
+                BinOp::Eq => lit1 == lit2,
+                BinOp::Ne => lit1 != lit2,
+                BinOp::Lt => lit1 < lit2,
+                BinOp::Le => lit1 <= lit2,
+                BinOp::Gt => lit1 > lit2,
+                BinOp::Ge => lit1 >= lit2,
+                BinOp::BitAnd | BinOp::BitOr | BinOp::BitXor => match (lit1, lit2) {
+                    (Literal::Bool(b1), Literal::Bool(b2)) => match op {
+                        BinOp::BitAnd => b1 & b2,
+                        BinOp::BitOr => b1 | b2,
+                        _ => b1 ^ b2,
+                    },
+                    _ => return ub("ill-typed binary operation"),
+                },
+                _ => return ub("ill-typed binary operation"),
+            };
+            Ok(bool_value(b))
+        }
+        _ => ub("ill-typed binary operation"),
+    }
+}
+
+fn eval_scalar_binop(op: BinOp, v1: ScalarValue, v2: ScalarValue) -> Result<Value> {
+    let ty = v1.get_integer_ty();
+    // The shifts are the only operations whose operands may have different types
+    if let BinOp::Shl | BinOp::Shr = op {
+        let shift = if v2.is_int() {
+            v2.as_int().unwrap()
+        } else {
+            v2.as_uint().unwrap().min(u32::MAX as u128) as i128
+        };
+        let bits = (ty.size() * 8) as i128;
+        if shift < 0 || shift >= bits {
+            return overflow(if op == BinOp::Shl {
+                "shift left"
+            } else {
+                "shift right"
+            });
+        }
+        let r = match op {
+            BinOp::Shl => ScalarValue::from_le_bytes(ty, (v1.to_bits() << shift).to_le_bytes()),
+            _ if ty.is_signed() => ScalarValue::from_unchecked_int(ty, v1.as_int().unwrap() >> shift),
+            _ => ScalarValue::from_unchecked_uint(ty, v1.as_uint().unwrap() >> shift),
+        };
+        return Ok(scalar_value(r));
+    }
+
+    if v2.get_integer_ty() != ty {
+        return ub("binary operation on integers of different types");
+    }
+    // The scalar values of the same type are ordered by value
+    let b = match op {
+        BinOp::Eq => Some(v1 == v2),
+        BinOp::Ne => Some(v1 != v2),
+        BinOp::Lt => Some(v1 < v2),
+        BinOp::Le => Some(v1 <= v2),
+        BinOp::Gt => Some(v1 > v2),
+        BinOp::Ge => Some(v1 >= v2),
+        _ => None,
+    };
+    if let Some(b) = b {
+        return Ok(bool_value(b));
+    }
+
+    if matches!(op, BinOp::Div | BinOp::Rem) && v2.to_bits() == 0 {
+        return Err(ExecError::Panic(if op == BinOp::Div {
+            "attempt to divide by zero".to_string()
+        } else {
+            "attempt to calculate the remainder with a divisor of zero".to_string()
+        }));
+    }
+    let name = match op {
+        BinOp::Add => "add",
+        BinOp::Sub => "subtract",
+        BinOp::Mul => "multiply",
+        BinOp::Div => "divide",
+        BinOp::Rem => "calculate the remainder",
+        _ => "compute",
+    };
+    let r = if ty.is_signed() {
+        let (x, y) = (v1.as_int().unwrap(), v2.as_int().unwrap());
+        let r = match op {
+            BinOp::Add => x.checked_add(y),
+            BinOp::Sub => x.checked_sub(y),
+            BinOp::Mul => x.checked_mul(y),
+            BinOp::Div => x.checked_div(y),
+            // `MIN % -1` overflows, as `MIN / -1`
+            BinOp::Rem => x
+                .checked_div(y)
+                .filter(|q| ScalarValue::int_is_in_bounds(ty, *q))
+                .and_then(|_| x.checked_rem(y)),
+            BinOp::BitAnd => Some(x & y),
+            BinOp::BitOr => Some(x | y),
+            BinOp::BitXor => Some(x ^ y),
+            _ => unreachable!(),
+        };
+        r.and_then(|r| ScalarValue::from_int(ty, r).ok())
+    } else {
+        let (x, y) = (v1.as_uint().unwrap(), v2.as_uint().unwrap());
+        let r = match op {
+            BinOp::Add => x.checked_add(y),
+            BinOp::Sub => x.checked_sub(y),
+            BinOp::Mul => x.checked_mul(y),
+            BinOp::Div => x.checked_div(y),
+            BinOp::Rem => x.checked_rem(y),
+            BinOp::BitAnd => Some(x & y),
+            BinOp::BitOr => Some(x | y),
+            BinOp::BitXor => Some(x ^ y),
+            _ => unreachable!(),
+        };
+        r.and_then(|r| ScalarValue::from_uint(ty, r).ok())
+    };
+    match r {
+        Some(r) => Ok(scalar_value(r)),
+        None => overflow(name),
+    }
+}
+
+/// The behavior of an arithmetic operation on overflow (see [BinOp::CheckedAdd] for instance).
+#[derive(Clone, Copy)]
+enum OverflowMode {
+    Checked,
+    Wrapping,
+    Overflowing,
+    Saturating,
+}
+
+/// Split an arithmetic operation with an explicit behavior on overflow into the underlying
+/// operation and the behavior.
+fn split_overflow_binop(op: BinOp) -> Option<(BinOp, OverflowMode)> {
+    use OverflowMode::*;
+    Some(match op {
+        BinOp::CheckedAdd => (BinOp::Add, Checked),
+        BinOp::CheckedSub => (BinOp::Sub, Checked),
+        BinOp::CheckedMul => (BinOp::Mul, Checked),
+        BinOp::WrappingAdd => (BinOp::Add, Wrapping),
+        BinOp::WrappingSub => (BinOp::Sub, Wrapping),
+        BinOp::WrappingMul => (BinOp::Mul, Wrapping),
+        BinOp::OverflowingAdd => (BinOp::Add, Overflowing),
+        BinOp::OverflowingSub => (BinOp::Sub, Overflowing),
+        BinOp::OverflowingMul => (BinOp::Mul, Overflowing),
+        BinOp::SaturatingAdd => (BinOp::Add, Saturating),
+        BinOp::SaturatingSub => (BinOp::Sub, Saturating),
+        BinOp::SaturatingMul => (BinOp::Mul, Saturating),
+        _ => return None,
+    })
+}
+
+fn eval_overflow_binop(
+    op: BinOp,
+    mode: OverflowMode,
+    v1: ScalarValue,
+    v2: ScalarValue,
+) -> Result<Value> {
+    let ty = v1.get_integer_ty();
+    if v2.get_integer_ty() != ty {
+        return ub("binary operation on integers of different types");
+    }
+    // The result of the operation, or `None` if it overflows
+    let exact = match eval_scalar_binop(op, v1, v2) {
+        Ok(v) => Some(v),
+        Err(ExecError::Panic(_)) => None,
+        Err(e) => return Err(e),
+    };
+    // The two's complement arithmetic doesn't depend on the sign
+    let (x, y) = (v1.to_bits(), v2.to_bits());
+    let wrapped = match op {
+        BinOp::Add => x.wrapping_add(y),
+        BinOp::Sub => x.wrapping_sub(y),
+        _ => x.wrapping_mul(y),
+    };
+    let wrapped = scalar_value(ScalarValue::from_le_bytes(ty, wrapped.to_le_bytes()));
+    Ok(match mode {
+        // `Option` has the variants `None` and `Some`, in this order
+        OverflowMode::Checked => match exact {
+            Some(v) => Value::Adt(Some(VariantId::Id::new(1)), vec![v]),
+            None => Value::Adt(Some(VariantId::Id::new(0)), Vec::new()),
+        },
+        OverflowMode::Wrapping => wrapped,
+        OverflowMode::Overflowing => Value::Adt(None, vec![wrapped, bool_value(exact.is_none())]),
+        OverflowMode::Saturating => match exact {
+            Some(v) => v,
+            None => {
+                // Check if the result is above the maximum or below the minimum
+                let above = if ty.is_signed() {
+                    let (x, y) = (v1.as_int().unwrap(), v2.as_int().unwrap());
+                    match op {
+                        BinOp::Add => y > 0,
+                        BinOp::Sub => y < 0,
+                        _ => (x < 0) == (y < 0),
+                    }
+                } else {
+                    op != BinOp::Sub
+                };
+                let bits = ty.size() * 8;
+                let bound: u128 = match (ty.is_signed(), above) {
+                    (false, true) => u128::MAX,
+                    (false, false) => 0,
+                    (true, true) => (1 << (bits - 1)) - 1,
+                    (true, false) => 1 << (bits - 1),
+                };
+                scalar_value(ScalarValue::from_le_bytes(ty, bound.to_le_bytes()))
+            }
+        },
+    })
+}
+
+/// Execute the function with the given name (for instance `my_crate::main`),
+/// with arguments given as strings (see [parse_arg]), and pretty-print the
+/// value it returns. This is the entry point of `charon exec`.
+pub fn exec(krate: &LlbcCrate, name: &str, args: &[String]) -> Result<String> {
+    let mut interp = Interpreter::new(krate);
+    let Some(id) = interp.find_function(name) else {
+        return unsupported(format!("unknown function: {name}"));
+    };
+    let sig = &interp.decls.fun(id)?.signature;
+    if !sig.generics.types.is_empty() || !sig.generics.const_generics.is_empty() {
+        return unsupported(format!("{name} has generic parameters"));
+    }
+    if sig.inputs.len() != args.len() {
+        return unsupported(format!(
+            "{name} expects {} arguments, got {}",
+            sig.inputs.len(),
+            args.len()
+        ));
+    }
+    let args: Vec<Value> = sig
+        .inputs
+        .iter()
+        .zip(args.iter())
+        .map(|(ty, arg)| parse_arg(ty, arg))
+        .try_collect()?;
+    let value = interp.call_function(id, args)?;
+    Ok(interp.format_value(&value, &sig.output))
+}
@@ -22,40 +22,58 @@
 extern crate rustc_ast;
 extern crate rustc_ast_pretty;
 extern crate rustc_attr;
+extern crate rustc_borrowck;
+extern crate rustc_data_structures;
 extern crate rustc_driver;
 extern crate rustc_error_messages;
 extern crate rustc_hir;
+extern crate rustc_hir_analysis;
 extern crate rustc_index;
+extern crate rustc_infer;
 extern crate rustc_interface;
 extern crate rustc_middle;
 extern crate rustc_session;
 extern crate rustc_span;
 extern crate rustc_target;
+extern crate rustc_trait_selection;
 
 #[macro_use]
 pub mod logger;
 pub mod assumed;
+pub mod builtin_models;
+pub mod canonical;
 pub mod cli_options;
 pub mod common;
 pub mod deps_errors;
+pub mod diff;
 pub mod driver;
+pub mod errors;
 pub mod export;
 pub mod expressions;
 pub mod expressions_utils;
 pub mod formatter;
 pub mod gast;
 pub mod gast_utils;
+pub mod generate_ml;
 pub mod get_mir;
 pub mod graphs;
+pub mod hash_cons;
 #[macro_use]
 pub mod ids;
+pub mod interp;
 pub mod llbc_ast;
 pub mod llbc_ast_utils;
 pub mod meta;
 pub mod meta_utils;
 pub mod names;
 pub mod names_utils;
+pub mod profile;
+#[cfg(feature = "protobuf")]
+pub mod protobuf;
 pub mod reorder_decls;
+pub mod report;
+pub mod sexp;
+pub mod symbol;
 pub mod transform;
 pub mod translate_constants;
 pub mod translate_crate_to_ullbc;
@@ -69,5 +87,6 @@ pub mod types_utils;
 pub mod ullbc_ast;
 pub mod ullbc_ast_utils;
 pub mod ullbc_to_llbc;
+pub mod validate;
 pub mod values;
 pub mod values_utils;
@@ -7,6 +7,7 @@
 //! from MIR to use Statement only.
 
 pub use crate::gast::*;
+use crate::generate_index_type;
 pub use crate::llbc_ast_utils::*;
 use crate::meta::Meta;
 use crate::types::*;
@@ -15,14 +16,18 @@ use crate::values::*;
 use macros::{EnumAsGetters, EnumIsA, EnumToGetters, VariantIndexArity, VariantName};
 use serde::Serialize;
 
+generate_index_type!(LoopId);
+
 /// Asserts are special constructs introduced by Rust to perform dynamic
 /// checks, to detect out-of-bounds accesses or divisions by zero for
-/// instance. We eliminate the assertions in [crate::remove_dynamic_checks],
-/// then introduce other dynamic checks in [crate::reconstruct_asserts].
+/// instance. We eliminate the assertions in [crate::remove_dynamic_checks]
+/// (unless the options ask to keep them), then introduce other dynamic checks
+/// in [crate::reconstruct_asserts].
 #[derive(Debug, Clone, Serialize)]
 pub struct Assert {
     pub cond: Operand,
     pub expected: bool,
+    pub kind: AssertKind,
 }
 
 /// A raw statement: a statement without meta data.
@@ -32,8 +37,16 @@ pub enum RawStatement {
     FakeRead(Place),
     SetDiscriminant(Place, VariantId::Id),
     Drop(Place),
+    /// The `StorageLive` and `StorageDead` statements are only present if the
+    /// option `--keep-storage-statements` is set.
+    StorageLive(VarId::Id),
+    StorageDead(VarId::Id),
     Assert(Assert),
     Call(Call),
+    MemCopy(MemCopy),
+    WriteBytes(WriteBytes),
+    /// A call to a domain-specific intrinsic, or to a built-in of the verifiers
+    Intrinsic(Intrinsic),
     /// Panic also handles "unreachable"
     Panic,
     Return,
@@ -42,22 +55,51 @@ pub enum RawStatement {
     /// * 0: break to first outer loop (the current loop)
     /// * 1: break to second outer loop
     /// * ...
-    Break(usize),
+    ///
+    /// The label is the one of the loop we break to (see [LoopInfo::label]):
+    /// it is computed with the information of the loops.
+    Break(usize, Option<String>),
     /// Continue to outer loops.
     /// The `usize` gives the index of the outer loop to continue to:
     /// * 0: continue to first outer loop (the current loop)
     /// * 1: continue to second outer loop
     /// * ...
-    Continue(usize),
+    ///
+    /// The label works the same way as for `Break`.
+    Continue(usize, Option<String>),
     /// No-op.
     Nop,
-    /// The left statement must NOT be a sequence.
-    /// For instance, `(s0; s1); s2` is forbidden and should be rewritten
-    /// to the semantically equivalent statement `s0; (s1; s2)`
-    /// To ensure that, use [crate::llbc_ast_utils::new_sequence] to build sequences.
-    Sequence(Box<Statement>, Box<Statement>),
     Switch(Switch),
-    Loop(Box<Statement>),
+    Loop(Block, LoopInfo),
+}
+
+/// Information about a loop: we compute it once the bodies are fully
+/// transformed (see [crate::transform::compute_loop_info]), as the passes may
+/// create, remove or move loops.
+#[derive(Debug, Clone, Serialize)]
+pub struct LoopInfo {
+    /// The id of the loop, unique in the body: we number the loops in the
+    /// order in which they appear in the body.
+    pub id: LoopId::Id,
+    /// A fresh label for the loop, derived from its id: we use it to print the
+    /// loop and the `Break` and `Continue` statements which target it.
+    pub label: String,
+    /// The number of loops which contain this loop (0 for the outermost loops)
+    pub depth: usize,
+    /// The `Break` statements which exit this loop
+    pub breaks: Vec<LoopJump>,
+    /// The `Continue` statements which go back to the start of this loop
+    pub continues: Vec<LoopJump>,
+}
+
+/// A `Break` or a `Continue` statement which targets a loop
+#[derive(Debug, Clone, Serialize)]
+pub struct LoopJump {
+    pub meta: Meta,
+    /// The number of loops between the statement and the loop it targets: this
+    /// is the index given by the statement (0 if the statement is directly in
+    /// the loop).
+    pub distance: usize,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -66,12 +108,22 @@ pub struct Statement {
     pub content: RawStatement,
 }
 
+/// A block of statements, which are evaluated in sequence: this is used for
+/// the bodies, the branches of the switches and the bodies of the loops.
+///
+/// Remark: a block is never empty (we insert a `Nop` if necessary).
+#[derive(Debug, Clone, Serialize)]
+pub struct Block {
+    pub meta: Meta,
+    pub statements: Vec<Statement>,
+}
+
 #[derive(
     Debug, Clone, EnumIsA, EnumToGetters, EnumAsGetters, Serialize, VariantName, VariantIndexArity,
 )]
 pub enum Switch {
     /// Gives the `if` block and the `else` block
-    If(Operand, Box<Statement>, Box<Statement>),
+    If(Operand, Block, Block),
     /// Gives the integer type, a map linking values to switch branches, and the
     /// otherwise block. Note that matches over enumerations are performed by
     /// switching over the discriminant, which is an integer.
@@ -86,28 +138,19 @@ pub enum Switch {
     ///   E::V3 => ...
     /// }
     /// ```
-    SwitchInt(
-        Operand,
-        IntegerTy,
-        Vec<(Vec<ScalarValue>, Statement)>,
-        Box<Statement>,
-    ),
+    SwitchInt(Operand, IntegerTy, Vec<(Vec<ScalarValue>, Block)>, Block),
     /// A match over an ADT.
     ///
     /// The match statement is introduced in [crate::remove_read_discriminant]
     /// (whenever we find a discriminant read, we merge it with the subsequent
     /// switch into a match).
-    Match(
-        Place,
-        Vec<(Vec<VariantId::Id>, Statement)>,
-        Option<Box<Statement>>,
-    ),
+    Match(Place, Vec<(Vec<VariantId::Id>, Block)>, Option<Block>),
 }
 
-pub type ExprBody = GExprBody<Statement>;
+pub type ExprBody = GExprBody<Block>;
 
-pub type FunDecl = GFunDecl<Statement>;
+pub type FunDecl = GFunDecl<Block>;
 pub type FunDecls = FunDeclId::Map<FunDecl>;
 
-pub type GlobalDecl = GGlobalDecl<Statement>;
+pub type GlobalDecl = GGlobalDecl<Block>;
 pub type GlobalDecls = GlobalDeclId::Map<GlobalDecl>;
@@ -3,60 +3,16 @@
 use crate::common::*;
 use crate::expressions::{MutExprVisitor, Operand, Place, Rvalue};
 use crate::formatter::{AstFormatter, Formatter};
-use crate::llbc_ast::{Assert, FunDecl, GlobalDecl, RawStatement, Statement, Switch};
+use crate::llbc_ast::{
+    Assert, Block, FunDecl, GlobalDecl, LoopId, LoopInfo, RawStatement, Statement, Switch,
+};
 use crate::meta;
 use crate::meta::Meta;
 use crate::types::*;
 pub use crate::ullbc_ast::fmt_call;
 use crate::values::*;
 use macros::make_generic_in_borrows;
-use std::ops::DerefMut;
-use take_mut::take;
-
-/// Goes from e.g. `(A; B; C) ; D` to `(A; (B; (C; D)))`.
-pub fn chain_statements(firsts: Vec<Statement>, last: Statement) -> Statement {
-    firsts.into_iter().rev().fold(last, |cont, bind| {
-        assert!(!bind.content.is_sequence());
-        let meta = meta::combine_meta(&bind.meta, &cont.meta);
-        Statement::new(meta, RawStatement::Sequence(Box::new(bind), Box::new(cont)))
-    })
-}
-
-/// Utility function for [new_sequence].
-/// Efficiently appends a new statement at the rightmost place of a well-formed sequence.
-fn append_rightmost(seq: &mut Statement, r: Box<Statement>) {
-    let (_l1, l2) = match &mut seq.content {
-        RawStatement::Sequence(l1, l2) => (l1, l2),
-        _ => unreachable!(),
-    };
-    if l2.content.is_sequence() {
-        append_rightmost(l2, r);
-    } else {
-        take(l2.deref_mut(), move |l2| {
-            let meta = meta::combine_meta(&l2.meta, &r.meta);
-            Statement::new(meta, RawStatement::Sequence(Box::new(l2), r))
-        });
-    }
-}
-
-/// Builds a sequence from well-formed statements.
-/// Ensures that the left statement will not be a sequence in the new sequence:
-/// Must be used instead of the raw [RawStatement::Sequence] constructor,
-/// unless you're sure that the left statement is not a sequence.
-pub fn new_sequence(mut l: Statement, r: Statement) -> Statement {
-    let meta = meta::combine_meta(&l.meta, &r.meta);
-
-    let r = Box::new(r);
-    let nst = match l.content {
-        RawStatement::Sequence(_, _) => {
-            append_rightmost(&mut l, r);
-            l.content
-        }
-        lc => RawStatement::Sequence(Box::new(Statement::new(l.meta, lc)), r),
-    };
-
-    Statement::new(meta, nst)
-}
+use rustc_data_structures::stack::ensure_sufficient_stack;
 
 /// Combine the meta information from a [Switch]
 pub fn combine_switch_targets_meta(targets: &Switch) -> Meta {
@@ -80,13 +36,13 @@ pub fn combine_switch_targets_meta(targets: &Switch) -> Meta {
 }
 
 impl Switch {
-    pub fn get_targets(&self) -> Vec<&Statement> {
+    pub fn get_targets(&self) -> Vec<&Block> {
         match self {
             Switch::If(_, exp1, exp2) => {
                 vec![exp1, exp2]
             }
             Switch::SwitchInt(_, _, targets, otherwise) => {
-                let mut out: Vec<&Statement> = vec![];
+                let mut out: Vec<&Block> = vec![];
                 for (_, tgt) in targets {
                     out.push(tgt);
                 }
@@ -94,7 +50,7 @@ impl Switch {
                 out
             }
             Switch::Match(_, targets, otherwise) => {
-                let mut out: Vec<&Statement> = vec![];
+                let mut out: Vec<&Block> = vec![];
                 for (_, tgt) in targets {
                     out.push(tgt);
                 }
@@ -105,6 +61,41 @@ impl Switch {
             }
         }
     }
+
+    /// Mutable version of [Switch::get_targets]
+    pub fn get_targets_mut(&mut self) -> Vec<&mut Block> {
+        match self {
+            Switch::If(_, exp1, exp2) => {
+                vec![exp1, exp2]
+            }
+            Switch::SwitchInt(_, _, targets, otherwise) => {
+                let mut out: Vec<&mut Block> = targets.iter_mut().map(|(_, tgt)| tgt).collect();
+                out.push(otherwise);
+                out
+            }
+            Switch::Match(_, targets, otherwise) => {
+                let mut out: Vec<&mut Block> = targets.iter_mut().map(|(_, tgt)| tgt).collect();
+                if let Some(otherwise) = otherwise {
+                    out.push(otherwise);
+                }
+                out
+            }
+        }
+    }
+}
+
+impl LoopInfo {
+    /// The information of a loop we create: it is computed at the end of the
+    /// transformation passes (see [crate::transform::compute_loop_info]).
+    pub fn empty() -> Self {
+        LoopInfo {
+            id: LoopId::ZERO,
+            label: String::new(),
+            depth: 0,
+            breaks: Vec::new(),
+            continues: Vec::new(),
+        }
+    }
 }
 
 impl Statement {
@@ -135,6 +126,12 @@ impl Statement {
             RawStatement::Drop(place) => {
                 format!("{}drop {}", tab, place.fmt_with_ctx(ctx))
             }
+            RawStatement::StorageLive(vid) => {
+                format!("{tab}@storage_live({})", vid.to_pretty_string())
+            }
+            RawStatement::StorageDead(vid) => {
+                format!("{tab}@storage_dead({})", vid.to_pretty_string())
+            }
             RawStatement::Assert(assert) => format!(
                 "{}assert({} == {})",
                 tab,
@@ -145,16 +142,16 @@ impl Statement {
                 let (call_s, _) = fmt_call(ctx, call);
                 format!("{tab}{} := {call_s}", call.dest.fmt_with_ctx(ctx),)
             }
+            RawStatement::MemCopy(copy) => format!("{tab}{}", copy.fmt_with_ctx(ctx)),
+            RawStatement::WriteBytes(write) => format!("{tab}{}", write.fmt_with_ctx(ctx)),
+            RawStatement::Intrinsic(intrinsic) => format!("{tab}{}", intrinsic.fmt_with_ctx(ctx)),
             RawStatement::Panic => format!("{tab}panic"),
             RawStatement::Return => format!("{tab}return"),
-            RawStatement::Break(index) => format!("{tab}break {index}"),
-            RawStatement::Continue(index) => format!("{tab}continue {index}"),
+            RawStatement::Break(_, Some(label)) => format!("{tab}break '{label}"),
+            RawStatement::Break(index, None) => format!("{tab}break {index}"),
+            RawStatement::Continue(_, Some(label)) => format!("{tab}continue '{label}"),
+            RawStatement::Continue(index, None) => format!("{tab}continue {index}"),
             RawStatement::Nop => format!("{tab}nop"),
-            RawStatement::Sequence(st1, st2) => format!(
-                "{}\n{}",
-                st1.fmt_with_ctx(tab, ctx),
-                st2.fmt_with_ctx(tab, ctx)
-            ),
             RawStatement::Switch(switch) => match switch {
                 Switch::If(discr, true_st, false_st) => {
                     let inner_tab = format!("{tab}{TAB_INCR}");
@@ -238,11 +235,19 @@ impl Statement {
                     )
                 }
             },
-            RawStatement::Loop(body) => {
+            RawStatement::Loop(body, info) => {
                 let inner_tab = format!("{tab}{TAB_INCR}");
+                // The label is empty until we compute the information of the
+                // loops
+                let label = if info.label.is_empty() {
+                    String::new()
+                } else {
+                    format!("'{}: ", info.label)
+                };
                 format!(
-                    "{}loop {{\n{}\n{}}}",
+                    "{}{}loop {{\n{}\n{}}}",
                     tab,
+                    label,
                     body.fmt_with_ctx(&inner_tab, ctx),
                     tab
                 )
@@ -251,11 +256,29 @@ impl Statement {
     }
 }
 
+impl Block {
+    pub fn new(meta: Meta, statements: Vec<Statement>) -> Self {
+        Block { meta, statements }
+    }
+
+    pub fn fmt_with_ctx<C>(&self, tab: &str, ctx: &C) -> String
+    where
+        C: AstFormatter,
+    {
+        let statements: Vec<String> = self
+            .statements
+            .iter()
+            .map(|st| st.fmt_with_ctx(tab, ctx))
+            .collect();
+        statements.join("\n")
+    }
+}
+
 impl FunDecl {
     pub fn fmt_with_ctx<C>(&self, ctx: &C) -> String
     where
         C: AstFormatter,
-        for<'a> C: Formatter<&'a Statement>,
+        for<'a> C: Formatter<&'a Block>,
     {
         self.gfmt_with_ctx("", ctx)
     }
@@ -265,7 +288,7 @@ impl GlobalDecl {
     pub fn fmt_with_ctx<C>(&self, ctx: &C) -> String
     where
         C: AstFormatter,
-        for<'a> C: Formatter<&'a Statement>,
+        for<'a> C: Formatter<&'a Block>,
     {
         self.gfmt_with_ctx("", ctx)
     }
@@ -306,7 +329,9 @@ pub trait AstVisitor: crate::expressions::ExprVisitor {
     fn visit_meta(&mut self, st: &Meta) {}
 
     fn default_visit_raw_statement(&mut self, st: &RawStatement) {
-        match st {
+        // The statements may be deeply nested: we make sure we don't overflow
+        // the stack (see [ensure_sufficient_stack])
+        ensure_sufficient_stack(|| match st {
             RawStatement::Assign(p, rv) => {
                 self.visit_assign(p, rv);
             }
@@ -319,27 +344,41 @@ pub trait AstVisitor: crate::expressions::ExprVisitor {
             RawStatement::Drop(p) => {
                 self.visit_drop(p);
             }
+            RawStatement::StorageLive(vid) => {
+                self.visit_storage_live(vid);
+            }
+            RawStatement::StorageDead(vid) => {
+                self.visit_storage_dead(vid);
+            }
             RawStatement::Assert(a) => {
                 self.visit_assert(a);
             }
             RawStatement::Call(c) => {
                 self.visit_call(c);
             }
+            RawStatement::MemCopy(copy) => {
+                self.visit_mem_copy(copy);
+            }
+            RawStatement::WriteBytes(write) => {
+                self.visit_write_bytes(write);
+            }
+            RawStatement::Intrinsic(intrinsic) => {
+                self.visit_intrinsic(intrinsic);
+            }
             RawStatement::Panic => {
                 self.visit_panic();
             }
             RawStatement::Return => self.visit_return(),
-            RawStatement::Break(i) => {
+            RawStatement::Break(i, _) => {
                 self.visit_break(i);
             }
-            RawStatement::Continue(i) => {
+            RawStatement::Continue(i, _) => {
                 self.visit_continue(i);
             }
             RawStatement::Nop => self.visit_nop(),
-            RawStatement::Sequence(st1, st2) => self.visit_sequence(st1, st2),
             RawStatement::Switch(s) => self.visit_switch(s),
-            RawStatement::Loop(lp) => self.visit_loop(lp),
-        }
+            RawStatement::Loop(lp, _) => self.visit_loop(lp),
+        })
     }
 
     fn visit_raw_statement(&mut self, st: &RawStatement) {
@@ -363,19 +402,52 @@ pub trait AstVisitor: crate::expressions::ExprVisitor {
         self.visit_place(p);
     }
 
+    fn visit_storage_live(&mut self, vid: &VarId::Id) {
+        self.visit_var_id(vid);
+    }
+
+    fn visit_storage_dead(&mut self, vid: &VarId::Id) {
+        self.visit_var_id(vid);
+    }
+
     fn visit_assert(&mut self, a: &Assert) {
         self.visit_operand(&a.cond);
     }
 
+    fn visit_mem_copy(&mut self, copy: &MemCopy) {
+        self.visit_operand(&copy.src);
+        self.visit_operand(&copy.dst);
+        self.visit_operand(&copy.count);
+    }
+
+    fn visit_write_bytes(&mut self, write: &WriteBytes) {
+        self.visit_operand(&write.dst);
+        self.visit_operand(&write.val);
+        self.visit_operand(&write.count);
+    }
+
+    fn visit_intrinsic(&mut self, intrinsic: &Intrinsic) {
+        for arg in &intrinsic.args {
+            self.visit_operand(arg);
+        }
+        self.visit_place(&intrinsic.dest);
+    }
+
     fn visit_panic(&mut self) {}
     fn visit_return(&mut self) {}
     fn visit_break(&mut self, _: &usize) {}
     fn visit_continue(&mut self, _: &usize) {}
     fn visit_nop(&mut self) {}
 
-    fn visit_sequence(&mut self, st1: &Statement, st2: &Statement) {
-        self.visit_statement(st1);
-        self.visit_statement(st2);
+    fn default_visit_block(&mut self, b: &Block) {
+        self.visit_meta(&b.meta);
+        for st in b.statements.iter() {
+            self.visit_statement(st)
+        }
+    }
+
+    fn visit_block(&mut self, b: &Block) {
+        self.default_visit_block(b)
     }
 
     fn default_visit_switch(&mut self, s: &Switch) {
@@ -396,10 +468,10 @@ pub trait AstVisitor: crate::expressions::ExprVisitor {
         self.default_visit_switch(s)
     }
 
-    fn visit_if(&mut self, scrut: &Operand, then_branch: &Statement, else_branch: &Statement) {
+    fn visit_if(&mut self, scrut: &Operand, then_branch: &Block, else_branch: &Block) {
         self.visit_operand(scrut);
-        self.spawn(&mut |v| v.visit_statement(then_branch));
-        self.spawn(&mut |v| v.visit_statement(else_branch));
+        self.spawn(&mut |v| v.visit_block(then_branch));
+        self.spawn(&mut |v| v.visit_block(else_branch));
         self.merge();
     }
 
@@ -407,41 +479,41 @@ pub trait AstVisitor: crate::expressions::ExprVisitor {
         &mut self,
         scrut: &Operand,
         _: &IntegerTy,
-        branches: &Vec<(Vec<ScalarValue>, Statement)>,
-        otherwise: &Statement,
+        branches: &Vec<(Vec<ScalarValue>, Block)>,
+        otherwise: &Block,
     ) {
         self.visit_operand(scrut);
-        for (_, st) in branches {
-            self.spawn(&mut |v| v.visit_statement(st));
+        for (_, b) in branches {
+            self.spawn(&mut |v| v.visit_block(b));
         }
-        self.spawn(&mut |v| v.visit_statement(otherwise));
+        self.spawn(&mut |v| v.visit_block(otherwise));
         self.merge();
     }
 
     fn visit_match(
         &mut self,
         scrut: &Place,
-        branches: &Vec<(Vec<VariantId::Id>, Statement)>,
-        otherwise: &Option<Box<Statement>>,
+        branches: &Vec<(Vec<VariantId::Id>, Block)>,
+        otherwise: &Option<Block>,
     ) {
         self.visit_place(scrut);
-        for (_, st) in branches {
-            self.spawn(&mut |v| v.visit_statement(st));
+        for (_, b) in branches {
+            self.spawn(&mut |v| v.visit_block(b));
         }
         if let Some(otherwise) = otherwise {
-            self.spawn(&mut |v| v.visit_statement(otherwise));
+            self.spawn(&mut |v| v.visit_block(otherwise));
         }
         self.merge();
     }
 
-    fn visit_loop(&mut self, lp: &Statement) {
-        self.visit_statement(lp)
+    fn visit_loop(&mut self, lp: &Block) {
+        self.visit_block(lp)
     }
 }
 
 } // make_generic_in_borrows
 
-/// Helper for [transform_statements]
+/// Helper for [Block::transform]
 struct TransformStatements<'a, F: FnMut(&mut Statement) -> Option<Vec<Statement>>> {
     tr: &'a mut F,
 }
@@ -458,29 +530,22 @@ impl<'a, F: FnMut(&mut Statement) -> Option<Vec<Statement>>> MutExprVisitor
 impl<'a, F: FnMut(&mut Statement) -> Option<Vec<Statement>>> MutAstVisitor
     for TransformStatements<'a, F>
 {
-    fn visit_statement(&mut self, st: &mut Statement) {
-        match &mut st.content {
-            RawStatement::Sequence(st1, st2) => {
-                // Bottom-up
-                self.visit_statement(st2);
-                self.default_visit_raw_statement(&mut st1.content);
-
-                // Transform the current statement
-                let st_seq = (self.tr)(st1);
-                if let Some(seq) = st_seq && !seq.is_empty() {
-                    take(st, |st| chain_statements(seq, st))
-                }
-                // TODO: we might want to apply tr to the whole resulting sequence
-            }
-            _ => {
-                // Bottom-up
-                self.default_visit_raw_statement(&mut st.content);
-
-                // Transform the current statement
-                let st_seq = (self.tr)(st);
-                if let Some(seq) = st_seq && !seq.is_empty() {
-                    take(st, |st| chain_statements(seq, st))
-                }
+    fn visit_block(&mut self, b: &mut Block) {
+        self.visit_meta(&mut b.meta);
+        // Bottom-up, and from the right to the left (the order in which we
+        // visit the statements matters for the transformations which
+        // introduce fresh variables).
+        let mut i = b.statements.len();
+        while i > 0 {
+            i -= 1;
+            let st = &mut b.statements[i];
+            self.default_visit_raw_statement(&mut st.content);
+
+            // Transform the current statement, and insert the sequence of
+            // statements it returns before it
+            let st_seq = (self.tr)(st);
+            if let Some(seq) = st_seq && !seq.is_empty() {
+                b.statements.splice(i..i, seq);
             }
         }
     }
@@ -492,20 +557,47 @@ impl<'a, F: FnMut(&mut Statement) -> Option<Vec<Statement>>> MutAstVisitor
     fn merge(&mut self) {}
 }
 
-impl Statement {
+impl Block {
     /// Apply a transformer to all the statements, in a bottom-up manner.
     ///
     /// The transformer should:
     /// - mutate the current statement in place
     /// - return the sequence of statements to introduce before the current statement
     ///
-    /// We do the transformation in such a way that the
-    /// sequences of statements are properly chained. In particuliar,
-    /// if in `s1; s2` we transform `s1` to the sequence `s1_1; s1_2`,
-    /// then the resulting statement is `s1_1; s1_2; s2` and **not**
-    /// `{ s1_1; s1_2 }; s2`.
+    /// The statements returned by the transformer are inserted in the block
+    /// which contains the current statement: if in `s1; s2` we transform `s1`
+    /// to the sequence `s1_1; s1_2`, then the resulting block is
+    /// `s1_1; s1_2; s2`.
     pub fn transform<F: FnMut(&mut Statement) -> Option<Vec<Statement>>>(&mut self, f: &mut F) {
         let mut visitor = TransformStatements { tr: f };
-        visitor.visit_statement(self);
+        visitor.visit_block(self);
+    }
+
+    /// Apply a transformer to all the blocks, in a bottom-up manner.
+    pub fn transform_blocks<F: FnMut(&mut Block)>(&mut self, f: &mut F) {
+        let mut visitor = TransformBlocks { tr: f };
+        visitor.visit_block(self);
+    }
+}
+
+/// Helper for [Block::transform_blocks]
+struct TransformBlocks<'a, F: FnMut(&mut Block)> {
+    tr: &'a mut F,
+}
+
+impl<'a, F: FnMut(&mut Block)> MutTypeVisitor for TransformBlocks<'a, F> {}
+impl<'a, F: FnMut(&mut Block)> MutExprVisitor for TransformBlocks<'a, F> {}
+
+impl<'a, F: FnMut(&mut Block)> MutAstVisitor for TransformBlocks<'a, F> {
+    fn visit_block(&mut self, b: &mut Block) {
+        // Bottom-up
+        self.default_visit_block(b);
+        (self.tr)(b)
+    }
+
+    fn spawn(&mut self, visitor: &mut dyn FnMut(&mut Self)) {
+        visitor(self)
     }
+
+    fn merge(&mut self) {}
 }
@@ -22,8 +22,11 @@ pub fn initialize_logger() {
     // If the log level is not set, set it to "info"
     let env = env.default_filter_or("info");
 
-    // Initialize the log builder from the environment we just created
+    // Initialize the log builder from the environment we just created.
+    // We always log to stderr: the standard output may be used to export the
+    // crate data (see `--stdout`).
     let mut builder = Builder::from_env(env);
+    builder.target(env_logger::Target::Stderr);
 
     // Modify the output format - we add the line number
     builder.format(|buf, record| {
@@ -35,12 +35,21 @@
 // Don't link with the `charon_lib` crate so that the `charon` binary doesn't have to dynamically
 // link to `librustc_driver.so` etc.
 mod cli_options;
+mod diff;
+// The binary only builds some of the errors
+#[allow(dead_code)]
+mod errors;
+mod generate_ml;
 mod logger;
+#[cfg(feature = "protobuf")]
+mod protobuf;
+mod watch;
 
 use clap::Parser;
-use cli_options::{CliOpts, CHARON_ARGS};
+use cli_options::{CliOpts, OutputFormat, CHARON_ARGS};
+use errors::CharonError;
 use std::env;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 const RUST_VERSION: &str = macros::rust_version!();
@@ -49,10 +58,43 @@ pub fn main() {
     // Initialize the logger
     logger::initialize_logger();
 
+    // The `diff` subcommand compares two exported crates: it doesn't call cargo
+    if env::args().nth(1).as_deref() == Some("diff") {
+        let options = diff::DiffOpts::parse_from(env::args().skip(1));
+        std::process::exit(diff::run(&options));
+    }
+
+    // The `exec` subcommand extracts the crate in the given directory, then
+    // executes one of its functions with the LLBC interpreter
+    if env::args().nth(1).as_deref() == Some("exec") {
+        let exec_options = cli_options::ExecOpts::parse_from(env::args().skip(1));
+        if let Err(error) = env::set_current_dir(&exec_options.crate_dir) {
+            let error = CharonError::Io {
+                path: exec_options.crate_dir.clone(),
+                error,
+            };
+            log::error!("{}", error);
+            std::process::exit(error.exit_code());
+        }
+        if let Err(code) = process(&exec_options.to_cli_opts()) {
+            std::process::exit(code);
+        }
+        return;
+    }
+
     // Parse the command-line
     let options = CliOpts::parse();
     trace!("Arguments: {:?}", std::env::args());
 
+    // Generating the OCaml definitions doesn't require calling cargo
+    if let Some(dir) = &options.generate_ml {
+        std::process::exit(generate_ml::run(dir));
+    }
+    #[cfg(feature = "protobuf")]
+    if let Some(dir) = &options.generate_proto {
+        std::process::exit(protobuf::run(dir));
+    }
+
     // Check that the options are meaningful
     assert!(
         !options.lib || options.bin.is_none(),
@@ -64,12 +106,81 @@ pub fn main() {
         "Can't use --mir_promoted and --mir_optimized at the same time"
     );
 
+    assert!(
+        !options.body_regions || !options.mir_optimized,
+        "Can't use --body-regions and --mir_optimized at the same time"
+    );
+
+    assert!(
+        !options.borrowck_facts || !options.mir_optimized,
+        "Can't use --borrowck-facts and --mir_optimized at the same time"
+    );
+
     assert!(
         !options.abort_on_error || !options.errors_as_warnings,
         "Can't use --abort-on-error and --errors-as-warnings at the same time"
     );
 
-    if let Err(code) = process(&options) {
+    assert!(
+        !options.stdout || options.split_output.is_none(),
+        "Can't use --stdout and --split-output at the same time"
+    );
+
+    assert!(
+        !options.canonical || options.split_output.is_none(),
+        "Can't use --canonical and --split-output at the same time"
+    );
+
+    assert!(
+        options.format == OutputFormat::Json || options.split_output.is_none(),
+        "Can't use --split-output with another format than JSON"
+    );
+
+    assert!(
+        !options.ullbc || options.exec.is_none(),
+        "Can't use --ullbc and --exec at the same time"
+    );
+
+    assert!(
+        options.file.is_none() || (!options.lib && options.bin.is_none()),
+        "Can't use --lib or --bin when extracting a standalone file"
+    );
+
+    assert!(
+        options.file.is_some() || options.edition.is_none(),
+        "Can't use --edition without a standalone file"
+    );
+
+    assert!(
+        !options.release || options.cargo_profile.is_none(),
+        "Can't use --release and --cargo-profile at the same time"
+    );
+
+    assert!(
+        options.file.is_none() || (options.cargo_profile.is_none() && !options.no_default_features),
+        "Can't use --cargo-profile or --no-default-features when extracting a standalone file"
+    );
+
+    assert!(
+        !options.watch || (!options.stdout && options.exec.is_none()),
+        "Can't use --watch with --stdout or --exec"
+    );
+
+    let extract = || match &options.file {
+        Some(file) => process_file(&options, file),
+        None => process(&options),
+    };
+    if options.watch {
+        // We watch the directory of the standalone file, or the crate in the
+        // current directory
+        let root = match &options.file {
+            Some(file) => file.parent().filter(|dir| !dir.as_os_str().is_empty()),
+            None => None,
+        };
+        let split_output = options.split_output.as_deref();
+        watch::run(root.unwrap_or(Path::new(".")), split_output, extract);
+    }
+    if let Err(code) = extract() {
         std::process::exit(code);
     }
 }
@@ -112,16 +223,89 @@ fn process(options: &CliOpts) -> Result<(), i32> {
         cmd.arg(options.bin.as_ref().unwrap().clone());
     }
 
-    // Always compile in release mode: in effect, we want to analyze the released
-    // code. Also, rustc inserts a lot of dynamic checks in debug mode, that we
-    // have to clean.
-    cmd.arg("--release");
+    if let Some(target) = &options.target {
+        cmd.arg("--target");
+        cmd.arg(target);
+    }
+
+    for features in &options.features {
+        cmd.arg("--features");
+        cmd.arg(features);
+    }
+
+    if options.no_default_features {
+        cmd.arg("--no-default-features");
+    }
+
+    // Compile in release mode by default: in effect, we want to analyze the
+    // released code. Also, rustc inserts a lot of dynamic checks in debug mode,
+    // that we have to clean.
+    match &options.cargo_profile {
+        Some(profile) => {
+            cmd.arg("--profile");
+            cmd.arg(profile);
+        }
+        None => {
+            cmd.arg("--release");
+        }
+    }
+
+    run(cmd, "cargo")
+}
+
+/// Extract a standalone file. We don't need cargo to compute the arguments of
+/// rustc in this case: we call charon-driver directly, with the arguments
+/// cargo would give to rustc to build the file as a library.
+fn process_file(options: &CliOpts, file: &Path) -> Result<(), i32> {
+    // charon-driver is dynamically linked to the libraries of the toolchain it
+    // was built with: we call it through rustup to find them
+    let mut cmd = if options.cargo_no_rust_version {
+        Command::new(path())
+    } else {
+        let mut cmd = Command::new("rustup");
+        cmd.arg("run");
+        cmd.arg(RUST_VERSION.trim_start_matches('+'));
+        cmd.arg(path());
+        cmd
+    };
+    cmd.env(CHARON_ARGS, serde_json::to_string(&options).unwrap());
+
+    cmd.arg("rustc");
+    cmd.arg(file);
+    cmd.arg("--crate-type=lib");
+    let edition = options.edition.as_deref().unwrap_or("2021");
+    cmd.arg(format!("--edition={edition}"));
+
+    // rustc derives the crate name from the file name, but rejects the file
+    // names which are not identifiers (`my-test.rs` for instance). If the user
+    // gave a crate name, charon-driver adds it.
+    if options.crate_name.is_none() {
+        let stem = file.file_stem().expect("invalid file name");
+        cmd.arg("--crate-name");
+        cmd.arg(stem.to_string_lossy().replace('-', "_"));
+    }
+
+    // Cargo enables the features with `--cfg` options
+    for features in &options.features {
+        for feature in features.split([',', ' ']).filter(|f| !f.is_empty()) {
+            cmd.arg("--cfg");
+            cmd.arg(format!("feature=\"{feature}\""));
+        }
+    }
+
+    // As in [process]: we analyze the released code
+    cmd.arg("-Copt-level=3");
+
+    run(cmd, "charon-driver")
+}
 
+/// Run a command, and return its exit code if it fails
+fn run(mut cmd: Command, program: &str) -> Result<(), i32> {
     let exit_status = cmd
         .spawn()
-        .expect("could not run cargo")
+        .unwrap_or_else(|_| panic!("could not run {program}"))
         .wait()
-        .expect("failed to wait for cargo?");
+        .unwrap_or_else(|_| panic!("failed to wait for {program}?"));
 
     if exit_status.success() {
         Ok(())
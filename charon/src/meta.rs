@@ -1,11 +1,13 @@
 //! Meta-information about programs (spans, etc.).
 
 pub use crate::meta_utils::*;
+use crate::names::Name;
 use macros::{EnumAsGetters, EnumIsA};
 use serde::Serialize;
 
 generate_index_type!(LocalFileId);
 generate_index_type!(VirtualFileId);
+generate_index_type!(MacroExpansionId);
 
 #[allow(non_snake_case)]
 pub mod FileId {
@@ -74,6 +76,30 @@ pub struct Meta {
     pub span: Span,
     /// Where the code actually comes from, in case of macro expansion/inlining/etc.
     pub generated_from_span: Option<Span>,
+    /// The innermost macro expansion the code comes from, if any: the expansion
+    /// backtrace is given by the [MacroExpansion::parent] links.
+    pub expansion: Option<MacroExpansionId::Id>,
+    /// [true] if the code is inside an `unsafe { ... }` block. We only have
+    /// this information for the statements and the terminators (the bodies of
+    /// the `unsafe fn` are not considered as unsafe blocks, see
+    /// [crate::types::FunSig::is_unsafe]).
+    pub in_unsafe_block: bool,
+}
+
+/// A macro expansion. We export the table of the expansions together with the
+/// crate: the meta information refers to them by their ids.
+#[derive(Debug, Clone, Serialize)]
+pub struct MacroExpansion {
+    /// The name of the macro (for instance, `vec`).
+    pub macro_name: String,
+    /// The span of the macro call.
+    pub call_site: Span,
+    /// The span of the definition of the macro (we don't have it for some of
+    /// the builtin macros).
+    pub def_site: Option<Span>,
+    /// The expansion in which the macro is called, if the call itself comes
+    /// from a macro expansion.
+    pub parent: Option<MacroExpansionId::Id>,
 }
 
 impl From<Meta> for rustc_error_messages::MultiSpan {
@@ -96,12 +122,27 @@ pub enum InlineAttr {
     Always,
 }
 
+/// The declared visibility of an item.
+#[derive(Debug, Clone, Serialize)]
+pub enum Visibility {
+    /// `pub`
+    Public,
+    /// The item is only visible in the given module and its descendants. This is the translation
+    /// of `pub(crate)` (the module is the crate root), `pub(super)`, `pub(in path)` and of the
+    /// absence of modifier (the module is the one in which the item is declared).
+    Restricted(Name),
+}
+
 /// Meta information about an item (function, trait decl, trait impl, type decl, global).
 #[derive(Debug, Clone, Serialize)]
 pub struct ItemMeta {
     pub meta: Meta,
     /// Attributes (`#[...]`).
     pub attributes: Vec<Attribute>,
+    /// The conditions of the `#[cfg(...)]` attributes of the item and of its parents (outermost
+    /// first), for instance `feature = "std"`: this explains why an item is present in an
+    /// extraction and not in another one made with different features.
+    pub cfg: Vec<String>,
     /// Inline hints (on functions only).
     pub inline: Option<InlineAttr>,
     /// Whether this item is declared public. Impl blocks and closures don't have visibility
@@ -123,6 +164,19 @@ pub struct ItemMeta {
     /// API (this is called "pub-in-priv" items). With or without the `pub use`, we set `public =
     /// true`; computing item reachability is harder.
     pub public: bool,
+    /// The visibility of the item, as declared (`None` for impl blocks and closures). As for
+    /// [Self::public], this doesn't take reachability into account.
+    pub visibility: Option<Visibility>,
+    /// An identifier of the item which doesn't change between two extractions, if the path of the
+    /// item doesn't change (contrary to its numeric id). This is the hexadecimal representation
+    /// of the rustc `DefPathHash`, which is computed from the crate and the path of the item.
+    /// This allows the incremental consumers to map the items across versions of the crate.
+    pub stable_id: String,
+    /// A hash of the content of the item, which we compute just before exporting the crate. We
+    /// ignore the spans and identify the other items with their stable ids, so that the hash only
+    /// changes if the item itself (or the name of one of the items it refers to) changes: the
+    /// incremental consumers can use it to only re-check the items which changed.
+    pub hash: String,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize)]
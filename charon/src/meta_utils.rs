@@ -62,6 +62,15 @@ pub fn combine_meta(m0: &Meta, m1: &Meta) -> Meta {
         Meta {
             span,
             generated_from_span: None,
+            // We keep the macro expansion if the two pieces of code come from
+            // the same expansion.
+            expansion: if m0.expansion == m1.expansion {
+                m0.expansion
+            } else {
+                None
+            },
+            // A sequence is in an unsafe block if all its parts are
+            in_unsafe_block: m0.in_unsafe_block && m1.in_unsafe_block,
         }
     } else {
         // It happens that the spans don't come from the same file. In this
@@ -1,5 +1,6 @@
 //! Defines some utilities for the variables
 pub use crate::names_utils::*;
+use crate::symbol::Symbol;
 use crate::types::*;
 use macros::{EnumAsGetters, EnumIsA};
 use serde::Serialize;
@@ -9,7 +10,7 @@ generate_index_type!(Disambiguator);
 /// See the comments for [Name]
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, EnumIsA, EnumAsGetters)]
 pub enum PathElem {
-    Ident(String, Disambiguator::Id),
+    Ident(Symbol, Disambiguator::Id),
     Impl(ImplElem),
 }
 
@@ -5,6 +5,7 @@
 use crate::common::*;
 use crate::formatter::AstFormatter;
 use crate::names::*;
+use crate::symbol::Symbol;
 use crate::translate_ctx::*;
 use crate::types::*;
 use hax_frontend_exporter as hax;
@@ -14,7 +15,7 @@ use rustc_span::def_id::DefId;
 use std::collections::HashSet;
 
 impl PathElem {
-    fn equals_ident(&self, id: &str) -> bool {
+    pub(crate) fn equals_ident(&self, id: &str) -> bool {
         match self {
             PathElem::Ident(s, d) => s == id && d.is_zero(),
             PathElem::Impl(_) => false,
@@ -141,7 +142,7 @@ impl Name {
         if self.len() >= 2 {
             match (&self.name[0], &self.name[1]) {
                 (PathElem::Ident(s0, _), PathElem::Ident(s1, _)) => {
-                    s0 == krate && modules.contains(s1)
+                    s0 == krate && modules.contains(s1.as_str())
                 }
                 _ => false,
             }
@@ -212,7 +213,7 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
         let mut name: Vec<PathElem> = Vec::new();
 
         let def_path = tcx.def_path(def_id);
-        let crate_name = tcx.crate_name(def_path.krate).to_string();
+        let crate_name = Symbol::intern(tcx.crate_name(def_path.krate).as_str());
 
         let parents: Vec<DefId> = {
             let mut parents = vec![def_id];
@@ -232,11 +233,14 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
             let data = tcx.def_key(cur_id).disambiguated_data;
             // Match over the key data
             let disambiguator = Disambiguator::Id::new(data.disambiguator as usize);
+            // The user may have renamed the item with `#[charon::rename("...")]`
+            let rename = self.item_rename(cur_id);
             use rustc_hir::definitions::DefPathData;
             match &data.data {
                 DefPathData::TypeNs(symbol) => {
                     assert!(data.disambiguator == 0); // Sanity check
-                    name.push(PathElem::Ident(symbol.to_string(), disambiguator));
+                    let symbol = rename.unwrap_or_else(|| symbol.to_string());
+                    name.push(PathElem::Ident(symbol.into(), disambiguator));
                 }
                 DefPathData::ValueNs(symbol) => {
                     if data.disambiguator != 0 {
@@ -247,9 +251,10 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
                         // `betree_main::betree_utils::_#1::{impl#0}::deserialize::{impl#0}`
                         let s = symbol.to_string();
                         assert!(s == "_");
-                        name.push(PathElem::Ident(s, disambiguator));
+                        name.push(PathElem::Ident(s.into(), disambiguator));
                     } else {
-                        name.push(PathElem::Ident(symbol.to_string(), disambiguator));
+                        let symbol = rename.unwrap_or_else(|| symbol.to_string());
+                        name.push(PathElem::Ident(symbol.into(), disambiguator));
                     }
                 }
                 DefPathData::CrateRoot => {
@@ -259,7 +264,7 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
                     // This should be the beginning of the path
                     assert!(name.is_empty());
                     found_crate_name = true;
-                    name.push(PathElem::Ident(crate_name.clone(), disambiguator));
+                    name.push(PathElem::Ident(crate_name, disambiguator));
                 }
                 DefPathData::Impl => {
                     // We need to convert the type, which may contain quantified
@@ -285,7 +290,7 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
                     let mut bt_ctx = BodyTransCtx::new(id, self);
 
                     bt_ctx
-                        .translate_generic_params_from_hax(span, &substs)
+                        .translate_generic_params_from_hax(span, id, &substs)
                         .unwrap();
                     bt_ctx.translate_predicates_of(None, id).unwrap();
                     let erase_regions = false;
@@ -321,7 +326,10 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
                     }));
                 }
                 DefPathData::ImplTrait => {
-                    // TODO: do nothing for now
+                    // The opaque types introduced by `impl Trait` (we translate
+                    // them to type declarations, and they share the name of their
+                    // parent function)
+                    name.push(PathElem::Ident("opaque".into(), disambiguator))
                 }
                 DefPathData::MacroNs(symbol) => {
                     assert!(data.disambiguator == 0); // Sanity check
@@ -331,13 +339,13 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
                     // of an issue here, because for now we don't expose macros
                     // in the AST, and only use macro names in [register], for
                     // instance to filter opaque modules.
-                    name.push(PathElem::Ident(symbol.to_string(), disambiguator));
+                    name.push(PathElem::Ident(symbol.as_str().into(), disambiguator));
                 }
                 DefPathData::ClosureExpr => {
                     // TODO: this is not very satisfactory, but on the other hand
                     // we should be able to extract closures in local let-bindings
                     // (i.e., we shouldn't have to introduce top-level let-bindings).
-                    name.push(PathElem::Ident("closure".to_string(), disambiguator))
+                    name.push(PathElem::Ident("closure".into(), disambiguator))
                 }
                 DefPathData::ForeignMod => {
                     // Do nothing, functions in `extern` blocks are in the same namespace as the
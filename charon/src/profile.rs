@@ -0,0 +1,188 @@
+//! The profiling report (see the `--profile` option): the time we spend and the
+//! peak memory we use in every phase of the extraction (the exploration of the
+//! crate, the translation of the items, every micro-pass, the serialization),
+//! together with the items whose translation takes the longest and the
+//! statistics of the caches we use during the translation.
+//!
+//! We measure the peak memory with the peak resident set size given by
+//! `/proc/self/status`, which we reset at the beginning of every phase (see
+//! `clear_refs` in `man 5 proc`): we don't measure the memory on the other
+//! platforms.
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// The number of items we list in the report (the slowest ones)
+const SLOWEST_ITEMS: usize = 20;
+
+/// A phase of the extraction
+#[derive(Debug, Clone)]
+pub struct Phase {
+    pub name: String,
+    pub duration: Duration,
+    /// The peak memory during the phase, in bytes
+    pub peak_memory: Option<u64>,
+}
+
+/// The time we spent translating an item
+#[derive(Debug, Clone)]
+pub struct ItemTime {
+    pub name: String,
+    pub duration: Duration,
+}
+
+/// The statistics of a cache
+#[derive(Debug, Clone)]
+pub struct CacheStats {
+    pub name: String,
+    pub hits: usize,
+    pub misses: usize,
+    /// The number of entries in the cache
+    pub size: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct Profile {
+    pub phases: Vec<Phase>,
+    pub items: Vec<ItemTime>,
+    pub caches: Vec<CacheStats>,
+    /// The start of the current phase
+    start: Instant,
+}
+
+/// The peak resident set size of the process, in bytes
+fn peak_memory() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+    let kb: u64 = line["VmHWM:".len()..]
+        .trim()
+        .strip_suffix("kB")?
+        .trim()
+        .parse()
+        .ok()?;
+    Some(kb * 1024)
+}
+
+/// Reset the peak resident set size of the process to its current resident
+/// set size. If this fails, the peak memory of a phase is the peak memory
+/// since the start of the process.
+fn reset_peak_memory() {
+    let _ = std::fs::write("/proc/self/clear_refs", "5");
+}
+
+impl Profile {
+    /// Start the profiling, and the first phase
+    pub fn new() -> Self {
+        reset_peak_memory();
+        Profile {
+            phases: Vec::new(),
+            items: Vec::new(),
+            caches: Vec::new(),
+            start: Instant::now(),
+        }
+    }
+
+    /// End the current phase, and start the next one
+    pub fn end_phase(&mut self, name: impl Into<String>) {
+        self.phases.push(Phase {
+            name: name.into(),
+            duration: self.start.elapsed(),
+            peak_memory: peak_memory(),
+        });
+        self.restart();
+    }
+
+    /// Start a new phase, without recording the time spent since the end of
+    /// the last phase (we use this to ignore the time spent validating the
+    /// bodies, for instance).
+    pub fn restart(&mut self) {
+        reset_peak_memory();
+        self.start = Instant::now();
+    }
+
+    /// Record the time spent translating an item
+    pub fn record_item(&mut self, name: String, duration: Duration) {
+        self.items.push(ItemTime { name, duration });
+    }
+
+    /// Record the statistics of a cache
+    pub fn record_cache(
+        &mut self,
+        name: impl Into<String>,
+        hits: usize,
+        misses: usize,
+        size: usize,
+    ) {
+        self.caches.push(CacheStats {
+            name: name.into(),
+            hits,
+            misses,
+            size,
+        });
+    }
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let millis = |d: &Duration| d.as_secs_f64() * 1000.0;
+        let width = self
+            .phases
+            .iter()
+            .map(|phase| phase.name.len())
+            .max()
+            .unwrap_or_default()
+            .max("Phase".len());
+        writeln!(
+            f,
+            "{:width$}  {:>12}  {:>16}",
+            "Phase", "Time (ms)", "Peak memory (MB)"
+        )?;
+        for phase in &self.phases {
+            let memory = match phase.peak_memory {
+                Some(bytes) => format!("{:.1}", bytes as f64 / (1024.0 * 1024.0)),
+                None => "-".to_string(),
+            };
+            writeln!(
+                f,
+                "{:width$}  {:>12.1}  {:>16}",
+                phase.name,
+                millis(&phase.duration),
+                memory
+            )?;
+        }
+        let total: Duration = self.phases.iter().map(|phase| phase.duration).sum();
+        writeln!(f, "{:width$}  {:>12.1}", "Total", millis(&total))?;
+
+        let mut items: Vec<&ItemTime> = self.items.iter().collect();
+        items.sort_by(|item0, item1| item1.duration.cmp(&item0.duration));
+        if !items.is_empty() {
+            writeln!(f, "\nThe slowest items to translate:")?;
+        }
+        for item in items.into_iter().take(SLOWEST_ITEMS) {
+            writeln!(f, "{:>12.1}  {}", millis(&item.duration), item.name)?;
+        }
+
+        if !self.caches.is_empty() {
+            writeln!(f, "\nThe caches:")?;
+        }
+        for cache in &self.caches {
+            let lookups = cache.hits + cache.misses;
+            let hit_rate = if lookups == 0 {
+                0.0
+            } else {
+                cache.hits as f64 * 100.0 / lookups as f64
+            };
+            writeln!(
+                f,
+                "{}: {} hits, {} misses ({:.1}% hit rate), {} entries",
+                cache.name, cache.hits, cache.misses, hit_rate, cache.size
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,720 @@
+//! The protobuf export (behind the `protobuf` feature), for the consumers in
+//! languages with good protobuf support (Go, Java, C++, etc.).
+//!
+//! We generate the schema from the definitions of the AST, with the front-end
+//! of `--generate-ml` (see [crate::generate_ml]), and we emit the crate data by
+//! walking its JSON serialization along the schema. The schema thus mirrors the
+//! JSON representation of the crate data:
+//! - the structures become messages;
+//! - the enumerations become messages containing a single `oneof` (the
+//!   variants with several fields get their own messages, and the variants
+//!   without fields use the `Unit` message);
+//! - the generic types are monomorphized (for instance, the groups of type
+//!   declarations have the type `GDeclarationGroupTypeDeclId`);
+//! - the lists and options become `repeated` and `optional` fields (we
+//!   introduce wrapper messages when they are nested), the indices become
+//!   `uint64` and the other integers `sint64`;
+//! - the types which are left as JSON values by `--generate-ml` (the types
+//!   with a custom serialization, like the scalar values) become strings
+//!   containing their JSON serialization.
+//!
+//! # Numbering
+//! The field numbers (in particular those of the variants of the enumerations,
+//! like `TyKind` or `TraitInstanceId`) must not change when the AST evolves, so
+//! that the consumers can read the files generated by different versions of
+//! Charon. We register them in `proto/numbering.txt`: we never change the
+//! numbers which are registered, and we give the new fields the next free
+//! numbers of their messages, in order of declaration. The numbers of the
+//! fields which were removed are never reused (we mark them as `reserved`).
+//! `charon --generate-proto <dir>` generates the schema together with the
+//! updated numbering, that must be copied to `proto/` when changing the AST
+//! (the tests check that the registered numbering is up to date).
+//! The emitter numbers the fields in the same way: it stays consistent with the
+//! generated schema even if the registered numbering is out of date.
+//!
+//! Remark: like [crate::generate_ml], this module is also compiled with the
+//! `charon` binary.
+use crate::generate_ml::{translate_ast, Def, Fields, MlTy, TypeDef};
+use heck::{CamelCase, SnakeCase};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write;
+use std::path::Path;
+
+/// The registered numbering of the fields (see the module documentation).
+const NUMBERING: &str = include_str!("../proto/numbering.txt");
+
+/// The name of the generated schema.
+pub const SCHEMA_FILE: &str = "charon.proto";
+/// The name of the generated numbering.
+pub const NUMBERING_FILE: &str = "numbering.txt";
+
+/// The root message, for the ULLBC crates.
+pub const ULLBC_CRATE: &str = "UllbcCrate";
+/// The root message, for the LLBC crates.
+pub const LLBC_CRATE: &str = "LlbcCrate";
+
+/// The name of the `oneof` of the messages encoding the enumerations.
+const ONEOF: &str = "variant";
+
+/// The field numbers reserved by the protobuf implementation.
+const RESERVED_NUMBERS: std::ops::RangeInclusive<u32> = 19000..=19999;
+
+/// The scalar types we use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Scalar {
+    String,
+    Bool,
+    SInt64,
+    UInt64,
+    /// A value we leave in JSON: we encode it as a string.
+    Json,
+}
+
+impl Scalar {
+    fn to_proto(self) -> &'static str {
+        match self {
+            Scalar::String | Scalar::Json => "string",
+            Scalar::Bool => "bool",
+            Scalar::SInt64 => "sint64",
+            Scalar::UInt64 => "uint64",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Elem {
+    Scalar(Scalar),
+    Message(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Label {
+    Single,
+    Optional,
+    Repeated,
+}
+
+#[derive(Debug, Clone)]
+struct Field {
+    name: String,
+    /// The number of the field (we assign them once we built all the messages).
+    number: u32,
+    label: Label,
+    elem: Elem,
+}
+
+/// The shape of a message, which tells how to encode its JSON representation.
+#[derive(Debug, Clone)]
+enum Shape {
+    /// A message without fields: we ignore its JSON representation.
+    Unit,
+    /// Serialized as an object: we remember the JSON names of the fields.
+    Record(Vec<(String, Field)>),
+    /// Serialized as a list.
+    Tuple(Vec<Field>),
+    /// A wrapper around a nested list or option, serialized as its content.
+    Wrapper(Field),
+    /// An enumeration: the variants without fields are serialized as their
+    /// (JSON) names, the others as objects with a single field (the name).
+    Enum(Vec<(String, Field)>),
+}
+
+impl Shape {
+    fn fields(&self) -> Vec<&Field> {
+        match self {
+            Shape::Unit => Vec::new(),
+            Shape::Record(fields) | Shape::Enum(fields) => fields.iter().map(|(_, f)| f).collect(),
+            Shape::Tuple(fields) => fields.iter().collect(),
+            Shape::Wrapper(field) => vec![field],
+        }
+    }
+
+    fn fields_mut(&mut self) -> Vec<&mut Field> {
+        match self {
+            Shape::Unit => Vec::new(),
+            Shape::Record(fields) | Shape::Enum(fields) => {
+                fields.iter_mut().map(|(_, f)| f).collect()
+            }
+            Shape::Tuple(fields) => fields.iter_mut().collect(),
+            Shape::Wrapper(field) => vec![field],
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Message {
+    name: String,
+    shape: Shape,
+    /// The numbers and names of the registered fields which were removed.
+    reserved: Vec<(u32, String)>,
+}
+
+/// For every message, the numbers of its fields.
+type Numbering = BTreeMap<String, BTreeMap<String, u32>>;
+
+/// Parse a numbering file: every line has the shape `Message.field = number`
+/// (the lines starting with `#` are comments).
+fn parse_numbering(content: &str) -> Result<Numbering, String> {
+    let mut numbering = Numbering::new();
+    for (i, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let entry = line.split_once('=').and_then(|(field, number)| {
+            let (message, field) = field.trim().split_once('.')?;
+            Some((message, field, number.trim().parse::<u32>().ok()?))
+        });
+        let Some((message, field, number)) = entry else {
+            return Err(format!("{NUMBERING_FILE}:{}: invalid entry: {line}", i + 1));
+        };
+        numbering
+            .entry(message.to_string())
+            .or_default()
+            .insert(field.to_string(), number);
+    }
+    Ok(numbering)
+}
+
+/// The protobuf schema, computed from the definitions of the AST.
+pub struct Schema {
+    messages: Vec<Message>,
+    /// The index of every message in [Self::messages].
+    indices: HashMap<String, usize>,
+    /// The numbering of the fields, including the registered fields which were
+    /// removed.
+    numbering: Numbering,
+}
+
+/// The builder of the schema: we monomorphize the types of the AST, starting
+/// from the root messages.
+struct Builder {
+    defs: HashMap<String, TypeDef>,
+    messages: Vec<Message>,
+    /// The messages we already built, or are building.
+    visited: HashSet<String>,
+}
+
+/// Substitute the type parameters.
+fn subst(ty: &MlTy, params: &[String], args: &[MlTy]) -> MlTy {
+    let subst = |ty: &MlTy| subst(ty, params, args);
+    match ty {
+        MlTy::Param(p) => match params.iter().position(|q| q == p) {
+            Some(i) => args[i].clone(),
+            None => MlTy::Base("json"),
+        },
+        MlTy::Base(_) | MlTy::Id(_) => ty.clone(),
+        MlTy::List(ty) => MlTy::List(Box::new(subst(ty))),
+        MlTy::Option(ty) => MlTy::Option(Box::new(subst(ty))),
+        MlTy::Tuple(tys) => MlTy::Tuple(tys.iter().map(subst).collect()),
+        MlTy::Adt(name, tys) => MlTy::Adt(name.clone(), tys.iter().map(subst).collect()),
+    }
+}
+
+impl Builder {
+    /// Expand the type aliases.
+    fn expand(&self, ty: &MlTy) -> MlTy {
+        match ty {
+            MlTy::Adt(name, args) => match self.defs.get(name) {
+                Some(TypeDef {
+                    params,
+                    def: Def::Alias(aliased),
+                    ..
+                }) => self.expand(&subst(aliased, params, args)),
+                Some(TypeDef {
+                    def: Def::Unit(_), ..
+                }) => MlTy::Base("unit"),
+                _ => ty.clone(),
+            },
+            _ => ty.clone(),
+        }
+    }
+
+    /// The name of the message encoding a type (the type must be expanded).
+    fn mangle(&self, ty: &MlTy) -> String {
+        match ty {
+            MlTy::Base(name) | MlTy::Id(name) | MlTy::Param(name) => name.to_camel_case(),
+            MlTy::List(ty) => format!("List{}", self.mangle(&self.expand(ty))),
+            MlTy::Option(ty) => format!("Option{}", self.mangle(&self.expand(ty))),
+            MlTy::Tuple(tys) => {
+                let tys: Vec<String> = tys.iter().map(|ty| self.mangle(&self.expand(ty))).collect();
+                format!("Tuple{}", tys.concat())
+            }
+            MlTy::Adt(name, args) => {
+                let args: Vec<String> = args
+                    .iter()
+                    .map(|ty| self.mangle(&self.expand(ty)))
+                    .collect();
+                format!("{}{}", name.to_camel_case(), args.concat())
+            }
+        }
+    }
+
+    fn field(&mut self, name: &str, ty: &MlTy) -> Field {
+        let (label, elem) = match self.expand(ty) {
+            MlTy::List(ty) => (Label::Repeated, self.elem(&ty)),
+            MlTy::Option(ty) => (Label::Optional, self.elem(&ty)),
+            ty => (Label::Single, self.elem(&ty)),
+        };
+        Field {
+            name: name.trim_end_matches('_').to_string(),
+            number: 0,
+            label,
+            elem,
+        }
+    }
+
+    /// A field which is not repeated nor optional (we need this for the
+    /// fields of the `oneof`s).
+    fn single_field(&mut self, name: &str, ty: &MlTy) -> Field {
+        let elem = self.elem(ty);
+        Field {
+            name: name.to_string(),
+            number: 0,
+            label: Label::Single,
+            elem,
+        }
+    }
+
+    fn elem(&mut self, ty: &MlTy) -> Elem {
+        let ty = self.expand(ty);
+        match &ty {
+            MlTy::Base("string" | "char") => Elem::Scalar(Scalar::String),
+            MlTy::Base("bool") => Elem::Scalar(Scalar::Bool),
+            MlTy::Base("int") => Elem::Scalar(Scalar::SInt64),
+            MlTy::Base("unit") => Elem::Message("Unit".to_string()),
+            MlTy::Base(_) | MlTy::Param(_) => Elem::Scalar(Scalar::Json),
+            MlTy::Id(_) => Elem::Scalar(Scalar::UInt64),
+            MlTy::List(_) | MlTy::Option(_) | MlTy::Tuple(_) | MlTy::Adt(..) => {
+                let name = self.mangle(&ty);
+                if self.visited.insert(name.clone()) {
+                    let shape = self.shape(&name, &ty);
+                    self.add_message(name.clone(), shape);
+                }
+                Elem::Message(name)
+            }
+        }
+    }
+
+    fn add_message(&mut self, name: String, shape: Shape) {
+        self.messages.push(Message {
+            name,
+            shape,
+            reserved: Vec::new(),
+        })
+    }
+
+    /// Compute the shape of the message encoding a (expanded) type.
+    fn shape(&mut self, name: &str, ty: &MlTy) -> Shape {
+        match ty {
+            MlTy::List(_) | MlTy::Option(_) => Shape::Wrapper(self.field("value", ty)),
+            MlTy::Tuple(tys) => Shape::Tuple(self.tuple_fields(tys)),
+            MlTy::Adt(adt, args) => {
+                let Some(def) = self.defs.get(adt).cloned() else { return Shape::Unit };
+                let subst = |ty: &MlTy| subst(ty, &def.params, args);
+                match &def.def {
+                    Def::Record(fields) => Shape::Record(
+                        fields
+                            .iter()
+                            .map(|f| (f.json_name.clone(), self.field(&f.ml_name, &subst(&f.ty))))
+                            .collect(),
+                    ),
+                    Def::Tuple(tys) => {
+                        let tys: Vec<MlTy> = tys.iter().map(subst).collect();
+                        Shape::Tuple(self.tuple_fields(&tys))
+                    }
+                    Def::Variants(variants) => {
+                        let mut fields = Vec::new();
+                        for v in variants {
+                            let field_name = v.name.to_snake_case();
+                            let field = match &v.fields {
+                                Fields::Unnamed(tys) if tys.len() == 1 => {
+                                    self.single_field(&field_name, &subst(&tys[0]))
+                                }
+                                Fields::Unit => self.single_field(&field_name, &MlTy::Base("unit")),
+                                Fields::Named(named) if named.is_empty() => {
+                                    self.single_field(&field_name, &MlTy::Base("unit"))
+                                }
+                                Fields::Named(named) => {
+                                    let shape = Shape::Record(
+                                        named
+                                            .iter()
+                                            .map(|f| {
+                                                let ty = subst(&f.ty);
+                                                (f.json_name.clone(), self.field(&f.ml_name, &ty))
+                                            })
+                                            .collect(),
+                                    );
+                                    self.variant_field(name, &v.name, &field_name, shape)
+                                }
+                                Fields::Unnamed(tys) => {
+                                    let tys: Vec<MlTy> = tys.iter().map(subst).collect();
+                                    let shape = Shape::Tuple(self.tuple_fields(&tys));
+                                    self.variant_field(name, &v.name, &field_name, shape)
+                                }
+                            };
+                            fields.push((v.json_name.clone(), field))
+                        }
+                        Shape::Enum(fields)
+                    }
+                    Def::Unit(_) | Def::Alias(_) => Shape::Unit,
+                }
+            }
+            MlTy::Base(_) | MlTy::Param(_) | MlTy::Id(_) => Shape::Unit,
+        }
+    }
+
+    fn tuple_fields(&mut self, tys: &[MlTy]) -> Vec<Field> {
+        tys.iter()
+            .enumerate()
+            .map(|(i, ty)| self.field(&format!("x{i}"), ty))
+            .collect()
+    }
+
+    /// The field of a variant with several fields: we introduce a message for
+    /// the variant.
+    fn variant_field(
+        &mut self,
+        enum_name: &str,
+        variant: &str,
+        field: &str,
+        shape: Shape,
+    ) -> Field {
+        let name = format!("{enum_name}{variant}");
+        if self.visited.insert(name.clone()) {
+            self.add_message(name.clone(), shape);
+        }
+        Field {
+            name: field.to_string(),
+            number: 0,
+            label: Label::Single,
+            elem: Elem::Message(name),
+        }
+    }
+
+    /// The root message, which mirrors `GCrateData` (instantiated with the
+    /// declarations of `module`).
+    fn root(&mut self, name: &str, module: &str) -> Shape {
+        let decl = |kind: &str| MlTy::Adt(format!("{module}_{kind}"), Vec::new());
+        let crate_data = MlTy::Adt(
+            "g_crate_data".to_string(),
+            vec![decl("fun_decl"), decl("global_decl")],
+        );
+        self.visited.insert(name.to_string());
+        self.shape(name, &crate_data)
+    }
+}
+
+impl Schema {
+    /// Compute the schema, given the registered numbering (in the format of
+    /// the numbering file).
+    pub fn new(numbering: &str) -> Result<Self, String> {
+        let mut numbering = parse_numbering(numbering)?;
+        let (defs, _) = translate_ast()?;
+        let mut builder = Builder {
+            defs: defs
+                .into_iter()
+                .map(|def| (def.name.clone(), def))
+                .collect(),
+            messages: Vec::new(),
+            visited: HashSet::new(),
+        };
+        builder.visited.insert("Unit".to_string());
+        builder.add_message("Unit".to_string(), Shape::Unit);
+        for (name, module) in [(ULLBC_CRATE, "ullbc"), (LLBC_CRATE, "llbc")] {
+            let shape = builder.root(name, module);
+            builder.add_message(name.to_string(), shape);
+        }
+
+        // Number the fields
+        let mut messages = builder.messages;
+        for message in &mut messages {
+            let registered = numbering.entry(message.name.clone()).or_default();
+            let field_names: HashSet<&String> = message
+                .shape
+                .fields()
+                .into_iter()
+                .map(|f| &f.name)
+                .collect();
+            message.reserved = registered
+                .iter()
+                .filter(|(name, _)| !field_names.contains(*name))
+                .map(|(name, number)| (*number, name.clone()))
+                .collect();
+            message.reserved.sort();
+            let mut next = registered.values().max().copied().unwrap_or(0) + 1;
+            for field in message.shape.fields_mut() {
+                field.number = match registered.get(&field.name) {
+                    Some(number) => *number,
+                    None => {
+                        if RESERVED_NUMBERS.contains(&next) {
+                            next = RESERVED_NUMBERS.end() + 1;
+                        }
+                        registered.insert(field.name.clone(), next);
+                        next += 1;
+                        next - 1
+                    }
+                };
+            }
+        }
+        let indices = messages
+            .iter()
+            .enumerate()
+            .map(|(i, m)| (m.name.clone(), i))
+            .collect();
+        Ok(Schema {
+            messages,
+            indices,
+            numbering,
+        })
+    }
+
+    /// The schema, with the registered numbering.
+    pub fn registered() -> Result<Self, String> {
+        Self::new(NUMBERING)
+    }
+
+    /// Print the schema, in the protobuf language.
+    pub fn to_proto(&self) -> String {
+        let mut out = String::new();
+        out.push_str(
+            "// Generated by `charon --generate-proto` from the definitions of the Rust AST: do \
+             not edit.\n",
+        );
+        out.push_str("syntax = \"proto3\";\n\npackage charon;\n");
+        let field_ty = |f: &Field| match &f.elem {
+            Elem::Scalar(scalar) => scalar.to_proto().to_string(),
+            Elem::Message(name) => name.clone(),
+        };
+        for message in &self.messages {
+            writeln!(out, "\nmessage {} {{", message.name).unwrap();
+            for (number, name) in &message.reserved {
+                writeln!(out, "  reserved {number};\n  reserved \"{name}\";").unwrap();
+            }
+            match &message.shape {
+                Shape::Enum(variants) => {
+                    writeln!(out, "  oneof {ONEOF} {{").unwrap();
+                    for (_, f) in variants {
+                        writeln!(out, "    {} {} = {};", field_ty(f), f.name, f.number).unwrap();
+                    }
+                    writeln!(out, "  }}").unwrap();
+                }
+                shape => {
+                    for f in shape.fields() {
+                        let label = match f.label {
+                            Label::Single => "",
+                            Label::Optional => "optional ",
+                            Label::Repeated => "repeated ",
+                        };
+                        writeln!(out, "  {label}{} {} = {};", field_ty(f), f.name, f.number)
+                            .unwrap();
+                    }
+                }
+            }
+            writeln!(out, "}}").unwrap();
+        }
+        out
+    }
+
+    /// Print the numbering of the fields (see the module documentation).
+    pub fn numbering(&self) -> String {
+        let mut out = String::new();
+        out.push_str("# The numbering of the fields of the protobuf schema: see `protobuf.rs`.\n");
+        for (message, fields) in &self.numbering {
+            let mut fields: Vec<(&String, &u32)> = fields.iter().collect();
+            fields.sort_by_key(|(_, number)| **number);
+            for (field, number) in fields {
+                writeln!(out, "{message}.{field} = {number}").unwrap();
+            }
+        }
+        out
+    }
+
+    /// Encode the JSON representation of a crate, as a message of the schema
+    /// (for instance, [LLBC_CRATE]).
+    pub fn encode(&self, message: &str, value: &Value) -> Result<Vec<u8>, String> {
+        let mut out = Vec::new();
+        self.encode_message(&mut out, message, value)?;
+        Ok(out)
+    }
+
+    /// Encode a field of a record message (for instance, of [LLBC_CRATE]), given
+    /// its JSON name and its JSON representation. The encoding of a message is
+    /// the concatenation of the encodings of its fields: this allows to encode
+    /// a crate one field at a time. We ignore the fields which are not in the
+    /// schema (as [Self::encode] does).
+    pub fn encode_record_field(
+        &self,
+        message: &str,
+        json_name: &str,
+        value: &Value,
+    ) -> Result<Vec<u8>, String> {
+        let mut out = Vec::new();
+        if let Some(field) = self.record_field(message, json_name)? {
+            self.encode_field(&mut out, field, value)?;
+        }
+        Ok(out)
+    }
+
+    /// Encode an element of a repeated field of a record message (see
+    /// [Self::encode_record_field]): the encoding of a repeated field is the
+    /// concatenation of the encodings of its elements.
+    pub fn encode_record_elem(
+        &self,
+        message: &str,
+        json_name: &str,
+        value: &Value,
+    ) -> Result<Vec<u8>, String> {
+        let mut out = Vec::new();
+        match self.record_field(message, json_name)? {
+            Some(field) if field.label == Label::Repeated => {
+                self.encode_elem(&mut out, field, value)?
+            }
+            Some(field) => return Err(format!("not a repeated field: {}", field.name)),
+            None => (),
+        }
+        Ok(out)
+    }
+
+    /// Lookup a field of a record message, given its JSON name.
+    fn record_field(&self, message: &str, json_name: &str) -> Result<Option<&Field>, String> {
+        let Some(index) = self.indices.get(message) else {
+            return Err(format!("unknown message: {message}"));
+        };
+        let Shape::Record(fields) = &self.messages[*index].shape else {
+            return Err(format!("not a record message: {message}"));
+        };
+        Ok(fields
+            .iter()
+            .find(|(name, _)| name == json_name)
+            .map(|(_, field)| field))
+    }
+
+    fn encode_message(
+        &self,
+        out: &mut Vec<u8>,
+        message: &str,
+        value: &Value,
+    ) -> Result<(), String> {
+        let Some(index) = self.indices.get(message) else {
+            return Err(format!("unknown message: {message}"));
+        };
+        let error = || format!("unexpected value for the message {message}: {value}");
+        match &self.messages[*index].shape {
+            Shape::Unit => Ok(()),
+            Shape::Record(fields) => {
+                let Value::Object(obj) = value else { return Err(error()) };
+                for (json_name, field) in fields {
+                    if let Some(value) = obj.get(json_name) {
+                        self.encode_field(out, field, value)?;
+                    }
+                }
+                Ok(())
+            }
+            Shape::Tuple(fields) => {
+                let Value::Array(values) = value else { return Err(error()) };
+                for (field, value) in fields.iter().zip(values) {
+                    self.encode_field(out, field, value)?;
+                }
+                Ok(())
+            }
+            Shape::Wrapper(field) => self.encode_field(out, field, value),
+            Shape::Enum(variants) => {
+                let null = Value::Null;
+                let (variant, payload) = match value {
+                    Value::String(variant) => (variant, &null),
+                    Value::Object(obj) if obj.len() == 1 => obj.iter().next().unwrap(),
+                    _ => return Err(error()),
+                };
+                let Some((_, field)) = variants.iter().find(|(name, _)| name == variant) else {
+                    return Err(error());
+                };
+                self.encode_elem(out, field, payload)
+            }
+        }
+    }
+
+    fn encode_field(&self, out: &mut Vec<u8>, field: &Field, value: &Value) -> Result<(), String> {
+        match (field.label, value) {
+            (Label::Repeated, Value::Array(values)) => {
+                for value in values {
+                    self.encode_elem(out, field, value)?;
+                }
+                Ok(())
+            }
+            (Label::Repeated, _) => Err(format!("expected a list for {}: {value}", field.name)),
+            (Label::Optional, Value::Null) => Ok(()),
+            (Label::Optional | Label::Single, _) => self.encode_elem(out, field, value),
+        }
+    }
+
+    fn encode_elem(&self, out: &mut Vec<u8>, field: &Field, value: &Value) -> Result<(), String> {
+        let error = || format!("unexpected value for {}: {value}", field.name);
+        // The wire types: 0 for the varints, 2 for the length-delimited values
+        let tag = |wire_type: u64| ((field.number as u64) << 3) | wire_type;
+        match &field.elem {
+            Elem::Scalar(Scalar::Bool) => {
+                write_varint(out, tag(0));
+                write_varint(out, value.as_bool().ok_or_else(error)? as u64);
+            }
+            Elem::Scalar(Scalar::UInt64) => {
+                write_varint(out, tag(0));
+                write_varint(out, value.as_u64().ok_or_else(error)?);
+            }
+            Elem::Scalar(Scalar::SInt64) => {
+                let n = value.as_i64().ok_or_else(error)?;
+                write_varint(out, tag(0));
+                write_varint(out, ((n << 1) ^ (n >> 63)) as u64);
+            }
+            Elem::Scalar(Scalar::String) => {
+                let s = value.as_str().ok_or_else(error)?;
+                write_bytes(out, tag(2), s.as_bytes());
+            }
+            Elem::Scalar(Scalar::Json) => write_bytes(out, tag(2), value.to_string().as_bytes()),
+            Elem::Message(message) => {
+                let mut content = Vec::new();
+                self.encode_message(&mut content, message, value)?;
+                write_bytes(out, tag(2), &content);
+            }
+        }
+        Ok(())
+    }
+}
+
+fn write_varint(out: &mut Vec<u8>, mut n: u64) {
+    while n >= 0x80 {
+        out.push((n as u8) | 0x80);
+        n >>= 7;
+    }
+    out.push(n as u8)
+}
+
+fn write_bytes(out: &mut Vec<u8>, tag: u64, bytes: &[u8]) {
+    write_varint(out, tag);
+    write_varint(out, bytes.len() as u64);
+    out.extend_from_slice(bytes)
+}
+
+/// Run `charon --generate-proto`, and return the exit code.
+pub fn run(dir: &Path) -> i32 {
+    let write = |file: &str, content: &str| {
+        let path = dir.join(file);
+        std::fs::write(&path, content).map_err(|error| format!("{}: {}", path.display(), error))
+    };
+    let res = Schema::registered().and_then(|schema| {
+        std::fs::create_dir_all(dir).map_err(|error| format!("{}: {}", dir.display(), error))?;
+        write(SCHEMA_FILE, &schema.to_proto())?;
+        write(NUMBERING_FILE, &schema.numbering())
+    });
+    match res {
+        Ok(()) => {
+            log::info!("Generated the protobuf schema in: {}", dir.display());
+            0
+        }
+        Err(msg) => {
+            log::error!("{}", msg);
+            2
+        }
+    }
+}
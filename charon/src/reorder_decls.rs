@@ -11,6 +11,7 @@ use macros::{EnumAsGetters, EnumIsA, VariantIndexArity, VariantName};
 use petgraph::algo::tarjan_scc;
 use petgraph::graphmap::DiGraphMap;
 use serde::Serialize;
+use std::collections::HashSet;
 use std::fmt::{Debug, Display, Error};
 use std::vec::Vec;
 
@@ -170,6 +171,7 @@ impl DeclarationGroup {
     Debug,
     PartialOrd,
     Ord,
+    Serialize,
 )]
 pub enum AnyDeclId<TypeId, FunId, GlobalId, TraitDeclId, TraitImplId> {
     Type(TypeId),
@@ -266,6 +268,9 @@ pub struct Deps {
     /// //                                    refers to the trait impl
     /// ```
     impl_trait_id: Option<TraitImplId::Id>,
+    /// The globals of the promoted constants of the body we are exploring
+    /// (see [GExprBody::promoted]).
+    promoted: PromotedId::Vector<GlobalDeclId::Id>,
 }
 
 impl Deps {
@@ -275,6 +280,7 @@ impl Deps {
             graph: LinkedHashMap::new(),
             current_id: None,
             impl_trait_id: None,
+            promoted: PromotedId::Vector::new(),
         }
     }
 
@@ -293,7 +299,7 @@ impl Deps {
                 // Lookup the function declaration.
                 //
                 // The declaration may not be present if we encountered errors.
-                if let Some(decl) = ctx.fun_decls.get(id) {
+                if let Some(decl) = ctx.translated.fun_decls.get(id) {
                     if let ItemKind::TraitItemImpl {
                         impl_id,
                         trait_id: _,
@@ -306,7 +312,7 @@ impl Deps {
                     }
                 } else {
                     // Sanity check
-                    assert!(ctx.error_count > 0);
+                    assert!(ctx.errors.error_count > 0);
                 }
             }
         }
@@ -386,7 +392,19 @@ impl SharedTypeVisitor for Deps {
     }
 }
 
-impl SharedExprVisitor for Deps {}
+impl SharedExprVisitor for Deps {
+    fn visit_raw_constant_expr(&mut self, expr: &RawConstantExpr) {
+        // The body depends on the promoted constants it uses (the bodies of the
+        // promoted constants list all the promoted constants of their parent)
+        if let RawConstantExpr::Promoted(id, _) = expr
+            && let Some(id) = self.promoted.get(*id).copied()
+        {
+            self.visit_global_decl_id(&id)
+        }
+        self.default_visit_raw_constant_expr(expr)
+    }
+}
+
 impl SharedAstVisitor for Deps {}
 
 impl Deps {
@@ -394,6 +412,7 @@ impl Deps {
         match &body {
             Option::None => (),
             Option::Some(body) => {
+                self.promoted = body.promoted.clone();
                 for v in &body.locals {
                     self.visit_ty(&v.ty);
                 }
@@ -416,7 +435,7 @@ impl Deps {
 
     /// Lookup a function and visit its signature
     fn visit_fun_signature_from_trait(&mut self, ctx: &TransCtx, fid: FunDeclId::Id) {
-        let decl = ctx.fun_decls.get(fid).unwrap();
+        let decl = ctx.translated.fun_decls.get(fid).unwrap();
         self.visit_fun_sig(&decl.signature);
     }
 }
@@ -463,7 +482,7 @@ pub fn reorder_declarations(ctx: &mut TransCtx) {
         graph.set_current_id(ctx, *id);
         match id {
             AnyTransId::Type(id) => {
-                if let Some(d) = ctx.type_decls.get(*id) {
+                if let Some(d) = ctx.translated.type_decls.get(*id) {
                     use TypeDeclKind::*;
 
                     // Visit the generics and the predicates
@@ -476,22 +495,34 @@ pub fn reorder_declarations(ctx: &mut TransCtx) {
                                 graph.visit_ty(&f.ty)
                             }
                         }
-                        Enum(vl) => {
+                        Enum(vl, ..) => {
                             for v in vl {
                                 for f in &v.fields {
                                     graph.visit_ty(&f.ty);
                                 }
                             }
                         }
+                        ImplTrait { bounds, hidden_ty } => {
+                            for b in bounds {
+                                graph.visit_trait_decl_id(&b.trait_id);
+                                graph.visit_generic_args(&b.generics);
+                                for (_, ty) in &b.type_constraints {
+                                    graph.visit_ty(ty);
+                                }
+                            }
+                            if let Some(ty) = hidden_ty {
+                                graph.visit_ty(ty);
+                            }
+                        }
                         Opaque | Error(_) => (),
                     }
                 } else {
                     // There may have been errors
-                    assert!(ctx.error_count > 0);
+                    assert!(ctx.errors.error_count > 0);
                 }
             }
             AnyTransId::Fun(id) => {
-                if let Some(d) = ctx.fun_decls.get(*id) {
+                if let Some(d) = ctx.translated.fun_decls.get(*id) {
                     // Explore the signature
                     let sig = &d.signature;
                     graph.visit_generics_and_preds(&sig.generics, &sig.preds);
@@ -504,20 +535,20 @@ pub fn reorder_declarations(ctx: &mut TransCtx) {
                     graph.visit_body(&d.body);
                 } else {
                     // There may have been errors
-                    assert!(ctx.error_count > 0);
+                    assert!(ctx.errors.error_count > 0);
                 }
             }
             AnyTransId::Global(id) => {
-                if let Some(d) = ctx.global_decls.get(*id) {
+                if let Some(d) = ctx.translated.global_decls.get(*id) {
                     // Explore the body
                     graph.visit_body(&d.body);
                 } else {
                     // There may have been errors
-                    assert!(ctx.error_count > 0);
+                    assert!(ctx.errors.error_count > 0);
                 }
             }
             AnyTransId::TraitDecl(id) => {
-                if let Some(d) = ctx.trait_decls.get(*id) {
+                if let Some(d) = ctx.translated.trait_decls.get(*id) {
                     // Visit the generics and the predicates
                     graph.visit_generics_and_preds(&d.generics, &d.preds);
 
@@ -534,7 +565,8 @@ pub fn reorder_declarations(ctx: &mut TransCtx) {
                         }
                     }
 
-                    for (_, (clauses, ty)) in &d.types {
+                    for (_, (generics, clauses, ty)) in &d.types {
+                        graph.visit_generic_params(generics);
                         for c in clauses {
                             graph.visit_trait_clause(c);
                         }
@@ -564,11 +596,11 @@ pub fn reorder_declarations(ctx: &mut TransCtx) {
                     }
                 } else {
                     // There may have been errors
-                    assert!(ctx.error_count > 0);
+                    assert!(ctx.errors.error_count > 0);
                 }
             }
             AnyTransId::TraitImpl(id) => {
-                if let Some(d) = ctx.trait_impls.get(*id) {
+                if let Some(d) = ctx.translated.trait_impls.get(*id) {
                     // Visit the generics and the predicates
                     graph.visit_generics_and_preds(&d.generics, &d.preds);
 
@@ -587,19 +619,25 @@ pub fn reorder_declarations(ctx: &mut TransCtx) {
                         graph.visit_global_decl_id(id);
                     }
 
-                    for (_, (trait_refs, ty)) in &d.types {
+                    for (_, (generics, trait_refs, ty)) in &d.types {
+                        graph.visit_generic_params(generics);
                         graph.visit_ty(ty);
                         for trait_ref in trait_refs {
                             graph.visit_trait_ref(trait_ref);
                         }
                     }
 
-                    for (_, id) in d.required_methods.iter().chain(d.provided_methods.iter()) {
+                    let method_ids = d
+                        .required_methods
+                        .iter()
+                        .map(|(_, id)| id)
+                        .chain(d.provided_methods.iter().map(|(_, (id, _))| id));
+                    for id in method_ids {
                         graph.visit_fun_decl_id(id)
                     }
                 } else {
                     // There may have been errors
-                    assert!(ctx.error_count > 0);
+                    assert!(ctx.errors.error_count > 0);
                 }
             }
         }
@@ -621,77 +659,165 @@ pub fn reorder_declarations(ctx: &mut TransCtx) {
     let all_ids: Vec<AnyTransId> = graph.graph.keys().copied().collect();
     let SCCs {
         sccs: reordered_sccs,
-        scc_deps: _,
+        scc_deps,
     } = reorder_sccs::<AnyTransId>(get_id_dependencies, &all_ids, &sccs);
 
     // Finally, generate the list of declarations
     let mut reordered_decls: DeclarationsGroups = Vec::new();
+    // The indices of the groups generated for every SCC
+    let mut scc_groups: Vec<Vec<usize>> = Vec::new();
 
     // Iterate over the SCC ids in the proper order
     for scc in reordered_sccs.iter() {
         // Retrieve the SCC
         assert!(!scc.is_empty());
 
-        // Note that the length of an SCC should be at least 1.
-        let mut it = scc.iter();
-        let id0 = *it.next().unwrap();
-        let decl = graph.graph.get(&id0).unwrap();
-
-        // The group should consist of only functions, only types or only one global.
-        for id in scc {
+        // The group should consist of only functions, only types or only
+        // globals. The only exception is the cycles between the globals and
+        // the functions, for instance if a static stores a function pointer
+        // and the function reads the static: we split the SCC into a group of
+        // functions followed by a group of globals, both recursive.
+        let parts: Vec<Vec<AnyTransId>> = if scc
+            .iter()
+            .all(|id| id.variant_index_arity() == scc[0].variant_index_arity())
+        {
+            vec![scc.clone()]
+        } else {
             assert!(
-                id0.variant_index_arity() == id.variant_index_arity(),
+                scc.iter().all(|id| id.is_fun() || id.is_global()),
                 "Invalid scc:\n{}",
                 scc.iter()
                     .map(|x| x.fmt_with_ctx(ctx))
                     .collect::<Vec<String>>()
                     .join("\n")
             );
+            let (funs, globals) = scc.iter().copied().partition(|id| id.is_fun());
+            vec![funs, globals]
+        };
+
+        let mut groups = Vec::new();
+        for part in parts {
+            groups.push(reordered_decls.len());
+            reordered_decls.push(make_group(ctx, &graph, scc.len(), &part));
         }
-        if let AnyDeclId::Global(_) = id0 {
-            assert!(scc.len() == 1);
+        scc_groups.push(groups);
+    }
+
+    trace!("{:?}", reordered_decls);
+
+    // Compute the dependencies of the groups: the groups they depend on, and
+    // the previous groups of the same SCC
+    let mut decls_deps = Vec::new();
+    for (groups, deps) in scc_groups.iter().zip(scc_deps.iter()) {
+        let deps: Vec<usize> = deps
+            .iter()
+            .flat_map(|scc_id| scc_groups[*scc_id].iter().copied())
+            .collect();
+        for i in 0..groups.len() {
+            let mut group_deps = deps.clone();
+            group_deps.extend(&groups[..i]);
+            decls_deps.push(group_deps);
         }
+    }
 
-        // If an SCC has length one, the declaration may be simply recursive:
-        // we determine whether it is the case by checking if the def id is in
-        // its own set of dependencies.
-        let is_mutually_recursive = scc.len() > 1;
-        let is_simply_recursive = !is_mutually_recursive && decl.contains(&id0);
-
-        // Add the declaration.
-        // Note that we clone the vectors: it is not optimal, but they should
-        // be pretty small.
-        let is_rec = is_mutually_recursive || is_simply_recursive;
-        let group: DeclarationGroup = match id0 {
-            AnyDeclId::Type(_) => DeclarationGroup::make_type_group(
-                is_rec,
-                scc.iter().map(AnyDeclId::as_type).copied(),
-            ),
-            AnyDeclId::Fun(_) => {
-                DeclarationGroup::make_fun_group(is_rec, scc.iter().map(AnyDeclId::as_fun).copied())
+    // Compute the initialization dependencies of the globals
+    for id in &all_ids {
+        if let AnyDeclId::Global(id) = id {
+            let deps = compute_global_init_deps(&graph, *id);
+            if let Some(d) = ctx.translated.global_decls.get_mut(*id) {
+                d.init_deps = deps;
             }
-            AnyDeclId::Global(_) => DeclarationGroup::make_global_group(
-                is_rec,
-                scc.iter().map(AnyDeclId::as_global).copied(),
-            ),
-            AnyDeclId::TraitDecl(_) => DeclarationGroup::make_trait_decl_group(
-                ctx,
-                is_rec,
-                scc.iter().map(AnyDeclId::as_trait_decl).copied(),
-            ),
-            AnyDeclId::TraitImpl(_) => DeclarationGroup::make_trait_impl_group(
-                ctx,
-                is_rec,
-                scc.iter().map(AnyDeclId::as_trait_impl).copied(),
-            ),
-        };
+        }
+    }
 
-        reordered_decls.push(group);
+    // Record the recursive groups of the types and of the functions
+    for group in &reordered_decls {
+        match group {
+            DeclarationGroup::Type(GDeclarationGroup::Rec(ids)) => {
+                for id in ids {
+                    if let Some(d) = ctx.translated.type_decls.get_mut(*id) {
+                        d.rec_group = Some(ids.clone());
+                    }
+                }
+            }
+            DeclarationGroup::Fun(GDeclarationGroup::Rec(ids)) => {
+                for id in ids {
+                    if let Some(d) = ctx.translated.fun_decls.get_mut(*id) {
+                        d.rec_group = Some(ids.clone());
+                    }
+                }
+            }
+            _ => (),
+        }
     }
 
-    trace!("{:?}", reordered_decls);
+    ctx.translated.ordered_decls = Some(reordered_decls);
+    ctx.translated.ordered_decls_deps = decls_deps;
+}
+
+/// Generate the declaration group for (a part of) an SCC of size `scc_len`.
+fn make_group(
+    ctx: &TransCtx,
+    graph: &Deps,
+    scc_len: usize,
+    ids: &[AnyTransId],
+) -> DeclarationGroup {
+    let id0 = ids[0];
+    let decl = graph.graph.get(&id0).unwrap();
+
+    // If an SCC has length one, the declaration may be simply recursive:
+    // we determine whether it is the case by checking if the def id is in
+    // its own set of dependencies.
+    let is_mutually_recursive = scc_len > 1;
+    let is_simply_recursive = !is_mutually_recursive && decl.contains(&id0);
+
+    let is_rec = is_mutually_recursive || is_simply_recursive;
+    match id0 {
+        AnyDeclId::Type(_) => {
+            DeclarationGroup::make_type_group(is_rec, ids.iter().map(AnyDeclId::as_type).copied())
+        }
+        AnyDeclId::Fun(_) => {
+            DeclarationGroup::make_fun_group(is_rec, ids.iter().map(AnyDeclId::as_fun).copied())
+        }
+        AnyDeclId::Global(_) => DeclarationGroup::make_global_group(
+            is_rec,
+            ids.iter().map(AnyDeclId::as_global).copied(),
+        ),
+        AnyDeclId::TraitDecl(_) => DeclarationGroup::make_trait_decl_group(
+            ctx,
+            is_rec,
+            ids.iter().map(AnyDeclId::as_trait_decl).copied(),
+        ),
+        AnyDeclId::TraitImpl(_) => DeclarationGroup::make_trait_impl_group(
+            ctx,
+            is_rec,
+            ids.iter().map(AnyDeclId::as_trait_impl).copied(),
+        ),
+    }
+}
 
-    ctx.ordered_decls = Some(reordered_decls);
+/// Compute the initialization dependencies of a global (see
+/// [GGlobalDecl::init_deps]). We explore the declarations its body refers to,
+/// and the declarations the functions and the trait implementations we find
+/// refer to, transitively, but we don't explore the bodies of the globals we
+/// find: those are initialized separately.
+fn compute_global_init_deps(graph: &Deps, id: GlobalDeclId::Id) -> Vec<GlobalDeclId::Id> {
+    let mut deps = Vec::new();
+    let mut visited = HashSet::new();
+    let mut stack: Vec<AnyTransId> = vec![AnyDeclId::Global(id)];
+    while let Some(id) = stack.pop() {
+        for dep in graph.graph.get(&id).unwrap() {
+            if !visited.insert(*dep) {
+                continue;
+            }
+            match dep {
+                AnyDeclId::Global(id) => deps.push(*id),
+                AnyDeclId::Fun(_) | AnyDeclId::TraitImpl(_) => stack.push(*dep),
+                AnyDeclId::Type(_) | AnyDeclId::TraitDecl(_) => (),
+            }
+        }
+    }
+    deps
 }
 
 #[cfg(test)]
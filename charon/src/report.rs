@@ -0,0 +1,196 @@
+//! The extraction report: we list all the items we encountered, together
+//! with the outcome of their extraction (see [Report]). We export it with
+//! the `--report` option: when we don't abort on the first error, this allows
+//! the user to know what is actually in the output file.
+use crate::common::CharonError;
+use crate::formatter::IntoFormatter;
+use crate::gast::ItemKind;
+use crate::ids::generator::MapGenerator;
+use crate::names::Name;
+use crate::reorder_decls::AnyDeclId;
+use crate::translate_ctx::TransCtx;
+use crate::types::TypeDeclKind;
+use index_vec::Idx;
+use rustc_hir::def_id::DefId;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::Path;
+
+/// The reason we give for the items in the modules marked as opaque.
+pub(crate) const OPAQUE_MODULE: &str = "the item is in an opaque module (see `--opaque`)";
+/// The reason we give for the items marked with `#[charon::opaque]` (or
+/// which are in such items).
+pub(crate) const OPAQUE_ATTRIBUTE: &str = "the item is marked with `#[charon::opaque]`";
+/// The reason we give for the items marked with `#[charon::skip]`.
+pub(crate) const SKIP_ATTRIBUTE: &str = "the item is marked with `#[charon::skip]`";
+
+/// The outcome of the extraction of an item.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum ItemStatus {
+    /// We translated the item.
+    Translated,
+    /// We only translated the signature of the item (or, for a type, we
+    /// don't have access to its definition).
+    Opaque { reason: String },
+    /// We encountered errors when translating the item. If the item is
+    /// not present in the output file, this means we had to ignore it.
+    Errored { errors: Vec<String> },
+    /// We didn't look at the item because of the options.
+    Skipped { reason: String },
+}
+
+/// The outcome of the extraction of an item.
+#[derive(Debug, Clone, Serialize)]
+pub struct ItemReport {
+    pub name: String,
+    /// The kind of the item (`"type"`, `"function"`, etc.)
+    pub kind: String,
+    #[serde(flatten)]
+    pub status: ItemStatus,
+}
+
+/// A trait obligation we failed to solve (see
+/// [crate::transform::solve_trait_obligations]).
+#[derive(Debug, Clone, Serialize)]
+pub struct UnsolvedObligation {
+    /// The name of the item in which the obligation appears
+    pub item: String,
+    /// The clause of the item in which the obligation appears, if it appears
+    /// in a clause
+    pub clause: Option<String>,
+    /// The trait reference for which we didn't find an implementation
+    pub missing_impl: String,
+}
+
+/// The items we encountered, in the order in which we encountered them
+/// (followed by the items we skipped), and the trait obligations we failed to
+/// solve.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Report {
+    pub items: Vec<ItemReport>,
+    pub unsolved_obligations: Vec<UnsolvedObligation>,
+}
+
+/// Compute the map from the translated ids to the Rust ids.
+pub(crate) fn invert_ids<I: Idx>(ids: &MapGenerator<DefId, I>) -> HashMap<I, DefId> {
+    ids.iter().map(|(rust_id, id)| (*id, *rust_id)).collect()
+}
+
+impl Report {
+    pub(crate) fn new(ctx: &TransCtx) -> Self {
+        let type_ids = invert_ids(&ctx.type_id_map);
+        let fun_ids = invert_ids(&ctx.fun_id_map);
+        let global_ids = invert_ids(&ctx.global_id_map);
+        let trait_decl_ids = invert_ids(&ctx.trait_decl_id_map);
+        let translated = &ctx.translated;
+
+        let mut items = Vec::new();
+        for id in &ctx.all_ids {
+            // For every item, we retrieve the Rust id, the name if we managed
+            // to translate the item, and whether the item is opaque.
+            let (kind, rust_id, decl) = match id {
+                AnyDeclId::Type(id) => {
+                    let decl = translated.type_decls.get(*id);
+                    let decl = decl.map(|d| (&d.name, matches!(d.kind, TypeDeclKind::Opaque)));
+                    ("type", type_ids[id], decl)
+                }
+                AnyDeclId::Fun(id) => {
+                    let decl = translated.fun_decls.get(*id).map(|d| {
+                        // The trait method declarations don't have a body
+                        let is_decl = matches!(d.kind, ItemKind::TraitItemDecl(..));
+                        (&d.name, d.body.is_none() && !is_decl)
+                    });
+                    ("function", fun_ids[id], decl)
+                }
+                AnyDeclId::Global(id) => {
+                    let decl = translated.global_decls.get(*id);
+                    let decl = decl.map(|d| (&d.name, d.body.is_none()));
+                    ("global", global_ids[id], decl)
+                }
+                AnyDeclId::TraitDecl(id) => {
+                    let decl = translated.trait_decls.get(*id).map(|d| (&d.name, false));
+                    ("trait", trait_decl_ids[id], decl)
+                }
+                AnyDeclId::TraitImpl(id) => {
+                    let decl = translated.trait_impls.get(*id).map(|d| (&d.name, false));
+                    ("trait impl", ctx.trait_impl_id_to_def_id[id], decl)
+                }
+            };
+            items.push(Self::item_report(ctx, kind, rust_id, decl));
+        }
+
+        let fmt_ctx = ctx.into_fmt();
+        for (name, kind, reason) in &ctx.skipped_items {
+            items.push(ItemReport {
+                name: name.fmt_with_ctx(&fmt_ctx),
+                kind: kind.to_string(),
+                status: ItemStatus::Skipped {
+                    reason: reason.to_string(),
+                },
+            });
+        }
+
+        Report {
+            items,
+            unsolved_obligations: ctx.errors.unsolved_obligations.clone(),
+        }
+    }
+
+    /// Compute the report of a declaration. `decl` contains the name of the
+    /// declaration and whether it is opaque, if we managed to translate it.
+    fn item_report(
+        ctx: &TransCtx,
+        kind: &str,
+        rust_id: DefId,
+        decl: Option<(&Name, bool)>,
+    ) -> ItemReport {
+        let name = match decl {
+            Some((name, _)) => name.fmt_with_ctx(&ctx.into_fmt()),
+            None => ctx.tcx.def_path_str(rust_id),
+        };
+        let status = match (ctx.errors.decls_with_errors.get(&rust_id), decl) {
+            (Some(errors), _) => ItemStatus::Errored {
+                errors: errors.clone(),
+            },
+            // We failed to translate the declaration: we should have registered
+            // an error, but we can't give any details
+            (None, None) => ItemStatus::Errored { errors: Vec::new() },
+            (None, Some((_, false))) => ItemStatus::Translated,
+            (None, Some((name, true))) => {
+                let reason = if ctx.crate_info.is_opaque_decl(name) {
+                    OPAQUE_MODULE
+                } else if ctx.id_is_marked_opaque(rust_id) {
+                    OPAQUE_ATTRIBUTE
+                } else if ctx.id_is_extern_item(rust_id) {
+                    "the item is declared in an `extern` block"
+                } else if !rust_id.is_local() {
+                    "the item comes from an external crate (see `--extract-opaque-bodies` and \
+                     `--extract-external-bodies`)"
+                } else {
+                    "the item doesn't have a body"
+                };
+                ItemStatus::Opaque {
+                    reason: reason.to_string(),
+                }
+            }
+        };
+        ItemReport {
+            name,
+            kind: kind.to_string(),
+            status,
+        }
+    }
+
+    /// Export the report to a JSON file.
+    pub fn serialize_to_file(&self, target_filename: &Path) -> Result<(), CharonError> {
+        let outfile = File::create(target_filename).map_err(|error| CharonError::Io {
+            path: target_filename.to_path_buf(),
+            error,
+        })?;
+        serde_json::to_writer_pretty(&outfile, self).map_err(CharonError::Serialize)?;
+        info!("Generated the report: {}", target_filename.display());
+        Ok(())
+    }
+}
@@ -0,0 +1,60 @@
+//! The S-expression export format (see the `--format` option), for the
+//! consumers whose parsers handle S-expressions more naturally than JSON.
+//!
+//! We use the same structure as the JSON output, that we translate as follows:
+//! - the arrays become lists: `[1, 2]` becomes `(1 2)`;
+//! - the objects become association lists, whose keys are strings:
+//!   `{ "line": 1, "col": 0 }` becomes `(("line" . 1) ("col" . 0))`;
+//! - `null`, `true` and `false` become the keywords `:null`, `:true` and
+//!   `:false` (we don't use `nil` and `t`, which would be ambiguous);
+//! - the numbers and strings are unchanged (we escape the `"` and `\`
+//!   characters in the strings).
+//!
+//! Note that the empty arrays and the empty objects both become `()`.
+use serde_json::Value;
+use std::io::{Result, Write};
+
+fn write_string(writer: &mut dyn Write, s: &str) -> Result<()> {
+    write!(writer, "\"")?;
+    for c in s.chars() {
+        match c {
+            '"' | '\\' => write!(writer, "\\{c}")?,
+            c => write!(writer, "{c}")?,
+        }
+    }
+    write!(writer, "\"")
+}
+
+/// Write a JSON value as an S-expression.
+pub fn write_value(writer: &mut dyn Write, value: &Value) -> Result<()> {
+    match value {
+        Value::Null => write!(writer, ":null"),
+        Value::Bool(b) => write!(writer, ":{b}"),
+        Value::Number(n) => write!(writer, "{n}"),
+        Value::String(s) => write_string(writer, s),
+        Value::Array(values) => {
+            write!(writer, "(")?;
+            for (i, v) in values.iter().enumerate() {
+                if i > 0 {
+                    write!(writer, " ")?;
+                }
+                write_value(writer, v)?;
+            }
+            write!(writer, ")")
+        }
+        Value::Object(fields) => {
+            write!(writer, "(")?;
+            for (i, (key, v)) in fields.iter().enumerate() {
+                if i > 0 {
+                    write!(writer, " ")?;
+                }
+                write!(writer, "(")?;
+                write_string(writer, key)?;
+                write!(writer, " . ")?;
+                write_value(writer, v)?;
+                write!(writer, ")")?;
+            }
+            write!(writer, ")")
+        }
+    }
+}
@@ -0,0 +1,191 @@
+//! Interned strings.
+//!
+//! The path segments of the names, the names of the fields and the names of
+//! the variants are heavily duplicated (all the items of a module share the
+//! path of the module, for instance). We intern them: a [Symbol] points to a
+//! string of a global table. Copying and comparing symbols is O(1), and reading
+//! their strings doesn't access the table (we only lock it to intern strings).
+//!
+//! We also share the strings in the output: while serializing a crate inside
+//! [with_string_table], we serialize the symbols as indices in a string table,
+//! which we serialize once (see [StringTable]). The indices are attributed in
+//! the order in which we serialize the symbols. Outside of
+//! [with_string_table], we serialize the symbols as strings: we do this for
+//! instance to compute the hashes of the declarations, which must not depend
+//! on the rest of the crate.
+//!
+//! Remark: we never remove strings from the table, for the same reasons as in
+//! [crate::hash_cons].
+use serde::ser::SerializeSeq;
+use serde::{Serialize, Serializer};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+use std::sync::Mutex;
+
+lazy_static::lazy_static! {
+    /// The interned strings
+    static ref INTERNER: Mutex<Interner> = Mutex::new(Interner::default());
+}
+
+#[derive(Default)]
+struct Interner {
+    strings: HashSet<&'static str>,
+}
+
+/// An interned string. Two symbols are equal if and only if they point to the
+/// same string of the table: we compare and hash the pointers.
+#[derive(Clone, Copy)]
+pub struct Symbol(&'static str);
+
+impl Symbol {
+    pub fn intern(s: &str) -> Self {
+        let mut interner = INTERNER.lock().unwrap();
+        if let Some(s) = interner.strings.get(s) {
+            return Symbol(s);
+        }
+        let s: &'static str = Box::leak(s.to_string().into_boxed_str());
+        interner.strings.insert(s);
+        Symbol(s)
+    }
+
+    pub fn as_str(self) -> &'static str {
+        self.0
+    }
+}
+
+impl PartialEq for Symbol {
+    fn eq(&self, other: &Symbol) -> bool {
+        std::ptr::eq(self.0, other.0)
+    }
+}
+
+impl Eq for Symbol {}
+
+impl Hash for Symbol {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::ptr::hash(self.0, state)
+    }
+}
+
+impl Deref for Symbol {
+    type Target = str;
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl From<&str> for Symbol {
+    fn from(s: &str) -> Self {
+        Symbol::intern(s)
+    }
+}
+
+impl From<String> for Symbol {
+    fn from(s: String) -> Self {
+        Symbol::intern(&s)
+    }
+}
+
+impl PartialEq<str> for Symbol {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for Symbol {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl PartialEq<String> for Symbol {
+    fn eq(&self, other: &String) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Debug for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+/// The string table of the output we are serializing
+#[derive(Default)]
+struct Table {
+    indices: HashMap<Symbol, usize>,
+    symbols: Vec<Symbol>,
+}
+
+thread_local! {
+    /// The string table of the output we are serializing, if we are inside
+    /// [with_string_table]
+    static TABLE: RefCell<Option<Table>> = RefCell::new(None);
+}
+
+/// Serialize the symbols as indices in a fresh string table while running `f`.
+pub fn with_string_table<T>(f: impl FnOnce() -> T) -> T {
+    with_string_table_from(Vec::new(), f)
+}
+
+/// Same as [with_string_table], but the string table starts with `strings`
+/// (the symbol at position `i` has index `i`). We use this to preserve the
+/// indices of a previous output (see [crate::export]).
+pub fn with_string_table_from<T>(strings: Vec<Symbol>, f: impl FnOnce() -> T) -> T {
+    let mut initial = Table::default();
+    for (index, symbol) in strings.iter().enumerate() {
+        initial.indices.entry(*symbol).or_insert(index);
+    }
+    initial.symbols = strings;
+    let previous = TABLE.with(|table| table.replace(Some(initial)));
+    let res = f();
+    TABLE.with(|table| table.replace(previous));
+    res
+}
+
+impl Serialize for Symbol {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let index = TABLE.with(|table| {
+            let mut table = table.borrow_mut();
+            let table = table.as_mut()?;
+            let next = table.symbols.len();
+            let index = *table.indices.entry(*self).or_insert(next);
+            if index == next {
+                table.symbols.push(*self);
+            }
+            Some(index)
+        });
+        match index {
+            Some(index) => serializer.serialize_u64(index as u64),
+            None => serializer.serialize_str(self.as_str()),
+        }
+    }
+}
+
+/// The strings of the current string table (see [with_string_table]), in the
+/// order of their indices. This must be serialized after all the symbols which
+/// refer to the table: we put it at the end of the serialized structures.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StringTable;
+
+impl Serialize for StringTable {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let symbols =
+            TABLE.with(|table| table.borrow().as_ref().map(|table| table.symbols.clone()));
+        let symbols = symbols.unwrap_or_default();
+        let mut seq = serializer.serialize_seq(Some(symbols.len()))?;
+        for symbol in symbols {
+            seq.serialize_element(symbol.as_str())?;
+        }
+        seq.end()
+    }
+}
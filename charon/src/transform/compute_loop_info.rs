@@ -0,0 +1,75 @@
+//! Compute the information of the loops (see [LoopInfo]): their ids, their
+//! labels, their nesting depths, and the `Break` and `Continue` statements
+//! which target them. We also give to the `Break` and `Continue` statements the
+//! labels of the loops they target, so that the structure of the loops can be
+//! printed unambiguously:
+//! ```text
+//! 'l0: loop {
+//!   'l1: loop {
+//!     if copy b { break 'l0 } else { continue 'l1 }
+//!   }
+//! }
+//! ```
+//! We do this at the end of the passes, which may create, remove or move loops.
+
+use crate::formatter::{Formatter, IntoFormatter};
+use crate::llbc_ast::*;
+use crate::translate_ctx::TransformCtx;
+
+/// Compute the information of the loops in a block. `loops` gives the
+/// information of the loops we are in, from the outermost to the innermost.
+fn compute_block(b: &mut Block, loop_ids: &mut LoopId::Generator, loops: &mut Vec<LoopInfo>) {
+    for st in &mut b.statements {
+        match &mut st.content {
+            RawStatement::Break(i, label) => {
+                let jump = LoopJump {
+                    meta: st.meta,
+                    distance: *i,
+                };
+                let j = loops.len() - 1 - *i;
+                *label = Some(loops[j].label.clone());
+                loops[j].breaks.push(jump);
+            }
+            RawStatement::Continue(i, label) => {
+                let jump = LoopJump {
+                    meta: st.meta,
+                    distance: *i,
+                };
+                let j = loops.len() - 1 - *i;
+                *label = Some(loops[j].label.clone());
+                loops[j].continues.push(jump);
+            }
+            RawStatement::Switch(switch) => {
+                for block in switch.get_targets_mut() {
+                    compute_block(block, loop_ids, loops);
+                }
+            }
+            RawStatement::Loop(body, info) => {
+                let id = loop_ids.fresh_id();
+                loops.push(LoopInfo {
+                    id,
+                    label: format!("l{}", id.index()),
+                    depth: loops.len(),
+                    breaks: Vec::new(),
+                    continues: Vec::new(),
+                });
+                compute_block(body, loop_ids, loops);
+                *info = loops.pop().unwrap();
+            }
+            _ => (),
+        }
+    }
+}
+
+pub fn transform(ctx: &mut TransformCtx, funs: &mut FunDecls, globals: &mut GlobalDecls) {
+    ctx.iter_bodies(funs, globals, |ctx, name, b| {
+        let fmt_ctx = ctx.into_fmt();
+        trace!(
+            "# About to compute the information of the loops in decl: {}:\n{}",
+            name.fmt_with_ctx(&fmt_ctx),
+            fmt_ctx.format_object(&*b)
+        );
+
+        compute_block(&mut b.body, &mut LoopId::Generator::new(), &mut Vec::new());
+    })
+}
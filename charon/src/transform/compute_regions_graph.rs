@@ -0,0 +1,280 @@
+//! # Micro-pass: compute the outlives relation between the generic parameters
+//! of every declaration, including the implied bounds (see [RegionGraph]).
+//!
+//! Remark: this pass must be applied to the function signatures *after* the
+//! [crate::transform::update_closure_signatures] pass, which introduces new
+//! regions in the signatures of the closures (we apply it right after the
+//! translation).
+use crate::translate_ctx::TranslatedCrate;
+use crate::types::*;
+
+/// The outlives relation, as an adjacency matrix. The nodes are the region
+/// parameters of the declaration, followed by `'static`.
+struct Graph<'a> {
+    /// The graphs of the type declarations, which we need to compute the
+    /// implied bounds of the ADTs.
+    type_graphs: &'a TypeDeclId::Map<RegionGraph>,
+    num_regions: usize,
+    /// `regions[r0][r1]` is true if `r0` outlives `r1`
+    regions: Vec<Vec<bool>>,
+    /// `types[t][r]` is true if the type parameter `t` outlives `r`
+    types: Vec<Vec<bool>>,
+}
+
+impl<'a> Graph<'a> {
+    fn new(type_graphs: &'a TypeDeclId::Map<RegionGraph>, generics: &GenericParams) -> Self {
+        let num_regions = generics.regions.len();
+        let mut regions = vec![vec![false; num_regions + 1]; num_regions + 1];
+        // `'static` outlives all the regions
+        regions[num_regions] = vec![true; num_regions + 1];
+        let types = vec![vec![false; num_regions + 1]; generics.types.len()];
+        Graph {
+            type_graphs,
+            num_regions,
+            regions,
+            types,
+        }
+    }
+
+    /// Return the node of a region, if it is `'static` or a region parameter
+    /// of the declaration. [depth] is the number of region groups (introduced
+    /// by arrow types) we dived into.
+    fn node(&self, r: &Region, depth: usize) -> Option<usize> {
+        match r {
+            Region::Static => Some(self.num_regions),
+            Region::BVar(db, rid) if db.index == depth => Some(rid.index()),
+            Region::BVar(..) | Region::Body(_) | Region::Erased | Region::Unknown => None,
+        }
+    }
+
+    /// Register the fact that the type outlives the region node [r]
+    fn ty_outlives(&mut self, ty: &Ty, r: usize, depth: usize) {
+        match ty.kind() {
+            TyKind::TypeVar(v) => self.types[v.index()][r] = true,
+            TyKind::Ref(r1, ty, _) => {
+                if let Some(r1) = self.node(r1, depth) {
+                    self.regions[r1][r] = true;
+                }
+                self.ty_outlives(ty, r, depth)
+            }
+            TyKind::RawPtr(ty, _) => self.ty_outlives(ty, r, depth),
+            TyKind::Adt(_, generics) | TyKind::FnDef(_, generics) => {
+                for r1 in &generics.regions {
+                    if let Some(r1) = self.node(r1, depth) {
+                        self.regions[r1][r] = true;
+                    }
+                }
+                for ty in &generics.types {
+                    self.ty_outlives(ty, r, depth)
+                }
+            }
+            TyKind::Arrow(_, inputs, output) => {
+                for ty in inputs.iter().chain(std::iter::once(output)) {
+                    self.ty_outlives(ty, r, depth + 1)
+                }
+            }
+            // We can't decompose the projections: we ignore them
+            TyKind::TraitType(..) | TyKind::Literal(_) | TyKind::Never => (),
+        }
+    }
+
+    /// Register the bounds implied by the well-formedness of a type
+    fn implied_bounds(&mut self, ty: &Ty, depth: usize) {
+        match ty.kind() {
+            TyKind::Ref(r, ty, _) => {
+                if let Some(r) = self.node(r, depth) {
+                    self.ty_outlives(ty, r, depth);
+                }
+                self.implied_bounds(ty, depth)
+            }
+            TyKind::RawPtr(ty, _) => self.implied_bounds(ty, depth),
+            TyKind::Adt(id, generics) => {
+                // Instantiate the graph of the type declaration
+                let type_graph = match id {
+                    TypeId::Adt(id) => self.type_graphs.get(*id),
+                    TypeId::Tuple | TypeId::Assumed(_) => None,
+                };
+                if let Some(type_graph) = type_graph {
+                    let subst = |g: &Self, r: &Region| match r {
+                        Region::Static => Some(g.num_regions),
+                        Region::BVar(_, rid) => generics
+                            .regions
+                            .get(rid.index())
+                            .and_then(|r| g.node(r, depth)),
+                        Region::Body(_) | Region::Erased | Region::Unknown => None,
+                    };
+                    for (rid, outlived) in type_graph.regions.iter_indexed_values() {
+                        let Some(r0) = generics.regions.get(rid.index()) else { continue };
+                        let Some(r0) = self.node(r0, depth) else { continue };
+                        for r1 in outlived {
+                            if let Some(r1) = subst(self, r1) {
+                                self.regions[r0][r1] = true;
+                            }
+                        }
+                    }
+                    for (tid, outlived) in type_graph.types.iter_indexed_values() {
+                        let Some(ty) = generics.types.get(tid.index()) else { continue };
+                        for r1 in outlived {
+                            if let Some(r1) = subst(self, r1) {
+                                self.ty_outlives(ty, r1, depth);
+                            }
+                        }
+                    }
+                }
+                for ty in &generics.types {
+                    self.implied_bounds(ty, depth)
+                }
+            }
+            TyKind::Arrow(_, inputs, output) => {
+                for ty in inputs.iter().chain(std::iter::once(output)) {
+                    self.implied_bounds(ty, depth + 1)
+                }
+            }
+            TyKind::FnDef(_, generics) => {
+                for ty in &generics.types {
+                    self.implied_bounds(ty, depth)
+                }
+            }
+            TyKind::TypeVar(_) | TyKind::TraitType(..) | TyKind::Literal(_) | TyKind::Never => (),
+        }
+    }
+
+    fn predicates(&mut self, preds: &Predicates) {
+        for OutlivesPred(r0, r1) in &preds.regions_outlive {
+            if let (Some(r0), Some(r1)) = (self.node(r0, 0), self.node(r1, 0)) {
+                self.regions[r0][r1] = true;
+            }
+        }
+        for OutlivesPred(ty, r) in &preds.types_outlive {
+            if let Some(r) = self.node(r, 0) {
+                self.ty_outlives(ty, r, 0);
+            }
+        }
+    }
+
+    /// Compute the transitive closure, and convert it to a [RegionGraph]
+    fn close(mut self) -> RegionGraph {
+        // Warshall's algorithm
+        for k in 0..(self.num_regions + 1) {
+            let outlived_by_k = self.regions[k].clone();
+            for outlived in self.regions.iter_mut().filter(|outlived| outlived[k]) {
+                union(outlived, &outlived_by_k);
+            }
+        }
+        // The relation between the regions is closed: we only need one pass
+        // for the types
+        for outlived in self.types.iter_mut() {
+            let direct = outlived.clone();
+            for (outlived_by_k, _) in self.regions.iter().zip(direct).filter(|(_, b)| *b) {
+                union(outlived, outlived_by_k);
+            }
+        }
+
+        // `'static` comes first
+        let num_regions = self.num_regions;
+        let to_regions = |outlived: &Vec<bool>, skip: Option<usize>| -> Vec<Region> {
+            let is_static = outlived[num_regions];
+            let params = (0..num_regions)
+                .filter(|i| outlived[*i] && Some(*i) != skip)
+                .map(|i| Region::BVar(DeBruijnId::new(0), RegionId::Id::new(i)));
+            is_static
+                .then_some(Region::Static)
+                .into_iter()
+                .chain(params)
+                .collect()
+        };
+        RegionGraph {
+            regions: self.regions[0..num_regions]
+                .iter()
+                .enumerate()
+                .map(|(i, outlived)| to_regions(outlived, Some(i)))
+                .collect(),
+            types: self.types.iter().map(|t| to_regions(t, None)).collect(),
+        }
+    }
+}
+
+fn union(x: &mut [bool], y: &[bool]) {
+    for (x, y) in x.iter_mut().zip(y.iter()) {
+        *x |= *y;
+    }
+}
+
+fn compute_graph<'a>(
+    type_graphs: &TypeDeclId::Map<RegionGraph>,
+    generics: &GenericParams,
+    preds: &Predicates,
+    tys: impl Iterator<Item = &'a Ty>,
+) -> RegionGraph {
+    let mut graph = Graph::new(type_graphs, generics);
+    graph.predicates(preds);
+    for ty in tys {
+        graph.implied_bounds(ty, 0);
+    }
+    graph.close()
+}
+
+fn type_decl_fields(decl: &TypeDecl) -> Vec<&Ty> {
+    match &decl.kind {
+        TypeDeclKind::Struct(fields) => fields.iter().map(|f| &f.ty).collect(),
+        TypeDeclKind::Enum(variants, ..) => variants
+            .iter()
+            .flat_map(|v| v.fields.iter().map(|f| &f.ty))
+            .collect(),
+        TypeDeclKind::Opaque | TypeDeclKind::ImplTrait { .. } | TypeDeclKind::Error(_) => {
+            Vec::new()
+        }
+    }
+}
+
+fn transform_fun_sig(type_graphs: &TypeDeclId::Map<RegionGraph>, sig: &mut FunSig) {
+    let tys = sig.inputs.iter().chain(std::iter::once(&sig.output));
+    sig.preds.regions_graph = compute_graph(type_graphs, &sig.generics, &sig.preds, tys);
+}
+
+pub fn transform(ctx: &mut TranslatedCrate) {
+    // Compute the graphs of the type declarations. Because the types may be
+    // (mutually) recursive, we iterate until we reach a fixed point (this
+    // terminates because the graphs can only grow).
+    let mut type_graphs = TypeDeclId::Map::new();
+    loop {
+        let mut changed = false;
+        for decl in ctx.type_decls.iter() {
+            let graph = compute_graph(
+                &type_graphs,
+                &decl.generics,
+                &decl.preds,
+                type_decl_fields(decl).into_iter(),
+            );
+            if type_graphs.get(decl.def_id) != Some(&graph) {
+                type_graphs.insert(decl.def_id, graph);
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+    for decl in ctx.type_decls.iter_mut() {
+        decl.preds.regions_graph = type_graphs.get(decl.def_id).unwrap().clone();
+    }
+
+    // The trait declarations and implementations
+    for decl in ctx.trait_decls.iter_mut() {
+        decl.preds.regions_graph = compute_graph(
+            &type_graphs,
+            &decl.generics,
+            &decl.preds,
+            std::iter::empty(),
+        );
+    }
+    for decl in ctx.trait_impls.iter_mut() {
+        let tys = decl.impl_trait.generics.types.iter();
+        decl.preds.regions_graph = compute_graph(&type_graphs, &decl.generics, &decl.preds, tys);
+    }
+
+    // The functions
+    for decl in ctx.fun_decls.iter_mut() {
+        transform_fun_sig(&type_graphs, &mut decl.signature);
+    }
+}
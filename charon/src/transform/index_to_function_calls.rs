@@ -5,7 +5,7 @@ use crate::formatter::{Formatter, IntoFormatter};
 use crate::gast::{Call, GenericArgs, Var};
 use crate::llbc_ast::*;
 use crate::meta::Meta;
-use crate::translate_ctx::TransCtx;
+use crate::translate_ctx::TransformCtx;
 use crate::types::*;
 use crate::values::VarId;
 use std::mem::replace;
@@ -27,7 +27,12 @@ struct Transform<'a> {
 
 impl<'a> Transform<'a> {
     fn fresh_var(&mut self, name: Option<String>, ty: Ty) -> VarId::Id {
-        self.locals.push_with(|index| Var { index, name, ty })
+        self.locals.push_with(|index| Var {
+            index,
+            name,
+            ty,
+            debug_info: Vec::new(),
+        })
     }
 
     fn visit_transform_place(&mut self, mut_access: bool, p: &mut Place) {
@@ -70,7 +75,7 @@ impl<'a> Transform<'a> {
 
                 // Push the statement:
                 //`tmp0 = & proj`
-                let buf_borrow_ty = Ty::Ref(Region::Erased, Box::new(buf_ty), ref_kind);
+                let buf_borrow_ty = TyKind::Ref(Region::Erased, buf_ty, ref_kind).into_ty();
                 let buf_borrow_var = self.fresh_var(Option::None, buf_borrow_ty);
                 let borrow_st = RawStatement::Assign(
                     Place::new(buf_borrow_var),
@@ -90,7 +95,8 @@ impl<'a> Transform<'a> {
 
                 // Push the statement:
                 // `tmp1 = Array{Mut,Shared}Index(move tmp0, copy i)`
-                let elem_borrow_ty = Ty::Ref(Region::Erased, Box::new(elem_ty.clone()), ref_kind);
+                let elem_borrow_ty =
+                    TyKind::Ref(Region::Erased, elem_ty.clone(), ref_kind).into_ty();
                 let elem_borrow_var = self.fresh_var(Option::None, elem_borrow_ty);
                 let arg_buf = Operand::Move(Place::new(buf_borrow_var));
                 let arg_index = Operand::Copy(Place::new(index_var_id));
@@ -105,6 +111,10 @@ impl<'a> Transform<'a> {
                     func,
                     args: vec![arg_buf, arg_index],
                     dest: index_dest,
+                    receiver_adjustments: Vec::new(),
+                    resolution: None,
+                    is_tail: false,
+                    is_ghost: false,
                 };
                 let index_st = Statement {
                     content: RawStatement::Call(index_call),
@@ -144,13 +154,15 @@ impl<'a> MutExprVisitor for Transform<'a> {
             Operand::Move(p) => self.visit_transform_place(true, p),
             Operand::Copy(p) => self.visit_transform_place(false, p),
             Operand::Const(..) => (),
+            Operand::Expr(..) => self.default_visit_operand(op),
         }
     }
 
     fn visit_rvalue(&mut self, rv: &mut Rvalue) {
         use Rvalue::*;
         match rv {
-            Use(_) | UnaryOp(..) | BinaryOp(..) | Aggregate(..) | Global(..) | Repeat(..) => {
+            Use(_) | UnaryOp(..) | BinaryOp(..) | Aggregate(..) | Global(..) | Repeat(..)
+            | NullaryOp(..) => {
                 // We don't access places here, only operands
                 self.default_visit_rvalue(rv)
             }
@@ -197,14 +209,16 @@ impl<'a> MutAstVisitor for Transform<'a> {
         use RawStatement::*;
         // The match is explicit on purpose: we want to make sure we intercept changes
         match st {
-            Sequence(..) => {
-                // Do nothing: we don't want to dive
+            Loop(..) => {
+                // Do nothing: we don't want to dive (the statements of the
+                // loop body are transformed separately)
             }
             FakeRead(p) => {
                 self.visit_transform_place(false, p);
             }
-            Assign(..) | SetDiscriminant(..) | Drop(..) | Assert(..) | Call(..) | Panic
-            | Return | Break(..) | Continue(..) | Nop | Switch(..) | Loop(..) => {
+            Assign(..) | SetDiscriminant(..) | Drop(..) | StorageLive(..) | StorageDead(..)
+            | Assert(..) | Call(..) | MemCopy(..) | WriteBytes(..) | Intrinsic(..) | Panic
+            | Return | Break(..) | Continue(..) | Nop | Switch(..) => {
                 // Explore
                 self.default_visit_raw_statement(st)
             }
@@ -293,7 +307,7 @@ fn transform_st(locals: &mut VarId::Vector<Var>, s: &mut Statement) -> Option<Ve
 ///   tmp1 : &mut T = ArrayIndexMut(move y, i)
 ///   *tmp1 = x
 /// ```
-pub fn transform(ctx: &mut TransCtx, funs: &mut FunDecls, globals: &mut GlobalDecls) {
+pub fn transform(ctx: &mut TransformCtx, funs: &mut FunDecls, globals: &mut GlobalDecls) {
     ctx.iter_bodies(funs, globals, |ctx, name, b| {
         let ctx = ctx.into_fmt();
         trace!(
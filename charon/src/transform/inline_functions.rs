@@ -0,0 +1,347 @@
+//! Inline the calls to the small functions (this pass is optional, see the
+//! `--inline-threshold` option). For instance, if `g` is `fn g(x: u32) -> u32
+//! { x + 1 }`:
+//! ```text
+//! y := g(move a);
+//! ```
+//! becomes (where `x'` and `ret'` are the locals of `g`, renumbered):
+//! ```text
+//! x' := move a;
+//! ret' := copy x' + const 1;
+//! y := move ret';
+//! ```
+//!
+//! We inline the calls to the functions which are neither recursive nor trait
+//! methods, and whose bodies contain at most `--inline-threshold` statements or
+//! which are marked `#[inline(always)]` (we never inline the functions marked
+//! `#[inline(never)]`). We substitute the generics of the callee with the
+//! arguments of the call, and append the locals and the promoted constants of
+//! the callee to those of the caller. If the callee returns before the end of its body, we wrap its
+//! body in a loop, and replace the returns with breaks out of this loop.
+//!
+//! We only inline one level of calls: we use the bodies of the callees as they
+//! are before the pass. The passes which follow (like
+//! [crate::transform::remove_redundant_copies]) then clean up the inlined
+//! bodies.
+
+use crate::expressions::{MutExprVisitor, SharedExprVisitor};
+use crate::formatter::{Formatter, IntoFormatter};
+use crate::llbc_ast::*;
+use crate::meta::{InlineAttr, Meta};
+use crate::reorder_decls::{DeclarationGroup, GDeclarationGroup};
+use crate::translate_ctx::TransformCtx;
+use crate::types::*;
+use crate::ullbc_to_llbc::is_terminal;
+use crate::values::*;
+use std::collections::{HashMap, HashSet};
+
+/// A function we inline
+struct Callee {
+    generics: GenericParams,
+    body: ExprBody,
+    /// The number of returns in the body
+    returns: usize,
+}
+
+/// Count the statements (but the no-ops) and the returns of a body
+#[derive(Default)]
+struct BodySize {
+    statements: usize,
+    returns: usize,
+}
+
+impl SharedTypeVisitor for BodySize {}
+impl SharedExprVisitor for BodySize {}
+
+impl SharedAstVisitor for BodySize {
+    fn spawn(&mut self, visitor: &mut dyn FnMut(&mut Self)) {
+        visitor(self)
+    }
+
+    fn merge(&mut self) {}
+
+    fn visit_statement(&mut self, st: &Statement) {
+        if !st.content.is_nop() {
+            self.statements += 1;
+        }
+        self.default_visit_statement(st)
+    }
+
+    fn visit_return(&mut self) {
+        self.returns += 1;
+    }
+}
+
+/// Compute the functions we inline
+fn compute_callees(
+    ctx: &TransformCtx,
+    threshold: usize,
+    funs: &FunDecls,
+) -> HashMap<FunDeclId::Id, Callee> {
+    let recursive: HashSet<FunDeclId::Id> = ctx
+        .translated
+        .ordered_decls
+        .iter()
+        .flatten()
+        .filter_map(|group| match group {
+            DeclarationGroup::Fun(GDeclarationGroup::Rec(ids)) => Some(ids.iter().copied()),
+            _ => None,
+        })
+        .flatten()
+        .collect();
+    funs.iter()
+        .filter(|f| f.kind == ItemKind::Regular && !recursive.contains(&f.def_id))
+        .filter_map(|f| {
+            let body = f.body.as_ref()?;
+            let mut size = BodySize::default();
+            size.visit_block(&body.body);
+            let inline = match f.item_meta.inline {
+                Some(InlineAttr::Always) => true,
+                Some(InlineAttr::Never) => false,
+                Some(InlineAttr::Hint) | None => size.statements <= threshold,
+            };
+            inline.then(|| {
+                let callee = Callee {
+                    generics: f.signature.generics.clone(),
+                    body: body.clone(),
+                    returns: size.returns,
+                };
+                (f.def_id, callee)
+            })
+        })
+        .collect()
+}
+
+/// Substitute the generics of a callee with the arguments of the call, and
+/// its locals with the locals we append to the locals of the caller.
+struct Subst<'a> {
+    generics: &'a GenericArgs,
+    /// The trait references given for the trait clauses (note that the clause
+    /// ids are not necessarily contiguous, see [GenericParams::trait_clauses]).
+    clauses: HashMap<TraitClauseId::Id, &'a TraitRef>,
+    /// The index of the first local of the callee in the locals of the caller
+    offset: usize,
+    /// The index of the first promoted constant of the callee in the promoted
+    /// constants of the caller
+    promoted_offset: usize,
+    /// If `true`, we replace the returns with breaks out of the loop we wrap
+    /// the body in.
+    replace_returns: bool,
+    /// The number of loops we are in (inside the body of the callee)
+    loops: usize,
+}
+
+impl<'a> Subst<'a> {
+    fn local(&self, vid: VarId::Id) -> VarId::Id {
+        VarId::Id::new(self.offset + vid.index())
+    }
+}
+
+impl<'a> MutTypeVisitor for Subst<'a> {
+    fn visit_ty(&mut self, ty: &mut Ty) {
+        if let TyKind::TypeVar(id) = ty.kind()
+            && let Some(arg) = self.generics.types.get(id.index())
+        {
+            *ty = arg.clone()
+        } else {
+            self.default_visit_ty(ty)
+        }
+    }
+
+    fn visit_const_generic(&mut self, cg: &mut ConstGeneric) {
+        match cg {
+            ConstGeneric::Var(id) => {
+                if let Some(arg) = self.generics.const_generics.get(id.index()) {
+                    *cg = arg.clone()
+                }
+            }
+            ConstGeneric::TraitConst(tr, _) => self.visit_trait_ref(tr),
+            ConstGeneric::Global(_) | ConstGeneric::Value(_) => (),
+        }
+    }
+
+    fn visit_trait_ref(&mut self, tr: &mut TraitRef) {
+        if let TraitInstanceId::Clause(id) = &tr.trait_id
+            && let Some(arg) = self.clauses.get(id)
+        {
+            *tr = (*arg).clone()
+        } else {
+            tr.with_data_mut(|tr| self.visit_trait_ref_data(tr))
+        }
+    }
+
+    fn visit_trait_instance_id(&mut self, id: &mut TraitInstanceId) {
+        // The clauses which appear in the parent clauses and the item clauses
+        if let TraitInstanceId::Clause(clause_id) = id
+            && let Some(arg) = self.clauses.get(clause_id)
+        {
+            *id = arg.trait_id.clone()
+        } else {
+            self.default_visit_trait_instance_id(id)
+        }
+    }
+}
+
+impl<'a> MutExprVisitor for Subst<'a> {
+    fn visit_var_id(&mut self, vid: &mut VarId::Id) {
+        *vid = self.local(*vid)
+    }
+
+    fn visit_raw_constant_expr(&mut self, expr: &mut RawConstantExpr) {
+        if let RawConstantExpr::Promoted(id, _) = expr {
+            *id = PromotedId::Id::new(self.promoted_offset + id.index());
+        }
+        if let RawConstantExpr::Var(id) = expr
+            && let Some(arg) = self.generics.const_generics.get(id.index())
+        {
+            *expr = match arg.clone() {
+                ConstGeneric::Global(id) => RawConstantExpr::Global(id, GenericArgs::empty()),
+                ConstGeneric::TraitConst(tr, name) => RawConstantExpr::TraitConst(tr, name),
+                ConstGeneric::Var(id) => RawConstantExpr::Var(id),
+                ConstGeneric::Value(lit) => RawConstantExpr::Literal(lit),
+            }
+        } else {
+            self.default_visit_raw_constant_expr(expr)
+        }
+    }
+}
+
+impl<'a> MutAstVisitor for Subst<'a> {
+    fn spawn(&mut self, visitor: &mut dyn FnMut(&mut Self)) {
+        visitor(self)
+    }
+
+    fn merge(&mut self) {}
+
+    fn visit_raw_statement(&mut self, st: &mut RawStatement) {
+        match st {
+            RawStatement::Return if self.replace_returns => {
+                *st = RawStatement::Break(self.loops, None)
+            }
+            _ => self.default_visit_raw_statement(st),
+        }
+    }
+
+    fn visit_loop(&mut self, lp: &mut Block) {
+        self.loops += 1;
+        self.visit_block(lp);
+        self.loops -= 1;
+    }
+}
+
+/// Inline a call: append the locals and the promoted constants of the callee to
+/// those of the caller, and return the statements which evaluate the call,
+/// together with the local which then contains its result.
+fn inline_call(
+    locals: &mut VarId::Vector<Var>,
+    promoted: &mut PromotedId::Vector<GlobalDeclId::Id>,
+    callee: &Callee,
+    generics: &GenericArgs,
+    args: &[Operand],
+    meta: Meta,
+) -> (Vec<Statement>, VarId::Id) {
+    let mut body = callee.body.body.clone();
+    // If the only return is the last statement of the body, we simply remove
+    // it: otherwise we wrap the body in a loop
+    let returns_at_end = callee.returns == 1
+        && body
+            .statements
+            .last()
+            .is_some_and(|st| st.content.is_return());
+    if returns_at_end {
+        body.statements.pop();
+    }
+    let mut subst = Subst {
+        generics,
+        clauses: callee
+            .generics
+            .trait_clauses
+            .iter()
+            .map(|clause| clause.clause_id)
+            .zip(generics.trait_refs.iter())
+            .collect(),
+        offset: locals.len(),
+        promoted_offset: promoted.len(),
+        replace_returns: callee.returns > 0 && !returns_at_end,
+        loops: 0,
+    };
+
+    for var in callee.body.locals.iter() {
+        let mut ty = var.ty.clone();
+        subst.visit_ty(&mut ty);
+        locals.push_with(|index| Var {
+            index,
+            name: var.name.clone(),
+            ty,
+            debug_info: var.debug_info.clone(),
+        });
+    }
+
+    for id in callee.body.promoted.iter() {
+        promoted.push(*id);
+    }
+
+    // Initialize the inputs of the callee with the arguments
+    let mut statements: Vec<Statement> = args
+        .iter()
+        .enumerate()
+        .map(|(i, arg)| {
+            let input = Place::new(subst.local(VarId::Id::new(i + 1)));
+            Statement::new(meta, RawStatement::Assign(input, Rvalue::Use(arg.clone())))
+        })
+        .collect();
+
+    subst.visit_block(&mut body);
+    if subst.replace_returns {
+        // We must exit the loop at the end of the body
+        if !body.statements.iter().any(is_terminal) {
+            body.statements
+                .push(Statement::new(meta, RawStatement::Break(0, None)));
+        }
+        statements.push(Statement::new(
+            meta,
+            RawStatement::Loop(body, LoopInfo::empty()),
+        ));
+    } else {
+        statements.extend(body.statements);
+    }
+    (statements, subst.local(VarId::ZERO))
+}
+
+pub fn transform(
+    ctx: &mut TransformCtx,
+    threshold: usize,
+    funs: &mut FunDecls,
+    globals: &mut GlobalDecls,
+) {
+    let callees = compute_callees(ctx, threshold, funs);
+    ctx.iter_bodies(funs, globals, |ctx, name, b| {
+        let fmt_ctx = ctx.into_fmt();
+        trace!(
+            "# About to inline the small functions in decl: {}:\n{}",
+            name.fmt_with_ctx(&fmt_ctx),
+            fmt_ctx.format_object(&*b)
+        );
+
+        let locals = &mut b.locals;
+        let promoted = &mut b.promoted;
+        b.body.transform(&mut |st| {
+            let RawStatement::Call(call) = &st.content else {
+                return None;
+            };
+            let FnOperand::Regular(FnPtr {
+                func: FunIdOrTraitMethodRef::Fun(FunId::Regular(id)),
+                generics,
+            }) = &call.func
+            else {
+                return None;
+            };
+            let callee = callees.get(id)?;
+            let (statements, ret) =
+                inline_call(locals, promoted, callee, generics, &call.args, st.meta);
+            let dest = call.dest.clone();
+            st.content = RawStatement::Assign(dest, Rvalue::Use(Operand::Move(Place::new(ret))));
+            Some(statements)
+        });
+    })
+}
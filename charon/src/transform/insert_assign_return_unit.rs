@@ -10,7 +10,7 @@ use crate::llbc_ast::{
     ExprBody, FunDecl, FunDecls, GlobalDecl, GlobalDecls, RawStatement, Statement,
 };
 use crate::names::Name;
-use crate::translate_ctx::TransCtx;
+use crate::translate_ctx::TransformCtx;
 use crate::types::*;
 use crate::values::*;
 
@@ -25,13 +25,13 @@ fn transform_st(st: &mut Statement) -> Option<Vec<Statement>> {
             Vec::new(),
         );
         let assign_st = Statement::new(st.meta, RawStatement::Assign(ret_place, unit_value));
-        let ret_st = Statement::new(st.meta, RawStatement::Return);
-        st.content = RawStatement::Sequence(Box::new(assign_st), Box::new(ret_st));
-    };
-    None
+        Some(vec![assign_st])
+    } else {
+        None
+    }
 }
 
-fn transform_body(ctx: &TransCtx, name: &Name, body: &mut Option<ExprBody>) {
+fn transform_body(ctx: &TransformCtx, name: &Name, body: &mut Option<ExprBody>) {
     let ctx = ctx.into_fmt();
     if let Some(b) = body.as_mut() {
         trace!(
@@ -43,14 +43,14 @@ fn transform_body(ctx: &TransCtx, name: &Name, body: &mut Option<ExprBody>) {
     }
 }
 
-fn transform_function(ctx: &mut TransCtx, def: &mut FunDecl) {
+fn transform_function(ctx: &mut TransformCtx, def: &mut FunDecl) {
     if def.signature.output.is_unit() {
         ctx.with_def_id(def.rust_id, |ctx| {
             transform_body(ctx, &def.name, &mut def.body)
         });
     }
 }
-fn transform_global(ctx: &mut TransCtx, def: &mut GlobalDecl) {
+fn transform_global(ctx: &mut TransformCtx, def: &mut GlobalDecl) {
     if def.ty.is_unit() {
         ctx.with_def_id(def.rust_id, |ctx| {
             transform_body(ctx, &def.name, &mut def.body)
@@ -58,7 +58,7 @@ fn transform_global(ctx: &mut TransCtx, def: &mut GlobalDecl) {
     }
 }
 
-pub fn transform(ctx: &mut TransCtx, funs: &mut FunDecls, globals: &mut GlobalDecls) {
+pub fn transform(ctx: &mut TransformCtx, funs: &mut FunDecls, globals: &mut GlobalDecls) {
     funs.iter_mut().for_each(|d| transform_function(ctx, d));
     globals.iter_mut().for_each(|d| transform_global(ctx, d));
 }
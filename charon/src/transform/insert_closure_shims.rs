@@ -0,0 +1,230 @@
+//! # Micro-pass: generate the `call_mut` and `call_once` methods of the closures.
+//!
+//! A closure of kind `Fn` also implements `FnMut` and `FnOnce`, and a closure
+//! of kind `FnMut` also implements `FnOnce`. We only translate the body of the
+//! closure itself, which takes its state by reference for the `Fn` and `FnMut`
+//! closures: for the other methods, rustc generates shims which borrow the
+//! state and call the closure. We generate the corresponding functions, and
+//! refer to them in the closure information (see [ClosureInfo::call_mut_shim]
+//! and [ClosureInfo::call_once_shim]), so that the consumers can resolve all
+//! the method calls on a [TraitInstanceId::Closure].
+//!
+//! We work on the ULLBC, before reordering the declarations: the shims are
+//! regular functions, which call their closures. Like the closures (see
+//! [super::update_closure_signatures], which must be applied before), they take
+//! the state explicitly.
+use crate::expressions::*;
+use crate::meta::Meta;
+use crate::names::{Disambiguator, PathElem};
+use crate::reorder_decls::AnyTransId;
+use crate::translate_ctx::TransCtx;
+use crate::types::*;
+use crate::ullbc_ast::*;
+
+/// The trait method implemented by a shim.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ShimKind {
+    /// `FnMut::call_mut`, for the `Fn` closures: takes the state by mutable
+    /// reference.
+    CallMut,
+    /// `FnOnce::call_once`, for the `Fn` and `FnMut` closures: takes the state
+    /// by value.
+    CallOnce,
+}
+
+impl ShimKind {
+    fn method_name(self) -> &'static str {
+        match self {
+            ShimKind::CallMut => "call_mut",
+            ShimKind::CallOnce => "call_once",
+        }
+    }
+}
+
+fn mk_block(meta: Meta, statements: Vec<Statement>, content: RawTerminator) -> BlockData {
+    BlockData {
+        statements,
+        terminator: Terminator { meta, content },
+    }
+}
+
+/// Generate a shim for a closure of kind `Fn` or `FnMut`.
+fn make_shim(
+    closure: &FunDecl,
+    info: &ClosureInfo,
+    def_id: FunDeclId::Id,
+    kind: ShimKind,
+) -> FunDecl {
+    let meta = closure.item_meta.meta;
+
+    // The signature: the state, then the inputs of the closure. The closure
+    // takes its state by reference (see [super::update_closure_signatures]):
+    // we update the kind of this reference.
+    let mut signature = closure.signature.clone();
+    let TyKind::Ref(region, state_tuple_ty, _) = signature.inputs[0].kind().clone() else {
+        unreachable!()
+    };
+    signature.inputs[0] = match kind {
+        ShimKind::CallMut => TyKind::Ref(region, state_tuple_ty.clone(), RefKind::Mut).into_ty(),
+        ShimKind::CallOnce => {
+            // The region of the reference is the last region parameter of the
+            // closure: we don't need it anymore
+            signature.generics.regions.pop();
+            state_tuple_ty.clone()
+        }
+    };
+    signature.is_closure = false;
+    signature.closure_info = None;
+
+    // The locals: the return value, the state, the inputs, and the borrow of
+    // the state we give to the closure
+    let mut locals = VarId::Vector::new();
+    let dest = locals.push_with(|index| Var {
+        index,
+        name: None,
+        ty: signature.output.clone(),
+        debug_info: Vec::new(),
+    });
+    let state = locals.push_with(|index| Var {
+        index,
+        name: Some("state".to_string()),
+        ty: signature.inputs[0].clone(),
+        debug_info: Vec::new(),
+    });
+    let inputs: Vec<VarId::Id> = closure.signature.inputs[1..]
+        .iter()
+        .map(|ty| {
+            locals.push_with(|index| Var {
+                index,
+                name: None,
+                ty: ty.clone(),
+                debug_info: Vec::new(),
+            })
+        })
+        .collect();
+    let (borrow_kind, ref_kind) = match info.kind {
+        ClosureKind::Fn => (BorrowKind::Shared, RefKind::Shared),
+        ClosureKind::FnMut => (BorrowKind::Mut, RefKind::Mut),
+        ClosureKind::FnOnce => unreachable!(),
+    };
+    let state_borrow = locals.push_with(|index| Var {
+        index,
+        name: None,
+        ty: TyKind::Ref(Region::Erased, state_tuple_ty, ref_kind).into_ty(),
+        debug_info: Vec::new(),
+    });
+
+    // The body:
+    // ```text
+    // state_borrow = &state; // or `&*state` for the `call_mut` shims
+    // dest = closure(move state_borrow, move inputs...);
+    // drop(state); // only for the `call_once` shims
+    // return;
+    // ```
+    let borrowed = Place {
+        var_id: state,
+        projection: match kind {
+            ShimKind::CallMut => vec![ProjectionElem::Deref],
+            ShimKind::CallOnce => Vec::new(),
+        },
+    };
+    let borrow = Statement {
+        meta,
+        content: RawStatement::Assign(Place::new(state_borrow), Rvalue::Ref(borrowed, borrow_kind)),
+    };
+    let call = Call {
+        func: FnOperand::Regular(FnPtr {
+            func: FunIdOrTraitMethodRef::Fun(FunId::Regular(closure.def_id)),
+            generics: closure.signature.generics.identity_args(),
+        }),
+        args: std::iter::once(state_borrow)
+            .chain(inputs)
+            .map(|var_id| Operand::Move(Place::new(var_id)))
+            .collect(),
+        dest: Place::new(dest),
+        receiver_adjustments: Vec::new(),
+        resolution: None,
+        is_tail: false,
+        is_ghost: false,
+    };
+    let mut blocks = BlockId::Vector::new();
+    let target = BlockId::Id::new(1);
+    let call = RawTerminator::Call { call, target };
+    blocks.push(mk_block(meta, vec![borrow], call));
+    if kind == ShimKind::CallOnce {
+        let target = BlockId::Id::new(2);
+        let drop = RawTerminator::Drop {
+            place: Place::new(state),
+            target,
+        };
+        blocks.push(mk_block(meta, Vec::new(), drop));
+    }
+    blocks.push(mk_block(meta, Vec::new(), RawTerminator::Return));
+
+    let mut name = closure.name.clone();
+    name.name.push(PathElem::Ident(
+        kind.method_name().into(),
+        Disambiguator::Id::new(0),
+    ));
+    let mut item_meta = closure.item_meta.clone();
+    item_meta.stable_id = format!("{}::{}", item_meta.stable_id, kind.method_name());
+
+    FunDecl {
+        def_id,
+        rust_id: closure.rust_id,
+        item_meta,
+        is_local: closure.is_local,
+        name,
+        lang_item: None,
+        body: Some(ExprBody {
+            meta,
+            arg_count: signature.inputs.len(),
+            locals,
+            regions: BodyRegions::default(),
+            borrowck_facts: None,
+            scopes: ScopeId::Vector::new(),
+            promoted: PromotedId::Vector::new(),
+            body: blocks,
+        }),
+        signature,
+        kind: ItemKind::Regular,
+        rec_group: None,
+    }
+}
+
+pub fn transform(ctx: &mut TransCtx) {
+    let closures: Vec<FunDeclId::Id> = ctx
+        .translated
+        .fun_decls
+        .iter()
+        .filter(|decl| decl.signature.closure_info.is_some())
+        .map(|decl| decl.def_id)
+        .collect();
+    for closure_id in closures {
+        let closure = ctx.translated.fun_decls.get(closure_id).unwrap();
+        let info = closure.signature.closure_info.as_ref().unwrap();
+        let shim_kinds = match info.kind {
+            ClosureKind::Fn => vec![ShimKind::CallMut, ShimKind::CallOnce],
+            ClosureKind::FnMut => vec![ShimKind::CallOnce],
+            ClosureKind::FnOnce => Vec::new(),
+        };
+        let shims: Vec<(ShimKind, FunDecl)> = shim_kinds
+            .into_iter()
+            .map(|kind| {
+                let shim_id = ctx.fun_id_map.fresh_id();
+                (kind, make_shim(closure, info, shim_id, kind))
+            })
+            .collect();
+
+        let closure = ctx.translated.fun_decls.get_mut(closure_id).unwrap();
+        let info = closure.signature.closure_info.as_mut().unwrap();
+        for (kind, shim) in shims {
+            match kind {
+                ShimKind::CallMut => info.call_mut_shim = Some(shim.def_id),
+                ShimKind::CallOnce => info.call_once_shim = Some(shim.def_id),
+            }
+            ctx.all_ids.insert(AnyTransId::Fun(shim.def_id));
+            ctx.translated.fun_decls.insert(shim.def_id, shim);
+        }
+    }
+}
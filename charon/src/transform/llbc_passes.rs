@@ -0,0 +1,42 @@
+//! The custom passes on the LLBC bodies: a tool which embeds Charon can
+//! register them (see [crate::driver::CharonCallbacks::register_llbc_pass]) to
+//! rewrite the bodies without forking the pipeline of the micro-passes. We run
+//! them after the passes which clean up the bodies, and before we remove the
+//! unused locals and the no-ops: the custom passes don't have to clean up after
+//! themselves.
+
+use crate::formatter::{Formatter, IntoFormatter};
+use crate::llbc_ast::*;
+use crate::names::Name;
+use crate::translate_ctx::TransformCtx;
+
+/// A custom pass on the LLBC bodies. The pass can look up the translated
+/// declarations and report errors through the [TransformCtx]: the errors are
+/// attributed to the declaration whose body we are transforming.
+pub trait LlbcPass: Send {
+    /// The name of the pass, which we use in the logs, in the errors of the
+    /// validation and in the profile.
+    fn name(&self) -> &str;
+
+    /// Transform the body of a function or of a global, named `name`.
+    fn transform_body(&mut self, ctx: &mut TransformCtx, name: &Name, body: &mut ExprBody);
+}
+
+pub fn transform(
+    pass: &mut dyn LlbcPass,
+    ctx: &mut TransformCtx,
+    funs: &mut FunDecls,
+    globals: &mut GlobalDecls,
+) {
+    ctx.iter_bodies(funs, globals, |ctx, name, b| {
+        let fmt_ctx = ctx.into_fmt();
+        trace!(
+            "# About to apply the pass {} to decl: {}:\n{}",
+            pass.name(),
+            name.fmt_with_ctx(&fmt_ctx),
+            fmt_ctx.format_object(&*b)
+        );
+
+        pass.transform_body(ctx, name, b);
+    })
+}
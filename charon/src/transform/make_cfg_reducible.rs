@@ -0,0 +1,277 @@
+//! Make the control-flow graphs of the ULLBC bodies reducible, by duplicating
+//! blocks (node splitting). The control-flow reconstruction (see
+//! [crate::ullbc_to_llbc]) only handles the reducible control-flow graphs: the
+//! graphs whose loops (at every nesting level) can only be entered through a
+//! single block, their header. The compiler generates such graphs for the
+//! structured code, but some macros and optimizations can generate loops with
+//! several entries. For instance, below, the loop made of `bb1` and `bb2` can be
+//! entered through `bb1` and `bb2`:
+//! ```text
+//! bb0: if copy b { goto bb1 } else { goto bb2 }
+//! bb1: ...; if copy c { goto bb2 } else { goto bb3 }
+//! bb2: ...; goto bb1
+//! ```
+//! We keep one entry of the loop as its header (`bb1`), and give copies of the
+//! other entries to the predecessors which are outside the loop: above, `bb0`
+//! jumps to a copy of `bb2` (which jumps to `bb1`). The copies may jump to new
+//! entries of the loop, so we repeat this until the graph is reducible.
+//!
+//! Node splitting may duplicate many blocks: we stop when the body would have
+//! more than [MAX_GROWTH] times its original number of blocks. In this case, we
+//! report the function and the blocks of the loop we could not fix, and we
+//! remove the body (as we do for the bodies we fail to translate).
+
+use crate::formatter::IntoFormatter;
+use crate::names::Name;
+use crate::translate_ctx::{register_error_or_panic, TransCtx};
+use crate::ullbc_ast::*;
+use petgraph::algo::tarjan_scc;
+use petgraph::graphmap::DiGraphMap;
+use std::collections::{BTreeSet, HashSet};
+
+/// The maximal ratio between the number of blocks of a body once made
+/// reducible and its original number of blocks.
+const MAX_GROWTH: usize = 2;
+
+/// A loop which can be entered through several blocks
+#[derive(Debug)]
+struct Irreducible {
+    /// The blocks of the loop
+    blocks: Vec<usize>,
+    /// The blocks through which we can enter the loop
+    entries: Vec<usize>,
+}
+
+/// The blocks reachable from the entry block, and their predecessors (among
+/// those blocks).
+fn reachable_and_predecessors(succs: &[Vec<usize>]) -> (BTreeSet<usize>, Vec<Vec<usize>>) {
+    let mut reachable = BTreeSet::new();
+    let mut stack = vec![START_BLOCK_ID.index()];
+    while let Some(n) = stack.pop() {
+        if reachable.insert(n) {
+            stack.extend(succs[n].iter().copied());
+        }
+    }
+    let mut preds = vec![Vec::new(); succs.len()];
+    for n in &reachable {
+        for s in &succs[*n] {
+            if !preds[*s].contains(n) {
+                preds[*s].push(*n);
+            }
+        }
+    }
+    (reachable, preds)
+}
+
+/// Look for a loop with several entries among the blocks in `nodes`. We
+/// explore the loops from the outermost to the innermost: once we found a loop
+/// with a single entry, we look for the inner loops in its blocks (but its
+/// header).
+fn find_irreducible(
+    succs: &[Vec<usize>],
+    preds: &[Vec<usize>],
+    nodes: &BTreeSet<usize>,
+) -> Option<Irreducible> {
+    let mut graph = DiGraphMap::<usize, ()>::new();
+    for n in nodes {
+        graph.add_node(*n);
+        for s in succs[*n].iter().filter(|s| nodes.contains(s)) {
+            graph.add_edge(*n, *s, ());
+        }
+    }
+    for scc in tarjan_scc(&graph) {
+        let is_loop = scc.len() > 1 || succs[scc[0]].contains(&scc[0]);
+        if !is_loop {
+            continue;
+        }
+        let blocks: BTreeSet<usize> = scc.into_iter().collect();
+        // The entry block of the body has an implicit predecessor
+        let entries: Vec<usize> = blocks
+            .iter()
+            .copied()
+            .filter(|n| {
+                *n == START_BLOCK_ID.index() || preds[*n].iter().any(|p| !blocks.contains(p))
+            })
+            .collect();
+        if entries.len() > 1 {
+            return Some(Irreducible {
+                blocks: blocks.into_iter().collect(),
+                entries,
+            });
+        }
+        let mut inner = blocks;
+        inner.remove(&entries[0]);
+        if let Some(irreducible) = find_irreducible(succs, preds, &inner) {
+            return Some(irreducible);
+        }
+    }
+    None
+}
+
+/// Make a control-flow graph reducible, by duplicating nodes. `succs` gives
+/// the successors of every node (we update it with the new nodes), and node 0
+/// is the entry. We return the original node of every node, or the loop we
+/// could not fix if the graph would have more than `max_nodes` nodes (in terms
+/// of the original nodes).
+fn make_reducible(
+    succs: &mut Vec<Vec<usize>>,
+    max_nodes: usize,
+) -> Result<Vec<usize>, Irreducible> {
+    let mut origins: Vec<usize> = (0..succs.len()).collect();
+    loop {
+        let (reachable, preds) = reachable_and_predecessors(succs);
+        let Some(irreducible) = find_irreducible(succs, &preds, &reachable) else {
+            return Ok(origins);
+        };
+        if succs.len() + irreducible.entries.len() - 1 > max_nodes {
+            let to_origins = |nodes: &[usize]| -> Vec<usize> {
+                let nodes: BTreeSet<usize> = nodes.iter().map(|n| origins[*n]).collect();
+                nodes.into_iter().collect()
+            };
+            return Err(Irreducible {
+                blocks: to_origins(&irreducible.blocks),
+                entries: to_origins(&irreducible.entries),
+            });
+        }
+
+        // We keep the first entry as the header: if the entry block of the
+        // body is in the loop, this is this block (we can't duplicate it)
+        let blocks: HashSet<usize> = irreducible.blocks.iter().copied().collect();
+        for entry in &irreducible.entries[1..] {
+            let copy = succs.len();
+            succs.push(succs[*entry].clone());
+            origins.push(origins[*entry]);
+            for pred in preds[*entry].iter().filter(|p| !blocks.contains(p)) {
+                for target in succs[*pred].iter_mut().filter(|t| **t == *entry) {
+                    *target = copy;
+                }
+            }
+        }
+    }
+}
+
+/// Make the control-flow graph of a body reducible, by duplicating blocks.
+fn transform_body(body: &mut ExprBody) -> Result<(), Irreducible> {
+    let num_blocks = body.body.len();
+    let mut succs: Vec<Vec<usize>> = body
+        .body
+        .iter()
+        .map(|block| {
+            let targets = block.terminator.content.get_targets();
+            targets.into_iter().map(|id| id.index()).collect()
+        })
+        .collect();
+    let origins = make_reducible(&mut succs, MAX_GROWTH * num_blocks)?;
+    if origins.len() == num_blocks {
+        return Ok(());
+    }
+
+    for origin in &origins[num_blocks..] {
+        let block = body.body.get(BlockId::Id::new(*origin)).unwrap().clone();
+        body.body.push(block);
+    }
+    for (block, succs) in body.body.iter_mut().zip(succs) {
+        let targets = block.terminator.content.get_targets_mut();
+        for (target, succ) in targets.into_iter().zip(succs) {
+            *target = BlockId::Id::new(succ);
+        }
+    }
+    Ok(())
+}
+
+/// The error we report for a body we could not make reducible
+fn error_message(ctx: &TransCtx, name: &Name, irreducible: &Irreducible) -> String {
+    let fmt_blocks = |blocks: &[usize]| -> String {
+        let blocks: Vec<String> = blocks.iter().map(|id| format!("bb{id}")).collect();
+        blocks.join(", ")
+    };
+    format!(
+        "Could not reconstruct the control-flow of {}: the loop made of the blocks {} can be \
+         entered through the blocks {}, and duplicating blocks to give it a single entry would \
+         more than multiply by {MAX_GROWTH} the number of blocks of the body",
+        name.fmt_with_ctx(&ctx.into_fmt()),
+        fmt_blocks(&irreducible.blocks),
+        fmt_blocks(&irreducible.entries),
+    )
+}
+
+pub fn transform(ctx: &mut TransCtx) {
+    let mut failed_funs = Vec::new();
+    for decl in ctx.translated.fun_decls.iter_mut() {
+        if let Some(body) = &mut decl.body
+            && let Err(irreducible) = transform_body(body)
+        {
+            failed_funs.push((decl.def_id, irreducible));
+        }
+    }
+    let mut failed_globals = Vec::new();
+    for decl in ctx.translated.global_decls.iter_mut() {
+        if let Some(body) = &mut decl.body
+            && let Err(irreducible) = transform_body(body)
+        {
+            failed_globals.push((decl.def_id, irreducible));
+        }
+    }
+
+    // Report the failures, and remove the bodies we could not fix
+    for (id, irreducible) in failed_funs {
+        let decl = ctx.translated.fun_decls.get(id).unwrap();
+        let msg = error_message(ctx, &decl.name, &irreducible);
+        let span = decl.item_meta.meta.span.rust_span_data.span();
+        ctx.errors.with_def_id(decl.rust_id, |errors| {
+            register_error_or_panic!(errors, span, msg)
+        });
+        ctx.translated.fun_decls.get_mut(id).unwrap().body = None;
+    }
+    for (id, irreducible) in failed_globals {
+        let decl = ctx.translated.global_decls.get(id).unwrap();
+        let msg = error_message(ctx, &decl.name, &irreducible);
+        let span = decl.item_meta.meta.span.rust_span_data.span();
+        ctx.errors.with_def_id(decl.rust_id, |errors| {
+            register_error_or_panic!(errors, span, msg)
+        });
+        ctx.translated.global_decls.get_mut(id).unwrap().body = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_make_reducible() {
+        // The loop made of 1 and 2 can be entered through 1 and 2
+        let mut succs = vec![vec![1, 2], vec![2, 3], vec![1], vec![]];
+        let origins = make_reducible(&mut succs, 8).unwrap();
+        assert!(origins == vec![0, 1, 2, 3, 2]);
+        assert!(succs == vec![vec![1, 4], vec![2, 3], vec![1], vec![], vec![1]]);
+        let (reachable, preds) = reachable_and_predecessors(&succs);
+        assert!(find_irreducible(&succs, &preds, &reachable).is_none());
+    }
+
+    #[test]
+    fn test_make_reducible_nested() {
+        // The loop made of 2 and 3 is in the loop with header 1, and can be
+        // entered through 2 and 3
+        let mut succs = vec![vec![1], vec![2, 3, 4], vec![3], vec![2, 1], vec![]];
+        let origins = make_reducible(&mut succs, 10).unwrap();
+        assert!(origins == vec![0, 1, 2, 3, 4, 3]);
+        assert!(succs[1] == vec![2, 5, 4]);
+    }
+
+    #[test]
+    fn test_make_reducible_unchanged() {
+        // A `while` loop
+        let mut succs = vec![vec![1], vec![2, 3], vec![1], vec![]];
+        let origins = make_reducible(&mut succs, 8).unwrap();
+        assert!(origins == vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_make_reducible_too_big() {
+        let mut succs = vec![vec![1, 2], vec![2, 3], vec![1], vec![]];
+        let irreducible = make_reducible(&mut succs, 4).unwrap_err();
+        assert!(irreducible.blocks == vec![1, 2]);
+        assert!(irreducible.entries == vec![1, 2]);
+    }
+}
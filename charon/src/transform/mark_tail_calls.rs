@@ -0,0 +1,162 @@
+//! Mark the calls which are in tail position (see [Call::is_tail]): the calls
+//! whose result is returned, possibly after being moved to the return value,
+//! and which are only followed by drops. For instance, the call below is in
+//! tail position:
+//! ```text
+//! if copy b {
+//!   tmp := f(move x);
+//!   drop y;
+//!   _0 := move tmp;
+//! } else {
+//!   ...
+//! }
+//! return;
+//! ```
+//!
+//! Remark: we ignore the drops, though the destructors of the dropped values
+//! are executed after the call.
+//!
+//! We also mark the tail calls of the ULLBC bodies when the user asks for the
+//! ULLBC (see [transform_ullbc]): there, the call terminators whose target
+//! block only contains drops before the return.
+
+use crate::formatter::{Formatter, IntoFormatter};
+use crate::llbc_ast::*;
+use crate::translate_ctx::{TransCtx, TransformCtx};
+use crate::ullbc_ast::{self, BlockId};
+use crate::values::*;
+use std::collections::HashMap;
+
+/// Mark the tail calls in a block. `returned` is the local whose value is
+/// returned when we reach the end of the block, if only drops happen between
+/// the end of the block and the return. `loops` gives the same information for
+/// the ends of the loops we are in, from the outermost to the innermost: the
+/// breaks go to the end of those loops.
+fn mark_block(b: &mut Block, mut returned: Option<VarId::Id>, loops: &mut Vec<Option<VarId::Id>>) {
+    for st in b.statements.iter_mut().rev() {
+        returned = match &mut st.content {
+            RawStatement::Return => Some(VarId::ZERO),
+            RawStatement::Drop(p) if returned != Some(p.var_id) => returned,
+            RawStatement::StorageDead(vid) if returned != Some(*vid) => returned,
+            RawStatement::StorageLive(_) | RawStatement::FakeRead(_) | RawStatement::Nop => {
+                returned
+            }
+            // `_0 := move x; return`: the value of `x` is returned
+            RawStatement::Assign(dest, Rvalue::Use(Operand::Move(src) | Operand::Copy(src)))
+                if returned.is_some_and(|vid| *dest == Place::new(vid))
+                    && src.projection.is_empty() =>
+            {
+                Some(src.var_id)
+            }
+            RawStatement::Call(call) => {
+                call.is_tail = returned.is_some_and(|vid| call.dest == Place::new(vid));
+                None
+            }
+            RawStatement::Switch(switch) => {
+                for block in switch.get_targets_mut() {
+                    mark_block(block, returned, loops);
+                }
+                None
+            }
+            RawStatement::Loop(body, _) => {
+                // Falling through the end of the body goes back to its start
+                loops.push(returned);
+                mark_block(body, None, loops);
+                loops.pop();
+                None
+            }
+            RawStatement::Break(i, _) => loops[loops.len() - 1 - *i],
+            _ => None,
+        }
+    }
+}
+
+pub fn transform(ctx: &mut TransformCtx, funs: &mut FunDecls, globals: &mut GlobalDecls) {
+    ctx.iter_bodies(funs, globals, |ctx, name, b| {
+        let fmt_ctx = ctx.into_fmt();
+        trace!(
+            "# About to mark the tail calls in decl: {}:\n{}",
+            name.fmt_with_ctx(&fmt_ctx),
+            fmt_ctx.format_object(&*b)
+        );
+
+        mark_block(&mut b.body, None, &mut Vec::new());
+    })
+}
+
+/// The local whose value is returned when we reach the start of a ULLBC block,
+/// if only drops happen between the start of the block and the return. We
+/// memoize the results in `returned`: the blocks we are exploring are mapped
+/// to `None`, so that we stop on the loops (which never return).
+fn returned_at_block(
+    blocks: &BlockId::Vector<ullbc_ast::BlockData>,
+    returned: &mut HashMap<BlockId::Id, Option<VarId::Id>>,
+    id: BlockId::Id,
+) -> Option<VarId::Id> {
+    use ullbc_ast::{RawStatement, RawTerminator};
+    if let Some(vid) = returned.get(&id) {
+        return *vid;
+    }
+    returned.insert(id, None);
+    let block = blocks.get(id).unwrap();
+    let mut vid = match &block.terminator.content {
+        RawTerminator::Return => Some(VarId::ZERO),
+        RawTerminator::Goto { target } => returned_at_block(blocks, returned, *target),
+        RawTerminator::Drop { place, target } => {
+            returned_at_block(blocks, returned, *target).filter(|vid| *vid != place.var_id)
+        }
+        _ => None,
+    };
+    for st in block.statements.iter().rev() {
+        vid = match &st.content {
+            RawStatement::StorageDead(v) if vid != Some(*v) => vid,
+            RawStatement::StorageLive(_) | RawStatement::FakeRead(_) => vid,
+            RawStatement::Assign(dest, Rvalue::Use(Operand::Move(src) | Operand::Copy(src)))
+                if vid.is_some_and(|vid| *dest == Place::new(vid)) && src.projection.is_empty() =>
+            {
+                Some(src.var_id)
+            }
+            _ => None,
+        };
+    }
+    returned.insert(id, vid);
+    vid
+}
+
+/// Mark the tail calls of the ULLBC bodies
+pub fn transform_ullbc(ctx: &mut TransCtx) {
+    // Slightly annoying: we have to clone because of borrowing issues
+    let mut fun_decls = ctx.translated.fun_decls.clone();
+    let mut global_decls = ctx.translated.global_decls.clone();
+
+    let mut t_ctx = ctx.transform_ctx();
+    t_ctx.iter_bodies(&mut fun_decls, &mut global_decls, |ctx, name, b| {
+        let fmt_ctx = ctx.into_fmt();
+        trace!(
+            "# About to mark the tail calls in decl: {}:\n{}",
+            name.fmt_with_ctx(&fmt_ctx),
+            fmt_ctx.format_object(&*b)
+        );
+
+        let mut returned = HashMap::new();
+        let tails: Vec<bool> = b
+            .body
+            .iter()
+            .map(|block| match &block.terminator.content {
+                ullbc_ast::RawTerminator::Call { call, target } => {
+                    returned_at_block(&b.body, &mut returned, *target)
+                        .is_some_and(|vid| call.dest == Place::new(vid))
+                }
+                _ => false,
+            })
+            .collect();
+        for (block, is_tail) in b.body.iter_mut().zip(tails) {
+            if let ullbc_ast::RawTerminator::Call { call, .. } = &mut block.terminator.content {
+                call.is_tail = is_tail;
+            }
+        }
+    });
+
+    ctx.translated.fun_decls = fun_decls;
+    ctx.translated.global_decls = global_decls;
+}
@@ -1,11 +1,28 @@
+pub mod compute_loop_info;
+pub mod compute_regions_graph;
 pub mod index_to_function_calls;
+pub mod inline_functions;
+pub mod make_cfg_reducible;
+pub mod mark_tail_calls;
 pub mod insert_assign_return_unit;
+pub mod insert_closure_shims;
+pub mod llbc_passes;
+pub mod normalize_trait_types;
 pub mod ops_to_function_calls;
+pub mod propagate_constants;
 pub mod reconstruct_asserts;
+pub mod reconstruct_expressions;
+pub mod remove_dead_branches;
 pub mod remove_drop_never;
 pub mod remove_dynamic_checks;
 pub mod remove_nops;
+pub mod remove_phantom_data;
 pub mod remove_read_discriminant;
+pub mod remove_redundant_copies;
+pub mod remove_unit_locals;
 pub mod remove_unused_locals;
+pub mod rename_items;
 pub mod simplify_constants;
+pub mod simplify_trait_refs;
+pub mod solve_trait_obligations;
 pub mod update_closure_signatures;
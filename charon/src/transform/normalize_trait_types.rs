@@ -0,0 +1,143 @@
+//! # Micro-pass: normalize the associated types of the traits. We replace a
+//! type `<T as Trait>::Item` with the type it is equal to, when this type is
+//! determined by:
+//! - a constraint of the declaration (see [TraitTypeConstraint]): for instance,
+//!   `T::Output` becomes `u32` in `fn f<T: Add<Output = u32>>`;
+//! - or the trait implementation the trait reference refers to, if this
+//!   implementation is statically known (see [TraitInstanceId::resolve_impl])
+//!   and doesn't have generics: for instance, `<u32 as Foo>::Item` becomes the
+//!   type given by `impl Foo for u32`.
+//!
+//! We don't normalize the constraints themselves. This pass can be disabled
+//! with `--no-normalize-trait-types`.
+use crate::expressions::MutExprVisitor;
+use crate::translate_ctx::TransCtx;
+use crate::types::*;
+use crate::ullbc_ast::*;
+
+struct Normalize<'a> {
+    trait_impls: &'a TraitImpls,
+    /// The constraints of the declaration we explore
+    constraints: &'a [TraitTypeConstraint],
+}
+
+impl<'a> Normalize<'a> {
+    fn normalize(&self, tr: &TraitRef, name: &TraitItemName, generics: &GenericArgs) -> Option<Ty> {
+        if let Some(constraint) = self
+            .constraints
+            .iter()
+            .find(|c| c.trait_ref == *tr && c.type_name == *name && c.generics == *generics)
+        {
+            return Some(constraint.ty.clone());
+        }
+        let impl_id = tr.trait_id.resolve_impl(self.trait_impls)?;
+        let imp = self.trait_impls.get(impl_id)?;
+        if !imp.generics.is_empty() {
+            return None;
+        }
+        let (_, (params, _, ty)) = imp.types.iter().find(|(item, _)| item == name)?;
+        // The generic associated types are parameterized by their own generics
+        if !params.is_empty() {
+            return None;
+        }
+        Some(ty.clone())
+    }
+
+    fn visit_body(&mut self, body: &mut ExprBody) {
+        for var in body.locals.iter_mut() {
+            self.visit_ty(&mut var.ty);
+        }
+        for block in body.body.iter_mut() {
+            self.visit_block_data(block);
+        }
+    }
+}
+
+impl<'a> MutTypeVisitor for Normalize<'a> {
+    fn visit_ty(&mut self, ty: &mut Ty) {
+        self.default_visit_ty(ty);
+        let normalized = match ty.kind() {
+            TyKind::TraitType(tr, name, generics) => self.normalize(tr, name, generics),
+            _ => None,
+        };
+        if let Some(normalized) = normalized {
+            // The type given by the implementation may itself need to be normalized
+            *ty = normalized;
+            self.visit_ty(ty)
+        }
+    }
+}
+
+impl<'a> MutExprVisitor for Normalize<'a> {}
+
+impl<'a> MutAstVisitor for Normalize<'a> {}
+
+pub fn transform(ctx: &mut TransCtx) {
+    let trait_impls = &ctx.translated.trait_impls;
+    let mut type_decls = ctx.translated.type_decls.clone();
+    let mut fun_decls = ctx.translated.fun_decls.clone();
+    let mut global_decls = ctx.translated.global_decls.clone();
+    let mut impls = trait_impls.clone();
+
+    for d in type_decls.iter_mut() {
+        let mut visitor = Normalize {
+            trait_impls,
+            constraints: &d.preds.trait_type_constraints,
+        };
+        match &mut d.kind {
+            TypeDeclKind::Struct(fields) => {
+                for field in fields.iter_mut() {
+                    visitor.visit_ty(&mut field.ty);
+                }
+            }
+            TypeDeclKind::Enum(variants, ..) => {
+                for field in variants.iter_mut().flat_map(|v| v.fields.iter_mut()) {
+                    visitor.visit_ty(&mut field.ty);
+                }
+            }
+            TypeDeclKind::ImplTrait { hidden_ty, .. } => {
+                if let Some(ty) = hidden_ty {
+                    visitor.visit_ty(ty);
+                }
+            }
+            TypeDeclKind::Opaque | TypeDeclKind::Error(_) => (),
+        }
+    }
+    for f in fun_decls.iter_mut() {
+        let mut visitor = Normalize {
+            trait_impls,
+            constraints: &f.signature.preds.trait_type_constraints,
+        };
+        for ty in f.signature.inputs.iter_mut() {
+            visitor.visit_ty(ty);
+        }
+        visitor.visit_ty(&mut f.signature.output);
+        if let Some(body) = &mut f.body {
+            visitor.visit_body(body);
+        }
+    }
+    for g in global_decls.iter_mut() {
+        let mut visitor = Normalize {
+            trait_impls,
+            constraints: &g.preds.trait_type_constraints,
+        };
+        visitor.visit_ty(&mut g.ty);
+        if let Some(body) = &mut g.body {
+            visitor.visit_body(body);
+        }
+    }
+    for imp in impls.iter_mut() {
+        let mut visitor = Normalize {
+            trait_impls,
+            constraints: &imp.preds.trait_type_constraints,
+        };
+        for (_, (_, _, ty)) in imp.types.iter_mut() {
+            visitor.visit_ty(ty);
+        }
+    }
+
+    ctx.translated.type_decls = type_decls;
+    ctx.translated.fun_decls = fun_decls;
+    ctx.translated.global_decls = global_decls;
+    ctx.translated.trait_impls = impls;
+}
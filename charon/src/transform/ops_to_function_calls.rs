@@ -5,7 +5,7 @@
 use crate::expressions::{Rvalue, UnOp};
 use crate::formatter::{Formatter, IntoFormatter};
 use crate::llbc_ast::*;
-use crate::translate_ctx::TransCtx;
+use crate::translate_ctx::TransformCtx;
 use crate::types::*;
 
 fn transform_st(s: &mut Statement) -> Option<Vec<Statement>> {
@@ -30,6 +30,10 @@ fn transform_st(s: &mut Statement) -> Option<Vec<Statement>> {
                 func,
                 args: vec![op.clone()],
                 dest: p.clone(),
+                receiver_adjustments: Vec::new(),
+                resolution: None,
+                is_tail: false,
+                is_ghost: false,
             });
 
             None
@@ -51,6 +55,10 @@ fn transform_st(s: &mut Statement) -> Option<Vec<Statement>> {
                 func,
                 args: vec![op.clone()],
                 dest: p.clone(),
+                receiver_adjustments: Vec::new(),
+                resolution: None,
+                is_tail: false,
+                is_ghost: false,
             });
 
             None
@@ -59,7 +67,7 @@ fn transform_st(s: &mut Statement) -> Option<Vec<Statement>> {
     }
 }
 
-pub fn transform(ctx: &mut TransCtx, funs: &mut FunDecls, globals: &mut GlobalDecls) {
+pub fn transform(ctx: &mut TransformCtx, funs: &mut FunDecls, globals: &mut GlobalDecls) {
     ctx.iter_bodies(funs, globals, |ctx, name, b| {
         let fmt_ctx = ctx.into_fmt();
         trace!(
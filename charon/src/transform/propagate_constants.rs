@@ -0,0 +1,285 @@
+//! Propagate the literal constants through the locals, fold the operations on
+//! constants and simplify the switches and the asserts on constants (this pass
+//! is optional, see the `--propagate-constants` option). For instance:
+//! ```text
+//! x := const 3;
+//! y := copy x * const 2;
+//! if copy y > const 4 { ... } else { ... }
+//! ```
+//! becomes the body of the `then` branch.
+//!
+//! We only propagate the constants stored in the locals which are assigned once
+//! and are otherwise only read (copied or moved) or dropped: those locals
+//! always contain the constant when they are read. We fold the operations with
+//! the functions of the interpreter (see [crate::interp]), and we don't fold
+//! the operations which panic (like the overflows): the panic is preserved.
+//!
+//! We iterate until we reach a fixed point: folding an operation gives a new
+//! constant to propagate, and removing the dead branches of the switches on
+//! constants (see [crate::transform::remove_dead_branches]) may remove
+//! assignments.
+//! The locals whose constant we propagated become unused: they are removed by
+//! [crate::transform::remove_unused_locals].
+
+use crate::expressions::{MutExprVisitor, SharedExprVisitor};
+use crate::formatter::{Formatter, IntoFormatter};
+use crate::interp::{eval_binop, eval_unop, Value};
+use crate::llbc_ast::*;
+use crate::transform::remove_dead_branches;
+use crate::translate_ctx::TransformCtx;
+use crate::types::{LiteralTy, MutTypeVisitor, SharedTypeVisitor, TyKind};
+use crate::values::*;
+use std::collections::HashMap;
+
+/// The occurrences of a local in a body
+#[derive(Debug, Default)]
+pub(crate) struct Occurrences {
+    /// All the occurrences, but the ones in the storage statements
+    pub(crate) all: usize,
+    /// The assignments to the whole local
+    pub(crate) assigns: usize,
+    /// The copies and moves of the whole local
+    pub(crate) reads: usize,
+    /// The drops of the whole local
+    pub(crate) drops: usize,
+    /// The constant assigned to the local, if its (unique) assignment is a
+    /// literal constant
+    pub(crate) constant: Option<ConstantExpr>,
+}
+
+struct CountOccurrences {
+    occurrences: HashMap<VarId::Id, Occurrences>,
+}
+
+impl SharedTypeVisitor for CountOccurrences {}
+impl SharedExprVisitor for CountOccurrences {
+    fn visit_var_id(&mut self, vid: &VarId::Id) {
+        self.occurrences.entry(*vid).or_default().all += 1;
+    }
+
+    fn visit_copy(&mut self, p: &Place) {
+        if p.projection.is_empty() {
+            self.occurrences.entry(p.var_id).or_default().reads += 1;
+        }
+        self.visit_place(p)
+    }
+
+    fn visit_move(&mut self, p: &Place) {
+        if p.projection.is_empty() {
+            self.occurrences.entry(p.var_id).or_default().reads += 1;
+        }
+        self.visit_place(p)
+    }
+}
+
+impl SharedAstVisitor for CountOccurrences {
+    fn spawn(&mut self, visitor: &mut dyn FnMut(&mut Self)) {
+        visitor(self)
+    }
+
+    fn merge(&mut self) {}
+
+    fn visit_assign(&mut self, p: &Place, rv: &Rvalue) {
+        if p.projection.is_empty() {
+            let occ = self.occurrences.entry(p.var_id).or_default();
+            occ.assigns += 1;
+            if let Rvalue::Use(Operand::Const(cv)) = rv
+                && cv.value.is_literal()
+            {
+                occ.constant = Some(cv.clone());
+            }
+        }
+        self.visit_place(p);
+        self.visit_rvalue(rv)
+    }
+
+    fn visit_drop(&mut self, p: &Place) {
+        if p.projection.is_empty() {
+            self.occurrences.entry(p.var_id).or_default().drops += 1;
+        }
+        self.visit_place(p)
+    }
+
+    fn visit_storage_live(&mut self, _: &VarId::Id) {}
+
+    fn visit_storage_dead(&mut self, _: &VarId::Id) {}
+}
+
+/// Count the occurrences of the locals in a block
+pub(crate) fn count_occurrences(b: &Block) -> HashMap<VarId::Id, Occurrences> {
+    let mut visitor = CountOccurrences {
+        occurrences: HashMap::new(),
+    };
+    visitor.visit_block(b);
+    visitor.occurrences
+}
+
+/// Compute the constants we can propagate: the ones stored in the locals
+/// (which are neither the return value nor an input) which are assigned once,
+/// and otherwise only read or dropped.
+fn compute_constants(b: &ExprBody) -> HashMap<VarId::Id, ConstantExpr> {
+    count_occurrences(&b.body)
+        .into_iter()
+        .filter(|(vid, occ)| {
+            vid.index() > b.arg_count
+                && occ.assigns == 1
+                && occ.all == occ.assigns + occ.reads + occ.drops
+        })
+        .filter_map(|(vid, occ)| Some((vid, occ.constant?)))
+        .collect()
+}
+
+/// Replace the reads of the locals with the constants they contain, and remove
+/// the assignments and the drops of those locals.
+struct PropagateConstants {
+    constants: HashMap<VarId::Id, ConstantExpr>,
+}
+
+impl PropagateConstants {
+    fn is_propagated(&self, p: &Place) -> bool {
+        p.projection.is_empty() && self.constants.contains_key(&p.var_id)
+    }
+}
+
+impl MutTypeVisitor for PropagateConstants {}
+impl MutExprVisitor for PropagateConstants {
+    fn visit_operand(&mut self, op: &mut Operand) {
+        match op {
+            Operand::Copy(p) | Operand::Move(p) if self.is_propagated(p) => {
+                *op = Operand::Const(self.constants.get(&p.var_id).unwrap().clone())
+            }
+            _ => self.default_visit_operand(op),
+        }
+    }
+}
+
+impl MutAstVisitor for PropagateConstants {
+    fn spawn(&mut self, visitor: &mut dyn FnMut(&mut Self)) {
+        visitor(self)
+    }
+
+    fn merge(&mut self) {}
+
+    fn visit_raw_statement(&mut self, st: &mut RawStatement) {
+        match st {
+            RawStatement::Assign(p, _) | RawStatement::Drop(p) if self.is_propagated(p) => {
+                *st = RawStatement::Nop
+            }
+            _ => self.default_visit_raw_statement(st),
+        }
+    }
+}
+
+/// The constant of the type of a literal
+fn literal_constant(lit: Literal) -> ConstantExpr {
+    let ty = match &lit {
+        Literal::Scalar(v) => LiteralTy::Integer(v.get_integer_ty()),
+        Literal::Bool(_) => LiteralTy::Bool,
+        Literal::Char(_) => LiteralTy::Char,
+    };
+    ConstantExpr {
+        value: RawConstantExpr::Literal(lit),
+        ty: TyKind::Literal(ty).into_ty(),
+    }
+}
+
+/// Fold the unary and binary operations on literal constants
+struct FoldConstants {
+    changed: bool,
+}
+
+impl FoldConstants {
+    /// Evaluate an operation on constants. We don't fold the operations which
+    /// fail (because they panic for instance), nor the ones which don't give a
+    /// literal (like the checked operations).
+    fn fold(rv: &Rvalue) -> Option<ConstantExpr> {
+        let value = match rv {
+            Rvalue::UnaryOp(op, op1) => {
+                let lit1 = op1.as_literal()?.clone();
+                eval_unop(op, Value::Literal(lit1)).ok()?
+            }
+            Rvalue::BinaryOp(op, op1, op2) => {
+                let lit1 = op1.as_literal()?.clone();
+                let lit2 = op2.as_literal()?.clone();
+                eval_binop(*op, Value::Literal(lit1), Value::Literal(lit2)).ok()?
+            }
+            _ => return None,
+        };
+        match value {
+            Value::Literal(lit) => Some(literal_constant(lit)),
+            _ => None,
+        }
+    }
+}
+
+impl MutTypeVisitor for FoldConstants {}
+impl MutExprVisitor for FoldConstants {
+    fn visit_rvalue(&mut self, rv: &mut Rvalue) {
+        // Bottom-up: we first fold the nested expressions
+        self.default_visit_rvalue(rv);
+        if let Some(cv) = Self::fold(rv) {
+            *rv = Rvalue::Use(Operand::Const(cv));
+            self.changed = true;
+        }
+    }
+
+    fn visit_operand(&mut self, op: &mut Operand) {
+        self.default_visit_operand(op);
+        // Simplify the nested expressions which became constants
+        if let Operand::Expr(rv, _) = op
+            && let Rvalue::Use(Operand::Const(cv)) = rv.as_ref()
+        {
+            *op = Operand::Const(cv.clone());
+        }
+    }
+}
+
+impl MutAstVisitor for FoldConstants {
+    fn spawn(&mut self, visitor: &mut dyn FnMut(&mut Self)) {
+        visitor(self)
+    }
+
+    fn merge(&mut self) {}
+}
+
+/// Remove the asserts which always succeed
+fn remove_asserts(changed: &mut bool, b: &mut Block) {
+    for st in &mut b.statements {
+        if let RawStatement::Assert(assert) = &st.content
+            && assert.cond.as_literal() == Some(&Literal::Bool(assert.expected))
+        {
+            st.content = RawStatement::Nop;
+            *changed = true;
+        }
+    }
+}
+
+pub fn transform(ctx: &mut TransformCtx, funs: &mut FunDecls, globals: &mut GlobalDecls) {
+    ctx.iter_bodies(funs, globals, |ctx, name, b| {
+        let fmt_ctx = ctx.into_fmt();
+        trace!(
+            "# About to propagate the constants in decl: {}:\n{}",
+            name.fmt_with_ctx(&fmt_ctx),
+            fmt_ctx.format_object(&*b)
+        );
+
+        loop {
+            let constants = compute_constants(b);
+            let mut changed = !constants.is_empty();
+            let mut visitor = PropagateConstants { constants };
+            visitor.visit_block(&mut b.body);
+
+            let mut visitor = FoldConstants { changed: false };
+            visitor.visit_block(&mut b.body);
+            changed |= visitor.changed;
+
+            b.body.transform_blocks(&mut |block| {
+                remove_asserts(&mut changed, block);
+                changed |= remove_dead_branches::simplify_block(block);
+            });
+            if !changed {
+                break;
+            }
+        }
+    })
+}
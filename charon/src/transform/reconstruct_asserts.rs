@@ -3,36 +3,36 @@
 //! introduce `if ... then { panic!(...) } else { ...}`.
 //! This pass introduces `assert` instead in order to make the code shorter.
 
-use take_mut::take;
-
 use crate::formatter::{Formatter, IntoFormatter};
 use crate::llbc_ast::*;
-use crate::translate_ctx::TransCtx;
+use crate::translate_ctx::TransformCtx;
+
+fn is_panic(b: &Block) -> bool {
+    matches!(b.statements.as_slice(), [st] if st.content.is_panic())
+}
 
-fn transform_st(st: &mut Statement) -> Option<Vec<Statement>> {
-    if let RawStatement::Switch(Switch::If(_, st1, _)) = &mut st.content {
-        // Check if the first statement is a panic: if yes, replace
-        // the if .. then ... else ... by an assertion.
-        if st1.content.is_panic() {
-            // Replace: we need to take the value
-            take(&mut st.content, |st| {
-                let (op, st1, st2) = st.to_switch().to_if();
-                let st1 = Statement::new(
-                    st1.meta,
-                    RawStatement::Assert(Assert {
-                        cond: op,
-                        expected: false,
-                    }),
-                );
-                let st1 = Box::new(st1);
-                RawStatement::Sequence(st1, st2)
-            });
+fn transform_block(b: &mut Block) {
+    let statements = std::mem::take(&mut b.statements);
+    for st in statements {
+        match st.content {
+            // Check if the first branch is a panic: if yes, replace
+            // the if .. then ... else ... by an assertion followed by
+            // the statements of the second branch.
+            RawStatement::Switch(Switch::If(op, st1, st2)) if is_panic(&st1) => {
+                let assert = RawStatement::Assert(Assert {
+                    cond: op,
+                    expected: false,
+                    kind: AssertKind::User,
+                });
+                b.statements.push(Statement::new(st1.meta, assert));
+                b.statements.extend(st2.statements);
+            }
+            content => b.statements.push(Statement::new(st.meta, content)),
         }
     }
-    None
 }
 
-pub fn transform(ctx: &mut TransCtx, funs: &mut FunDecls, globals: &mut GlobalDecls) {
+pub fn transform(ctx: &mut TransformCtx, funs: &mut FunDecls, globals: &mut GlobalDecls) {
     ctx.iter_bodies(funs, globals, |ctx, name, b| {
         let fmt_ctx = ctx.into_fmt();
         trace!(
@@ -40,6 +40,6 @@ pub fn transform(ctx: &mut TransCtx, funs: &mut FunDecls, globals: &mut GlobalDe
             name.fmt_with_ctx(&fmt_ctx),
             fmt_ctx.format_object(&*b)
         );
-        b.body.transform(&mut transform_st);
+        b.body.transform_blocks(&mut transform_block);
     })
 }
@@ -0,0 +1,323 @@
+//! Reconstruct the nested expressions, by inlining the temporaries which are
+//! used only once (this pass is optional, see the `--reconstruct-expressions`
+//! option). For instance:
+//! ```text
+//! tmp := copy b + copy c;
+//! d := move tmp * const 2;
+//! ```
+//! becomes `d := (copy b + copy c) * const 2`.
+//!
+//! We only inline the definition of a temporary in the statement which
+//! immediately follows it (ignoring the `StorageLive`), and only if the
+//! operands of this statement which are evaluated before the temporary don't
+//! move the variables read by its definition: this preserves the evaluation
+//! order. We only inline the definitions which don't have side effects (other
+//! than panicking).
+//!
+//! The inlined temporaries become unused: they are removed by
+//! [crate::transform::remove_unused_locals].
+
+use crate::expressions::{MutExprVisitor, SharedExprVisitor};
+use crate::formatter::{Formatter, IntoFormatter};
+use crate::llbc_ast::*;
+use crate::meta_utils::combine_meta;
+use crate::translate_ctx::TransformCtx;
+use crate::types::{MutTypeVisitor, SharedTypeVisitor};
+use crate::values::*;
+use std::collections::{HashMap, HashSet};
+use take_mut::take;
+
+/// The occurrences of a local in a body
+#[derive(Debug, Default)]
+struct Occurrences {
+    /// All the occurrences, but the ones in the storage statements
+    all: usize,
+    /// The assignments to the whole local
+    assigns: usize,
+    /// The moves of the whole local
+    moves: usize,
+    /// The drops of the whole local
+    drops: usize,
+}
+
+struct CountOccurrences {
+    occurrences: HashMap<VarId::Id, Occurrences>,
+}
+
+impl SharedTypeVisitor for CountOccurrences {}
+impl SharedExprVisitor for CountOccurrences {
+    fn visit_var_id(&mut self, vid: &VarId::Id) {
+        self.occurrences.entry(*vid).or_default().all += 1;
+    }
+
+    fn visit_move(&mut self, p: &Place) {
+        if p.projection.is_empty() {
+            self.occurrences.entry(p.var_id).or_default().moves += 1;
+        }
+        self.visit_place(p)
+    }
+}
+
+impl SharedAstVisitor for CountOccurrences {
+    fn spawn(&mut self, visitor: &mut dyn FnMut(&mut Self)) {
+        visitor(self)
+    }
+
+    fn merge(&mut self) {}
+
+    fn visit_assign(&mut self, p: &Place, rv: &Rvalue) {
+        if p.projection.is_empty() {
+            self.occurrences.entry(p.var_id).or_default().assigns += 1;
+        }
+        self.visit_place(p);
+        self.visit_rvalue(rv)
+    }
+
+    fn visit_drop(&mut self, p: &Place) {
+        if p.projection.is_empty() {
+            self.occurrences.entry(p.var_id).or_default().drops += 1;
+        }
+        self.visit_place(p)
+    }
+
+    fn visit_storage_live(&mut self, _: &VarId::Id) {}
+
+    fn visit_storage_dead(&mut self, _: &VarId::Id) {}
+}
+
+/// Compute the temporaries we may inline: the locals without names (which are
+/// neither the return value nor an input), which are assigned and moved once,
+/// and otherwise only dropped.
+fn compute_temporaries(b: &ExprBody) -> HashSet<VarId::Id> {
+    let mut visitor = CountOccurrences {
+        occurrences: HashMap::new(),
+    };
+    visitor.visit_block(&b.body);
+    visitor
+        .occurrences
+        .into_iter()
+        .filter(|(vid, occ)| {
+            vid.index() > b.arg_count
+                && b.locals.get(*vid).unwrap().name.is_none()
+                && occ.assigns == 1
+                && occ.moves == 1
+                && occ.all == occ.assigns + occ.moves + occ.drops
+        })
+        .map(|(vid, _)| vid)
+        .collect()
+}
+
+/// The rvalues we inline: the ones which don't have side effects (they may
+/// panic, though).
+fn is_inlinable(rv: &Rvalue) -> bool {
+    matches!(
+        rv,
+        Rvalue::Use(_)
+            | Rvalue::Ref(..)
+            | Rvalue::UnaryOp(..)
+            | Rvalue::BinaryOp(..)
+            | Rvalue::NullaryOp(..)
+    )
+}
+
+/// Push the operands of an operand, in evaluation order (we dive into the
+/// nested expressions).
+fn push_operand<'a>(op: &'a Operand, ops: &mut Vec<&'a Operand>) {
+    match op {
+        Operand::Expr(rv, _) => push_rvalue_operands(rv, ops),
+        Operand::Copy(_) | Operand::Move(_) | Operand::Const(_) => ops.push(op),
+    }
+}
+
+/// Push the operands of an rvalue, in evaluation order.
+fn push_rvalue_operands<'a>(rv: &'a Rvalue, ops: &mut Vec<&'a Operand>) {
+    match rv {
+        Rvalue::Use(op) | Rvalue::UnaryOp(_, op) | Rvalue::Repeat(op, ..) => push_operand(op, ops),
+        Rvalue::BinaryOp(_, op1, op2) => {
+            push_operand(op1, ops);
+            push_operand(op2, ops);
+        }
+        Rvalue::Aggregate(_, args) => args.iter().for_each(|op| push_operand(op, ops)),
+        Rvalue::Ref(..)
+        | Rvalue::Discriminant(..)
+        | Rvalue::Global(..)
+        | Rvalue::Len(..)
+        | Rvalue::NullaryOp(..) => (),
+    }
+}
+
+/// The operands of a statement, in evaluation order. We ignore the operands of
+/// the statements which contain other statements.
+fn statement_operands(st: &RawStatement) -> Vec<&Operand> {
+    let mut ops = Vec::new();
+    match st {
+        RawStatement::Assign(_, rv) => push_rvalue_operands(rv, &mut ops),
+        RawStatement::Call(call) => call.args.iter().for_each(|op| push_operand(op, &mut ops)),
+        RawStatement::Assert(assert) => push_operand(&assert.cond, &mut ops),
+        RawStatement::MemCopy(copy) => {
+            for op in [&copy.src, &copy.dst, &copy.count] {
+                push_operand(op, &mut ops)
+            }
+        }
+        RawStatement::WriteBytes(write) => {
+            for op in [&write.dst, &write.val, &write.count] {
+                push_operand(op, &mut ops)
+            }
+        }
+        RawStatement::Intrinsic(intrinsic) => {
+            for op in &intrinsic.args {
+                push_operand(op, &mut ops)
+            }
+        }
+        RawStatement::Switch(Switch::If(op, ..) | Switch::SwitchInt(op, ..)) => {
+            push_operand(op, &mut ops)
+        }
+        RawStatement::FakeRead(_)
+        | RawStatement::SetDiscriminant(..)
+        | RawStatement::Drop(_)
+        | RawStatement::StorageLive(_)
+        | RawStatement::StorageDead(_)
+        | RawStatement::Panic
+        | RawStatement::Return
+        | RawStatement::Break(..)
+        | RawStatement::Continue(..)
+        | RawStatement::Nop
+        | RawStatement::Switch(Switch::Match(..))
+        | RawStatement::Loop(..) => (),
+    }
+    ops
+}
+
+/// Collect the variables which appear in an rvalue
+struct CollectVars {
+    vars: HashSet<VarId::Id>,
+}
+
+impl SharedTypeVisitor for CollectVars {}
+impl SharedExprVisitor for CollectVars {
+    fn visit_var_id(&mut self, vid: &VarId::Id) {
+        self.vars.insert(*vid);
+    }
+}
+
+/// Check that we can inline the definition `tmp := rv` in the statement `st`:
+/// the temporary must be one of the operands of the statement, and the operands
+/// evaluated before it must not move the variables read by `rv`.
+pub(crate) fn can_inline(tmp: VarId::Id, rv: &Rvalue, st: &RawStatement) -> bool {
+    let mut visitor = CollectVars {
+        vars: HashSet::new(),
+    };
+    visitor.visit_rvalue(rv);
+    for op in statement_operands(st) {
+        match op {
+            Operand::Move(p) if p.var_id == tmp => return true,
+            Operand::Move(p) if visitor.vars.contains(&p.var_id) => return false,
+            _ => (),
+        }
+    }
+    false
+}
+
+/// Replace the move of a temporary with its definition
+struct ReplaceMove {
+    tmp: VarId::Id,
+    expr: Option<Operand>,
+}
+
+impl MutTypeVisitor for ReplaceMove {}
+impl MutExprVisitor for ReplaceMove {
+    fn visit_operand(&mut self, op: &mut Operand) {
+        if let Operand::Move(p) = op
+            && p.var_id == self.tmp
+        {
+            *op = self.expr.take().unwrap();
+        } else {
+            self.default_visit_operand(op)
+        }
+    }
+}
+
+impl MutAstVisitor for ReplaceMove {
+    fn spawn(&mut self, visitor: &mut dyn FnMut(&mut Self)) {
+        visitor(self)
+    }
+
+    fn merge(&mut self) {}
+}
+
+/// Inline the temporaries in a block. We explore the statements from the end:
+/// in `x := a + b; y := c * d; z := move x - move y`, we first inline `y`,
+/// after which the definition of `x` is followed by its use.
+fn transform_block(
+    locals: &VarId::Vector<Var>,
+    temporaries: &HashSet<VarId::Id>,
+    inlined: &mut HashSet<VarId::Id>,
+    b: &mut Block,
+) {
+    let mut i = b.statements.len();
+    while i > 0 {
+        i -= 1;
+        let RawStatement::Assign(p, rv) = &b.statements[i].content else { continue };
+        if !p.projection.is_empty() || !temporaries.contains(&p.var_id) || !is_inlinable(rv) {
+            continue;
+        }
+        let tmp = p.var_id;
+        // The statement which follows, ignoring the `StorageLive`
+        let Some(j) = (i + 1..b.statements.len())
+            .find(|j| !b.statements[*j].content.is_storage_live())
+        else {
+            continue;
+        };
+        if !can_inline(tmp, rv, &b.statements[j].content) {
+            continue;
+        }
+
+        let def = b.statements.remove(i);
+        let RawStatement::Assign(_, rv) = def.content else { unreachable!() };
+        let ty = locals.get(tmp).unwrap().ty.clone();
+        let st = &mut b.statements[j - 1];
+        st.meta = combine_meta(&def.meta, &st.meta);
+        let mut visitor = ReplaceMove {
+            tmp,
+            expr: Some(Operand::Expr(Box::new(rv), ty)),
+        };
+        visitor.visit_statement(st);
+        // Simplify `x := (rv)` to `x := rv`
+        if let RawStatement::Assign(_, rv) = &mut st.content {
+            take(rv, |rv| match rv {
+                Rvalue::Use(Operand::Expr(rv, _)) => *rv,
+                rv => rv,
+            })
+        }
+        inlined.insert(tmp);
+    }
+}
+
+pub fn transform(ctx: &mut TransformCtx, funs: &mut FunDecls, globals: &mut GlobalDecls) {
+    ctx.iter_bodies(funs, globals, |ctx, name, b| {
+        let fmt_ctx = ctx.into_fmt();
+        trace!(
+            "# About to reconstruct the expressions in decl: {}:\n{}",
+            name.fmt_with_ctx(&fmt_ctx),
+            fmt_ctx.format_object(&*b)
+        );
+
+        let temporaries = compute_temporaries(b);
+        let mut inlined = HashSet::new();
+        let locals = &b.locals;
+        b.body.transform_blocks(&mut |block| {
+            transform_block(locals, &temporaries, &mut inlined, block)
+        });
+
+        // The inlined temporaries are moved: we remove their drops
+        b.body.transform(&mut |st| {
+            if let RawStatement::Drop(p) = &st.content
+                && p.projection.is_empty()
+                && inlined.contains(&p.var_id)
+            {
+                st.content = RawStatement::Nop;
+            }
+            None
+        });
+    })
+}
@@ -0,0 +1,110 @@
+//! Remove the dead branches of the switches whose scrutinee is a literal
+//! constant, and the statements which can't be reached. For instance:
+//! ```text
+//! if const true { s1 } else { s2 }
+//! return;
+//! s3
+//! ```
+//! becomes `s1; return`.
+//!
+//! The constant scrutinees come for instance from `cfg!(...)` (in which case the
+//! scrutinee is a temporary initialized with the constant), or from the
+//! constants propagated by [crate::transform::propagate_constants] (which
+//! calls [simplify_block] until it reaches a fixed point).
+
+use crate::formatter::{Formatter, IntoFormatter};
+use crate::llbc_ast::*;
+use crate::translate_ctx::TransformCtx;
+use crate::ullbc_to_llbc::is_terminal;
+use crate::values::*;
+
+/// The scrutinee of a switch is often a temporary, assigned by the statement
+/// just before the switch: `tmp := const false; if move tmp { ... }`. If this
+/// statement assigns a literal constant, we move the constant to the switch
+/// (the temporary is moved by the switch: it is not used afterwards).
+fn forward_constant_scrutinee(statements: &mut [Statement], i: usize) {
+    let [.., prev, st] = &mut statements[..=i] else {
+        return;
+    };
+    let RawStatement::Switch(Switch::If(op, ..) | Switch::SwitchInt(op, ..)) = &mut st.content
+    else {
+        return;
+    };
+    let RawStatement::Assign(dest, Rvalue::Use(cv)) = &prev.content else {
+        return;
+    };
+    if cv.as_literal().is_some()
+        && dest.projection.is_empty()
+        && matches!(op, Operand::Move(p) if *p == *dest)
+    {
+        *op = cv.clone();
+        prev.content = RawStatement::Nop;
+    }
+}
+
+/// The branch taken by a switch, if its scrutinee is a literal constant
+fn taken_branch(switch: &mut Switch) -> Option<&mut Block> {
+    match switch {
+        Switch::If(op, then_block, else_block) => match op.as_literal()? {
+            Literal::Bool(true) => Some(then_block),
+            Literal::Bool(false) => Some(else_block),
+            _ => None,
+        },
+        Switch::SwitchInt(op, _, targets, otherwise) => match op.as_literal()? {
+            Literal::Scalar(v) => Some(
+                targets
+                    .iter_mut()
+                    .find(|(values, _)| values.contains(v))
+                    .map_or(otherwise, |(_, block)| block),
+            ),
+            _ => None,
+        },
+        Switch::Match(..) => None,
+    }
+}
+
+/// Replace the switches on constants with the block of the branch they take,
+/// and remove the statements which follow a terminal statement (a return, a
+/// panic, a break, etc.). Return `true` if we changed the block.
+pub(crate) fn simplify_block(b: &mut Block) -> bool {
+    let mut changed = false;
+    let mut i = 0;
+    while i < b.statements.len() {
+        forward_constant_scrutinee(&mut b.statements, i);
+        let branch = match &mut b.statements[i].content {
+            RawStatement::Switch(switch) => {
+                taken_branch(switch).map(|block| std::mem::take(&mut block.statements))
+            }
+            _ => None,
+        };
+        if let Some(statements) = branch {
+            // The branch may contain breaks and continues: they refer to the
+            // loops, so we can inline its statements. Remark: the blocks are
+            // never empty.
+            b.statements.splice(i..i + 1, statements);
+            changed = true;
+            continue;
+        }
+        if is_terminal(&b.statements[i]) && i + 1 < b.statements.len() {
+            b.statements.truncate(i + 1);
+            changed = true;
+        }
+        i += 1;
+    }
+    changed
+}
+
+pub fn transform(ctx: &mut TransformCtx, funs: &mut FunDecls, globals: &mut GlobalDecls) {
+    ctx.iter_bodies(funs, globals, |ctx, name, b| {
+        let fmt_ctx = ctx.into_fmt();
+        trace!(
+            "# About to remove the dead branches in decl: {}:\n{}",
+            name.fmt_with_ctx(&fmt_ctx),
+            fmt_ctx.format_object(&*b)
+        );
+
+        b.body.transform_blocks(&mut |block| {
+            simplify_block(block);
+        });
+    })
+}
@@ -5,7 +5,7 @@
 
 use crate::formatter::{Formatter, IntoFormatter};
 use crate::llbc_ast::{FunDecls, GlobalDecls, RawStatement, Statement, Var};
-use crate::translate_ctx::TransCtx;
+use crate::translate_ctx::TransformCtx;
 use crate::values::*;
 
 /// Filter the statement by replacing it with `Nop` if it is a `Drop(x)` where
@@ -30,7 +30,7 @@ fn transform_st(locals: &VarId::Vector<Var>, st: &mut Statement) {
     }
 }
 
-pub fn transform(ctx: &mut TransCtx, funs: &mut FunDecls, globals: &mut GlobalDecls) {
+pub fn transform(ctx: &mut TransformCtx, funs: &mut FunDecls, globals: &mut GlobalDecls) {
     ctx.iter_bodies(funs, globals, |ctx, name, b| {
         let fmt_ctx = ctx.into_fmt();
         trace!(
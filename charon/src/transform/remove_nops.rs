@@ -1,26 +1,20 @@
 //! Remove the useless no-ops.
 
 use crate::formatter::{Formatter, IntoFormatter};
-use crate::llbc_ast::{FunDecls, GlobalDecls, RawStatement, Statement};
-use crate::meta::combine_meta;
-use crate::translate_ctx::TransCtx;
-use take_mut::take;
+use crate::llbc_ast::{Block, FunDecls, GlobalDecls};
+use crate::translate_ctx::TransformCtx;
 
-fn transform_st(s: &mut Statement) {
-    if let RawStatement::Sequence(s1, _) = &s.content {
-        if s1.content.is_nop() {
-            take(s, |s| {
-                let (s1, s2) = s.content.to_sequence();
-                Statement {
-                    content: s2.content,
-                    meta: combine_meta(&s1.meta, &s2.meta),
-                }
-            })
-        }
+fn transform_block(b: &mut Block) {
+    // We keep a no-op if it is the only statement of the block (blocks
+    // are never empty)
+    if b.statements.iter().all(|st| st.content.is_nop()) {
+        b.statements.truncate(1);
+    } else {
+        b.statements.retain(|st| !st.content.is_nop());
     }
 }
 
-pub fn transform(ctx: &mut TransCtx, funs: &mut FunDecls, globals: &mut GlobalDecls) {
+pub fn transform(ctx: &mut TransformCtx, funs: &mut FunDecls, globals: &mut GlobalDecls) {
     ctx.iter_bodies(funs, globals, |ctx, name, b| {
         let fmt_ctx = ctx.into_fmt();
         trace!(
@@ -29,10 +23,6 @@ pub fn transform(ctx: &mut TransCtx, funs: &mut FunDecls, globals: &mut GlobalDe
             fmt_ctx.format_object(&*b)
         );
 
-        // Compute the set of local variables
-        b.body.transform(&mut |st| {
-            transform_st(st);
-            None
-        });
+        b.body.transform_blocks(&mut transform_block);
     })
 }
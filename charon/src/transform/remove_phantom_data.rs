@@ -0,0 +1,218 @@
+//! # Micro-pass (optional): remove the fields of type `PhantomData<T>` from the
+//! structures and the enumerations (see [Field::is_phantom_data]). Those fields
+//! are zero-sized, and are only there to give the variance of the type
+//! parameters, which we record separately (see [Variances]): the backends
+//! which model the memory don't need them. This pass is enabled with
+//! `--remove-phantom-data`.
+//!
+//! We update the field projections, the aggregates, the constant values and
+//! the paths of the places captured by the closures accordingly. We keep the
+//! fields which the bodies project (like in `let p = x.phantom`) or which the
+//! closures capture, which is rare in practice.
+use crate::expressions::{MutExprVisitor, SharedExprVisitor};
+use crate::translate_ctx::TransCtx;
+use crate::types::*;
+use crate::ullbc_ast::*;
+use std::collections::{HashMap, HashSet};
+
+/// A structure (without variant), or a variant of an enumeration
+type AdtVariant = (TypeDeclId::Id, Option<VariantId::Id>);
+
+/// The new ids of the fields of the structures and variants from which we
+/// remove fields (`None` if we remove the field).
+type NewFieldIds = HashMap<AdtVariant, FieldId::Vector<Option<FieldId::Id>>>;
+
+/// Collect the fields which the bodies project, or which the closures capture
+struct CollectProjections {
+    projected: HashSet<(AdtVariant, FieldId::Id)>,
+}
+
+impl CollectProjections {
+    fn visit_captures(&mut self, info: &ClosureInfo) {
+        for capture in &info.captures {
+            for (proj, adt) in capture.projection.iter().zip(&capture.field_adts) {
+                if let (CaptureProjection::Field(field_id), Some(adt)) = (proj, adt) {
+                    self.projected.insert((*adt, *field_id));
+                }
+            }
+        }
+    }
+}
+
+impl SharedTypeVisitor for CollectProjections {}
+
+impl SharedExprVisitor for CollectProjections {
+    fn visit_projection_field(&mut self, kind: &FieldProjKind, field_id: &FieldId::Id) {
+        if let FieldProjKind::Adt(id, variant) = kind {
+            self.projected.insert(((*id, *variant), *field_id));
+        }
+    }
+}
+
+impl SharedAstVisitor for CollectProjections {}
+
+struct UpdateFields<'a> {
+    new_ids: &'a NewFieldIds,
+}
+
+impl<'a> UpdateFields<'a> {
+    /// Remove the values of the removed fields, in an aggregate or a constant
+    fn filter_fields<T>(&self, adt: AdtVariant, values: &mut Vec<T>) {
+        if let Some(new_ids) = self.new_ids.get(&adt) {
+            let mut new_ids = new_ids.iter();
+            values.retain(|_| new_ids.next().is_some_and(|id| id.is_some()));
+        }
+    }
+
+    fn visit_body(&mut self, body: &mut ExprBody) {
+        for block in body.body.iter_mut() {
+            self.visit_block_data(block);
+        }
+    }
+
+    /// Update the paths of the captured places, which are given in terms of
+    /// the fields of the parent function's variables
+    fn update_captures(&self, info: &mut ClosureInfo) {
+        for capture in info.captures.iter_mut() {
+            for (proj, adt) in capture.projection.iter_mut().zip(&capture.field_adts) {
+                if let (CaptureProjection::Field(field_id), Some(adt)) = (proj, adt)
+                    && let Some(new_ids) = self.new_ids.get(adt)
+                {
+                    // We don't remove the fields which are captured
+                    *field_id = new_ids[*field_id].unwrap();
+                }
+            }
+        }
+    }
+}
+
+impl<'a> MutTypeVisitor for UpdateFields<'a> {}
+
+impl<'a> MutExprVisitor for UpdateFields<'a> {
+    fn visit_projection_elem(&mut self, pe: &mut ProjectionElem) {
+        if let ProjectionElem::Field(FieldProjKind::Adt(id, variant), field_id) = pe
+            && let Some(new_ids) = self.new_ids.get(&(*id, *variant))
+        {
+            // We don't remove the fields which are projected
+            *field_id = new_ids[*field_id].unwrap();
+        }
+        self.default_visit_projection_elem(pe)
+    }
+
+    fn visit_aggregate(&mut self, ak: &mut AggregateKind, ops: &mut Vec<Operand>) {
+        if let AggregateKind::Adt(TypeId::Adt(id), variant, _) = ak {
+            self.filter_fields((*id, *variant), ops);
+        }
+        self.visit_aggregate_kind(ak);
+        for o in ops {
+            self.visit_operand(o)
+        }
+    }
+
+    fn visit_constant_expr(&mut self, expr: &mut ConstantExpr) {
+        if let TyKind::Adt(TypeId::Adt(id), _) = expr.ty.kind()
+            && let RawConstantExpr::Adt(variant, fields) = &mut expr.value
+        {
+            self.filter_fields((*id, *variant), fields);
+        }
+        self.visit_ty(&mut expr.ty);
+        self.visit_raw_constant_expr(&mut expr.value);
+    }
+}
+
+impl<'a> MutAstVisitor for UpdateFields<'a> {}
+
+/// Remove the `PhantomData` fields which the bodies don't project, and register
+/// the new ids of the fields.
+fn remove_fields(
+    adt: AdtVariant,
+    fields: &mut FieldId::Vector<Field>,
+    projected: &HashSet<(AdtVariant, FieldId::Id)>,
+    new_ids: &mut NewFieldIds,
+) {
+    let is_removed = |field_id: FieldId::Id, field: &Field| {
+        field.is_phantom_data && !projected.contains(&(adt, field_id))
+    };
+    if !fields
+        .iter_indexed_values()
+        .any(|(field_id, field)| is_removed(field_id, field))
+    {
+        return;
+    }
+    let mut kept_fields = FieldId::Vector::new();
+    let mut field_ids = FieldId::Vector::new();
+    for (field_id, field) in fields.iter_indexed_values() {
+        if is_removed(field_id, field) {
+            field_ids.push(None);
+        } else {
+            field_ids.push(Some(kept_fields.push(field.clone())));
+        }
+    }
+    *fields = kept_fields;
+    new_ids.insert(adt, field_ids);
+}
+
+pub fn transform(ctx: &mut TransCtx) {
+    let mut collect = CollectProjections {
+        projected: HashSet::new(),
+    };
+    let translated = &ctx.translated;
+    let fun_bodies = translated.fun_decls.iter().filter_map(|f| f.body.as_ref());
+    let global_bodies = translated
+        .global_decls
+        .iter()
+        .filter_map(|g| g.body.as_ref());
+    for body in fun_bodies.chain(global_bodies) {
+        for block in body.body.iter() {
+            collect.visit_block_data(block);
+        }
+    }
+    for f in translated.fun_decls.iter() {
+        if let Some(info) = &f.signature.closure_info {
+            collect.visit_captures(info);
+        }
+    }
+
+    let mut new_ids = NewFieldIds::new();
+    for d in ctx.translated.type_decls.iter_mut() {
+        match &mut d.kind {
+            TypeDeclKind::Struct(fields) => {
+                remove_fields((d.def_id, None), fields, &collect.projected, &mut new_ids)
+            }
+            TypeDeclKind::Enum(variants, _, layout) => {
+                for (i, variant) in variants.iter_mut().enumerate() {
+                    let adt = (d.def_id, Some(VariantId::Id::new(i)));
+                    remove_fields(adt, &mut variant.fields, &collect.projected, &mut new_ids);
+                }
+                if let Some(EnumLayout::Niche {
+                    untagged_variant,
+                    field,
+                }) = layout
+                    && let Some(field_ids) = new_ids.get(&(d.def_id, Some(*untagged_variant)))
+                {
+                    // The field which contains the niche can't be zero-sized
+                    *field = field_ids[*field].unwrap();
+                }
+            }
+            TypeDeclKind::Opaque | TypeDeclKind::ImplTrait { .. } | TypeDeclKind::Error(_) => (),
+        }
+    }
+    if new_ids.is_empty() {
+        return;
+    }
+
+    let mut visitor = UpdateFields { new_ids: &new_ids };
+    for f in ctx.translated.fun_decls.iter_mut() {
+        if let Some(info) = &mut f.signature.closure_info {
+            visitor.update_captures(info);
+        }
+        if let Some(body) = &mut f.body {
+            visitor.visit_body(body);
+        }
+    }
+    for g in ctx.translated.global_decls.iter_mut() {
+        if let Some(body) = &mut g.body {
+            visitor.visit_body(body);
+        }
+    }
+}
@@ -1,184 +1,347 @@
-//! The MIR code often contains variables with type `Never`, and we want to get
-//! rid of those. We proceed in two steps. First, we remove the instructions
-//! `drop(v)` where `v` has type `Never` (it can happen - this module does the
-//! filtering). Then, we filter the unused variables ([crate::remove_unused_locals]).
+//! Remove the discriminant reads. The MIR matches over the enumerations by
+//! reading the discriminant, then switching over its value: we merge the
+//! discriminant read and the switch into a match over the variants. The
+//! discriminant reads which are not followed by a switch (for instance when
+//! casting an enumeration to an integer) become matches which assign the
+//! discriminant of the variant. We also merge the switches over casts of the
+//! discriminants (which are common with the `#[repr(u8)]` enumerations).
 
 use crate::formatter::{Formatter, IntoFormatter};
 use crate::llbc_ast::*;
-use crate::meta::combine_meta;
+use crate::meta::{combine_meta, Meta};
 use crate::translate_ctx::*;
 use crate::types::*;
+use crate::values::*;
 use itertools::Itertools;
 use std::collections::{HashMap, HashSet};
 
-struct Visitor<'a, 'tcx, 'ctx> {
-    ctx: &'a mut TransCtx<'tcx, 'ctx>,
+struct Visitor<'a, 'b, 'ctx> {
+    ctx: &'a mut TransformCtx<'b, 'ctx>,
+    /// The local variables of the body, to retrieve the types of the
+    /// discriminants.
+    locals: &'a VarId::Vector<Var>,
+    /// The number of occurrences of the variables in the body (see [CountUses]).
+    uses: HashMap<VarId::Id, usize>,
 }
 
-impl<'a, 'tcx, 'ctx> Visitor<'a, 'tcx, 'ctx> {
-    fn update_statement(&mut self, st: &mut Statement) {
-        match &mut st.content {
-            RawStatement::Sequence(
-                box Statement {
-                    content: RawStatement::Assign(dest, Rvalue::Discriminant(p, adt_id)),
-                    meta: meta1,
-                },
-                box st2,
-            ) => {
-                // The destination should be a variable
-                assert!(dest.projection.is_empty());
-
-                // Take st2
-                let st2 = std::mem::replace(
-                    st2,
-                    Statement {
-                        content: RawStatement::Nop,
-                        meta: st2.meta,
-                    },
-                );
+impl<'a, 'b, 'ctx> Visitor<'a, 'b, 'ctx> {
+    /// Update the statement at index [i] in [statements], if it is a
+    /// discriminant read, together with the switch which follows it.
+    fn update_statements(&mut self, statements: &mut Vec<Statement>, i: usize) {
+        let Statement {
+            content: RawStatement::Assign(dest, Rvalue::Discriminant(p, adt_id)),
+            meta: meta1,
+        } = &statements[i]
+        else {
+            return;
+        };
+        let (dest, p, adt_id, meta1) = (dest.clone(), p.clone(), *adt_id, *meta1);
 
-                // A discriminant read must be immediately followed by a switch int.
-                // Note that it may be contained in a sequence, of course.
-                let (meta2, switch, st3_opt) = match st2.content {
-                    RawStatement::Sequence(
-                        box Statement {
-                            content: RawStatement::Switch(switch @ Switch::SwitchInt(..)),
-                            meta: meta2,
-                        },
-                        box st3,
-                    ) => (meta2, switch, Some(st3)),
-                    RawStatement::Switch(switch @ Switch::SwitchInt(..)) => {
-                        (st2.meta, switch, None)
-                    }
-                    _ => {
+        // Lookup the type of the scrutinee
+        let variants = match self.ctx.translated.type_decls.get(adt_id) {
+            // This can happen if there was an error while extracting the definitions
+            None => None,
+            Some(d) => {
+                match &d.kind {
+                    TypeDeclKind::Struct(_)
+                    | TypeDeclKind::Opaque
+                    | TypeDeclKind::ImplTrait { .. } => {
+                        // We shouldn't get there
                         register_error_or_panic!(
                             self.ctx,
-                            st.meta.span.rust_span_data.span(),
-                            "A discriminant read must be followed by a `SwitchInt`"
+                            meta1.span.rust_span_data.span(),
+                            "Unreachable case"
                         );
-                        // An error occurred. We can't keep the `Rvalue::Discriminant` around so we
-                        // `Nop` the whole statement sequence.
-                        // FIXME: add `RawStatement::Error` for cases like this.
-                        *st = Statement {
-                            content: RawStatement::Nop,
-                            meta: st.meta,
-                        };
-                        return;
+                        None
                     }
-                };
-
-                let Switch::SwitchInt(Operand::Move(op_p), _int_ty, targets, otherwise) = switch
-                else { unreachable!() };
-                assert!(op_p.projection.is_empty() && op_p.var_id == dest.var_id);
-
-                // Lookup the type of the scrutinee
-                let variants = match self.ctx.type_decls.get(*adt_id) {
-                    // This can happen if there was an error while extracting the definitions
-                    None => None,
-                    Some(d) => {
-                        match &d.kind {
-                            TypeDeclKind::Struct(_) | TypeDeclKind::Opaque => {
-                                // We shouldn't get there
-                                register_error_or_panic!(
-                                    self.ctx,
-                                    st.meta.span.rust_span_data.span(),
-                                    "Unreachable case"
-                                );
-                                None
-                            }
-                            TypeDeclKind::Error(_) => None,
-                            TypeDeclKind::Enum(variants) => Some(variants),
-                        }
-                    }
-                };
-                let Some(variants) = variants else {
-                    // An error occurred. We can't keep the `Rvalue::Discriminant` around so we
-                    // `Nop` the whole statement sequence.
-                    assert!(self.ctx.error_count > 0);
-                    *st = Statement {
-                        content: RawStatement::Nop,
-                        meta: st.meta,
-                    };
-                    return
-                };
-
-                // Convert between discriminants and variant indices. Remark: the discriminant can
-                // be of any *signed* integer type (`isize`, `i8`, etc.).
-                let discr_to_id: HashMap<u128, VariantId::Id> = variants
-                    .iter_indexed_values()
-                    .map(|(id, variant)| (variant.discriminant, id))
-                    .collect();
-                let mut covered_discriminants: HashSet<u128> = HashSet::default();
-                let targets = targets
-                    .into_iter()
-                    .map(|(v, e)| {
-                        (
-                            v.into_iter()
-                                .filter_map(|x| {
-                                    let discr = x.to_bits();
-                                    covered_discriminants.insert(discr);
-                                    discr_to_id.get(&discr).or_else(|| {
-                                        register_error_or_panic!(
-                                            self.ctx,
-                                            st.meta.span.rust_span_data.span(),
-                                            "Found incorrect discriminant {discr} for enum {adt_id}"
-                                        );
-                                        None
-                                    })
-                                })
-                                .copied()
-                                .collect_vec(),
-                            e,
-                        )
-                    })
-                    .collect_vec();
-                // Filter the otherwise branch if it is not necessary.
-                let covers_all = covered_discriminants.len() == discr_to_id.len();
-                let otherwise = if covers_all { None } else { Some(otherwise) };
-
-                let switch = RawStatement::Switch(Switch::Match(p.clone(), targets, otherwise));
-
-                // Add the next statement if there is one
-                st.content = if let Some(st3) = st3_opt {
-                    let meta = combine_meta(meta1, &meta2);
-                    let switch = Statement {
-                        meta,
-                        content: switch,
-                    };
-                    new_sequence(switch, st3).content
-                } else {
-                    switch
-                };
+                    TypeDeclKind::Error(_) => None,
+                    TypeDeclKind::Enum(variants, ..) => Some(variants),
+                }
             }
-            RawStatement::Assign(_, Rvalue::Discriminant(_, _)) => {
-                // We failed to remove a [Discriminant]
-                unreachable!();
+        };
+        let Some(variants) = variants else {
+            // An error occurred. We can't keep the `Rvalue::Discriminant` around so we
+            // `Nop` the remaining statements of the block.
+            assert!(self.ctx.errors.error_count > 0);
+            statements.truncate(i);
+            statements.push(Statement::new(meta1, RawStatement::Nop));
+            return
+        };
+
+        // The discriminant read is usually immediately followed by a switch
+        // over the discriminant, or by a cast of the discriminant to another
+        // integer type and a switch over the result of the cast (for instance
+        // in `match e as u8 { ... }`): we merge them.
+        let switch = if is_switch_over(statements.get(i + 1), &dest) {
+            let st2 = statements.remove(i + 1);
+            self.merge_with_switch(st2, p, adt_id, variants, meta1, false)
+        } else if let Some(cast_dest) = cast_of(statements.get(i + 1), &dest)
+            && let Some(j) = self.switch_after_cast(statements, i + 2, &cast_dest)
+        {
+            // Remove the cast, the fake reads in between and the switch
+            let st2 = statements.remove(j);
+            statements.drain(i + 1..j);
+            self.merge_with_switch(st2, p, adt_id, variants, meta1, true)
+        } else {
+            match self.read_discriminant(dest, p, variants, meta1) {
+                Some(switch) => switch,
+                None => {
+                    // An error occurred: see above
+                    statements.truncate(i);
+                    statements.push(Statement::new(meta1, RawStatement::Nop));
+                    return;
+                }
             }
-            _ => (),
+        };
+        statements[i] = switch;
+    }
+
+    /// Find the switch over the result of a cast of a discriminant, starting
+    /// at index `i` and skipping the fake reads of the result. The switch may
+    /// copy the result rather than move it (this happens in the matches over
+    /// casts, like `match e as u8 { ... }`), in which case we check that the
+    /// result is not used anywhere else.
+    fn switch_after_cast(&self, statements: &[Statement], i: usize, var: &Place) -> Option<usize> {
+        let j = i + statements[i..]
+            .iter()
+            .take_while(|st| matches!(&st.content, RawStatement::FakeRead(p) if p == var))
+            .count();
+        if is_switch_over(statements.get(j), var)
+            || (matches!(
+                statements.get(j),
+                Some(Statement {
+                    content: RawStatement::Switch(Switch::SwitchInt(Operand::Copy(op_p), ..)),
+                    ..
+                }) if op_p == var
+            ) && self.uses.get(&var.var_id) == Some(&2))
+        {
+            Some(j)
+        } else {
+            None
+        }
+    }
+
+    /// Replace a discriminant read followed by a switch over the discriminant
+    /// with a match over the variants. `cast` is true if the switch is over a
+    /// cast of the discriminant to another integer type.
+    fn merge_with_switch(
+        &mut self,
+        st2: Statement,
+        p: Place,
+        adt_id: TypeDeclId::Id,
+        variants: &VariantId::Vector<Variant>,
+        meta1: Meta,
+        cast: bool,
+    ) -> Statement {
+        let meta2 = st2.meta;
+        let RawStatement::Switch(Switch::SwitchInt(_, int_ty, targets, otherwise)) = st2.content
+        else { unreachable!() };
+
+        // Convert between discriminants and variant indices. The discriminants have the
+        // discriminant type of the enumeration, and we compare them with the values of the
+        // switch as typed values. Several variants may have the same discriminant once cast
+        // to a smaller type.
+        let mut discr_to_ids: HashMap<ScalarValue, Vec<VariantId::Id>> = HashMap::new();
+        for (id, variant) in variants.iter_indexed_values() {
+            let discr = if cast {
+                cast_scalar(variant.discriminant, int_ty)
+            } else {
+                variant.discriminant
+            };
+            discr_to_ids.entry(discr).or_default().push(id);
         }
+        let mut covered_variants: HashSet<VariantId::Id> = HashSet::default();
+        let targets = targets
+            .into_iter()
+            .map(|(v, e)| {
+                (
+                    v.into_iter()
+                        .flat_map(|discr| {
+                            match discr_to_ids.get(&discr) {
+                                Some(ids) => ids.clone(),
+                                // The result of a cast may not be the discriminant of
+                                // any variant: the branch is unreachable
+                                None if cast => Vec::new(),
+                                None => {
+                                    register_error_or_panic!(
+                                        self.ctx,
+                                        meta1.span.rust_span_data.span(),
+                                        "Found incorrect discriminant {discr} for enum {adt_id}"
+                                    );
+                                    Vec::new()
+                                }
+                            }
+                        })
+                        .inspect(|id| {
+                            covered_variants.insert(*id);
+                        })
+                        .collect_vec(),
+                    e,
+                )
+            })
+            .filter(|(ids, _)| !cast || !ids.is_empty())
+            .collect_vec();
+        // Filter the otherwise branch if it is not necessary.
+        let covers_all = covered_variants.len() == variants.len();
+        let otherwise = if covers_all { None } else { Some(otherwise) };
+
+        // Replace the discriminant read with the match
+        let switch = RawStatement::Switch(Switch::Match(p, targets, otherwise));
+        Statement::new(combine_meta(&meta1, &meta2), switch)
+    }
+
+    /// Replace a discriminant read which is not followed by a switch (for
+    /// instance, because the discriminant is compared or cast to an integer)
+    /// with a match over the variants, which assigns the discriminant of the
+    /// variant in every branch:
+    /// ```text
+    /// dest = discriminant(p);
+    ///
+    ///   ~~>
+    ///
+    /// match p {
+    ///   V0 => dest = discr0,
+    ///   V1 => dest = discr1,
+    ///   ...
+    /// }
+    /// ```
+    fn read_discriminant(
+        &mut self,
+        dest: Place,
+        p: Place,
+        variants: &VariantId::Vector<Variant>,
+        meta: Meta,
+    ) -> Option<Statement> {
+        // Retrieve the type of the discriminant
+        let int_ty = match self.locals.get(dest.var_id).map(|v| v.ty.kind()) {
+            Some(TyKind::Literal(LiteralTy::Integer(int_ty))) if dest.projection.is_empty() => {
+                *int_ty
+            }
+            _ => {
+                register_error_or_panic!(
+                    self.ctx,
+                    meta.span.rust_span_data.span(),
+                    "The result of a discriminant read must be stored in an integer variable"
+                );
+                return None;
+            }
+        };
+        let ty = TyKind::Literal(LiteralTy::Integer(int_ty)).into_ty();
+        let targets = variants
+            .iter_indexed_values()
+            .map(|(id, variant)| {
+                let discr = cast_scalar(variant.discriminant, int_ty);
+                let value = Operand::Const(ConstantExpr {
+                    value: RawConstantExpr::Literal(Literal::Scalar(discr)),
+                    ty: ty.clone(),
+                });
+                let assign = RawStatement::Assign(dest.clone(), Rvalue::Use(value));
+                let block = Block::new(meta, vec![Statement::new(meta, assign)]);
+                (vec![id], block)
+            })
+            .collect_vec();
+        let switch = RawStatement::Switch(Switch::Match(p, targets, None));
+        Some(Statement::new(meta, switch))
     }
 }
 
-impl<'a, 'tcx, 'ctx> MutTypeVisitor for Visitor<'a, 'tcx, 'ctx> {}
-impl<'a, 'tcx, 'ctx> MutExprVisitor for Visitor<'a, 'tcx, 'ctx> {}
-impl<'a, 'tcx, 'ctx> MutAstVisitor for Visitor<'a, 'tcx, 'ctx> {
+/// Count the occurrences of the variables in a body, ignoring the drops and the
+/// fake reads.
+struct CountUses {
+    uses: HashMap<VarId::Id, usize>,
+}
+
+impl SharedTypeVisitor for CountUses {}
+impl SharedExprVisitor for CountUses {
+    fn visit_var_id(&mut self, id: &VarId::Id) {
+        *self.uses.entry(*id).or_default() += 1
+    }
+}
+impl SharedAstVisitor for CountUses {
     fn spawn(&mut self, visitor: &mut dyn FnMut(&mut Self)) {
         visitor(self)
     }
 
     fn merge(&mut self) {}
 
-    fn visit_statement(&mut self, st: &mut Statement) {
-        self.update_statement(st);
+    fn visit_drop(&mut self, _: &Place) {}
+
+    fn visit_fake_read(&mut self, _: &Place) {}
+}
+
+/// Check if a statement is a switch over the (moved) variable `var`.
+fn is_switch_over(st: Option<&Statement>, var: &Place) -> bool {
+    var.projection.is_empty()
+        && matches!(
+            st,
+            Some(Statement {
+                content: RawStatement::Switch(Switch::SwitchInt(Operand::Move(op_p), ..)),
+                ..
+            }) if op_p.projection.is_empty() && op_p.var_id == var.var_id
+        )
+}
+
+/// If a statement casts the (moved) variable `var` to another integer type,
+/// return the destination of the cast.
+fn cast_of(st: Option<&Statement>, var: &Place) -> Option<Place> {
+    let Some(Statement {
+        content:
+            RawStatement::Assign(
+                dest,
+                Rvalue::UnaryOp(
+                    UnOp::Cast(CastKind::Scalar(
+                        LiteralTy::Integer(_),
+                        LiteralTy::Integer(_),
+                    )),
+                    Operand::Move(op_p),
+                ),
+            ),
+        ..
+    }) = st
+    else {
+        return None;
+    };
+    if var.projection.is_empty() && op_p.projection.is_empty() && op_p.var_id == var.var_id {
+        Some(dest.clone())
+    } else {
+        None
+    }
+}
+
+/// Cast a discriminant to the integer type `tgt_ty`, with the semantics of
+/// the `as` casts.
+fn cast_scalar(discr: ScalarValue, tgt_ty: IntegerTy) -> ScalarValue {
+    // Sign-extend or zero-extend the value, then truncate it
+    let bits = if discr.is_int() {
+        discr.as_int().unwrap() as u128
+    } else {
+        discr.as_uint().unwrap()
+    };
+    ScalarValue::from_le_bytes(tgt_ty, bits.to_le_bytes())
+}
+
+impl<'a, 'b, 'ctx> MutTypeVisitor for Visitor<'a, 'b, 'ctx> {}
+impl<'a, 'b, 'ctx> MutExprVisitor for Visitor<'a, 'b, 'ctx> {}
+impl<'a, 'b, 'ctx> MutAstVisitor for Visitor<'a, 'b, 'ctx> {
+    fn spawn(&mut self, visitor: &mut dyn FnMut(&mut Self)) {
+        visitor(self)
+    }
+
+    fn merge(&mut self) {}
+
+    fn visit_block(&mut self, b: &mut Block) {
+        let mut i = 0;
+        while i < b.statements.len() {
+            self.update_statements(&mut b.statements, i);
+            i += 1;
+        }
 
-        // Visit again, to make sure we transform the branches and
-        // the next statement, in case we updated, or to update the
-        // sub-statements, in case we didn't perform any updates.
-        self.default_visit_statement(st);
+        // Visit the sub-statements, to make sure we transform the branches
+        self.default_visit_block(b);
     }
 }
 
-pub fn transform(ctx: &mut TransCtx, funs: &mut FunDecls, globals: &mut GlobalDecls) {
+pub fn transform(ctx: &mut TransformCtx, funs: &mut FunDecls, globals: &mut GlobalDecls) {
     ctx.iter_bodies(funs, globals, |ctx, name, b| {
         let fmt_ctx = ctx.into_fmt();
         trace!(
@@ -187,7 +350,15 @@ pub fn transform(ctx: &mut TransCtx, funs: &mut FunDecls, globals: &mut GlobalDe
             fmt_ctx.format_object(&*b)
         );
 
-        let mut visitor = Visitor { ctx };
-        visitor.visit_statement(&mut b.body);
+        let mut count_uses = CountUses {
+            uses: HashMap::new(),
+        };
+        count_uses.visit_block(&b.body);
+        let mut visitor = Visitor {
+            ctx,
+            locals: &b.locals,
+            uses: count_uses.uses,
+        };
+        visitor.visit_block(&mut b.body);
     })
 }
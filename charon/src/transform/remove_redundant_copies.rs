@@ -0,0 +1,171 @@
+//! Remove the redundant reborrows and copies (unless the option
+//! `--keep-redundant-copies` is set). For instance:
+//! ```text
+//! tmp := &*x; // x : &T
+//! f(move tmp);
+//! y := copy z;
+//! g(move y);
+//! ```
+//! becomes `f(copy x); g(copy z)`.
+//!
+//! We only remove the definition of a local which is assigned once, moved
+//! once by the statement which immediately follows the definition (ignoring the
+//! `StorageLive`), and otherwise only dropped. We use the same conditions as
+//! [crate::transform::reconstruct_expressions] to preserve the evaluation order.
+//! We remove:
+//! - the copies and the moves (`tmp := copy x` and `tmp := move x`)
+//! - the shared reborrows of shared references (`tmp := &*x`), which we
+//!   replace with copies of the references
+//! - the mutable reborrows of mutable references (`tmp := &mut *x`), if the
+//!   reference is not used elsewhere: we replace them with moves of the
+//!   references
+//!
+//! Remark: the receiver adjustments of the method calls may then refer to
+//! reborrows we removed.
+//!
+//! The locals we removed become unused: they are removed by
+//! [crate::transform::remove_unused_locals].
+
+use crate::expressions::MutExprVisitor;
+use crate::formatter::{Formatter, IntoFormatter};
+use crate::llbc_ast::*;
+use crate::meta_utils::combine_meta;
+use crate::transform::propagate_constants::{count_occurrences, Occurrences};
+use crate::transform::reconstruct_expressions::can_inline;
+use crate::translate_ctx::TransformCtx;
+use crate::types::{MutTypeVisitor, RefKind, TyKind};
+use crate::values::*;
+use std::collections::{HashMap, HashSet};
+
+/// The operand we can use in place of a local defined with `rv`, if the
+/// definition is a redundant reborrow or copy.
+fn replacement(
+    locals: &VarId::Vector<Var>,
+    occurrences: &HashMap<VarId::Id, Occurrences>,
+    rv: &Rvalue,
+) -> Option<Operand> {
+    match rv {
+        Rvalue::Use(op @ (Operand::Copy(_) | Operand::Move(_))) => Some(op.clone()),
+        Rvalue::Ref(p, bk) if p.projection == [ProjectionElem::Deref] => {
+            let x = p.var_id;
+            match (locals.get(x)?.ty.kind(), bk) {
+                (TyKind::Ref(_, _, RefKind::Shared), BorrowKind::Shared) => {
+                    Some(Operand::Copy(Place::new(x)))
+                }
+                // We can move the reference only if it is not used elsewhere
+                (TyKind::Ref(_, _, RefKind::Mut), BorrowKind::Mut | BorrowKind::TwoPhaseMut) => {
+                    let occ = occurrences.get(&x)?;
+                    (occ.all == occ.drops + 1).then(|| Operand::Move(Place::new(x)))
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Replace the move of a local with an operand
+struct ReplaceMove {
+    var: VarId::Id,
+    op: Option<Operand>,
+}
+
+impl MutTypeVisitor for ReplaceMove {}
+impl MutExprVisitor for ReplaceMove {
+    fn visit_operand(&mut self, op: &mut Operand) {
+        if let Operand::Move(p) = op
+            && p.var_id == self.var
+        {
+            *op = self.op.take().unwrap();
+        } else {
+            self.default_visit_operand(op)
+        }
+    }
+}
+
+impl MutAstVisitor for ReplaceMove {
+    fn spawn(&mut self, visitor: &mut dyn FnMut(&mut Self)) {
+        visitor(self)
+    }
+
+    fn merge(&mut self) {}
+}
+
+/// Remove the redundant definitions in a block. We explore the statements
+/// from the end, so that we can remove chains of copies.
+fn transform_block(
+    arg_count: usize,
+    locals: &VarId::Vector<Var>,
+    occurrences: &HashMap<VarId::Id, Occurrences>,
+    removed: &mut HashSet<VarId::Id>,
+    b: &mut Block,
+) {
+    let mut i = b.statements.len();
+    while i > 0 {
+        i -= 1;
+        let RawStatement::Assign(p, rv) = &b.statements[i].content else {
+            continue;
+        };
+        let var = p.var_id;
+        let Some(occ) = occurrences.get(&var) else {
+            continue;
+        };
+        if !p.projection.is_empty()
+            || var.index() <= arg_count
+            || occ.assigns != 1
+            || occ.reads != 1
+            || occ.all != occ.assigns + occ.reads + occ.drops
+        {
+            continue;
+        }
+        let Some(op) = replacement(locals, occurrences, rv) else {
+            continue;
+        };
+        // The statement which follows, ignoring the `StorageLive`
+        let Some(j) =
+            (i + 1..b.statements.len()).find(|j| !b.statements[*j].content.is_storage_live())
+        else {
+            continue;
+        };
+        if !can_inline(var, rv, &b.statements[j].content) {
+            continue;
+        }
+
+        let def = b.statements.remove(i);
+        let st = &mut b.statements[j - 1];
+        st.meta = combine_meta(&def.meta, &st.meta);
+        let mut visitor = ReplaceMove { var, op: Some(op) };
+        visitor.visit_statement(st);
+        removed.insert(var);
+    }
+}
+
+pub fn transform(ctx: &mut TransformCtx, funs: &mut FunDecls, globals: &mut GlobalDecls) {
+    ctx.iter_bodies(funs, globals, |ctx, name, b| {
+        let fmt_ctx = ctx.into_fmt();
+        trace!(
+            "# About to remove the redundant reborrows and copies in decl: {}:\n{}",
+            name.fmt_with_ctx(&fmt_ctx),
+            fmt_ctx.format_object(&*b)
+        );
+
+        let occurrences = count_occurrences(&b.body);
+        let mut removed = HashSet::new();
+        let (arg_count, locals) = (b.arg_count, &b.locals);
+        b.body.transform_blocks(&mut |block| {
+            transform_block(arg_count, locals, &occurrences, &mut removed, block)
+        });
+
+        // The locals we removed are not initialized anymore: we remove their
+        // drops
+        b.body.transform(&mut |st| {
+            if let RawStatement::Drop(p) = &st.content
+                && p.projection.is_empty()
+                && removed.contains(&p.var_id)
+            {
+                st.content = RawStatement::Nop;
+            }
+            None
+        });
+    })
+}
@@ -0,0 +1,143 @@
+//! Remove the assignments of `()` to the locals of type unit, and the copies
+//! of those locals. For instance:
+//! ```text
+//! tmp1 := ();
+//! tmp2 := f();
+//! x := move tmp1;
+//! _0 := move tmp2;
+//! ```
+//! becomes `tmp2 := f(); x := (); _0 := ()`.
+//!
+//! We only transform the locals (which are neither the return value nor an
+//! input) which are only assigned, used as the destination of calls (we keep
+//! the calls, as they may have side effects), copied or moved by assignments,
+//! or dropped: those locals always contain `()`, and they are never borrowed.
+//! The locals which are not the destination of a call become unused: they are
+//! removed by [crate::transform::remove_unused_locals].
+
+use crate::expressions::{MutExprVisitor, SharedExprVisitor};
+use crate::formatter::{Formatter, IntoFormatter};
+use crate::llbc_ast::*;
+use crate::translate_ctx::TransformCtx;
+use crate::types::{GenericArgs, MutTypeVisitor, SharedTypeVisitor, TypeId};
+use crate::values::*;
+use std::collections::{HashMap, HashSet};
+
+/// The local copied or moved by an rvalue, if the rvalue is a copy or a move
+/// of a whole local
+fn read_local(rv: &Rvalue) -> Option<VarId::Id> {
+    match rv {
+        Rvalue::Use(Operand::Copy(p) | Operand::Move(p)) if p.projection.is_empty() => {
+            Some(p.var_id)
+        }
+        _ => None,
+    }
+}
+
+/// Count, for every local, all its occurrences (but the ones in the storage
+/// statements), and the ones we can remove.
+struct CountOccurrences {
+    occurrences: HashMap<VarId::Id, (usize, usize)>,
+}
+
+impl CountOccurrences {
+    fn removable(&mut self, p: &Place) {
+        if p.projection.is_empty() {
+            self.occurrences.entry(p.var_id).or_default().1 += 1;
+        }
+    }
+}
+
+impl SharedTypeVisitor for CountOccurrences {}
+impl SharedExprVisitor for CountOccurrences {
+    fn visit_var_id(&mut self, vid: &VarId::Id) {
+        self.occurrences.entry(*vid).or_default().0 += 1;
+    }
+
+    fn visit_call(&mut self, c: &Call) {
+        self.removable(&c.dest);
+        self.visit_fn_operand(&c.func);
+        for o in &c.args {
+            self.visit_operand(o);
+        }
+        self.visit_place(&c.dest)
+    }
+}
+
+impl SharedAstVisitor for CountOccurrences {
+    fn spawn(&mut self, visitor: &mut dyn FnMut(&mut Self)) {
+        visitor(self)
+    }
+
+    fn merge(&mut self) {}
+
+    fn visit_assign(&mut self, p: &Place, rv: &Rvalue) {
+        self.removable(p);
+        if let Some(vid) = read_local(rv) {
+            self.occurrences.entry(vid).or_default().1 += 1;
+        }
+        self.visit_place(p);
+        self.visit_rvalue(rv)
+    }
+
+    fn visit_drop(&mut self, p: &Place) {
+        self.removable(p);
+        self.visit_place(p)
+    }
+
+    fn visit_storage_live(&mut self, _: &VarId::Id) {}
+
+    fn visit_storage_dead(&mut self, _: &VarId::Id) {}
+}
+
+/// Compute the unit locals we can remove
+fn compute_unit_locals(b: &ExprBody) -> HashSet<VarId::Id> {
+    let mut visitor = CountOccurrences {
+        occurrences: HashMap::new(),
+    };
+    visitor.visit_block(&b.body);
+    visitor
+        .occurrences
+        .into_iter()
+        .filter(|(vid, (all, removable))| {
+            vid.index() > b.arg_count
+                && b.locals.get(*vid).unwrap().ty.is_unit()
+                && all == removable
+        })
+        .map(|(vid, _)| vid)
+        .collect()
+}
+
+fn transform_st(locals: &HashSet<VarId::Id>, st: &mut Statement) {
+    let is_removed = |p: &Place| p.projection.is_empty() && locals.contains(&p.var_id);
+    match &mut st.content {
+        // The rvalues of type unit don't have side effects
+        RawStatement::Assign(p, _) | RawStatement::Drop(p) if is_removed(p) => {
+            st.content = RawStatement::Nop
+        }
+        RawStatement::Assign(_, rv) if read_local(rv).is_some_and(|vid| locals.contains(&vid)) => {
+            *rv = Rvalue::Aggregate(
+                AggregateKind::Adt(TypeId::Tuple, None, GenericArgs::empty()),
+                Vec::new(),
+            )
+        }
+        _ => (),
+    }
+}
+
+pub fn transform(ctx: &mut TransformCtx, funs: &mut FunDecls, globals: &mut GlobalDecls) {
+    ctx.iter_bodies(funs, globals, |ctx, name, b| {
+        let fmt_ctx = ctx.into_fmt();
+        trace!(
+            "# About to remove the unit locals in decl: {}:\n{}",
+            name.fmt_with_ctx(&fmt_ctx),
+            fmt_ctx.format_object(&*b)
+        );
+
+        let locals = compute_unit_locals(b);
+        b.body.transform(&mut |st| {
+            transform_st(&locals, st);
+            None
+        });
+    })
+}
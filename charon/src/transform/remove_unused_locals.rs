@@ -2,13 +2,21 @@
 //! never used in the function bodies.  This is useful to remove the locals with
 //! type `Never`. We actually check that there are no such local variables
 //! remaining afterwards.
+//!
+//! The `StorageLive` and `StorageDead` statements (see the option
+//! `--keep-storage-statements`) don't count as uses: we remove the ones which
+//! refer to the locals we remove.
 use crate::expressions::{MutExprVisitor, SharedExprVisitor};
 use crate::formatter::{Formatter, IntoFormatter};
-use crate::llbc_ast::{FunDecls, GlobalDecls, MutAstVisitor, SharedAstVisitor, Statement};
-use crate::translate_ctx::TransCtx;
+use crate::gast_utils::{iter_function_bodies, iter_global_bodies};
+use crate::llbc_ast::{
+    Block, FunDecls, GlobalDecls, MutAstVisitor, RawStatement, SharedAstVisitor,
+};
+use crate::translate_ctx::TransformCtx;
 use crate::types::{MutTypeVisitor, SharedTypeVisitor};
 use crate::ullbc_ast::Var;
 use crate::values::*;
+use rustc_hir::def_id::DefId;
 use std::collections::{HashMap, HashSet};
 use take_mut::take;
 
@@ -24,9 +32,9 @@ impl ComputeUsedLocals {
         }
     }
 
-    pub(crate) fn compute_in_statement(st: &Statement) -> im::HashMap<VarId::Id, usize> {
+    pub(crate) fn compute_in_block(b: &Block) -> im::HashMap<VarId::Id, usize> {
         let mut visitor = Self::new();
-        visitor.visit_statement(st);
+        visitor.visit_block(b);
         visitor.vars
     }
 }
@@ -49,6 +57,11 @@ impl SharedAstVisitor for ComputeUsedLocals {
     }
 
     fn merge(&mut self) {}
+
+    /// The storage statements are not uses
+    fn visit_storage_live(&mut self, _: &VarId::Id) {}
+
+    fn visit_storage_dead(&mut self, _: &VarId::Id) {}
 }
 
 #[derive(Debug, Clone)]
@@ -57,9 +70,9 @@ struct UpdateUsedLocals {
 }
 
 impl UpdateUsedLocals {
-    fn update_statement(vids_map: HashMap<VarId::Id, VarId::Id>, st: &mut Statement) {
+    fn update_block(vids_map: HashMap<VarId::Id, VarId::Id>, b: &mut Block) {
         let mut v = UpdateUsedLocals { vids_map };
-        v.visit_statement(st);
+        v.visit_block(b);
     }
 }
 
@@ -76,6 +89,21 @@ impl MutAstVisitor for UpdateUsedLocals {
     }
 
     fn merge(&mut self) {}
+
+    fn visit_raw_statement(&mut self, st: &mut RawStatement) {
+        // Remove the storage statements of the locals we removed
+        let removed = match st {
+            RawStatement::StorageLive(vid) | RawStatement::StorageDead(vid) => {
+                !self.vids_map.contains_key(vid)
+            }
+            _ => false,
+        };
+        if removed {
+            *st = RawStatement::Nop;
+        } else {
+            self.default_visit_raw_statement(st);
+        }
+    }
 }
 
 /// Compute the set of used locals, filter the unused locals and compute a new
@@ -83,7 +111,7 @@ impl MutAstVisitor for UpdateUsedLocals {
 fn update_locals(
     num_inputs: usize,
     old_locals: VarId::Vector<Var>,
-    st: &Statement,
+    body: &Block,
 ) -> (VarId::Vector<Var>, HashMap<VarId::Id, VarId::Id>) {
     // Compute the set of used locals
     let mut used_locals: HashSet<VarId::Id> = HashSet::new();
@@ -92,7 +120,7 @@ fn update_locals(
         used_locals.insert(VarId::Id::new(i));
     }
     // Explore the body
-    let used_locals_cnt = ComputeUsedLocals::compute_in_statement(st);
+    let used_locals_cnt = ComputeUsedLocals::compute_in_block(body);
     for (vid, cnt) in used_locals_cnt.iter() {
         if *cnt > 0 {
             used_locals.insert(*vid);
@@ -119,28 +147,53 @@ fn update_locals(
     (locals, vids_map)
 }
 
-pub fn transform(ctx: &mut TransCtx, funs: &mut FunDecls, globals: &mut GlobalDecls) {
-    ctx.iter_bodies(funs, globals, |ctx, name, b| {
-        let fmt_ctx = ctx.into_fmt();
-        trace!(
-            "# About to remove unused locals in decl: {}:\n{}",
-            name.fmt_with_ctx(&fmt_ctx),
-            fmt_ctx.format_object(&*b)
-        );
-        take(b, |mut b| {
-            let (locals, vids_map) = update_locals(b.arg_count, b.locals, &b.body);
-            b.locals = locals;
-            trace!("vids_maps: {:?}", vids_map);
-            UpdateUsedLocals::update_statement(vids_map, &mut b.body);
-            b
-        });
-        let fmt_ctx = ctx.into_fmt();
-        trace!(
-            "# After removing unused locals of: {}:\n{}",
-            name.fmt_with_ctx(&fmt_ctx),
-            fmt_ctx.format_object(&*b)
-        );
-        // Check that there are no remaining locals with the type `Never`
-        assert!(b.locals.iter().all(|v| !v.ty.is_never()));
-    })
+pub fn transform(ctx: &mut TransformCtx, funs: &mut FunDecls, globals: &mut GlobalDecls) {
+    // The new ids of the locals of the bodies: we need them to update the
+    // variables captured by the closures (see [crate::types::CapturedPlace])
+    let mut vids_maps: HashMap<DefId, HashMap<VarId::Id, VarId::Id>> = HashMap::new();
+    for (def_id, name, b) in iter_function_bodies(funs).chain(iter_global_bodies(globals)) {
+        ctx.with_def_id(def_id, |ctx| {
+            let fmt_ctx = ctx.into_fmt();
+            trace!(
+                "# About to remove unused locals in decl: {}:\n{}",
+                name.fmt_with_ctx(&fmt_ctx),
+                fmt_ctx.format_object(&*b)
+            );
+            take(b, |mut b| {
+                let (locals, vids_map) = update_locals(b.arg_count, b.locals, &b.body);
+                b.locals = locals;
+                trace!("vids_maps: {:?}", vids_map);
+                // Update the locals of the scopes
+                for scope in b.scopes.iter_mut() {
+                    scope.locals = scope
+                        .locals
+                        .iter()
+                        .filter_map(|vid| vids_map.get(vid).copied())
+                        .collect();
+                }
+                UpdateUsedLocals::update_block(vids_map.clone(), &mut b.body);
+                vids_maps.insert(def_id, vids_map);
+                b
+            });
+            let fmt_ctx = ctx.into_fmt();
+            trace!(
+                "# After removing unused locals of: {}:\n{}",
+                name.fmt_with_ctx(&fmt_ctx),
+                fmt_ctx.format_object(&*b)
+            );
+            // Check that there are no remaining locals with the type `Never`
+            assert!(b.locals.iter().all(|v| !v.ty.is_never()));
+        })
+    }
+
+    // Update the variables captured by the closures
+    for f in funs.iter_mut() {
+        let Some(info) = &mut f.signature.closure_info else { continue };
+        for capture in info.captures.iter_mut() {
+            let vids_map = vids_maps.get(&capture.var_owner);
+            if let Some(var_id) = vids_map.and_then(|vids_map| vids_map.get(&capture.var_id)) {
+                capture.var_id = *var_id;
+            }
+        }
+    }
 }
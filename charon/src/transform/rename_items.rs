@@ -0,0 +1,90 @@
+//! # Micro-pass: rename the declarations according to the renaming table
+//! given on the command line (see [RenameTable]). This allows the users to
+//! avoid name collisions, or to adapt the names to the conventions of their
+//! target language, without having to post-process the output file.
+use crate::common::CharonError;
+use crate::names::{Name, PathElem};
+use crate::translate_ctx::TranslatedCrate;
+use serde::Deserialize;
+use std::path::Path;
+
+/// An entry of the renaming table.
+#[derive(Debug, Clone, Deserialize)]
+struct Renaming {
+    /// The names of the items to rename: a path in which `*` matches any path
+    /// element, including the `impl` blocks (for instance: `my_crate::*::new`
+    /// matches the `new` methods of the inherent impls of the crate root).
+    pattern: String,
+    /// The new name of the items: we replace the last identifier of their
+    /// names with it.
+    name: String,
+}
+
+impl Renaming {
+    fn matches(&self, name: &Name) -> bool {
+        let pattern: Vec<&str> = self.pattern.split("::").collect();
+        name.name.len() == pattern.len()
+            && name
+                .name
+                .iter()
+                .zip(pattern)
+                .all(|(elem, pat)| pat == "*" || elem.equals_ident(pat))
+    }
+}
+
+/// The renaming table. We read it from a TOML file of the following shape (if
+/// several entries match a name, we use the first one):
+/// ```toml
+/// [[rename]]
+/// pattern = "my_crate::*::new"
+/// name = "make"
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RenameTable {
+    #[serde(default)]
+    rename: Vec<Renaming>,
+}
+
+impl RenameTable {
+    pub fn from_file(path: &Path) -> Result<Self, CharonError> {
+        let content = std::fs::read_to_string(path).map_err(|error| CharonError::Io {
+            path: path.to_path_buf(),
+            error,
+        })?;
+        toml::from_str(&content).map_err(|error| CharonError::Config {
+            path: path.to_path_buf(),
+            msg: error.to_string(),
+        })
+    }
+
+    /// Rename the item, if its name matches one of the patterns.
+    pub fn rename(&self, name: &mut Name) {
+        // The names of the trait implementations end with an `impl` block:
+        // there is no identifier to replace.
+        if !matches!(name.name.last(), Some(PathElem::Ident(..))) {
+            return;
+        }
+        if let Some(renaming) = self.rename.iter().find(|r| r.matches(name)) {
+            if let Some(PathElem::Ident(ident, _)) = name.name.last_mut() {
+                *ident = renaming.name.as_str().into();
+            }
+        }
+    }
+}
+
+pub fn transform(table: &RenameTable, ctx: &mut TranslatedCrate) {
+    for decl in ctx.type_decls.iter_mut() {
+        table.rename(&mut decl.name);
+    }
+    for decl in ctx.fun_decls.iter_mut() {
+        table.rename(&mut decl.name);
+    }
+    for decl in ctx.global_decls.iter_mut() {
+        table.rename(&mut decl.name);
+    }
+    for decl in ctx.trait_decls.iter_mut() {
+        table.rename(&mut decl.name);
+    }
+    // We don't need to look at the trait implementations: their names end
+    // with an `impl` block.
+}
@@ -40,7 +40,9 @@ fn transform_constant_expr<F: FnMut(Ty) -> VarId::Id>(
         RawConstantExpr::Literal(_)
         | RawConstantExpr::Var(_)
         | RawConstantExpr::TraitConst(..)
-        | RawConstantExpr::FnPtr(..) => {
+        | RawConstantExpr::FnPtr(..)
+        | RawConstantExpr::MutStatic(_)
+        | RawConstantExpr::Promoted(..) => {
             // Nothing to do
             // TODO: for trait const: might come from a top-level impl, so we might
             // want to introduce an intermediate statement to be able to evaluate
@@ -118,10 +120,11 @@ fn transform_operand<F: FnMut(Ty) -> VarId::Id>(
 
 pub fn transform(ctx: &mut TransCtx) {
     // Slightly annoying: we have to clone because of borrowing issues
-    let mut fun_decls = ctx.fun_decls.clone();
-    let mut global_decls = ctx.global_decls.clone();
+    let mut fun_decls = ctx.translated.fun_decls.clone();
+    let mut global_decls = ctx.translated.global_decls.clone();
 
-    ctx.iter_bodies(&mut fun_decls, &mut global_decls, |ctx, name, b| {
+    let mut t_ctx = ctx.transform_ctx();
+    t_ctx.iter_bodies(&mut fun_decls, &mut global_decls, |ctx, name, b| {
         let fmt_ctx = ctx.into_fmt();
         trace!(
             "# About to simplify constants in function: {}:\n{}",
@@ -135,6 +138,6 @@ pub fn transform(ctx: &mut TransCtx) {
         });
     });
 
-    ctx.fun_decls = fun_decls;
-    ctx.global_decls = global_decls;
+    ctx.translated.fun_decls = fun_decls;
+    ctx.translated.global_decls = global_decls;
 }
@@ -0,0 +1,106 @@
+//! # Micro-pass: simplify the chains of parent clauses and item clauses in the
+//! trait references. The deep hierarchies of supertraits give long chains like
+//! `ParentClause(ItemClause(ParentClause(Clause(0), ...), ...), ...)`, which
+//! are hard to interpret. We replace such a chain:
+//! - with the trait implementation it refers to, if this implementation is
+//!   statically known (see [TraitInstanceId::resolve_impl]) and doesn't have
+//!   generics: the chains which start from a trait implementation come from the
+//!   substitution of the clauses, when we inline the functions for instance;
+//! - with a clause of the declaration, if one of its clauses gives the same
+//!   trait reference. For instance, in `fn f<T: Bar + Foo>` where `Foo` is a
+//!   parent trait of `Bar`, we use the clause `T: Foo` rather than the parent
+//!   clause of `T: Bar`.
+use crate::expressions::MutExprVisitor;
+use crate::formatter::{Formatter, IntoFormatter};
+use crate::llbc_ast::*;
+use crate::names::Name;
+use crate::translate_ctx::TransformCtx;
+use crate::types::*;
+
+struct Simplify<'a> {
+    trait_impls: &'a TraitImplId::Map<TraitImpl>,
+    /// The clauses of the declaration whose body we explore
+    clauses: &'a [TraitClause],
+}
+
+impl<'a> Simplify<'a> {
+    fn simplify(&self, tr: &TraitRef) -> Option<TraitInstanceId> {
+        if !matches!(
+            tr.trait_id,
+            TraitInstanceId::ParentClause(..) | TraitInstanceId::ItemClause(..)
+        ) {
+            return None;
+        }
+        if let Some(impl_id) = tr.trait_id.resolve_impl(self.trait_impls)
+            && self
+                .trait_impls
+                .get(impl_id)
+                .is_some_and(|imp| imp.generics.is_empty())
+        {
+            return Some(TraitInstanceId::TraitImpl(impl_id));
+        }
+        // The generics of the higher-ranked clauses refer to their bound regions
+        let clause = self.clauses.iter().find(|clause| {
+            clause.bound_regions.is_empty()
+                && clause.trait_id == tr.trait_decl_ref.trait_id
+                && clause.generics == tr.trait_decl_ref.generics
+        })?;
+        Some(TraitInstanceId::Clause(clause.clause_id))
+    }
+}
+
+impl<'a> MutTypeVisitor for Simplify<'a> {
+    fn visit_trait_ref(&mut self, tr: &mut TraitRef) {
+        tr.with_data_mut(|tr| {
+            self.visit_generic_args(&mut tr.generics);
+            self.visit_trait_decl_ref(&mut tr.trait_decl_ref);
+        });
+        match self.simplify(tr) {
+            // The chains have no generic arguments, and neither have the
+            // clauses and the implementations we replace them with
+            Some(trait_id) => tr.with_data_mut(|tr| tr.trait_id = trait_id),
+            None => tr.with_data_mut(|tr| self.visit_trait_instance_id(&mut tr.trait_id)),
+        }
+    }
+}
+
+impl<'a> MutExprVisitor for Simplify<'a> {}
+
+impl<'a> MutAstVisitor for Simplify<'a> {
+    fn spawn(&mut self, visitor: &mut dyn FnMut(&mut Self)) {
+        visitor(self)
+    }
+
+    fn merge(&mut self) {}
+}
+
+fn simplify_body(ctx: &TransformCtx, clauses: &[TraitClause], name: &Name, b: &mut ExprBody) {
+    let fmt_ctx = ctx.into_fmt();
+    trace!(
+        "# About to simplify the trait references in decl: {}:\n{}",
+        name.fmt_with_ctx(&fmt_ctx),
+        fmt_ctx.format_object(&*b)
+    );
+
+    let mut visitor = Simplify {
+        trait_impls: &ctx.translated.trait_impls,
+        clauses,
+    };
+    for var in b.locals.iter_mut() {
+        visitor.visit_ty(&mut var.ty);
+    }
+    visitor.visit_block(&mut b.body);
+}
+
+pub fn transform(ctx: &mut TransformCtx, funs: &mut FunDecls, globals: &mut GlobalDecls) {
+    for f in funs.iter_mut() {
+        if let Some(b) = &mut f.body {
+            simplify_body(ctx, &f.signature.generics.trait_clauses, &f.name, b);
+        }
+    }
+    for g in globals.iter_mut() {
+        if let Some(b) = &mut g.body {
+            simplify_body(ctx, &g.generics.trait_clauses, &g.name, b);
+        }
+    }
+}
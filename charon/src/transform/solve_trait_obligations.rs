@@ -0,0 +1,270 @@
+//! # Micro-pass: solve the trait obligations which remained unsolved during the
+//! translation ([TraitInstanceId::Unsolved]). When we register the clauses of
+//! an item, solving an obligation may require a clause we haven't registered
+//! yet: we solve the clauses until we reach a fixed point, but we may get
+//! stuck. Once all the items are translated, we retry the remaining
+//! obligations: we look for a clause of the item, then for a trait
+//! implementation without generics, which gives the same trait reference.
+//!
+//! We explore all the declarations: the obligations may appear in the clauses
+//! of the types and of the traits (in their parent clauses for instance) as
+//! well as in the functions, the globals and the trait implementations. We
+//! replace the obligations we still fail to solve with
+//! [TraitInstanceId::Unknown], and report them: we register an error for the
+//! item, and list them in the extraction report (see [UnsolvedObligation]).
+//! This way, we never export [TraitInstanceId::Unsolved].
+use crate::expressions::MutExprVisitor;
+use crate::formatter::{FmtCtx, Formatter, IntoFormatter};
+use crate::report::{invert_ids, UnsolvedObligation};
+use crate::translate_ctx::TransCtx;
+use crate::types::*;
+use crate::ullbc_ast::*;
+
+struct Solver<'a> {
+    trait_impls: &'a TraitImpls,
+    /// The clauses of the item we explore
+    clauses: &'a [TraitClause],
+    fmt_ctx: &'a FmtCtx<'a>,
+    /// The name of the item we explore
+    item: String,
+    /// The obligations we failed to solve
+    unsolved: Vec<UnsolvedObligation>,
+}
+
+impl<'a> Solver<'a> {
+    fn new(
+        trait_impls: &'a TraitImpls,
+        clauses: &'a [TraitClause],
+        fmt_ctx: &'a FmtCtx<'a>,
+        item: String,
+    ) -> Self {
+        Solver {
+            trait_impls,
+            clauses,
+            fmt_ctx,
+            item,
+            unsolved: Vec::new(),
+        }
+    }
+
+    fn solve(&self, trait_id: TraitDeclId::Id, generics: &GenericArgs) -> Option<TraitInstanceId> {
+        if let Some(clause) = self
+            .clauses
+            .iter()
+            .find(|clause| clause.trait_id == trait_id && clause.generics == *generics)
+        {
+            return Some(TraitInstanceId::Clause(clause.clause_id));
+        }
+        let imp = self.trait_impls.iter().find(|imp| {
+            imp.generics.is_empty()
+                && imp.impl_trait.trait_id == trait_id
+                && imp.impl_trait.generics == *generics
+        })?;
+        Some(TraitInstanceId::TraitImpl(imp.def_id))
+    }
+
+    /// Explore the clauses of the item, remembering the clause in which we
+    /// find the obligations we fail to solve.
+    fn visit_clauses<'c>(&mut self, clauses: impl Iterator<Item = &'c mut TraitClause>) {
+        for clause in clauses {
+            let num_unsolved = self.unsolved.len();
+            self.visit_generic_args(&mut clause.generics);
+            for unsolved in &mut self.unsolved[num_unsolved..] {
+                unsolved.clause = Some(clause.fmt_with_ctx(self.fmt_ctx));
+            }
+        }
+    }
+
+    fn visit_type_decl_kind(&mut self, kind: &mut TypeDeclKind) {
+        match kind {
+            TypeDeclKind::Struct(fields) => {
+                for field in fields.iter_mut() {
+                    self.visit_ty(&mut field.ty);
+                }
+            }
+            TypeDeclKind::Enum(variants, ..) => {
+                for field in variants.iter_mut().flat_map(|v| v.fields.iter_mut()) {
+                    self.visit_ty(&mut field.ty);
+                }
+            }
+            TypeDeclKind::ImplTrait { bounds, hidden_ty } => {
+                for bound in bounds.iter_mut() {
+                    self.visit_generic_args(&mut bound.generics);
+                    for (_, ty) in bound.type_constraints.iter_mut() {
+                        self.visit_ty(ty);
+                    }
+                }
+                if let Some(ty) = hidden_ty {
+                    self.visit_ty(ty);
+                }
+            }
+            TypeDeclKind::Opaque | TypeDeclKind::Error(_) => (),
+        }
+    }
+
+    fn visit_body(&mut self, body: &mut ExprBody) {
+        for var in body.locals.iter_mut() {
+            self.visit_ty(&mut var.ty);
+        }
+        for block in body.body.iter_mut() {
+            self.visit_block_data(block);
+        }
+    }
+}
+
+impl<'a> MutTypeVisitor for Solver<'a> {
+    fn visit_trait_instance_id(&mut self, id: &mut TraitInstanceId) {
+        if let TraitInstanceId::Unsolved(trait_id, generics) = id {
+            self.visit_generic_args(generics);
+            if let Some(solved) = self.solve(*trait_id, generics) {
+                *id = solved;
+            } else {
+                let missing_impl = format!(
+                    "{}{}",
+                    self.fmt_ctx.format_object(*trait_id),
+                    generics.fmt_with_ctx(self.fmt_ctx)
+                );
+                *id = TraitInstanceId::Unknown(format!(
+                    "Could not solve the trait obligation: {missing_impl}"
+                ));
+                self.unsolved.push(UnsolvedObligation {
+                    item: self.item.clone(),
+                    clause: None,
+                    missing_impl,
+                });
+            }
+        } else {
+            self.default_visit_trait_instance_id(id)
+        }
+    }
+}
+
+impl<'a> MutExprVisitor for Solver<'a> {}
+
+impl<'a> MutAstVisitor for Solver<'a> {}
+
+pub fn transform(ctx: &mut TransCtx) {
+    // Slightly annoying: we have to clone because of borrowing issues
+    let mut type_decls = ctx.translated.type_decls.clone();
+    let mut trait_decls = ctx.translated.trait_decls.clone();
+    let mut fun_decls = ctx.translated.fun_decls.clone();
+    let mut global_decls = ctx.translated.global_decls.clone();
+    let mut trait_impls = ctx.translated.trait_impls.clone();
+
+    let fmt_ctx = ctx.translated.into_fmt();
+    let mut unsolved = Vec::new();
+    let type_ids = invert_ids(&ctx.type_id_map);
+    for d in type_decls.iter_mut() {
+        let clauses = d.generics.trait_clauses.clone();
+        let item = d.name.fmt_with_ctx(&fmt_ctx);
+        let mut solver = Solver::new(&ctx.translated.trait_impls, &clauses, &fmt_ctx, item);
+        solver.visit_clauses(d.generics.trait_clauses.iter_mut());
+        for x in d.preds.trait_type_constraints.iter_mut() {
+            solver.visit_trait_type_constraint(x);
+        }
+        solver.visit_type_decl_kind(&mut d.kind);
+        let rust_id = type_ids[&d.def_id];
+        let meta = d.item_meta.meta;
+        unsolved.extend(solver.unsolved.into_iter().map(|x| (rust_id, meta, x)));
+    }
+    let trait_decl_ids = invert_ids(&ctx.trait_decl_id_map);
+    for d in trait_decls.iter_mut() {
+        let clauses = d.generics.trait_clauses.clone();
+        let item = d.name.fmt_with_ctx(&fmt_ctx);
+        let mut solver = Solver::new(&ctx.translated.trait_impls, &clauses, &fmt_ctx, item);
+        solver.visit_clauses(d.generics.trait_clauses.iter_mut());
+        solver.visit_clauses(d.parent_clauses.iter_mut());
+        for x in d.preds.trait_type_constraints.iter_mut() {
+            solver.visit_trait_type_constraint(x);
+        }
+        for (_, (ty, _)) in d.consts.iter_mut() {
+            solver.visit_ty(ty);
+        }
+        for (_, (_, clauses, ty)) in d.types.iter_mut() {
+            solver.visit_clauses(clauses.iter_mut());
+            if let Some(ty) = ty {
+                solver.visit_ty(ty);
+            }
+        }
+        let rust_id = trait_decl_ids[&d.def_id];
+        let meta = d.item_meta.meta;
+        unsolved.extend(solver.unsolved.into_iter().map(|x| (rust_id, meta, x)));
+    }
+    for f in fun_decls.iter_mut() {
+        let clauses = f.signature.generics.trait_clauses.clone();
+        let item = f.name.fmt_with_ctx(&fmt_ctx);
+        let mut solver = Solver::new(&ctx.translated.trait_impls, &clauses, &fmt_ctx, item);
+        solver.visit_clauses(f.signature.generics.trait_clauses.iter_mut());
+        for x in f.signature.preds.trait_type_constraints.iter_mut() {
+            solver.visit_trait_type_constraint(x);
+        }
+        for ty in f.signature.inputs.iter_mut() {
+            solver.visit_ty(ty);
+        }
+        solver.visit_ty(&mut f.signature.output);
+        if let Some(body) = &mut f.body {
+            solver.visit_body(body);
+        }
+        let meta = f.item_meta.meta;
+        unsolved.extend(solver.unsolved.into_iter().map(|x| (f.rust_id, meta, x)));
+    }
+    for g in global_decls.iter_mut() {
+        let clauses = g.generics.trait_clauses.clone();
+        let item = g.name.fmt_with_ctx(&fmt_ctx);
+        let mut solver = Solver::new(&ctx.translated.trait_impls, &clauses, &fmt_ctx, item);
+        solver.visit_clauses(g.generics.trait_clauses.iter_mut());
+        for x in g.preds.trait_type_constraints.iter_mut() {
+            solver.visit_trait_type_constraint(x);
+        }
+        solver.visit_ty(&mut g.ty);
+        if let Some(body) = &mut g.body {
+            solver.visit_body(body);
+        }
+        let meta = g.item_meta.meta;
+        unsolved.extend(solver.unsolved.into_iter().map(|x| (g.rust_id, meta, x)));
+    }
+    for imp in trait_impls.iter_mut() {
+        let clauses = imp.generics.trait_clauses.clone();
+        let item = imp.name.fmt_with_ctx(&fmt_ctx);
+        let mut solver = Solver::new(&ctx.translated.trait_impls, &clauses, &fmt_ctx, item);
+        solver.visit_clauses(imp.generics.trait_clauses.iter_mut());
+        for x in imp.preds.trait_type_constraints.iter_mut() {
+            solver.visit_trait_type_constraint(x);
+        }
+        solver.visit_trait_decl_ref(&mut imp.impl_trait);
+        for tr in imp.parent_trait_refs.iter_mut() {
+            solver.visit_trait_ref(tr);
+        }
+        for (_, (_, trait_refs, ty)) in imp.types.iter_mut() {
+            for tr in trait_refs.iter_mut() {
+                solver.visit_trait_ref(tr);
+            }
+            solver.visit_ty(ty);
+        }
+        let rust_id = ctx.trait_impl_id_to_def_id[&imp.def_id];
+        let meta = imp.item_meta.meta;
+        unsolved.extend(solver.unsolved.into_iter().map(|x| (rust_id, meta, x)));
+    }
+
+    for (rust_id, meta, obligation) in unsolved {
+        let msg = match &obligation.clause {
+            Some(clause) => format!(
+                "Could not solve the trait obligation {} in the clause {}",
+                obligation.missing_impl, clause
+            ),
+            None => format!(
+                "Could not solve the trait obligation {}",
+                obligation.missing_impl
+            ),
+        };
+        ctx.errors
+            .with_def_id(rust_id, |errors| errors.span_err(meta, &msg));
+        ctx.errors.unsolved_obligations.push(obligation);
+    }
+
+    ctx.translated.type_decls = type_decls;
+    ctx.translated.trait_decls = trait_decls;
+    ctx.translated.fun_decls = fun_decls;
+    ctx.translated.global_decls = global_decls;
+    ctx.translated.trait_impls = trait_impls;
+}
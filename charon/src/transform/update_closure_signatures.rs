@@ -1,10 +1,14 @@
 //! # Micro-pass: the first local variable of closures is (a borrow to) the
 //! closure itself. This is not consistent with the closure signature,
 //! which ignores this first variable. This micro-pass updates this.
-use crate::common::*;
-use crate::llbc_ast::*;
-use crate::translate_ctx::TransCtx;
+//!
+//! We work on the ULLBC, right after the translation and in both modes: the
+//! shims of the closures (see [super::insert_closure_shims]) and the other
+//! passes see the updated signatures.
+use crate::expressions::MutExprVisitor;
+use crate::translate_ctx::TranslatedCrate;
 use crate::types::*;
+use crate::ullbc_ast::*;
 
 struct InsertRegions<'a> {
     regions: &'a mut RegionId::Vector<RegionVar>,
@@ -51,15 +55,43 @@ impl MutExprVisitor for ClosureStateAccess {
     }
 }
 
-impl MutAstVisitor for ClosureStateAccess {
-    fn spawn(&mut self, visitor: &mut dyn FnMut(&mut Self)) {
-        visitor(self)
+impl MutAstVisitor for ClosureStateAccess {}
+
+/// Compute the type of the state of a closure (a tuple), possibly behind a reference, and
+/// introduce fresh regions (pushed to `regions`) for the erased regions it contains.
+///
+/// The region of the reference is the last region we introduce: the `call_once` shims,
+/// which take the state by value, remove it (see [super::insert_closure_shims]).
+fn closure_state_ty(
+    info: &ClosureInfo,
+    ref_kind: Option<RefKind>,
+    regions: &mut RegionId::Vector<RegionVar>,
+) -> Ty {
+    // Group the types into a tuple
+    let mut state = TyKind::Adt(
+        TypeId::Tuple,
+        GenericArgs::new_from_types(info.state.clone()),
+    )
+    .into_ty();
+
+    // Explore the state and introduce fresh regions for the erased
+    // regions we find.
+    let mut visitor = InsertRegions {
+        regions: &mut *regions,
+        depth: 0,
+    };
+    visitor.visit_ty(&mut state);
+
+    match ref_kind {
+        None => state,
+        Some(ref_kind) => {
+            let index = regions.push_with(|index| RegionVar { index, name: None });
+            TyKind::Ref(Region::BVar(DeBruijnId::new(0), index), state, ref_kind).into_ty()
+        }
     }
-
-    fn merge(&mut self) {}
 }
 
-fn transform_function(_ctx: &TransCtx, def: &mut FunDecl) -> Result<(), Error> {
+fn transform_function(def: &mut FunDecl) {
     let FunSig {
         closure_info,
         inputs,
@@ -76,37 +108,14 @@ fn transform_function(_ctx: &TransCtx, def: &mut FunDecl) -> Result<(), Error> {
         // However, we introduce fresh regions for the state (in particular
         // because it is easy to do so).
 
-        // Group the types into a tuple
         let num_fields = info.state.len();
-        let state = Ty::Adt(
-            TypeId::Tuple,
-            GenericArgs::new_from_types(info.state.clone()),
-        );
         // Depending on the kind of the closure, add a reference
-        let mut state = match &info.kind {
-            ClosureKind::FnOnce => state,
-            ClosureKind::Fn | ClosureKind::FnMut => {
-                // We introduce an erased region, that we replace later
-                //let index = RegionId::Id::new(generics.regions.len());
-                //generics.regions.push_back(RegionVar { index, name: None });
-
-                let mutability = if info.kind == ClosureKind::Fn {
-                    RefKind::Shared
-                } else {
-                    RefKind::Mut
-                };
-                //let r = Region::BVar(DeBruijnId::new(0), index);
-                Ty::Ref(Region::Erased, Box::new(state), mutability)
-            }
+        let ref_kind = match &info.kind {
+            ClosureKind::FnOnce => None,
+            ClosureKind::Fn => Some(RefKind::Shared),
+            ClosureKind::FnMut => Some(RefKind::Mut),
         };
-
-        // Explore the state and introduce fresh regions for the erased
-        // regions we find.
-        let mut visitor = InsertRegions {
-            regions: &mut generics.regions,
-            depth: 0,
-        };
-        visitor.visit_ty(&mut state);
+        let state = closure_state_ty(info, ref_kind, &mut generics.regions);
 
         // Update the inputs (slightly annoying to push to the front of
         // a vector...).
@@ -148,18 +157,13 @@ fn transform_function(_ctx: &TransCtx, def: &mut FunDecl) -> Result<(), Error> {
 
             // Update the body, and in particular the accesses to the states
             let mut visitor = ClosureStateAccess { num_fields };
-            visitor.visit_statement(&mut body.body);
+            for block in body.body.iter_mut() {
+                visitor.visit_block_data(block);
+            }
         }
-
-        Ok(())
-    } else {
-        Ok(())
     }
 }
 
-pub fn transform(ctx: &TransCtx, funs: &mut FunDecls) {
-    // Ignore the errors, which should have been reported
-    funs.iter_mut().for_each(|d| {
-        let _ = transform_function(ctx, d);
-    });
+pub fn transform(ctx: &mut TranslatedCrate) {
+    ctx.fun_decls.iter_mut().for_each(transform_function);
 }
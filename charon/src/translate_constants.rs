@@ -7,6 +7,24 @@ use crate::values::*;
 use hax_frontend_exporter as hax;
 use rustc_hir::def_id::DefId;
 
+/// The string literal in an operand, if it is one (like the message in
+/// `kani::assert(b, "message")`).
+pub(crate) fn string_literal(operand: &hax::Operand) -> Option<String> {
+    let hax::Operand::Constant(constant) = operand else {
+        return None;
+    };
+    let mut value = &constant.literal.constant_kind;
+    if let hax::ConstantExprKind::Borrow(inner) = &*value.contents {
+        value = inner;
+    }
+    match &*value.contents {
+        hax::ConstantExprKind::Literal(hax::ConstantLiteral::ByteStr(bytes, ..)) => {
+            String::from_utf8(bytes.clone()).ok()
+        }
+        _ => None,
+    }
+}
+
 impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
     fn translate_constant_literal_to_raw_constant_expr(
         &mut self,
@@ -100,6 +118,15 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                 let name = TraitItemName(name.clone());
                 RawConstantExpr::TraitConst(trait_ref, name)
             }
+            // The accesses to the mutable statics are made through raw pointers
+            ConstantExprKind::GlobalName { id, .. }
+            | ConstantExprKind::Borrow(hax::ConstantExpr {
+                contents: box ConstantExprKind::GlobalName { id, .. },
+                ..
+            }) if self.t_ctx.tcx.is_mutable_static(DefId::from(id)) => {
+                let global_decl_id = self.translate_global_decl_id(span, DefId::from(id));
+                RawConstantExpr::MutStatic(global_decl_id)
+            }
             ConstantExprKind::GlobalName {
                 id,
                 generics,
@@ -155,6 +182,12 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                     trait_info,
                 )?;
                 let SubstFunIdOrPanic::Fun(fn_id) = fn_id else  { unreachable!() };
+                // The local function items have their own type (see [TyKind::FnDef])
+                if let FunIdOrTraitMethodRef::Fun(FunId::Regular(id)) = &fn_id.func.func {
+                    let ty = TyKind::FnDef(*id, fn_id.func.generics.clone()).into_ty();
+                    let value = RawConstantExpr::FnPtr(fn_id.func);
+                    return Ok(ConstantExpr { value, ty });
+                }
                 RawConstantExpr::FnPtr(fn_id.func)
             }
             ConstantExprKind::Todo(msg) => {
@@ -185,10 +218,14 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                 error_assert!(self, span, substs.is_empty());
                 Ok(ConstGeneric::Global(id))
             }
+            RawConstantExpr::TraitConst(trait_ref, name) => {
+                Ok(ConstGeneric::TraitConst(trait_ref, name))
+            }
             RawConstantExpr::Adt(..)
-            | RawConstantExpr::TraitConst { .. }
             | RawConstantExpr::Ref(_)
-            | RawConstantExpr::FnPtr { .. } => {
+            | RawConstantExpr::FnPtr { .. }
+            | RawConstantExpr::MutStatic(_)
+            | RawConstantExpr::Promoted(..) => {
                 error_or_panic!(
                     self,
                     span,
@@ -199,6 +236,16 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
         }
     }
 
+    /// If the constant with the given span at the current location refers to a
+    /// promoted constant, return its index. The constants of a location are
+    /// translated in the order in which they appear, so we take the first
+    /// promoted constant which has this span.
+    fn take_promoted_constant(&mut self, span: rustc_span::Span) -> Option<PromotedId::Id> {
+        let refs = self.promoted_constants.get_mut(&self.location?)?;
+        let pos = refs.iter().position(|(s, _)| *s == span)?;
+        refs.remove(pos).map(|(_, index)| index)
+    }
+
     /// Remark: [hax::ConstantExpr] contains span information, but it is often
     /// the default span (i.e., it is useless), hence the additional span argument.
     pub(crate) fn translate_constant_to_constant_expr(
@@ -206,6 +253,15 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
         span: rustc_span::Span,
         v: &hax::Constant,
     ) -> Result<ConstantExpr, Error> {
+        // The promoted constants are translated as separate globals: we
+        // identify them with their location and their span (see
+        // [BodyTransCtx::promoted_constants])
+        let rspan = v.span.rust_span_data.unwrap().span();
+        if let Some(index) = self.take_promoted_constant(rspan) {
+            let value = RawConstantExpr::Promoted(index, self.promoted_args.clone());
+            let ty = self.translate_ty(span, true, &v.literal.constant_kind.ty)?;
+            return Ok(ConstantExpr { value, ty });
+        }
         self.translate_constant_expr_to_constant_expr(span, &v.literal.constant_kind)
     }
 }
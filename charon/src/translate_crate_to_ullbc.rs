@@ -1,18 +1,26 @@
+use crate::assumed;
 use crate::cli_options::CliOpts;
 use crate::common::*;
+use crate::gast::{IntrinsicKind, Module, ModuleId};
 use crate::get_mir::{extract_constants_at_top_level, MirLevel};
 use crate::meta;
+use crate::profile::Profile;
+use crate::reorder_decls::AnyTransId;
+use crate::report;
 use crate::translate_ctx::*;
 use crate::translate_functions_to_ullbc;
+use crate::translate_predicates::TraitResolutionCache;
 use crate::types as ty;
 use crate::ullbc_ast as ast;
 use hax_frontend_exporter as hax;
 use hax_frontend_exporter::SInto;
 use linked_hash_set::LinkedHashSet;
+use rustc_hir::def_id::DefId;
 use rustc_hir::{Defaultness, ForeignItemKind, ImplItem, ImplItemKind, Item, ItemKind};
 use rustc_middle::ty::TyCtxt;
 use rustc_session::Session;
 use std::collections::{BTreeSet, HashMap, HashSet};
+use std::time::Instant;
 
 impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
     fn register_local_hir_impl_item(&mut self, _top_item: bool, impl_item: &ImplItem) {
@@ -20,6 +28,7 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
         assert!(impl_item.defaultness == Defaultness::Final);
 
         let def_id = impl_item.owner_id.to_def_id();
+        self.check_charon_attributes(def_id);
 
         // Match on the impl item kind
         match &impl_item.kind {
@@ -90,19 +99,39 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
                 Option::Some(item_name) => {
                     if self.crate_info.is_opaque_decl(&item_name) {
                         trace!("Ignoring {:?} (marked as opaque)", item.item_id());
+                        let kind = item.kind.descr();
+                        self.skipped_items
+                            .push((item_name, kind, report::OPAQUE_MODULE));
                         return Ok(());
                     }
                     // Continue
                 }
             }
         }
+        let def_id = item.owner_id.to_def_id();
+        self.check_charon_attributes(def_id);
+        if self.id_is_marked_skip(def_id) {
+            trace!(
+                "Ignoring {:?} (marked with `#[charon::skip]`)",
+                item.item_id()
+            );
+            if let Some(item_name) = self.hir_item_to_name(item)? {
+                let kind = item.kind.descr();
+                self.skipped_items
+                    .push((item_name, kind, report::SKIP_ATTRIBUTE));
+            }
+            return Ok(());
+        }
         trace!("Registering {:?}", item.item_id());
 
         // Case disjunction on the item kind.
-        let def_id = item.owner_id.to_def_id();
         match &item.kind {
             ItemKind::TyAlias(_, _) => {
-                // We ignore the type aliases - it seems they are inlined
+                // The type aliases are inlined: we only translate them if the
+                // user asked for it (see [TypeAliasDecl])
+                if self.translate_type_aliases {
+                    let _ = self.register_type_alias_id(def_id);
+                }
                 Ok(())
             }
             ItemKind::OpaqueTy(_) => unimplemented!(),
@@ -186,6 +215,13 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
                 if opaque {
                     // Ignore
                     trace!("Ignoring module [{:?}] because marked as opaque", def_id);
+                    let name = self.def_id_to_name(def_id)?;
+                    let reason = if self.id_is_marked_opaque(def_id) {
+                        report::OPAQUE_ATTRIBUTE
+                    } else {
+                        report::OPAQUE_MODULE
+                    };
+                    self.skipped_items.push((name, "module", reason));
                 } else {
                     trace!("Diving into module [{:?}]", def_id);
                     let hir_map = self.tcx.hir();
@@ -229,12 +265,110 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
             }
         }
     }
+
+    /// Register a module of the crate, together with its ancestors.
+    fn register_module(&mut self, def_id: DefId) -> Result<ModuleId::Id, Error> {
+        if let Some(id) = self.module_id_map.get(&def_id) {
+            return Ok(*id);
+        }
+        let parent = match self.tcx.opt_parent(def_id) {
+            Some(parent) => Some(self.register_module(parent)?),
+            None => None,
+        };
+        let module = Module {
+            module_id: self.translated.modules.next_id(),
+            item_meta: self.translate_item_meta_from_rid(def_id)?,
+            name: self.def_id_to_name(def_id)?,
+            parent,
+            submodules: Vec::new(),
+            items: Vec::new(),
+        };
+        let id = self.translated.modules.push(module);
+        if let Some(parent) = parent {
+            self.translated.modules[parent].submodules.push(id);
+        }
+        self.module_id_map.insert(def_id, id);
+        Ok(id)
+    }
+
+    /// Compute the module tree of the crate: we register the crate root, and the modules which
+    /// contain the translated local items (together with their ancestors).
+    fn translate_modules(&mut self) -> Result<(), Error> {
+        let translated = &self.translated;
+        let mut items: Vec<(DefId, AnyTransId)> = Vec::new();
+        items.extend(
+            self.type_id_map
+                .iter()
+                .filter(|(_, id)| translated.type_decls.get(**id).is_some())
+                .map(|(def_id, id)| (*def_id, AnyTransId::Type(*id))),
+        );
+        items.extend(
+            self.fun_id_map
+                .iter()
+                .filter(|(_, id)| translated.fun_decls.get(**id).is_some())
+                .map(|(def_id, id)| (*def_id, AnyTransId::Fun(*id))),
+        );
+        items.extend(
+            self.global_id_map
+                .iter()
+                .filter(|(_, id)| translated.global_decls.get(**id).is_some())
+                .map(|(def_id, id)| (*def_id, AnyTransId::Global(*id))),
+        );
+        items.extend(
+            self.trait_decl_id_map
+                .iter()
+                .filter(|(_, id)| translated.trait_decls.get(**id).is_some())
+                .map(|(def_id, id)| (*def_id, AnyTransId::TraitDecl(*id))),
+        );
+        items.extend(
+            self.trait_impl_id_map
+                .iter()
+                .filter(|(_, id)| translated.trait_impls.get(**id).is_some())
+                .map(|(def_id, id)| (*def_id, AnyTransId::TraitImpl(*id))),
+        );
+
+        let items: Vec<(DefId, AnyTransId)> = items
+            .into_iter()
+            .filter_map(|(def_id, id)| {
+                let module = self.tcx.parent_module_from_def_id(def_id.as_local()?);
+                Some((module.to_def_id(), id))
+            })
+            .collect();
+
+        // We register the modules in the order of their paths, so that their ids and the order
+        // of the submodules don't depend on the order in which we translated the items (the
+        // parents come before their children)
+        let mut modules: HashSet<DefId> = HashSet::new();
+        let root = rustc_hir::def_id::CRATE_DEF_ID.to_def_id();
+        for mut module in std::iter::once(root).chain(items.iter().map(|(module, _)| *module)) {
+            while modules.insert(module)
+                && let Some(parent) = self.tcx.opt_parent(module)
+            {
+                module = parent;
+            }
+        }
+        let mut modules: Vec<DefId> = modules.into_iter().collect();
+        modules.sort_by_cached_key(|module| self.tcx.def_path_str(*module));
+        for module in modules {
+            self.register_module(module)?;
+        }
+
+        for (module, id) in items {
+            let module_id = self.module_id_map[&module];
+            self.translated.modules[module_id].items.push(id);
+        }
+        for module in self.translated.modules.iter_mut() {
+            module.items.sort();
+        }
+        Ok(())
+    }
 }
 
 /// Translate all the declarations in the crate.
 pub fn translate<'tcx, 'ctx>(
     crate_info: CrateInfo,
     options: &CliOpts,
+    intrinsics: &[(String, IntrinsicKind)],
     session: &'ctx Session,
     tcx: TyCtxt<'tcx>,
     mir_level: MirLevel,
@@ -251,33 +385,60 @@ pub fn translate<'tcx, 'ctx>(
         hax_state,
         mir_level,
         crate_info,
-        continue_on_failure: !options.abort_on_error,
-        errors_as_warnings: options.errors_as_warnings,
-        error_count: 0,
+        target_info: ty::TargetInfo::from_session(session),
+        cargo_profile: options
+            .cargo_profile
+            .clone()
+            .unwrap_or_else(|| "release".to_string()),
+        errors: ErrorCtx {
+            session,
+            continue_on_failure: !options.abort_on_error,
+            errors_as_warnings: options.errors_as_warnings,
+            error_count: 0,
+            def_id: None,
+            dep_sources: HashMap::new(),
+            decls_with_errors: HashMap::new(),
+            ignored_failed_decls: HashSet::new(),
+            unsolved_obligations: Vec::new(),
+        },
         no_code_duplication: options.no_code_duplication,
+        keep_storage_statements: options.keep_storage_statements,
         extract_opaque_bodies: options.extract_opaque_bodies,
+        extract_external_bodies: options
+            .extract_external_bodies
+            .iter()
+            .map(|pattern| pattern.split("::").map(|s| s.to_string()).collect())
+            .collect(),
+        use_builtin_models: options.use_builtin_models,
+        translate_type_aliases: options.type_aliases,
+        intrinsics: intrinsics
+            .iter()
+            .map(|(pattern, kind)| {
+                let pattern = pattern.split("::").map(|s| s.to_string()).collect();
+                (pattern, kind.clone())
+            })
+            .chain(assumed::get_verifier_primitives(options))
+            .collect(),
+        harness_attributes: assumed::get_harness_attributes(options),
+        ignored_traits: assumed::get_ignored_traits(options),
+        erase_body_regions: !(options.body_regions || options.borrowck_facts),
+        borrowck_facts: options.borrowck_facts,
         all_ids: LinkedHashSet::new(),
         stack: BTreeSet::new(),
-        def_id: None,
-        file_to_id: HashMap::new(),
-        id_to_file: HashMap::new(),
         real_file_counter: meta::LocalFileId::Generator::new(),
         virtual_file_counter: meta::VirtualFileId::Generator::new(),
-        dep_sources: HashMap::new(),
-        decls_with_errors: HashSet::new(),
-        ignored_failed_decls: HashSet::new(),
         type_id_map: ty::TypeDeclId::MapGenerator::new(),
-        type_decls: ty::TypeDeclId::Map::new(),
         fun_id_map: ast::FunDeclId::MapGenerator::new(),
-        fun_decls: ast::FunDeclId::Map::new(),
         global_id_map: ast::GlobalDeclId::MapGenerator::new(),
-        global_decls: ast::GlobalDeclId::Map::new(),
         trait_decl_id_map: ast::TraitDeclId::MapGenerator::new(),
-        trait_decls: ast::TraitDeclId::Map::new(),
         trait_impl_id_map: ast::TraitImplId::MapGenerator::new(),
         trait_impl_id_to_def_id: HashMap::new(),
-        trait_impls: ast::TraitImplId::Map::new(),
-        ordered_decls: None,
+        module_id_map: HashMap::new(),
+        type_alias_id_map: ty::TypeAliasId::MapGenerator::new(),
+        skipped_items: Vec::new(),
+        translated: TranslatedCrate::default(),
+        profile: options.profile.then(Profile::new),
+        trait_resolution_cache: TraitResolutionCache::default(),
     };
 
     // First push all the items in the stack of items to translate.
@@ -305,6 +466,9 @@ pub fn translate<'tcx, 'ctx>(
         };
         ctx.register_local_hir_item(true, item)?;
     }
+    if let Some(profile) = &mut ctx.profile {
+        profile.end_phase("exploration of the crate");
+    }
 
     trace!("Stack after we explored the crate:\n{:?}", &ctx.stack);
 
@@ -319,14 +483,37 @@ pub fn translate<'tcx, 'ctx>(
     // from Rust ids to translated ids.
     while let Some(id) = ctx.stack.pop_first() {
         trace!("About to translate id: {:?}", id);
+        let start = Instant::now();
         match id {
             OrdRustId::Type(id) => ctx.translate_type(id),
             OrdRustId::Fun(id) | OrdRustId::ConstFun(id) => ctx.translate_function(id),
             OrdRustId::Global(id) => ctx.translate_global(id),
             OrdRustId::TraitDecl(id) => ctx.translate_trait_decl(id),
             OrdRustId::TraitImpl(id) => ctx.translate_trait_impl(id),
+            OrdRustId::TypeAlias(id) => ctx.translate_type_alias(id),
+        }
+        if let Some(profile) = &mut ctx.profile {
+            let name = tcx.def_path_str(id.get_id());
+            profile.record_item(name, start.elapsed());
         }
     }
+    if let Some(profile) = &mut ctx.profile {
+        profile.end_phase("translation of the items");
+        let cache = &ctx.trait_resolution_cache;
+        profile.record_cache(
+            "trait references",
+            cache.impl_expr_hits,
+            cache.impl_expr_misses,
+            cache.impl_exprs_len(),
+        );
+        profile.record_cache("trait resolution", cache.hits, cache.misses, cache.len());
+    }
+
+    // Compute the module tree of the crate.
+    ctx.translate_modules()?;
+    if let Some(profile) = &mut ctx.profile {
+        profile.end_phase("module tree");
+    }
 
     // Return the context
     Ok(ctx)
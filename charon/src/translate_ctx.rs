@@ -4,11 +4,16 @@ use crate::formatter::{DeclFormatter, FmtCtx, Formatter, IntoFormatter};
 use crate::gast::*;
 use crate::get_mir::MirLevel;
 use crate::llbc_ast;
-use crate::meta::{self, Attribute, ItemMeta, Span};
-use crate::meta::{FileId, FileName, InlineAttr, LocalFileId, Meta, VirtualFileId};
+use crate::meta::{self, Attribute, ItemMeta, Span, Visibility};
+use crate::meta::{
+    FileId, FileName, InlineAttr, LocalFileId, MacroExpansion, MacroExpansionId, Meta,
+    VirtualFileId,
+};
 use crate::names::Name;
+use crate::profile::Profile;
 use crate::reorder_decls::{AnyTransId, DeclarationGroup, DeclarationsGroups, GDeclarationGroup};
-use crate::translate_predicates::NonLocalTraitClause;
+use crate::report::UnsolvedObligation;
+use crate::translate_predicates::{NonLocalTraitClause, TraitResolutionCache};
 use crate::types::*;
 use crate::ullbc_ast as ast;
 use crate::values::*;
@@ -22,6 +27,7 @@ use rustc_hir::def_id::DefId;
 use rustc_hir::Node as HirNode;
 use rustc_middle::ty::TyCtxt;
 use rustc_session::Session;
+use rustc_span::hygiene::{ExpnId, ExpnKind};
 use std::cmp::{Ord, Ordering, PartialOrd};
 use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
 use std::fmt;
@@ -115,6 +121,67 @@ impl CrateInfo {
     }
 }
 
+/// Match the path of an item against a pattern of `--extract-external-bodies`:
+/// a `*` matches any path element, and a `*` at the end of the pattern matches
+/// any non-empty suffix.
+fn path_matches(pattern: &[String], path: &[String]) -> bool {
+    match (pattern, path) {
+        ([], []) => true,
+        ([star], [_, ..]) if star == "*" => true,
+        ([p, pattern @ ..], [e, path @ ..]) => (p == "*" || p == e) && path_matches(pattern, path),
+        _ => false,
+    }
+}
+
+/// The attributes in the `charon::` namespace, with which the user can control
+/// the extraction directly from the source code. We register the `charon` tool
+/// with rustc so that it accepts those attributes (see
+/// [crate::driver::CharonCallbacks::run_compiler]).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CharonAttr {
+    /// `#[charon::opaque]`: do not translate the body of the item (nor the
+    /// items it contains, if it is a module or an impl block).
+    Opaque,
+    /// `#[charon::rename("...")]`: export the item with this name.
+    Rename(String),
+    /// `#[charon::skip]`: do not register the item (note that we still
+    /// translate it if another item uses it).
+    Skip,
+    /// `#[charon::ghost]`: the function is ghost code (see [Call::is_ghost]).
+    Ghost,
+}
+
+impl CharonAttr {
+    /// Parse an attribute. Returns `None` if the attribute is not in the
+    /// `charon::` namespace, and an error message if it is malformed.
+    pub(crate) fn parse(attr: &rustc_ast::Attribute) -> Option<Result<Self, String>> {
+        use rustc_ast::ast::{AttrKind, LitKind};
+        let AttrKind::Normal(normal_attr) = &attr.kind else {
+            return None;
+        };
+        let segments = &normal_attr.item.path.segments;
+        if segments.len() != 2 || segments[0].ident.as_str() != "charon" {
+            return None;
+        }
+        let args = attr.meta_item_list();
+        let attr = match (segments[1].ident.as_str(), args.as_deref()) {
+            ("opaque", None) => Ok(CharonAttr::Opaque),
+            ("skip", None) => Ok(CharonAttr::Skip),
+            ("ghost", None) => Ok(CharonAttr::Ghost),
+            ("rename", Some([arg])) => match arg.lit().map(|lit| &lit.kind) {
+                Some(LitKind::Str(name, _)) => Ok(CharonAttr::Rename(name.to_string())),
+                _ => Err("Expected a string literal: `#[charon::rename(\"...\")]`".to_string()),
+            },
+            ("rename", _) => Err("Expected an argument: `#[charon::rename(\"...\")]`".to_string()),
+            (name @ ("opaque" | "skip" | "ghost"), Some(_)) => {
+                Err(format!("`#[charon::{name}]` doesn't take arguments"))
+            }
+            (name, _) => Err(format!("Unknown attribute: `#[charon::{name}]`")),
+        };
+        Some(attr)
+    }
+}
+
 /// We use a special type to store the Rust identifiers in the stack, to
 /// make sure we translate them in a specific order (top-level constants
 /// before constant functions before functions...). This allows us to
@@ -127,17 +194,19 @@ pub enum OrdRustId {
     TraitImpl(DefId),
     Fun(DefId),
     Type(DefId),
+    TypeAlias(DefId),
 }
 
 impl OrdRustId {
-    fn get_id(&self) -> DefId {
+    pub(crate) fn get_id(&self) -> DefId {
         match self {
             OrdRustId::Global(id)
             | OrdRustId::ConstFun(id)
             | OrdRustId::TraitDecl(id)
             | OrdRustId::TraitImpl(id)
             | OrdRustId::Fun(id)
-            | OrdRustId::Type(id) => *id,
+            | OrdRustId::Type(id)
+            | OrdRustId::TypeAlias(id) => *id,
         }
     }
 }
@@ -162,6 +231,70 @@ impl Ord for OrdRustId {
     }
 }
 
+/// The translated declarations. The passes which run after the translation
+/// only need to look up the declarations in this store: they don't need
+/// the (mutable) [TransCtx].
+#[derive(Default)]
+pub struct TranslatedCrate {
+    /// File names to ids and vice-versa
+    pub file_to_id: HashMap<FileName, FileId::Id>,
+    pub id_to_file: HashMap<FileId::Id, FileName>,
+    /// The macro expansions the code comes from (see [Meta::expansion]).
+    pub macro_expansions: MacroExpansionId::Vector<MacroExpansion>,
+    /// The ids of the macro expansions we registered.
+    pub expansion_to_id: HashMap<ExpnId, MacroExpansionId::Id>,
+    /// The module tree of the crate (see [crate::translate_crate_to_ullbc::translate_modules]).
+    pub modules: ModuleId::Vector<Module>,
+    /// The translated type definitions
+    pub type_decls: TypeDecls,
+    /// The translated function definitions
+    pub fun_decls: ast::FunDecls,
+    /// The translated global definitions
+    pub global_decls: ast::GlobalDecls,
+    /// The translated trait declarations
+    pub trait_decls: ast::TraitDecls,
+    /// The translated trait implementations
+    pub trait_impls: ast::TraitImpls,
+    /// The translated type aliases (see [TypeAliasDecl]).
+    pub type_aliases: TypeAliasId::Map<TypeAliasDecl>,
+    /// The re-ordered groups of declarations, initialized as empty.
+    pub ordered_decls: Option<DeclarationsGroups>,
+    /// For every group of [Self::ordered_decls], the indices of the groups it
+    /// depends on (those indices are always smaller than the index of the group).
+    pub ordered_decls_deps: Vec<Vec<usize>>,
+    /// The verification entry points (see [Harness]).
+    pub harnesses: Vec<Harness>,
+    /// The functions marked with `#[charon::ghost]`.
+    pub ghost_functions: Vec<ast::FunDeclId::Id>,
+}
+
+/// The context used to report the errors.
+pub struct ErrorCtx<'ctx> {
+    /// The compiler session, used to print the errors
+    pub session: &'ctx Session,
+    /// Do not abort on the first error and attempt to extract as much as possible.
+    pub continue_on_failure: bool,
+    /// Print the errors as warnings, and do not
+    pub errors_as_warnings: bool,
+    /// The number of errors encountered so far.
+    pub error_count: usize,
+    /// The id of the definition we are exploring, if any: we register it
+    /// in [Self::decls_with_errors] if we encounter an error.
+    pub def_id: Option<DefId>,
+    /// Dependency graph with sources. We use this for error reporting.
+    /// See [DepSource].
+    pub dep_sources: HashMap<DefId, HashSet<DepSource>>,
+    /// The ids of the declarations for which extraction we encountered errors,
+    /// together with the error messages.
+    pub decls_with_errors: HashMap<DefId, Vec<String>>,
+    /// The ids of the declarations we completely failed to extract
+    /// and had to ignore.
+    pub ignored_failed_decls: HashSet<DefId>,
+    /// The trait obligations we failed to solve (see
+    /// [crate::transform::solve_trait_obligations]).
+    pub unsolved_obligations: Vec<UnsolvedObligation>,
+}
+
 /// Translation context containing the top-level definitions.
 pub struct TransCtx<'tcx, 'ctx> {
     /// The compiler session
@@ -174,62 +307,87 @@ pub struct TransCtx<'tcx, 'ctx> {
     pub mir_level: MirLevel,
     ///
     pub crate_info: CrateInfo,
-    /// Do not abort on the first error and attempt to extract as much as possible.
-    pub continue_on_failure: bool,
-    /// Print the errors as warnings, and do not
-    pub errors_as_warnings: bool,
-    /// The number of errors encountered so far.
-    pub error_count: usize,
+    /// Information about the target we compile for
+    pub target_info: TargetInfo,
+    /// The cargo profile we compile the crate with (see `--cargo-profile`)
+    pub cargo_profile: String,
+    /// The errors we encountered so far
+    pub errors: ErrorCtx<'ctx>,
     /// Error out if some code ends up being duplicated by the control-flow
     /// reconstruction (note that because several patterns in a match may lead
     /// to the same branch, it is node always possible not to duplicate code).
     pub no_code_duplication: bool,
+    /// Whether to keep the `StorageLive` and `StorageDead` statements, rather
+    /// than ignoring the former and translating the latter to drops in the LLBC.
+    pub keep_storage_statements: bool,
     /// Whether to extract the bodies of foreign methods and structs with private fields.
     pub extract_opaque_bodies: bool,
+    /// The patterns of the external items whose bodies we extract (see
+    /// [TransCtx::extract_external_body]).
+    pub extract_external_bodies: Vec<Vec<String>>,
+    /// Whether to use the models of the standard library functions (see
+    /// [crate::builtin_models]).
+    pub use_builtin_models: bool,
+    /// Whether to translate the type aliases, and to record the aliases the
+    /// user wrote for the types (see [TypeAliasDecl]).
+    pub translate_type_aliases: bool,
+    /// The patterns of the functions whose calls we translate to
+    /// domain-specific intrinsics (see [TransCtx::intrinsic_kind]): the ones
+    /// registered by the tool embedding Charon, followed by the built-ins of the
+    /// verifiers (see [crate::assumed::get_verifier_primitives]).
+    pub intrinsics: Vec<(Vec<String>, IntrinsicKind)>,
+    /// The paths of the attributes which mark the verification entry points
+    /// (see [crate::assumed::get_harness_attributes]).
+    pub harness_attributes: Vec<Vec<String>>,
+    /// The names of the traits whose clauses we filter (see [crate::assumed::get_ignored_traits]).
+    pub ignored_traits: Vec<Vec<String>>,
+    /// Whether to erase the regions in the bodies, rather than using the region
+    /// variables computed by the borrow checker (see [crate::gast::BodyRegions]).
+    pub erase_body_regions: bool,
+    /// Whether to export the facts computed by the borrow checker for the
+    /// bodies (see [crate::gast::BorrowckFacts]).
+    pub borrowck_facts: bool,
     /// All the ids, in the order in which we encountered them
     pub all_ids: LinkedHashSet<AnyTransId>,
     /// The declarations we came accross and which we haven't translated yet.
     /// We use an ordered set to make sure we translate them in a specific
     /// order (this avoids stealing issues when querying the MIR bodies).
     pub stack: BTreeSet<OrdRustId>,
-    /// The id of the definition we are exploring
-    pub def_id: Option<DefId>,
-    /// File names to ids and vice-versa
-    pub file_to_id: HashMap<FileName, FileId::Id>,
-    pub id_to_file: HashMap<FileId::Id, FileName>,
     pub real_file_counter: LocalFileId::Generator,
     pub virtual_file_counter: VirtualFileId::Generator,
     /// The map from Rust type ids to translated type ids
     pub type_id_map: TypeDeclId::MapGenerator<DefId>,
-    /// The translated type definitions
-    pub type_decls: TypeDecls,
-    /// Dependency graph with sources. We use this for error reporting.
-    /// See [DepSource].
-    pub dep_sources: HashMap<DefId, HashSet<DepSource>>,
-    /// The ids of the declarations for which extraction we encountered errors.
-    pub decls_with_errors: HashSet<DefId>,
-    /// The ids of the declarations we completely failed to extract
-    /// and had to ignore.
-    pub ignored_failed_decls: HashSet<DefId>,
     /// The map from Rust function ids to translated function ids
     pub fun_id_map: ast::FunDeclId::MapGenerator<DefId>,
-    /// The translated function definitions
-    pub fun_decls: ast::FunDecls,
     /// The map from Rust global ids to translated global ids
     pub global_id_map: ast::GlobalDeclId::MapGenerator<DefId>,
-    /// The translated global definitions
-    pub global_decls: ast::GlobalDecls,
     /// The map from Rust trait decl ids to translated trait decl ids
     pub trait_decl_id_map: ast::TraitDeclId::MapGenerator<DefId>,
-    /// The translated trait declarations
-    pub trait_decls: ast::TraitDecls,
     /// The map from Rust trait impls ids to translated trait impls ids
     pub trait_impl_id_map: ast::TraitImplId::MapGenerator<DefId>,
     pub trait_impl_id_to_def_id: HashMap<ast::TraitImplId::Id, DefId>,
-    /// The translated trait declarations
-    pub trait_impls: ast::TraitImpls,
-    /// The re-ordered groups of declarations, initialized as empty.
-    pub ordered_decls: Option<DeclarationsGroups>,
+    /// The map from Rust module ids to translated module ids
+    pub module_id_map: HashMap<DefId, ModuleId::Id>,
+    /// The map from Rust type alias ids to translated type alias ids
+    pub type_alias_id_map: TypeAliasId::MapGenerator<DefId>,
+    /// The items we didn't explore because of the options or the attributes,
+    /// together with their kinds and the reason why we skipped them. We use
+    /// this for the report (see [crate::report]).
+    pub skipped_items: Vec<(Name, &'static str, &'static str)>,
+    /// The translated declarations
+    pub translated: TranslatedCrate,
+    /// The profiling report, if the user asked for it (see [crate::profile])
+    pub profile: Option<Profile>,
+    /// The cache of the resolved trait obligations, shared by all the items
+    pub trait_resolution_cache: TraitResolutionCache,
+}
+
+/// The context of the micro-passes which transform the bodies: we can
+/// look up the translated declarations and register errors, but not
+/// modify the declarations.
+pub struct TransformCtx<'a, 'ctx> {
+    pub translated: &'a TranslatedCrate,
+    pub errors: &'a mut ErrorCtx<'ctx>,
 }
 
 /// A translation context for type/global/function bodies.
@@ -242,7 +400,7 @@ pub struct TransCtx<'tcx, 'ctx> {
 /// us to use those collections.
 pub(crate) struct BodyTransCtx<'tcx, 'ctx, 'ctx1> {
     /// The definition we are currently extracting.
-    /// TODO: this duplicates the field of [TransCtx]
+    /// TODO: this duplicates the field of [ErrorCtx]
     pub def_id: DefId,
     /// The translation context containing the top-level definitions/ids.
     pub t_ctx: &'ctx mut TransCtx<'tcx, 'ctx1>,
@@ -281,11 +439,26 @@ pub(crate) struct BodyTransCtx<'tcx, 'ctx, 'ctx1> {
     /// ==============
     /// We use DeBruijn indices. See the comments for [Region::Var].
     pub bound_region_vars: im::Vector<im::Vector<RegionId::Id>>,
+    /// The map from the region variables computed by the borrow checker to
+    /// translated regions. This is only used when translating a body without
+    /// erasing the regions.
+    ///
+    /// The regions of the signature are mapped to [Region::BVar] with De Bruijn
+    /// index 0: we update the index depending on the current stack of bound
+    /// region groups.
+    pub body_regions_map: HashMap<u32, Region>,
+    /// The region variables of the body, and the constraints between them
+    pub body_regions: BodyRegions,
     /// The type variables
     pub type_vars: TypeVarId::Vector<TypeVar>,
     /// The map from rust type variable indices to translated type variable
     /// indices.
     pub type_vars_map: TypeVarId::MapGenerator<u32>,
+    /// The aliases (projections on generic associated types, opaque types)
+    /// that we can't translate from the hax types, indexed by the type
+    /// parameters which replace them in the rustc types (see
+    /// [BodyTransCtx::fold_aliases]).
+    pub folded_aliases: HashMap<u32, rustc_middle::ty::Ty<'tcx>>,
     /// The "regular" variables
     pub vars: VarId::Vector<ast::Var>,
     /// The map from rust variable indices to translated variables indices.
@@ -302,6 +475,10 @@ pub(crate) struct BodyTransCtx<'tcx, 'ctx, 'ctx1> {
     /// TODO: we don't need something as generic anymore because most of the
     /// work of solving the trait obligations is now done in hax.
     pub trait_clauses: OrdMap<TraitInstanceId, NonLocalTraitClause>,
+    /// The id of [Self::trait_clauses] in the cache of the resolved trait
+    /// obligations (see [TraitResolutionCache]). We compute it lazily, and
+    /// reset it whenever we update the trait clauses.
+    pub trait_env: Option<usize>,
     /// If [true] it means we are currently registering trait clauses in the
     /// local context. As a consequence, we allow not solving all the trait
     /// obligations, because the obligations for some clauses may be solved
@@ -322,13 +499,53 @@ pub(crate) struct BodyTransCtx<'tcx, 'ctx, 'ctx1> {
     /// Note that when translating terminators like DropAndReplace, we might have
     /// to introduce new blocks which don't appear in the original MIR.
     pub blocks_map: ast::BlockId::MapGenerator<hax::BasicBlock>,
+    /// For every translated block, the map from the indices of the MIR
+    /// statements to the indices of the translated statements (the last
+    /// index is the one of the terminator). We need this because we ignore
+    /// some statements.
+    pub statement_indices: HashMap<ast::BlockId::Id, Vec<usize>>,
     /// We register the blocks to translate in a stack, so as to avoid
     /// writing the translation functions as recursive functions. We do
     /// so because we had stack overflows in the past.
     pub blocks_stack: VecDeque<hax::BasicBlock>,
+    /// The adjustments of the receivers of the method calls of the body, indexed by the MIR
+    /// locations of the calls (see [Call::receiver_adjustments]).
+    pub receiver_adjustments: HashMap<rustc_middle::mir::Location, Vec<Adjustment>>,
+    /// The functions called by the body and their rustc arguments, indexed by
+    /// the MIR locations of the calls: hax doesn't give us the types of the
+    /// function items in the arguments (see
+    /// [BodyTransCtx::translate_fn_def_args]).
+    pub fn_def_calls:
+        HashMap<rustc_middle::mir::Location, (DefId, rustc_middle::ty::SubstsRef<'tcx>)>,
+    /// The spans of the invocations of the `ghost!` macro in the body. The
+    /// tokens given to the macro keep the spans of the source: we check if the
+    /// calls are inside those spans to know if they are ghost.
+    pub ghost_spans: Vec<rustc_span::Span>,
+    /// The globals in which we translated the promoted constants of the
+    /// current declaration (see [PromotedInfo]). The bodies refer to them with
+    /// [crate::expressions::RawConstantExpr::Promoted].
+    pub promoted_ids: PromotedId::Vector<ast::GlobalDeclId::Id>,
+    /// The generic arguments with which the bodies refer to the promoted
+    /// constants: those have the generics of the current declaration, without
+    /// its late-bound regions.
+    pub promoted_args: GenericArgs,
+    /// The constants of the body which refer to promoted constants, indexed by
+    /// the MIR locations of the statements and terminators in which they
+    /// appear. The constants of a location are listed in the order in which
+    /// they appear, with their spans: we identify them with their spans and
+    /// their order, because several promoted constants may share a span (if
+    /// they come from the same macro invocation).
+    pub promoted_constants:
+        HashMap<rustc_middle::mir::Location, VecDeque<(rustc_span::Span, PromotedId::Id)>>,
+    /// The MIR location of the statement or terminator we are translating.
+    pub location: Option<rustc_middle::mir::Location>,
+    /// The paths of the `offset_of!` operations of the body, indexed by their
+    /// spans (see [NullOp::OffsetOf]). We compute them on the MIR, because we
+    /// need the types of the intermediate fields.
+    pub offset_of_paths: HashMap<rustc_span::Span, Vec<(FieldProjKind, FieldId::Id)>>,
 }
 
-impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
+impl<'ctx> ErrorCtx<'ctx> {
     pub fn continue_on_failure(&self) -> bool {
         self.continue_on_failure
     }
@@ -347,7 +564,10 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
         self.span_err_no_register(span, msg);
         self.increment_error_count();
         if let Some(id) = self.def_id {
-            let _ = self.decls_with_errors.insert(id);
+            self.decls_with_errors
+                .entry(id)
+                .or_default()
+                .push(msg.to_string());
         }
     }
 
@@ -355,10 +575,102 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
         self.error_count += 1;
     }
 
+    /// Register the fact that `id` is a dependency of `src` (if `src` is not `None`).
+    pub(crate) fn register_dep_source(&mut self, src: &Option<DepSource>, id: DefId) {
+        if let Some(src) = src {
+            if src.src_id != id {
+                match self.dep_sources.get_mut(&id) {
+                    None => {
+                        let _ = self.dep_sources.insert(id, HashSet::from([*src]));
+                    }
+                    Some(srcs) => {
+                        let _ = srcs.insert(*src);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Call `f` while registering `def_id` as the definition we are exploring
+    /// (see [Self::def_id]).
+    pub(crate) fn with_def_id<F, T>(&mut self, def_id: DefId, f: F) -> T
+    where
+        F: FnOnce(&mut Self) -> T,
+    {
+        Self::with_def_id_in(self, |errors| errors, def_id, f)
+    }
+
+    /// Same as [Self::with_def_id], for the contexts which contain an error
+    /// context: `errors` retrieves the error context from `ctx`.
+    pub(crate) fn with_def_id_in<C, F, T>(
+        ctx: &mut C,
+        errors: impl Fn(&mut C) -> &mut Self,
+        def_id: DefId,
+        f: F,
+    ) -> T
+    where
+        F: FnOnce(&mut C) -> T,
+    {
+        let current_def_id = errors(ctx).def_id.replace(def_id);
+        let ret = f(ctx);
+        errors(ctx).def_id = current_def_id;
+        ret
+    }
+}
+
+impl<'a, 'ctx> TransformCtx<'a, 'ctx> {
+    pub fn continue_on_failure(&self) -> bool {
+        self.errors.continue_on_failure()
+    }
+
+    pub fn span_err<S: Into<MultiSpan>>(&mut self, span: S, msg: &str) {
+        self.errors.span_err(span, msg)
+    }
+
+    pub(crate) fn with_def_id<F, T>(&mut self, def_id: DefId, f: F) -> T
+    where
+        F: FnOnce(&mut Self) -> T,
+    {
+        ErrorCtx::with_def_id_in(self, |ctx| &mut *ctx.errors, def_id, f)
+    }
+
+    /// Apply a transformation to all the bodies of the functions and globals,
+    /// while registering the id of the current declaration for the errors.
+    pub(crate) fn iter_bodies<F, B>(
+        &mut self,
+        funs: &mut FunDeclId::Map<GFunDecl<B>>,
+        globals: &mut GlobalDeclId::Map<GGlobalDecl<B>>,
+        mut f: F,
+    ) where
+        F: FnMut(&mut Self, &Name, &mut GExprBody<B>),
+    {
+        for (id, name, b) in iter_function_bodies(funs).chain(iter_global_bodies(globals)) {
+            self.with_def_id(id, |ctx| f(ctx, name, b))
+        }
+    }
+}
+
+impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
+    pub fn continue_on_failure(&self) -> bool {
+        self.errors.continue_on_failure()
+    }
+
+    pub fn span_err<S: Into<MultiSpan>>(&mut self, span: S, msg: &str) {
+        self.errors.span_err(span, msg)
+    }
+
+    /// The context of the passes which transform the bodies: see [TransformCtx].
+    pub fn transform_ctx(&mut self) -> TransformCtx<'_, 'ctx> {
+        TransformCtx {
+            translated: &self.translated,
+            errors: &mut self.errors,
+        }
+    }
+
     /// Register a file if it is a "real" file and was not already registered
     fn register_file(&mut self, filename: FileName) -> FileId::Id {
         // Lookup the file if it was already registered
-        match self.file_to_id.get(&filename) {
+        match self.translated.file_to_id.get(&filename) {
             Option::Some(id) => *id,
             Option::None => {
                 // Generate the fresh id
@@ -369,8 +681,8 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
                     }
                     FileName::NotReal(_) => unimplemented!(),
                 };
-                self.file_to_id.insert(filename.clone(), id);
-                self.id_to_file.insert(id, filename);
+                self.translated.file_to_id.insert(filename.clone(), id);
+                self.translated.id_to_file.insert(id, filename);
                 id
             }
         }
@@ -385,18 +697,31 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
     }
 
     /// Compute the meta information for a Rust item identified by its id.
-    pub(crate) fn translate_item_meta_from_rid(&mut self, def_id: DefId) -> ItemMeta {
+    pub(crate) fn translate_item_meta_from_rid(
+        &mut self,
+        def_id: DefId,
+    ) -> Result<ItemMeta, Error> {
         let meta = self.translate_meta_from_rid(def_id);
+        let visibility = match self.translate_visibility_from_rid(def_id, meta.span) {
+            None => None,
+            Some(rustc_middle::ty::Visibility::Public) => Some(Visibility::Public),
+            Some(rustc_middle::ty::Visibility::Restricted(module)) => {
+                Some(Visibility::Restricted(self.def_id_to_name(module)?))
+            }
+        };
         // Default to `false` for impl blocks and closures.
-        let public = self
-            .translate_visibility_from_rid(def_id, meta.span)
-            .unwrap_or(false);
-        ItemMeta {
+        let public = matches!(visibility, Some(Visibility::Public));
+        Ok(ItemMeta {
             meta,
             attributes: self.translate_attributes_from_rid(def_id),
+            cfg: self.translate_cfg_from_rid(def_id),
             inline: self.translate_inline_from_rid(def_id),
             public,
-        }
+            visibility,
+            stable_id: self.tcx.def_path_hash(def_id).0.to_hex(),
+            // We compute the hash once the item is fully translated (see [crate::export])
+            hash: String::new(),
+        })
     }
 
     pub fn translate_span(&mut self, rspan: hax::Span) -> meta::Span {
@@ -431,6 +756,15 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
         let mut scope_data = source_scopes.get(source_info.scope).unwrap();
         let span = self.translate_span(scope_data.span.clone());
 
+        // The scopes record whether the code is inside an `unsafe { ... }` block
+        let in_unsafe_block = matches!(
+            &scope_data.local_data,
+            hax::ClearCrossCrate::Set(hax::SourceScopeLocalData {
+                safety: hax::Safety::ExplicitUnsafe(_),
+                ..
+            })
+        );
+
         // Lookup the top-most inlined parent scope.
         if scope_data.inlined_parent_scope.is_some() {
             while scope_data.inlined_parent_scope.is_some() {
@@ -443,11 +777,13 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
             Meta {
                 span: parent_span,
                 generated_from_span: Some(span),
+                expansion: None,
+                in_unsafe_block,
             }
         } else {
             Meta {
-                span,
-                generated_from_span: None,
+                in_unsafe_block,
+                ..self.translate_meta_with_expansion(span)
             }
         }
     }
@@ -456,15 +792,81 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
     pub(crate) fn translate_meta_from_rspan(&mut self, rspan: hax::Span) -> Meta {
         // Translate the span
         let span = self.translate_span(rspan);
+        self.translate_meta_with_expansion(span)
+    }
 
-        Meta {
-            span,
-            generated_from_span: None,
+    /// Compute the meta information of a (translated) span, looking at the
+    /// macro expansions it comes from. If it comes from a macro expansion, we
+    /// use the span of the (outermost) macro call as the main span: the users
+    /// should see where they called `vec!`, not the definition of `vec!`.
+    fn translate_meta_with_expansion(&mut self, span: meta::Span) -> Meta {
+        let rust_span = span.rust_span_data.span();
+        let expansion = if rust_span.from_expansion() {
+            self.register_macro_expansion(rust_span.ctxt().outer_expn())
+        } else {
+            None
+        };
+        let call_site = match expansion {
+            Some(_) => self.translate_real_span(rust_span.source_callsite()),
+            None => None,
+        };
+        match call_site {
+            Some(call_site) => Meta {
+                span: call_site,
+                generated_from_span: Some(span),
+                expansion,
+                in_unsafe_block: false,
+            },
+            None => Meta {
+                span,
+                generated_from_span: None,
+                expansion,
+                in_unsafe_block: false,
+            },
         }
     }
 
+    /// Translate a Rust span, if it comes from a "real" file.
+    fn translate_real_span(&mut self, span: rustc_span::Span) -> Option<meta::Span> {
+        let rspan: hax::Span = span.sinto(&self.hax_state);
+        match meta::convert_filename(&rspan.filename) {
+            FileName::NotReal(_) => None,
+            FileName::Virtual(_) | FileName::Local(_) => Some(self.translate_span(rspan)),
+        }
+    }
+
+    /// Register a macro expansion, together with the expansions it comes from.
+    /// We skip the expansions which don't come from macros (the desugarings of
+    /// `for` loops, `?`, etc.): in this case, we return the id of the innermost
+    /// macro expansion the code comes from, if any.
+    fn register_macro_expansion(&mut self, expn: ExpnId) -> Option<MacroExpansionId::Id> {
+        if expn == ExpnId::root() {
+            return None;
+        }
+        if let Some(id) = self.translated.expansion_to_id.get(&expn) {
+            return Some(*id);
+        }
+        let data = expn.expn_data();
+        let parent = self.register_macro_expansion(data.call_site.ctxt().outer_expn());
+        let ExpnKind::Macro(_, macro_name) = data.kind else { return parent };
+        let Some(call_site) = self.translate_real_span(data.call_site) else { return parent };
+        let def_site = if data.def_site.is_dummy() {
+            None
+        } else {
+            self.translate_real_span(data.def_site)
+        };
+        let id = self.translated.macro_expansions.push(MacroExpansion {
+            macro_name: macro_name.to_string(),
+            call_site,
+            def_site,
+            parent,
+        });
+        self.translated.expansion_to_id.insert(expn, id);
+        Some(id)
+    }
+
     /// Returns the attributes (`#[...]`) of this item.
-    pub(crate) fn item_attributes(&self, id: DefId) -> &[rustc_ast::Attribute] {
+    pub(crate) fn item_attributes(&self, id: DefId) -> &'tcx [rustc_ast::Attribute] {
         use rustc_hir::hir_id::HirId;
         id.as_local()
             .map(|local_def_id| self.tcx.hir().attrs(HirId::make_owner(local_def_id)))
@@ -495,6 +897,132 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
             .collect()
     }
 
+    /// The name of the lang item this item corresponds to, if any (`add` for `core::ops::Add`,
+    /// `drop` for `Drop`, etc.).
+    pub(crate) fn translate_lang_item_from_rid(&self, id: DefId) -> Option<String> {
+        let lang_item = self.tcx.lang_items().from_def_id(id)?;
+        Some(lang_item.name().to_string())
+    }
+
+    /// Returns the conditions of the `#[cfg(...)]` attributes of the item and of its parents,
+    /// starting with the outermost ones. Note that rustc removed the items whose conditions don't
+    /// hold, but keeps the attributes of the other items.
+    pub(crate) fn translate_cfg_from_rid(&self, id: DefId) -> Vec<String> {
+        let mut ids: Vec<DefId> =
+            std::iter::successors(Some(id), |id| self.tcx.opt_parent(*id)).collect();
+        ids.reverse();
+        ids.into_iter()
+            .flat_map(|id| self.item_attributes(id))
+            .filter(|attr| attr.has_name(rustc_span::sym::cfg))
+            .flat_map(|attr| attr.meta_item_list().unwrap_or_default())
+            .map(|cond| rustc_ast_pretty::pprust::meta_list_item_to_string(&cond))
+            .collect()
+    }
+
+    /// The features enabled for the extraction (with `--cfg 'feature="..."'`, which is what Cargo
+    /// passes to rustc), sorted by name.
+    pub(crate) fn enabled_features(&self) -> Vec<String> {
+        let mut features: Vec<String> = self
+            .session
+            .parse_sess
+            .config
+            .iter()
+            .filter(|(name, _)| *name == rustc_span::sym::feature)
+            .filter_map(|(_, value)| value.map(|value| value.to_string()))
+            .collect();
+        features.sort();
+        features
+    }
+
+    /// If this function is a verification entry point, returns the path of the attribute which
+    /// marks it, and the arguments of this attribute (see [Harness]).
+    pub(crate) fn harness_attribute(&self, id: DefId) -> Option<(String, Vec<String>)> {
+        use rustc_ast::ast::AttrKind;
+        self.item_attributes(id).iter().find_map(|attr| {
+            let AttrKind::Normal(normal_attr) = &attr.kind else {
+                return None;
+            };
+            let path: Vec<String> = normal_attr
+                .item
+                .path
+                .segments
+                .iter()
+                .map(|segment| segment.ident.to_string())
+                .collect();
+            if !self.harness_attributes.contains(&path) {
+                return None;
+            }
+            let args = attr
+                .meta_item_list()
+                .unwrap_or_default()
+                .iter()
+                .map(rustc_ast_pretty::pprust::meta_list_item_to_string)
+                .collect();
+            Some((path.join("::"), args))
+        })
+    }
+
+    /// Returns the well-formed attributes of this item in the `charon::` namespace.
+    pub(crate) fn charon_attributes(&self, id: DefId) -> impl Iterator<Item = CharonAttr> + 'tcx {
+        self.item_attributes(id)
+            .iter()
+            .filter_map(|attr| CharonAttr::parse(attr)?.ok())
+    }
+
+    /// Register an error for every malformed attribute of this item in the `charon::`
+    /// namespace. We call this once per item, when registering it.
+    pub(crate) fn check_charon_attributes(&mut self, id: DefId) {
+        for attr in self.item_attributes(id) {
+            if let Some(Err(msg)) = CharonAttr::parse(attr) {
+                self.span_err(attr.span, &msg);
+            }
+        }
+    }
+
+    /// Whether this item, or one of its parents, is marked with `#[charon::opaque]`.
+    pub(crate) fn id_is_marked_opaque(&self, id: DefId) -> bool {
+        std::iter::successors(Some(id), |id| self.tcx.opt_parent(*id)).any(|id| {
+            self.charon_attributes(id)
+                .any(|attr| attr == CharonAttr::Opaque)
+        })
+    }
+
+    /// Whether this item is marked with `#[charon::skip]`.
+    pub(crate) fn id_is_marked_skip(&self, id: DefId) -> bool {
+        self.charon_attributes(id)
+            .any(|attr| attr == CharonAttr::Skip)
+    }
+
+    /// Whether this function is marked with `#[charon::ghost]`.
+    pub(crate) fn id_is_marked_ghost(&self, id: DefId) -> bool {
+        self.charon_attributes(id)
+            .any(|attr| attr == CharonAttr::Ghost)
+    }
+
+    /// If this span comes from the expansion of a `ghost!` macro, the span of
+    /// the invocation of the macro (see [Call::is_ghost]).
+    pub(crate) fn ghost_call_site(&self, span: rustc_span::Span) -> Option<rustc_span::Span> {
+        let mut expn = span.ctxt().outer_expn();
+        while expn != ExpnId::root() {
+            let data = expn.expn_data();
+            if let ExpnKind::Macro(_, name) = data.kind
+                && name.as_str() == "ghost"
+            {
+                return Some(data.call_site);
+            }
+            expn = data.call_site.ctxt().outer_expn();
+        }
+        None
+    }
+
+    /// The name given with `#[charon::rename("...")]`, if any.
+    pub(crate) fn item_rename(&self, id: DefId) -> Option<String> {
+        self.charon_attributes(id).find_map(|attr| match attr {
+            CharonAttr::Rename(name) => Some(name),
+            _ => None,
+        })
+    }
+
     pub(crate) fn translate_inline_from_rid(&self, id: DefId) -> Option<InlineAttr> {
         use rustc_attr as rustc;
         if !self.tcx.def_kind(id).has_codegen_attrs() {
@@ -510,7 +1038,11 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
 
     /// Returns the visibility of the item/field/etc. Returns `None` for items that don't have a
     /// visibility, like impl blocks.
-    pub(crate) fn translate_visibility_from_rid(&mut self, id: DefId, span: Span) -> Option<bool> {
+    pub(crate) fn translate_visibility_from_rid(
+        &mut self,
+        id: DefId,
+        span: Span,
+    ) -> Option<rustc_middle::ty::Visibility<DefId>> {
         use rustc_hir::def::DefKind::*;
         let def_kind = self.tcx.def_kind(id);
         match def_kind {
@@ -529,7 +1061,7 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
             | TraitAlias
             | TyAlias
             | Union
-            | Use => Some(self.tcx.visibility(id).is_public()),
+            | Use => Some(self.tcx.visibility(id)),
             // These kinds don't have visibility modifiers (which would cause `visibility` to panic).
             Closure | Impl { .. } => None,
             // Kinds we shouldn't be calling this function on.
@@ -558,7 +1090,7 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
     }
 
     /// Whether this item is in an `extern { .. }` block, in which case it has no body.
-    pub(crate) fn id_is_extern_item(&mut self, id: DefId) -> bool {
+    pub(crate) fn id_is_extern_item(&self, id: DefId) -> bool {
         id.as_local().is_some_and(|local_def_id| {
             let node = self.tcx.hir().find_by_def_id(local_def_id);
             matches!(node, Some(HirNode::ForeignItem(_)))
@@ -567,13 +1099,70 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
 
     pub(crate) fn id_is_opaque(&mut self, id: DefId) -> Result<bool, Error> {
         let name = self.def_id_to_name(id)?;
-        Ok(self.crate_info.is_opaque_decl(&name) || self.id_is_extern_item(id))
+        Ok(self.crate_info.is_opaque_decl(&name)
+            || self.id_is_extern_item(id)
+            || self.id_is_marked_opaque(id))
     }
 
     pub(crate) fn id_is_transparent(&mut self, id: DefId) -> Result<bool, Error> {
         Ok(!(self.id_is_opaque(id)?))
     }
 
+    /// The path of an item, as we match it against the patterns of
+    /// `--extract-external-bodies`: the impl blocks are named after the type
+    /// they implement methods for, and we ignore the disambiguators.
+    fn pattern_path(&self, id: DefId) -> Vec<String> {
+        use rustc_hir::definitions::DefPathData;
+        let tcx = self.tcx;
+        let mut path: Vec<DefId> =
+            std::iter::successors(Some(id), |id| tcx.opt_parent(*id)).collect();
+        path.reverse();
+        path.into_iter()
+            .filter_map(|id| match tcx.def_key(id).disambiguated_data.data {
+                DefPathData::CrateRoot => Some(tcx.crate_name(id.krate).to_string()),
+                DefPathData::TypeNs(symbol)
+                | DefPathData::ValueNs(symbol)
+                | DefPathData::MacroNs(symbol) => Some(symbol.to_string()),
+                DefPathData::Impl => {
+                    let ty = tcx.type_of(id).subst_identity();
+                    Some(match ty.ty_adt_def() {
+                        Some(adt) => tcx.item_name(adt.did()).to_string(),
+                        None => ty.to_string(),
+                    })
+                }
+                DefPathData::ClosureExpr => Some("closure".to_string()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Whether we extract the body of an item, if it is external (see the
+    /// `--extract-opaque-bodies` and `--extract-external-bodies` options).
+    pub(crate) fn extract_external_body(&self, id: DefId) -> bool {
+        if self.extract_opaque_bodies {
+            return true;
+        }
+        if self.extract_external_bodies.is_empty() {
+            return false;
+        }
+        let path = self.pattern_path(id);
+        self.extract_external_bodies
+            .iter()
+            .any(|pattern| path_matches(pattern, &path))
+    }
+
+    /// The domain-specific intrinsic to which we translate the calls to a
+    /// function, if the function matches one of the patterns registered with
+    /// [crate::driver::CharonCallbacks::register_intrinsic] or one of the
+    /// built-ins of the verifiers. The registered patterns come first.
+    pub(crate) fn intrinsic_kind(&self, id: DefId) -> Option<IntrinsicKind> {
+        let path = self.pattern_path(id);
+        self.intrinsics
+            .iter()
+            .find(|(pattern, _)| path_matches(pattern, &path))
+            .map(|(_, kind)| kind.clone())
+    }
+
     pub(crate) fn push_id(&mut self, _rust_id: DefId, id: OrdRustId, trans_id: AnyTransId) {
         // Add the id to the stack of declarations to translate
         self.stack.insert(id);
@@ -582,18 +1171,7 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
 
     /// Register the fact that `id` is a dependency of `src` (if `src` is not `None`).
     pub(crate) fn register_dep_source(&mut self, src: &Option<DepSource>, id: DefId) {
-        if let Some(src) = src {
-            if src.src_id != id {
-                match self.dep_sources.get_mut(&id) {
-                    None => {
-                        let _ = self.dep_sources.insert(id, HashSet::from([*src]));
-                    }
-                    Some(srcs) => {
-                        let _ = srcs.insert(*src);
-                    }
-                }
-            }
-        }
+        self.errors.register_dep_source(src, id)
     }
 
     pub(crate) fn register_type_decl_id(
@@ -651,9 +1229,9 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
         id: DefId,
     ) -> Result<Option<ast::TraitDeclId::Id>, Error> {
         use crate::assumed;
-        if assumed::IGNORE_BUILTIN_MARKER_TRAITS {
+        if !self.ignored_traits.is_empty() {
             let name = self.def_id_to_name(id)?;
-            if assumed::is_marker_trait(&name) {
+            if assumed::is_marker_trait(&name, &self.ignored_traits) {
                 return Ok(None);
             }
         }
@@ -751,28 +1329,23 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
         self.register_global_decl_id(src, id)
     }
 
+    /// Register a type alias (see [TypeAliasDecl]). Note that the type aliases
+    /// aren't declarations: they don't appear in the declaration groups.
+    pub(crate) fn register_type_alias_id(&mut self, id: DefId) -> TypeAliasId::Id {
+        match self.type_alias_id_map.get(&id) {
+            Option::Some(id) => id,
+            Option::None => {
+                self.stack.insert(OrdRustId::TypeAlias(id));
+                self.type_alias_id_map.insert(id)
+            }
+        }
+    }
+
     pub(crate) fn with_def_id<F, T>(&mut self, def_id: DefId, f: F) -> T
     where
         F: FnOnce(&mut Self) -> T,
     {
-        let current_def_id = self.def_id;
-        self.def_id = Some(def_id);
-        let ret = f(self);
-        self.def_id = current_def_id;
-        ret
-    }
-
-    pub(crate) fn iter_bodies<F, B>(
-        &mut self,
-        funs: &mut FunDeclId::Map<GFunDecl<B>>,
-        globals: &mut GlobalDeclId::Map<GGlobalDecl<B>>,
-        f: F,
-    ) where
-        F: Fn(&mut Self, &Name, &mut GExprBody<B>),
-    {
-        for (id, name, b) in iter_function_bodies(funs).chain(iter_global_bodies(globals)) {
-            self.with_def_id(id, |ctx| f(ctx, name, b))
-        }
+        ErrorCtx::with_def_id_in(self, |ctx| &mut ctx.errors, def_id, f)
     }
 }
 
@@ -792,21 +1365,34 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
             region_vars: im::vector![RegionId::Vector::new()],
             free_region_vars: std::collections::BTreeMap::new(),
             bound_region_vars: im::Vector::new(),
+            body_regions_map: HashMap::new(),
+            body_regions: BodyRegions::default(),
             type_vars: TypeVarId::Vector::new(),
             type_vars_map: TypeVarId::MapGenerator::new(),
+            folded_aliases: HashMap::new(),
             vars: VarId::Vector::new(),
             vars_map: VarId::MapGenerator::new(),
             const_generic_vars: ConstGenericVarId::Vector::new(),
             const_generic_vars_map: ConstGenericVarId::MapGenerator::new(),
             trait_instance_id_gen,
             trait_clauses: OrdMap::new(),
+            trait_env: None,
             registering_trait_clauses: false,
             regions_outlive: Vec::new(),
             types_outlive: Vec::new(),
             trait_type_constraints: Vec::new(),
             blocks: im::OrdMap::new(),
             blocks_map: ast::BlockId::MapGenerator::new(),
+            statement_indices: HashMap::new(),
             blocks_stack: VecDeque::new(),
+            receiver_adjustments: HashMap::new(),
+            fn_def_calls: HashMap::new(),
+            ghost_spans: Vec::new(),
+            promoted_ids: PromotedId::Vector::new(),
+            promoted_args: GenericArgs::empty(),
+            promoted_constants: HashMap::new(),
+            location: None,
+            offset_of_paths: HashMap::new(),
         }
     }
 
@@ -949,23 +1535,42 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
         res
     }
 
-    pub(crate) fn push_type_var(&mut self, rindex: u32, name: String) -> TypeVarId::Id {
+    pub(crate) fn push_type_var(
+        &mut self,
+        rindex: u32,
+        name: String,
+        is_sized: bool,
+    ) -> TypeVarId::Id {
         let var_id = self.type_vars_map.insert(rindex);
         assert!(var_id == self.type_vars.next_id());
-        self.type_vars.push_with(|index| TypeVar { index, name })
+        self.type_vars.push_with(|index| TypeVar {
+            index,
+            name,
+            is_sized,
+            default: None,
+        })
     }
 
     pub(crate) fn push_var(&mut self, rid: usize, ty: Ty, name: Option<String>) {
         let var_id = self.vars_map.insert(rid);
         assert!(var_id == self.vars.next_id());
-        self.vars.push_with(|index| ast::Var { index, name, ty });
+        self.vars.push_with(|index| ast::Var {
+            index,
+            name,
+            ty,
+            debug_info: Vec::new(),
+        });
     }
 
     pub(crate) fn push_const_generic_var(&mut self, rid: u32, ty: LiteralTy, name: String) {
         let var_id = self.const_generic_vars_map.insert(rid);
         assert!(var_id == self.const_generic_vars.next_id());
-        self.const_generic_vars
-            .push_with(|index| (ConstGenericVar { index, name, ty }));
+        self.const_generic_vars.push_with(|index| ConstGenericVar {
+            index,
+            name,
+            ty,
+            default: None,
+        });
     }
 
     pub(crate) fn fresh_block_id(&mut self, rid: hax::BasicBlock) -> ast::BlockId::Id {
@@ -997,8 +1602,8 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
             .iter()
             .filter_map(|(_, x)| x.to_local_trait_clause())
             .collect();
-        // Sanity check
-        if !crate::assumed::IGNORE_BUILTIN_MARKER_TRAITS {
+        // Sanity check: if we don't filter any trait, the indices are contiguous
+        if self.t_ctx.ignored_traits.is_empty() {
             assert!(clauses
                 .iter()
                 .enumerate()
@@ -1029,6 +1634,8 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
             regions_outlive: self.regions_outlive.clone(),
             types_outlive: self.types_outlive.clone(),
             trait_type_constraints: self.trait_type_constraints.clone(),
+            // Computed later, by [crate::transform::compute_regions_graph]
+            regions_graph: RegionGraph::default(),
         }
     }
 
@@ -1078,7 +1685,7 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
     }
 }
 
-impl<'tcx, 'ctx, 'a> IntoFormatter for &'a TransCtx<'tcx, 'ctx> {
+impl<'a> IntoFormatter for &'a TranslatedCrate {
     type C = FmtCtx<'a>;
 
     fn into_fmt(self) -> Self::C {
@@ -1096,16 +1703,32 @@ impl<'tcx, 'ctx, 'a> IntoFormatter for &'a TransCtx<'tcx, 'ctx> {
     }
 }
 
+impl<'tcx, 'ctx, 'a> IntoFormatter for &'a TransCtx<'tcx, 'ctx> {
+    type C = FmtCtx<'a>;
+
+    fn into_fmt(self) -> Self::C {
+        self.translated.into_fmt()
+    }
+}
+
+impl<'a, 'b, 'ctx> IntoFormatter for &'a TransformCtx<'b, 'ctx> {
+    type C = FmtCtx<'a>;
+
+    fn into_fmt(self) -> Self::C {
+        self.translated.into_fmt()
+    }
+}
+
 impl<'tcx, 'ctx, 'ctx1, 'a> IntoFormatter for &'a BodyTransCtx<'tcx, 'ctx, 'ctx1> {
     type C = FmtCtx<'a>;
 
     fn into_fmt(self) -> Self::C {
         FmtCtx {
-            type_decls: Some(&self.t_ctx.type_decls),
-            fun_decls: Some(&self.t_ctx.fun_decls),
-            global_decls: Some(&self.t_ctx.global_decls),
-            trait_decls: Some(&self.t_ctx.trait_decls),
-            trait_impls: Some(&self.t_ctx.trait_impls),
+            type_decls: Some(&self.t_ctx.translated.type_decls),
+            fun_decls: Some(&self.t_ctx.translated.fun_decls),
+            global_decls: Some(&self.t_ctx.translated.global_decls),
+            trait_decls: Some(&self.t_ctx.translated.trait_decls),
+            trait_impls: Some(&self.t_ctx.translated.trait_impls),
             region_vars: self.region_vars.clone(),
             type_vars: Some(&self.type_vars),
             const_generic_vars: Some(&self.const_generic_vars),
@@ -1130,7 +1753,7 @@ impl<'a> FmtCtx<'a> {
     }
 }
 
-impl<'tcx, 'ctx> fmt::Display for TransCtx<'tcx, 'ctx> {
+impl fmt::Display for TranslatedCrate {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let fmt: FmtCtx = self.into_fmt();
 
@@ -1175,7 +1798,13 @@ impl<'tcx, 'ctx> fmt::Display for TransCtx<'tcx, 'ctx> {
     }
 }
 
-impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
+impl<'tcx, 'ctx> fmt::Display for TransCtx<'tcx, 'ctx> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.translated)
+    }
+}
+
+impl TranslatedCrate {
     fn fmt_with_llbc_defs(
         &self,
         f: &mut fmt::Formatter,
@@ -1239,13 +1868,13 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
     }
 }
 
-pub(crate) struct LlbcTransCtx<'a, 'tcx, 'ctx> {
-    pub ctx: &'a TransCtx<'tcx, 'ctx>,
+pub(crate) struct LlbcTransCtx<'a> {
+    pub ctx: &'a TranslatedCrate,
     pub llbc_globals: &'a llbc_ast::GlobalDecls,
     pub llbc_funs: &'a llbc_ast::FunDecls,
 }
 
-impl<'a, 'tcx, 'ctx> fmt::Display for LlbcTransCtx<'a, 'tcx, 'ctx> {
+impl<'a> fmt::Display for LlbcTransCtx<'a> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         self.ctx
             .fmt_with_llbc_defs(f, self.llbc_globals, self.llbc_funs)
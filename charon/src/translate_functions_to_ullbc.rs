@@ -7,7 +7,14 @@ use crate::assumed;
 use crate::common::*;
 use crate::expressions::*;
 use crate::formatter::{Formatter, IntoFormatter};
-use crate::get_mir::{boxes_are_desugared, get_mir_for_def_id_and_level};
+use crate::get_mir::{
+    boxes_are_desugared, get_mir_for_def_id_and_level, get_mir_with_borrowck_facts,
+    get_promoted_mir_for_def_id_and_level,
+};
+use crate::meta::Meta;
+use crate::names::{Disambiguator, PathElem};
+use crate::reorder_decls::AnyTransId;
+use crate::translate_constants::string_literal;
 use crate::translate_ctx::*;
 use crate::translate_types;
 use crate::types::*;
@@ -15,9 +22,17 @@ use crate::ullbc_ast::*;
 use crate::values::*;
 use hax_frontend_exporter as hax;
 use hax_frontend_exporter::SInto;
-use rustc_hir::def_id::DefId;
-use rustc_middle::mir::START_BLOCK;
+use rustc_borrowck::consumers::{
+    calculate_borrows_out_of_scope_at_location, BodyWithBorrowckFacts, BorrowIndex,
+    ConsumerOptions, RegionInferenceContext, RichLocation,
+};
+use rustc_hir::def::DefKind;
+use rustc_hir::def_id::{DefId, LocalDefId};
+use rustc_index::IndexVec;
+use rustc_middle::mir::visit::Visitor as MirVisitor;
+use rustc_middle::mir::{Promoted, VarDebugInfoContents, START_BLOCK};
 use rustc_middle::ty;
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
 use translate_types::translate_bound_region_kind_name;
 
 pub(crate) struct SubstFunId {
@@ -30,6 +45,31 @@ pub(crate) enum SubstFunIdOrPanic {
     Fun(SubstFunId),
 }
 
+/// Collect the references to the promoted constants in a MIR body (see
+/// [BodyTransCtx::promoted_constants]).
+struct PromotedRefs(
+    HashMap<rustc_middle::mir::Location, VecDeque<(rustc_span::Span, PromotedId::Id)>>,
+);
+
+impl<'tcx> MirVisitor<'tcx> for PromotedRefs {
+    fn visit_constant(
+        &mut self,
+        constant: &rustc_middle::mir::Constant<'tcx>,
+        location: rustc_middle::mir::Location,
+    ) {
+        if let rustc_middle::mir::ConstantKind::Unevaluated(uv, _) = constant.literal {
+            if let Some(promoted) = uv.promoted {
+                let index = PromotedId::Id::new(promoted.as_usize());
+                self.0
+                    .entry(location)
+                    .or_default()
+                    .push_back((constant.span, index))
+            }
+        }
+        self.super_constant(constant, location)
+    }
+}
+
 fn translate_variant_id(id: hax::VariantIdx) -> VariantId::Id {
     VariantId::Id::new(id)
 }
@@ -69,24 +109,20 @@ fn translate_unaryop_kind(binop: hax::UnOp) -> UnOp {
 /// Small utility
 pub(crate) fn check_impl_item(impl_item: &rustc_hir::Impl<'_>) {
     // TODO: make proper error messages
-    use rustc_hir::{Constness, Defaultness, ImplPolarity, Unsafety};
+    use rustc_hir::Unsafety;
     assert!(impl_item.unsafety == Unsafety::Normal);
-    // About polarity:
-    // [https://doc.rust-lang.org/beta/unstable-book/language-features/negative-impls.html]
-    // Not sure about what I should do about it. Should I do anything, actually?
-    // This seems useful to enforce some discipline on the user-side, but not
-    // necessary for analysis purposes.
-    assert!(impl_item.polarity == ImplPolarity::Positive);
-    // Note sure what this is about
-    assert!(impl_item.defaultness == Defaultness::Final);
-    // Note sure what this is about
-    assert!(impl_item.constness == Constness::NotConst);
+    // The negative impls are translated like the other trait impls (see
+    // [TraitImpl::is_negative]).
+    // The `default impl` are handled like the impls with `default` items (see
+    // [TraitInstanceId::Specializable])
+    // The `impl const` are translated like the other impls (see
+    // [TraitImpl::is_const]).
 }
 
 impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
     fn translate_binaryop_kind(
         &mut self,
-        span: rustc_span::Span,
+        _span: rustc_span::Span,
         binop: hax::BinOp,
     ) -> Result<BinOp, Error> {
         match binop {
@@ -106,9 +142,25 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
             hax::BinOp::Mul => Ok(BinOp::Mul),
             hax::BinOp::Shl => Ok(BinOp::Shl),
             hax::BinOp::Shr => Ok(BinOp::Shr),
-            hax::BinOp::Offset => {
-                error_or_panic!(self, span, "Unsupported binary operation: offset")
+            hax::BinOp::Offset => Ok(BinOp::Offset),
+        }
+    }
+
+    /// Translate the message of a MIR assertion to the kind of dynamic check it performs.
+    fn translate_assert_kind(
+        &mut self,
+        span: rustc_span::Span,
+        msg: &hax::AssertKind,
+    ) -> Result<AssertKind, Error> {
+        match msg {
+            hax::AssertKind::BoundsCheck { .. } => Ok(AssertKind::Bounds),
+            hax::AssertKind::Overflow { .. } | hax::AssertKind::OverflowNeg { .. } => {
+                Ok(AssertKind::Overflow)
+            }
+            hax::AssertKind::DivisionByZero { .. } | hax::AssertKind::RemainderByZero { .. } => {
+                Ok(AssertKind::DivisionByZero)
             }
+            _ => error_or_panic!(self, span, format!("Unsupported assertion: {:?}", msg)),
         }
     }
 
@@ -230,8 +282,176 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
 }
 
 impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
+    /// Translate the region variables computed by the borrow checker, and the
+    /// outlives constraints between them (see [BodyRegions]). This must be
+    /// called after we translated the signature.
+    ///
+    /// The universal regions (i.e., `'static` and the regions of the signature)
+    /// are mapped to the corresponding regions of the signature, while the
+    /// other region variables are mapped to fresh body regions.
+    fn translate_body_regions(&mut self, def_id: DefId, rctx: &RegionInferenceContext<'tcx>) {
+        let tcx = self.t_ctx.tcx;
+        let mut universal_regions: HashMap<ty::RegionVid, Region> = HashMap::new();
+        universal_regions.insert(rctx.to_region_vid(tcx.lifetimes.re_static), Region::Static);
+
+        // The early-bound regions
+        let db_id = DeBruijnId::new(0);
+        let substs = ty::InternalSubsts::identity_for_item(tcx, def_id);
+        for r in substs.regions() {
+            let hax_region: hax::Region = r.sinto(&self.hax_state);
+            if let Some(rid) = self.free_region_vars.get(&hax_region) {
+                universal_regions.insert(rctx.to_region_vid(r), Region::BVar(db_id, *rid));
+            }
+        }
+
+        // The late-bound regions: the borrow checker liberates them. Note that
+        // their group is the outermost one.
+        let bound_vars = match tcx.def_kind(def_id) {
+            DefKind::Fn | DefKind::AssocFn => tcx.fn_sig(def_id).subst_identity().bound_vars(),
+            DefKind::Closure => match tcx.type_of(def_id).subst_identity().kind() {
+                ty::TyKind::Closure(_, substs) => substs.as_closure().sig().bound_vars(),
+                _ => unreachable!(),
+            },
+            _ => ty::List::empty(),
+        };
+        let late_bound_rids = self.bound_region_vars.back().cloned().unwrap_or_default();
+        for (bvar, rid) in bound_vars.iter().zip(late_bound_rids) {
+            if let ty::BoundVariableKind::Region(br) = bvar {
+                let r = ty::Region::new_free(tcx, def_id, br);
+                universal_regions.insert(rctx.to_region_vid(r), Region::BVar(db_id, rid));
+            }
+        }
+
+        // Map the region variables
+        for vid in rctx.regions() {
+            let r = match universal_regions.get(&vid) {
+                Some(r) => *r,
+                None => {
+                    let vars = &mut self.body_regions.vars;
+                    Region::Body(vars.push_with(|index| RegionVar { index, name: None }))
+                }
+            };
+            self.body_regions_map.insert(vid.as_u32(), r);
+        }
+
+        // Translate the constraints
+        let mut constraints: Vec<RegionOutlives> = rctx
+            .outlives_constraints()
+            .map(|c| {
+                OutlivesPred(
+                    self.body_regions_map[&c.sup.as_u32()],
+                    self.body_regions_map[&c.sub.as_u32()],
+                )
+            })
+            .filter(|OutlivesPred(r0, r1)| r0 != r1)
+            .collect();
+        constraints.sort_by_key(|OutlivesPred(r0, r1)| (*r0, *r1));
+        constraints.dedup();
+        self.body_regions.constraints = constraints;
+    }
+
+    /// Translate a MIR location. Return `None` if we didn't translate the block.
+    fn translate_location(&self, loc: rustc_middle::mir::Location) -> Option<Location> {
+        let block = self.get_block_id_from_rid(rustc_index::Idx::new(loc.block.as_usize()))?;
+        let statement_index = *self
+            .statement_indices
+            .get(&block)?
+            .get(loc.statement_index)?;
+        Some(Location {
+            block,
+            statement_index,
+        })
+    }
+
+    /// Translate the facts computed by the borrow checker. This must be called
+    /// after we translated the body regions and the blocks.
+    fn translate_borrowck_facts(&self, facts: &BodyWithBorrowckFacts<'tcx>) -> BorrowckFacts {
+        let region = |vid: ty::RegionVid| self.body_regions_map[&vid.as_u32()];
+
+        // The loans: we ignore the loans which happen in blocks that we
+        // didn't translate
+        let mut loans = LoanId::Vector::new();
+        let mut loan_ids: HashMap<usize, LoanId::Id> = HashMap::new();
+        for (bid, data) in facts.borrow_set.location_map.values().enumerate() {
+            if let Some(location) = self.translate_location(data.reserve_location) {
+                let region = region(data.region);
+                let id = loans.push_with(|index| Loan {
+                    index,
+                    region,
+                    location,
+                });
+                loan_ids.insert(bid, id);
+            }
+        }
+        let loan = |l: BorrowIndex| loan_ids.get(&l.as_usize()).copied();
+
+        // The Polonius facts. Those use points, which distinguish the start
+        // and the middle of the statements: we ignore this distinction.
+        let location_table = facts.location_table.as_ref().unwrap();
+        let point = |p| match location_table.to_location(p) {
+            RichLocation::Start(loc) | RichLocation::Mid(loc) => self.translate_location(loc),
+        };
+        let input_facts = facts.input_facts.as_ref().unwrap();
+        let mut subset_constraints: Vec<(Region, Region, Location)> = input_facts
+            .subset_base
+            .iter()
+            .filter_map(|(r0, r1, p)| Some((region(*r0), region(*r1), point(*p)?)))
+            .filter(|(r0, r1, _)| r0 != r1)
+            .collect();
+        subset_constraints.sort();
+        subset_constraints.dedup();
+        let loan_facts = |facts: Vec<(BorrowIndex, _)>| -> Vec<(LoanId::Id, Location)> {
+            let mut facts: Vec<_> = facts
+                .into_iter()
+                .filter_map(|(l, p)| Some((loan(l)?, point(p)?)))
+                .collect();
+            facts.sort();
+            facts.dedup();
+            facts
+        };
+        // Note that `loan_invalidated_at` is indexed by the points first
+        let loans_invalidated_at = loan_facts(
+            input_facts
+                .loan_invalidated_at
+                .iter()
+                .map(|(p, l)| (*l, *p))
+                .collect(),
+        );
+        let loans_killed_at = loan_facts(input_facts.loan_killed_at.clone());
+
+        // The loans which go out of scope, as computed by NLL
+        let mut loans_out_of_scope_at: BTreeMap<Location, BTreeSet<LoanId::Id>> = BTreeMap::new();
+        let out_of_scope = calculate_borrows_out_of_scope_at_location(
+            &facts.body,
+            &facts.region_inference_context,
+            &facts.borrow_set,
+        );
+        for (loc, bids) in out_of_scope.iter() {
+            if let Some(loc) = self.translate_location(*loc) {
+                let ids = loans_out_of_scope_at.entry(loc).or_default();
+                ids.extend(bids.iter().filter_map(|l| loan(*l)));
+            }
+        }
+        let loans_out_of_scope_at = loans_out_of_scope_at
+            .into_iter()
+            .map(|(loc, ids)| (loc, ids.into_iter().collect()))
+            .collect();
+
+        BorrowckFacts {
+            loans,
+            subset_constraints,
+            loans_invalidated_at,
+            loans_killed_at,
+            loans_out_of_scope_at,
+        }
+    }
+
     /// Translate a function's local variables by adding them in the environment.
-    fn translate_body_locals(&mut self, body: &hax::MirBody<()>) -> Result<(), Error> {
+    fn translate_body_locals(
+        &mut self,
+        body: &hax::MirBody<()>,
+        rust_local_tys: &[rustc_middle::ty::Ty<'tcx>],
+    ) -> Result<(), Error> {
         // Translate the parameters
         for (index, var) in body.local_decls.raw.iter().enumerate() {
             trace!("Translating local of index {} and type {:?}", index, var.ty);
@@ -240,9 +460,12 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
             let name: Option<String> = var.name.clone();
 
             // Translate the type
-            let erase_regions = true;
+            let erase_regions = self.t_ctx.erase_body_regions;
             let span = var.source_info.span.rust_span_data.unwrap().span();
-            let ty = self.translate_ty(span, erase_regions, &var.ty)?;
+            // We need the rustc type to translate the `impl Trait` types and
+            // the projections on generic associated types
+            let ty =
+                self.translate_ty_or_opaque(span, erase_regions, rust_local_tys[index], &var.ty)?;
 
             // Add the variable to the environment
             self.push_var(index, ty, name);
@@ -251,6 +474,33 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
         Ok(())
     }
 
+    /// Attach the variables of the source code to the locals which store them,
+    /// using the debug information of the MIR. This gives names to the locals
+    /// which don't have one in the local declarations (some user variables lose
+    /// their names after the MIR optimizations), and tells which parts of the
+    /// locals store user variables (for instance, the captured variables in the
+    /// state of a closure).
+    fn translate_var_debug_info(
+        &mut self,
+        var_debug_info: Vec<(String, hax::Span, hax::Place)>,
+    ) -> Result<(), Error> {
+        for (name, span, place) in var_debug_info {
+            let rspan = span.rust_span_data.unwrap().span();
+            let (var_id, projection) = self.translate_projection(rspan, &place)?;
+            let meta = self.translate_meta_from_rspan(span);
+            let var = &mut self.vars[var_id];
+            if projection.is_empty() && var.name.is_none() {
+                var.name = Some(name.clone());
+            }
+            var.debug_info.push(VarDebugInfo {
+                name,
+                projection,
+                meta,
+            });
+        }
+        Ok(())
+    }
+
     /// Translate an expression's body (either a function or a global).
     ///
     /// The local variables should already have been translated and inserted in
@@ -297,8 +547,14 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
 
         // Translate the statements
         let mut statements = Vec::new();
-        for statement in &block.statements {
+        let mut statement_indices = Vec::new();
+        for (statement_index, statement) in block.statements.iter().enumerate() {
             trace!("statement: {:?}", statement);
+            statement_indices.push(statements.len());
+            self.location = Some(rustc_middle::mir::Location {
+                block: rustc_index::Idx::new(rustc_index::Idx::index(block_id)),
+                statement_index,
+            });
 
             // Some statements might be ignored, hence the optional returned value
             let opt_statement = self.translate_statement(body, statement)?;
@@ -308,8 +564,15 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
         }
 
         // Translate the terminator
+        statement_indices.push(statements.len());
+        self.statement_indices.insert(nid, statement_indices);
         let terminator = block.terminator.as_ref().unwrap();
-        let terminator = self.translate_terminator(body, terminator)?;
+        let loc = rustc_middle::mir::Location {
+            block: rustc_index::Idx::new(rustc_index::Idx::index(block_id)),
+            statement_index: block.statements.len(),
+        };
+        self.location = Some(loc);
+        let terminator = self.translate_terminator(body, loc, terminator, &mut statements)?;
 
         // Insert the block in the translated blocks
         let block = BlockData {
@@ -328,9 +591,15 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
         span: rustc_span::Span,
         place: &hax::Place,
     ) -> Result<(Place, Ty), Error> {
-        let erase_regions = true;
-        let ty = self.translate_ty(span, erase_regions, &place.ty)?;
+        let erase_regions = self.t_ctx.erase_body_regions;
         let (var_id, projection) = self.translate_projection(span, place)?;
+        // Hax gives arrow types to the function items: we use the type of the
+        // variable, which we translated from the rustc type (see [TyKind::FnDef])
+        let ty = if projection.is_empty() {
+            self.get_var_from_id(var_id).unwrap().ty.clone()
+        } else {
+            self.translate_ty(span, erase_regions, &place.ty)?
+        };
         Ok((Place { var_id, projection }, ty))
     }
 
@@ -351,7 +620,7 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
         span: rustc_span::Span,
         place: &hax::Place,
     ) -> Result<(VarId::Id, Projection), Error> {
-        let erase_regions = true;
+        let erase_regions = self.t_ctx.erase_body_regions;
         match &place.kind {
             hax::PlaceKind::Local(local) => {
                 let var_id = self.get_local(local).unwrap();
@@ -360,16 +629,21 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
             hax::PlaceKind::Projection { place, kind } => {
                 let (var_id, mut projection) = self.translate_projection(span, place)?;
                 // Compute the type of the value *before* projection - we use this
-                // to disambiguate
-                let current_ty = self.translate_ty(span, erase_regions, &place.ty)?;
+                // to disambiguate. For the locals, we reuse the type we translated
+                // from the rustc type (see [translate_body_locals]).
+                let current_ty = if projection.is_empty() {
+                    self.get_var_from_id(var_id).unwrap().ty.clone()
+                } else {
+                    self.translate_ty(span, erase_regions, &place.ty)?
+                };
                 match kind {
                     hax::ProjectionElem::Deref => {
                         // We use the type to disambiguate
-                        match current_ty {
-                            Ty::Ref(_, _, _) => {
+                        match current_ty.kind() {
+                            TyKind::Ref(_, _, _) => {
                                 projection.push(ProjectionElem::Deref);
                             }
-                            Ty::Adt(TypeId::Assumed(AssumedTy::Box), generics) => {
+                            TyKind::Adt(TypeId::Assumed(AssumedTy::Box), generics) => {
                                 // This case only happens in some MIR levels
                                 assert!(!boxes_are_desugared(self.t_ctx.mir_level));
                                 assert!(generics.regions.is_empty());
@@ -377,7 +651,7 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                                 assert!(generics.const_generics.is_empty());
                                 projection.push(ProjectionElem::DerefBox);
                             }
-                            Ty::RawPtr(_, _) => {
+                            TyKind::RawPtr(_, _) => {
                                 projection.push(ProjectionElem::DerefRawPtr);
                             }
                             _ => {
@@ -404,12 +678,12 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                             } => {
                                 let field_id = translate_field_id(*index);
                                 let variant_id = variant.map(translate_variant_id);
-                                match current_ty {
-                                    Ty::Adt(TypeId::Adt(type_id), ..) => {
-                                        let proj_kind = FieldProjKind::Adt(type_id, variant_id);
+                                match current_ty.kind() {
+                                    TyKind::Adt(TypeId::Adt(type_id), ..) => {
+                                        let proj_kind = FieldProjKind::Adt(*type_id, variant_id);
                                         ProjectionElem::Field(proj_kind, field_id)
                                     }
-                                    Ty::Adt(TypeId::Tuple, generics) => {
+                                    TyKind::Adt(TypeId::Tuple, generics) => {
                                         assert!(generics.regions.is_empty());
                                         assert!(variant.is_none());
                                         assert!(generics.const_generics.is_empty());
@@ -417,7 +691,7 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
 
                                         ProjectionElem::Field(proj_kind, field_id)
                                     }
-                                    Ty::Adt(TypeId::Assumed(AssumedTy::Box), generics) => {
+                                    TyKind::Adt(TypeId::Assumed(AssumedTy::Box), generics) => {
                                         assert!(!boxes_are_desugared(self.t_ctx.mir_level));
 
                                         // Some more sanity checks
@@ -450,10 +724,15 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                         // downcast has been propagated to the other
                         // projection elements by Hax)
                     }
-                    hax::ProjectionElem::ConstantIndex { .. }
-                    | hax::ProjectionElem::Subslice { .. } => {
-                        // Those don't seem to occur in MIR built
-                        error_or_panic!(self, span, "Unexpected ProjectionElem::Subslice");
+                    hax::ProjectionElem::Subslice { from, to, from_end } => {
+                        projection.push(ProjectionElem::Subslice {
+                            from: *from,
+                            to: *to,
+                            from_end: *from_end,
+                        });
+                    }
+                    hax::ProjectionElem::ConstantIndex { .. } => {
+                        error_or_panic!(self, span, "Unexpected ProjectionElem::ConstantIndex");
                     }
                     hax::ProjectionElem::OpaqueCast => {
                         // Don't know what that is
@@ -540,7 +819,7 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
         rvalue: &hax::Rvalue,
     ) -> Result<Rvalue, Error> {
         use std::ops::Deref;
-        let erase_regions = true;
+        let erase_regions = self.t_ctx.erase_body_regions;
         match rvalue {
             hax::Rvalue::Use(operand) => Ok(Rvalue::Use(self.translate_operand(span, operand)?)),
             hax::Rvalue::CopyForDeref(place) => {
@@ -568,8 +847,8 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
             }
             hax::Rvalue::Len(place) => {
                 let (place, ty) = self.translate_place_with_type(span, place)?;
-                let cg = match &ty {
-                    Ty::Adt(
+                let cg = match ty.kind() {
+                    TyKind::Adt(
                         TypeId::Assumed(aty @ (AssumedTy::Array | AssumedTy::Slice)),
                         generics,
                     ) => {
@@ -594,7 +873,7 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                 // Translate the operand
                 let (op, src_ty) = self.translate_operand_with_type(span, operand)?;
 
-                match (cast_kind, &src_ty, &tgt_ty) {
+                match (cast_kind, src_ty.kind(), tgt_ty.kind()) {
                     (hax::CastKind::IntToInt, _, _) => {
                         // Note that bool is considered as an integer by Rust.
                         let tgt_ty = *tgt_ty.as_literal();
@@ -607,17 +886,17 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                     }
                     (
                         hax::CastKind::Pointer(hax::PointerCast::Unsize),
-                        Ty::Ref(_, t1, kind1),
-                        Ty::Ref(_, t2, kind2),
+                        TyKind::Ref(_, t1, kind1),
+                        TyKind::Ref(_, t2, kind2),
                     ) => {
                         // In MIR terminology, we go from &[T; l] to &[T] which means we
                         // effectively "unsize" the type, as `l` no longer appears in the
                         // destination type. At runtime, the converse happens: the length
                         // materializes into the fat pointer.
-                        match (&**t1, &**t2) {
+                        match (t1.kind(), t2.kind()) {
                             (
-                                Ty::Adt(TypeId::Assumed(AssumedTy::Array), generics),
-                                Ty::Adt(TypeId::Assumed(AssumedTy::Slice), generics1),
+                                TyKind::Adt(TypeId::Assumed(AssumedTy::Array), generics),
+                                TyKind::Adt(TypeId::Assumed(AssumedTy::Slice), generics1),
                             ) => {
                                 assert!(
                                     generics.types.len() == 1 && generics.const_generics.len() == 1
@@ -647,8 +926,8 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                     }
                     (
                         hax::CastKind::Pointer(hax::PointerCast::ClosureFnPointer(unsafety)),
-                        src_ty @ Ty::Arrow(..),
-                        tgt_ty @ Ty::Arrow(..),
+                        TyKind::Arrow(..),
+                        TyKind::Arrow(..),
                     ) => {
                         assert!(*unsafety == hax::Unsafety::Normal);
                         let src_ty = src_ty.clone();
@@ -660,9 +939,10 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                     }
                     (
                         hax::CastKind::Pointer(hax::PointerCast::ReifyFnPointer),
-                        src_ty @ Ty::Arrow(..),
-                        tgt_ty @ Ty::Arrow(..),
+                        TyKind::Arrow(..) | TyKind::FnDef(..),
+                        TyKind::Arrow(..),
                     ) => {
+                        // Coercion of a function item to a function pointer
                         let src_ty = src_ty.clone();
                         let tgt_ty = tgt_ty.clone();
                         Ok(Rvalue::UnaryOp(
@@ -692,11 +972,18 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                     self.translate_operand(span, right)?,
                 ))
             }
-            hax::Rvalue::NullaryOp(nullop, _ty) => {
+            hax::Rvalue::NullaryOp(nullop, ty) => {
                 trace!("NullOp: {:?}", nullop);
-                // Nullary operations are very low-level and shouldn't be necessary
-                // unless one needs to write unsafe code.
-                error_or_panic!(self, span, "Nullary operations are not supported");
+                let ty = self.translate_ty(span, erase_regions, ty)?;
+                let op = match nullop {
+                    hax::NullOp::SizeOf => NullOp::SizeOf,
+                    hax::NullOp::AlignOf => NullOp::AlignOf,
+                    hax::NullOp::OffsetOf(_) => match self.offset_of_paths.get(&span) {
+                        Some(path) => NullOp::OffsetOf(path.clone()),
+                        None => error_or_panic!(self, span, "Unexpected offset_of"),
+                    },
+                };
+                Ok(Rvalue::NullaryOp(op, ty))
             }
             hax::Rvalue::UnaryOp(unop, operand) => Ok(Rvalue::UnaryOp(
                 translate_unaryop_kind(*unop),
@@ -704,7 +991,7 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
             )),
             hax::Rvalue::Discriminant(place) => {
                 let (place, ty) = self.translate_place_with_type(span, place)?;
-                if let Ty::Adt(TypeId::Adt(adt_id), _) = &ty {
+                if let TyKind::Adt(TypeId::Adt(adt_id), _) = ty.kind() {
                     Ok(Rvalue::Discriminant(place, *adt_id))
                 } else {
                     error_or_panic!(
@@ -852,13 +1139,12 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
     ) -> Result<SubstFunIdOrPanic, Error> {
         let rust_id = DefId::from(def_id);
         let name = self.t_ctx.hax_def_id_to_name(def_id)?;
+        let lang_item = self.t_ctx.translate_lang_item_from_rid(rust_id);
+        let lang_item = lang_item.as_deref();
         let is_local = rust_id.is_local();
 
         // Check if this function is a actually `panic`
-        if name.equals_ref_name(&assumed::PANIC_NAME)
-            || name.equals_ref_name(&assumed::BEGIN_PANIC_NAME)
-            || name.equals_ref_name(&assumed::ASSERT_FAILED_NAME)
-        {
+        if assumed::is_panic_fun(&name, lang_item) {
             return Ok(SubstFunIdOrPanic::Panic);
         }
 
@@ -866,7 +1152,7 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
         // sometimes introduces very low-level functions, which we need to
         // catch early - in particular, before we start translating types and
         // arguments, because we won't be able to translate some of them.
-        if name.equals_ref_name(&assumed::BOX_FREE_NAME) {
+        if assumed::is_box_free_fun(&name, lang_item) {
             assert!(!is_local);
 
             // This deallocates a box.
@@ -908,7 +1194,7 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
             let (used_type_args, used_args) = if is_local {
                 (Option::None, Option::None)
             } else {
-                match assumed::function_to_info(&name) {
+                match assumed::function_to_info(&name, lang_item) {
                     Option::None => (Option::None, Option::None),
                     Option::Some(used) => (
                         Option::Some(used.used_type_params),
@@ -936,7 +1222,7 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
             let is_prim = if is_local {
                 false
             } else {
-                assumed::get_fun_id_from_name(&name).is_some()
+                assumed::get_fun_id_from_name(&name, lang_item).is_some()
             };
 
             // Trait information
@@ -998,7 +1284,7 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                 // (the type parameter is not `Box<T>` but `T`).
                 assert!(trait_info.is_none());
 
-                let aid = assumed::get_fun_id_from_name(&name).unwrap();
+                let aid = assumed::get_fun_id_from_name(&name, lang_item).unwrap();
 
                 // Note that some functions are actually traits (deref, index, etc.):
                 // we assume that they are called only on a limited set of types
@@ -1045,7 +1331,8 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
 
     /// Translate a statement
     ///
-    /// We return an option, because we ignore some statements (`Nop`, `StorageLive`...)
+    /// We return an option, because we ignore some statements (`Nop`, `StorageLive`
+    /// unless the option `--keep-storage-statements` is set...)
     fn translate_statement(
         &mut self,
         body: &hax::MirBody<()>,
@@ -1089,9 +1376,14 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                 let variant_id = translate_variant_id(*variant_index);
                 Some(RawStatement::SetDiscriminant(t_place, variant_id))
             }
-            StatementKind::StorageLive(_) => {
-                // We ignore StorageLive
-                None
+            StatementKind::StorageLive(local) => {
+                // We ignore StorageLive, unless we were asked to keep it
+                if self.t_ctx.keep_storage_statements {
+                    let var_id = self.get_local(local).unwrap();
+                    Some(RawStatement::StorageLive(var_id))
+                } else {
+                    None
+                }
             }
             StatementKind::StorageDead(local) => {
                 let var_id = self.get_local(local).unwrap();
@@ -1120,9 +1412,21 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                 let t_place = self.translate_place(span, place)?;
                 Some(RawStatement::Deinit(t_place))
             }
-            StatementKind::Intrinsic(_) => {
-                error_or_panic!(self, span, "Unsupported statement kind: intrinsic");
-            }
+            StatementKind::Intrinsic(intrinsic) => match intrinsic.deref() {
+                hax::NonDivergingIntrinsic::CopyNonOverlapping(hax::CopyNonOverlapping {
+                    src,
+                    dst,
+                    count,
+                }) => Some(RawStatement::MemCopy(MemCopy {
+                    src: self.translate_operand(span, src)?,
+                    dst: self.translate_operand(span, dst)?,
+                    count: self.translate_operand(span, count)?,
+                    nonoverlapping: true,
+                })),
+                hax::NonDivergingIntrinsic::Assume(_) => {
+                    error_or_panic!(self, span, "Unsupported intrinsic: assume");
+                }
+            },
             StatementKind::ConstEvalCounter => {
                 // See the doc: only used in the interpreter, to check that
                 // const code doesn't run for too long or even indefinitely.
@@ -1144,11 +1448,15 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
         }
     }
 
-    /// Translate a terminator
+    /// Translate a terminator. Some terminators are desugared to statements, which we push to
+    /// `statements` (those are the statements of the block of the terminator).
+    /// Translate a terminator (`loc` is its MIR location).
     fn translate_terminator(
         &mut self,
         body: &hax::MirBody<()>,
+        loc: rustc_middle::mir::Location,
         terminator: &hax::Terminator,
+        statements: &mut Vec<Statement>,
     ) -> Result<Terminator, Error> {
         trace!("About to translate terminator (MIR) {:?}", terminator);
         let span = terminator.source_info.span.rust_span_data.unwrap().span();
@@ -1205,6 +1513,9 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                 fn_span: _,
             } => self.translate_function_call(
                 span,
+                loc,
+                meta,
+                statements,
                 fun,
                 substs,
                 args,
@@ -1216,15 +1527,17 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
             TerminatorKind::Assert {
                 cond,
                 expected,
-                msg: _,
+                msg,
                 target,
                 unwind: _, // We consider that panic is an error, and don't model unwinding
             } => {
                 let cond = self.translate_operand(span, cond)?;
+                let kind = self.translate_assert_kind(span, msg)?;
                 let target = self.translate_basic_block_id(*target);
                 RawTerminator::Assert {
                     cond,
                     expected: *expected,
+                    kind,
                     target,
                 }
             }
@@ -1308,10 +1621,22 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
     /// Note that `body` is the body of the function being translated, not of the
     /// function referenced in the function call: we need it in order to translate
     /// the blocks we go to after the function call returns.
+    ///
+    /// We translate the calls to the functions on raw pointers like `<*const T>::offset`, and
+    /// to the arithmetic operations like `u32::checked_add`, to binary operations (see
+    /// [assumed::get_ptr_binop_from_name] and [assumed::get_arith_binop_from_name]), and the
+    /// copies of raw memory
+    /// to [RawStatement::MemCopy] and [RawStatement::WriteBytes], and the calls to the
+    /// domain-specific intrinsics and to the built-ins of the verifiers to
+    /// [RawStatement::Intrinsic]: in this case, we push the statement to `statements` and
+    /// return a goto.
     #[allow(clippy::too_many_arguments)]
     fn translate_function_call(
         &mut self,
         span: rustc_span::Span,
+        loc: rustc_middle::mir::Location,
+        meta: Meta,
+        statements: &mut Vec<Statement>,
         fun: &hax::FunOperand,
         substs: &Vec<hax::GenericArg>,
         args: &Vec<hax::Operand>,
@@ -1333,9 +1658,85 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                 // support closures for now
                 trace!("func: {:?}", rust_id);
 
+                // Check if this is an operation on raw pointers, or an arithmetic operation
+                let name = self.t_ctx.hax_def_id_to_name(def_id)?;
+                if !rust_id.is_local()
+                    && let Some(binop) = assumed::get_ptr_binop_from_name(&name)
+                        .or_else(|| assumed::get_arith_binop_from_name(&name))
+                {
+                    let [op1, op2] = args.as_slice() else {
+                        error_or_panic!(self, span, "Unexpected arguments for a binary operation")
+                    };
+                    let op1 = self.translate_operand(span, op1)?;
+                    let op2 = self.translate_operand(span, op2)?;
+                    let dest = self.translate_place(span, destination)?;
+                    let rvalue = Rvalue::BinaryOp(binop, op1, op2);
+                    statements.push(Statement::new(meta, RawStatement::Assign(dest, rvalue)));
+                    let target = self.translate_basic_block_id(target.unwrap());
+                    return Ok(RawTerminator::Goto { target });
+                }
+
+                // Check if this is a domain-specific intrinsic, or a built-in of the verifiers
+                if let Some(kind) = self.t_ctx.intrinsic_kind(rust_id) {
+                    let Some(target) = target else {
+                        error_or_panic!(self, span, "Unexpected diverging intrinsic")
+                    };
+                    // The assertions may be given a message, which we don't keep in the arguments
+                    let message = match args.as_slice() {
+                        [_, message] if kind.is_assert() => string_literal(message),
+                        _ => None,
+                    };
+                    let mut args = self.translate_arguments(span, None, args)?;
+                    if message.is_some() {
+                        args.pop();
+                    }
+                    let dest = self.translate_place(span, destination)?;
+                    let st = RawStatement::Intrinsic(Intrinsic {
+                        kind,
+                        args,
+                        dest,
+                        message,
+                    });
+                    statements.push(Statement::new(meta, st));
+                    let target = self.translate_basic_block_id(*target);
+                    return Ok(RawTerminator::Goto { target });
+                }
+
+                // Check if this is a copy of raw memory
+                if !rust_id.is_local()
+                    && let Some(mem_op) = assumed::get_mem_op_from_name(&name)
+                {
+                    let args = self.translate_arguments(span, None, args)?;
+                    let Ok([arg0, arg1, count]) = <[Operand; 3]>::try_from(args) else {
+                        error_or_panic!(self, span, "Unexpected arguments for a memory operation")
+                    };
+                    let st = match mem_op {
+                        assumed::MemOp::Copy {
+                            nonoverlapping,
+                            dst_first,
+                        } => {
+                            let (src, dst) = if dst_first { (arg1, arg0) } else { (arg0, arg1) };
+                            RawStatement::MemCopy(MemCopy {
+                                src,
+                                dst,
+                                count,
+                                nonoverlapping,
+                            })
+                        }
+                        assumed::MemOp::WriteBytes => RawStatement::WriteBytes(WriteBytes {
+                            dst: arg0,
+                            val: arg1,
+                            count,
+                        }),
+                    };
+                    statements.push(Statement::new(meta, st));
+                    let target = self.translate_basic_block_id(target.unwrap());
+                    return Ok(RawTerminator::Goto { target });
+                }
+
                 // Translate the function id, with its parameters
-                let erase_regions = true;
-                let fid = self.translate_fun_decl_id_with_args(
+                let erase_regions = self.t_ctx.erase_body_regions;
+                let mut fid = self.translate_fun_decl_id_with_args(
                     span,
                     erase_regions,
                     def_id,
@@ -1345,6 +1746,36 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                     trait_info,
                 )?;
 
+                // Give the function items their types in the generic arguments, and
+                // in the trait reference if we call a method of `Fn`, `FnMut` or
+                // `FnOnce` on a function item
+                if let SubstFunIdOrPanic::Fun(fid) = &mut fid
+                    && let Some((_, rust_substs)) = self.fn_def_calls.get(&loc).copied()
+                {
+                    let generics = &mut fid.func.generics;
+                    match &mut fid.func.func {
+                        FunIdOrTraitMethodRef::Fun(FunId::Regular(_)) => {
+                            self.translate_fn_def_args(
+                                span,
+                                erase_regions,
+                                rust_id,
+                                rust_substs,
+                                generics,
+                            )?;
+                        }
+                        FunIdOrTraitMethodRef::Trait(trait_ref, ..) => {
+                            let self_ty = rust_substs.type_at(0);
+                            self.translate_fn_pointer_trait_ref(
+                                span,
+                                erase_regions,
+                                self_ty,
+                                trait_ref,
+                            )?;
+                        }
+                        FunIdOrTraitMethodRef::Fun(FunId::Assumed(_)) => (),
+                    }
+                }
+
                 match fid {
                     SubstFunIdOrPanic::Panic => {
                         // If the call is `panic!`, then the target is `None`.
@@ -1363,10 +1794,23 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                         let lval = self.translate_place(span, destination)?;
                         let next_block = self.translate_basic_block_id(next_block);
 
+                        let receiver_adjustments = self
+                            .receiver_adjustments
+                            .get(&loc)
+                            .cloned()
+                            .unwrap_or_default();
+                        let resolution =
+                            self.translate_method_resolution(rust_id, &fid.func.func, trait_info);
+                        let is_ghost =
+                            self.t_ctx.id_is_marked_ghost(rust_id) || self.span_is_ghost(span);
                         let call = Call {
                             func: FnOperand::Regular(fid.func),
                             args: fid.args.unwrap(),
                             dest: lval,
+                            receiver_adjustments,
+                            resolution,
+                            is_tail: false,
+                            is_ghost,
                         };
 
                         Ok(RawTerminator::Call {
@@ -1399,6 +1843,10 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                     func: FnOperand::Move(p),
                     args,
                     dest: lval,
+                    receiver_adjustments: Vec::new(),
+                    resolution: None,
+                    is_tail: false,
+                    is_ghost: self.span_is_ghost(span),
                 };
                 Ok(RawTerminator::Call {
                     call,
@@ -1437,11 +1885,280 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
         Ok(t_args)
     }
 
+    /// Compute the adjustments that the compiler applied to the receivers of the method calls of
+    /// a local body (see [Call::receiver_adjustments]): we look for the method calls in the HIR,
+    /// and find the MIR calls they were lowered to with their spans and the called methods.
+    fn translate_receiver_adjustments(
+        &mut self,
+        local_id: LocalDefId,
+        body: &rustc_middle::mir::Body<'tcx>,
+    ) {
+        use rustc_hir::intravisit::{self, Visitor};
+        use rustc_middle::ty::adjustment::{Adjust, AutoBorrow, AutoBorrowMutability};
+        use rustc_middle::ty::TypeckResults;
+
+        struct MethodCalls<'tcx> {
+            typeck_results: &'tcx TypeckResults<'tcx>,
+            /// The adjustments, indexed by the spans of the calls and the called methods.
+            adjustments: HashMap<(rustc_span::Span, DefId), Vec<Adjustment>>,
+        }
+
+        impl<'tcx> Visitor<'tcx> for MethodCalls<'tcx> {
+            fn visit_expr(&mut self, expr: &'tcx rustc_hir::Expr<'tcx>) {
+                if let rustc_hir::ExprKind::MethodCall(_, receiver, _, _) = expr.kind
+                    && let Some(method) = self.typeck_results.type_dependent_def_id(expr.hir_id)
+                {
+                    let ref_kind = |mutability: rustc_hir::Mutability| match mutability {
+                        rustc_hir::Mutability::Mut => RefKind::Mut,
+                        rustc_hir::Mutability::Not => RefKind::Shared,
+                    };
+                    let adjustments = self
+                        .typeck_results
+                        .expr_adjustments(receiver)
+                        .iter()
+                        .filter_map(|adjustment| match &adjustment.kind {
+                            Adjust::Deref(None) => Some(Adjustment::Deref),
+                            Adjust::Deref(Some(deref)) => {
+                                Some(Adjustment::OverloadedDeref(ref_kind(deref.mutbl)))
+                            }
+                            Adjust::Borrow(AutoBorrow::Ref(_, mutability)) => {
+                                Some(Adjustment::Borrow(match mutability {
+                                    AutoBorrowMutability::Mut { .. } => RefKind::Mut,
+                                    AutoBorrowMutability::Not => RefKind::Shared,
+                                }))
+                            }
+                            Adjust::Pointer(rustc_middle::ty::adjustment::PointerCast::Unsize) => {
+                                Some(Adjustment::Unsize)
+                            }
+                            // The other adjustments can't be applied to a receiver
+                            _ => None,
+                        })
+                        .collect();
+                    self.adjustments.insert((expr.span, method), adjustments);
+                }
+                intravisit::walk_expr(self, expr)
+            }
+        }
+
+        let tcx = self.t_ctx.tcx;
+        let Some(body_id) = tcx.hir().maybe_body_owned_by(local_id) else { return };
+        let mut visitor = MethodCalls {
+            typeck_results: tcx.typeck(local_id),
+            adjustments: HashMap::new(),
+        };
+        visitor.visit_body(tcx.hir().body(body_id));
+        self.receiver_adjustments = body
+            .basic_blocks
+            .iter_enumerated()
+            .filter_map(|(block, data)| {
+                let terminator = data.terminator();
+                let rustc_middle::mir::TerminatorKind::Call { func, .. } = &terminator.kind else {
+                    return None;
+                };
+                let (method, _) = func.const_fn_def()?;
+                let adjustments = visitor
+                    .adjustments
+                    .get(&(terminator.source_info.span, method))?;
+                Some((body.terminator_loc(block), adjustments.clone()))
+            })
+            .collect();
+    }
+
+    /// Register the functions called by a body with their rustc arguments (see
+    /// [BodyTransCtx::fn_def_calls]).
+    fn register_fn_def_calls(&mut self, body: &rustc_middle::mir::Body<'tcx>) {
+        self.fn_def_calls = body
+            .basic_blocks
+            .iter_enumerated()
+            .filter_map(|(block, data)| {
+                let rustc_middle::mir::TerminatorKind::Call { func, .. } = &data.terminator().kind
+                else {
+                    return None;
+                };
+                Some((body.terminator_loc(block), func.const_fn_def()?))
+            })
+            .collect();
+    }
+
+    /// Compute how a method call was resolved (see [Call::resolution]). `rust_id` is the id of
+    /// the called function: for the trait methods, this is the id of the method in the trait
+    /// declaration, and `trait_info` gives the implementation of the trait.
+    fn translate_method_resolution(
+        &self,
+        rust_id: DefId,
+        func: &FunIdOrTraitMethodRef,
+        trait_info: &Option<hax::ImplExpr>,
+    ) -> Option<MethodResolution> {
+        let tcx = self.t_ctx.tcx;
+        let trait_ref = match func {
+            FunIdOrTraitMethodRef::Fun(_) => {
+                let impl_id = tcx.impl_of_method(rust_id)?;
+                return tcx
+                    .trait_id_of_impl(impl_id)
+                    .is_none()
+                    .then_some(MethodResolution::Inherent);
+            }
+            FunIdOrTraitMethodRef::Trait(trait_ref, ..) => trait_ref,
+        };
+        let resolution = match &trait_ref.trait_id {
+            TraitInstanceId::TraitImpl(impl_id)
+            | TraitInstanceId::Specializable { impl_id, .. } => {
+                // Check if the impl overrides the method
+                let overrides = match trait_info.as_ref().map(|info| &info.r#impl) {
+                    Some(hax::ImplExprAtom::Concrete { id, .. }) => tcx
+                        .impl_item_implementor_ids(DefId::from(id))
+                        .contains_key(&rust_id),
+                    _ => true,
+                };
+                if overrides {
+                    MethodResolution::TraitImpl(*impl_id)
+                } else {
+                    MethodResolution::DefaultMethod(*impl_id)
+                }
+            }
+            TraitInstanceId::BuiltinOrAuto(decl_id) => MethodResolution::BuiltinOrAuto(*decl_id),
+            _ => MethodResolution::Clause,
+        };
+        Some(resolution)
+    }
+
+    /// Collect the invocations of the `ghost!` macro of a local body (see [Call::is_ghost]): we
+    /// look for the expressions of the HIR which come from the expansion of the macro.
+    fn translate_ghost_spans(&mut self, local_id: LocalDefId) {
+        use rustc_hir::intravisit::{self, Visitor};
+
+        struct GhostSpans<'a, 'tcx, 'ctx> {
+            t_ctx: &'a TransCtx<'tcx, 'ctx>,
+            spans: Vec<rustc_span::Span>,
+        }
+
+        impl<'a, 'tcx, 'ctx> Visitor<'tcx> for GhostSpans<'a, 'tcx, 'ctx> {
+            fn visit_expr(&mut self, expr: &'tcx rustc_hir::Expr<'tcx>) {
+                match self.t_ctx.ghost_call_site(expr.span) {
+                    // The sub-expressions are in the same invocation
+                    Some(call_site) => self.spans.push(call_site),
+                    None => intravisit::walk_expr(self, expr),
+                }
+            }
+        }
+
+        let tcx = self.t_ctx.tcx;
+        let Some(body_id) = tcx.hir().maybe_body_owned_by(local_id) else { return };
+        let mut visitor = GhostSpans {
+            t_ctx: self.t_ctx,
+            spans: Vec::new(),
+        };
+        visitor.visit_body(tcx.hir().body(body_id));
+        self.ghost_spans = visitor.spans;
+    }
+
+    /// Whether a call comes from an invocation of the `ghost!` macro
+    fn span_is_ghost(&self, span: rustc_span::Span) -> bool {
+        self.t_ctx.ghost_call_site(span).is_some()
+            || self.ghost_spans.iter().any(|ghost| ghost.contains(span))
+    }
+
+    /// The id of a captured variable, together with the body which declares it (see
+    /// [CapturedPlace::var_id]). We find its local in the MIR of this body with its span.
+    fn translate_captured_var_id(
+        &mut self,
+        span: rustc_span::Span,
+        var: rustc_hir::HirId,
+    ) -> Result<(VarId::Id, DefId), Error> {
+        let tcx = self.t_ctx.tcx;
+        let owner = tcx.hir().enclosing_body_owner(var).to_def_id();
+        let var_span = tcx.hir().span(var);
+        let body = get_mir_for_def_id_and_level(tcx, owner, self.t_ctx.mir_level);
+        let local = body.and_then(|body| {
+            body.local_decls
+                .iter_enumerated()
+                .find(|(_, decl)| decl.is_user_variable() && decl.source_info.span == var_span)
+                .map(|(local, _)| local)
+        });
+        match local {
+            Some(local) => Ok((VarId::Id::new(local.as_usize()), owner)),
+            None => error_or_panic!(self, span, "Could not find the local of a captured variable"),
+        }
+    }
+
+    /// Compute the places captured by a local closure, and whether it is a `move` closure.
+    fn translate_closure_captures(
+        &mut self,
+        local_id: LocalDefId,
+    ) -> Result<(Vec<CapturedPlace>, bool), Error> {
+        use rustc_middle::hir::place::ProjectionKind;
+        use rustc_middle::ty::{BorrowKind, UpvarCapture};
+
+        let tcx = self.t_ctx.tcx;
+        let span = tcx.def_span(local_id);
+        let mut captures = Vec::new();
+        for captured in tcx.closure_captures(local_id) {
+            let mut projection = Vec::new();
+            let mut field_adts = Vec::new();
+            for (i, proj) in captured.place.projections.iter().enumerate() {
+                match proj.kind {
+                    ProjectionKind::Deref => {
+                        projection.push(CaptureProjection::Deref);
+                        field_adts.push(None);
+                    }
+                    ProjectionKind::Field(field, variant) => {
+                        let field_id = FieldId::Id::new(field.as_usize());
+                        projection.push(CaptureProjection::Field(field_id));
+                        // We remember the structure or the variant we project (see
+                        // [CapturedPlace::field_adts])
+                        let adt = match captured.place.ty_before_projection(i).kind() {
+                            ty::Adt(adt, _) => {
+                                let type_id = self.translate_type_decl_id(span, adt.did());
+                                let variant = adt
+                                    .is_enum()
+                                    .then(|| VariantId::Id::new(variant.as_usize()));
+                                Some((type_id, variant))
+                            }
+                            _ => None,
+                        };
+                        field_adts.push(adt);
+                    }
+                    // The capture analysis truncates the captured places before the indices
+                    // and the subslices
+                    ProjectionKind::Index | ProjectionKind::Subslice => {
+                        error_or_panic!(self, span, "Unexpected index in a captured place")
+                    }
+                }
+            }
+            let mode = match captured.info.capture_kind {
+                UpvarCapture::ByValue => CaptureMode::ByValue,
+                UpvarCapture::ByRef(BorrowKind::ImmBorrow) => CaptureMode::ByRef(RefKind::Shared),
+                UpvarCapture::ByRef(BorrowKind::UniqueImmBorrow | BorrowKind::MutBorrow) => {
+                    CaptureMode::ByRef(RefKind::Mut)
+                }
+            };
+            let (var_id, var_owner) =
+                self.translate_captured_var_id(span, captured.get_root_variable())?;
+            captures.push(CapturedPlace {
+                var_id,
+                var_owner,
+                projection,
+                mode,
+                field_adts,
+            });
+        }
+        let is_move = match tcx.hir().get_by_def_id(local_id) {
+            rustc_hir::Node::Expr(rustc_hir::Expr {
+                kind: rustc_hir::ExprKind::Closure(closure),
+                ..
+            }) => matches!(closure.capture_clause, rustc_ast::CaptureBy::Value),
+            _ => false,
+        };
+        Ok((captures, is_move))
+    }
+
     /// Translate a function body if we can (it has MIR) and we want to (we don't translate bodies
-    /// declared opaque, and only translate non-local bodies if `extract_opaque_bodies` is set).
+    /// declared opaque, and only translate non-local bodies if `extract_opaque_bodies` is set or
+    /// if they match a pattern of `extract_external_bodies`).
     fn translate_body(
         mut self,
         rust_id: DefId,
+        trans_id: AnyTransId,
         arg_count: usize,
     ) -> Result<Option<ExprBody>, Error> {
         let tcx = self.t_ctx.tcx;
@@ -1449,14 +2166,50 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
         if !self.t_ctx.id_is_transparent(rust_id)? {
             return Ok(None);
         }
-        if !rust_id.is_local() && !self.t_ctx.extract_opaque_bodies {
-            // We only extract non-local bodies if the `extract_opaque_bodies` option is set.
+        if !rust_id.is_local() && !self.t_ctx.extract_external_body(rust_id) {
+            // We only extract non-local bodies if the `extract_opaque_bodies` option is set, or
+            // if they match a pattern of the `extract_external_bodies` option.
             return Ok(None);
         }
 
-        // Retrive the body
-        let Some(body) = get_mir_for_def_id_and_level(tcx, rust_id, self.t_ctx.mir_level)
-        else { return Ok(None) };
+        // Retrive the body. If we don't erase the regions, we retrieve the body
+        // analyzed by the borrow checker (this is only possible for the local
+        // bodies) and translate its region variables.
+        let (body, facts) = match rust_id.as_local() {
+            Some(local_id) if !self.t_ctx.erase_body_regions => {
+                let options = if self.t_ctx.borrowck_facts {
+                    ConsumerOptions::PoloniusInputFacts
+                } else {
+                    ConsumerOptions::RegionInferenceContext
+                };
+                let facts = get_mir_with_borrowck_facts(tcx, local_id, options);
+                self.translate_body_regions(rust_id, &facts.region_inference_context);
+                (facts.body.clone(), Some(facts))
+            }
+            _ => {
+                let Some(body) = get_mir_for_def_id_and_level(tcx, rust_id, self.t_ctx.mir_level)
+                else { return Ok(None) };
+                (body, None)
+            }
+        };
+
+        // The promoted constants are translated as separate globals
+        let promoted = get_promoted_mir_for_def_id_and_level(tcx, rust_id, self.t_ctx.mir_level);
+        self.translate_promoted_constants(rust_id, trans_id, promoted)?;
+
+        self.translate_mir_body(rust_id, body, facts, arg_count)
+            .map(Some)
+    }
+
+    /// Translate a MIR body (see [translate_body]).
+    fn translate_mir_body(
+        mut self,
+        rust_id: DefId,
+        body: rustc_middle::mir::Body<'tcx>,
+        facts: Option<BodyWithBorrowckFacts<'tcx>>,
+        arg_count: usize,
+    ) -> Result<ExprBody, Error> {
+        let tcx = self.t_ctx.tcx;
 
         // Here, we have to create a MIR state, which contains the body
         let state = hax::state::State::new_from_mir(
@@ -1469,17 +2222,54 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
             // Owner id
             rust_id,
         );
+        let rust_local_tys: Vec<rustc_middle::ty::Ty<'tcx>> =
+            body.local_decls.iter().map(|decl| decl.ty).collect();
+        self.translate_offset_of_paths(&body)?;
+        let scopes = self.translate_scopes(&body);
+        // The debug information of the variables which are stored in places (we
+        // ignore the constants and the variables split across several locals)
+        let var_debug_info: Vec<(String, hax::Span, hax::Place)> = body
+            .var_debug_info
+            .iter()
+            .filter_map(|info| match &info.value {
+                VarDebugInfoContents::Place(place) => Some((
+                    info.name.to_string(),
+                    info.source_info.span.sinto(&state),
+                    place.sinto(&state),
+                )),
+                _ => None,
+            })
+            .collect();
+        // Retrieve the adjustments of the receivers of the method calls and the ghost code (we
+        // need the HIR)
+        if let Some(local_id) = rust_id.as_local() {
+            self.translate_receiver_adjustments(local_id, &body);
+            self.translate_ghost_spans(local_id);
+        }
+        self.register_fn_def_calls(&body);
+        let mut promoted_refs = PromotedRefs(HashMap::new());
+        promoted_refs.visit_body(&body);
+        self.promoted_constants = promoted_refs.0;
+
         // Translate
         let body: hax::MirBody<()> = body.sinto(&state);
 
         // Initialize the local variables
         trace!("Translating the body locals");
-        self.translate_body_locals(&body)?;
+        self.translate_body_locals(&body, &rust_local_tys)?;
+        self.translate_var_debug_info(var_debug_info)?;
 
         // Translate the expression body
         trace!("Translating the expression body");
         self.translate_transparent_expression_body(&body)?;
 
+        // Translate the borrow checker facts, if necessary (we need to do this
+        // after we translated the blocks)
+        let borrowck_facts = match &facts {
+            Some(facts) if self.t_ctx.borrowck_facts => Some(self.translate_borrowck_facts(facts)),
+            _ => None,
+        };
+
         // Compute the meta information
         let meta = self.translate_meta_from_rspan(body.span);
 
@@ -1493,12 +2283,172 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
         }
 
         // Create the body
-        Ok(Some(ExprBody {
+        Ok(ExprBody {
             meta,
             arg_count,
             locals: self.vars,
+            regions: self.body_regions,
+            borrowck_facts,
+            scopes,
+            promoted: self.promoted_ids,
             body: blocks,
-        }))
+        })
+    }
+
+    /// Translate the tree of the source scopes of a body, and register the
+    /// locals in their scopes: the user variables are in the scopes in which
+    /// they are visible (as given by the debug information), the other locals
+    /// in the scopes in which they are declared.
+    fn translate_scopes(&mut self, body: &rustc_middle::mir::Body<'tcx>) -> ScopeId::Vector<Scope> {
+        use rustc_index::Idx;
+        let mut scopes = ScopeId::Vector::new();
+        for data in body.source_scopes.iter() {
+            let meta = self.translate_meta_from_rspan(data.span.sinto(&self.hax_state));
+            scopes.push_with(|index| Scope {
+                index,
+                parent: data.parent_scope.map(|s| ScopeId::Id::new(s.index())),
+                meta,
+                inlined: data.inlined.is_some(),
+                locals: Vec::new(),
+            });
+        }
+        let mut local_scopes: Vec<_> = body
+            .local_decls
+            .iter()
+            .map(|decl| decl.source_info.scope)
+            .collect();
+        for info in &body.var_debug_info {
+            if let VarDebugInfoContents::Place(place) = &info.value
+                && place.projection.is_empty()
+            {
+                local_scopes[place.local.index()] = info.source_info.scope;
+            }
+        }
+        for (index, scope) in local_scopes.into_iter().enumerate() {
+            // The locals are translated in order (see [Self::translate_body_locals])
+            let var_id = VarId::Id::new(index);
+            scopes[ScopeId::Id::new(scope.index())].locals.push(var_id);
+        }
+        scopes
+    }
+
+    /// Compute the paths of the `offset_of!` operations of a body (see
+    /// [BodyTransCtx::offset_of_paths]).
+    fn translate_offset_of_paths(
+        &mut self,
+        body: &rustc_middle::mir::Body<'tcx>,
+    ) -> Result<(), Error> {
+        use rustc_middle::mir::{NullOp, Rvalue, StatementKind};
+        let tcx = self.t_ctx.tcx;
+        for block in body.basic_blocks.iter() {
+            for statement in &block.statements {
+                let StatementKind::Assign(assign) = &statement.kind else { continue };
+                let (_, Rvalue::NullaryOp(NullOp::OffsetOf(fields), ty)) = &**assign else {
+                    continue;
+                };
+                let span = statement.source_info.span;
+                let mut ty = *ty;
+                let mut path = Vec::new();
+                for field in fields.iter() {
+                    let field_id = FieldId::Id::new(field.as_usize());
+                    let (proj_kind, field_ty) = match ty.kind() {
+                        ty::Adt(adt, substs) if adt.is_struct() => {
+                            let type_id = self.translate_type_decl_id(span, adt.did());
+                            let field_ty = adt.non_enum_variant().fields[field].ty(tcx, substs);
+                            (FieldProjKind::Adt(type_id, None), field_ty)
+                        }
+                        ty::Tuple(tys) => (FieldProjKind::Tuple(tys.len()), tys[field.as_usize()]),
+                        _ => error_or_panic!(self, span, "Unexpected type in an offset_of"),
+                    };
+                    path.push((proj_kind, field_id));
+                    ty = field_ty;
+                }
+                self.offset_of_paths.insert(span, path);
+            }
+        }
+        Ok(())
+    }
+
+    /// Translate the promoted constants of a body as separate globals (see
+    /// [PromotedInfo]), and register their ids (see
+    /// [BodyTransCtx::promoted_ids]). A promoted constant may also refer to
+    /// the other promoted constants of the same body.
+    fn translate_promoted_constants(
+        &mut self,
+        rust_id: DefId,
+        parent: AnyTransId,
+        promoted: IndexVec<Promoted, rustc_middle::mir::Body<'tcx>>,
+    ) -> Result<(), Error> {
+        if promoted.is_empty() {
+            return Ok(());
+        }
+        let name = self.t_ctx.def_id_to_name(rust_id)?;
+        let item_meta = self.t_ctx.translate_item_meta_from_rid(rust_id)?;
+
+        // The promoted constants have the generics of the parent declaration
+        // (without its late-bound regions)
+        let (generics, preds) = {
+            let mut bt_ctx = BodyTransCtx::new(rust_id, self.t_ctx);
+            bt_ctx.translate_generic_params(rust_id)?;
+            bt_ctx.translate_predicates_solve_trait_obligations_of(None, rust_id)?;
+            (bt_ctx.get_generics(), bt_ctx.get_predicates())
+        };
+        self.promoted_args = generics.identity_args();
+
+        // Register the ids
+        self.promoted_ids = promoted
+            .iter()
+            .map(|_| self.t_ctx.global_id_map.fresh_id())
+            .collect();
+
+        // Translate the bodies
+        for (index, promoted_body) in promoted.into_iter_enumerated() {
+            let def_id = self.promoted_ids[PromotedId::Id::new(index.as_usize())];
+            let span = promoted_body.span;
+            let mut bt_ctx = BodyTransCtx::new(rust_id, self.t_ctx);
+            bt_ctx.promoted_ids = self.promoted_ids.clone();
+            bt_ctx.promoted_args = self.promoted_args.clone();
+            bt_ctx.translate_generic_params(rust_id)?;
+            bt_ctx.translate_predicates_solve_trait_obligations_of(None, rust_id)?;
+            let ty = promoted_body.local_decls[rustc_middle::mir::RETURN_PLACE].ty;
+            let ty = bt_ctx.translate_ty(span, true, &ty.sinto(&bt_ctx.hax_state))?;
+            let body = bt_ctx.translate_mir_body(rust_id, promoted_body, None, 0)?;
+
+            let mut name = name.clone();
+            name.name.push(PathElem::Ident(
+                "promoted".into(),
+                Disambiguator::Id::new(index.as_usize()),
+            ));
+            let mut item_meta = item_meta.clone();
+            item_meta.stable_id =
+                format!("{}::promoted[{}]", item_meta.stable_id, index.as_usize());
+            self.t_ctx.all_ids.insert(AnyTransId::Global(def_id));
+            self.t_ctx.translated.global_decls.insert(
+                def_id,
+                GlobalDecl {
+                    def_id,
+                    rust_id,
+                    item_meta,
+                    is_local: rust_id.is_local(),
+                    name,
+                    generics: generics.clone(),
+                    preds: preds.clone(),
+                    ty,
+                    // A promoted constant is immutable and can't contain an `UnsafeCell`
+                    is_mutable: false,
+                    is_thread_local: false,
+                    is_interior_mutable: false,
+                    kind: ItemKind::Regular,
+                    promoted: Some(PromotedInfo {
+                        parent,
+                        index: PromotedId::Id::new(index.as_usize()),
+                    }),
+                    init_deps: Vec::new(),
+                    body: Some(body),
+                },
+            );
+        }
+        Ok(())
     }
 
     /// Translate a function's signature, and initialize a body translation context
@@ -1602,6 +2552,10 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
 
             (substs, fn_sig, None)
         };
+        // We need the rustc types to translate the `impl Trait` types and the
+        // projections on generic associated types
+        let rust_inputs = signature.skip_binder().inputs();
+        let rust_output = signature.skip_binder().output();
         let signature: hax::MirPolyFnSig = signature.sinto(&self.hax_state);
 
         // Start by translating the early-bound parameters (those are contained by `substs`).
@@ -1610,7 +2564,7 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                tcx.generics_of(def_id), signature.bound_vars, signature);
 
         // Add the *early-bound* parameters.
-        self.translate_generic_params_from_hax(span, &substs)?;
+        self.translate_generic_params_from_hax(span, def_id, &substs)?;
 
         //
         // Add the *late-bound* parameters (bound in the signature, can only be lifetimes)
@@ -1619,6 +2573,7 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
             hax::Unsafety::Unsafe => true,
             hax::Unsafety::Normal => false,
         };
+        let is_const = tcx.is_const_fn_raw(def_id);
         let bvar_names = signature
             .bound_vars
             .into_iter()
@@ -1678,9 +2633,13 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
         let inputs: Vec<Ty> = signature
             .inputs
             .iter()
-            .map(|ty| self.translate_ty(span, erase_regions, ty))
+            .zip(rust_inputs.iter())
+            .map(|(ty, rust_ty)| self.translate_ty_or_opaque(span, erase_regions, *rust_ty, ty))
             .try_collect()?;
-        let output = self.translate_ty(span, erase_regions, &signature.output)?;
+        let output =
+            self.translate_ty_or_opaque(span, erase_regions, rust_output, &signature.output)?;
+        let (input_aliases, output_alias) =
+            self.translate_signature_aliases(def_id, is_closure, inputs.len())?;
 
         let fmt_ctx = self.into_fmt();
         trace!(
@@ -1700,7 +2659,20 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                 .map(|ty| self.translate_ty(span, erase_regions, &ty.sinto(&self.hax_state)))
                 .try_collect::<Vec<Ty>>()?;
 
-            Some(ClosureInfo { kind, state })
+            let (captures, is_move) = match def_id.as_local() {
+                Some(local_id) => self.translate_closure_captures(local_id)?,
+                None => (Vec::new(), false),
+            };
+
+            Some(ClosureInfo {
+                kind,
+                state,
+                captures,
+                is_move,
+                // We generate the shims later
+                call_mut_shim: None,
+                call_once_shim: None,
+            })
         } else {
             None
         };
@@ -1722,14 +2694,50 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
             generics: self.get_generics(),
             preds: self.get_predicates(),
             is_unsafe,
+            is_const,
             is_closure,
             closure_info,
             parent_params_info,
             inputs,
             output,
+            input_aliases,
+            output_alias,
         })
     }
 
+    /// Retrieve the type aliases the user wrote for the inputs and the output
+    /// of a local function (see [FunSig::input_aliases]).
+    fn translate_signature_aliases(
+        &mut self,
+        def_id: DefId,
+        is_closure: bool,
+        num_inputs: usize,
+    ) -> Result<(Vec<Option<TypeAliasRef>>, Option<TypeAliasRef>), Error> {
+        // The inputs of the closures are tupled in the signatures
+        if !self.t_ctx.translate_type_aliases || is_closure {
+            return Ok((Vec::new(), None));
+        }
+        let Some(local_id) = def_id.as_local() else {
+            return Ok((Vec::new(), None));
+        };
+        let Some(decl) = self.t_ctx.tcx.hir().get_by_def_id(local_id).fn_decl() else {
+            return Ok((Vec::new(), None));
+        };
+        if decl.inputs.len() != num_inputs {
+            return Ok((Vec::new(), None));
+        }
+        let input_aliases: Vec<Option<TypeAliasRef>> = decl
+            .inputs
+            .iter()
+            .map(|ty| self.translate_type_alias_ref(ty))
+            .try_collect()?;
+        let output_alias = match decl.output {
+            rustc_hir::FnRetTy::Return(ty) => self.translate_type_alias_ref(ty)?,
+            rustc_hir::FnRetTy::DefaultReturn(_) => None,
+        };
+        Ok((input_aliases, output_alias))
+    }
+
     fn get_function_parent_params_info(
         &mut self,
         src: &Option<DepSource>,
@@ -1762,7 +2770,7 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
                     ),
                 );
                 // Save the definition
-                let _ = ctx.ignored_failed_decls.insert(rust_id);
+                let _ = ctx.errors.ignored_failed_decls.insert(rust_id);
             }
         });
     }
@@ -1774,7 +2782,8 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
         let def_span = self.tcx.def_span(rust_id);
 
         // Compute the meta information
-        let item_meta = self.translate_item_meta_from_rid(rust_id);
+        let item_meta = self.translate_item_meta_from_rid(rust_id)?;
+        let lang_item = self.translate_lang_item_from_rid(rust_id);
 
         // Initialize the body translation context
         let mut bt_ctx = BodyTransCtx::new(rust_id, self);
@@ -1798,20 +2807,37 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
         trace!("Translating function signature");
         let signature = bt_ctx.translate_function_signature(rust_id)?;
 
-        let body = if !is_trait_method_decl {
+        let body = if is_trait_method_decl {
+            None
+        } else if let Ok(Some(body)) = bt_ctx.translate_builtin_model(rust_id, &name, &signature) {
+            // We use the model of the function (see [crate::builtin_models])
+            Some(body)
+        } else {
             // Translate the body. This returns `None` if we can't/decide not to translate this
             // body.
-            match bt_ctx.translate_body(rust_id, signature.inputs.len()) {
+            match bt_ctx.translate_body(rust_id, AnyTransId::Fun(def_id), signature.inputs.len()) {
                 Ok(body) => body,
                 // Error case: we could have a variant for this
                 Err(_) => None,
             }
-        } else {
-            None
         };
 
+        // Register the ghost functions
+        if self.id_is_marked_ghost(rust_id) {
+            self.translated.ghost_functions.push(def_id);
+        }
+
+        // Register the verification entry points
+        if let Some((attribute, args)) = self.harness_attribute(rust_id) {
+            self.translated.harnesses.push(Harness {
+                fun_id: def_id,
+                attribute,
+                args,
+            });
+        }
+
         // Save the new function
-        self.fun_decls.insert(
+        self.translated.fun_decls.insert(
             def_id,
             FunDecl {
                 def_id,
@@ -1819,8 +2845,10 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
                 item_meta,
                 is_local: rust_id.is_local(),
                 name,
+                lang_item,
                 signature,
                 kind,
+                rec_group: None,
                 body,
             },
         );
@@ -1841,7 +2869,7 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
                     ),
                 );
                 // Save the definition
-                let _ = ctx.ignored_failed_decls.insert(rust_id);
+                let _ = ctx.errors.ignored_failed_decls.insert(rust_id);
             }
         });
     }
@@ -1854,7 +2882,7 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
         let def_id = self.translate_global_decl_id(&None, rust_id);
 
         // Compute the meta information
-        let item_meta = self.translate_item_meta_from_rid(rust_id);
+        let item_meta = self.translate_item_meta_from_rid(rust_id)?;
 
         // Initialize the body translation context
         let mut bt_ctx = BodyTransCtx::new(rust_id, self);
@@ -1878,6 +2906,10 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
         let mir_ty = bt_ctx.t_ctx.tcx.type_of(rust_id).subst_identity();
         let erase_regions = false; // This doesn't matter: there shouldn't be any regions
         let ty = bt_ctx.translate_ty(span, erase_regions, &mir_ty.sinto(hax_state))?;
+        let tcx = bt_ctx.t_ctx.tcx;
+        let is_mutable = tcx.is_mutable_static(rust_id);
+        let is_thread_local = tcx.is_thread_local_static(rust_id);
+        let is_interior_mutable = !mir_ty.is_freeze(tcx, tcx.param_env(rust_id));
 
         // Retrieve the kind
         let kind = bt_ctx
@@ -1889,14 +2921,14 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
 
         // Translate its body like the body of a function. This returns `None` if we can't/decide
         // not to translate this body.
-        let body = match bt_ctx.translate_body(rust_id, 0) {
+        let body = match bt_ctx.translate_body(rust_id, AnyTransId::Global(def_id), 0) {
             Ok(body) => body,
             // Error case: we could have a specific variant
             Err(_) => None,
         };
 
         // Save the new global
-        self.global_decls.insert(
+        self.translated.global_decls.insert(
             def_id,
             GlobalDecl {
                 def_id,
@@ -1907,7 +2939,12 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
                 generics,
                 preds,
                 ty,
+                is_mutable,
+                is_thread_local,
+                is_interior_mutable,
                 kind,
+                promoted: None,
+                init_deps: Vec::new(),
                 body,
             },
         );
@@ -5,10 +5,13 @@ use crate::gast::*;
 use crate::meta::Meta;
 use crate::translate_ctx::*;
 use crate::types::*;
+use crate::types_utils::fmt_trait_clause_with_ctx;
+use crate::types_utils::TySubst;
 use hax_frontend_exporter as hax;
 use hax_frontend_exporter::SInto;
 use macros::{EnumAsGetters, EnumIsA, EnumToGetters};
 use rustc_hir::def_id::DefId;
+use std::collections::HashMap;
 
 /// Same as [TraitClause], but where the clause id is a [TraitInstanceId].
 /// We need this information to solve the provenance of traits coming from
@@ -39,7 +42,11 @@ pub(crate) struct NonLocalTraitClause {
     /// associated type clause.
     pub meta: Option<Meta>,
     pub trait_id: TraitDeclId::Id,
+    /// The regions bound by the clause itself (see [TraitClause]).
+    pub bound_regions: RegionId::Vector<RegionVar>,
     pub generics: GenericArgs,
+    /// [true] if this is a `~const` bound (see [TraitClause::is_const]).
+    pub is_const: bool,
 }
 
 impl NonLocalTraitClause {
@@ -49,7 +56,9 @@ impl NonLocalTraitClause {
                 clause_id: *id,
                 meta: self.meta,
                 trait_id: self.trait_id,
+                bound_regions: self.bound_regions.clone(),
                 generics: self.generics.clone(),
+                is_const: self.is_const,
             })
         } else {
             None
@@ -64,7 +73,9 @@ impl NonLocalTraitClause {
             clause_id,
             meta: self.meta,
             trait_id: self.trait_id,
+            bound_regions: self.bound_regions.clone(),
             generics: self.generics.clone(),
+            is_const: self.is_const,
         })
     }
 
@@ -72,13 +83,83 @@ impl NonLocalTraitClause {
     where
         C: AstFormatter,
     {
-        let clause_id = self.clause_id.fmt_with_ctx(ctx);
-        let trait_id = ctx.format_object(self.trait_id);
-        let generics = self.generics.fmt_with_ctx(ctx);
-        format!("[{clause_id}]: {trait_id}{generics}")
+        fmt_trait_clause_with_ctx(
+            ctx,
+            &self.clause_id.fmt_with_ctx(ctx),
+            self.trait_id,
+            &self.bound_regions,
+            &self.generics,
+            self.is_const,
+        )
     }
 }
 
+/// The trait clauses of an environment, as far as the resolution of the trait
+/// obligations is concerned (see [TraitResolutionCache]): the clause id, the
+/// trait, whether the clause is higher-ranked, whether it is a `~const` bound,
+/// and the generic arguments.
+type TraitEnv = Vec<(TraitInstanceId, TraitDeclId::Id, bool, bool, GenericArgs)>;
+
+/// The cache of the resolved trait obligations. We cache at two levels:
+/// - the translation of the trait references solved by hax (see
+///   [BodyTransCtx::translate_trait_impl_expr]), which is where the resolution
+///   of the obligations starts: the same references appear many times in an
+///   item (every call to `Vec::push` for instance), and their translation
+///   recursively translates their arguments and their nested references. We
+///   key them by the item and its environment, because the translation of the
+///   type variables and of the regions depends on the item;
+/// - the resolution of the obligations with the local clauses (see
+///   [BodyTransCtx::find_trait_clause_for_param]). The same obligations appear
+///   in many items which share their trait clauses (for instance the methods of
+///   a trait impl, which inherit the clauses of the impl): we resolve them once
+///   per environment.
+///
+/// We only cache the obligations we could resolve: the unsolved ones are
+/// either solved later (while we register the trait clauses), or errors.
+#[derive(Default)]
+pub struct TraitResolutionCache {
+    /// The environments we encountered so far, and their ids
+    envs: HashMap<TraitEnv, usize>,
+    /// The translated trait references, indexed by the item, the id of its
+    /// environment and whether we erase the regions
+    impl_exprs: HashMap<(DefId, usize, bool), HashMap<hax::ImplExpr, TraitRef>>,
+    pub impl_expr_hits: usize,
+    pub impl_expr_misses: usize,
+    /// The resolved obligations: the trait, its arguments, whether we are in
+    /// a const context and the id of the environment
+    resolved: HashMap<(TraitDeclId::Id, GenericArgs, bool, usize), TraitInstanceId>,
+    pub hits: usize,
+    pub misses: usize,
+}
+
+impl TraitResolutionCache {
+    /// The number of resolved obligations in the cache
+    pub fn len(&self) -> usize {
+        self.resolved.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.resolved.is_empty()
+    }
+
+    /// The number of translated trait references in the cache
+    pub fn impl_exprs_len(&self) -> usize {
+        self.impl_exprs.values().map(HashMap::len).sum()
+    }
+}
+
+/// The information about a predicate that hax doesn't give us (see
+/// [BodyTransCtx::get_predicates_of]).
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PredicateInfo {
+    /// For the projection predicates on generic associated types: the own
+    /// arguments of the associated type (`U` in `T : Foo<S<U> = V>`).
+    pub assoc_type_args: Option<Vec<hax::GenericArg>>,
+    /// For the trait predicates: [true] if this is a `~const` bound (hax
+    /// erases the constness of the trait predicates).
+    pub is_const: bool,
+}
+
 #[derive(Debug, Clone, EnumIsA, EnumAsGetters, EnumToGetters)]
 pub(crate) enum Predicate {
     Trait(NonLocalTraitClause),
@@ -145,7 +226,7 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
     pub(crate) fn get_predicates_of(
         &mut self,
         def_id: DefId,
-    ) -> Result<hax::GenericPredicates, Error> {
+    ) -> Result<Vec<(hax::Predicate, hax::Span, PredicateInfo)>, Error> {
         // **IMPORTANT**:
         // There are two functions which allow to retrieve the predicates of
         // a definition:
@@ -180,13 +261,11 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
         // ```
         let tcx = self.t_ctx.tcx;
         let param_env = tcx.param_env(def_id);
-        let parent: Option<hax::DefId>;
 
         let trait_preds = {
             // Remark: we don't convert the predicates yet because we need to
             // normalize them before.
             let predicates = tcx.predicates_defined_on(def_id);
-            parent = predicates.parent.sinto(&self.hax_state);
             let predicates: Vec<_> = predicates.predicates.iter().collect();
             trace!(
                 "TyCtxt::predicates_defined_on({:?}):\n{:?}",
@@ -207,41 +286,52 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                     })
                     .collect();
 
-            let trait_clauses: Vec<(rustc_middle::ty::TraitPredicate<'_>, rustc_span::Span)> =
-                trait_clauses
-                    .into_iter()
-                    .map(|(pred, span)| {
-                        if let Some(pred) = &pred.kind().no_bound_vars() {
-                            if let rustc_middle::ty::PredicateKind::Clause(
-                                rustc_middle::ty::Clause::Trait(tr),
-                            ) = pred
-                            {
-                                // Normalize the trait clause
-                                let tr = tcx.normalize_erasing_regions(param_env, *tr);
-                                Ok((tr, *span))
-                            } else {
-                                unreachable!();
-                            }
+            // We keep the binders, which may introduce bound regions (i.e.,
+            // `for<'a> ...`): they are handled in [translate_predicate].
+            let trait_clauses: Vec<(
+                rustc_middle::ty::Binder<'_, rustc_middle::ty::TraitPredicate<'_>>,
+                rustc_span::Span,
+            )> = trait_clauses
+                .into_iter()
+                .map(|(pred, span)| {
+                    let tr = pred.kind().map_bound(|pred| {
+                        if let rustc_middle::ty::PredicateKind::Clause(
+                            rustc_middle::ty::Clause::Trait(tr),
+                        ) = pred
+                        {
+                            tr
                         } else {
-                            // Report an error
-                            error_or_panic!(self, *span, "Predicates with bound regions (i.e., `for<'a> ...`) are not supported yet")
+                            unreachable!();
                         }
-                    })
-                    .try_collect()?;
+                    });
+                    // Normalize the trait clause (this doesn't erase the
+                    // bound regions)
+                    let tr = tcx.normalize_erasing_regions(param_env, tr);
+                    (tr, *span)
+                })
+                .collect();
 
             let trait_preds: Vec<_> = trait_clauses
                 .iter()
                 .map(|(tr, span)| {
+                    // Hax doesn't keep the constness of the trait predicates
+                    let info = PredicateInfo {
+                        is_const: tr.skip_binder().constness
+                            == rustc_middle::ty::BoundConstness::ConstIfConst,
+                        ..PredicateInfo::default()
+                    };
+                    let tr = self.fold_aliases(*tr);
+                    let tr: hax::Binder<hax::TraitPredicate> = tr.sinto(&self.hax_state);
                     let value = hax::PredicateKind::Clause(hax::Clause {
-                        kind: hax::ClauseKind::Trait(tr.sinto(&self.hax_state)),
+                        kind: hax::ClauseKind::Trait(tr.value),
                         // Remark: we introduce a dummy id...
                         id: 0,
                     });
                     let pred = hax::Binder {
                         value,
-                        bound_vars: Vec::new(),
+                        bound_vars: tr.bound_vars,
                     };
-                    (pred, span.sinto(&self.hax_state))
+                    (pred, span.sinto(&self.hax_state), info)
                 })
                 .collect();
             trait_preds
@@ -271,17 +361,44 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
             );
             let non_trait_preds: Vec<_> = non_trait_preds
                 .iter()
-                .map(|(pred, span)| (pred.sinto(&self.hax_state), span.sinto(&self.hax_state)))
+                .map(|(pred, span)| self.get_predicate_with_info(*pred, *span))
                 .collect();
             non_trait_preds
         };
 
-        let predicates: Vec<(hax::Predicate, hax::Span)> = trait_preds
+        let predicates: Vec<(hax::Predicate, hax::Span, PredicateInfo)> = trait_preds
             .into_iter()
             .chain(non_trait_preds.into_iter())
             .collect();
         trace!("Predicates of {:?}\n{:?}", def_id, predicates);
-        Ok(hax::GenericPredicates { parent, predicates })
+        Ok(predicates)
+    }
+
+    /// Convert a rustc predicate, and retrieve the information that hax
+    /// doesn't give us (see [PredicateInfo]).
+    pub(crate) fn get_predicate_with_info(
+        &mut self,
+        pred: rustc_middle::ty::Predicate<'tcx>,
+        span: rustc_span::Span,
+    ) -> (hax::Predicate, hax::Span, PredicateInfo) {
+        use rustc_middle::ty::{Clause, PredicateKind};
+        let pred = self.fold_aliases(pred);
+        let assoc_type_args = match pred.kind().skip_binder() {
+            PredicateKind::Clause(Clause::Projection(proj_pred)) => {
+                let proj_ty = proj_pred.projection_ty;
+                let parent_count = self.t_ctx.tcx.generics_of(proj_ty.def_id).parent_count;
+                Some(proj_ty.substs[parent_count..].sinto(&self.hax_state))
+            }
+            _ => None,
+        };
+        (
+            pred.sinto(&self.hax_state),
+            span.sinto(&self.hax_state),
+            PredicateInfo {
+                assoc_type_args,
+                is_const: false,
+            },
+        )
     }
 
     /// This function should be called **after** we translated the generics
@@ -370,14 +487,7 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
 
     pub(crate) fn translate_predicates(
         &mut self,
-        preds: &hax::GenericPredicates,
-    ) -> Result<(), Error> {
-        self.translate_predicates_vec(&preds.predicates)
-    }
-
-    pub(crate) fn translate_predicates_vec(
-        &mut self,
-        preds: &Vec<(hax::Predicate, hax::Span)>,
+        preds: &[(hax::Predicate, hax::Span, PredicateInfo)],
     ) -> Result<(), Error> {
         trace!("Predicates:\n{:?}", preds);
         // We reorder the trait predicates so that we translate the predicates
@@ -385,7 +495,7 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
         // (because in order to translate the latters we might need to solve
         // trait parameters which need the formers).
         use hax::{Clause, ClauseKind, PredicateKind};
-        let (preds_traits, preds): (Vec<_>, Vec<_>) = preds.iter().partition(|(pred, _)| {
+        let (preds_traits, preds): (Vec<_>, Vec<_>) = preds.iter().partition(|(pred, _, _)| {
             matches!(
                 &pred.value,
                 PredicateKind::Clause(Clause {
@@ -396,8 +506,8 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
         });
         let preds: Vec<_> = preds_traits.into_iter().chain(preds.into_iter()).collect();
 
-        for (pred, span) in preds {
-            match self.translate_predicate(pred, span)? {
+        for (pred, span, info) in preds {
+            match self.translate_predicate(pred, span, info)? {
                 None => (),
                 Some(pred) => match pred {
                     Predicate::Trait(_) => {
@@ -447,10 +557,19 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
     /// context, so that we can use them when solving the trait obligations which depend
     /// on the trait parameters. In order to make the resolution truly work, we should
     /// (give the possibility of) normalizing the types.
+    ///
+    /// [bound_regions]: the regions bound by the clause itself (if the clause
+    /// is of the shape `for<'a> ...`). The caller must have pushed the
+    /// corresponding group of bound regions (see [with_locally_bound_regions_group]).
+    ///
+    /// [is_const]: [true] if this is a `~const` bound, or the self clause of
+    /// a `const` impl.
     pub(crate) fn translate_trait_clause(
         &mut self,
         hspan: &hax::Span,
+        bound_regions: RegionId::Vector<RegionVar>,
         trait_pred: &hax::TraitPredicate,
+        is_const: bool,
     ) -> Result<Option<NonLocalTraitClause>, Error> {
         // Note sure what this is about
         assert!(trait_pred.is_positive);
@@ -483,10 +602,13 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
             clause_id,
             meta: Some(meta),
             trait_id,
+            bound_regions,
             generics,
+            is_const,
         };
         self.trait_clauses
             .insert(trait_clause.clause_id.clone(), trait_clause.clone());
+        self.trait_env = None;
 
         // Return
         Ok(Some(trait_clause))
@@ -496,6 +618,7 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
         &mut self,
         pred: &hax::Predicate,
         hspan: &hax::Span,
+        info: &PredicateInfo,
     ) -> Result<Option<Predicate>, Error> {
         trace!("{:?}", pred);
         // Predicates are always used in signatures/type definitions, etc.
@@ -506,14 +629,36 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
         // Skip the binder (which lists the quantified variables).
         // By doing so, we allow the predicates to contain DeBruijn indices,
         // but it is ok because we only do a simple check.
+        // The exception is the trait clauses, which remember their bound
+        // regions.
         let pred_kind = &pred.value;
         use hax::{Clause, ClauseKind, PredicateKind};
         match pred_kind {
             PredicateKind::Clause(Clause { kind, .. }) => {
                 match kind {
-                    ClauseKind::Trait(trait_pred) => Ok(self
-                        .translate_trait_clause(hspan, trait_pred)?
-                        .map(Predicate::Trait)),
+                    ClauseKind::Trait(trait_pred) => {
+                        let clause = if pred.bound_vars.is_empty() {
+                            self.translate_trait_clause(
+                                hspan,
+                                RegionId::Vector::new(),
+                                trait_pred,
+                                info.is_const,
+                            )?
+                        } else {
+                            let names =
+                                self.translate_bound_region_names(span, &pred.bound_vars)?;
+                            self.with_locally_bound_regions_group(names, |ctx| {
+                                let bound_regions = ctx.region_vars[0].clone();
+                                ctx.translate_trait_clause(
+                                    hspan,
+                                    bound_regions,
+                                    trait_pred,
+                                    info.is_const,
+                                )
+                            })?
+                        };
+                        Ok(clause.map(Predicate::Trait))
+                    }
                     ClauseKind::RegionOutlives(p) => {
                         let r0 = self.translate_region(span, erase_regions, &p.lhs)?;
                         let r1 = self.translate_region(span, erase_regions, &p.rhs)?;
@@ -543,10 +688,18 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                         // we may filter) don't have associated types.
                         let trait_ref = trait_ref.unwrap();
                         let ty = self.translate_ty(span, erase_regions, ty).unwrap();
+                        let generics = self.translate_assoc_type_args(
+                            span,
+                            erase_regions,
+                            &impl_expr.r#trait,
+                            type_name,
+                            info.assoc_type_args.as_deref(),
+                        )?;
                         let type_name = TraitItemName(type_name.clone());
                         Ok(Some(Predicate::TraitType(TraitTypeConstraint {
                             trait_ref,
                             type_name,
+                            generics,
                             ty,
                         })))
                     }
@@ -632,6 +785,24 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
         erase_regions: bool,
         impl_expr: &hax::ImplExpr,
     ) -> Result<Option<TraitRef>, Error> {
+        // Check if we already translated this reference in the same item and
+        // environment. We don't use the cache while we register the trait
+        // clauses (the obligations may not be solved yet), nor under binders
+        // (the translation of the regions depends on the binders).
+        let cache_key = if self.registering_trait_clauses || !self.bound_region_vars.is_empty() {
+            None
+        } else {
+            Some((self.def_id, self.trait_env_id(), erase_regions))
+        };
+        if let Some(key) = &cache_key {
+            let cache = &mut self.t_ctx.trait_resolution_cache;
+            if let Some(trait_ref) = cache.impl_exprs.get(key).and_then(|m| m.get(impl_expr)) {
+                cache.impl_expr_hits += 1;
+                return Ok(Some(trait_ref.clone()));
+            }
+            cache.impl_expr_misses += 1;
+        }
+
         let trait_decl_ref =
             match self.translate_trait_decl_ref(span, erase_regions, &impl_expr.r#trait)? {
                 None => return Ok(None),
@@ -644,19 +815,31 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
             impl_expr,
             trait_decl_ref.clone(),
         ) {
-            Ok(res) => Ok(res),
+            Ok(res) => {
+                if let (Some(key), Some(trait_ref)) = (cache_key, &res)
+                    && !matches!(trait_ref.trait_id, TraitInstanceId::Unknown(_))
+                {
+                    self.t_ctx
+                        .trait_resolution_cache
+                        .impl_exprs
+                        .entry(key)
+                        .or_default()
+                        .insert(impl_expr.clone(), trait_ref.clone());
+                }
+                Ok(res)
+            }
             Err(err) => {
-                if !self.t_ctx.continue_on_failure {
+                if !self.t_ctx.continue_on_failure() {
                     panic!("Error during trait resolution: {}", err.msg)
                 } else {
                     let msg = format!("Error during trait resolution: {}", &err.msg);
                     self.span_err(span, &msg);
                     let trait_id = TraitInstanceId::Unknown(err.msg);
-                    Ok(Some(TraitRef {
+                    Ok(Some(TraitRef::new(
                         trait_id,
-                        generics: GenericArgs::empty(),
+                        GenericArgs::empty(),
                         trait_decl_ref,
-                    }))
+                    )))
                 }
             }
         }
@@ -682,8 +865,25 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                 let def_id = DefId::from(impl_def_id);
                 let trait_id = self.translate_trait_impl_id(span, def_id)?;
                 // We already tested above whether the trait should be filtered
-                let trait_id = trait_id.unwrap();
-                let trait_id = TraitInstanceId::TraitImpl(trait_id);
+                let impl_id = trait_id.unwrap();
+                // With specialization, the items used at runtime may come from
+                // an impl which specializes this one
+                let mut specializing_impls = Vec::new();
+                if self.t_ctx.trait_impl_is_specializable(def_id) {
+                    for id in self.t_ctx.specializing_impls(def_id) {
+                        if let Some(id) = self.translate_trait_impl_id(span, id)? {
+                            specializing_impls.push(id);
+                        }
+                    }
+                }
+                let trait_id = if specializing_impls.is_empty() {
+                    TraitInstanceId::TraitImpl(impl_id)
+                } else {
+                    TraitInstanceId::Specializable {
+                        impl_id,
+                        specializing_impls,
+                    }
+                };
 
                 let generics = self.translate_substs_and_trait_refs(
                     span,
@@ -692,11 +892,7 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                     generics,
                     nested,
                 )?;
-                TraitRef {
-                    trait_id,
-                    generics,
-                    trait_decl_ref,
-                }
+                TraitRef::new(trait_id, generics, trait_decl_ref)
             }
             // The self clause and the other clauses are handled in a similar manner
             ImplExprAtom::SelfImpl {
@@ -708,15 +904,16 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                 r#trait: trait_ref,
                 path,
             } => {
-                // (trait_ref, trait_refs, constness)
-                // TODO: the constness information is not there anymore...
+                // The constness information is not there anymore: we recorded
+                // it in the clauses themselves (see [TraitClause::is_const]),
+                // and in the impls (see [crate::gast::TraitImpl::is_const]).
                 // Explanations about constness: https://stackoverflow.com/questions/70441495/what-is-impl-const-in-rust
                 trace!(
                     "impl source (self or clause): param:\n- trait_ref: {:?}\n- path: {:?}",
                     trait_ref,
                     path,
                 );
-                assert!(trait_ref.bound_vars.is_empty());
+                let bound_vars = &trait_ref.bound_vars;
                 let trait_ref = &trait_ref.value;
 
                 let def_id = DefId::from(&trait_ref.def_id);
@@ -724,14 +921,29 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                 // the trait decl ref: the trait decl id should be Some(...).
                 let trait_decl_id = self.translate_trait_decl_id(span, def_id)?.unwrap();
 
-                // Retrieve the arguments
-                let generics = self.translate_substs_and_trait_refs(
-                    span,
-                    erase_regions,
-                    None,
-                    &trait_ref.generic_args,
-                    nested,
-                )?;
+                // Retrieve the arguments.
+                // If the clause is higher-ranked, the arguments may refer to
+                // the bound regions: we push the corresponding group.
+                let generics = if bound_vars.is_empty() {
+                    self.translate_substs_and_trait_refs(
+                        span,
+                        erase_regions,
+                        None,
+                        &trait_ref.generic_args,
+                        nested,
+                    )?
+                } else {
+                    let names = self.translate_bound_region_names(span, bound_vars)?;
+                    self.with_locally_bound_regions_group(names, |ctx| {
+                        ctx.translate_substs_and_trait_refs(
+                            span,
+                            erase_regions,
+                            None,
+                            &trait_ref.generic_args,
+                            nested,
+                        )
+                    })?
+                };
                 assert!(generics.trait_refs.is_empty());
 
                 // If we are refering to a trait clause, we need to find the
@@ -785,11 +997,7 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
 
                 // Ignore the arguments: we forbid using universal quantifiers
                 // on the trait clauses for now.
-                TraitRef {
-                    trait_id,
-                    generics: GenericArgs::empty(),
-                    trait_decl_ref,
-                }
+                TraitRef::new(trait_id, GenericArgs::empty(), trait_decl_ref)
             }
             ImplExprAtom::Dyn { .. } => {
                 error_or_panic!(self, span, "Unsupported trait impl source kind: object")
@@ -808,28 +1016,15 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                     &trait_ref.generic_args,
                     nested,
                 )?;
-                TraitRef {
-                    trait_id,
-                    generics,
-                    trait_decl_ref,
-                }
+                TraitRef::new(trait_id, generics, trait_decl_ref)
             }
             ImplExprAtom::FnPointer { fn_ty } => {
                 let ty = self.translate_ty(span, erase_regions, fn_ty)?;
                 let trait_id = TraitInstanceId::FnPointer(Box::new(ty));
                 let trait_refs =
                     self.translate_trait_impl_exprs(span, erase_regions, &impl_source.args)?;
-                let generics = GenericArgs {
-                    regions: vec![],
-                    types: vec![],
-                    const_generics: vec![],
-                    trait_refs,
-                };
-                TraitRef {
-                    trait_id,
-                    generics,
-                    trait_decl_ref,
-                }
+                let generics = GenericArgs::new(vec![], vec![], vec![], trait_refs);
+                TraitRef::new(trait_id, generics, trait_decl_ref)
             }
             ImplExprAtom::Closure {
                 closure_def_id,
@@ -848,30 +1043,17 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                 // TODO: translate the signature
                 let trait_refs = self.translate_trait_impl_exprs(span, erase_regions, nested)?;
                 let trait_id = TraitInstanceId::Closure(fn_id, parent_substs);
-                let generics = GenericArgs {
-                    regions: vec![],
-                    types: vec![],
-                    const_generics: vec![],
-                    trait_refs,
-                };
-                TraitRef {
-                    trait_id,
-                    generics,
-                    trait_decl_ref,
-                }
+                let generics = GenericArgs::new(vec![], vec![], vec![], trait_refs);
+                TraitRef::new(trait_id, generics, trait_decl_ref)
             }
             ImplExprAtom::Error(msg) | ImplExprAtom::Todo(msg) => {
                 let error = format!("Error during trait resolution: {}", msg);
                 self.span_err(span, &error);
-                if !self.t_ctx.continue_on_failure {
+                if !self.t_ctx.continue_on_failure() {
                     panic!("{}", error)
                 } else {
                     let trait_id = TraitInstanceId::Unknown(msg.clone());
-                    TraitRef {
-                        trait_id,
-                        generics: GenericArgs::empty(),
-                        trait_decl_ref,
-                    }
+                    TraitRef::new(trait_id, GenericArgs::empty(), trait_decl_ref)
                 }
             }
         };
@@ -901,22 +1083,79 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
             let src_types = &clause.generics.types;
             let src_const_generics = &clause.generics.const_generics;
 
-            // We simply check the equality between the arguments:
-            // there are no universally quantified variables to unify.
+            // If the clause doesn't bind regions, we simply check the equality
+            // between the arguments: there are no universally quantified variables
+            // to unify.
             // TODO: normalize the trait clauses (we actually
             // need to check equality **modulo** equality clauses)
-            // TODO: if we need to unify (later, when allowing universal
-            // quantification over clause parameters), use types_utils::TySubst.
-            let matched = src_types == tgt_types && src_const_generics == tgt_const_generics;
+            let matched = if clause.bound_regions.is_empty() {
+                src_types == tgt_types && src_const_generics == tgt_const_generics
+            } else {
+                // The clause is higher-ranked (`for<'a> ...`): we instantiate
+                // the bound regions by unifying the arguments. As we ignore the
+                // regions, it amounts to checking that the arguments are equal
+                // up to the regions, while the type and const generic variables
+                // of the current context are fixed.
+                TySubst::unify_args_with_fixed(
+                    self.type_vars.iter().map(|v| v.index),
+                    self.const_generic_vars.iter().map(|v| v.index),
+                    &clause.generics,
+                    generics,
+                )
+                .is_ok()
+            };
             trace!("Match successful: {}", matched);
             matched
         }
     }
 
+    /// The id of the current environment in the cache of the resolved trait
+    /// obligations (see [TraitResolutionCache]).
+    fn trait_env_id(&mut self) -> usize {
+        if let Some(id) = self.trait_env {
+            return id;
+        }
+        let env: TraitEnv = self
+            .trait_clauses
+            .values()
+            .map(|clause| {
+                (
+                    clause.clause_id.clone(),
+                    clause.trait_id,
+                    clause.bound_regions.is_empty(),
+                    clause.is_const,
+                    clause.generics.clone(),
+                )
+            })
+            .collect();
+        let envs = &mut self.t_ctx.trait_resolution_cache.envs;
+        let fresh_id = envs.len();
+        let id = *envs.entry(env).or_insert(fresh_id);
+        self.trait_env = Some(id);
+        id
+    }
+
+    /// Whether the current item is evaluated in a const context: the bodies of
+    /// the `const fn` (including the methods of the `const` impls) and of the
+    /// constants.
+    fn in_const_context(&self) -> bool {
+        use rustc_hir::def::DefKind;
+        let tcx = self.t_ctx.tcx;
+        tcx.is_const_fn_raw(self.def_id)
+            || matches!(
+                tcx.def_kind(self.def_id),
+                DefKind::Const
+                    | DefKind::AssocConst
+                    | DefKind::Static(_)
+                    | DefKind::AnonConst
+                    | DefKind::InlineConst
+            )
+    }
+
     /// Find the trait instance fullfilling a trait obligation.
     /// TODO: having to do this is very annoying. Isn't there a better way?
     fn find_trait_clause_for_param(
-        &self,
+        &mut self,
         trait_id: TraitDeclId::Id,
         generics: &GenericArgs,
     ) -> TraitInstanceId {
@@ -926,11 +1165,35 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
             self.t_ctx.tcx.def_ident_span(self.def_id)
         );
 
-        // Simply explore the trait clauses
-        for trait_clause in self.trait_clauses.values() {
-            if self.match_trait_clauses(trait_id, generics, trait_clause) {
-                return trait_clause.clause_id.clone();
-            }
+        // Check if we already resolved this obligation in the same environment
+        let is_const = self.in_const_context();
+        let key = (trait_id, generics.clone(), is_const, self.trait_env_id());
+        let cache = &mut self.t_ctx.trait_resolution_cache;
+        if let Some(clause_id) = cache.resolved.get(&key) {
+            cache.hits += 1;
+            return clause_id.clone();
+        }
+        cache.misses += 1;
+
+        // Simply explore the trait clauses. In a const context, the obligations
+        // are solved by the `~const` bounds if there are some (the other ones
+        // only allow non-const uses of the trait), and conversely.
+        let clauses: Vec<_> = self
+            .trait_clauses
+            .values()
+            .filter(|trait_clause| self.match_trait_clauses(trait_id, generics, trait_clause))
+            .collect();
+        let clause_id = clauses
+            .iter()
+            .find(|trait_clause| trait_clause.is_const == is_const)
+            .or(clauses.first())
+            .map(|trait_clause| trait_clause.clause_id.clone());
+        if let Some(clause_id) = clause_id {
+            self.t_ctx
+                .trait_resolution_cache
+                .resolved
+                .insert(key, clause_id.clone());
+            return clause_id;
         }
 
         // Could not find a clause.
@@ -951,7 +1214,7 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                 .map(|x| x.fmt_with_ctx(&fmt_ctx))
                 .collect();
 
-            if !self.t_ctx.continue_on_failure {
+            if !self.t_ctx.continue_on_failure() {
                 let clauses = clauses.join("\n");
                 unreachable!(
                     "Could not find a clause for parameter:\n- target param: {}\n- available clauses:\n{}\n- context: {:?}",
@@ -1044,6 +1307,7 @@ impl<'a, 'tcx, 'ctx, 'ctx1> TraitInstancesSolver<'a, 'tcx, 'ctx, 'ctx1> {
         // If we are solving: reconstruct the trait clauses map, and replace the one in the context
         if solve {
             self.ctx.trait_clauses = im::OrdMap::from(trait_clauses);
+            self.ctx.trait_env = None;
         }
 
         //
@@ -1104,43 +1368,15 @@ impl<'a, 'tcx, 'ctx, 'ctx1> TraitInstancesSolver<'a, 'tcx, 'ctx, 'ctx1> {
             log::trace!("Pass id: {}, unsolved count: {}", pass_id, count);
             self.solve_one_pass();
             if self.unsolved_count >= count {
-                // We're stuck: report an error
-
-                // Retraverse the context, collecting the unsolved clauses.
+                // We're stuck: we retry the remaining obligations once all the
+                // items are translated, and report those we still fail to solve
+                // (see [crate::transform::solve_trait_obligations]).
                 self.collect_unsolved();
-
-                let ctx = self.ctx.into_fmt();
-                let unsolved = self
-                    .unsolved
-                    .iter()
-                    .map(|(trait_id, generics)| {
-                        format!(
-                            "{}{}",
-                            ctx.format_object(*trait_id),
-                            generics.fmt_with_ctx(&ctx)
-                        )
-                    })
-                    .collect::<Vec<String>>()
-                    .join("\n");
-                let clauses = self
-                    .ctx
-                    .trait_clauses
-                    .values()
-                    .map(|x| x.fmt_with_ctx(&ctx))
-                    .collect::<Vec<String>>()
-                    .join("\n");
-
-                if !self.ctx.t_ctx.continue_on_failure {
-                    unreachable!(
-                        "Could not find clauses for trait obligations:{}\n\nAvailable clauses:\n{}\n- context: {:?}",
-                        unsolved, clauses, self.ctx.def_id
-                    );
-                } else {
-                    let msg = format!("Could not find clauses for trait obligations:{}\n\nAvailable clauses:\n{}\n- context: {:?}",
-                        unsolved, clauses, self.ctx.def_id);
-                    self.ctx.span_err(self.span, &msg);
-                }
-
+                log::trace!(
+                    "Unsolved trait obligations at {:?}:\n{:?}",
+                    self.span,
+                    self.unsolved
+                );
                 return;
             } else {
                 // We made progress: update the count
@@ -25,14 +25,72 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
         )
     }
 
+    /// Translate the generic parameters which are specific to an associated
+    /// item (for instance, `'a` in `type Item<'a>`), then call `f` in a context
+    /// where those parameters are available.
+    ///
+    /// We return the *full* generics of the item: the parameters of the trait
+    /// declaration (or implementation) come first, followed by the parameters
+    /// which are specific to the item. The parameters of the item are removed
+    /// from the context once we are done.
+    fn with_assoc_item_generics<T>(
+        &mut self,
+        item: &rustc_middle::ty::AssocItem,
+        f: impl FnOnce(&mut Self) -> Result<T, Error>,
+    ) -> Result<(GenericParams, T), Error> {
+        let tcx = self.t_ctx.tcx;
+        let item_generics = tcx.generics_of(item.def_id);
+        if item_generics.params.is_empty() {
+            // Not a generic item: the parameters are those of the parent
+            let x = f(self)?;
+            return Ok((self.get_generics(), x));
+        }
+
+        // Save the parameters, so that we can restore them afterwards
+        let region_vars = self.region_vars.clone();
+        let free_region_vars = self.free_region_vars.clone();
+        let type_vars = self.type_vars.clone();
+        let type_vars_map = self.type_vars_map.clone();
+        let const_generic_vars = self.const_generic_vars.clone();
+        let const_generic_vars_map = self.const_generic_vars_map.clone();
+
+        // The identity substitution also contains the parameters of the parent:
+        // we skip those.
+        let span = tcx.def_span(item.def_id);
+        let substs: Vec<hax::GenericArg> =
+            rustc_middle::ty::subst::InternalSubsts::identity_for_item(tcx, item.def_id)
+                .iter()
+                .skip(item_generics.parent_count)
+                .map(|x| x.sinto(&self.hax_state))
+                .collect();
+        let res = self
+            .translate_generic_params_from_hax(span, item.def_id, &substs)
+            .and_then(|()| f(self));
+        let generics = self.get_generics();
+
+        // Restore the parameters
+        self.region_vars = region_vars;
+        self.free_region_vars = free_region_vars;
+        self.type_vars = type_vars;
+        self.type_vars_map = type_vars_map;
+        self.const_generic_vars = const_generic_vars;
+        self.const_generic_vars_map = const_generic_vars_map;
+
+        res.map(|x| (generics, x))
+    }
+
     /// Helper for [translate_trait_impl].
     ///
-    /// Remark: the [decl_item] is the item from the trait declaration.
+    /// Remark: the [decl_item] is the item from the trait declaration, while
+    /// the [def_item] is the item which defines the associated type: either
+    /// the item from the trait implementation, or [decl_item] if the
+    /// implementation uses the default value.
     fn translate_trait_refs_from_impl_trait_item(
         &mut self,
         trait_impl_def_id: DefId,
         rust_impl_trait_ref: &rustc_middle::ty::TraitRef<'tcx>,
         decl_item: &rustc_middle::ty::AssocItem,
+        def_item: &rustc_middle::ty::AssocItem,
     ) -> Result<Vec<TraitRef>, Error> {
         trace!(
             "- trait_impl_def_id: {:?}\n- rust_impl_trait_ref: {:?}\n- decl_item: {:?}",
@@ -45,7 +103,17 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
         let span = tcx.def_span(trait_impl_def_id);
 
         // Lookup the trait clauses and substitute - TODO: not sure about the substitution
-        let subst = rust_impl_trait_ref.substs;
+        // If the associated type is generic, the bounds also refer to the
+        // parameters of the item (which come after the parameters of the trait).
+        let item_substs =
+            rustc_middle::ty::subst::InternalSubsts::identity_for_item(tcx, def_item.def_id);
+        let subst = tcx.mk_substs_from_iter(
+            rust_impl_trait_ref.substs.iter().chain(
+                item_substs
+                    .iter()
+                    .skip(tcx.generics_of(def_item.def_id).parent_count),
+            ),
+        );
         let bounds = tcx.item_bounds(decl_item.def_id);
         let param_env = tcx.param_env(trait_impl_def_id);
         let bounds = tcx.subst_and_normalize_erasing_regions(subst, param_env, bounds);
@@ -58,7 +126,8 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                 trait_pred,
             )) = bound.kind().skip_binder()
             {
-                let trait_ref = rustc_middle::ty::Binder::dummy(trait_pred.trait_ref);
+                // Keep the binder: the bound may be higher-ranked
+                let trait_ref = bound.kind().rebind(trait_pred.trait_ref);
                 let trait_ref = hax::solve_trait(&self.hax_state, param_env, trait_ref);
                 let trait_ref = self.translate_trait_impl_expr(span, erase_regions, &trait_ref)?;
                 if let Some(trait_ref) = trait_ref {
@@ -135,7 +204,7 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
             TraitInstanceId::SelfId
         });
         let self_clause = self.with_local_trait_clauses(self_instance_id_gen, move |s| {
-            s.translate_trait_clause(&span, &self_pred)
+            s.translate_trait_clause(&span, RegionId::Vector::new(), &self_pred, false)
         })?;
         trace!(
             "self clause: {}",
@@ -170,7 +239,9 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
         };
         let trait_pred = trait_pred.sinto(&self.hax_state);
 
-        // Save the self clause (and its parent/item clauses)
+        // Save the self clause (and its parent/item clauses). In a `const`
+        // impl, the self clause is a `~const` bound.
+        let is_const = tcx.constness(def_id) == rustc_hir::Constness::Const;
         let mut initialized = false;
         let span = tcx.def_span(def_id).sinto(&self.t_ctx.hax_state);
         let _ = self.with_local_trait_clauses(
@@ -179,7 +250,9 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                 initialized = true;
                 TraitInstanceId::SelfId
             }),
-            move |s| s.translate_trait_clause(&span, &trait_pred),
+            move |s| {
+                s.translate_trait_clause(&span, RegionId::Vector::new(), &trait_pred, is_const)
+            },
         )?;
         Ok(())
     }
@@ -246,7 +319,7 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
                     ),
                 );
                 // Save the definition
-                let _ = ctx.ignored_failed_decls.insert(rust_id);
+                let _ = ctx.errors.ignored_failed_decls.insert(rust_id);
             }
         });
     }
@@ -301,10 +374,11 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
                     let span = tcx.def_span(rust_id);
                     let method_name = bt_ctx.t_ctx.translate_trait_item_name(item.def_id)?;
                     // Skip the provided methods for the *external* trait declarations,
-                    // but still remember their name (unless `extract_opaque_bodies` is set).
+                    // but still remember their name (unless `extract_opaque_bodies` is set,
+                    // or the method matches a pattern of `extract_external_bodies`).
                     if has_default_value {
                         // This is a *provided* method
-                        if rust_id.is_local() || bt_ctx.t_ctx.extract_opaque_bodies {
+                        if rust_id.is_local() || bt_ctx.t_ctx.extract_external_body(item.def_id) {
                             let fun_id = bt_ctx.translate_fun_decl_id(span, item.def_id);
                             provided_methods.push((method_name, Some(fun_id)));
                         } else {
@@ -333,56 +407,61 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
                 }
                 AssocKind::Type => {
                     let name = item.name.to_string();
-
-                    // Translating the predicates
-                    {
-                        // TODO: this is an ugly manip
-                        let bounds = tcx.item_bounds(item.def_id).subst_identity();
-                        use crate::rustc_middle::query::Key;
-                        let span = bounds.default_span(tcx);
-                        let bounds: Vec<_> = bounds.into_iter().map(|x| (x, span)).collect();
-                        let bounds = bounds.sinto(&bt_ctx.hax_state);
-
-                        // Register the trait clauses as item trait clauses
-                        bt_ctx.with_item_trait_clauses(
-                            TraitInstanceId::SelfId,
-                            def_id,
-                            name.clone(),
-                            &mut |s| s.translate_predicates_vec(&bounds),
-                        )?;
-                    }
-
-                    // Retrieve the trait clauses which are specific to this item
-                    // - we simply need to filter the trait clauses by using their id.
-                    let item_trait_clauses: Vec<_> = bt_ctx
-                        .trait_clauses
-                        .values()
-                        .filter_map(|c| {
-                            c.to_trait_clause_with_id(&|id| match id {
-                                TraitInstanceId::ItemClause(
-                                    box TraitInstanceId::SelfId,
-                                    _,
-                                    TraitItemName(item_name),
-                                    clause_id,
-                                ) => {
-                                    if item_name == &name {
-                                        Some(*clause_id)
-                                    } else {
-                                        None
-                                    }
-                                }
-                                _ => None,
-                            })
-                        })
-                        .collect();
-
-                    let ty = if has_default_value {
-                        Some(bt_ctx.translate_ty_from_trait_item(item)?)
-                    } else {
-                        None
-                    };
-
-                    types.push((TraitItemName(name), (item_trait_clauses, ty)));
+                    let (generics, (item_trait_clauses, ty)) =
+                        bt_ctx.with_assoc_item_generics(item, |bt_ctx| {
+                            // Translating the predicates
+                            {
+                                // TODO: this is an ugly manip
+                                let bounds = tcx.item_bounds(item.def_id).subst_identity();
+                                use crate::rustc_middle::query::Key;
+                                let span = bounds.default_span(tcx);
+                                let bounds: Vec<_> = bounds
+                                    .into_iter()
+                                    .map(|x| bt_ctx.get_predicate_with_info(x, span))
+                                    .collect();
+
+                                // Register the trait clauses as item trait clauses
+                                bt_ctx.with_item_trait_clauses(
+                                    TraitInstanceId::SelfId,
+                                    def_id,
+                                    name.clone(),
+                                    &mut |s| s.translate_predicates(&bounds),
+                                )?;
+                            }
+
+                            // Retrieve the trait clauses which are specific to this item
+                            // - we simply need to filter the trait clauses by using their id.
+                            let item_trait_clauses: Vec<_> = bt_ctx
+                                .trait_clauses
+                                .values()
+                                .filter_map(|c| {
+                                    c.to_trait_clause_with_id(&|id| match id {
+                                        TraitInstanceId::ItemClause(
+                                            box TraitInstanceId::SelfId,
+                                            _,
+                                            TraitItemName(item_name),
+                                            clause_id,
+                                        ) => {
+                                            if item_name == &name {
+                                                Some(*clause_id)
+                                            } else {
+                                                None
+                                            }
+                                        }
+                                        _ => None,
+                                    })
+                                })
+                                .collect();
+
+                            let ty = if has_default_value {
+                                Some(bt_ctx.translate_ty_from_trait_item(item)?)
+                            } else {
+                                None
+                            };
+                            Ok((item_trait_clauses, ty))
+                        })?;
+
+                    types.push((TraitItemName(name), (generics, item_trait_clauses, ty)));
                 }
             }
         }
@@ -424,7 +503,8 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
             def_id,
             is_local: rust_id.is_local(),
             name,
-            item_meta: self.translate_item_meta_from_rid(rust_id),
+            item_meta: self.translate_item_meta_from_rid(rust_id)?,
+            lang_item: self.translate_lang_item_from_rid(rust_id),
             generics,
             preds,
             parent_clauses,
@@ -433,11 +513,62 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
             required_methods,
             provided_methods,
         };
-        self.trait_decls.insert(def_id, trait_decl);
+        self.translated.trait_decls.insert(def_id, trait_decl);
 
         Ok(())
     }
 
+    /// Whether a trait impl contains items which can be specialized (the
+    /// `default` items, or all the items of a `default impl`).
+    pub(crate) fn trait_impl_is_specializable(&self, impl_id: DefId) -> bool {
+        let tcx = self.tcx;
+        tcx.impl_defaultness(impl_id).is_default()
+            || tcx
+                .associated_items(impl_id)
+                .in_definition_order()
+                .any(|item| item.defaultness(tcx).is_default())
+    }
+
+    /// The impls which specialize a given impl (directly or not). We sort them
+    /// so that an impl comes before the impls it specializes (see
+    /// [TraitInstanceId::Specializable]).
+    pub(crate) fn specializing_impls(&self, impl_id: DefId) -> Vec<DefId> {
+        let tcx = self.tcx;
+        let trait_id = tcx.trait_id_of_impl(impl_id).unwrap();
+        let impls: Vec<DefId> = tcx
+            .all_impls(trait_id)
+            .filter(|id| *id != impl_id && tcx.specializes((*id, impl_id)))
+            .collect();
+        // An impl specializes strictly more impls than the impls it specializes
+        let mut impls: Vec<(usize, String, DefId)> = impls
+            .iter()
+            .map(|id| {
+                let num_specialized = impls
+                    .iter()
+                    .filter(|other| *other != id && tcx.specializes((*id, **other)))
+                    .count();
+                (num_specialized, tcx.def_path_str(*id), *id)
+            })
+            .collect();
+        impls.sort_by(|(n0, path0, _), (n1, path1, _)| n1.cmp(n0).then(path0.cmp(path1)));
+        impls.into_iter().map(|(_, _, id)| id).collect()
+    }
+
+    /// Find out how a trait impl was introduced (see [TraitImplKind]).
+    fn translate_trait_impl_kind(&self, rust_id: DefId) -> TraitImplKind {
+        use rustc_span::hygiene::{ExpnKind, MacroKind};
+        // The impls generated by the derive macros come from their expansions
+        let expn_data = self.tcx.def_span(rust_id).ctxt().outer_expn_data();
+        if let ExpnKind::Macro(MacroKind::Derive, macro_name) = expn_data.kind {
+            return TraitImplKind::Derived(macro_name.to_string());
+        }
+        let self_ty = self.tcx.type_of(rust_id).subst_identity();
+        match self_ty.kind() {
+            rustc_middle::ty::TyKind::Param(_) => TraitImplKind::Blanket,
+            _ => TraitImplKind::Regular,
+        }
+    }
+
     pub(crate) fn translate_trait_impl(&mut self, rust_id: DefId) {
         self.with_def_id(rust_id, |ctx| {
             if ctx.translate_trait_impl_aux(rust_id).is_err() {
@@ -450,7 +581,7 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
                     ),
                 );
                 // Save the definition
-                let _ = ctx.ignored_failed_decls.insert(rust_id);
+                let _ = ctx.errors.ignored_failed_decls.insert(rust_id);
             }
         });
     }
@@ -469,6 +600,8 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
 
         let tcx = self.tcx;
         let span = tcx.def_span(rust_id);
+        let is_negative = tcx.impl_polarity(rust_id) == rustc_middle::ty::ImplPolarity::Negative;
+        let is_const = tcx.constness(rust_id) == rustc_hir::Constness::Const;
         let mut bt_ctx = BodyTransCtx::new(rust_id, self);
 
         let name = bt_ctx.t_ctx.def_id_to_name(rust_id)?;
@@ -512,24 +645,24 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
             let (regions, types, const_generics) =
                 bt_ctx.translate_substs(span, erase_regions, None, &trait_ref.generic_args)?;
 
-            let parent_trait_refs = hax::solve_item_traits(
-                &bt_ctx.hax_state,
-                tcx.param_env(rust_id),
-                rust_trait_ref.def_id,
-                rust_trait_ref.substs,
-                None,
-            );
+            // The negative impls don't need to satisfy the parent clauses
+            let parent_trait_refs = if is_negative {
+                Vec::new()
+            } else {
+                hax::solve_item_traits(
+                    &bt_ctx.hax_state,
+                    tcx.param_env(rust_id),
+                    rust_trait_ref.def_id,
+                    rust_trait_ref.substs,
+                    None,
+                )
+            };
             let parent_trait_refs: Vec<TraitRef> =
                 bt_ctx.translate_trait_impl_exprs(span, erase_regions, &parent_trait_refs)?;
             let parent_trait_refs: TraitClauseId::Vector<TraitRef> =
                 TraitClauseId::Vector::from(parent_trait_refs);
 
-            let generics = GenericArgs {
-                regions,
-                types,
-                const_generics,
-                trait_refs: Vec::new(),
-            };
+            let generics = GenericArgs::new(regions, types, const_generics, Vec::new());
             let trait_ref = TraitDeclRef { trait_id, generics };
             (trait_rust_id, trait_ref, rust_trait_ref, parent_trait_refs)
         };
@@ -561,12 +694,17 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
         // We do something subtle here: TODO
         let tcx = bt_ctx.t_ctx.tcx;
         let mut consts = HashMap::new();
-        let mut types: HashMap<TraitItemName, Ty> = HashMap::new();
+        let mut types: HashMap<TraitItemName, rustc_middle::ty::AssocItem> = HashMap::new();
         let mut required_methods = Vec::new();
-        let mut provided_methods = Vec::new();
+        let mut provided_methods = HashMap::new();
+        let mut default_items = Vec::new();
 
         use rustc_middle::ty::AssocKind;
+        let is_default_impl = tcx.impl_defaultness(rust_id).is_default();
         for item in tcx.associated_items(rust_id).in_definition_order() {
+            if is_default_impl || item.defaultness(tcx).is_default() {
+                default_items.push(TraitItemName(item.name.to_string()));
+            }
             match &item.kind {
                 AssocKind::Fn => {
                     let method_name = bt_ctx.t_ctx.translate_trait_item_name(item.def_id)?;
@@ -578,7 +716,7 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
                     if is_required {
                         required_methods.push((method_name, fun_id));
                     } else {
-                        provided_methods.push((method_name, fun_id));
+                        provided_methods.insert(method_name, fun_id);
                     }
                 }
                 AssocKind::Const => {
@@ -586,9 +724,9 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
                     consts.insert(name, c);
                 }
                 AssocKind::Type => {
+                    // We translate the type below, together with the trait refs
                     let name = TraitItemName(item.name.to_string());
-                    let ty = bt_ctx.translate_ty_from_trait_item(item)?;
-                    types.insert(name, ty);
+                    types.insert(name, *item);
                 }
             }
         }
@@ -598,14 +736,42 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
         // check those, and lookup the relevant values.
         let partial_consts = consts;
         let partial_types = types;
+        let partial_provided_methods = provided_methods;
         let mut consts = Vec::new();
-        let mut types: Vec<(TraitItemName, (Vec<TraitRef>, Ty))> = Vec::new();
-        for item in tcx
-            .associated_items(implemented_trait_rust_id)
-            .in_definition_order()
-        {
+        let mut types: Vec<(TraitItemName, (GenericParams, Vec<TraitRef>, Ty))> = Vec::new();
+        let mut provided_methods = Vec::new();
+        // The negative impls don't implement the items of the trait
+        let trait_items: Vec<&rustc_middle::ty::AssocItem> = if is_negative {
+            Vec::new()
+        } else {
+            tcx.associated_items(implemented_trait_rust_id)
+                .in_definition_order()
+                .collect()
+        };
+        for item in trait_items {
             match &item.kind {
-                AssocKind::Fn => (),
+                AssocKind::Fn => {
+                    if !item.defaultness(tcx).has_value() {
+                        // Required method: already handled above
+                        continue;
+                    }
+                    let name = TraitItemName(item.name.to_string());
+                    // Does the trait impl override the default implementation?
+                    match partial_provided_methods.get(&name) {
+                        Some(fun_id) => provided_methods.push((name, (*fun_id, true))),
+                        None => {
+                            // Refer to the default implementation, that we
+                            // only translate in some situations (see the
+                            // translation of the trait declarations)
+                            if implemented_trait_rust_id.is_local()
+                                || bt_ctx.t_ctx.extract_external_body(item.def_id)
+                            {
+                                let fun_id = bt_ctx.translate_fun_decl_id(span, item.def_id);
+                                provided_methods.push((name, (fun_id, false)));
+                            }
+                        }
+                    }
+                }
                 AssocKind::Const => {
                     let name = TraitItemName(item.name.to_string());
                     // Does the trait impl provide an implementation for this const?
@@ -622,24 +788,28 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
                 AssocKind::Type => {
                     let name = TraitItemName(item.name.to_string());
                     // Does the trait impl provide an implementation for this type?
-                    let ty = match partial_types.get(&name) {
-                        Some(ty) => ty.clone(),
-                        None => {
-                            // The item is not defined in the trait impl:
-                            // the trait decl *must* define a default value.
-                            // TODO: should we normalize the type?
-                            bt_ctx.translate_ty_from_trait_item(item)?
-                        }
+                    // If not, the trait decl *must* define a default value.
+                    // TODO: should we normalize the type in this case?
+                    let def_item = match partial_types.get(&name) {
+                        Some(impl_item) => impl_item,
+                        None => item,
                     };
 
-                    // Retrieve the trait refs
-                    let trait_refs = bt_ctx.translate_trait_refs_from_impl_trait_item(
-                        rust_id,
-                        &rust_implemented_trait_ref,
-                        item,
-                    )?;
-
-                    types.push((name, (trait_refs, ty)));
+                    let (generics, (trait_refs, ty)) =
+                        bt_ctx.with_assoc_item_generics(def_item, |bt_ctx| {
+                            let ty = bt_ctx.translate_ty_from_trait_item(def_item)?;
+
+                            // Retrieve the trait refs
+                            let trait_refs = bt_ctx.translate_trait_refs_from_impl_trait_item(
+                                rust_id,
+                                &rust_implemented_trait_ref,
+                                item,
+                                def_item,
+                            )?;
+                            Ok((trait_refs, ty))
+                        })?;
+
+                    types.push((name, (generics, trait_refs, ty)));
                 }
             }
         }
@@ -648,7 +818,10 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
             def_id,
             is_local: rust_id.is_local(),
             name,
-            item_meta: bt_ctx.t_ctx.translate_item_meta_from_rid(rust_id),
+            item_meta: bt_ctx.t_ctx.translate_item_meta_from_rid(rust_id)?,
+            impl_kind: bt_ctx.t_ctx.translate_trait_impl_kind(rust_id),
+            is_negative,
+            is_const,
             impl_trait: implemented_trait,
             generics: bt_ctx.get_generics(),
             preds: bt_ctx.get_predicates(),
@@ -657,8 +830,9 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
             types,
             required_methods,
             provided_methods,
+            default_items,
         };
-        self.trait_impls.insert(def_id, trait_impl);
+        self.translated.trait_impls.insert(def_id, trait_impl);
 
         Ok(())
     }
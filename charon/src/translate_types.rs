@@ -2,12 +2,16 @@ use crate::assumed;
 use crate::common::*;
 use crate::formatter::IntoFormatter;
 use crate::gast::*;
+use crate::symbol::Symbol;
 use crate::translate_ctx::*;
 use crate::types::*;
+use crate::values::ScalarValue;
 use core::convert::*;
 use hax_frontend_exporter as hax;
 use hax_frontend_exporter::SInto;
 use rustc_hir::def_id::DefId;
+use rustc_middle::ty::util::IntTypeExt;
+use std::collections::HashMap;
 
 /// Small helper: we ignore some region names (when they are equal to "'_")
 fn check_region_name(s: Option<String>) -> Option<String> {
@@ -48,6 +52,95 @@ pub fn translate_region_name(region: &hax::Region) -> Option<String> {
     check_region_name(s)
 }
 
+/// Returns `true` if the projection is a projection on a generic associated type.
+fn is_gat_projection<'tcx>(
+    tcx: rustc_middle::ty::TyCtxt<'tcx>,
+    alias_ty: &rustc_middle::ty::AliasTy<'tcx>,
+) -> bool {
+    !tcx.generics_of(alias_ty.def_id).params.is_empty()
+}
+
+/// Returns `true` if we can't translate the alias from the hax type (see
+/// [BodyTransCtx::fold_aliases]).
+fn is_folded_alias<'tcx>(
+    tcx: rustc_middle::ty::TyCtxt<'tcx>,
+    kind: rustc_middle::ty::AliasKind,
+    alias_ty: &rustc_middle::ty::AliasTy<'tcx>,
+) -> bool {
+    use rustc_middle::ty::AliasKind;
+    match kind {
+        AliasKind::Projection => is_gat_projection(tcx, alias_ty),
+        AliasKind::Opaque => true,
+        _ => false,
+    }
+}
+
+/// Returns `true` if the type contains aliases or function items that we
+/// can't translate from the hax type (see [BodyTransCtx::fold_aliases]).
+pub(crate) fn has_folded_aliases<'tcx>(
+    tcx: rustc_middle::ty::TyCtxt<'tcx>,
+    ty: rustc_middle::ty::Ty<'tcx>,
+) -> bool {
+    use rustc_middle::ty::subst::GenericArgKind;
+    use rustc_middle::ty::TyKind;
+    ty.walk().any(|arg| match arg.unpack() {
+        GenericArgKind::Type(ty) => match ty.kind() {
+            TyKind::Alias(kind, alias_ty) => is_folded_alias(tcx, *kind, alias_ty),
+            TyKind::FnDef(..) => true,
+            _ => false,
+        },
+        _ => false,
+    })
+}
+
+/// Replaces the aliases and the function items that we can't translate from
+/// the hax types with type parameters (see [BodyTransCtx::fold_aliases]). We number those parameters
+/// downwards from [u32::MAX], so that they don't clash with the parameters
+/// of the definitions.
+struct AliasesFolder<'a, 'tcx> {
+    tcx: rustc_middle::ty::TyCtxt<'tcx>,
+    aliases: &'a mut HashMap<u32, rustc_middle::ty::Ty<'tcx>>,
+}
+
+impl<'a, 'tcx> AliasesFolder<'a, 'tcx> {
+    fn register(&mut self, ty: rustc_middle::ty::Ty<'tcx>) -> rustc_middle::ty::Ty<'tcx> {
+        let index = u32::MAX - self.aliases.len() as u32;
+        self.aliases.insert(index, ty);
+        let name = rustc_span::symbol::Symbol::intern(&format!("@Alias{}", self.aliases.len()));
+        self.tcx.mk_ty_param(index, name)
+    }
+}
+
+impl<'a, 'tcx> rustc_middle::ty::TypeFolder<rustc_middle::ty::TyCtxt<'tcx>>
+    for AliasesFolder<'a, 'tcx>
+{
+    fn interner(&self) -> rustc_middle::ty::TyCtxt<'tcx> {
+        self.tcx
+    }
+
+    fn fold_ty(&mut self, ty: rustc_middle::ty::Ty<'tcx>) -> rustc_middle::ty::Ty<'tcx> {
+        use rustc_middle::ty::{AliasKind, TyKind, TypeSuperFoldable};
+        // We translate the opaque types from their arguments *and* the
+        // bounds of their definitions, and the function items from their
+        // arguments *and* the predicates of the functions: we don't fold their
+        // arguments
+        if let TyKind::Alias(AliasKind::Opaque, _) | TyKind::FnDef(..) = ty.kind() {
+            return self.register(ty);
+        }
+        // Fold the arguments first, so that the projections we register
+        // don't contain projections on generic associated types
+        let ty = ty.super_fold_with(self);
+        match ty.kind() {
+            TyKind::Alias(AliasKind::Projection, alias_ty)
+                if is_gat_projection(self.tcx, alias_ty) =>
+            {
+                self.register(ty)
+            }
+            _ => ty,
+        }
+    }
+}
+
 impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
     // Translate a region
     pub(crate) fn translate_region(
@@ -76,6 +169,20 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                     Ok(Region::BVar(br_id, *rid))
                 }
                 hax::RegionKind::ReVar(re_var) => {
+                    // If we are translating a body without erasing the regions,
+                    // this is a region variable of the borrow checker.
+                    if let Some(r) = self.body_regions_map.get(&re_var.index) {
+                        return Ok(match r {
+                            // Note that the DeBruijn index depends on the current
+                            // stack of bound region groups.
+                            Region::BVar(_, rid) => {
+                                let db_id = self.region_vars.len() - 1;
+                                Region::BVar(DeBruijnId::new(db_id), *rid)
+                            }
+                            _ => *r,
+                        });
+                    }
+
                     // TODO: I'm really not sure how to handle those, here.
                     // They sometimes appear and seem to refer to the early bound
                     // regions. But on the other hand, whenever I investigated, I
@@ -157,6 +264,329 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
         }
     }
 
+    /// Compute the arguments of an associated type, in case it is a generic
+    /// associated type (see [TyKind::TraitType]).
+    ///
+    /// Hax only gives us the arguments of the *trait* (in the trait reference)
+    /// when projecting an associated type: the caller has to retrieve the own
+    /// arguments of the projections on the generic associated types from the
+    /// rustc types (see [Self::fold_aliases]).
+    pub(crate) fn translate_assoc_type_args(
+        &mut self,
+        span: rustc_span::Span,
+        erase_regions: bool,
+        trait_ref: &hax::TraitRef,
+        name: &str,
+        own_args: Option<&[hax::GenericArg]>,
+    ) -> Result<GenericArgs, Error> {
+        let tcx = self.t_ctx.tcx;
+        let trait_id = DefId::from(&trait_ref.def_id);
+        let item = tcx.associated_items(trait_id).find_by_name_and_kind(
+            tcx,
+            rustc_span::symbol::Ident::from_str(name),
+            rustc_middle::ty::AssocKind::Type,
+            trait_id,
+        );
+        let is_generic = item.is_some_and(|item| !tcx.generics_of(item.def_id).params.is_empty());
+        match own_args {
+            Some(own_args) if is_generic => {
+                let (regions, types, const_generics) =
+                    self.translate_substs(span, erase_regions, None, own_args)?;
+                Ok(GenericArgs::new(regions, types, const_generics, Vec::new()))
+            }
+            None if is_generic => {
+                error_or_panic!(
+                    self,
+                    span,
+                    format!(
+                        "The arguments of the projection on the generic associated type {name} are not available in this position"
+                    )
+                )
+            }
+            _ => Ok(GenericArgs::empty()),
+        }
+    }
+
+    /// Replace the aliases that we can't translate from the hax types with
+    /// fresh type parameters, that we register in [Self::folded_aliases]:
+    /// - hax only gives us the trait reference and the name of a projection:
+    ///   we lose the own arguments of the generic associated types
+    /// - hax doesn't give us the identifiers of the opaque types (`impl Trait`)
+    /// - hax translates the types of the function items to arrow types: we
+    ///   lose the functions and their generic arguments (see [TyKind::FnDef])
+    ///
+    /// We translate the folded value with hax, then translate the type
+    /// parameters which stand for the aliases (in the [hax::Ty::Param] case of
+    /// [Self::translate_ty]) by using the rustc types.
+    pub(crate) fn fold_aliases<T>(&mut self, value: T) -> T
+    where
+        T: rustc_middle::ty::TypeFoldable<rustc_middle::ty::TyCtxt<'tcx>>,
+    {
+        let mut folder = AliasesFolder {
+            tcx: self.t_ctx.tcx,
+            aliases: &mut self.folded_aliases,
+        };
+        value.fold_with(&mut folder)
+    }
+
+    /// Translate an alias or a function item which was replaced by a type
+    /// parameter (see [Self::fold_aliases]).
+    fn translate_folded_alias(
+        &mut self,
+        span: rustc_span::Span,
+        erase_regions: bool,
+        ty: rustc_middle::ty::Ty<'tcx>,
+    ) -> Result<Ty, Error> {
+        use rustc_middle::ty::{AliasKind, TyKind};
+        let tcx = self.t_ctx.tcx;
+        match ty.kind() {
+            TyKind::Alias(AliasKind::Opaque, alias_ty) => {
+                self.translate_opaque_ty(span, erase_regions, alias_ty)
+            }
+            TyKind::Alias(AliasKind::Projection, alias_ty) => {
+                let hax::Ty::Alias(hax::AliasKind::Projection { impl_expr, name }) =
+                    ty.sinto(&self.hax_state)
+                else {
+                    unreachable!()
+                };
+                let parent_count = tcx.generics_of(alias_ty.def_id).parent_count;
+                let own_args: Vec<hax::GenericArg> =
+                    alias_ty.substs[parent_count..].sinto(&self.hax_state);
+                self.translate_projection_ty(
+                    span,
+                    erase_regions,
+                    &impl_expr,
+                    &name,
+                    Some(&own_args),
+                )
+            }
+            TyKind::FnDef(def_id, substs) => {
+                match self.translate_fn_def_ty(span, erase_regions, *def_id, substs)? {
+                    Some(ty) => Ok(ty),
+                    // We keep the arrow type
+                    None => {
+                        let ty: hax::Ty = ty.sinto(&self.hax_state);
+                        self.translate_ty(span, erase_regions, &ty)
+                    }
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    /// Translate a projection on an associated type.
+    fn translate_projection_ty(
+        &mut self,
+        span: rustc_span::Span,
+        erase_regions: bool,
+        impl_expr: &hax::ImplExpr,
+        name: &str,
+        own_args: Option<&[hax::GenericArg]>,
+    ) -> Result<Ty, Error> {
+        let trait_ref = self.translate_trait_impl_expr(span, erase_regions, impl_expr)?;
+        // This should succeed because no marker trait (that we may
+        // ignore) has associated types.
+        let trait_ref = trait_ref.unwrap();
+        let generics = self.translate_assoc_type_args(
+            span,
+            erase_regions,
+            &impl_expr.r#trait,
+            name,
+            own_args,
+        )?;
+        let name = TraitItemName(name.to_string());
+        Ok(TyKind::TraitType(trait_ref, name, generics).into_ty())
+    }
+
+    /// Translate an opaque type introduced by an `impl Trait` (see
+    /// [TypeDeclKind::ImplTrait]).
+    ///
+    /// hax doesn't give us the identifier of the opaque types: we have to work
+    /// with the rustc type.
+    pub(crate) fn translate_opaque_ty(
+        &mut self,
+        span: rustc_span::Span,
+        erase_regions: bool,
+        alias_ty: &rustc_middle::ty::AliasTy<'tcx>,
+    ) -> Result<Ty, Error> {
+        let tcx = self.t_ctx.tcx;
+        let id = self.translate_type_decl_id(span, alias_ty.def_id);
+        let substs: Vec<hax::GenericArg> = alias_ty.substs.sinto(&self.hax_state);
+        let trait_refs = hax::solve_item_traits(
+            &self.hax_state,
+            tcx.param_env(self.def_id),
+            alias_ty.def_id,
+            alias_ty.substs,
+            None,
+        );
+        let generics =
+            self.translate_substs_and_trait_refs(span, erase_regions, None, &substs, &trait_refs)?;
+        Ok(TyKind::Adt(TypeId::Adt(id), generics).into_ty())
+    }
+
+    /// Translate a type for which we have the rustc version at hand. This
+    /// allows us to translate the `impl Trait` types, the projections on
+    /// generic associated types and the types of the function items (see
+    /// [fold_aliases]).
+    pub(crate) fn translate_ty_or_opaque(
+        &mut self,
+        span: rustc_span::Span,
+        erase_regions: bool,
+        rust_ty: rustc_middle::ty::Ty<'tcx>,
+        ty: &hax::Ty,
+    ) -> Result<Ty, Error> {
+        if has_folded_aliases(self.t_ctx.tcx, rust_ty) {
+            self.translate_rust_ty(span, erase_regions, rust_ty)
+        } else {
+            self.translate_ty(span, erase_regions, ty)
+        }
+    }
+
+    /// Translate a rustc type, by folding the aliases and the function items
+    /// that we can't translate from the hax type (see [fold_aliases]).
+    fn translate_rust_ty(
+        &mut self,
+        span: rustc_span::Span,
+        erase_regions: bool,
+        rust_ty: rustc_middle::ty::Ty<'tcx>,
+    ) -> Result<Ty, Error> {
+        let ty: hax::Ty = self.fold_aliases(rust_ty).sinto(&self.hax_state);
+        self.translate_ty(span, erase_regions, &ty)
+    }
+
+    /// Translate the type of a function item, given by the function and its
+    /// generic arguments (see [TyKind::FnDef]). The trait methods and the
+    /// assumed functions don't have a [FunDeclId]: we return `None` for them,
+    /// and the caller keeps the arrow type that hax gives us.
+    fn translate_fn_def_ty(
+        &mut self,
+        span: rustc_span::Span,
+        erase_regions: bool,
+        def_id: DefId,
+        substs: rustc_middle::ty::SubstsRef<'tcx>,
+    ) -> Result<Option<Ty>, Error> {
+        let tcx = self.t_ctx.tcx;
+        if tcx.trait_of_item(def_id).is_some() {
+            return Ok(None);
+        }
+        if !def_id.is_local() {
+            let name = self.t_ctx.def_id_to_name(def_id)?;
+            let lang_item = self.t_ctx.translate_lang_item_from_rid(def_id);
+            let lang_item = lang_item.as_deref();
+            if assumed::is_panic_fun(&name, lang_item)
+                || assumed::function_to_info(&name, lang_item).is_some()
+            {
+                return Ok(None);
+            }
+        }
+        let id = self.translate_fun_decl_id(span, def_id);
+        let hax_substs: Vec<hax::GenericArg> = self.fold_aliases(substs).sinto(&self.hax_state);
+        let trait_refs = hax::solve_item_traits(
+            &self.hax_state,
+            tcx.param_env(self.def_id),
+            def_id,
+            substs,
+            None,
+        );
+        let mut generics = self.translate_substs_and_trait_refs(
+            span,
+            erase_regions,
+            None,
+            &hax_substs,
+            &trait_refs,
+        )?;
+        self.translate_fn_def_args(span, erase_regions, def_id, substs, &mut generics)?;
+        Ok(Some(TyKind::FnDef(id, generics).into_ty()))
+    }
+
+    /// Hax translates the types of the function items to arrow types (see
+    /// [Self::fold_aliases]): given the generic arguments `generics` of the
+    /// item `def_id`, which we translated from the hax arguments, retranslate
+    /// the types from the rustc arguments `substs`, and give the trait
+    /// references to the function items their types (see
+    /// [TraitInstanceId::FnPointer]).
+    pub(crate) fn translate_fn_def_args(
+        &mut self,
+        span: rustc_span::Span,
+        erase_regions: bool,
+        def_id: DefId,
+        substs: rustc_middle::ty::SubstsRef<'tcx>,
+        generics: &mut GenericArgs,
+    ) -> Result<(), Error> {
+        use rustc_middle::ty::{Clause, PredicateKind};
+        let tcx = self.t_ctx.tcx;
+
+        // The assumed functions ignore some of their type parameters: we can't
+        // match their arguments
+        let rust_tys: Vec<_> = substs.types().collect();
+        if rust_tys.len() != generics.types.len() {
+            return Ok(());
+        }
+        let mut types = generics.types.clone();
+        for (ty, rust_ty) in types.iter_mut().zip(rust_tys) {
+            if has_folded_aliases(tcx, rust_ty) {
+                *ty = self.translate_rust_ty(span, erase_regions, rust_ty)?;
+            }
+        }
+
+        // The trait references are given in the order of the trait clauses of
+        // the item, starting with the clauses of its parent (see
+        // [Self::translate_predicates_of])
+        let mut trait_refs = generics.trait_refs.clone();
+        if trait_refs
+            .iter()
+            .any(|tr| matches!(tr.trait_id, TraitInstanceId::FnPointer(_)))
+        {
+            let preds = tcx.predicates_of(def_id).instantiate(tcx, substs);
+            let mut clauses = Vec::new();
+            for pred in preds.predicates {
+                if let PredicateKind::Clause(Clause::Trait(tr)) = pred.kind().skip_binder()
+                    && let Some(trait_id) = self.translate_trait_decl_id(span, tr.def_id())?
+                {
+                    clauses.push((trait_id, tr.self_ty()));
+                }
+            }
+            if clauses.len() == trait_refs.len() {
+                for (tr, (trait_id, self_ty)) in trait_refs.iter_mut().zip(clauses) {
+                    if tr.trait_decl_ref.trait_id == trait_id {
+                        self.translate_fn_pointer_trait_ref(span, erase_regions, self_ty, tr)?;
+                    }
+                }
+            }
+        }
+
+        generics.with_data_mut(|generics| {
+            generics.types = types;
+            generics.trait_refs = trait_refs;
+        });
+        Ok(())
+    }
+
+    /// Give its type to the function item that a trait reference uses as an
+    /// instance of `Fn`, `FnMut` or `FnOnce` (see [TraitInstanceId::FnPointer]):
+    /// `self_ty` is the rustc version of the self type of the trait reference.
+    pub(crate) fn translate_fn_pointer_trait_ref(
+        &mut self,
+        span: rustc_span::Span,
+        erase_regions: bool,
+        self_ty: rustc_middle::ty::Ty<'tcx>,
+        trait_ref: &mut TraitRef,
+    ) -> Result<(), Error> {
+        if let TraitInstanceId::FnPointer(_) = &trait_ref.trait_id
+            && let rustc_middle::ty::TyKind::FnDef(def_id, substs) = self_ty.kind()
+            && let Some(ty) = self.translate_fn_def_ty(span, erase_regions, *def_id, substs)?
+        {
+            trait_ref.with_data_mut(|trait_ref| {
+                trait_ref.trait_id = TraitInstanceId::FnPointer(Box::new(ty.clone()));
+                trait_ref
+                    .trait_decl_ref
+                    .generics
+                    .with_data_mut(|generics| generics.types[0] = ty);
+            });
+        }
+        Ok(())
+    }
+
     /// Translate a Ty.
     ///
     /// Typically used in this module to translate the fields of a structure/
@@ -173,29 +603,32 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
     ) -> Result<Ty, Error> {
         trace!("{:?}", ty);
         match ty {
-            hax::Ty::Bool => Ok(Ty::Literal(LiteralTy::Bool)),
-            hax::Ty::Char => Ok(Ty::Literal(LiteralTy::Char)),
-            hax::Ty::Int(int_ty) => Ok(Ty::Literal(LiteralTy::Integer(
+            hax::Ty::Bool => Ok(TyKind::Literal(LiteralTy::Bool).into_ty()),
+            hax::Ty::Char => Ok(TyKind::Literal(LiteralTy::Char).into_ty()),
+            hax::Ty::Int(int_ty) => Ok(TyKind::Literal(LiteralTy::Integer(
                 IntegerTy::rust_int_ty_to_integer_ty(*int_ty),
-            ))),
-            hax::Ty::Uint(int_ty) => Ok(Ty::Literal(LiteralTy::Integer(
+            ))
+            .into_ty()),
+            hax::Ty::Uint(int_ty) => Ok(TyKind::Literal(LiteralTy::Integer(
                 IntegerTy::rust_uint_ty_to_integer_ty(*int_ty),
-            ))),
+            ))
+            .into_ty()),
             hax::Ty::Float(_) => {
                 trace!("Float");
                 error_or_panic!(self, span, "Floats are not supported yet")
             }
-            hax::Ty::Never => Ok(Ty::Never),
+            hax::Ty::Never => Ok(TyKind::Never.into_ty()),
 
             hax::Ty::Alias(alias_kind) => match alias_kind {
                 hax::AliasKind::Projection { impl_expr, name } => {
-                    let trait_ref =
-                        self.translate_trait_impl_expr(span, erase_regions, impl_expr)?;
-                    // This should succeed because no marker trait (that we may
-                    // ignore) has associated types.
-                    let trait_ref = trait_ref.unwrap();
-                    let name = TraitItemName(name.clone());
-                    Ok(Ty::TraitType(trait_ref, name))
+                    self.translate_projection_ty(span, erase_regions, impl_expr, name, None)
+                }
+                hax::AliasKind::Opaque { .. } => {
+                    error_or_panic!(
+                        self,
+                        span,
+                        "`impl Trait` types are only supported in function signatures and local variables"
+                    )
                 }
                 _ => {
                     error_or_panic!(self, span, format!("Unimplemented: {:?}", ty))
@@ -214,8 +647,9 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                 let used_params = if adt_did.is_local() {
                     Option::None
                 } else {
-                    let name = self.t_ctx.def_id_to_name(DefId::from(def_id))?;
-                    assumed::type_to_used_params(&name)
+                    let name = self.t_ctx.def_id_to_name(adt_did)?;
+                    let lang_item = self.t_ctx.translate_lang_item_from_rid(adt_did);
+                    assumed::type_to_used_params(&name, lang_item.as_deref())
                 };
 
                 // Translate the type parameters instantiation
@@ -231,13 +665,13 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                 let def_id = self.translate_type_id(span, def_id)?;
 
                 // Return the instantiated ADT
-                Ok(Ty::Adt(def_id, generics))
+                Ok(TyKind::Adt(def_id, generics).into_ty())
             }
             hax::Ty::Str => {
                 trace!("Str");
 
                 let id = TypeId::Assumed(AssumedTy::Str);
-                Ok(Ty::Adt(id, GenericArgs::empty()))
+                Ok(TyKind::Adt(id, GenericArgs::empty()).into_ty())
             }
             hax::Ty::Array(ty, const_param) => {
                 trace!("Array");
@@ -246,17 +680,14 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                 let tys = vec![self.translate_ty(span, erase_regions, ty)?];
                 let cgs = vec![c];
                 let id = TypeId::Assumed(AssumedTy::Array);
-                Ok(Ty::Adt(
-                    id,
-                    GenericArgs::new(Vec::new(), tys, cgs, Vec::new()),
-                ))
+                Ok(TyKind::Adt(id, GenericArgs::new(Vec::new(), tys, cgs, Vec::new())).into_ty())
             }
             hax::Ty::Slice(ty) => {
                 trace!("Slice");
 
                 let tys = vec![self.translate_ty(span, erase_regions, ty)?];
                 let id = TypeId::Assumed(AssumedTy::Slice);
-                Ok(Ty::Adt(id, GenericArgs::new_from_types(tys)))
+                Ok(TyKind::Adt(id, GenericArgs::new_from_types(tys)).into_ty())
             }
             hax::Ty::Ref(region, ty, mutability) => {
                 trace!("Ref");
@@ -268,7 +699,7 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                 } else {
                     RefKind::Shared
                 };
-                Ok(Ty::Ref(region, Box::new(ty), kind))
+                Ok(TyKind::Ref(region, ty, kind).into_ty())
             }
             hax::Ty::RawPtr(ty_and_mut) => {
                 trace!("RawPtr: {:?}", ty_and_mut);
@@ -278,7 +709,7 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                 } else {
                     RefKind::Shared
                 };
-                Ok(Ty::RawPtr(Box::new(ty), kind))
+                Ok(TyKind::RawPtr(ty, kind).into_ty())
             }
             hax::Ty::Tuple(substs) => {
                 trace!("Tuple");
@@ -289,7 +720,7 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                     params.push(param_ty);
                 }
 
-                Ok(Ty::Adt(TypeId::Tuple, GenericArgs::new_from_types(params)))
+                Ok(TyKind::Adt(TypeId::Tuple, GenericArgs::new_from_types(params)).into_ty())
             }
 
             hax::Ty::Param(param) => {
@@ -302,6 +733,12 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                 // parameter.
                 trace!("Param");
 
+                // The parameter may stand for an alias that we couldn't
+                // translate from the hax type (see [Self::fold_aliases])
+                if let Some(ty) = self.folded_aliases.get(&param.index).copied() {
+                    return self.translate_folded_alias(span, erase_regions, ty);
+                }
+
                 // Retrieve the translation of the substituted type:
                 match self.type_vars_map.get(&param.index) {
                     None => error_or_panic!(
@@ -312,7 +749,7 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                             param.name, param.index
                         )
                     ),
-                    Some(var_id) => Ok(Ty::TypeVar(var_id)),
+                    Some(var_id) => Ok(TyKind::TypeVar(var_id).into_ty()),
                 }
             }
 
@@ -353,30 +790,8 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
 
                 // Translate the generics parameters.
                 // Note that there can only be bound regions.
-                let bound_region_names: Vec<Option<String>> = sig
-                    .bound_vars
-                    .iter()
-                    .map(|p| {
-                        use hax::BoundVariableKind::*;
-                        match p {
-                            Region(region) => Ok(translate_bound_region_kind_name(region)),
-                            Ty(_) => {
-                                error_or_panic!(
-                                    self,
-                                    span,
-                                    "Unexpected locally bound type variable"
-                                );
-                            }
-                            Const => {
-                                error_or_panic!(
-                                    self,
-                                    span,
-                                    "Unexpected locally bound const generic variable"
-                                );
-                            }
-                        }
-                    })
-                    .try_collect()?;
+                let bound_region_names =
+                    self.translate_bound_region_names(span, &sig.bound_vars)?;
 
                 // Push the ground region group
                 let erase_regions = false;
@@ -389,7 +804,7 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                         .map(|x| ctx.translate_ty(span, erase_regions, x))
                         .try_collect()?;
                     let output = ctx.translate_ty(span, erase_regions, &sig.value.output)?;
-                    Ok(Ty::Arrow(regions, inputs, Box::new(output)))
+                    Ok(TyKind::Arrow(regions, inputs, output).into_ty())
                 })
             }
             hax::Ty::Error => {
@@ -403,6 +818,34 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
         }
     }
 
+    /// Translate the names of the variables introduced by a binder (for arrow
+    /// types or `for<'a> ...` clauses). Note that there can only be bound regions.
+    pub(crate) fn translate_bound_region_names(
+        &mut self,
+        span: rustc_span::Span,
+        bound_vars: &[hax::BoundVariableKind],
+    ) -> Result<Vec<Option<String>>, Error> {
+        bound_vars
+            .iter()
+            .map(|p| {
+                use hax::BoundVariableKind::*;
+                match p {
+                    Region(region) => Ok(translate_bound_region_kind_name(region)),
+                    Ty(_) => {
+                        error_or_panic!(self, span, "Unexpected locally bound type variable");
+                    }
+                    Const => {
+                        error_or_panic!(
+                            self,
+                            span,
+                            "Unexpected locally bound const generic variable"
+                        );
+                    }
+                }
+            })
+            .try_collect()
+    }
+
     #[allow(clippy::type_complexity)]
     pub fn translate_substs(
         &mut self,
@@ -457,12 +900,7 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
         let (regions, types, const_generics) =
             self.translate_substs(span, erase_regions, used_params, substs)?;
         let trait_refs = self.translate_trait_impl_exprs(span, erase_regions, trait_refs)?;
-        Ok(GenericArgs {
-            regions,
-            types,
-            const_generics,
-            trait_refs,
-        })
+        Ok(GenericArgs::new(regions, types, const_generics, trait_refs))
     }
 
     /// Translate a type def id
@@ -480,7 +918,8 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
 
             // Retrieve the type name
             let name = self.t_ctx.hax_def_id_to_name(def_id)?;
-            match assumed::get_type_id_from_name(&name) {
+            let lang_item = self.t_ctx.translate_lang_item_from_rid(rust_id);
+            match assumed::get_type_id_from_name(&name, lang_item.as_deref()) {
                 Option::Some(id) => {
                     // The type has primitive support
                     Ok(TypeId::Assumed(id))
@@ -493,6 +932,57 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
         }
     }
 
+    /// If a type was written as a type alias (like `Result<u32>`, with
+    /// `type Result<T> = std::result::Result<T, MyError>`), give the alias and
+    /// its arguments (see [TypeAliasRef]). We only look at the top of the type,
+    /// and only at the local aliases.
+    pub(crate) fn translate_type_alias_ref(
+        &mut self,
+        hir_ty: &rustc_hir::Ty<'_>,
+    ) -> Result<Option<TypeAliasRef>, Error> {
+        use rustc_hir::def::{DefKind, Res};
+        if !self.t_ctx.translate_type_aliases {
+            return Ok(None);
+        }
+        let rustc_hir::TyKind::Path(rustc_hir::QPath::Resolved(None, path)) = &hir_ty.kind else {
+            return Ok(None);
+        };
+        let Res::Def(DefKind::TyAlias { .. }, alias_id) = path.res else {
+            return Ok(None);
+        };
+        if !alias_id.is_local() {
+            return Ok(None);
+        }
+
+        let tcx = self.t_ctx.tcx;
+        let erase_regions = true;
+        let mut regions = Vec::new();
+        let mut types = Vec::new();
+        if let Some(args) = path.segments.last().unwrap().args {
+            for arg in args.args {
+                match arg {
+                    rustc_hir::GenericArg::Lifetime(_) => regions.push(Region::Erased),
+                    rustc_hir::GenericArg::Type(ty) => {
+                        let ty = rustc_hir_analysis::hir_ty_to_ty(tcx, ty);
+                        let ty = self.translate_ty(
+                            hir_ty.span,
+                            erase_regions,
+                            &ty.sinto(&self.hax_state),
+                        )?;
+                        types.push(ty);
+                    }
+                    // We don't record the aliases with const arguments
+                    rustc_hir::GenericArg::Const(_) | rustc_hir::GenericArg::Infer(_) => {
+                        return Ok(None);
+                    }
+                }
+            }
+        }
+        let alias_id = self.t_ctx.register_type_alias_id(alias_id);
+        let generics = GenericArgs::new(regions, types, Vec::new(), Vec::new());
+        Ok(Some(TypeAliasRef { alias_id, generics }))
+    }
+
     /// Translate the body of a type declaration.
     ///
     /// Note that the type may be external, in which case we translate the body
@@ -514,8 +1004,8 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
         // transparent (i.e., extract its body). If it is an enumeration, then yes
         // (because the variants of public enumerations are public, together with their
         // fields). If it is a structure, we check if all the fields are public.
-        let is_transparent = self.t_ctx.extract_opaque_bodies
-            || is_local
+        let is_transparent = is_local
+            || self.t_ctx.extract_external_body(rust_id)
             || match adt.adt_kind() {
                 AdtKind::Enum => true,
                 AdtKind::Struct => {
@@ -535,30 +1025,77 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
             return Ok(TypeDeclKind::Opaque);
         }
 
+        // The type of the discriminant: `isize` by default, or the type given
+        // by the `#[repr(...)]` attribute
+        let discr_ty = adt.repr().discr_type().to_ty(self.t_ctx.tcx);
+        let discr_ty = self.translate_ty(def_span, true, &discr_ty.sinto(&self.hax_state))?;
+        let discr_ty = match discr_ty.kind() {
+            TyKind::Literal(LiteralTy::Integer(int_ty)) => *int_ty,
+            _ => error_or_panic!(
+                self,
+                def_span,
+                "The discriminant type should be an integer type"
+            ),
+        };
+
         // The type is transparent: explore the variants
+        let tcx = self.t_ctx.tcx;
+        let param_env = tcx.param_env(rust_id);
+        let substs = rustc_middle::ty::subst::InternalSubsts::identity_for_item(tcx, rust_id);
         let mut variants: VariantId::Vector<Variant> = Default::default();
         let erase_regions = false;
-        for (i, (rust_var_id, var_def)) in adt.variants().iter_enumerated().enumerate() {
-            let var_def: hax::VariantDef = var_def.sinto(&self.hax_state);
+        for (i, (rust_var_id, rust_var_def)) in adt.variants().iter_enumerated().enumerate() {
+            let var_def: hax::VariantDef = rust_var_def.sinto(&self.hax_state);
             trace!("variant {i}: {var_def:?}");
 
+            // The discriminant is given by its bit representation: we
+            // reinterpret it with the proper type (this takes care of the sign)
             let discriminant: u128 = if adt.is_enum() {
                 adt.discriminant_for_variant(self.t_ctx.tcx, rust_var_id)
                     .val
             } else {
                 0
             };
+            let discriminant = ScalarValue::from_le_bytes(discr_ty, discriminant.to_le_bytes());
 
             let mut fields: FieldId::Vector<Field> = Default::default();
             /* This is for sanity: check that either all the fields have names, or
              * none of them has */
             let mut have_names: Option<bool> = Option::None;
-            for (j, field_def) in var_def.fields.into_iter().enumerate() {
+            for (j, (field_def, rust_field_def)) in var_def
+                .fields
+                .into_iter()
+                .zip(rust_var_def.fields.iter())
+                .enumerate()
+            {
                 trace!("variant {i}: field {j}: {field_def:?}");
                 let field_span = field_def.span.rust_span_data.unwrap().span();
 
-                // Translate the field type
-                let ty = self.translate_ty(field_span, erase_regions, &field_def.ty)?;
+                // Translate the field type (we need the rustc type for the
+                // projections on generic associated types)
+                let rust_ty = rust_field_def.ty(tcx, substs);
+                let ty =
+                    self.translate_ty_or_opaque(field_span, erase_regions, rust_ty, &field_def.ty)?;
+
+                // Check if the field is zero-sized: we can't compute the
+                // layout if it depends on the type parameters
+                let rust_ty = tcx.erase_regions(rust_ty);
+                let is_phantom_data = rust_ty.is_phantom_data();
+                let is_zst = is_phantom_data
+                    || tcx
+                        .layout_of(param_env.and(rust_ty))
+                        .is_ok_and(|layout| layout.is_zst());
+
+                // Retrieve the type alias the user wrote, if the field is local
+                let alias = match rust_field_def.did.as_local() {
+                    Some(local_id) => match tcx.hir().get_by_def_id(local_id) {
+                        rustc_hir::Node::Field(hir_field) => {
+                            self.translate_type_alias_ref(hir_field.ty)?
+                        }
+                        _ => None,
+                    },
+                    None => None,
+                };
 
                 // Retrieve the field name.
                 let field_name = field_def.name;
@@ -581,8 +1118,11 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                 // Store the field
                 let field = Field {
                     meta,
-                    name: field_name.clone(),
+                    name: field_name.as_deref().map(Symbol::intern),
                     ty,
+                    is_zst,
+                    is_phantom_data,
+                    alias,
                 };
                 fields.push(field);
             }
@@ -591,7 +1131,7 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
             let variant_name = var_def.name;
             variants.push(Variant {
                 meta,
-                name: variant_name,
+                name: Symbol::intern(&variant_name),
                 fields,
                 discriminant,
             });
@@ -600,7 +1140,10 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
         // Register the type
         let type_def_kind: TypeDeclKind = match adt.adt_kind() {
             AdtKind::Struct => TypeDeclKind::Struct(variants[0].fields.clone()),
-            AdtKind::Enum => TypeDeclKind::Enum(variants),
+            AdtKind::Enum => {
+                let layout = self.translate_enum_layout(rust_id);
+                TypeDeclKind::Enum(variants, discr_ty, layout)
+            }
             AdtKind::Union => {
                 error_or_panic!(self, def_span, "Union types are not supported")
             }
@@ -609,6 +1152,167 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
         Ok(type_def_kind)
     }
 
+    /// Compute the layout of an enumeration (see [EnumLayout]). Returns `None`
+    /// if the layout depends on the type parameters, as is the case for
+    /// `Option<T>` for instance.
+    fn translate_enum_layout(&self, rust_id: DefId) -> Option<EnumLayout> {
+        use rustc_target::abi::{TagEncoding, Variants};
+        let tcx = self.t_ctx.tcx;
+        let param_env = tcx.param_env(rust_id);
+        let ty = tcx.erase_regions(tcx.type_of(rust_id).subst_identity());
+        let layout = tcx.layout_of(param_env.and(ty)).ok()?;
+        match &layout.variants {
+            Variants::Single { .. } => Some(EnumLayout::Single),
+            Variants::Multiple {
+                tag_encoding: TagEncoding::Direct,
+                ..
+            } => Some(EnumLayout::Tagged),
+            Variants::Multiple {
+                tag_encoding:
+                    TagEncoding::Niche {
+                        untagged_variant, ..
+                    },
+                tag_field,
+                variants,
+                ..
+            } => {
+                // The niche is the tag of the enumeration: we look for the
+                // field of the untagged variant which contains it
+                let rustc_middle::ty::Adt(adt, substs) = ty.kind() else {
+                    unreachable!()
+                };
+                let tag_offset = layout.fields.offset(*tag_field);
+                let variant_layout = &variants[*untagged_variant];
+                let (field, _) = adt
+                    .variant(*untagged_variant)
+                    .fields
+                    .iter()
+                    .enumerate()
+                    .find(|(i, field)| {
+                        let offset = variant_layout.fields.offset(*i);
+                        let field_ty = field.ty(tcx, substs);
+                        tcx.layout_of(param_env.and(field_ty))
+                            .is_ok_and(|field_layout| {
+                                offset <= tag_offset && tag_offset < offset + field_layout.size
+                            })
+                    })?;
+                Some(EnumLayout::Niche {
+                    untagged_variant: VariantId::Id::new(untagged_variant.as_usize()),
+                    field: FieldId::Id::new(field),
+                })
+            }
+        }
+    }
+
+    /// Translate the "body" of an opaque type introduced by an `impl Trait`,
+    /// that is its bounds and its hidden type (see [TypeDeclKind::ImplTrait]).
+    ///
+    /// We translate the hidden type only if the type is transparent, and
+    /// if it doesn't itself contain closures or other `impl Trait` (this often
+    /// happens in practice, for instance with `impl Iterator`).
+    fn translate_impl_trait_body(
+        &mut self,
+        rust_id: DefId,
+        is_transparent: bool,
+    ) -> Result<TypeDeclKind, Error> {
+        use rustc_middle::ty::{Clause, PredicateKind};
+        let tcx = self.t_ctx.tcx;
+        let span = tcx.def_span(rust_id);
+        let erase_regions = false;
+
+        // We translate the trait predicates first, because the projection
+        // predicates refer to them.
+        let (trait_preds, proj_preds): (Vec<_>, Vec<_>) = tcx
+            .item_bounds(rust_id)
+            .subst_identity()
+            .iter()
+            .partition(|pred| {
+                matches!(
+                    pred.kind().skip_binder(),
+                    PredicateKind::Clause(Clause::Trait(_))
+                )
+            });
+        let mut bounds: Vec<ImplTraitBound> = Vec::new();
+        for pred in trait_preds.into_iter().chain(proj_preds.into_iter()) {
+            if !pred.kind().bound_vars().is_empty() {
+                error_or_panic!(
+                    self,
+                    span,
+                    "Higher-ranked bounds on `impl Trait` types are not supported yet"
+                )
+            }
+            match pred.kind().skip_binder() {
+                PredicateKind::Clause(Clause::Trait(trait_pred)) => {
+                    let trait_ref = trait_pred.trait_ref;
+                    // This returns `None` for the marker traits we ignore
+                    let Some(trait_id) = self.translate_trait_decl_id(span, trait_ref.def_id)?
+                    else { continue };
+                    // Skip the self type, which is the opaque type itself
+                    let substs: Vec<hax::GenericArg> = trait_ref
+                        .substs
+                        .iter()
+                        .skip(1)
+                        .map(|x| x.sinto(&self.hax_state))
+                        .collect();
+                    let (regions, types, const_generics) =
+                        self.translate_substs(span, erase_regions, None, &substs)?;
+                    bounds.push(ImplTraitBound {
+                        trait_id,
+                        generics: GenericArgs::new(regions, types, const_generics, Vec::new()),
+                        type_constraints: Vec::new(),
+                    });
+                }
+                PredicateKind::Clause(Clause::Projection(proj_pred)) => {
+                    let proj_ty = proj_pred.projection_ty;
+                    let Some(trait_id) =
+                        self.translate_trait_decl_id(span, proj_ty.trait_def_id(tcx))?
+                    else { continue };
+                    let name = TraitItemName(tcx.item_name(proj_ty.def_id).to_string());
+                    let Some(ty) = proj_pred.term.ty() else {
+                        error_or_panic!(self, span, "Unexpected constant projection")
+                    };
+                    let ty = self.translate_ty(span, erase_regions, &ty.sinto(&self.hax_state))?;
+                    match bounds.iter_mut().rev().find(|b| b.trait_id == trait_id) {
+                        Some(bound) => bound.type_constraints.push((name, ty)),
+                        None => {
+                            error_or_panic!(
+                                self,
+                                span,
+                                format!("Could not find the bound for the projection on {name}")
+                            )
+                        }
+                    }
+                }
+                // We ignore the outlives predicates
+                _ => (),
+            }
+        }
+
+        let hidden_ty = if is_transparent && rust_id.is_local() {
+            use rustc_middle::ty::subst::GenericArgKind;
+            use rustc_middle::ty::TyKind;
+            let hidden_ty = tcx.type_of(rust_id).subst_identity();
+            let is_supported = hidden_ty.walk().all(|arg| match arg.unpack() {
+                GenericArgKind::Type(ty) => !matches!(
+                    ty.kind(),
+                    TyKind::Closure(..)
+                        | TyKind::Generator(..)
+                        | TyKind::Alias(rustc_middle::ty::AliasKind::Opaque, _)
+                ),
+                _ => true,
+            });
+            if is_supported {
+                Some(self.translate_ty(span, erase_regions, &hidden_ty.sinto(&self.hax_state))?)
+            } else {
+                None
+            }
+        } else {
+            None
+        };
+
+        Ok(TypeDeclKind::ImplTrait { bounds, hidden_ty })
+    }
+
     /// Sanity check: region names are pairwise distinct (this caused trouble
     /// when generating names for the backward functions in Aeneas): at some
     /// point, Rustc introduced names equal to `Some("'_")` for the anonymous
@@ -649,14 +1353,19 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
         let substs = rustc_middle::ty::subst::InternalSubsts::identity_for_item(tcx, def_id)
             .sinto(&self.hax_state);
 
-        self.translate_generic_params_from_hax(span, &substs)
+        self.translate_generic_params_from_hax(span, def_id, &substs)
     }
 
+    /// [def_id]: the definition which introduces the generic parameters (we
+    /// use its environment to check whether the type parameters are sized).
     pub(crate) fn translate_generic_params_from_hax(
         &mut self,
         span: rustc_span::Span,
+        def_id: DefId,
         substs: &Vec<hax::GenericArg>,
     ) -> Result<(), Error> {
+        let tcx = self.t_ctx.tcx;
+        let param_env = tcx.param_env(def_id);
         let erase_regions = false;
         for p in substs {
             use hax::GenericArg::*;
@@ -664,7 +1373,11 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                 Type(p) => {
                     // The type should be a Param
                     if let hax::Ty::Param(p) = p {
-                        let _ = self.push_type_var(p.index, p.name.clone());
+                        // Check if the parameter has an (implicit) `Sized` bound
+                        let rust_ty =
+                            tcx.mk_ty_param(p.index, rustc_span::symbol::Symbol::intern(&p.name));
+                        let is_sized = rust_ty.is_sized(tcx, param_env);
+                        let _ = self.push_type_var(p.index, p.name.clone(), is_sized);
                     } else {
                         unreachable!("unexpected");
                     }
@@ -677,7 +1390,7 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
                     // The type should be primitive, meaning it shouldn't contain variables,
                     // non-primitive adts, etc. As a result, we can use an empty context.
                     let ty = self.translate_ty(span, erase_regions, &c.ty)?;
-                    let ty = ty.to_literal();
+                    let ty = *ty.as_literal();
                     if let hax::ConstantExprKind::ConstRef { id: cp } = &*c.contents {
                         self.push_const_generic_var(cp.index, ty, cp.name.clone());
                     } else {
@@ -687,6 +1400,36 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
             }
         }
 
+        // The default values of the parameters: they may refer to the other
+        // parameters, so we translate them once all the parameters are registered
+        for param in &tcx.generics_of(def_id).params {
+            use rustc_middle::ty::GenericParamDefKind;
+            match param.kind {
+                GenericParamDefKind::Type {
+                    has_default: true, ..
+                } => {
+                    let ty = tcx.type_of(param.def_id).subst_identity();
+                    let ty = self.translate_ty(span, erase_regions, &ty.sinto(&self.hax_state))?;
+                    if let Some(var_id) = self.type_vars_map.get(&param.index) {
+                        self.type_vars[var_id].default = Some(ty);
+                    }
+                }
+                GenericParamDefKind::Const {
+                    has_default: true, ..
+                } => {
+                    let c = tcx.const_param_default(param.def_id).subst_identity();
+                    let c = self.translate_constant_expr_to_const_generic(
+                        span,
+                        &c.sinto(&self.hax_state),
+                    )?;
+                    if let Some(var_id) = self.const_generic_vars_map.get(&param.index) {
+                        self.const_generic_vars[var_id].default = Some(c);
+                    }
+                }
+                _ => (),
+            }
+        }
+
         // Sanity check
         self.check_generics();
 
@@ -695,6 +1438,131 @@ impl<'tcx, 'ctx, 'ctx1> BodyTransCtx<'tcx, 'ctx, 'ctx1> {
 }
 
 impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
+    /// Compute whether a type declaration implements an auto trait.
+    fn translate_auto_trait_impl(&self, rust_id: DefId, trait_id: Option<DefId>) -> AutoTraitImpl {
+        use rustc_infer::infer::TyCtxtInferExt;
+        use rustc_middle::ty::{Binder, ParamEnv, ToPredicate, TraitRef};
+        use rustc_trait_selection::traits::type_known_to_meet_bound_modulo_regions;
+        let tcx = self.tcx;
+        // This happens with `#![no_core]`
+        let Some(trait_id) = trait_id else { return AutoTraitImpl::Never };
+        let ty = tcx.type_of(rust_id).subst_identity();
+        let implements = |param_env| {
+            let infcx = tcx.infer_ctxt().build();
+            type_known_to_meet_bound_modulo_regions(&infcx, param_env, ty, trait_id)
+        };
+
+        let param_env = tcx.param_env(rust_id);
+        if implements(param_env) {
+            return AutoTraitImpl::Always;
+        }
+        // Check again, assuming that the type parameters implement the trait
+        let substs = rustc_middle::ty::InternalSubsts::identity_for_item(tcx, rust_id);
+        let param_bounds = substs.types().map(|ty| {
+            let trait_ref = TraitRef::new(tcx, trait_id, [ty]);
+            Binder::dummy(trait_ref).without_const().to_predicate(tcx)
+        });
+        let bounds = param_env.caller_bounds().iter().chain(param_bounds);
+        let param_env = ParamEnv::new(
+            tcx.mk_predicates_from_iter(bounds),
+            param_env.reveal(),
+            param_env.constness(),
+        );
+        if implements(param_env) {
+            AutoTraitImpl::IfParams
+        } else {
+            AutoTraitImpl::Never
+        }
+    }
+
+    /// Compute the auto traits implemented by a type declaration (see [AutoTraits]).
+    fn translate_auto_traits(&self, rust_id: DefId) -> AutoTraits {
+        let lang_items = self.tcx.lang_items();
+        let send = self.tcx.get_diagnostic_item(rustc_span::sym::Send);
+        AutoTraits {
+            send: self.translate_auto_trait_impl(rust_id, send),
+            sync: self.translate_auto_trait_impl(rust_id, lang_items.sync_trait()),
+            unpin: self.translate_auto_trait_impl(rust_id, lang_items.unpin_trait()),
+        }
+    }
+
+    /// Translate a type alias (see [TypeAliasDecl]).
+    pub(crate) fn translate_type_alias(&mut self, rust_id: DefId) {
+        self.with_def_id(rust_id, |ctx| {
+            if ctx.translate_type_alias_aux(rust_id).is_err() {
+                let span = ctx.tcx.def_span(rust_id);
+                ctx.span_err(
+                    span,
+                    &format!(
+                        "Ignoring the following type alias due to an error: {:?}",
+                        rust_id
+                    ),
+                );
+            }
+        });
+    }
+
+    /// Auxliary helper to properly handle errors, see [translate_type_alias].
+    fn translate_type_alias_aux(&mut self, rust_id: DefId) -> Result<(), Error> {
+        let def_id = self.register_type_alias_id(rust_id);
+        let span = self.tcx.def_span(rust_id);
+        let ty = self.tcx.type_of(rust_id).subst_identity();
+        let mut bt_ctx = BodyTransCtx::new(rust_id, self);
+
+        // The aliases don't have predicates: rustc doesn't check them
+        bt_ctx.translate_generic_params(rust_id)?;
+        let erase_regions = false;
+        let ty = bt_ctx.translate_ty(span, erase_regions, &ty.sinto(&bt_ctx.hax_state))?;
+
+        let name = bt_ctx.t_ctx.def_id_to_name(rust_id)?;
+        let generics = bt_ctx.get_generics();
+        let item_meta = bt_ctx.t_ctx.translate_item_meta_from_rid(rust_id)?;
+        let alias = TypeAliasDecl {
+            def_id,
+            item_meta,
+            name,
+            generics,
+            ty,
+        };
+        self.translated.type_aliases.insert(def_id, alias);
+        Ok(())
+    }
+
+    /// Compute the variances of the parameters of a type declaration (see
+    /// [Variances]). Only the ADTs have variances: we return empty variances
+    /// for the opaque types introduced by `impl Trait`.
+    fn translate_variances(&self, rust_id: DefId) -> Variances {
+        use rustc_hir::def::DefKind;
+        use rustc_middle::ty::GenericParamDefKind;
+        let mut variances = Variances::default();
+        if !matches!(
+            self.tcx.def_kind(rust_id),
+            DefKind::Struct | DefKind::Enum | DefKind::Union
+        ) {
+            return variances;
+        }
+        let rust_variances = self.tcx.variances_of(rust_id);
+        // The parameters are in the order in which we registered them
+        for param in &self.tcx.generics_of(rust_id).params {
+            let variance = match rust_variances[param.index as usize] {
+                rustc_middle::ty::Variance::Covariant => Variance::Covariant,
+                rustc_middle::ty::Variance::Invariant => Variance::Invariant,
+                rustc_middle::ty::Variance::Contravariant => Variance::Contravariant,
+                rustc_middle::ty::Variance::Bivariant => Variance::Bivariant,
+            };
+            match param.kind {
+                GenericParamDefKind::Lifetime => {
+                    variances.regions.push(variance);
+                }
+                GenericParamDefKind::Type { .. } => {
+                    variances.types.push(variance);
+                }
+                GenericParamDefKind::Const { .. } => (),
+            }
+        }
+        variances
+    }
+
     /// Translate a type definition.
     ///
     /// Note that we translate the types one by one: we don't need to take into
@@ -709,7 +1577,7 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
                     &format!("Ignoring the following type due to an error: {:?}", rust_id),
                 );
                 // Save the definition
-                let _ = ctx.ignored_failed_decls.insert(rust_id);
+                let _ = ctx.errors.ignored_failed_decls.insert(rust_id);
             }
         });
     }
@@ -733,7 +1601,9 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
         // For instance, because `core::option::Option` is public, we can
         // manipulate its variants. If we encounter this type, we must retrieve
         // its definition.
-        let kind = if !is_transparent {
+        let kind = if self.tcx.def_kind(rust_id) == rustc_hir::def::DefKind::OpaqueTy {
+            bt_ctx.translate_impl_trait_body(rust_id, is_transparent)?
+        } else if !is_transparent {
             TypeDeclKind::Opaque
         } else {
             match bt_ctx.translate_type_body(trans_id, rust_id) {
@@ -747,7 +1617,7 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
         let generics = bt_ctx.get_generics();
 
         // Translate the meta information
-        let item_meta = bt_ctx.t_ctx.translate_item_meta_from_rid(rust_id);
+        let item_meta = bt_ctx.t_ctx.translate_item_meta_from_rid(rust_id)?;
 
         let type_def = TypeDecl {
             def_id: trans_id,
@@ -757,6 +1627,9 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
             generics,
             preds: bt_ctx.get_predicates(),
             kind,
+            auto_traits: bt_ctx.t_ctx.translate_auto_traits(rust_id),
+            variances: bt_ctx.t_ctx.translate_variances(rust_id),
+            rec_group: None,
         };
 
         trace!("translate_type: preds: {:?}", &type_def.preds);
@@ -767,7 +1640,7 @@ impl<'tcx, 'ctx> TransCtx<'tcx, 'ctx> {
             type_def.fmt_with_ctx(&self.into_fmt())
         );
 
-        self.type_decls.insert(trans_id, type_def);
+        self.translated.type_decls.insert(trans_id, type_def);
 
         Ok(())
     }
@@ -1,8 +1,10 @@
 pub use crate::gast::{FunDeclId, TraitItemName};
+use crate::hash_cons::HashConsed;
 use crate::meta::{ItemMeta, Meta};
 use crate::names::Name;
+use crate::symbol::Symbol;
 pub use crate::types_utils::*;
-use crate::values::Literal;
+use crate::values::{Literal, ScalarValue, VarId};
 use derivative::Derivative;
 use macros::{EnumAsGetters, EnumIsA, EnumToGetters, VariantIndexArity, VariantName};
 use serde::Serialize;
@@ -16,6 +18,7 @@ pub type FieldName = String;
 // generated by using macros.
 generate_index_type!(TypeVarId);
 generate_index_type!(TypeDeclId);
+generate_index_type!(TypeAliasId);
 generate_index_type!(VariantId);
 generate_index_type!(FieldId);
 generate_index_type!(RegionId);
@@ -31,6 +34,15 @@ pub struct TypeVar {
     pub index: TypeVarId::Id,
     /// Variable name
     pub name: String,
+    /// [false] if the type parameter may be instantiated with a dynamically
+    /// sized type (i.e., it has a `?Sized` bound, or it is the `Self` parameter
+    /// of a trait declaration). We need this information because we filter the
+    /// [core::marker::Sized] trait clauses by default.
+    pub is_sized: bool,
+    /// The default value of the parameter, like `u32` in `struct Foo<T = u32>`
+    /// (only the type declarations and the trait declarations can give some).
+    /// The default may refer to the previous parameters (`struct Foo<T, U = T>`).
+    pub default: Option<Ty>,
 }
 
 /// Region variable.
@@ -51,6 +63,9 @@ pub struct ConstGenericVar {
     pub name: String,
     /// Type of the const generic
     pub ty: LiteralTy,
+    /// The default value of the parameter, like `4` in
+    /// `struct Foo<const N: usize = 4>` (see [TypeVar::default]).
+    pub default: Option<ConstGeneric>,
 }
 
 #[derive(Debug, PartialEq, Eq, Copy, Clone, Hash, PartialOrd, Ord, Serialize)]
@@ -88,6 +103,11 @@ pub enum Region {
     ///                                   Var id: 1
     /// ```
     BVar(DeBruijnId, RegionId::Id),
+    /// Region variable of a function body, introduced by the borrow checker
+    /// (see [crate::gast::BodyRegions]). Those only appear in the bodies, and
+    /// only if we do not erase the regions of the bodies (see the
+    /// `--body-regions` option).
+    Body(RegionId::Id),
     /// Erased region
     Erased,
     /// For error reporting.
@@ -107,6 +127,31 @@ pub enum TraitInstanceId {
     /// A specific implementation
     TraitImpl(TraitImplId::Id),
     ///
+    /// A specific implementation which contains items that can be specialized
+    /// (see [crate::gast::TraitImpl::default_items]). Depending on the
+    /// instantiation of the generics, the items used at runtime may come from
+    /// one of the impls which specialize it: we list those impls so that an
+    /// impl comes before the impls it specializes (the first impl which applies
+    /// is the one used at runtime, and we fall back to `impl_id` if none applies).
+    ///
+    /// Example:
+    /// ```text
+    /// impl<T: Display> ToString for T {
+    ///   default fn to_string(&self) -> String { ... }
+    /// }
+    /// impl ToString for str { ... }
+    ///
+    /// fn f<T: Display>(x: &T) -> String {
+    ///   x.to_string()
+    ///   ^^^^^^^^^^^^^
+    ///   Specializable { impl_id: ToString for T, specializing_impls: [ToString for str, ...] }
+    /// }
+    /// ```
+    Specializable {
+        impl_id: TraitImplId::Id,
+        specializing_impls: Vec<TraitImplId::Id>,
+    },
+    ///
     /// A specific builtin trait implementation like [core::marker::Sized] or
     /// auto trait implementation like [core::marker::Syn].
     BuiltinOrAuto(TraitDeclId::Id),
@@ -193,6 +238,10 @@ pub enum TraitInstanceId {
     ///   a.map(incr)
     /// }
     /// ```
+    ///
+    /// The type is the type of the function: a [TyKind::FnDef] if we use a
+    /// function item (`incr` above), an [TyKind::Arrow] if we use a function
+    /// pointer (or the item of a trait method, see [TyKind::FnDef]).
     FnPointer(Box<Ty>),
     /// Similar to [FnPointer], but where we use a closure.
     ///
@@ -211,18 +260,28 @@ pub enum TraitInstanceId {
     /// Clause which hasn't been solved yet.
     /// This happens when we register clauses in the context: solving some
     /// trait obligations/references might require to refer to clauses which
-    /// haven't been registered yet. This variant is purely internal: after we
-    /// finished solving the trait obligations, all the remaining unsolved
-    /// clauses (in case we don't fail hard on error) are converted to [Unknown].
+    /// haven't been registered yet. This variant is purely internal: once all
+    /// the items are translated, we retry the remaining unsolved clauses, and
+    /// convert those we still fail to solve to [Unknown] (see
+    /// [crate::transform::solve_trait_obligations]).
     Unsolved(TraitDeclId::Id, GenericArgs),
     /// For error reporting.
     /// Can appear only if the option [CliOpts::continue_on_failure] is used.
     Unknown(String),
 }
 
-/// A reference to a trait
+/// A reference to a trait.
+///
+/// The trait references are hash-consed, like the types (see [Ty]). Use
+/// [TraitRef::new] to build one: we can directly read the fields of the
+/// [TraitRefData].
+#[derive(Clone, PartialEq, Eq, Hash, Ord, PartialOrd, Serialize)]
+#[serde(transparent)]
+pub struct TraitRef(pub(crate) HashConsed<TraitRefData>);
+
+/// The content of a [TraitRef].
 #[derive(Debug, Clone, Serialize, PartialEq, Eq, Hash, Ord, PartialOrd)]
-pub struct TraitRef {
+pub struct TraitRefData {
     pub trait_id: TraitInstanceId,
     pub generics: GenericArgs,
     /// Not necessary, but useful
@@ -261,6 +320,10 @@ pub type TypeOutlives = OutlivesPred<Ty, Region>;
 pub struct TraitTypeConstraint {
     pub trait_ref: TraitRef,
     pub type_name: TraitItemName,
+    /// The arguments of the associated type, if it is a generic associated
+    /// type (e.g., `T : Foo<S<'a> = &'a u32>`). Those don't include the
+    /// arguments of the trait, which are given by the trait ref.
+    pub generics: GenericArgs,
     pub ty: Ty,
 }
 
@@ -273,10 +336,45 @@ pub struct Predicates {
     pub types_outlive: Vec<TypeOutlives>,
     /// Constraints over trait associated types
     pub trait_type_constraints: Vec<TraitTypeConstraint>,
+    /// The outlives relation between the generic parameters, computed from
+    /// the outlives predicates above *and* from the bounds implied by the
+    /// types appearing in the declaration (see [RegionGraph]).
+    pub regions_graph: RegionGraph,
+}
+
+/// The transitive closure of the outlives relation between the region and
+/// type parameters of a declaration.
+///
+/// It is computed from the outlives predicates of the declaration, and from
+/// the bounds implied by the types of the signature (for functions), of the
+/// fields (for type declarations) or of the implemented trait (for trait
+/// implementations). For instance, the type `&'a &'b T` implies the bounds
+/// `'b : 'a` and `T : 'b` (and thus `T : 'a`).
+///
+/// The relation is normalized, so that it can be compared directly: the lists
+/// of regions are sorted, with `'static` first, and the region parameters are
+/// referred to with [Region::BVar] and the De Bruijn index 0. We don't list
+/// the fact that a region outlives itself.
+#[derive(Debug, Clone, Default, Serialize, PartialEq, Eq)]
+pub struct RegionGraph {
+    /// For every region parameter, the regions it outlives.
+    pub regions: RegionId::Vector<Vec<Region>>,
+    /// For every type parameter, the regions it outlives.
+    pub types: TypeVarId::Vector<Vec<Region>>,
 }
 
+/// Generic arguments.
+///
+/// The generic arguments are hash-consed, like the types (see [Ty]). Use
+/// [GenericArgs::new] to build them: we can directly read the fields of the
+/// [GenericArgsData].
+#[derive(Clone, PartialEq, Eq, Hash, Ord, PartialOrd, Serialize)]
+#[serde(transparent)]
+pub struct GenericArgs(pub(crate) HashConsed<GenericArgsData>);
+
+/// The content of [GenericArgs].
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Hash, Ord, PartialOrd)]
-pub struct GenericArgs {
+pub struct GenericArgsData {
     pub regions: Vec<Region>,
     pub types: Vec<Ty>,
     pub const_generics: Vec<ConstGeneric>,
@@ -300,6 +398,8 @@ pub struct GenericParams {
     // Remark: we use a regular [Vec], not a [TraitClauseId::Vector], because due to the
     // filtering of some trait clauses (for the marker traits for instance) the indexation
     // is not contiguous (e.g., we may have [clause 0; clause 3; clause 4]).
+    // The indexation is contiguous if we don't filter any clause (see the
+    // `--keep-all-trait-clauses` option).
     pub trait_clauses: Vec<TraitClause>,
 }
 
@@ -317,8 +417,18 @@ pub struct TraitClause {
     #[derivative(PartialEq = "ignore")]
     pub meta: Option<Meta>,
     pub trait_id: TraitDeclId::Id,
+    /// The regions universally quantified at the level of the clause, for
+    /// clauses like `F : for<'a> Fn(&'a u32) -> &'a u32`. Those regions are
+    /// bound with De Bruijn index 0 inside the [generics] field (see
+    /// [Region::BVar]): the regions bound outside of the clause are thus
+    /// shifted by one.
+    pub bound_regions: RegionId::Vector<RegionVar>,
     /// Remark: the trait refs list in the [generics] field should be empty.
     pub generics: GenericArgs,
+    /// [true] if this is a `~const` bound (`T : ~const Foo`): in a const
+    /// context, the clause must be satisfied by a `const` impl (see
+    /// [crate::gast::TraitImpl::is_const]).
+    pub is_const: bool,
 }
 
 impl Eq for TraitClause {}
@@ -349,37 +459,181 @@ pub struct TypeDecl {
     pub preds: Predicates,
     /// The type kind: enum, struct, or opaque.
     pub kind: TypeDeclKind,
+    /// The auto traits implemented by the type.
+    pub auto_traits: AutoTraits,
+    /// The variances of the parameters of the type.
+    pub variances: Variances,
+    /// If the type is (mutually) recursive, the types of the recursive group it
+    /// belongs to, including itself (see
+    /// [crate::reorder_decls::DeclarationGroup]).
+    pub rec_group: Option<Vec<TypeDeclId::Id>>,
+}
+
+/// A type alias, like `type Result<T> = std::result::Result<T, MyError>`.
+///
+/// The type aliases are inlined in MIR, so the types we translate lose this
+/// abstraction: we translate the local type aliases from the HIR (with
+/// `--type-aliases`), so that the backends can print the types the way the user
+/// wrote them (see [TypeAliasRef]).
+#[derive(Debug, Clone, Serialize)]
+pub struct TypeAliasDecl {
+    pub def_id: TypeAliasId::Id,
+    pub item_meta: ItemMeta,
+    pub name: Name,
+    pub generics: GenericParams,
+    /// The type the alias stands for.
+    pub ty: Ty,
+}
+
+/// A reference to a type alias, which records the alias the user wrote for a
+/// type (for instance, `Result<u32>` for the type
+/// `std::result::Result<u32, MyError>`). We only record the aliases written at
+/// the top of the types of the fields and of the signatures.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TypeAliasRef {
+    pub alias_id: TypeAliasId::Id,
+    /// The arguments of the alias. The regions are erased.
+    pub generics: GenericArgs,
+}
+
+/// The variance of a parameter of a type declaration, which tells how the
+/// subtyping relation on the parameter lifts to the type. For instance,
+/// `&'a T` is covariant in `'a` and `T`, while `&'a mut T` is covariant in `'a`
+/// and invariant in `T`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Variance {
+    Covariant,
+    Invariant,
+    Contravariant,
+    /// The parameter isn't used by the type.
+    Bivariant,
+}
+
+/// The variances of the parameters of a type declaration, as computed by rustc.
+///
+/// Those don't depend on the fields we keep: a backend may drop the fields of
+/// type `PhantomData<T>`, which are only there to give the variance of `T`
+/// (see [crate::transform::remove_phantom_data]).
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Variances {
+    pub regions: RegionId::Vector<Variance>,
+    pub types: TypeVarId::Vector<Variance>,
+}
+
+/// Whether a type implements an auto trait.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum AutoTraitImpl {
+    /// The type implements the trait.
+    Always,
+    /// The type implements the trait if its type parameters implement it (for
+    /// instance, `Vec<T>` is `Send` if `T` is `Send`).
+    IfParams,
+    /// The type doesn't implement the trait (for instance, `Rc<T>` is never
+    /// `Send`).
+    Never,
+}
+
+/// The auto traits implemented by a type declaration. Those take into account
+/// the negative impls (`impl !Send for Foo {}`) and the types of the fields.
+/// For the `impl Trait` types, those are the traits implemented by the hidden
+/// type (which leak to the users of the `impl Trait` type).
+#[derive(Debug, Clone, Serialize)]
+pub struct AutoTraits {
+    pub send: AutoTraitImpl,
+    pub sync: AutoTraitImpl,
+    pub unpin: AutoTraitImpl,
 }
 
 #[derive(Debug, Clone, EnumIsA, EnumAsGetters, Serialize)]
 pub enum TypeDeclKind {
     Struct(FieldId::Vector<Field>),
-    Enum(VariantId::Vector<Variant>),
+    /// An enumeration, together with the type of its discriminant: `isize`,
+    /// unless the enumeration has a `#[repr(...)]` attribute giving another
+    /// integer type (ex.: `#[repr(u8)]`), and its layout, if it doesn't depend
+    /// on the type parameters (see [EnumLayout]).
+    Enum(VariantId::Vector<Variant>, IntegerTy, Option<EnumLayout>),
     /// An opaque type.
     ///
     /// Either a local type marked as opaque, or an external type.
     Opaque,
+    /// An opaque type introduced by an `impl Trait` in return position
+    /// (ex.: `fn f() -> impl Iterator<Item = u32>`).
+    ///
+    /// We know the traits implemented by the type, and not much more. We may
+    /// also know its hidden type, if the type was introduced by a local
+    /// function, but backends should be careful: the whole point of `impl Trait`
+    /// is that callers don't rely on it.
+    ImplTrait {
+        bounds: Vec<ImplTraitBound>,
+        hidden_ty: Option<Ty>,
+    },
     /// Used if an error happened during the extraction, and we don't panic
     /// on error.
     Error(String),
 }
 
+/// A bound on an `impl Trait` type, for instance `Iterator<Item = u32>` in
+/// `impl Iterator<Item = u32>`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ImplTraitBound {
+    pub trait_id: TraitDeclId::Id,
+    /// The arguments of the trait. Contrary to the other trait references,
+    /// they don't contain the self type (which is the opaque type itself),
+    /// and the trait refs are not resolved (the list is empty).
+    pub generics: GenericArgs,
+    /// The constraints on the associated types (ex.: `Item = u32`).
+    pub type_constraints: Vec<(TraitItemName, Ty)>,
+}
+
+/// The layout chosen by rustc for an enumeration, which tells how the
+/// discriminant is represented in memory.
+#[derive(Debug, Clone, Serialize)]
+pub enum EnumLayout {
+    /// No discriminant is stored, because the enumeration has at most one
+    /// inhabited variant.
+    Single,
+    /// The discriminant is stored in a dedicated field (the tag).
+    Tagged,
+    /// The discriminant is encoded in the invalid values (the niche) of a field
+    /// of one of the variants, the untagged variant: the other variants are
+    /// represented by invalid values of this field. For instance, the `None`
+    /// variant of `Option<&T>` is represented by the null pointer.
+    Niche {
+        untagged_variant: VariantId::Id,
+        /// The field of the untagged variant which contains the niche. The
+        /// niche may be nested in this field (if the field is a structure
+        /// containing a reference, for instance).
+        field: FieldId::Id,
+    },
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct Variant {
     pub meta: Meta,
-    pub name: String,
+    pub name: Symbol,
     pub fields: FieldId::Vector<Field>,
-    #[serde(skip)]
-    /// The discriminant used at runtime. This is used in `remove_read_discriminant` to match up
+    /// The discriminant used at runtime, whose type is the discriminant type of the enumeration
+    /// (see [TypeDeclKind::Enum]). This is used in `remove_read_discriminant` to match up
     /// `SwitchInt` targets with the corresponding `Variant`.
-    pub discriminant: u128,
+    pub discriminant: ScalarValue,
 }
 
 #[derive(Debug, Clone, Serialize)]
 pub struct Field {
     pub meta: Meta,
-    pub name: Option<String>,
+    pub name: Option<Symbol>,
     pub ty: Ty,
+    /// [true] if the type of the field is zero-sized, like `()` or
+    /// `PhantomData<T>`. This is [false] if the size of the field depends on
+    /// the type parameters.
+    pub is_zst: bool,
+    /// [true] if the type of the field is `PhantomData<T>`. Such fields are
+    /// only used to give the variance of the type parameters, or to tell that
+    /// the type owns a value of type `T` (see [Variances]).
+    pub is_phantom_data: bool,
+    /// The type alias the user wrote for the type of the field, if any (see
+    /// [TypeAliasRef]).
+    pub alias: Option<TypeAliasRef>,
 }
 
 #[derive(
@@ -400,6 +654,22 @@ pub enum IntegerTy {
     U128,
 }
 
+/// Information about the target the crate was compiled for.
+///
+/// This gives a concrete semantics to the target-dependent types, like
+/// [IntegerTy::Isize] and [IntegerTy::Usize] (see [IntegerTy::target_size]).
+#[derive(Debug, Clone, Serialize)]
+pub struct TargetInfo {
+    /// The target triple (for instance: `x86_64-unknown-linux-gnu`)
+    pub target_triple: String,
+    /// The size of the pointers, in bits (this is also the size of `usize`
+    /// and `isize`)
+    pub pointer_width: u32,
+    pub is_little_endian: bool,
+    /// The size of the C `int` type, in bits
+    pub c_int_width: u32,
+}
+
 #[derive(
     Debug, PartialEq, Eq, Clone, Copy, Hash, VariantName, EnumIsA, Serialize, Ord, PartialOrd,
 )]
@@ -484,6 +754,14 @@ pub enum LiteralTy {
 pub enum ConstGeneric {
     /// A global constant
     Global(GlobalDeclId::Id),
+    /// A trait associated constant
+    ///
+    /// Ex.:
+    /// ```text
+    /// impl<T : Trait> Foo<T, { T::LEN }> { ... }
+    ///                         ^^^^^^
+    /// ```
+    TraitConst(TraitRef, TraitItemName),
     /// A const generic variable
     Var(ConstGenericVarId::Id),
     /// A concrete value
@@ -491,6 +769,15 @@ pub enum ConstGeneric {
 }
 
 /// A type.
+///
+/// The types are hash-consed (see [HashConsed]): cloning a type is cheap, and
+/// comparing two types for equality is O(1). Use [Ty::kind] to inspect a type
+/// and [TyKind::into_ty] to build one.
+#[derive(Clone, PartialEq, Eq, Hash, Ord, PartialOrd, Serialize)]
+#[serde(transparent)]
+pub struct Ty(pub(crate) HashConsed<TyKind>);
+
+/// The kind of a type.
 #[derive(
     Debug,
     Clone,
@@ -506,7 +793,7 @@ pub enum ConstGeneric {
     Ord,
     PartialOrd,
 )]
-pub enum Ty {
+pub enum TyKind {
     /// An ADT.
     /// Note that here ADTs are very general. They can be:
     /// - user-defined ADTs
@@ -535,23 +822,46 @@ pub enum Ty {
     Never,
     // We don't support floating point numbers on purpose (for now)
     /// A borrow
-    Ref(Region, Box<Ty>, RefKind),
+    Ref(Region, Ty, RefKind),
     /// A raw pointer.
-    RawPtr(Box<Ty>, RefKind),
+    RawPtr(Ty, RefKind),
     /// A trait associated type
     ///
     /// Ex.:
     /// ```text
     /// trait Foo {
     ///   type Bar; // type associated to the trait Foo
+    ///   type Baz<'a>; // generic associated type
     /// }
     /// ```
-    TraitType(TraitRef, TraitItemName),
+    ///
+    /// The generic arguments are the arguments of the associated type itself,
+    /// in case it is a generic associated type (`Foo::Baz<'a>`). They don't
+    /// include the arguments of the trait, which are given by the trait ref.
+    TraitType(TraitRef, TraitItemName, GenericArgs),
     /// Arrow type, used in particular for the local function pointers.
     /// This is essentially a "constrained" function signature:
     /// arrow types can only contain generic lifetime parameters
     /// (no generic types), no predicates, etc.
-    Arrow(RegionId::Vector<RegionVar>, Vec<Ty>, Box<Ty>),
+    Arrow(RegionId::Vector<RegionVar>, Vec<Ty>, Ty),
+    /// The (zero-sized) type of a function item, given by the id of the
+    /// function and its generic arguments. Contrary to the [Arrow] types, this
+    /// allows to refer to a function instantiated with type or const generic
+    /// arguments.
+    ///
+    /// ```text
+    /// fn incr<const N: u32>(x : u32) -> u32 { x + N }
+    ///
+    /// fn f() -> fn(u32) -> u32 {
+    ///   let g = incr::<3>; // `g` has type `FnDef(incr, [3])`
+    ///   g // we coerce `g` to `fn(u32) -> u32`
+    /// }
+    /// ```
+    ///
+    /// Remark: we use this type for the items of the regular functions only
+    /// (local or not). The methods of the traits and the assumed functions
+    /// have [Arrow] types.
+    FnDef(FunDeclId::Id, GenericArgs),
 }
 
 /// Assumed types identifiers.
@@ -581,7 +891,7 @@ pub enum Ty {
 pub enum AssumedTy {
     /// Boxes have a special treatment: we translate them as identity.
     Box,
-    /// Comes from the standard library. See the comments for [Ty::RawPtr]
+    /// Comes from the standard library. See the comments for [TyKind::RawPtr]
     /// as to why we have this here.
     PtrUnique,
     /// Same comments as for [AssumedTy::PtrUnique]
@@ -654,6 +964,49 @@ pub enum ClosureKind {
     FnOnce,
 }
 
+/// The way a closure captures a place.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+pub enum CaptureMode {
+    /// The place is moved (or copied) into the closure state.
+    ByValue,
+    /// The closure state contains a borrow of the place. Remark: the unique
+    /// immutable borrows of rustc (used to mutate the targets of mutable
+    /// references captured by the closure) are translated as mutable borrows.
+    ByRef(RefKind),
+}
+
+/// A projection in the path of a captured place.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+pub enum CaptureProjection {
+    Deref,
+    Field(FieldId::Id),
+}
+
+/// A place captured by a closure, given as a path from a local variable of
+/// the parent function. For instance, in `|| x.0.clone()` the closure
+/// captures `x.0` by shared reference.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CapturedPlace {
+    /// The captured variable, in the body of the parent function (for the
+    /// nested closures, this is the body of the function or of the closure
+    /// which declares the variable).
+    pub var_id: VarId::Id,
+    /// The body which declares the captured variable. We don't export this: we
+    /// need it to update the variable ids when we remove locals from the bodies
+    /// (see [crate::transform::remove_unused_locals]).
+    #[serde(skip)]
+    pub var_owner: rustc_hir::def_id::DefId,
+    pub projection: Vec<CaptureProjection>,
+    pub mode: CaptureMode,
+    /// For every element of [Self::projection], the structure or the variant
+    /// of the enumeration whose field it projects (`None` for the dereferences
+    /// and the fields of the tuples and of the closure states). We don't export
+    /// this: we need it to update the field ids when we remove fields from the
+    /// type declarations (see [crate::transform::remove_phantom_data]).
+    #[serde(skip)]
+    pub field_adts: Vec<Option<(TypeDeclId::Id, Option<VariantId::Id>)>>,
+}
+
 /// Additional information for closures.
 /// We mostly use it in micro-passes like [crate::update_closure_signature].
 #[derive(Debug, Clone, PartialEq, Eq, Serialize)]
@@ -671,6 +1024,18 @@ pub struct ClosureInfo {
     /// }
     /// ```
     pub state: Vec<Ty>,
+    /// The places captured by the closure: the i-th place corresponds to the
+    /// i-th field of the state. We only have this information for the local
+    /// closures (the vector is empty otherwise).
+    pub captures: Vec<CapturedPlace>,
+    /// Is this a `move` closure?
+    pub is_move: bool,
+    /// The function we generate for the `call_mut` method when the closure implements `Fn`
+    /// (see [crate::transform::insert_closure_shims]).
+    pub call_mut_shim: Option<FunDeclId::Id>,
+    /// The function we generate for the `call_once` method when the closure implements `Fn`
+    /// or `FnMut`.
+    pub call_once_shim: Option<FunDeclId::Id>,
 }
 
 /// A function signature.
@@ -678,6 +1043,9 @@ pub struct ClosureInfo {
 pub struct FunSig {
     /// Is the function unsafe or not
     pub is_unsafe: bool,
+    /// Is the function a `const fn` (this includes the methods of the `const`
+    /// trait impls)
+    pub is_const: bool,
     /// `true` if the signature is for a closure.
     ///
     /// Importantly: if the signature is for a closure, then:
@@ -693,4 +1061,11 @@ pub struct FunSig {
     pub parent_params_info: Option<ParamsInfo>,
     pub inputs: Vec<Ty>,
     pub output: Ty,
+    /// The type aliases the user wrote for the types of the inputs (see
+    /// [TypeAliasRef]). This is empty if we don't know them (for the closures,
+    /// and for the functions of the other crates), otherwise this has the same
+    /// length as [FunSig::inputs].
+    pub input_aliases: Vec<Option<TypeAliasRef>>,
+    /// The type alias the user wrote for the type of the output.
+    pub output_alias: Option<TypeAliasRef>,
 }
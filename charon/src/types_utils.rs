@@ -2,6 +2,7 @@
 use crate::assumed::get_name_from_type_id;
 use crate::common::TAB_INCR;
 use crate::formatter::{AstFormatter, FmtCtx};
+use crate::hash_cons::HashConsed;
 use crate::types::*;
 use crate::values::*;
 use hax_frontend_exporter as hax;
@@ -33,6 +34,7 @@ impl Region {
         match self {
             Region::Static => "'static".to_string(),
             Region::BVar(grid, id) => ctx.format_object((*grid, *id)),
+            Region::Body(id) => format!("'?{}", id.index()),
             Region::Erased => "'_".to_string(),
             Region::Unknown => "'_UNKNOWN_".to_string(),
         }
@@ -40,8 +42,13 @@ impl Region {
 }
 
 impl TypeVar {
-    pub fn new(index: TypeVarId::Id, name: String) -> TypeVar {
-        TypeVar { index, name }
+    pub fn new(index: TypeVarId::Id, name: String, is_sized: bool) -> TypeVar {
+        TypeVar {
+            index,
+            name,
+            is_sized,
+            default: None,
+        }
     }
 }
 
@@ -90,6 +97,35 @@ impl GenericParams {
         }
     }
 
+    /// The generic arguments which instantiate the parameters with themselves, with erased
+    /// regions (this is useful to refer to an item from a body which has the same generics).
+    pub fn identity_args(&self) -> GenericArgs {
+        GenericArgs::new(
+            self.regions.iter().map(|_| Region::Erased).collect(),
+            self.types
+                .iter()
+                .map(|var| TyKind::TypeVar(var.index).into_ty())
+                .collect(),
+            self.const_generics
+                .iter()
+                .map(|var| ConstGeneric::Var(var.index))
+                .collect(),
+            self.trait_clauses
+                .iter()
+                .map(|clause| {
+                    TraitRef::new(
+                        TraitInstanceId::Clause(clause.clause_id),
+                        GenericArgs::empty(),
+                        TraitDeclRef {
+                            trait_id: clause.trait_id,
+                            generics: clause.generics.clone(),
+                        },
+                    )
+                })
+                .collect(),
+        )
+    }
+
     pub fn fmt_with_ctx<C>(&self, ctx: &C) -> String
     where
         C: AstFormatter,
@@ -152,6 +188,27 @@ impl GenericParams {
         }
         (params, clauses)
     }
+
+    /// Format the parameters which are not inherited from `parent` (for
+    /// instance, the parameters specific to a generic associated type).
+    /// We ignore the trait clauses.
+    pub fn fmt_own_params(&self, parent: &GenericParams) -> String {
+        let mut params = Vec::new();
+        for x in self.regions.iter().skip(parent.regions.len()) {
+            params.push(x.to_string());
+        }
+        for x in self.types.iter().skip(parent.types.len()) {
+            params.push(x.to_string());
+        }
+        for x in self.const_generics.iter().skip(parent.const_generics.len()) {
+            params.push(x.to_string());
+        }
+        if params.is_empty() {
+            "".to_string()
+        } else {
+            format!("<{}>", params.join(", "))
+        }
+    }
 }
 
 /// [num_parent_clauses]: we store in the definitions all the clauses
@@ -197,17 +254,21 @@ impl TraitTypeConstraint {
         C: AstFormatter,
     {
         let trait_ref = self.trait_ref.fmt_with_ctx(ctx);
+        let generics = self.generics.fmt_with_ctx(ctx);
         let ty = self.ty.fmt_with_ctx(ctx);
-        format!("{}::{} = {}", trait_ref, self.type_name, ty)
+        format!("{}::{}{} = {}", trait_ref, self.type_name, generics, ty)
     }
 }
 
 impl Predicates {
     pub fn is_empty(&self) -> bool {
+        // We ignore the regions graph, which is derived from the other
+        // predicates and from the types of the declaration
         let Predicates {
             regions_outlive,
             types_outlive,
             trait_type_constraints,
+            regions_graph: _,
         } = self;
         regions_outlive.is_empty() && types_outlive.is_empty() && trait_type_constraints.is_empty()
     }
@@ -279,50 +340,56 @@ where
 }
 
 impl GenericArgs {
-    pub fn len(&self) -> usize {
-        let GenericArgs {
+    pub fn new(
+        regions: Vec<Region>,
+        types: Vec<Ty>,
+        const_generics: Vec<ConstGeneric>,
+        trait_refs: Vec<TraitRef>,
+    ) -> Self {
+        GenericArgs(HashConsed::new(GenericArgsData {
             regions,
             types,
             const_generics,
             trait_refs,
-        } = self;
-        regions.len() + types.len() + const_generics.len() + trait_refs.len()
+        }))
     }
 
-    pub fn is_empty(&self) -> bool {
-        self.len() == 0
+    pub fn data(&self) -> &GenericArgsData {
+        self.0.inner()
     }
 
-    pub fn empty() -> Self {
-        GenericArgs {
-            regions: Vec::new(),
-            types: Vec::new(),
-            const_generics: Vec::new(),
-            trait_refs: Vec::new(),
-        }
+    /// Shared version of [GenericArgs::with_data_mut] (this is convenient for
+    /// the visitors, which are generic in the borrows).
+    pub fn with_data<R>(&self, f: impl FnOnce(&GenericArgsData) -> R) -> R {
+        f(self.data())
     }
 
-    pub fn new_from_types(types: Vec<Ty>) -> Self {
-        GenericArgs {
-            regions: Vec::new(),
-            types,
-            const_generics: Vec::new(),
-            trait_refs: Vec::new(),
-        }
+    /// Modify the generic arguments. Because they are hash-consed, this builds
+    /// new generic arguments.
+    pub fn with_data_mut<R>(&mut self, f: impl FnOnce(&mut GenericArgsData) -> R) -> R {
+        self.0.with_inner_mut(f)
     }
 
-    pub fn new(
-        regions: Vec<Region>,
-        types: Vec<Ty>,
-        const_generics: Vec<ConstGeneric>,
-        trait_refs: Vec<TraitRef>,
-    ) -> Self {
-        GenericArgs {
+    pub fn len(&self) -> usize {
+        let GenericArgsData {
             regions,
             types,
             const_generics,
             trait_refs,
-        }
+        } = self.data();
+        regions.len() + types.len() + const_generics.len() + trait_refs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn empty() -> Self {
+        GenericArgs::new(Vec::new(), Vec::new(), Vec::new(), Vec::new())
+    }
+
+    pub fn new_from_types(types: Vec<Ty>) -> Self {
+        GenericArgs::new(Vec::new(), types, Vec::new(), Vec::new())
     }
 
     /// Return the same generics, but where we pop the first type arguments.
@@ -330,23 +397,23 @@ impl GenericArgs {
     /// because the first type argument is the type for which the trait is
     /// implemented.
     pub fn pop_first_type_arg(&self) -> (Ty, Self) {
-        let GenericArgs {
+        let GenericArgsData {
             regions,
             types,
             const_generics,
             trait_refs,
-        } = self;
+        } = self.data();
         let mut it = types.iter();
         let ty = it.next().unwrap().clone();
         let types = it.cloned().collect();
         (
             ty,
-            GenericArgs {
-                regions: regions.clone(),
+            GenericArgs::new(
+                regions.clone(),
                 types,
-                const_generics: const_generics.clone(),
-                trait_refs: trait_refs.clone(),
-            },
+                const_generics.clone(),
+                trait_refs.clone(),
+            ),
         )
     }
 
@@ -355,12 +422,12 @@ impl GenericArgs {
         C: AstFormatter,
     {
         let mut params = Vec::new();
-        let GenericArgs {
+        let GenericArgsData {
             regions,
             types,
             const_generics,
             trait_refs,
-        } = self;
+        } = self.data();
         for x in regions {
             params.push(x.fmt_with_ctx(ctx));
         }
@@ -392,12 +459,12 @@ impl GenericArgs {
         C: AstFormatter,
     {
         let mut params = Vec::new();
-        let GenericArgs {
+        let GenericArgsData {
             regions,
             types,
             const_generics,
             trait_refs,
-        } = self;
+        } = self.data();
         for x in regions {
             params.push(x.fmt_with_ctx(ctx));
         }
@@ -426,15 +493,50 @@ impl GenericArgs {
     }
 }
 
+/// Format a trait clause, given the formatted clause id. We use this for the
+/// [TraitClause] and the clauses we manipulate during the translation, which
+/// are identified by a [TraitInstanceId].
+pub(crate) fn fmt_trait_clause_with_ctx<C>(
+    ctx: &C,
+    clause_id: &str,
+    trait_id: TraitDeclId::Id,
+    bound_regions: &RegionId::Vector<RegionVar>,
+    generics: &GenericArgs,
+    is_const: bool,
+) -> String
+where
+    C: AstFormatter,
+{
+    let trait_id = ctx.format_object(trait_id);
+    let constness = if is_const { "~const " } else { "" };
+    if bound_regions.is_empty() {
+        let generics = generics.fmt_with_ctx(ctx);
+        format!("[{clause_id}]: {constness}{trait_id}{generics}")
+    } else {
+        // The regions bound by the clause are only visible in the generics
+        let regions = bound_regions
+            .iter()
+            .map(|r| r.to_string())
+            .collect::<Vec<String>>()
+            .join(", ");
+        let generics = generics.fmt_with_ctx(&ctx.push_bound_regions(bound_regions));
+        format!("[{clause_id}]: for<{regions}> {constness}{trait_id}{generics}")
+    }
+}
+
 impl TraitClause {
     pub fn fmt_with_ctx<C>(&self, ctx: &C) -> String
     where
         C: AstFormatter,
     {
-        let clause_id = ctx.format_object(self.clause_id);
-        let trait_id = ctx.format_object(self.trait_id);
-        let generics = self.generics.fmt_with_ctx(ctx);
-        format!("[{clause_id}]: {trait_id}{generics}")
+        fmt_trait_clause_with_ctx(
+            ctx,
+            &ctx.format_object(self.clause_id),
+            self.trait_id,
+            &self.bound_regions,
+            &self.generics,
+            self.is_const,
+        )
     }
 }
 
@@ -462,6 +564,17 @@ impl TraitInstanceId {
                 format!("({id}::{type_name}::[{clause}])")
             }
             TraitInstanceId::TraitImpl(id) => ctx.format_object(*id),
+            TraitInstanceId::Specializable {
+                impl_id,
+                specializing_impls,
+            } => {
+                let impls = specializing_impls
+                    .iter()
+                    .map(|id| ctx.format_object(*id))
+                    .collect::<Vec<String>>()
+                    .join(", ");
+                format!("{}(specialized by: [{impls}])", ctx.format_object(*impl_id))
+            }
             TraitInstanceId::Clause(id) => ctx.format_object(*id),
             TraitInstanceId::BuiltinOrAuto(id) => ctx.format_object(*id),
             TraitInstanceId::FnPointer(box ty) => {
@@ -487,6 +600,34 @@ impl TraitInstanceId {
 }
 
 impl TraitRef {
+    pub fn new(
+        trait_id: TraitInstanceId,
+        generics: GenericArgs,
+        trait_decl_ref: TraitDeclRef,
+    ) -> Self {
+        TraitRef(HashConsed::new(TraitRefData {
+            trait_id,
+            generics,
+            trait_decl_ref,
+        }))
+    }
+
+    pub fn data(&self) -> &TraitRefData {
+        self.0.inner()
+    }
+
+    /// Shared version of [TraitRef::with_data_mut] (this is convenient for
+    /// the visitors, which are generic in the borrows).
+    pub fn with_data<R>(&self, f: impl FnOnce(&TraitRefData) -> R) -> R {
+        f(self.data())
+    }
+
+    /// Modify the trait reference. Because the trait references are
+    /// hash-consed, this builds a new trait reference.
+    pub fn with_data_mut<R>(&mut self, f: impl FnOnce(&mut TraitRefData) -> R) -> R {
+        self.0.with_inner_mut(f)
+    }
+
     pub fn fmt_with_ctx<C>(&self, ctx: &C) -> String
     where
         C: AstFormatter,
@@ -517,12 +658,14 @@ impl TypeDecl {
         variant_id: Option<VariantId::Id>,
     ) -> Result<&FieldId::Vector<Field>, ()> {
         match &self.kind {
-            TypeDeclKind::Enum(variants) => Ok(&variants.get(variant_id.unwrap()).unwrap().fields),
+            TypeDeclKind::Enum(variants, ..) => {
+                Ok(&variants.get(variant_id.unwrap()).unwrap().fields)
+            }
             TypeDeclKind::Struct(fields) => {
                 assert!(variant_id.is_none());
                 Ok(fields)
             }
-            TypeDeclKind::Opaque => {
+            TypeDeclKind::Opaque | TypeDeclKind::ImplTrait { .. } => {
                 unreachable!("Opaque type")
             }
             TypeDeclKind::Error(_) => Err(()),
@@ -563,7 +706,7 @@ impl TypeDecl {
                     )
                 }
             }
-            TypeDeclKind::Enum(variants) => {
+            TypeDeclKind::Enum(variants, ..) => {
                 let variants: Vec<String> = variants
                     .iter()
                     .map(|v| format!("|  {}", v.fmt_with_ctx(ctx)))
@@ -577,6 +720,21 @@ impl TypeDecl {
             TypeDeclKind::Opaque => {
                 format!("opaque type {}{params}{preds}", self.name.fmt_with_ctx(ctx))
             }
+            TypeDeclKind::ImplTrait { bounds, hidden_ty } => {
+                let bounds = bounds
+                    .iter()
+                    .map(|b| b.fmt_with_ctx(ctx))
+                    .collect::<Vec<String>>()
+                    .join(" + ");
+                let hidden_ty = match hidden_ty {
+                    None => "".to_string(),
+                    Some(ty) => format!(" = {}", ty.fmt_with_ctx(ctx)),
+                };
+                format!(
+                    "opaque type {}{params}{preds} : impl {bounds}{hidden_ty}",
+                    self.name.fmt_with_ctx(ctx)
+                )
+            }
             TypeDeclKind::Error(msg) => {
                 format!(
                     "opaque type {}{params}{preds} = ERROR({msg})",
@@ -587,6 +745,27 @@ impl TypeDecl {
     }
 }
 
+impl ImplTraitBound {
+    pub fn fmt_with_ctx<C>(&self, ctx: &C) -> String
+    where
+        C: AstFormatter,
+    {
+        let trait_id = ctx.format_object(self.trait_id);
+        let mut params = Vec::new();
+        if !self.generics.is_empty() {
+            params.push(self.generics.fmt_with_ctx_no_brackets(ctx));
+        }
+        for (name, ty) in &self.type_constraints {
+            params.push(format!("{name} = {}", ty.fmt_with_ctx(ctx)));
+        }
+        if params.is_empty() {
+            trait_id
+        } else {
+            format!("{trait_id}<{}>", params.join(", "))
+        }
+    }
+}
+
 impl Variant {
     pub fn fmt_with_ctx<C>(&self, ctx: &C) -> String
     where
@@ -681,6 +860,27 @@ impl IntegerTy {
             IntegerTy::U128 => size_of::<u128>(),
         }
     }
+
+    /// Return the size (in bytes) of an integer of the proper type, on the
+    /// given target. Contrary to [IntegerTy::size], this doesn't depend on
+    /// the machine on which Charon is run.
+    pub fn target_size(&self, target: &TargetInfo) -> usize {
+        match self {
+            IntegerTy::Isize | IntegerTy::Usize => (target.pointer_width / 8) as usize,
+            _ => self.size(),
+        }
+    }
+}
+
+impl TargetInfo {
+    pub fn from_session(sess: &rustc_session::Session) -> Self {
+        TargetInfo {
+            target_triple: sess.opts.target_triple.triple().to_string(),
+            pointer_width: sess.target.pointer_width,
+            is_little_endian: matches!(sess.target.endian, rustc_target::abi::Endian::Little),
+            c_int_width: sess.target.c_int_width.parse().unwrap(),
+        }
+    }
 }
 
 impl TypeVarId::Id {
@@ -818,15 +1018,88 @@ impl ConstGeneric {
             ConstGeneric::Var(id) => ctx.format_object(*id),
             ConstGeneric::Value(v) => v.to_string(),
             ConstGeneric::Global(id) => ctx.format_object(*id),
+            ConstGeneric::TraitConst(trait_ref, name) => {
+                format!("{}::{name}", trait_ref.fmt_with_ctx(ctx))
+            }
         }
     }
 }
 
+impl TyKind {
+    pub fn into_ty(self) -> Ty {
+        Ty::new(self)
+    }
+}
+
+impl Ty {
+    pub fn new(kind: TyKind) -> Self {
+        Ty(HashConsed::new(kind))
+    }
+
+    pub fn kind(&self) -> &TyKind {
+        self.0.inner()
+    }
+
+    /// Shared version of [Ty::with_kind_mut] (this is convenient for the
+    /// visitors, which are generic in the borrows).
+    pub fn with_kind<R>(&self, f: impl FnOnce(&TyKind) -> R) -> R {
+        f(self.kind())
+    }
+
+    /// Modify the kind of the type. Because the types are hash-consed, this
+    /// builds a new type.
+    pub fn with_kind_mut<R>(&mut self, f: impl FnOnce(&mut TyKind) -> R) -> R {
+        self.0.with_inner_mut(f)
+    }
+}
+
+/// We can directly use the methods of [TyKind] on a [Ty]
+impl std::ops::Deref for Ty {
+    type Target = TyKind;
+    fn deref(&self) -> &TyKind {
+        self.kind()
+    }
+}
+
+impl std::fmt::Debug for Ty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.kind().fmt(f)
+    }
+}
+
+/// We can directly read the fields of [GenericArgsData] on [GenericArgs]
+impl std::ops::Deref for GenericArgs {
+    type Target = GenericArgsData;
+    fn deref(&self) -> &GenericArgsData {
+        self.data()
+    }
+}
+
+impl std::fmt::Debug for GenericArgs {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.data().fmt(f)
+    }
+}
+
+/// We can directly read the fields of [TraitRefData] on [TraitRef]
+impl std::ops::Deref for TraitRef {
+    type Target = TraitRefData;
+    fn deref(&self) -> &TraitRefData {
+        self.data()
+    }
+}
+
+impl std::fmt::Debug for TraitRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.data().fmt(f)
+    }
+}
+
 impl Ty {
     /// Return true if it is actually unit (i.e.: 0-tuple)
     pub fn is_unit(&self) -> bool {
-        match self {
-            Ty::Adt(TypeId::Tuple, args) => {
+        match self.kind() {
+            TyKind::Adt(TypeId::Tuple, args) => {
                 assert!(args.regions.is_empty());
                 assert!(args.const_generics.is_empty());
                 args.types.is_empty()
@@ -837,27 +1110,27 @@ impl Ty {
 
     /// Return the unit type
     pub fn mk_unit() -> Ty {
-        Ty::Adt(TypeId::Tuple, GenericArgs::empty())
+        TyKind::Adt(TypeId::Tuple, GenericArgs::empty()).into_ty()
     }
 
     /// Return true if this is a scalar type
     pub fn is_scalar(&self) -> bool {
-        match self {
-            Ty::Literal(kind) => kind.is_integer(),
+        match self.kind() {
+            TyKind::Literal(kind) => kind.is_integer(),
             _ => false,
         }
     }
 
     pub fn is_unsigned_scalar(&self) -> bool {
-        match self {
-            Ty::Literal(LiteralTy::Integer(kind)) => kind.is_unsigned(),
+        match self.kind() {
+            TyKind::Literal(LiteralTy::Integer(kind)) => kind.is_unsigned(),
             _ => false,
         }
     }
 
     pub fn is_signed_scalar(&self) -> bool {
-        match self {
-            Ty::Literal(LiteralTy::Integer(kind)) => kind.is_signed(),
+        match self.kind() {
+            TyKind::Literal(LiteralTy::Integer(kind)) => kind.is_signed(),
             _ => false,
         }
     }
@@ -871,8 +1144,8 @@ impl Ty {
     where
         C: AstFormatter,
     {
-        match self {
-            Ty::Adt(id, generics) => {
+        match self.kind() {
+            TyKind::Adt(id, generics) => {
                 let adt_ident = id.fmt_with_ctx(ctx);
 
                 if id.is_tuple() {
@@ -884,10 +1157,10 @@ impl Ty {
                     format!("{adt_ident}{generics}")
                 }
             }
-            Ty::TypeVar(id) => ctx.format_object(*id),
-            Ty::Literal(kind) => kind.to_string(),
-            Ty::Never => "!".to_string(),
-            Ty::Ref(r, ty, kind) => match kind {
+            TyKind::TypeVar(id) => ctx.format_object(*id),
+            TyKind::Literal(kind) => kind.to_string(),
+            TyKind::Never => "!".to_string(),
+            TyKind::Ref(r, ty, kind) => match kind {
                 RefKind::Mut => {
                     format!("&{} mut ({})", r.fmt_with_ctx(ctx), ty.fmt_with_ctx(ctx))
                 }
@@ -895,14 +1168,18 @@ impl Ty {
                     format!("&{} ({})", r.fmt_with_ctx(ctx), ty.fmt_with_ctx(ctx))
                 }
             },
-            Ty::RawPtr(ty, kind) => match kind {
+            TyKind::RawPtr(ty, kind) => match kind {
                 RefKind::Mut => format!("*const {}", ty.fmt_with_ctx(ctx)),
                 RefKind::Shared => format!("*mut {}", ty.fmt_with_ctx(ctx)),
             },
-            Ty::TraitType(trait_ref, name) => {
-                format!("{}::{name}", trait_ref.fmt_with_ctx(ctx),)
+            TyKind::TraitType(trait_ref, name, generics) => {
+                format!(
+                    "{}::{name}{}",
+                    trait_ref.fmt_with_ctx(ctx),
+                    generics.fmt_with_ctx(ctx)
+                )
             }
-            Ty::Arrow(regions, inputs, box output) => {
+            TyKind::Arrow(regions, inputs, output) => {
                 // Update the bound regions
                 let ctx = &ctx.push_bound_regions(regions);
 
@@ -930,13 +1207,20 @@ impl Ty {
                     format!("fn{regions}({inputs}) -> {output}")
                 }
             }
+            TyKind::FnDef(id, generics) => {
+                format!(
+                    "{{{}{}}}",
+                    ctx.format_object(*id),
+                    generics.fmt_with_ctx(ctx)
+                )
+            }
         }
     }
 
     /// Return true if the type is Box
     pub fn is_box(&self) -> bool {
-        match self {
-            Ty::Adt(TypeId::Assumed(AssumedTy::Box), generics) => {
+        match self.kind() {
+            TyKind::Adt(TypeId::Assumed(AssumedTy::Box), generics) => {
                 assert!(generics.regions.is_empty());
                 assert!(generics.types.len() == 1);
                 assert!(generics.const_generics.is_empty());
@@ -947,8 +1231,8 @@ impl Ty {
     }
 
     pub fn as_box(&self) -> Option<&Ty> {
-        match self {
-            Ty::Adt(TypeId::Assumed(AssumedTy::Box), generics) => {
+        match self.kind() {
+            TyKind::Adt(TypeId::Assumed(AssumedTy::Box), generics) => {
                 assert!(generics.regions.is_empty());
                 assert!(generics.types.len() == 1);
                 assert!(generics.const_generics.is_empty());
@@ -961,9 +1245,10 @@ impl Ty {
 
 impl std::fmt::Display for Region {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
-        match self {
+        match self.kind() {
             Region::Static => write!(f, "'static"),
             Region::BVar(grid, id) => write!(f, "'_{}_{id}", grid.index),
+            Region::Body(id) => write!(f, "'?{id}"),
             Region::Erased => write!(f, "'_"),
             Region::Unknown => write!(f, "'_UNKNOWN_"),
         }
@@ -973,18 +1258,19 @@ impl std::fmt::Display for Region {
 impl Ty {
     // TODO: reimplement this with visitors
     pub fn contains_never(&self) -> bool {
-        match self {
-            Ty::Never => true,
-            Ty::Adt(_, args) => {
+        match self.kind() {
+            TyKind::Never => true,
+            TyKind::Adt(_, args) => {
                 // For the trait type case: we are checking the projected type,
                 // so we don't need to explore the trait ref
                 args.types.iter().any(|ty| ty.contains_never())
             }
-            Ty::TraitType(..) | Ty::TypeVar(_) | Ty::Literal(_) => false,
-            Ty::Ref(_, ty, _) | Ty::RawPtr(ty, _) => ty.contains_never(),
-            Ty::Arrow(_, inputs, box output) => {
+            TyKind::TraitType(..) | TyKind::TypeVar(_) | TyKind::Literal(_) => false,
+            TyKind::Ref(_, ty, _) | TyKind::RawPtr(ty, _) => ty.contains_never(),
+            TyKind::Arrow(_, inputs, output) => {
                 inputs.iter().any(|ty| ty.contains_never()) || output.contains_never()
             }
+            TyKind::FnDef(..) => false,
         }
     }
 }
@@ -1062,13 +1348,13 @@ impl TySubst {
 
     fn unify_types(&mut self, src: &Ty, tgt: &Ty) -> Result<(), ()> {
         use Result::*;
-        use Ty::*;
+        use TyKind::*;
 
-        if let TypeVar(v) = src {
+        if let TypeVar(v) = src.kind() {
             check_ok_return!(self.type_vars_map.insert(*v, tgt.clone()).is_none());
         }
 
-        match (src, tgt) {
+        match (src.kind(), tgt.kind()) {
             (Adt(src_id, src_args), Adt(tgt_id, tgt_args)) => {
                 check_ok!(src_id == tgt_id);
                 self.unify_args(src_args, tgt_args)
@@ -1077,14 +1363,18 @@ impl TySubst {
                 check_ok_return!(src == tgt);
             }
             (Never, Never) => Ok(()),
-            (Ref(src_r, box src_ty, src_kind), Ref(tgt_r, box tgt_ty, tgt_kind)) => {
+            (FnDef(src_id, src_args), FnDef(tgt_id, tgt_args)) => {
+                check_ok!(src_id == tgt_id);
+                self.unify_args(src_args, tgt_args)
+            }
+            (Ref(src_r, src_ty, src_kind), Ref(tgt_r, tgt_ty, tgt_kind)) => {
                 if !self.ignore_regions {
                     self.unify_regions(src_r, tgt_r)?;
                 }
                 self.unify_types(src_ty, tgt_ty)?;
                 check_ok_return!(src_kind == tgt_kind);
             }
-            (RawPtr(box src_ty, src_kind), RawPtr(box tgt_ty, tgt_kind)) => {
+            (RawPtr(src_ty, src_kind), RawPtr(tgt_ty, tgt_kind)) => {
                 self.unify_types(src_ty, tgt_ty)?;
                 check_ok_return!(src_kind == tgt_kind);
             }
@@ -1144,7 +1434,7 @@ impl TySubst {
     ) -> Result<Self, ()> {
         let mut s = TySubst::new();
         for v in fixed_type_vars {
-            s.type_vars_map.insert(v, Ty::TypeVar(v));
+            s.type_vars_map.insert(v, TyKind::TypeVar(v).into_ty());
         }
         for v in fixed_const_generic_vars {
             s.const_generics_map.insert(v, ConstGeneric::Var(v));
@@ -1168,6 +1458,7 @@ impl MutTypeVisitor for TraitInstanceIdSelfReplacer {
             TraitInstanceId::ParentClause(box id, _, _)
             | TraitInstanceId::ItemClause(box id, _, _, _) => self.visit_trait_instance_id(id),
             TraitInstanceId::TraitImpl(_)
+            | TraitInstanceId::Specializable { .. }
             | TraitInstanceId::Clause(_)
             | TraitInstanceId::BuiltinOrAuto(_)
             | TraitInstanceId::FnPointer(_)
@@ -1199,7 +1490,11 @@ pub trait TypeVisitor {
     }
 
     fn default_visit_ty(&mut self, ty: &Ty) {
-        use Ty::*;
+        ty.with_kind(|kind| self.visit_ty_kind(kind))
+    }
+
+    fn visit_ty_kind(&mut self, ty: &TyKind) {
+        use TyKind::*;
         match ty {
             Adt(id, args) => self.visit_ty_adt(id, args),
             TypeVar(vid) => self.visit_ty_type_var(vid),
@@ -1207,16 +1502,21 @@ pub trait TypeVisitor {
             Never => self.visit_ty_never(),
             Ref(r, ty, rk) => self.visit_ty_ref(r, ty, rk),
             RawPtr(ty, rk) => self.visit_ty_raw_ptr(ty, rk),
-            TraitType(trait_ref, _name) => {
+            TraitType(trait_ref, _name, generics) => {
                 self.visit_trait_ref(trait_ref);
+                self.visit_generic_args(generics);
+            }
+            Arrow(regions, inputs, output) => self.visit_arrow(regions, inputs, output),
+            FnDef(id, generics) => {
+                self.visit_fun_decl_id(id);
+                self.visit_generic_args(generics);
             }
-            Arrow(regions, inputs, box output) => self.visit_arrow(regions, inputs, output),
         }
     }
 
     fn visit_region(&mut self, r: &Region) {
         match r {
-            Region::Erased | Region::Static | Region::Unknown => (),
+            Region::Erased | Region::Static | Region::Body(_) | Region::Unknown => (),
             Region::BVar(grid, id) => {
                 self.visit_region_bvar(grid, id)
             },
@@ -1260,12 +1560,12 @@ pub trait TypeVisitor {
 
     fn visit_ty_never(&mut self) {}
 
-    fn visit_ty_ref(&mut self, r: &Region, ty: &Box<Ty>, _rk: &RefKind) {
+    fn visit_ty_ref(&mut self, r: &Region, ty: &Ty, _rk: &RefKind) {
         self.visit_region(r);
         self.visit_ty(ty);
     }
 
-    fn visit_ty_raw_ptr(&mut self, ty: &Box<Ty>, _rk: &RefKind) {
+    fn visit_ty_raw_ptr(&mut self, ty: &Ty, _rk: &RefKind) {
         self.visit_ty(ty);
     }
 
@@ -1286,6 +1586,7 @@ pub trait TypeVisitor {
         use ConstGeneric::*;
         match cg {
             Global(id) => self.visit_global_decl_id(id),
+            TraitConst(trait_ref, _name) => self.visit_trait_ref(trait_ref),
             Var(id) => self.visit_const_generic_var_id(id),
             Value(lit) => self.visit_literal(lit),
         }
@@ -1294,11 +1595,17 @@ pub trait TypeVisitor {
     fn visit_type_var(&mut self, ty: &TypeVar) {
         self.visit_type_var_id(&ty.index);
         // Ignoring the name
+        if let Some(default) = &ty.default {
+            self.visit_ty(default);
+        }
     }
 
     fn visit_const_generic_var(&mut self, cg: &ConstGenericVar) {
         self.visit_const_generic_var_id(&cg.index);
         // Ignoring the name and type
+        if let Some(default) = &cg.default {
+            self.visit_const_generic(default);
+        }
     }
 
     fn visit_global_decl_id(&mut self, _: &GlobalDeclId::Id) {}
@@ -1308,7 +1615,11 @@ pub trait TypeVisitor {
     fn visit_literal(&mut self, _: &Literal) {}
 
     fn visit_trait_ref(&mut self, tr: &TraitRef) {
-        let TraitRef {
+        tr.with_data(|tr| self.visit_trait_ref_data(tr))
+    }
+
+    fn visit_trait_ref_data(&mut self, tr: &TraitRefData) {
+        let TraitRefData {
             trait_id,
             generics,
             trait_decl_ref,
@@ -1335,6 +1646,12 @@ pub trait TypeVisitor {
         match id {
             TraitInstanceId::SelfId => (),
             TraitInstanceId::TraitImpl(id) => self.visit_trait_impl_id(id),
+            TraitInstanceId::Specializable { impl_id, specializing_impls } => {
+                self.visit_trait_impl_id(impl_id);
+                for id in specializing_impls {
+                    self.visit_trait_impl_id(id);
+                }
+            },
             TraitInstanceId::BuiltinOrAuto(id) => self.visit_trait_decl_id(id),
             TraitInstanceId::Clause(id) => self.visit_trait_clause_id(id),
             TraitInstanceId::ParentClause(box id, decl_id, clause_id) => {
@@ -1367,6 +1684,10 @@ pub trait TypeVisitor {
     }
 
     fn visit_generic_args(&mut self, g: &GenericArgs) {
+        g.with_data(|g| self.visit_generic_args_data(g))
+    }
+
+    fn visit_generic_args_data(&mut self, g: &GenericArgsData) {
         for r in &g.regions {
             self.visit_region(r)
         }
@@ -1397,7 +1718,14 @@ pub trait TypeVisitor {
     }
 
     fn visit_trait_clause(&mut self, c: &TraitClause) {
-        let TraitClause { clause_id, meta: _, trait_id, generics } = c;
+        let TraitClause {
+            clause_id,
+            meta: _,
+            trait_id,
+            bound_regions: _,
+            generics,
+            is_const: _,
+        } = c;
         self.visit_trait_clause_id(clause_id);
         self.visit_trait_decl_id(trait_id);
         self.visit_generic_args(generics);
@@ -1408,6 +1736,7 @@ pub trait TypeVisitor {
             regions_outlive,
             types_outlive,
             trait_type_constraints,
+            regions_graph: _,
         } = preds;
         for p in regions_outlive {
             self.visit_region(&p.0);
@@ -1420,10 +1749,12 @@ pub trait TypeVisitor {
         for TraitTypeConstraint {
             trait_ref,
             type_name: _,
+            generics,
             ty,
         } in trait_type_constraints
         {
             self.visit_trait_ref(trait_ref);
+            self.visit_generic_args(generics);
             self.visit_ty(ty);
         }
     }
@@ -1431,6 +1762,7 @@ pub trait TypeVisitor {
     fn visit_fun_sig(&mut self, sig: &FunSig) {
         let FunSig {
             is_unsafe : _,
+            is_const: _,
             is_closure: _,
             closure_info,
             generics,
@@ -1438,6 +1770,8 @@ pub trait TypeVisitor {
             parent_params_info: _,
             inputs,
             output,
+            input_aliases: _,
+            output_alias: _,
         } = sig;
 
         if let Some(info) = closure_info {
@@ -1454,6 +1788,10 @@ pub trait TypeVisitor {
         let ClosureInfo {
             kind: _,
             state,
+            captures: _,
+            is_move: _,
+            call_mut_shim: _,
+            call_once_shim: _,
         } = info;
 
         for ty in state { self.visit_ty(ty); }
@@ -1464,8 +1802,9 @@ pub trait TypeVisitor {
     }
 
     fn visit_trait_type_constraint(&mut self, x : &TraitTypeConstraint) {
-        let TraitTypeConstraint { trait_ref, type_name: _, ty } = x;
+        let TraitTypeConstraint { trait_ref, type_name: _, generics, ty } = x;
         self.visit_trait_ref(trait_ref);
+        self.visit_generic_args(generics);
         self.visit_ty(ty);
     }
 
@@ -1481,7 +1820,8 @@ impl FunSig {
     {
         let ctx = &ctx.set_generics(&self.generics);
 
-        // Unsafe keyword
+        // Const and unsafe keywords
+        let const_kw = if self.is_const { "const " } else { "" };
         let unsafe_kw = if self.is_unsafe {
             "unsafe ".to_string()
         } else {
@@ -1516,6 +1856,6 @@ impl FunSig {
         );
 
         // Put everything together
-        format!("{unsafe_kw}fn{params}({args}){ret_ty}{clauses}",)
+        format!("{const_kw}{unsafe_kw}fn{params}({args}){ret_ty}{clauses}",)
     }
 }
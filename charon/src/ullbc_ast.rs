@@ -32,10 +32,18 @@ pub enum RawStatement {
     Assign(Place, Rvalue),
     FakeRead(Place),
     SetDiscriminant(Place, VariantId::Id),
-    /// We translate this to [crate::llbc_ast::RawStatement::Drop] in LLBC
+    /// Only present if the option `--keep-storage-statements` is set: we ignore
+    /// the `StorageLive` of the MIR otherwise.
+    StorageLive(VarId::Id),
+    /// We translate this to [crate::llbc_ast::RawStatement::Drop] in LLBC, unless
+    /// the option `--keep-storage-statements` is set.
     StorageDead(VarId::Id),
     /// We translate this to [crate::llbc_ast::RawStatement::Drop] in LLBC
     Deinit(Place),
+    MemCopy(MemCopy),
+    WriteBytes(WriteBytes),
+    /// A call to a domain-specific intrinsic, or to a built-in of the verifiers
+    Intrinsic(Intrinsic),
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -82,6 +90,7 @@ pub enum RawTerminator {
     Assert {
         cond: Operand,
         expected: bool,
+        kind: AssertKind,
         target: BlockId::Id,
     },
 }
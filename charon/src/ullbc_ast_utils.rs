@@ -25,6 +25,49 @@ impl SwitchTargets {
             }
         }
     }
+
+    /// Same as [SwitchTargets::get_targets], but with mutable references.
+    pub fn get_targets_mut(&mut self) -> Vec<&mut BlockId::Id> {
+        match self {
+            SwitchTargets::If(then_tgt, else_tgt) => vec![then_tgt, else_tgt],
+            SwitchTargets::SwitchInt(_, targets, otherwise) => targets
+                .iter_mut()
+                .map(|(_, target)| target)
+                .chain(std::iter::once(otherwise))
+                .collect(),
+        }
+    }
+}
+
+impl RawTerminator {
+    /// The blocks the terminator may jump to, in the order in which they appear
+    /// in the terminator.
+    pub fn get_targets(&self) -> Vec<BlockId::Id> {
+        match self {
+            RawTerminator::Goto { target }
+            | RawTerminator::Drop { target, .. }
+            | RawTerminator::Call { target, .. }
+            | RawTerminator::Assert { target, .. } => vec![*target],
+            RawTerminator::Switch { targets, .. } => targets.get_targets(),
+            RawTerminator::Panic | RawTerminator::Return | RawTerminator::Unreachable => {
+                Vec::new()
+            }
+        }
+    }
+
+    /// Same as [RawTerminator::get_targets], but with mutable references.
+    pub fn get_targets_mut(&mut self) -> Vec<&mut BlockId::Id> {
+        match self {
+            RawTerminator::Goto { target }
+            | RawTerminator::Drop { target, .. }
+            | RawTerminator::Call { target, .. }
+            | RawTerminator::Assert { target, .. } => vec![target],
+            RawTerminator::Switch { targets, .. } => targets.get_targets_mut(),
+            RawTerminator::Panic | RawTerminator::Return | RawTerminator::Unreachable => {
+                Vec::new()
+            }
+        }
+    }
 }
 
 impl Statement {
@@ -58,12 +101,18 @@ impl Statement {
                 place.fmt_with_ctx(ctx),
                 variant_id
             ),
+            RawStatement::StorageLive(vid) => {
+                format!("@storage_live({})", vid.to_pretty_string())
+            }
             RawStatement::StorageDead(vid) => {
                 format!("@storage_dead({})", vid.to_pretty_string())
             }
             RawStatement::Deinit(place) => {
                 format!("@deinit({})", place.fmt_with_ctx(ctx))
             }
+            RawStatement::MemCopy(copy) => copy.fmt_with_ctx(ctx),
+            RawStatement::WriteBytes(write) => write.fmt_with_ctx(ctx),
+            RawStatement::Intrinsic(intrinsic) => intrinsic.fmt_with_ctx(ctx),
         }
     }
 }
@@ -106,6 +155,7 @@ impl Terminator {
             RawTerminator::Assert {
                 cond,
                 expected,
+                kind: _,
                 target,
             } => format!(
                 "assert({} == {}) -> bb{}",
@@ -202,7 +252,11 @@ impl BlockData {
             Rvalue::Repeat(op, _, _) => {
                 f(meta, nst, op);
             }
-            Rvalue::Global(..) | Rvalue::Discriminant(..) | Rvalue::Ref(_, _) | Rvalue::Len(..) => {
+            Rvalue::Global(..)
+            | Rvalue::Discriminant(..)
+            | Rvalue::Ref(_, _)
+            | Rvalue::Len(..)
+            | Rvalue::NullaryOp(..) => {
                 // No operands: nothing to do
             }
         }
@@ -225,10 +279,28 @@ impl BlockData {
                 }
                 RawStatement::FakeRead(_)
                 | RawStatement::SetDiscriminant(_, _)
+                | RawStatement::StorageLive(_)
                 | RawStatement::StorageDead(_)
                 | RawStatement::Deinit(_) => {
                     // No operands: nothing to do
                 }
+                RawStatement::MemCopy(MemCopy {
+                    src, dst, count, ..
+                }) => {
+                    f(meta, &mut nst, src);
+                    f(meta, &mut nst, dst);
+                    f(meta, &mut nst, count);
+                }
+                RawStatement::WriteBytes(WriteBytes { dst, val, count }) => {
+                    f(meta, &mut nst, dst);
+                    f(meta, &mut nst, val);
+                    f(meta, &mut nst, count);
+                }
+                RawStatement::Intrinsic(intrinsic) => {
+                    for arg in &mut intrinsic.args {
+                        f(meta, &mut nst, arg);
+                    }
+                }
             }
             // Add the statement to the vector of statements
             nst.push(st)
@@ -248,6 +320,7 @@ impl BlockData {
             RawTerminator::Assert {
                 cond,
                 expected: _,
+                kind: _,
                 target: _,
             } => {
                 f(meta, &mut nst, cond);
@@ -326,8 +399,12 @@ pub trait AstVisitor: crate::expressions::ExprVisitor {
             Assign(p, rv) => self.visit_assign(p, rv),
             FakeRead(p) => self.visit_fake_read(p),
             SetDiscriminant(p, vid) => self.visit_set_discriminant(p, vid),
+            StorageLive(vid) => self.visit_storage_live(vid),
             StorageDead(vid) => self.visit_storage_dead(vid),
             Deinit(p) => self.visit_deinit(p),
+            MemCopy(copy) => self.visit_mem_copy(copy),
+            WriteBytes(write) => self.visit_write_bytes(write),
+            Intrinsic(intrinsic) => self.visit_intrinsic(intrinsic),
         }
     }
 
@@ -344,6 +421,10 @@ pub trait AstVisitor: crate::expressions::ExprVisitor {
         self.visit_place(p);
     }
 
+    fn visit_storage_live(&mut self, vid: &VarId::Id) {
+        self.visit_var_id(vid);
+    }
+
     fn visit_storage_dead(&mut self, vid: &VarId::Id) {
         self.visit_var_id(vid);
     }
@@ -352,6 +433,25 @@ pub trait AstVisitor: crate::expressions::ExprVisitor {
         self.visit_place(p);
     }
 
+    fn visit_mem_copy(&mut self, copy: &MemCopy) {
+        self.visit_operand(&copy.src);
+        self.visit_operand(&copy.dst);
+        self.visit_operand(&copy.count);
+    }
+
+    fn visit_write_bytes(&mut self, write: &WriteBytes) {
+        self.visit_operand(&write.dst);
+        self.visit_operand(&write.val);
+        self.visit_operand(&write.count);
+    }
+
+    fn visit_intrinsic(&mut self, intrinsic: &Intrinsic) {
+        for arg in &intrinsic.args {
+            self.visit_operand(arg);
+        }
+        self.visit_place(&intrinsic.dest);
+    }
+
     fn visit_terminator(&mut self, st: &Terminator) {
         self.visit_meta(&st.meta);
         self.visit_raw_terminator(&st.content);
@@ -378,6 +478,7 @@ pub trait AstVisitor: crate::expressions::ExprVisitor {
             Assert {
                 cond,
                 expected,
+                kind: _,
                 target,
             } => {
                 self.visit_assert(cond, expected, target);
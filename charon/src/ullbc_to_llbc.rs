@@ -23,7 +23,7 @@
 use crate::expressions::Place;
 use crate::formatter::{Formatter, IntoFormatter};
 use crate::llbc_ast as tgt;
-use crate::meta::{combine_meta, Meta};
+use crate::meta::{combine_meta, combine_meta_iter, Meta};
 use crate::translate_ctx::TransCtx;
 use crate::ullbc_ast::FunDeclId;
 use crate::ullbc_ast::{self as src, GlobalDeclId};
@@ -34,6 +34,7 @@ use petgraph::algo::floyd_warshall::floyd_warshall;
 use petgraph::algo::toposort;
 use petgraph::graphmap::DiGraphMap;
 use petgraph::Direction;
+use rustc_data_structures::stack::ensure_sufficient_stack;
 use std::cmp::Ordering;
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::iter::FromIterator;
@@ -50,6 +51,9 @@ struct BlockInfo<'a> {
     /// code duplication is necessary, in the presence of "fused" match branches for
     /// instance, like in `match ... { Foo | Bar => { ... }}`).
     no_code_duplication: bool,
+    /// `keep_storage_statements`: if true, keep the `StorageLive` and `StorageDead`
+    /// statements, rather than ignoring the former and translating the latter to drops.
+    keep_storage_statements: bool,
     cfg: &'a CfgInfo,
     body: &'a src::ExprBody,
     exits_info: &'a ExitInfo,
@@ -66,6 +70,7 @@ fn get_block_targets(body: &src::ExprBody, block_id: src::BlockId::Id) -> Vec<sr
         | src::RawTerminator::Assert {
             cond: _,
             expected: _,
+            kind: _,
             target,
         } => {
             vec![*target]
@@ -205,7 +210,9 @@ fn build_cfg_partial_info_edges(
         } else {
             // Not a backward edge: insert the edge and explore
             cfg.cfg_no_be.add_edge(block_id, *tgt, ());
-            build_cfg_partial_info_edges(cfg, &ancestors, explored, body, *tgt);
+            ensure_sufficient_stack(|| {
+                build_cfg_partial_info_edges(cfg, &ancestors, explored, body, *tgt)
+            });
         }
     }
 
@@ -557,14 +564,16 @@ fn compute_loop_exit_candidates(
         // this node a potential exit.
         match filter_loop_parents(cfg, &parent_loops, child) {
             None => {
-                compute_loop_exit_candidates(
-                    cfg,
-                    explored,
-                    ordered_loops,
-                    loop_exits,
-                    parent_loops.clone(),
-                    child,
-                );
+                ensure_sufficient_stack(|| {
+                    compute_loop_exit_candidates(
+                        cfg,
+                        explored,
+                        ordered_loops,
+                        loop_exits,
+                        parent_loops.clone(),
+                        child,
+                    )
+                });
             }
             Some(fparent_loops) => {
                 // We filtered some parent loops: it means this child and its
@@ -595,14 +604,16 @@ fn compute_loop_exit_candidates(
                 );
 
                 // Explore, with the filtered parents
-                compute_loop_exit_candidates(
-                    cfg,
-                    explored,
-                    ordered_loops,
-                    loop_exits,
-                    fparent_loops.remaining_parents,
-                    child,
-                );
+                ensure_sufficient_stack(|| {
+                    compute_loop_exit_candidates(
+                        cfg,
+                        explored,
+                        ordered_loops,
+                        loop_exits,
+                        fparent_loops.remaining_parents,
+                        child,
+                    )
+                });
             }
         }
     }
@@ -969,11 +980,11 @@ fn compute_switch_exits_explore(
 
     // Find the next blocks, and their successors
     let children: Vec<src::BlockId::Id> = Vec::from_iter(cfg.cfg_no_be.neighbors(block_id));
-    let mut children_succs: Vec<im::OrdSet<OrdBlockId>> = Vec::from_iter(
-        children
-            .iter()
-            .map(|bid| compute_switch_exits_explore(cfg, tsort_map, memoized, *bid).succs),
-    );
+    let mut children_succs: Vec<im::OrdSet<OrdBlockId>> =
+        Vec::from_iter(children.iter().map(|bid| {
+            ensure_sufficient_stack(|| compute_switch_exits_explore(cfg, tsort_map, memoized, *bid))
+                .succs
+        }));
     trace!("block: {}, children: {:?}", block_id, children);
 
     // Add the children themselves in their sets of successors
@@ -1418,31 +1429,15 @@ fn compute_loop_switch_exits(cfg_info: &CfgInfo) -> ExitInfo {
     exit_info
 }
 
-fn combine_statement_and_statement(
-    statement: Box<tgt::Statement>,
-    next_st: Option<Box<tgt::Statement>>,
-) -> Box<tgt::Statement> {
-    match next_st {
-        Some(next_st) => {
-            let meta = combine_meta(&statement.meta, &next_st.meta);
-            let st = tgt::RawStatement::Sequence(statement, next_st);
-            Box::new(tgt::Statement::new(meta, st))
-        }
-        None => statement,
+/// Build a block from a list of statements. Blocks are never empty: if there
+/// are no statements, we insert a `Nop`, which uses the meta information of the
+/// parent terminator.
+fn mk_block(parent_meta: Meta, mut statements: Vec<tgt::Statement>) -> tgt::Block {
+    if statements.is_empty() {
+        statements.push(tgt::Statement::new(parent_meta, tgt::RawStatement::Nop));
     }
-}
-
-fn combine_statements_and_statement(
-    statements: Vec<tgt::Statement>,
-    next: Option<Box<tgt::Statement>>,
-) -> Option<Box<tgt::Statement>> {
-    statements
-        .into_iter()
-        .map(Box::new)
-        .rev()
-        .fold(next, |seq, st| {
-            Some(combine_statement_and_statement(st, seq))
-        })
+    let meta = combine_meta_iter(statements.iter().map(|st| &st.meta));
+    tgt::Block::new(meta, statements)
 }
 
 fn get_goto_kind(
@@ -1488,6 +1483,8 @@ enum GotoKind {
     Goto,
 }
 
+/// Translate a child block, and append the resulting statements to `out`.
+///
 /// `parent_meta`: we need some meta data for the new statement.
 /// We use the one for the parent terminator.
 fn translate_child_block(
@@ -1496,37 +1493,54 @@ fn translate_child_block(
     switch_exit_blocks: &im::HashSet<src::BlockId::Id>,
     parent_meta: Meta,
     child_id: src::BlockId::Id,
-) -> Option<Box<tgt::Statement>> {
+    out: &mut Vec<tgt::Statement>,
+) {
     // Check if this is a backward call
     match get_goto_kind(info.exits_info, parent_loops, switch_exit_blocks, child_id) {
         GotoKind::Break(index) => {
-            let st = tgt::RawStatement::Break(index);
-            Some(Box::new(tgt::Statement::new(parent_meta, st)))
+            let st = tgt::RawStatement::Break(index, None);
+            out.push(tgt::Statement::new(parent_meta, st))
         }
         GotoKind::Continue(index) => {
-            let st = tgt::RawStatement::Continue(index);
-            Some(Box::new(tgt::Statement::new(parent_meta, st)))
+            let st = tgt::RawStatement::Continue(index, None);
+            out.push(tgt::Statement::new(parent_meta, st))
         }
         // If we are going to an exit block we simply ignore the goto
-        GotoKind::ExitBlock => None,
+        GotoKind::ExitBlock => (),
         GotoKind::Goto => {
             // "Standard" goto: just recursively translate
-            translate_block(info, parent_loops, switch_exit_blocks, child_id)
+            ensure_sufficient_stack(|| {
+                translate_block(info, parent_loops, switch_exit_blocks, child_id, out)
+            })
         }
     }
 }
 
-fn opt_statement_to_nop_if_none(
-    meta: Meta,
-    opt_st: Option<Box<tgt::Statement>>,
-) -> Box<tgt::Statement> {
-    match opt_st {
-        Some(st) => st,
-        None => Box::new(tgt::Statement::new(meta, tgt::RawStatement::Nop)),
-    }
+/// Translate a branch of a switch.
+fn translate_branch(
+    info: &mut BlockInfo<'_>,
+    parent_loops: &Vector<src::BlockId::Id>,
+    switch_exit_blocks: &im::HashSet<src::BlockId::Id>,
+    parent_meta: Meta,
+    child_id: src::BlockId::Id,
+) -> tgt::Block {
+    let mut statements = Vec::new();
+    translate_child_block(
+        info,
+        parent_loops,
+        switch_exit_blocks,
+        parent_meta,
+        child_id,
+        &mut statements,
+    );
+    // We use the terminator meta information in case the branch is empty
+    mk_block(parent_meta, statements)
 }
 
-fn translate_statement(st: &src::Statement) -> Option<tgt::Statement> {
+fn translate_statement(
+    keep_storage_statements: bool,
+    st: &src::Statement,
+) -> Option<tgt::Statement> {
     let src_meta = st.meta;
     let st = match &st.content {
         src::RawStatement::Assign(place, rvalue) => {
@@ -1536,113 +1550,123 @@ fn translate_statement(st: &src::Statement) -> Option<tgt::Statement> {
         src::RawStatement::SetDiscriminant(place, variant_id) => {
             tgt::RawStatement::SetDiscriminant(place.clone(), *variant_id)
         }
+        src::RawStatement::StorageLive(var_id) => {
+            // The StorageLive are only present if we were asked to keep them
+            assert!(keep_storage_statements);
+            tgt::RawStatement::StorageLive(*var_id)
+        }
         src::RawStatement::StorageDead(var_id) => {
-            // We translate a StorageDead as a drop
-            let place = Place::new(*var_id);
-            tgt::RawStatement::Drop(place)
+            if keep_storage_statements {
+                tgt::RawStatement::StorageDead(*var_id)
+            } else {
+                // We translate a StorageDead as a drop
+                let place = Place::new(*var_id);
+                tgt::RawStatement::Drop(place)
+            }
         }
         src::RawStatement::Deinit(place) => {
             // We translate a deinit as a drop
             tgt::RawStatement::Drop(place.clone())
         }
+        src::RawStatement::MemCopy(copy) => tgt::RawStatement::MemCopy(copy.clone()),
+        src::RawStatement::WriteBytes(write) => tgt::RawStatement::WriteBytes(write.clone()),
+        src::RawStatement::Intrinsic(intrinsic) => tgt::RawStatement::Intrinsic(intrinsic.clone()),
     };
     Some(tgt::Statement::new(src_meta, st))
 }
 
+/// Translate a terminator, and append the resulting statements to `out`.
 fn translate_terminator(
     info: &mut BlockInfo<'_>,
     parent_loops: &Vector<src::BlockId::Id>,
     switch_exit_blocks: &im::HashSet<src::BlockId::Id>,
     terminator: &src::Terminator,
-) -> Option<Box<tgt::Statement>> {
+    out: &mut Vec<tgt::Statement>,
+) {
     let src_meta = terminator.meta;
 
     match &terminator.content {
-        src::RawTerminator::Panic | src::RawTerminator::Unreachable => Some(Box::new(
-            tgt::Statement::new(src_meta, tgt::RawStatement::Panic),
-        )),
-        src::RawTerminator::Return => Some(Box::new(tgt::Statement::new(
-            src_meta,
-            tgt::RawStatement::Return,
-        ))),
+        src::RawTerminator::Panic | src::RawTerminator::Unreachable => {
+            out.push(tgt::Statement::new(src_meta, tgt::RawStatement::Panic))
+        }
+        src::RawTerminator::Return => {
+            out.push(tgt::Statement::new(src_meta, tgt::RawStatement::Return))
+        }
         src::RawTerminator::Goto { target } => translate_child_block(
             info,
             parent_loops,
             switch_exit_blocks,
             terminator.meta,
             *target,
+            out,
         ),
         src::RawTerminator::Drop { place, target } => {
-            let opt_child = translate_child_block(
+            let st = tgt::RawStatement::Drop(place.clone());
+            out.push(tgt::Statement::new(src_meta, st));
+            translate_child_block(
                 info,
                 parent_loops,
                 switch_exit_blocks,
                 terminator.meta,
                 *target,
-            );
-            let st = Box::new(tgt::Statement::new(
-                src_meta,
-                tgt::RawStatement::Drop(place.clone()),
-            ));
-            Some(combine_statement_and_statement(st, opt_child))
+                out,
+            )
         }
         src::RawTerminator::Call { call, target } => {
-            let opt_child = translate_child_block(
+            let st = tgt::RawStatement::Call(call.clone());
+            out.push(tgt::Statement::new(src_meta, st));
+            translate_child_block(
                 info,
                 parent_loops,
                 switch_exit_blocks,
                 terminator.meta,
                 *target,
-            );
-            let st = tgt::RawStatement::Call(call.clone());
-            let st = Box::new(tgt::Statement::new(src_meta, st));
-            Some(combine_statement_and_statement(st, opt_child))
+                out,
+            )
         }
         src::RawTerminator::Assert {
             cond,
             expected,
+            kind,
             target,
         } => {
-            let opt_child = translate_child_block(
+            let st = tgt::RawStatement::Assert(tgt::Assert {
+                cond: cond.clone(),
+                expected: *expected,
+                kind: *kind,
+            });
+            out.push(tgt::Statement::new(src_meta, st));
+            translate_child_block(
                 info,
                 parent_loops,
                 switch_exit_blocks,
                 terminator.meta,
                 *target,
-            );
-            let st = tgt::RawStatement::Assert(tgt::Assert {
-                cond: cond.clone(),
-                expected: *expected,
-            });
-            let st = Box::new(tgt::Statement::new(src_meta, st));
-            Some(combine_statement_and_statement(st, opt_child))
+                out,
+            )
         }
         src::RawTerminator::Switch { discr, targets } => {
             // Translate the target expressions
             let switch = match &targets {
                 src::SwitchTargets::If(then_tgt, else_tgt) => {
                     // Translate the children expressions
-                    let then_exp = translate_child_block(
+                    let then_block = translate_branch(
                         info,
                         parent_loops,
                         switch_exit_blocks,
                         terminator.meta,
                         *then_tgt,
                     );
-                    // We use the terminator meta information in case then
-                    // then statement is `None`
-                    let then_exp = opt_statement_to_nop_if_none(terminator.meta, then_exp);
-                    let else_exp = translate_child_block(
+                    let else_block = translate_branch(
                         info,
                         parent_loops,
                         switch_exit_blocks,
                         terminator.meta,
                         *else_tgt,
                     );
-                    let else_exp = opt_statement_to_nop_if_none(terminator.meta, else_exp);
 
                     // Translate
-                    tgt::Switch::If(discr.clone(), then_exp, else_exp)
+                    tgt::Switch::If(discr.clone(), then_block, else_block)
                 }
                 src::SwitchTargets::SwitchInt(int_ty, targets, otherwise) => {
                     // Note that some branches can be grouped together, like
@@ -1666,7 +1690,7 @@ fn translate_terminator(
                     // - translated blocks
                     let mut branches: LinkedHashMap<
                         src::BlockId::Id,
-                        (Vec<v::ScalarValue>, tgt::Statement),
+                        (Vec<v::ScalarValue>, tgt::Block),
                     > = LinkedHashMap::new();
 
                     // Translate the children expressions
@@ -1680,36 +1704,29 @@ fn translate_terminator(
                             branch.0.push(*v);
                         } else {
                             // Not translated: translate it
-                            let exp = translate_child_block(
+                            let block = translate_branch(
                                 info,
                                 parent_loops,
                                 switch_exit_blocks,
                                 terminator.meta,
                                 *bid,
                             );
-                            // We use the terminator meta information in case then
-                            // then statement is `None`
-                            let exp = opt_statement_to_nop_if_none(terminator.meta, exp);
-                            branches.insert(*bid, (vec![*v], *exp));
+                            branches.insert(*bid, (vec![*v], block));
                         }
                     }
-                    let targets_exps: Vec<(Vec<v::ScalarValue>, tgt::Statement)> =
+                    let targets_blocks: Vec<(Vec<v::ScalarValue>, tgt::Block)> =
                         branches.into_iter().map(|(_, x)| x).collect();
 
-                    let otherwise_exp = translate_child_block(
+                    let otherwise_block = translate_branch(
                         info,
                         parent_loops,
                         switch_exit_blocks,
                         terminator.meta,
                         *otherwise,
                     );
-                    // We use the terminator meta information in case then
-                    // then statement is `None`
-                    let otherwise_exp =
-                        opt_statement_to_nop_if_none(terminator.meta, otherwise_exp);
 
                     // Translate
-                    tgt::Switch::SwitchInt(discr.clone(), *int_ty, targets_exps, otherwise_exp)
+                    tgt::Switch::SwitchInt(discr.clone(), *int_ty, targets_blocks, otherwise_block)
                 }
             };
 
@@ -1717,30 +1734,12 @@ fn translate_terminator(
             let meta = tgt::combine_switch_targets_meta(&switch);
             let meta = combine_meta(&src_meta, &meta);
             let st = tgt::RawStatement::Switch(switch);
-            let st = Box::new(tgt::Statement::new(meta, st));
-            Some(st)
+            out.push(tgt::Statement::new(meta, st))
         }
     }
 }
 
-fn combine_expressions(
-    exp1: Option<Box<tgt::Statement>>,
-    exp2: Option<Box<tgt::Statement>>,
-) -> Option<Box<tgt::Statement>> {
-    match exp1 {
-        None => exp2,
-        Some(exp1) => match exp2 {
-            None => Some(exp1),
-            Some(exp2) => {
-                let meta = combine_meta(&exp1.meta, &exp2.meta);
-                let st = tgt::RawStatement::Sequence(exp1, exp2);
-                Some(Box::new(tgt::Statement::new(meta, st)))
-            }
-        },
-    }
-}
-
-fn is_terminal(exp: &tgt::Statement) -> bool {
+pub(crate) fn is_terminal(exp: &tgt::Statement) -> bool {
     is_terminal_explore(0, exp)
 }
 
@@ -1755,37 +1754,54 @@ fn is_terminal_explore(num_loops: usize, st: &tgt::Statement) -> bool {
         | tgt::RawStatement::FakeRead(_)
         | tgt::RawStatement::SetDiscriminant(_, _)
         | tgt::RawStatement::Drop(_)
+        | tgt::RawStatement::StorageLive(_)
+        | tgt::RawStatement::StorageDead(_)
         | tgt::RawStatement::Assert(_)
         | tgt::RawStatement::Call(_)
+        | tgt::RawStatement::MemCopy(_)
+        | tgt::RawStatement::WriteBytes(_)
+        | tgt::RawStatement::Intrinsic(_)
         | tgt::RawStatement::Nop => false,
         tgt::RawStatement::Panic | tgt::RawStatement::Return => true,
-        tgt::RawStatement::Break(index) => *index >= num_loops,
-        tgt::RawStatement::Continue(_index) => true,
-        tgt::RawStatement::Sequence(st1, st2) => {
-            if is_terminal_explore(num_loops, st1) {
-                true
-            } else {
-                is_terminal_explore(num_loops, st2)
-            }
-        }
+        tgt::RawStatement::Break(index, _) => *index >= num_loops,
+        tgt::RawStatement::Continue(_index, _) => true,
         tgt::RawStatement::Switch(switch) => switch
             .get_targets()
             .iter()
-            .all(|tgt_st| is_terminal_explore(num_loops, tgt_st)),
-        tgt::RawStatement::Loop(loop_st) => is_terminal_explore(num_loops + 1, loop_st),
+            .all(|tgt_block| is_terminal_block_explore(num_loops, tgt_block)),
+        tgt::RawStatement::Loop(loop_block, _) => {
+            is_terminal_block_explore(num_loops + 1, loop_block)
+        }
     }
 }
 
-/// Remark: some values are boxed (here, the returned statement) so that they
-/// are allocated on the heap. This reduces stack usage (we had problems with
-/// stack overflows in the past). A more efficient solution would be to use loops
-/// to make this code constant space, but that would require a serious rewriting.
+/// A block is terminal if one of its statements is terminal.
+fn is_terminal_block_explore(num_loops: usize, block: &tgt::Block) -> bool {
+    block
+        .statements
+        .iter()
+        .any(|st| is_terminal_explore(num_loops, st))
+}
+
+/// Translate a block, and append the resulting statements to `out`.
+///
+/// Remark: we append the statements to a vector which is threaded through
+/// the translation (rather than returning new vectors of statements),
+/// so that we don't have to copy the statements whenever we concatenate
+/// them.
+///
+/// Remark: the depth of the recursion is proportional to the number of blocks
+/// (we recursively translate the targets of the gotos). We make the recursive
+/// calls through [ensure_sufficient_stack], which allocates new stack segments
+/// on the heap if necessary, so that we don't overflow the stack on huge bodies.
+/// We do the same in the other recursive explorations of the control-flow graph.
 fn translate_block(
     info: &mut BlockInfo<'_>,
     parent_loops: &Vector<src::BlockId::Id>,
     switch_exit_blocks: &im::HashSet<src::BlockId::Id>,
     block_id: src::BlockId::Id,
-) -> Option<Box<tgt::Statement>> {
+    out: &mut Vec<tgt::Statement>,
+) {
     // If the user activated this check: check that we didn't already translate
     // this block, and insert the block id in the set of already translated blocks.
     trace!(
@@ -1813,8 +1829,7 @@ fn translate_block(
     };
 
     // If we enter a switch or a loop, we need to check if we own the exit
-    // block, in which case we need to append it to the loop/switch body
-    // in a sequence
+    // block, in which case we need to append it after the loop/switch
     let is_switch = block.terminator.content.is_switch();
     let next_block = if is_loop {
         *info.exits_info.owned_loop_exits.get(&block_id).unwrap()
@@ -1839,67 +1854,51 @@ fn translate_block(
         switch_exit_blocks.clone()
     };
 
-    // Translate the terminator and the subsequent blocks.
-    // Note that this terminator is an option: we might ignore it
-    // (if it is an exit).
-
-    let terminator =
-        translate_terminator(info, nparent_loops, &nswitch_exit_blocks, &block.terminator);
-
-    // Translate the statements inside the block
-    let statements = Vec::from_iter(block.statements.iter().filter_map(translate_statement));
-
-    // We do different things if this is a loop, a switch (which is not
-    // a loop) or something else.
-    // Note that we need to do different treatments, because we don't
-    // concatenate the exit block the same way for loops and switches.
-    // In particular, for switches, we have to make sure we concatenate
-    // the exit block *before* concatenating the statements preceding
-    // the terminator, in order to avoid generating code of the form:
-    // ```
-    // e_prev; (s1; ...: sn; switch ...); e_switch_exit
-    // ```
+    // Translate the statements inside the block, then the terminator and
+    // the subsequent blocks. If this is a loop, we put them inside a `Loop`
+    // wrapper.
+    let mut loop_body = Vec::new();
+    let body = if is_loop { &mut loop_body } else { &mut *out };
+    let keep_storage_statements = info.keep_storage_statements;
+    body.extend(
+        block
+            .statements
+            .iter()
+            .filter_map(|st| translate_statement(keep_storage_statements, st)),
+    );
+    translate_terminator(
+        info,
+        nparent_loops,
+        &nswitch_exit_blocks,
+        &block.terminator,
+        body,
+    );
     if is_loop {
-        // Put the statements and the terminator together
-        let exp = combine_statements_and_statement(statements, terminator);
-
-        // Put the whole loop body inside a `Loop` wrapper
-        let exp = exp.unwrap();
-        let exp = Box::new(tgt::Statement::new(exp.meta, tgt::RawStatement::Loop(exp)));
+        let loop_body = mk_block(block.terminator.meta, loop_body);
+        out.push(tgt::Statement::new(
+            loop_body.meta,
+            tgt::RawStatement::Loop(loop_body, tgt::LoopInfo::empty()),
+        ));
+    }
 
-        // Add the exit block
-        if let Some(exit_block_id) = next_block {
-            let next_exp = translate_block(info, parent_loops, switch_exit_blocks, exit_block_id);
-            combine_expressions(Some(exp), next_exp)
-        } else {
-            Some(exp)
-        }
-    } else if is_switch {
-        // Use the terminator
-        let exp = terminator.unwrap();
-
-        // Concatenate the exit expression, if needs be
-        let exp = if let Some(exit_block_id) = next_block {
-            // Sanity check: if there is an exit block, this block must be
-            // reachable (i.e, there must exist a path in the switch which
-            // doesn't end with `panic`, `return`, etc.).
-            assert!(!is_terminal(&exp));
-
-            let next_exp = translate_block(info, parent_loops, switch_exit_blocks, exit_block_id);
-            combine_expressions(Some(exp), next_exp)
-        } else {
-            Some(exp)
-        };
+    // Add the exit block
+    if let Some(exit_block_id) = next_block {
+        // Sanity check: if there is an exit block after a switch, this block
+        // must be reachable (i.e, there must exist a path in the switch which
+        // doesn't end with `panic`, `return`, etc.).
+        assert!(is_loop || !is_terminal(out.last().unwrap()));
 
-        // Concatenate the statements
-        combine_statements_and_statement(statements, exp)
-    } else {
-        // Simply concatenate the statements and the terminator
-        combine_statements_and_statement(statements, terminator)
+        ensure_sufficient_stack(|| {
+            translate_block(info, parent_loops, switch_exit_blocks, exit_block_id, out)
+        })
     }
 }
 
-fn translate_body(no_code_duplication: bool, src_body: &src::ExprBody) -> tgt::ExprBody {
+fn translate_body(
+    no_code_duplication: bool,
+    keep_storage_statements: bool,
+    src_body: &src::ExprBody,
+) -> tgt::ExprBody {
     // Explore the function body to create the control-flow graph without backward
     // edges, and identify the loop entries (which are destinations of backward edges).
     let cfg_info = build_cfg_partial_info(src_body);
@@ -1915,22 +1914,23 @@ fn translate_body(no_code_duplication: bool, src_body: &src::ExprBody) -> tgt::E
 
     // Translate the body by reconstructing the loops and the
     // conditional branchings.
-    // Note that we shouldn't get `None`.
     let mut explored = HashSet::new();
     let mut info = BlockInfo {
         no_code_duplication,
+        keep_storage_statements,
         cfg: &cfg_info,
         body: src_body,
         exits_info: &exits_info,
         explored: &mut explored,
     };
-    let stmt = translate_block(
+    let mut statements = Vec::new();
+    translate_block(
         &mut info,
         &Vector::new(),
         &im::HashSet::new(),
         src::BlockId::ZERO,
-    )
-    .unwrap();
+        &mut statements,
+    );
 
     // Sanity: check that we translated all the blocks
     for (bid, _) in src_body.body.iter_indexed_values() {
@@ -1941,13 +1941,17 @@ fn translate_body(no_code_duplication: bool, src_body: &src::ExprBody) -> tgt::E
         meta: src_body.meta,
         arg_count: src_body.arg_count,
         locals: src_body.locals.clone(),
-        body: *stmt,
+        regions: src_body.regions.clone(),
+        borrowck_facts: src_body.borrowck_facts.clone(),
+        scopes: src_body.scopes.clone(),
+        promoted: src_body.promoted.clone(),
+        body: mk_block(src_body.meta, statements),
     }
 }
 
 fn translate_function(ctx: &TransCtx, src_def_id: FunDeclId::Id) -> tgt::FunDecl {
     // Retrieve the function definition
-    let src_def = ctx.fun_decls.get(src_def_id).unwrap();
+    let src_def = ctx.translated.fun_decls.get(src_def_id).unwrap();
     let fctx = ctx.into_fmt();
     trace!(
         "# About to reconstruct: {}\n\n{}",
@@ -1962,18 +1966,20 @@ fn translate_function(ctx: &TransCtx, src_def_id: FunDeclId::Id) -> tgt::FunDecl
         item_meta: src_def.item_meta.clone(),
         is_local: src_def.is_local,
         name: src_def.name.clone(),
+        lang_item: src_def.lang_item.clone(),
         signature: src_def.signature.clone(),
         kind: src_def.kind.clone(),
+        rec_group: src_def.rec_group.clone(),
         body: src_def
             .body
             .as_ref()
-            .map(|b| translate_body(ctx.no_code_duplication, b)),
+            .map(|b| translate_body(ctx.no_code_duplication, ctx.keep_storage_statements, b)),
     }
 }
 
 fn translate_global(ctx: &TransCtx, global_id: GlobalDeclId::Id) -> tgt::GlobalDecl {
     // Retrieve the global definition
-    let src_def = ctx.global_decls.get(global_id).unwrap();
+    let src_def = ctx.translated.global_decls.get(global_id).unwrap();
     let fctx = ctx.into_fmt();
     trace!(
         "# About to reconstruct: {}\n\n{}",
@@ -1990,11 +1996,16 @@ fn translate_global(ctx: &TransCtx, global_id: GlobalDeclId::Id) -> tgt::GlobalD
         generics: src_def.generics.clone(),
         preds: src_def.preds.clone(),
         ty: src_def.ty.clone(),
+        is_mutable: src_def.is_mutable,
+        is_thread_local: src_def.is_thread_local,
+        is_interior_mutable: src_def.is_interior_mutable,
         kind: src_def.kind.clone(),
+        promoted: src_def.promoted.clone(),
+        init_deps: src_def.init_deps.clone(),
         body: src_def
             .body
             .as_ref()
-            .map(|b| translate_body(ctx.no_code_duplication, b)),
+            .map(|b| translate_body(ctx.no_code_duplication, ctx.keep_storage_statements, b)),
     }
 }
 
@@ -2004,10 +2015,10 @@ pub fn translate_functions(ctx: &TransCtx) -> Defs {
     let mut tgt_globals = GlobalDeclId::Map::new();
 
     // Translate the bodies one at a time
-    for (fun_id, _) in ctx.fun_decls.iter_indexed() {
+    for (fun_id, _) in ctx.translated.fun_decls.iter_indexed() {
         tgt_funs.insert(*fun_id, translate_function(ctx, *fun_id));
     }
-    for (global_id, _) in ctx.global_decls.iter_indexed() {
+    for (global_id, _) in ctx.translated.global_decls.iter_indexed() {
         tgt_globals.insert(*global_id, translate_global(ctx, *global_id));
     }
 
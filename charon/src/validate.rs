@@ -0,0 +1,624 @@
+//! Check that the bodies are well-formed: the variables, the blocks and the
+//! declarations they refer to exist, the projections and the switches are
+//! well-typed, the aggregates have the proper number of fields, etc. This
+//! doesn't check the types of the assignments.
+//!
+//! We use this to catch the bugs of the micro-passes: with `--validate`, we
+//! check all the bodies after every pass (see [crate::driver::transform]).
+//! The fuzzer (`tests/fuzz.rs`) also relies on it.
+use crate::gast::*;
+use crate::llbc_ast;
+use crate::translate_ctx::{ErrorCtx, TransCtx, TransformCtx, TranslatedCrate};
+use crate::types::*;
+use crate::ullbc_ast;
+use crate::values::*;
+
+/// Substitute the generic parameters of a type declaration, to compute the
+/// types of its fields.
+struct SubstTypes<'a> {
+    generics: &'a GenericArgs,
+}
+
+impl<'a> MutTypeVisitor for SubstTypes<'a> {
+    fn visit_ty(&mut self, ty: &mut Ty) {
+        if let TyKind::TypeVar(id) = ty.kind()
+            && let Some(arg) = self.generics.types.get(id.index())
+        {
+            *ty = arg.clone()
+        } else {
+            self.default_visit_ty(ty)
+        }
+    }
+
+    fn visit_const_generic(&mut self, cg: &mut ConstGeneric) {
+        if let ConstGeneric::Var(id) = cg
+            && let Some(arg) = self.generics.const_generics.get(id.index())
+        {
+            *cg = arg.clone()
+        }
+    }
+}
+
+struct Checker<'a> {
+    krate: &'a TranslatedCrate,
+    locals: &'a VarId::Vector<Var>,
+    promoted: &'a PromotedId::Vector<GlobalDeclId::Id>,
+    errors: Vec<String>,
+}
+
+impl<'a> Checker<'a> {
+    fn new<T>(krate: &'a TranslatedCrate, body: &'a GExprBody<T>) -> Self {
+        let mut checker = Checker {
+            krate,
+            locals: &body.locals,
+            promoted: &body.promoted,
+            errors: Vec::new(),
+        };
+        if body.arg_count >= body.locals.len() {
+            checker.error(format!(
+                "the body has {} arguments but only {} local variables",
+                body.arg_count,
+                body.locals.len()
+            ));
+        }
+        for (i, var) in body.locals.iter().enumerate() {
+            if var.index.index() != i {
+                checker.error(format!("the local variable {} has index {}", i, var.index));
+            }
+        }
+        for id in &body.promoted {
+            if krate.global_decls.get(*id).is_none() {
+                checker.error(format!("unknown global {id}"))
+            }
+        }
+        // The parents of the scopes come before them
+        for (i, scope) in body.scopes.iter().enumerate() {
+            if scope.index.index() != i || scope.parent.is_some_and(|p| p >= scope.index) {
+                checker.error(format!("ill-formed scope {i}"));
+            }
+            for var_id in &scope.locals {
+                checker.check_var(*var_id)
+            }
+        }
+        checker
+    }
+
+    fn error(&mut self, msg: String) {
+        self.errors.push(msg)
+    }
+
+    fn check_var(&mut self, id: VarId::Id) {
+        if self.locals.get(id).is_none() {
+            self.error(format!("unknown variable {}", id.to_pretty_string()))
+        }
+    }
+
+    /// The fields of a structure or of an enumeration variant, if the type
+    /// declaration is available.
+    fn fields(
+        &mut self,
+        id: TypeDeclId::Id,
+        variant: Option<VariantId::Id>,
+    ) -> Option<&'a FieldId::Vector<Field>> {
+        let decl = self.krate.type_decls.get(id)?;
+        match (&decl.kind, variant) {
+            (TypeDeclKind::Struct(fields), None) => Some(fields),
+            (TypeDeclKind::Enum(variants, ..), Some(variant_id)) => {
+                match variants.get(variant_id) {
+                    Some(variant) => Some(&variant.fields),
+                    None => {
+                        self.error(format!("unknown variant {variant_id} of the type {id}"));
+                        None
+                    }
+                }
+            }
+            // We can't check the opaque declarations and the declarations
+            // we failed to translate
+            (TypeDeclKind::Opaque | TypeDeclKind::ImplTrait { .. } | TypeDeclKind::Error(_), _) => {
+                None
+            }
+            _ => {
+                self.error(format!(
+                    "the variant {variant:?} doesn't match the declaration of the type {id}"
+                ));
+                None
+            }
+        }
+    }
+
+    /// Compute the type of a place, if we can. We can't compute the types of
+    /// the closure states, nor see through the generic parameters.
+    fn place_ty(&mut self, place: &Place) -> Option<Ty> {
+        let Some(var) = self.locals.get(place.var_id) else {
+            self.check_var(place.var_id);
+            return None;
+        };
+        let mut ty = var.ty.clone();
+        for elem in &place.projection {
+            ty = match (elem, ty.kind()) {
+                (ProjectionElem::Deref, TyKind::Ref(_, ty, _) | TyKind::RawPtr(ty, _))
+                | (ProjectionElem::DerefRawPtr, TyKind::RawPtr(ty, _)) => ty.clone(),
+                (
+                    ProjectionElem::Deref | ProjectionElem::DerefBox,
+                    TyKind::Adt(TypeId::Assumed(AssumedTy::Box), args),
+                ) => args.types.get(0)?.clone(),
+                (
+                    ProjectionElem::Field(FieldProjKind::Adt(id, variant), field_id),
+                    TyKind::Adt(TypeId::Adt(ty_id), args),
+                ) if id == ty_id => {
+                    let args = args.clone();
+                    let Some(field) = self.fields(*id, *variant)?.get(*field_id) else {
+                        self.error(format!("unknown field {field_id} of the type {id}"));
+                        return None;
+                    };
+                    let mut ty = field.ty.clone();
+                    SubstTypes { generics: &args }.visit_ty(&mut ty);
+                    ty
+                }
+                (
+                    ProjectionElem::Field(FieldProjKind::Tuple(arity), field_id),
+                    TyKind::Adt(TypeId::Tuple, args),
+                ) if args.types.len() == *arity && field_id.index() < *arity => {
+                    args.types[field_id.index()].clone()
+                }
+                (
+                    ProjectionElem::Index(var_id, _),
+                    TyKind::Adt(TypeId::Assumed(AssumedTy::Array | AssumedTy::Slice), args),
+                ) => {
+                    let elem_ty = args.types.get(0)?.clone();
+                    self.check_var(*var_id);
+                    elem_ty
+                }
+                (
+                    ProjectionElem::Subslice { .. },
+                    TyKind::Adt(TypeId::Assumed(AssumedTy::Slice), _),
+                ) => ty.clone(),
+                (
+                    ProjectionElem::Subslice { from, to, from_end },
+                    TyKind::Adt(TypeId::Assumed(AssumedTy::Array), args),
+                ) => {
+                    // The subslice of an array is an array: compute its length
+                    let len = match (from_end, args.const_generics.get(0)?) {
+                        (false, _) => to.checked_sub(*from),
+                        (true, ConstGeneric::Value(Literal::Scalar(len))) => {
+                            let len = len.as_uint().ok()? as u64;
+                            len.checked_sub(from + to)
+                        }
+                        // We can't compute the length
+                        (true, _) => return None,
+                    };
+                    let Some(len) = len else {
+                        self.error(format!("invalid subslice {elem:?} of the type {ty:?}"));
+                        return None;
+                    };
+                    let mut args = args.clone();
+                    args.with_data_mut(|args| {
+                        args.const_generics = vec![ConstGeneric::Value(Literal::Scalar(
+                            ScalarValue::Usize(len),
+                        ))]
+                    });
+                    TyKind::Adt(TypeId::Assumed(AssumedTy::Array), args).into_ty()
+                }
+                // We don't know the fields of the closure states and of the
+                // internal pointer types
+                (ProjectionElem::Field(FieldProjKind::ClosureState, _), _)
+                | (
+                    _,
+                    TyKind::Adt(TypeId::Assumed(AssumedTy::PtrUnique | AssumedTy::PtrNonNull), _),
+                )
+                | (_, TyKind::TypeVar(_) | TyKind::TraitType(..)) => return None,
+                _ => {
+                    self.error(format!(
+                        "ill-typed projection {elem:?} on a value of type {ty:?}"
+                    ));
+                    return None;
+                }
+            }
+        }
+        Some(ty)
+    }
+
+    fn check_constant(&mut self, cv: &ConstantExpr) {
+        match &cv.value {
+            RawConstantExpr::Global(id, _) | RawConstantExpr::MutStatic(id) => {
+                if self.krate.global_decls.get(*id).is_none() {
+                    self.error(format!("unknown global {id}"))
+                }
+            }
+            RawConstantExpr::Promoted(id, _) => {
+                if self.promoted.get(*id).is_none() {
+                    self.error(format!("unknown promoted constant {id}"))
+                }
+            }
+            RawConstantExpr::Adt(_, fields) => {
+                for field in fields {
+                    self.check_constant(field)
+                }
+            }
+            RawConstantExpr::Ref(cv) => self.check_constant(cv),
+            RawConstantExpr::Literal(_)
+            | RawConstantExpr::TraitConst(..)
+            | RawConstantExpr::Var(_)
+            | RawConstantExpr::FnPtr(_) => (),
+        }
+    }
+
+    fn operand_ty(&mut self, op: &Operand) -> Option<Ty> {
+        match op {
+            Operand::Copy(place) | Operand::Move(place) => self.place_ty(place),
+            Operand::Const(cv) => {
+                self.check_constant(cv);
+                Some(cv.ty.clone())
+            }
+            Operand::Expr(rv, ty) => {
+                self.check_rvalue(rv);
+                Some(ty.clone())
+            }
+        }
+    }
+
+    /// Check that an operand has the given literal type (if we can compute
+    /// its type).
+    fn check_operand_literal(&mut self, op: &Operand, expected: LiteralTy) {
+        if let Some(ty) = self.operand_ty(op)
+            && !matches!(ty.kind(), TyKind::Literal(lit_ty) if *lit_ty == expected)
+        {
+            self.error(format!("expected an operand of type {expected}, got {ty:?}"))
+        }
+    }
+
+    fn check_rvalue(&mut self, rv: &Rvalue) {
+        match rv {
+            Rvalue::Use(op) | Rvalue::UnaryOp(_, op) | Rvalue::Repeat(op, ..) => {
+                self.operand_ty(op);
+            }
+            Rvalue::Ref(place, _) | Rvalue::Len(place, ..) => {
+                self.place_ty(place);
+            }
+            Rvalue::BinaryOp(binop, op1, op2) => {
+                let ty1 = self.operand_ty(op1);
+                let ty2 = self.operand_ty(op2);
+                // The offsets are counted in `isize` or `usize`
+                if matches!(binop, BinOp::Offset | BinOp::WrappingOffset)
+                    && let Some(ty2) = &ty2
+                    && !matches!(
+                        ty2.kind(),
+                        TyKind::Literal(LiteralTy::Integer(IntegerTy::Isize | IntegerTy::Usize))
+                    )
+                {
+                    self.error(format!("the offset of {binop:?} has type {ty2:?}"))
+                }
+                // The operands of the shifts may have different types
+                if !matches!(binop, BinOp::Shl | BinOp::Shr)
+                    && let (Some(ty1), Some(ty2)) = (ty1, ty2)
+                    && ty1.kind().is_literal()
+                    && ty1 != ty2
+                {
+                    self.error(format!(
+                        "the operands of {binop:?} have different types: {ty1:?} and {ty2:?}"
+                    ))
+                }
+            }
+            Rvalue::Discriminant(place, id) => {
+                if let Some(ty) = self.place_ty(place)
+                    && !matches!(ty.kind(), TyKind::Adt(TypeId::Adt(ty_id), _) if ty_id == id)
+                {
+                    self.error(format!(
+                        "reading the discriminant of a value of type {ty:?} as the type {id}"
+                    ))
+                }
+                if let Some(decl) = self.krate.type_decls.get(*id)
+                    && matches!(decl.kind, TypeDeclKind::Struct(_))
+                {
+                    self.error(format!("reading the discriminant of the structure {id}"))
+                }
+            }
+            Rvalue::Aggregate(kind, ops) => {
+                for op in ops {
+                    self.operand_ty(op);
+                }
+                if let AggregateKind::Adt(TypeId::Adt(id), variant, _) = kind
+                    && let Some(fields) = self.fields(*id, *variant)
+                    && fields.len() != ops.len()
+                {
+                    self.error(format!(
+                        "the type {id} has {} fields but the aggregate has {} operands",
+                        fields.len(),
+                        ops.len()
+                    ))
+                }
+            }
+            Rvalue::Global(id, _) => {
+                if self.krate.global_decls.get(*id).is_none() {
+                    self.error(format!("unknown global {id}"))
+                }
+            }
+            Rvalue::NullaryOp(..) => (),
+        }
+    }
+
+    fn check_call(&mut self, call: &Call) {
+        match &call.func {
+            FnOperand::Regular(FnPtr {
+                func: FunIdOrTraitMethodRef::Fun(FunId::Regular(id)),
+                ..
+            }) => match self.krate.fun_decls.get(*id) {
+                None => self.error(format!("unknown function {id}")),
+                Some(f) if f.is_local && f.signature.inputs.len() != call.args.len() => {
+                    self.error(format!(
+                        "the function {id} expects {} arguments but receives {}",
+                        f.signature.inputs.len(),
+                        call.args.len()
+                    ))
+                }
+                Some(_) => (),
+            },
+            FnOperand::Regular(_) => (),
+            FnOperand::Move(place) => {
+                self.place_ty(place);
+            }
+        }
+        for arg in &call.args {
+            self.operand_ty(arg);
+        }
+        self.place_ty(&call.dest);
+    }
+
+    fn check_set_discriminant(&mut self, place: &Place, variant_id: VariantId::Id) {
+        if let Some(ty) = self.place_ty(place)
+            && let TyKind::Adt(TypeId::Adt(id), _) = ty.kind()
+        {
+            self.fields(*id, Some(variant_id));
+        }
+    }
+
+    fn check_switch_values(&mut self, int_ty: IntegerTy, values: &[ScalarValue]) {
+        for v in values {
+            if v.get_integer_ty() != int_ty {
+                self.error(format!(
+                    "the switch over a value of type {int_ty} has a case {v:?}"
+                ))
+            }
+        }
+    }
+
+    fn check_mem_copy(&mut self, copy: &MemCopy) {
+        self.operand_ty(&copy.src);
+        self.operand_ty(&copy.dst);
+        self.check_operand_literal(&copy.count, LiteralTy::Integer(IntegerTy::Usize));
+    }
+
+    fn check_write_bytes(&mut self, write: &WriteBytes) {
+        self.operand_ty(&write.dst);
+        self.check_operand_literal(&write.val, LiteralTy::Integer(IntegerTy::U8));
+        self.check_operand_literal(&write.count, LiteralTy::Integer(IntegerTy::Usize));
+    }
+
+    fn check_intrinsic(&mut self, intrinsic: &Intrinsic) {
+        match intrinsic.kind {
+            IntrinsicKind::Assume | IntrinsicKind::Assert => match intrinsic.args.as_slice() {
+                [cond] => self.check_operand_literal(cond, LiteralTy::Bool),
+                // The message of an assertion, if it is not a string literal
+                [cond, message] if intrinsic.kind.is_assert() && intrinsic.message.is_none() => {
+                    self.check_operand_literal(cond, LiteralTy::Bool);
+                    self.operand_ty(message);
+                }
+                _ => self.error(format!(
+                    "an assumption or an assertion has {} arguments",
+                    intrinsic.args.len()
+                )),
+            },
+            IntrinsicKind::Havoc | IntrinsicKind::Custom(_) => {
+                for arg in &intrinsic.args {
+                    self.operand_ty(arg);
+                }
+            }
+        }
+        self.place_ty(&intrinsic.dest);
+    }
+
+    fn check_ullbc_statement(&mut self, st: &ullbc_ast::Statement) {
+        use ullbc_ast::RawStatement;
+        match &st.content {
+            RawStatement::Assign(place, rv) => {
+                self.place_ty(place);
+                self.check_rvalue(rv);
+            }
+            RawStatement::FakeRead(place) | RawStatement::Deinit(place) => {
+                self.place_ty(place);
+            }
+            RawStatement::SetDiscriminant(place, variant_id) => {
+                self.check_set_discriminant(place, *variant_id)
+            }
+            RawStatement::StorageLive(var_id) | RawStatement::StorageDead(var_id) => {
+                self.check_var(*var_id)
+            }
+            RawStatement::MemCopy(copy) => self.check_mem_copy(copy),
+            RawStatement::WriteBytes(write) => self.check_write_bytes(write),
+            RawStatement::Intrinsic(intrinsic) => self.check_intrinsic(intrinsic),
+        }
+    }
+
+    fn check_ullbc_body(&mut self, blocks: &ullbc_ast::BlockId::Vector<ullbc_ast::BlockData>) {
+        use ullbc_ast::{RawTerminator, SwitchTargets};
+        if blocks.is_empty() {
+            self.error("the body doesn't have any block".to_string())
+        }
+        for block in blocks.iter() {
+            for st in &block.statements {
+                self.check_ullbc_statement(st)
+            }
+            let targets: Vec<ullbc_ast::BlockId::Id> = match &block.terminator.content {
+                RawTerminator::Goto { target } => vec![*target],
+                RawTerminator::Switch { discr, targets } => match targets {
+                    SwitchTargets::If(then_tgt, else_tgt) => {
+                        self.check_operand_literal(discr, LiteralTy::Bool);
+                        vec![*then_tgt, *else_tgt]
+                    }
+                    SwitchTargets::SwitchInt(int_ty, targets, otherwise) => {
+                        self.check_operand_literal(discr, LiteralTy::Integer(*int_ty));
+                        let values: Vec<ScalarValue> = targets.iter().map(|(v, _)| *v).collect();
+                        self.check_switch_values(*int_ty, &values);
+                        let mut targets: Vec<_> = targets.iter().map(|(_, tgt)| *tgt).collect();
+                        targets.push(*otherwise);
+                        targets
+                    }
+                },
+                RawTerminator::Panic | RawTerminator::Return | RawTerminator::Unreachable => {
+                    Vec::new()
+                }
+                RawTerminator::Drop { place, target } => {
+                    self.place_ty(place);
+                    vec![*target]
+                }
+                RawTerminator::Call { call, target } => {
+                    self.check_call(call);
+                    vec![*target]
+                }
+                RawTerminator::Assert { cond, target, .. } => {
+                    self.check_operand_literal(cond, LiteralTy::Bool);
+                    vec![*target]
+                }
+            };
+            for target in targets {
+                if blocks.get(target).is_none() {
+                    self.error(format!("unknown block {target}"))
+                }
+            }
+        }
+    }
+
+    /// `loop_depth` is the number of loops the block is in.
+    fn check_llbc_block(&mut self, block: &llbc_ast::Block, loop_depth: usize) {
+        use llbc_ast::{RawStatement, Switch};
+        for st in &block.statements {
+            match &st.content {
+                RawStatement::Assign(place, rv) => {
+                    self.place_ty(place);
+                    self.check_rvalue(rv);
+                }
+                RawStatement::FakeRead(place) | RawStatement::Drop(place) => {
+                    self.place_ty(place);
+                }
+                RawStatement::SetDiscriminant(place, variant_id) => {
+                    self.check_set_discriminant(place, *variant_id)
+                }
+                RawStatement::StorageLive(var_id) | RawStatement::StorageDead(var_id) => {
+                    self.check_var(*var_id)
+                }
+                RawStatement::Assert(assert) => {
+                    self.check_operand_literal(&assert.cond, LiteralTy::Bool)
+                }
+                RawStatement::Call(call) => self.check_call(call),
+                RawStatement::MemCopy(copy) => self.check_mem_copy(copy),
+                RawStatement::WriteBytes(write) => self.check_write_bytes(write),
+                RawStatement::Intrinsic(intrinsic) => self.check_intrinsic(intrinsic),
+                RawStatement::Panic | RawStatement::Return | RawStatement::Nop => (),
+                RawStatement::Break(i, _) | RawStatement::Continue(i, _) => {
+                    if *i >= loop_depth {
+                        self.error(format!(
+                            "break or continue to the loop {i}, but there are only {loop_depth} enclosing loops"
+                        ))
+                    }
+                }
+                RawStatement::Switch(switch) => match switch {
+                    Switch::If(op, then_block, else_block) => {
+                        self.check_operand_literal(op, LiteralTy::Bool);
+                        self.check_llbc_block(then_block, loop_depth);
+                        self.check_llbc_block(else_block, loop_depth);
+                    }
+                    Switch::SwitchInt(op, int_ty, targets, otherwise) => {
+                        self.check_operand_literal(op, LiteralTy::Integer(*int_ty));
+                        for (values, block) in targets {
+                            self.check_switch_values(*int_ty, values);
+                            self.check_llbc_block(block, loop_depth);
+                        }
+                        self.check_llbc_block(otherwise, loop_depth);
+                    }
+                    Switch::Match(place, targets, otherwise) => {
+                        if let Some(ty) = self.place_ty(place) {
+                            match ty.kind() {
+                                TyKind::Adt(TypeId::Adt(id), _) => {
+                                    for variant_id in targets.iter().flat_map(|(ids, _)| ids) {
+                                        self.fields(*id, Some(*variant_id));
+                                    }
+                                }
+                                _ => self.error(format!("matching on a value of type {ty:?}")),
+                            }
+                        }
+                        for (_, block) in targets {
+                            self.check_llbc_block(block, loop_depth);
+                        }
+                        if let Some(block) = otherwise {
+                            self.check_llbc_block(block, loop_depth);
+                        }
+                    }
+                },
+                RawStatement::Loop(body, _) => self.check_llbc_block(body, loop_depth + 1),
+            }
+        }
+    }
+}
+
+/// Check an ULLBC body, and return the list of the errors we found.
+pub fn check_ullbc_body(krate: &TranslatedCrate, body: &ullbc_ast::ExprBody) -> Vec<String> {
+    let mut checker = Checker::new(krate, body);
+    checker.check_ullbc_body(&body.body);
+    checker.errors
+}
+
+/// Check an LLBC body, and return the list of the errors we found.
+pub fn check_llbc_body(krate: &TranslatedCrate, body: &llbc_ast::ExprBody) -> Vec<String> {
+    let mut checker = Checker::new(krate, body);
+    checker.check_llbc_block(&body.body, 0);
+    checker.errors
+}
+
+/// Report the errors found in a body.
+fn report<T>(
+    errors: &mut ErrorCtx,
+    pass: &str,
+    rust_id: rustc_hir::def_id::DefId,
+    body: &GExprBody<T>,
+    msgs: Vec<String>,
+) {
+    let current_def_id = errors.def_id;
+    errors.def_id = Some(rust_id);
+    for msg in msgs {
+        errors.span_err(
+            body.meta.span.rust_span_data.span(),
+            &format!("Ill-formed body after {pass}: {msg}"),
+        );
+    }
+    errors.def_id = current_def_id;
+}
+
+/// Check the ULLBC bodies of the crate after a pass.
+pub fn check_ullbc(ctx: &mut TransCtx, pass: &str) {
+    let krate = &ctx.translated;
+    let funs = krate.fun_decls.iter().map(|f| (f.rust_id, &f.body));
+    let globals = krate.global_decls.iter().map(|g| (g.rust_id, &g.body));
+    for (rust_id, body) in funs.chain(globals) {
+        if let Some(body) = body {
+            let msgs = check_ullbc_body(krate, body);
+            report(&mut ctx.errors, pass, rust_id, body, msgs);
+        }
+    }
+}
+
+/// Check the LLBC bodies of the crate after a pass.
+pub fn check_llbc(
+    ctx: &mut TransformCtx,
+    funs: &llbc_ast::FunDecls,
+    globals: &llbc_ast::GlobalDecls,
+    pass: &str,
+) {
+    let funs = funs.iter().map(|f| (f.rust_id, &f.body));
+    let globals = globals.iter().map(|g| (g.rust_id, &g.body));
+    for (rust_id, body) in funs.chain(globals) {
+        if let Some(body) = body {
+            let msgs = check_llbc_body(ctx.translated, body);
+            report(ctx.errors, pass, rust_id, body, msgs);
+        }
+    }
+}
@@ -0,0 +1,109 @@
+//! The `--watch` mode: we extract the crate, then extract it again every time
+//! one of its source files changes (see [run]), to streamline the
+//! edit-extract-verify loop.
+//!
+//! We detect the changes by polling the modification times of the source files
+//! rather than by subscribing to the notifications of the file system, which
+//! are platform-specific. In watch mode, the driver only rewrites the output
+//! files whose content changed, so that the tools which monitor the output
+//! (for instance by reloading the group files of `--split-output`) only see
+//! the relevant changes. With `--split-output`, the driver decides which group
+//! files to rewrite with the fingerprints it stores in the index file (they
+//! are computed from the hashes of the declarations): we use them to report
+//! the group files which changed.
+//!
+//! Remark: this module is compiled with the `charon` binary, which doesn't link
+//! with the rest of the library: we can only depend on external crates.
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use walkdir::WalkDir;
+
+/// The interval at which we check the source files
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Whether a file is a source of the crate: its Rust files and its manifest
+fn is_source(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "rs")
+        || path.file_name().is_some_and(|name| name == "Cargo.toml")
+}
+
+/// The modification times of the source files in `root`. We skip the hidden
+/// directories and the `target` directory of cargo.
+fn snapshot(root: &Path) -> BTreeMap<PathBuf, SystemTime> {
+    WalkDir::new(root)
+        .into_iter()
+        .filter_entry(|entry| {
+            let name = entry.file_name().to_string_lossy();
+            entry.depth() == 0 || !(name.starts_with('.') || name == "target")
+        })
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file() && is_source(entry.path()))
+        .filter_map(|entry| {
+            let modified = entry.metadata().ok()?.modified().ok()?;
+            Some((entry.into_path(), modified))
+        })
+        .collect()
+}
+
+/// The fingerprints of the group files in the directory of a split output,
+/// indexed by the names of the files. We read them from the index file
+/// (`charon_lib::export::INDEX_FILE`), which may not exist yet.
+fn group_hashes(split_output: &Path) -> BTreeMap<String, String> {
+    let Ok(content) = std::fs::read(split_output.join("index.json")) else {
+        return BTreeMap::new();
+    };
+    let Ok(index) = serde_json::from_slice::<serde_json::Value>(&content) else {
+        return BTreeMap::new();
+    };
+    let groups = index["groups"].as_array().into_iter().flatten();
+    groups
+        .filter_map(|entry| {
+            let file = entry["file"].as_str()?.to_string();
+            let hash = entry["hash"].as_str()?.to_string();
+            Some((file, hash))
+        })
+        .collect()
+}
+
+/// Run `extract`, then run it again every time a source file in `root` is
+/// added, removed or modified. We never return: the user stops the watch mode
+/// with Ctrl-C. We keep watching when the extraction fails, so that the user
+/// can fix the errors (which the extraction already reported). If the output
+/// is split (in the `split_output` directory), we report the group files which
+/// the extraction rewrote.
+pub fn run(
+    root: &Path,
+    split_output: Option<&Path>,
+    mut extract: impl FnMut() -> Result<(), i32>,
+) -> ! {
+    let mut sources = snapshot(root);
+    loop {
+        let hashes = split_output.map(group_hashes);
+        if let Err(code) = extract() {
+            log::error!("The extraction failed (exit code: {code})");
+        } else if let (Some(split_output), Some(hashes)) = (split_output, hashes) {
+            let new_hashes = group_hashes(split_output);
+            let rewritten: Vec<&str> = new_hashes
+                .iter()
+                .filter(|(file, hash)| hashes.get(*file) != Some(*hash))
+                .map(|(file, _)| file.as_str())
+                .collect();
+            log::info!(
+                "Rewrote {} of the {} group files: [{}]",
+                rewritten.len(),
+                new_hashes.len(),
+                rewritten.join(", ")
+            );
+        }
+        log::info!("Watching {} for changes", root.display());
+        loop {
+            std::thread::sleep(POLL_INTERVAL);
+            let new_sources = snapshot(root);
+            if new_sources != sources {
+                sources = new_sources;
+                break;
+            }
+        }
+    }
+}
@@ -4,17 +4,61 @@ use itertools::Itertools;
 use std::{error::Error, fs::File};
 
 use charon_lib::{
+    cli_options::CliOpts,
+    driver::CharonCallbacks,
     export::GCrateData,
+    expressions::{FieldProjKind, FunId, FunIdOrTraitMethodRef, RawConstantExpr},
+    gast::{Scope, TraitImplKind},
     llbc_ast,
-    meta::InlineAttr,
+    meta::{FileName, InlineAttr, Visibility},
     names::{Name, PathElem},
+    reorder_decls::{AnyTransId, DeclarationGroup, GDeclarationGroup},
+    types::{
+        AutoTraitImpl, CaptureMode, CaptureProjection, CapturedPlace, ClosureKind, ConstGeneric,
+        DeBruijnId, EnumLayout, FieldId, IntegerTy, LiteralTy, OutlivesPred, RefKind, Region,
+        RegionId, TraitInstanceId, TyKind, TypeDeclKind, TypeId, TypeVarId, Variance,
+    },
+    values::ScalarValue,
 };
 
 fn translate(
     code: impl std::fmt::Display,
 ) -> Result<GCrateData<llbc_ast::FunDecl, llbc_ast::GlobalDecl>, Box<dyn Error>> {
-    use charon_lib::driver::CharonCallbacks;
-    use charon_lib::{export, logger};
+    translate_with_options(code, Default::default())
+}
+
+fn translate_with_options(
+    code: impl std::fmt::Display,
+    options: CliOpts,
+) -> Result<GCrateData<llbc_ast::FunDecl, llbc_ast::GlobalDecl>, Box<dyn Error>> {
+    use charon_lib::export;
+    let callback = run_charon(code, options, &[])?;
+    let export::CrateData::LLBC(crate_data) = callback.crate_data.unwrap() else {
+        panic!("expected llbc data, got ullbc instead")
+    };
+    Ok(crate_data)
+}
+
+/// Call charon on the code, and return the callbacks (which contain the
+/// results of the extraction). We pass the additional arguments to rustc.
+fn run_charon(
+    code: impl std::fmt::Display,
+    options: CliOpts,
+    rustc_args: &[&str],
+) -> Result<CharonCallbacks, Box<dyn Error>> {
+    let callback = run_charon_allowing_errors(code, options, rustc_args)?;
+    assert_eq!(callback.error_count, 0);
+    Ok(callback)
+}
+
+/// Same as [run_charon], but the translation may report errors (use
+/// `errors_as_warnings` to get the translated crate anyway).
+fn run_charon_allowing_errors(
+    code: impl std::fmt::Display,
+    options: CliOpts,
+    rustc_args: &[&str],
+) -> Result<CharonCallbacks, Box<dyn Error>> {
+    use charon_lib::logger;
 
     // Initialize the logger
     logger::initialize_logger();
@@ -30,17 +74,12 @@ fn translate(
     }
 
     // Call the Rust compiler with our custom callback.
-    let mut callback = CharonCallbacks::new(Default::default());
-    let args = vec![file_path.to_string_lossy().into_owned()];
+    let mut callback = CharonCallbacks::new(options);
+    let mut args = vec![file_path.to_string_lossy().into_owned()];
+    args.extend(rustc_args.iter().map(|arg| arg.to_string()));
     let res = callback.run_compiler(args);
-    // Extract the computed crate data.
-    assert_eq!(callback.error_count, 0);
     assert!(res.is_ok());
-    let export::CrateData::LLBC(crate_data) = callback.crate_data.unwrap() else {
-        panic!("expected llbc data, got ullbc instead")
-    };
-
-    Ok(crate_data)
+    Ok(callback)
 }
 
 /// `Name` is a complex datastructure; to inspect it we serialize it.
@@ -48,7 +87,7 @@ fn repr_name(n: &Name) -> String {
     n.name
         .iter()
         .map(|path_elem| match path_elem {
-            PathElem::Ident(i, _) => i,
+            PathElem::Ident(i, _) => i.as_str(),
             PathElem::Impl(_) => "<impl>",
         })
         .join("::")
@@ -155,3 +194,4201 @@ fn visibility() -> Result<(), Box<dyn Error>> {
     assert!(crate_data.types[2].item_meta.public);
     Ok(())
 }
+
+#[test]
+fn target_info() -> Result<(), Box<dyn Error>> {
+    let crate_data = translate(
+        "
+        fn main() {}
+        ",
+    )?;
+    // We compile for the host by default.
+    assert_eq!(crate_data.target_info.pointer_width, usize::BITS);
+    assert_eq!(
+        crate_data.target_info.is_little_endian,
+        cfg!(target_endian = "little")
+    );
+    assert_eq!(crate_data.target_info.c_int_width, 32);
+    Ok(())
+}
+
+#[test]
+fn generic_associated_types() -> Result<(), Box<dyn Error>> {
+    let crate_data = translate(
+        "
+        trait LendingIterator {
+            type Item<'a> where Self: 'a;
+        }
+        struct Slice<T>(Vec<T>);
+        impl<T> LendingIterator for Slice<T> {
+            type Item<'a> = &'a T where T: 'a;
+        }
+        ",
+    )?;
+    // The generics of the associated type contain the `'a` parameter.
+    let (name, (generics, _, _)) = &crate_data.trait_decls[0].types[0];
+    assert_eq!(name.0, "Item");
+    assert_eq!(generics.regions.len(), 1);
+    let (name, (generics, _, _)) = &crate_data.trait_impls[0].types[0];
+    assert_eq!(name.0, "Item");
+    assert_eq!(generics.regions.len(), 1);
+    assert_eq!(generics.types.len(), 1);
+    Ok(())
+}
+
+#[test]
+fn generic_associated_type_projections() -> Result<(), Box<dyn Error>> {
+    let crate_data = translate(
+        "
+        trait Container {
+            type Elem<U>;
+        }
+        fn convert<C: Container<Elem<u32> = bool>>(x: C::Elem<u8>) -> C::Elem<u8> {
+            let y: C::Elem<u8> = x;
+            y
+        }
+        ",
+    )?;
+    // The projections keep the own arguments of the associated type.
+    let convert = &crate_data.functions[0];
+    let check_elem_u8 = |ty: &charon_lib::types::Ty| {
+        let TyKind::TraitType(_, name, generics) = ty.kind() else {
+            panic!("expected a projection, got {ty:?}")
+        };
+        assert_eq!(name.0, "Elem");
+        assert_eq!(
+            generics.types[0].kind(),
+            &TyKind::Literal(LiteralTy::Integer(IntegerTy::U8))
+        );
+    };
+    check_elem_u8(&convert.signature.inputs[0]);
+    check_elem_u8(&convert.signature.output);
+    // The return value and the input argument
+    let body = convert.body.as_ref().unwrap();
+    body.locals
+        .iter()
+        .take(2)
+        .for_each(|var| check_elem_u8(&var.ty));
+    let constraint = &convert.signature.preds.trait_type_constraints[0];
+    assert_eq!(constraint.type_name.0, "Elem");
+    assert_eq!(
+        constraint.generics.types[0].kind(),
+        &TyKind::Literal(LiteralTy::Integer(IntegerTy::U32))
+    );
+    assert_eq!(constraint.ty.kind(), &TyKind::Literal(LiteralTy::Bool));
+    Ok(())
+}
+
+#[test]
+fn associated_consts() -> Result<(), Box<dyn Error>> {
+    let crate_data = translate(
+        "
+        trait HasLen {
+            const LEN: usize;
+            const IS_EMPTY: bool = false;
+        }
+        struct Unit;
+        impl HasLen for Unit {
+            const LEN: usize = 0;
+            const IS_EMPTY: bool = true;
+        }
+        fn len<T: HasLen>() -> usize {
+            T::LEN
+        }
+        ",
+    )?;
+    let decl = &crate_data.trait_decls[0];
+    let consts = decl
+        .consts
+        .iter()
+        .map(|(name, (_, default))| (name.0.as_str(), default.is_some()))
+        .collect_vec();
+    assert_eq!(consts, vec![("LEN", false), ("IS_EMPTY", true)]);
+    let consts = crate_data.trait_impls[0]
+        .consts
+        .iter()
+        .map(|(name, _)| name.0.as_str())
+        .collect_vec();
+    assert_eq!(consts, vec!["LEN", "IS_EMPTY"]);
+    Ok(())
+}
+
+#[test]
+fn trait_const_generics() -> Result<(), Box<dyn Error>> {
+    let crate_data = translate(
+        "
+        #![feature(generic_const_exprs)]
+        #![allow(incomplete_features)]
+        trait HasLen {
+            const LEN: usize;
+        }
+        fn id<T: HasLen>(x: [u8; T::LEN]) -> [u8; T::LEN] {
+            x
+        }
+        ",
+    )?;
+    // The length of the arrays is the associated constant of the trait clause.
+    let id = &crate_data.functions[0];
+    let TyKind::Adt(TypeId::Assumed(_), generics) = id.signature.output.kind() else {
+        panic!("expected an array type")
+    };
+    let ConstGeneric::TraitConst(trait_ref, name) = &generics.const_generics[0] else {
+        panic!(
+            "expected a trait constant, got {:?}",
+            generics.const_generics[0]
+        )
+    };
+    assert_eq!(name.0, "LEN");
+    assert!(matches!(trait_ref.trait_id, TraitInstanceId::Clause(_)));
+    assert_eq!(&id.signature.inputs[0], &id.signature.output);
+    Ok(())
+}
+
+#[test]
+fn impl_trait_in_return_position() -> Result<(), Box<dyn Error>> {
+    let crate_data = translate(
+        "
+        fn count() -> impl Iterator<Item = u32> {
+            0..10
+        }
+        ",
+    )?;
+    let opaque = crate_data
+        .types
+        .iter()
+        .find(|d| d.kind.is_impl_trait())
+        .unwrap();
+    assert_eq!(repr_name(&opaque.name), "test_crate::count::opaque");
+    let TypeDeclKind::ImplTrait { bounds, hidden_ty } = &opaque.kind else {
+        unreachable!()
+    };
+    assert_eq!(bounds.len(), 1);
+    assert_eq!(bounds[0].type_constraints.len(), 1);
+    assert!(hidden_ty.is_some());
+    // The output of the function refers to the opaque type
+    let output = &crate_data.functions[0].signature.output;
+    assert!(matches!(output.kind(), TyKind::Adt(TypeId::Adt(id), _) if *id == opaque.def_id));
+    Ok(())
+}
+
+#[test]
+fn nested_impl_trait() -> Result<(), Box<dyn Error>> {
+    let crate_data = translate(
+        "
+        fn pair() -> (impl Clone, Option<impl Copy>) {
+            (0u8, Some(1u32))
+        }
+        ",
+    )?;
+    let opaque_ids = crate_data
+        .types
+        .iter()
+        .filter(|d| d.kind.is_impl_trait())
+        .map(|d| d.def_id)
+        .collect_vec();
+    assert_eq!(opaque_ids.len(), 2);
+    // The output of the function refers to the opaque types
+    let output = &crate_data.functions[0].signature.output;
+    let TyKind::Adt(TypeId::Tuple, generics) = output.kind() else {
+        panic!("expected a tuple, got {output:?}")
+    };
+    let TyKind::Adt(TypeId::Adt(clone_id), _) = generics.types[0].kind() else {
+        panic!("expected an opaque type")
+    };
+    let TyKind::Adt(_, generics) = generics.types[1].kind() else {
+        panic!("expected an option")
+    };
+    let TyKind::Adt(TypeId::Adt(copy_id), _) = generics.types[0].kind() else {
+        panic!("expected an opaque type")
+    };
+    assert!(clone_id != copy_id);
+    assert!(opaque_ids.contains(clone_id) && opaque_ids.contains(copy_id));
+    Ok(())
+}
+
+#[test]
+fn higher_ranked_trait_clauses() -> Result<(), Box<dyn Error>> {
+    let crate_data = translate(
+        "
+        trait Foo<'a> {
+            fn foo(&self, x: &'a u32);
+        }
+        fn call_foo<T: for<'a> Foo<'a>>(x: &T, y: &u32) {
+            x.foo(y)
+        }
+        ",
+    )?;
+    let call_foo = crate_data
+        .functions
+        .iter()
+        .find(|f| repr_name(&f.name) == "test_crate::call_foo")
+        .unwrap();
+    let clauses = &call_foo.signature.generics.trait_clauses;
+    assert_eq!(clauses.len(), 1);
+    assert_eq!(clauses[0].bound_regions.len(), 1);
+    assert_eq!(clauses[0].generics.regions.len(), 1);
+    Ok(())
+}
+
+#[test]
+fn provided_methods() -> Result<(), Box<dyn Error>> {
+    let crate_data = translate(
+        "
+        trait Greet {
+            fn name(&self) -> u32;
+            fn greet(&self) -> u32 {
+                self.name()
+            }
+        }
+        struct A;
+        struct B;
+        impl Greet for A {
+            fn name(&self) -> u32 { 0 }
+        }
+        impl Greet for B {
+            fn name(&self) -> u32 { 1 }
+            fn greet(&self) -> u32 { 2 }
+        }
+        ",
+    )?;
+    let decl = &crate_data.trait_decls[0];
+    assert_eq!(decl.required_methods.len(), 1);
+    let (name, default) = &decl.provided_methods[0];
+    assert_eq!(name.0, "greet");
+    let default = default.unwrap();
+    // The default implementation is translated
+    let default_decl = crate_data
+        .functions
+        .iter()
+        .find(|f| f.def_id == default)
+        .unwrap();
+    assert!(default_decl.body.is_some());
+    // The impl for `A` refers to the default implementation
+    let provided = &crate_data.trait_impls[0].provided_methods;
+    assert_eq!(provided.len(), 1);
+    assert_eq!(provided[0].1, (default, false));
+    // The impl for `B` overrides it
+    let provided = &crate_data.trait_impls[1].provided_methods;
+    assert_eq!(provided.len(), 1);
+    assert!(provided[0].1 .1);
+    assert_ne!(provided[0].1 .0, default);
+    Ok(())
+}
+
+#[test]
+fn marker_trait_filtering() -> Result<(), Box<dyn Error>> {
+    let code = "
+        fn f<T: Clone + Send>(x: &T) -> T {
+            x.clone()
+        }
+        ";
+    let trait_clause_names = |crate_data: &GCrateData<llbc_ast::FunDecl, llbc_ast::GlobalDecl>| {
+        let f = crate_data
+            .functions
+            .iter()
+            .find(|f| repr_name(&f.name) == "test_crate::f")
+            .unwrap();
+        f.signature
+            .generics
+            .trait_clauses
+            .iter()
+            .map(|c| {
+                let decl = crate_data
+                    .trait_decls
+                    .iter()
+                    .find(|d| d.def_id == c.trait_id)
+                    .unwrap();
+                repr_name(&decl.name)
+            })
+            .collect_vec()
+    };
+
+    // By default, we filter the marker traits
+    let crate_data = translate(code)?;
+    assert_eq!(trait_clause_names(&crate_data), vec!["core::clone::Clone"]);
+
+    // Additionally filter `Clone`, but keep `Send`
+    let options = CliOpts {
+        ignored_traits: vec!["core::clone::Clone".to_string()],
+        kept_traits: vec!["core::marker::Send".to_string()],
+        ..Default::default()
+    };
+    let crate_data = translate_with_options(code, options)?;
+    assert_eq!(trait_clause_names(&crate_data), vec!["core::marker::Send"]);
+
+    // Keep all the clauses: their ids are contiguous
+    let options = CliOpts {
+        keep_all_trait_clauses: true,
+        ..Default::default()
+    };
+    let crate_data = translate_with_options(code, options)?;
+    let f = crate_data
+        .functions
+        .iter()
+        .find(|f| repr_name(&f.name) == "test_crate::f")
+        .unwrap();
+    let clauses = &f.signature.generics.trait_clauses;
+    assert_eq!(clauses.len(), 3);
+    assert!(clauses
+        .iter()
+        .enumerate()
+        .all(|(i, c)| c.clause_id.index() == i));
+    Ok(())
+}
+
+#[test]
+fn sized_type_params() -> Result<(), Box<dyn Error>> {
+    let crate_data = translate(
+        "
+        struct Wrapper<T: ?Sized, U>(Box<T>, U);
+        trait Trait {}
+        ",
+    )?;
+    let sized = crate_data.types[0]
+        .generics
+        .types
+        .iter()
+        .map(|v| (v.name.as_str(), v.is_sized))
+        .collect_vec();
+    assert_eq!(sized, vec![("T", false), ("U", true)]);
+    // The `Self` parameter of a trait may be unsized
+    let self_var = &crate_data.trait_decls[0].generics.types[0];
+    assert!(!self_var.is_sized);
+    Ok(())
+}
+
+#[test]
+fn regions_graph() -> Result<(), Box<dyn Error>> {
+    let crate_data = translate(
+        "
+        struct Ref<'a, T>(&'a T);
+        fn f<'a, 'b, T>(_x: &'a &'b T, _y: Ref<'b, T>) {}
+        ",
+    )?;
+    let rid = RegionId::Id::new;
+    let tid = TypeVarId::Id::new;
+    let bvar = |i| Region::BVar(DeBruijnId::new(0), rid(i));
+    // The implied bounds of the type declaration
+    let graph = &crate_data.types[0].preds.regions_graph;
+    assert_eq!(graph.regions[rid(0)], vec![]);
+    assert_eq!(graph.types[tid(0)], vec![bvar(0)]);
+    // The implied bounds of the function, which are transitively closed
+    let graph = &crate_data.functions[0].signature.preds.regions_graph;
+    // `&'a &'b T` implies `'b: 'a` and `T: 'b`
+    assert_eq!(graph.regions[rid(0)], vec![]);
+    assert_eq!(graph.regions[rid(1)], vec![bvar(0)]);
+    assert_eq!(graph.types[tid(0)], vec![bvar(0), bvar(1)]);
+    Ok(())
+}
+
+#[test]
+fn body_regions() -> Result<(), Box<dyn Error>> {
+    let code = "fn f<'a>(x: &'a mut u32) -> &'a u32 { &*x }";
+    let options = CliOpts {
+        body_regions: true,
+        ..Default::default()
+    };
+    let crate_data = translate_with_options(code, options)?;
+    let body = crate_data.functions[0].body.as_ref().unwrap();
+    // The references of the body carry region variables
+    assert!(!body.regions.vars.is_empty());
+    assert!(body
+        .locals
+        .iter()
+        .any(|v| matches!(v.ty.kind(), TyKind::Ref(Region::Body(_), ..))));
+    // The region of the signature is constrained by the body regions
+    let sig_region = Region::BVar(DeBruijnId::new(0), RegionId::Id::new(0));
+    assert!(body
+        .regions
+        .constraints
+        .iter()
+        .any(|OutlivesPred(r0, r1)| *r0 == sig_region || *r1 == sig_region));
+
+    // By default, the regions are erased
+    let crate_data = translate(code)?;
+    let body = crate_data.functions[0].body.as_ref().unwrap();
+    assert!(body.regions.vars.is_empty());
+    assert!(body.regions.constraints.is_empty());
+    assert!(body
+        .locals
+        .iter()
+        .all(|v| !matches!(v.ty.kind(), TyKind::Ref(Region::Body(_), ..))));
+    Ok(())
+}
+
+#[test]
+fn borrowck_facts() -> Result<(), Box<dyn Error>> {
+    let code = "
+        fn f() -> u32 {
+            let mut x = 0;
+            let y = &mut x;
+            *y += 1;
+            x
+        }
+        ";
+    let options = CliOpts {
+        borrowck_facts: true,
+        ..Default::default()
+    };
+    let crate_data = translate_with_options(code, options)?;
+    let body = crate_data.functions[0].body.as_ref().unwrap();
+    // The bodies regions are not erased
+    assert!(!body.regions.vars.is_empty());
+    let facts = body.borrowck_facts.as_ref().unwrap();
+    assert_eq!(facts.loans.len(), 1);
+    let loan = facts.loans.iter().next().unwrap();
+    assert!(matches!(loan.region, Region::Body(_)));
+    // The loan goes out of scope before we read `x`
+    assert!(facts
+        .loans_out_of_scope_at
+        .iter()
+        .any(|(_, loans)| loans.contains(&loan.index)));
+
+    // By default, we don't compute the facts
+    let crate_data = translate(code)?;
+    let body = crate_data.functions[0].body.as_ref().unwrap();
+    assert!(body.borrowck_facts.is_none());
+    Ok(())
+}
+
+#[test]
+fn hash_consed_types() -> Result<(), Box<dyn Error>> {
+    let crate_data = translate(
+        "
+        struct Foo(u32, (u32, bool), (u32, bool));
+        ",
+    )?;
+    let TypeDeclKind::Struct(fields) = &crate_data.types[0].kind else {
+        panic!()
+    };
+    // The structurally equal types are shared
+    assert_eq!(fields[1].ty, fields[2].ty);
+    assert_ne!(fields[0].ty, fields[1].ty);
+    // The hash-consing doesn't appear in the serialized types
+    assert_eq!(
+        serde_json::to_value(&fields[0].ty)?,
+        serde_json::json!({ "Literal": { "Integer": "U32" } })
+    );
+    Ok(())
+}
+
+#[test]
+fn hash_consed_generic_args() -> Result<(), Box<dyn Error>> {
+    let crate_data = translate(
+        "
+        struct A<T>(T);
+        struct B<T>(T);
+        struct Foo(A<u32>, B<u32>);
+        ",
+    )?;
+    let foo = crate_data
+        .types
+        .iter()
+        .find(|ty| repr_name(&ty.name) == "test_crate::Foo")
+        .unwrap();
+    let TypeDeclKind::Struct(fields) = &foo.kind else {
+        panic!()
+    };
+    let (TyKind::Adt(_, a_args), TyKind::Adt(_, b_args)) = (fields[0].ty.kind(), fields[1].ty.kind())
+    else {
+        panic!()
+    };
+    // The types are different, but their generic arguments are shared
+    assert_ne!(fields[0].ty, fields[1].ty);
+    assert_eq!(a_args, b_args);
+    // The hash-consing doesn't appear in the serialized generic arguments
+    assert_eq!(
+        serde_json::to_value(a_args)?,
+        serde_json::json!({
+            "regions": [],
+            "types": [{ "Literal": { "Integer": "U32" } }],
+            "const_generics": [],
+            "trait_refs": [],
+        })
+    );
+    Ok(())
+}
+
+#[test]
+fn flat_blocks() -> Result<(), Box<dyn Error>> {
+    let crate_data = translate(
+        "
+        fn f(x: u32) -> u32 {
+            let y = x + 1;
+            let z = y * 2;
+            if z > 10 { z } else { y }
+        }
+        ",
+    )?;
+    let body = &crate_data.functions[0].body.as_ref().unwrap().body;
+    // The statements of the body are in a flat list, which contains the `if`
+    // and ends with the `return` which follows it
+    assert!(body.statements.len() > 2);
+    assert!(body.statements.iter().any(|st| st.content.is_switch()));
+    assert!(body.statements.last().unwrap().content.is_return());
+    // The blocks are serialized as lists of statements
+    let json = serde_json::to_value(&crate_data)?;
+    assert_eq!(
+        json["format_version"],
+        charon_lib::export::LLBC_FORMAT_VERSION
+    );
+    let json_body = &json["functions"][0]["body"]["body"];
+    assert_eq!(
+        json_body["statements"].as_array().unwrap().len(),
+        body.statements.len()
+    );
+    Ok(())
+}
+
+#[test]
+fn huge_body() -> Result<(), Box<dyn Error>> {
+    // Every addition is followed by an overflow check, which introduces a new
+    // block: we check that the translation doesn't overflow the stack
+    let statements = "x = x + 1;\n".repeat(10000);
+    let crate_data = translate(format!(
+        "
+        fn f(mut x: u32) -> u32 {{
+            {statements}
+            x
+        }}
+        "
+    ))?;
+    let body = &crate_data.functions[0].body.as_ref().unwrap().body;
+    assert!(body.statements.len() >= 10000);
+    Ok(())
+}
+
+#[test]
+fn serialize_error() -> Result<(), Box<dyn Error>> {
+    use charon_lib::common::CharonError;
+    let crate_data = translate(
+        "
+        fn main() {}
+        ",
+    )?;
+    // We can't create a directory inside a file
+    let tmp_file = tempfile::NamedTempFile::new()?;
+    let dest_file = tmp_file.path().join("test_crate.llbc");
+    let Err(err) = crate_data.serialize_to_file(&dest_file) else {
+        panic!("the serialization should have failed")
+    };
+    assert!(matches!(err, CharonError::Io { .. }));
+    assert_eq!(err.exit_code(), 2);
+    Ok(())
+}
+
+#[test]
+fn report() -> Result<(), Box<dyn Error>> {
+    use charon_lib::report::ItemStatus;
+    let options = CliOpts {
+        opaque_modules: vec!["opaque".to_string(), "skipped".to_string()],
+        report: Some("report.json".into()),
+        ..Default::default()
+    };
+    let callback = run_charon(
+        "
+        mod opaque {
+            pub fn foo() {}
+        }
+        mod skipped {
+            pub fn bar() {}
+        }
+        fn main() {
+            opaque::foo()
+        }
+        ",
+        options,
+        &[],
+    )?;
+    let report = callback.report.unwrap();
+    let status = |name: &str| {
+        let item = report.items.iter().find(|item| item.name == name).unwrap();
+        (item.kind.as_str(), &item.status)
+    };
+    assert_eq!(
+        status("test_crate::main"),
+        ("function", &ItemStatus::Translated)
+    );
+    let ("function", ItemStatus::Opaque { .. }) = status("test_crate::opaque::foo") else {
+        panic!("`foo` should be opaque")
+    };
+    let ("module", ItemStatus::Skipped { .. }) = status("test_crate::skipped") else {
+        panic!("`skipped` should be skipped")
+    };
+    assert!(report
+        .items
+        .iter()
+        .all(|item| !item.name.starts_with("test_crate::skipped::")));
+    Ok(())
+}
+
+#[test]
+fn charon_attributes() -> Result<(), Box<dyn Error>> {
+    let crate_data = translate(
+        r#"
+        #[charon::rename("Bar")]
+        struct Foo;
+
+        #[charon::opaque]
+        fn opaque() {}
+
+        #[charon::opaque]
+        mod opaque_mod {
+            pub fn foo() {}
+        }
+
+        #[charon::skip]
+        fn skipped() {}
+
+        fn main() {
+            opaque();
+            opaque_mod::foo();
+        }
+        "#,
+    )?;
+    assert_eq!(repr_name(&crate_data.types[0].name), "test_crate::Bar");
+    let function = |name: &str| {
+        crate_data
+            .functions
+            .iter()
+            .find(|f| repr_name(&f.name) == name)
+    };
+    assert!(function("test_crate::opaque").unwrap().body.is_none());
+    assert!(function("test_crate::opaque_mod::foo")
+        .unwrap()
+        .body
+        .is_none());
+    assert!(function("test_crate::main").unwrap().body.is_some());
+    assert!(function("test_crate::skipped").is_none());
+    Ok(())
+}
+
+#[test]
+fn rename_table() -> Result<(), Box<dyn Error>> {
+    use std::io::Write;
+    let mut table = tempfile::NamedTempFile::new()?;
+    write!(
+        table,
+        r#"
+        [[rename]]
+        pattern = "test_crate::Foo"
+        name = "Bar"
+
+        [[rename]]
+        pattern = "test_crate::*::new"
+        name = "make"
+        "#
+    )?;
+    let options = CliOpts {
+        rename_table: Some(table.path().to_path_buf()),
+        ..Default::default()
+    };
+    let crate_data = translate_with_options(
+        "
+        struct Foo;
+        impl Foo {
+            fn new() -> Self {
+                Foo
+            }
+        }
+        fn main() {
+            let _ = Foo::new();
+        }
+        ",
+        options,
+    )?;
+    assert_eq!(repr_name(&crate_data.types[0].name), "test_crate::Bar");
+    let names = crate_data
+        .functions
+        .iter()
+        .map(|f| repr_name(&f.name))
+        .collect_vec();
+    assert!(names.contains(&"test_crate::<impl>::make".to_string()));
+    assert!(names.contains(&"test_crate::main".to_string()));
+    Ok(())
+}
+
+#[test]
+fn stable_ids() -> Result<(), Box<dyn Error>> {
+    let before = translate(
+        "
+        struct Foo;
+        fn main() {}
+        ",
+    )?;
+    // Adding an item changes the numeric ids, but not the stable ids
+    let after = translate(
+        "
+        struct Bar;
+        struct Foo;
+        fn main() {}
+        ",
+    )?;
+    let find = |crate_data: &GCrateData<_, _>, name: &str| {
+        crate_data
+            .types
+            .iter()
+            .find(|ty| repr_name(&ty.name) == name)
+            .unwrap()
+            .clone()
+    };
+    let foo_before = find(&before, "test_crate::Foo");
+    let foo_after = find(&after, "test_crate::Foo");
+    let bar_after = find(&after, "test_crate::Bar");
+    assert_ne!(foo_before.def_id, foo_after.def_id);
+    assert_eq!(
+        foo_before.item_meta.stable_id,
+        foo_after.item_meta.stable_id
+    );
+    assert_ne!(foo_after.item_meta.stable_id, bar_after.item_meta.stable_id);
+    Ok(())
+}
+
+#[test]
+fn diff() -> Result<(), Box<dyn Error>> {
+    use charon_lib::diff::{diff_files, Change, DeclKind, ItemDiff};
+    let old = translate(
+        "
+        struct Foo;
+        fn foo() -> u32 { 0 }
+        fn bar() {}
+        fn main() { let _ = foo(); }
+        ",
+    )?;
+    // We add items before the others, so that the numeric ids change
+    let new = translate(
+        "
+        struct Baz;
+        fn qux() {}
+        struct Foo;
+        fn foo() -> u32 { 1 }
+        fn main() { let _ = foo(); }
+        ",
+    )?;
+    let tmp_dir = tempfile::TempDir::new()?;
+    let old_file = tmp_dir.path().join("old.llbc");
+    let new_file = tmp_dir.path().join("new.llbc");
+    old.serialize_to_file(&old_file)?;
+    new.serialize_to_file(&new_file)?;
+
+    let diffs = diff_files(&old_file, &new_file)?;
+    let diff = |kind, name: &str, change| ItemDiff {
+        kind,
+        name: name.to_string(),
+        change,
+    };
+    assert_eq!(
+        diffs,
+        vec![
+            diff(DeclKind::Type, "test_crate::Baz", Change::Added),
+            diff(DeclKind::Fun, "test_crate::bar", Change::Removed),
+            diff(
+                DeclKind::Fun,
+                "test_crate::foo",
+                Change::Changed(vec!["body".to_string()])
+            ),
+            diff(DeclKind::Fun, "test_crate::qux", Change::Added),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn split_output() -> Result<(), Box<dyn Error>> {
+    use charon_lib::export::INDEX_FILE;
+    use serde_json::Value;
+    let crate_data = translate(
+        "
+        struct Foo;
+        fn foo() -> Foo { Foo }
+        fn main() { let _ = foo(); }
+        ",
+    )?;
+    let tmp_dir = tempfile::TempDir::new()?;
+    crate_data.serialize_split_to_dir(tmp_dir.path())?;
+    let read = |file: &str| -> Result<Value, Box<dyn Error>> {
+        let file = File::open(tmp_dir.path().join(file))?;
+        Ok(serde_json::from_reader(file)?)
+    };
+
+    let index = read(INDEX_FILE)?;
+    let groups = index["groups"].as_array().unwrap();
+    assert_eq!(groups.len(), crate_data.declarations.len());
+    // Retrieve the group files, together with the last identifier of the name of
+    // the (single) declaration they contain.
+    let mut files = Vec::new();
+    for (i, entry) in groups.iter().enumerate() {
+        let file = read(entry["file"].as_str().unwrap())?;
+        assert_eq!(file["index"], i);
+        assert_eq!(file["depends_on"], entry["depends_on"]);
+        let kinds = [
+            "types",
+            "functions",
+            "globals",
+            "trait_decls",
+            "trait_impls",
+        ];
+        let decls = kinds
+            .iter()
+            .flat_map(|kind| file[kind].as_array().unwrap().clone())
+            .collect_vec();
+        assert_eq!(decls.len(), 1);
+        let name = decls[0]["name"].as_array().unwrap().last().unwrap()["Ident"][0].clone();
+        // The symbols are indices in the string table of the index
+        let name = &index["strings"][name.as_u64().unwrap() as usize];
+        files.push((name.as_str().unwrap().to_string(), file));
+    }
+    let group_of = |name: &str| files.iter().position(|(n, _)| n == name).unwrap();
+    // `foo` refers to `Foo`, and `main` to `foo`
+    let deps = |name: &str| files[group_of(name)].1["depends_on"].clone();
+    assert_eq!(deps("Foo"), serde_json::json!([]));
+    assert_eq!(deps("foo"), serde_json::json!([group_of("Foo")]));
+    let main_deps = deps("main");
+    assert!(main_deps
+        .as_array()
+        .unwrap()
+        .contains(&group_of("foo").into()));
+    Ok(())
+}
+
+#[test]
+fn split_output_unchanged_groups() -> Result<(), Box<dyn Error>> {
+    use charon_lib::export::INDEX_FILE;
+    use serde_json::Value;
+    let crate_data = translate(
+        "
+        struct Foo;
+        fn foo() -> Foo { Foo }
+        fn main() { let _ = foo(); }
+        ",
+    )?;
+    let tmp_dir = tempfile::TempDir::new()?;
+    crate_data.serialize_split_to_dir(tmp_dir.path())?;
+    let index_path = tmp_dir.path().join(INDEX_FILE);
+    let index: Value = serde_json::from_reader(File::open(&index_path)?)?;
+    let files = index["groups"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|entry| tmp_dir.path().join(entry["file"].as_str().unwrap()))
+        .collect_vec();
+
+    // We mark the group files: a file loses its mark when we rewrite it
+    for file in &files {
+        let mut content = std::fs::read(file)?;
+        content.push(b' ');
+        std::fs::write(file, content)?;
+    }
+    let marked = |file: &std::path::Path| -> Result<bool, Box<dyn Error>> {
+        Ok(std::fs::read(file)?.ends_with(b" "))
+    };
+    // Pretend that the first group changed since the previous extraction
+    let mut previous_index = index.clone();
+    previous_index["groups"][0]["hash"] = Value::from("");
+    std::fs::write(&index_path, serde_json::to_vec(&previous_index)?)?;
+
+    crate_data.serialize_split_to_dir(tmp_dir.path())?;
+    assert!(!marked(&files[0])?);
+    for file in &files[1..] {
+        assert!(marked(file)?);
+    }
+    // We kept the string table: the symbols of the files we didn't rewrite
+    // still refer to the right strings
+    let new_index: Value = serde_json::from_reader(File::open(&index_path)?)?;
+    assert_eq!(new_index, index);
+    Ok(())
+}
+
+#[test]
+fn compressed_output() -> Result<(), Box<dyn Error>> {
+    use charon_lib::diff::diff_files;
+    let crate_data = translate(
+        "
+        fn foo() -> u32 { 0 }
+        ",
+    )?;
+    let tmp_dir = tempfile::TempDir::new()?;
+    let plain_file = tmp_dir.path().join("test_crate.llbc");
+    let compressed_file = tmp_dir.path().join("test_crate.llbc.zst");
+    crate_data.serialize_to_file(&plain_file)?;
+    crate_data.serialize_to_file(&compressed_file)?;
+
+    let compressed = std::fs::read(&compressed_file)?;
+    assert!(compressed.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]));
+    assert_eq!(
+        zstd::decode_all(compressed.as_slice())?,
+        std::fs::read(&plain_file)?
+    );
+    // The reader transparently decompresses the files
+    assert!(diff_files(&plain_file, &compressed_file)?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn canonical_output() -> Result<(), Box<dyn Error>> {
+    use serde_json::{json, Value};
+    let code = "
+        struct B;
+        struct A;
+        macro_rules! b {
+            () => { B };
+        }
+        macro_rules! a {
+            ($x:expr) => { b!() };
+        }
+        fn f(_: A) -> B { b!() }
+        fn g(x: A) -> B { a!(x) }
+        ";
+    // We translate the crate twice (in different temporary directories)
+    let tmp_dir = tempfile::TempDir::new()?;
+    let mut files = Vec::new();
+    for i in 0..2 {
+        let mut crate_data = translate(code)?;
+        crate_data.canonical = true;
+        let file = tmp_dir.path().join(format!("test_crate{i}.llbc"));
+        crate_data.serialize_to_file(&file)?;
+        files.push(std::fs::read(file)?);
+    }
+    assert_eq!(files[0], files[1]);
+
+    // The declarations are numbered in the order of their names, and the
+    // local files are relative to their directory
+    let mut krate: Value = serde_json::from_slice(&files[0])?;
+    charon_lib::diff::resolve_symbols(&mut krate);
+    let last_ident = |decl: &Value| decl["name"].as_array().unwrap().last().unwrap().clone();
+    assert_eq!(last_ident(&krate["types"][0]), json!({ "Ident": ["A", 0] }));
+    assert_eq!(krate["types"][0]["def_id"], 0);
+    assert_eq!(last_ident(&krate["types"][1]), json!({ "Ident": ["B", 0] }));
+    let inputs = &krate["functions"][0]["signature"]["inputs"];
+    assert_eq!(inputs[0]["Adt"][0], json!({ "Adt": 0 }));
+    assert!(krate["id_to_file"]
+        .as_array()
+        .unwrap()
+        .contains(&json!([0, { "Local": "test_crate.rs" }, null])));
+    // The macro expansions are numbered in the order of their contents
+    let expansions = krate["macro_expansions"].as_array().unwrap();
+    let names: Vec<&str> = expansions
+        .iter()
+        .map(|expansion| expansion["macro_name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["a", "b", "b"]);
+    // One of the calls of `b` is nested in the expansion of `a`
+    let mut parents: Vec<&Value> = expansions[1..]
+        .iter()
+        .map(|expansion| &expansion["parent"])
+        .collect();
+    parents.sort_by_key(|parent| parent.is_null());
+    assert_eq!(parents, [&json!(0), &Value::Null]);
+    Ok(())
+}
+
+#[test]
+fn canonical_rec_groups() -> Result<(), Box<dyn Error>> {
+    use charon_lib::diff::{diff_files, Change};
+    use serde_json::Value;
+    let code = "
+        fn odd(n: u32) -> bool { if n == 0 { false } else { even(n - 1) } }
+        fn even(n: u32) -> bool { if n == 0 { true } else { odd(n - 1) } }
+        enum Tree { Leaf, Node(Forest) }
+        struct Forest { trees: Vec<Tree> }
+        ";
+    // We add items which come first in the canonical order, so that the
+    // numeric ids of the recursive groups change
+    let shifted_code = format!("fn a() {{}} fn b() {{}} struct A; struct B; {code}");
+    let tmp_dir = tempfile::TempDir::new()?;
+    let mut files = Vec::new();
+    for (i, code) in [code, shifted_code.as_str()].into_iter().enumerate() {
+        let mut crate_data = translate(code)?;
+        crate_data.canonical = true;
+        let file = tmp_dir.path().join(format!("test_crate{i}.llbc"));
+        crate_data.serialize_to_file(&file)?;
+        files.push(file);
+    }
+    // The recursive groups are renumbered with the declarations
+    for file in &files {
+        let mut krate: Value = serde_json::from_slice(&std::fs::read(file)?)?;
+        charon_lib::diff::resolve_symbols(&mut krate);
+        for (field, names) in [
+            ("functions", ["even", "odd"]),
+            ("types", ["Forest", "Tree"]),
+        ] {
+            let decls = krate[field].as_array().unwrap();
+            let last_ident = |id: &Value| {
+                let decl = decls.iter().find(|d| &d["def_id"] == id).unwrap();
+                let name = decl["name"].as_array().unwrap();
+                name.last().unwrap()["Ident"][0].clone()
+            };
+            for decl in decls {
+                let Some(group) = decl["rec_group"].as_array() else {
+                    continue;
+                };
+                let group_names: Vec<Value> = group.iter().map(last_ident).collect();
+                assert_eq!(group_names, names);
+            }
+        }
+    }
+    // The recursive groups don't show up in the differences: only the new
+    // items do
+    let diffs = diff_files(&files[0], &files[1])?;
+    assert_eq!(diffs.len(), 4);
+    assert!(diffs.iter().all(|diff| diff.change == Change::Added));
+    Ok(())
+}
+
+#[test]
+fn deterministic_output() -> Result<(), Box<dyn Error>> {
+    use serde_json::Value;
+    let code = "
+        use std::collections::HashMap;
+        trait Trait<T> {
+            fn method(&self, x: T) -> T;
+        }
+        struct Foo<T>(T);
+        impl<T: Clone> Trait<T> for Foo<T> {
+            fn method(&self, _: T) -> T {
+                self.0.clone()
+            }
+        }
+        fn apply<T: Clone, F: Fn(T) -> T>(f: F, x: T) -> T {
+            f(x)
+        }
+        fn main() {
+            let mut map = HashMap::new();
+            map.insert(0u32, Foo(1u64));
+            let y = apply(|x: u64| x + 1, 0);
+            let _ = map.get(&0).map(|foo| foo.method(y));
+        }
+        ";
+    // We translate the crate twice, without `--canonical`: the outputs only
+    // differ by the names of the temporary files
+    let tmp_dir = tempfile::TempDir::new()?;
+    let mut outputs = Vec::new();
+    for i in 0..2 {
+        let crate_data = translate(code)?;
+        let file = tmp_dir.path().join(format!("test_crate{i}.llbc"));
+        crate_data.serialize_to_file(&file)?;
+        let mut krate: Value = serde_json::from_slice(&std::fs::read(file)?)?;
+        krate.as_object_mut().unwrap().remove("id_to_file");
+        outputs.push(krate);
+    }
+    assert!(outputs[0] == outputs[1]);
+    Ok(())
+}
+
+#[test]
+fn content_hashes() -> Result<(), Box<dyn Error>> {
+    let before = translate(
+        "
+        struct Foo;
+        fn foo() -> Foo { Foo }
+        fn bar() -> u32 { 0 }
+        ",
+    )?;
+    // We add an item (which changes the numeric ids and the spans) and change
+    // the body of `bar`
+    let after = translate(
+        "
+        struct Baz;
+
+        struct Foo;
+        fn foo() -> Foo { Foo }
+        fn bar() -> u32 { 1 }
+        ",
+    )?;
+    let hash = |crate_data: &GCrateData<_, _>, name: &str| {
+        crate_data
+            .functions
+            .iter()
+            .find(|f| repr_name(&f.name) == name)
+            .unwrap()
+            .item_meta
+            .hash
+            .clone()
+    };
+    assert!(!hash(&before, "test_crate::foo").is_empty());
+    assert_eq!(
+        hash(&before, "test_crate::foo"),
+        hash(&after, "test_crate::foo")
+    );
+    assert_ne!(
+        hash(&before, "test_crate::bar"),
+        hash(&after, "test_crate::bar")
+    );
+    assert_ne!(
+        hash(&before, "test_crate::foo"),
+        hash(&before, "test_crate::bar")
+    );
+    Ok(())
+}
+
+#[test]
+fn generate_ml() -> Result<(), Box<dyn Error>> {
+    let generated = charon_lib::generate_ml::generate()?;
+    // The indices are integers, and we follow the representation of serde
+    assert!(generated.types.contains("type type_decl_id = int"));
+    assert!(generated.types.contains("loc = { line : int; col : int }"));
+    assert!(generated
+        .types
+        .contains("| Adt of type_decl_id * variant_id option"));
+    assert!(generated
+        .of_json
+        .contains("| `Assoc [ (\"ProjAdt\", `List [ x0; x1 ]) ] ->"));
+    assert!(generated.of_json.contains("loc_of_json (js : json)"));
+    // The symbols are serialized as indices in the string table
+    assert!(generated.types.contains("and symbol = int"));
+    assert!(generated.types.contains("and string_table = string list"));
+    assert!(generated
+        .types
+        .contains("and ('fd, 'gd) g_crate_data = { format_version : int;"));
+    assert!(generated.types.contains("strings : string_table }"));
+    Ok(())
+}
+
+#[test]
+fn sexp_output() -> Result<(), Box<dyn Error>> {
+    use charon_lib::cli_options::OutputFormat;
+    let mut crate_data = translate(
+        "
+        struct Foo;
+        ",
+    )?;
+    crate_data.format = OutputFormat::Sexp;
+    let tmp_dir = tempfile::TempDir::new()?;
+    let file = tmp_dir.path().join("test_crate.llbc");
+    crate_data.serialize_to_file(&file)?;
+    let sexp = std::fs::read_to_string(file)?;
+    let version = charon_lib::export::LLBC_FORMAT_VERSION;
+    assert!(sexp.starts_with(&format!(
+        "((\"format_version\" . {version}) (\"name\" . \"test_crate\")"
+    )));
+    // The symbols are indices in the string table
+    assert!(sexp.contains("(\"Ident\" . (1 0))"));
+    assert!(sexp.contains("(\"strings\" . (\"test_crate\" \"Foo\""));
+    // We write the crate one field at a time: this gives the translation of
+    // the whole serialized crate
+    let value = charon_lib::symbol::with_string_table(|| serde_json::to_value(&crate_data))?;
+    let mut out = Vec::new();
+    charon_lib::sexp::write_value(&mut out, &value)?;
+    assert_eq!(sexp, String::from_utf8(out)?);
+
+    // The translation of the JSON values
+    let mut out = Vec::new();
+    let value = serde_json::json!({ "a": [null, true, "\"x\""], "b": {} });
+    charon_lib::sexp::write_value(&mut out, &value)?;
+    assert_eq!(
+        String::from_utf8(out)?,
+        r#"(("a" . (:null :true "\"x\"")) ("b" . ()))"#
+    );
+    Ok(())
+}
+
+#[cfg(feature = "protobuf")]
+#[test]
+fn protobuf_output() -> Result<(), Box<dyn Error>> {
+    use charon_lib::cli_options::OutputFormat;
+    use charon_lib::protobuf::Schema;
+
+    // The registered numbers don't change, and the new fields get the next
+    // free numbers
+    let schema = Schema::new("TyKind.adt = 42\nTyKind.removed = 43\n")?.to_proto();
+    assert!(schema.contains("message TyKind {\n  reserved 43;\n  reserved \"removed\";"));
+    assert!(schema.contains(" adt = 42;"));
+    assert!(schema.contains(" type_var = 44;"));
+
+    let mut crate_data = translate(
+        "
+        struct Foo;
+        ",
+    )?;
+    crate_data.format = OutputFormat::Protobuf;
+    let tmp_dir = tempfile::TempDir::new()?;
+    let file = tmp_dir.path().join("test_crate.llbc");
+    crate_data.serialize_to_file(&file)?;
+    let bytes = std::fs::read(file)?;
+    // The first field is the format version, encoded as a `sint64`
+    let version = charon_lib::export::LLBC_FORMAT_VERSION as u8;
+    assert_eq!(bytes[..2], [0x08, version * 2]);
+    // We write the crate one field at a time: this gives the encoding of the
+    // whole serialized crate
+    let value = charon_lib::symbol::with_string_table(|| serde_json::to_value(&crate_data))?;
+    let schema = Schema::registered()?;
+    assert_eq!(
+        bytes,
+        schema.encode(charon_lib::protobuf::LLBC_CRATE, &value)?
+    );
+    Ok(())
+}
+
+#[cfg(feature = "protobuf")]
+#[test]
+fn protobuf_numbering() -> Result<(), Box<dyn Error>> {
+    // The registered numbering must be updated when the AST changes: run
+    // `charon --generate-proto <dir>` and copy `<dir>/numbering.txt` to `proto/`
+    let numbering = charon_lib::protobuf::Schema::registered()?.numbering();
+    assert_eq!(numbering, include_str!("../proto/numbering.txt"));
+    Ok(())
+}
+
+#[test]
+fn include_sources() -> Result<(), Box<dyn Error>> {
+    let code = "
+        struct Foo;
+        ";
+    let crate_data = translate(code)?;
+    assert!(crate_data
+        .id_to_file
+        .iter()
+        .all(|(_, _, content)| content.is_none()));
+
+    let options = CliOpts {
+        include_sources: true,
+        ..Default::default()
+    };
+    let crate_data = translate_with_options(code, options)?;
+    let (_, _, content) = crate_data
+        .id_to_file
+        .iter()
+        .find(
+            |(_, name, _)| matches!(name, FileName::Local(name) if name.ends_with("test_crate.rs")),
+        )
+        .unwrap();
+    assert_eq!(content.as_deref(), Some(code));
+    Ok(())
+}
+
+#[test]
+fn macro_expansions() -> Result<(), Box<dyn Error>> {
+    let crate_data = translate(
+        "
+        macro_rules! incr {
+            ($x:expr) => { $x + 1 };
+        }
+        fn foo(x: u32) -> u32 {
+            incr!(x)
+        }
+        ",
+    )?;
+    let expansion = crate_data
+        .macro_expansions
+        .iter()
+        .find(|expansion| expansion.macro_name == "incr")
+        .unwrap();
+    assert_eq!(expansion.call_site.beg.line, 6);
+    assert_eq!(expansion.def_site.unwrap().beg.line, 2);
+    assert!(expansion.parent.is_none());
+    Ok(())
+}
+
+#[test]
+fn modules() -> Result<(), Box<dyn Error>> {
+    let crate_data = translate(
+        "
+        pub mod foo {
+            pub(crate) struct Bar;
+            pub mod baz {
+                pub(super) fn f() {}
+                fn g() {}
+            }
+        }
+        pub mod a {
+            pub const C: u32 = 0;
+        }
+        ",
+    )?;
+    // The modules are numbered in the order of their paths
+    let names: Vec<String> = crate_data
+        .modules
+        .iter()
+        .map(|module| repr_name(&module.name))
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "test_crate",
+            "test_crate::a",
+            "test_crate::foo",
+            "test_crate::foo::baz"
+        ]
+    );
+    let module = |name: &str| {
+        crate_data
+            .modules
+            .iter()
+            .find(|module| repr_name(&module.name) == name)
+            .unwrap()
+    };
+    let root = module("test_crate");
+    let foo = module("test_crate::foo");
+    let baz = module("test_crate::foo::baz");
+    assert!(root.parent.is_none());
+    let a = module("test_crate::a");
+    assert_eq!(root.submodules, vec![a.module_id, foo.module_id]);
+    assert_eq!(foo.parent, Some(root.module_id));
+    assert_eq!(foo.submodules, vec![baz.module_id]);
+    assert!(matches!(foo.item_meta.visibility, Some(Visibility::Public)));
+    assert!(matches!(&foo.items[..], [AnyTransId::Type(_)]));
+    assert!(matches!(
+        &baz.items[..],
+        [AnyTransId::Fun(_), AnyTransId::Fun(_)]
+    ));
+
+    let restricted_to = |visibility: &Option<Visibility>| match visibility {
+        Some(Visibility::Restricted(module)) => repr_name(module),
+        _ => panic!("expected a restricted visibility"),
+    };
+    assert_eq!(
+        restricted_to(&crate_data.types[0].item_meta.visibility),
+        "test_crate"
+    );
+    assert_eq!(
+        restricted_to(&crate_data.functions[0].item_meta.visibility),
+        "test_crate::foo"
+    );
+    assert_eq!(
+        restricted_to(&crate_data.functions[1].item_meta.visibility),
+        "test_crate::foo::baz"
+    );
+    Ok(())
+}
+
+#[test]
+fn cfg_provenance() -> Result<(), Box<dyn Error>> {
+    use charon_lib::export;
+    let callback = run_charon(
+        r#"
+        #[cfg(feature = "foo")]
+        mod foo {
+            #[cfg(not(feature = "bar"))]
+            pub fn f() {}
+        }
+        #[cfg(feature = "bar")]
+        pub fn g() {}
+        pub fn h() {}
+        "#,
+        Default::default(),
+        &["--cfg", "feature=\"foo\""],
+    )?;
+    let export::CrateData::LLBC(crate_data) = callback.crate_data.unwrap() else {
+        panic!("expected llbc data, got ullbc instead")
+    };
+    assert_eq!(crate_data.features, vec!["foo"]);
+    assert_eq!(crate_data.functions.len(), 2);
+    let cfg = |name: &str| {
+        let decl = crate_data
+            .functions
+            .iter()
+            .find(|decl| repr_name(&decl.name) == name)
+            .unwrap();
+        decl.item_meta.cfg.clone()
+    };
+    assert_eq!(
+        cfg("test_crate::foo::f"),
+        vec!["feature = \"foo\"", "not(feature = \"bar\")"]
+    );
+    assert!(cfg("test_crate::h").is_empty());
+    Ok(())
+}
+
+#[test]
+fn lang_items() -> Result<(), Box<dyn Error>> {
+    let crate_data = translate(
+        "
+        use std::ops::Add;
+        struct Foo;
+        impl Add for Foo {
+            type Output = Foo;
+            fn add(self, _other: Foo) -> Foo {
+                Foo
+            }
+        }
+        trait Trait {}
+        ",
+    )?;
+    let lang_item = |name: &str| {
+        let decl = crate_data
+            .trait_decls
+            .iter()
+            .find(|decl| repr_name(&decl.name) == name)
+            .unwrap();
+        decl.lang_item.clone()
+    };
+    assert_eq!(lang_item("core::ops::arith::Add").as_deref(), Some("add"));
+    assert_eq!(lang_item("test_crate::Trait"), None);
+    assert!(crate_data.functions.iter().all(|f| f.lang_item.is_none()));
+    Ok(())
+}
+
+#[test]
+fn receiver_adjustments() -> Result<(), Box<dyn Error>> {
+    let crate_data = translate(
+        "
+        struct Foo;
+        impl Foo {
+            fn by_ref(&self) {}
+            fn by_mut(&mut self) {}
+        }
+        fn f(mut x: Foo, b: Box<Foo>, r: std::rc::Rc<Foo>, a: [u32; 2]) -> usize {
+            x.by_ref();
+            x.by_mut();
+            b.by_ref();
+            r.by_ref();
+            // The call of `into_iter` introduced by the loop has the same span
+            // as the method call: it doesn't have receiver adjustments
+            for _ in a.iter() {}
+            a.len()
+        }
+        ",
+    )?;
+    let f = crate_data
+        .functions
+        .iter()
+        .find(|f| repr_name(&f.name) == "test_crate::f")
+        .unwrap();
+    let adjustments: Vec<String> = f
+        .body
+        .as_ref()
+        .unwrap()
+        .body
+        .statements
+        .iter()
+        .filter_map(|st| match &st.content {
+            llbc_ast::RawStatement::Call(call) if !call.receiver_adjustments.is_empty() => {
+                Some(format!("{:?}", call.receiver_adjustments))
+            }
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        adjustments,
+        vec![
+            "[Borrow(Shared)]",
+            "[Borrow(Mut)]",
+            "[Deref, Borrow(Shared)]",
+            "[OverloadedDeref(Shared), Borrow(Shared)]",
+            "[Borrow(Shared), Unsize]",
+            "[Borrow(Shared), Unsize]",
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn closure_captures() -> Result<(), Box<dyn Error>> {
+    let crate_data = translate(
+        "
+        fn f(x: u32, mut y: u32, p: (u32, u32)) -> u32 {
+            let a = || x + p.1;
+            let mut b = || y += 1;
+            b();
+            let c = move || x;
+            a() + c()
+        }
+        ",
+    )?;
+    let mut closures: Vec<(Vec<CapturedPlace>, bool)> = crate_data
+        .functions
+        .iter()
+        .filter_map(|f| f.signature.closure_info.as_ref())
+        .map(|info| (info.captures.clone(), info.is_move))
+        .collect();
+    closures.sort_by_key(|(captures, _)| captures.len());
+    // The captured variables are the variables of `f`
+    let f = crate_data
+        .functions
+        .iter()
+        .find(|f| repr_name(&f.name) == "test_crate::f")
+        .unwrap();
+    let locals = &f.body.as_ref().unwrap().locals;
+    let place = |var_name: &str, projection: Vec<CaptureProjection>, mode| CapturedPlace {
+        var_id: locals
+            .iter()
+            .find(|var| var.name.as_deref() == Some(var_name))
+            .unwrap()
+            .index,
+        var_owner: f.rust_id,
+        // The places don't go through the fields of ADTs
+        field_adts: vec![None; projection.len()],
+        projection,
+        mode,
+    };
+    assert_eq!(closures.len(), 3);
+    assert!(closures.contains(&(
+        vec![place("y", vec![], CaptureMode::ByRef(RefKind::Mut))],
+        false
+    )));
+    assert!(closures.contains(&(vec![place("x", vec![], CaptureMode::ByValue)], true)));
+    assert_eq!(
+        closures[2],
+        (
+            vec![
+                place("x", vec![], CaptureMode::ByRef(RefKind::Shared)),
+                place(
+                    "p",
+                    vec![CaptureProjection::Field(FieldId::Id::new(1))],
+                    CaptureMode::ByRef(RefKind::Shared)
+                ),
+            ],
+            false
+        )
+    );
+    Ok(())
+}
+
+#[test]
+fn closure_shims() -> Result<(), Box<dyn Error>> {
+    let crate_data = translate(
+        "
+        fn f(x: u32) -> u32 {
+            let g = |y: u32| x + y;
+            let mut z = 0;
+            let mut h = || z += 1;
+            h();
+            g(1)
+        }
+        ",
+    )?;
+    let find_fun = |id| {
+        crate_data
+            .functions
+            .iter()
+            .find(|f| f.def_id == id)
+            .unwrap()
+    };
+    let calls_closure = |shim: &llbc_ast::FunDecl, closure_id| {
+        let closure_fn = FunIdOrTraitMethodRef::Fun(FunId::Regular(closure_id));
+        let statements = &shim.body.as_ref().unwrap().body.statements;
+        statements.iter().any(|st| match &st.content {
+            llbc_ast::RawStatement::Call(call) => match &call.func {
+                llbc_ast::FnOperand::Regular(fn_ptr) => fn_ptr.func == closure_fn,
+                llbc_ast::FnOperand::Move(_) => false,
+            },
+            _ => false,
+        })
+    };
+    let closures: Vec<_> = crate_data
+        .functions
+        .iter()
+        .filter_map(|f| Some((f, f.signature.closure_info.as_ref()?)))
+        .collect();
+    assert_eq!(closures.len(), 2);
+    for (closure, info) in closures {
+        // The `call_once` shim takes the state by value, then the inputs of the closure
+        let call_once = find_fun(info.call_once_shim.unwrap());
+        assert_eq!(
+            repr_name(&call_once.name),
+            format!("{}::call_once", repr_name(&closure.name))
+        );
+        assert_eq!(
+            call_once.signature.inputs.len(),
+            closure.signature.inputs.len()
+        );
+        assert!(matches!(
+            call_once.signature.inputs[0].kind(),
+            TyKind::Adt(TypeId::Tuple, _)
+        ));
+        assert!(calls_closure(call_once, closure.def_id));
+        match info.kind {
+            ClosureKind::Fn => {
+                let call_mut = find_fun(info.call_mut_shim.unwrap());
+                assert!(matches!(
+                    call_mut.signature.inputs[0].kind(),
+                    TyKind::Ref(_, _, RefKind::Mut)
+                ));
+                assert!(calls_closure(call_mut, closure.def_id));
+            }
+            ClosureKind::FnMut => assert!(info.call_mut_shim.is_none()),
+            ClosureKind::FnOnce => panic!("unexpected `FnOnce` closure"),
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn ullbc_closure_signatures() -> Result<(), Box<dyn Error>> {
+    use charon_lib::{export, ullbc_ast};
+    // The validator checks the numbers of arguments of the calls, including
+    // the calls of the closures by their shims
+    let options = CliOpts {
+        ullbc: true,
+        validate: true,
+        ..Default::default()
+    };
+    let callback = run_charon(
+        "
+        fn f(x: u32) -> u32 {
+            let g = |y: u32| x + y;
+            let mut z = 0;
+            let mut h = || z += 1;
+            h();
+            g(1)
+        }
+        ",
+        options,
+        &[],
+    )?;
+    let export::CrateData::ULLBC(crate_data) = callback.crate_data.unwrap() else {
+        panic!("expected ullbc data, got llbc instead")
+    };
+    let closures: Vec<_> = crate_data
+        .functions
+        .iter()
+        .filter_map(|f| Some((f, f.signature.closure_info.as_ref()?)))
+        .collect();
+    assert_eq!(closures.len(), 2);
+    for (closure, info) in closures {
+        // The closures take their state as first input, as in the LLBC
+        let ref_kind = match info.kind {
+            ClosureKind::Fn => RefKind::Shared,
+            ClosureKind::FnMut => RefKind::Mut,
+            ClosureKind::FnOnce => panic!("unexpected `FnOnce` closure"),
+        };
+        let TyKind::Ref(_, state, kind) = closure.signature.inputs[0].kind() else {
+            panic!("the closure doesn't take its state by reference")
+        };
+        assert_eq!(*kind, ref_kind);
+        assert!(matches!(state.kind(), TyKind::Adt(TypeId::Tuple, _)));
+        let body = closure.body.as_ref().unwrap();
+        assert_eq!(body.arg_count, closure.signature.inputs.len());
+        assert_eq!(body.locals[1].ty, closure.signature.inputs[0]);
+
+        // The accesses to the state are accesses to the fields of a tuple
+        let body = serde_json::to_string(&body.body)?;
+        assert!(!body.contains("ProjClosureState"));
+
+        // The shims call the closure with the same number of arguments
+        let call_once = crate_data
+            .functions
+            .iter()
+            .find(|f| f.def_id == info.call_once_shim.unwrap())
+            .unwrap();
+        let calls: Vec<_> = call_once
+            .body
+            .as_ref()
+            .unwrap()
+            .body
+            .iter()
+            .filter_map(|block| match &block.terminator.content {
+                ullbc_ast::RawTerminator::Call { call, .. } => Some(call),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].args.len(), closure.signature.inputs.len());
+    }
+    Ok(())
+}
+
+#[test]
+fn fn_def_types() -> Result<(), Box<dyn Error>> {
+    let crate_data = translate(
+        "
+        fn incr<const N: u32>(x: u32) -> u32 {
+            x + N
+        }
+        fn f() -> fn(u32) -> u32 {
+            let g = incr::<3>;
+            g
+        }
+        fn apply<F: Fn(u32) -> u32>(f: F) -> u32 {
+            f(0)
+        }
+        fn h() -> u32 {
+            apply(incr::<1>)
+        }
+        ",
+    )?;
+    let find_fun = |name: &str| {
+        crate_data
+            .functions
+            .iter()
+            .find(|f| repr_name(&f.name) == name)
+            .unwrap()
+    };
+    let incr = find_fun("test_crate::incr");
+    let f = find_fun("test_crate::f");
+    let body = f.body.as_ref().unwrap();
+    // The local `g` has the type of the function item
+    let is_incr = |ty: &charon_lib::types::Ty| {
+        matches!(ty.kind(), TyKind::FnDef(id, generics)
+            if *id == incr.def_id && generics.const_generics.len() == 1)
+    };
+    assert!(body.locals.iter().any(|var| is_incr(&var.ty)));
+    // We coerce it to a function pointer
+    let casts: Vec<_> = body
+        .body
+        .statements
+        .iter()
+        .filter_map(|st| match &st.content {
+            llbc_ast::RawStatement::Assign(
+                _,
+                llbc_ast::Rvalue::UnaryOp(
+                    llbc_ast::UnOp::Cast(llbc_ast::CastKind::FnPtr(src, tgt)),
+                    _,
+                ),
+            ) => Some((src.clone(), tgt.clone())),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(casts.len(), 1);
+    assert!(is_incr(&casts[0].0));
+    assert!(matches!(casts[0].1.kind(), TyKind::Arrow(..)));
+
+    // The generic arguments of `apply` and its trait reference use the type of
+    // the function item
+    let h = find_fun("test_crate::h");
+    let call = h
+        .body
+        .as_ref()
+        .unwrap()
+        .body
+        .statements
+        .iter()
+        .find_map(|st| match &st.content {
+            llbc_ast::RawStatement::Call(call) => Some(call),
+            _ => None,
+        })
+        .unwrap();
+    let llbc_ast::FnOperand::Regular(fn_ptr) = &call.func else {
+        panic!()
+    };
+    assert_eq!(fn_ptr.generics.types.len(), 1);
+    assert!(is_incr(&fn_ptr.generics.types[0]));
+    assert_eq!(fn_ptr.generics.trait_refs.len(), 1);
+    let trait_ref = &fn_ptr.generics.trait_refs[0];
+    let TraitInstanceId::FnPointer(ty) = &trait_ref.trait_id else {
+        panic!("unexpected trait instance: {:?}", trait_ref.trait_id)
+    };
+    assert!(is_incr(ty));
+    assert!(is_incr(&trait_ref.trait_decl_ref.generics.types[0]));
+    Ok(())
+}
+
+#[test]
+fn fn_def_coercions() -> Result<(), Box<dyn Error>> {
+    let crate_data = translate(
+        "
+        fn incr(x: u32) -> u32 {
+            x + 1
+        }
+        fn apply<F: Fn(u32) -> u32>(f: F) -> u32 {
+            f(0)
+        }
+        fn g() -> u32 {
+            apply(incr)
+        }
+        ",
+    )?;
+    let find_fun = |name: &str| {
+        crate_data
+            .functions
+            .iter()
+            .find(|f| repr_name(&f.name) == name)
+            .unwrap()
+    };
+    let incr = find_fun("test_crate::incr");
+    let g = find_fun("test_crate::g");
+    let body = g.body.as_ref().unwrap();
+    // Rust doesn't coerce the function item: `F` is instantiated with its type
+    let is_incr = |ty: &Ty| matches!(ty.kind(), TyKind::FnDef(id, _) if *id == incr.def_id);
+    let mut calls = Vec::new();
+    for st in &body.body.statements {
+        match &st.content {
+            llbc_ast::RawStatement::Assign(
+                _,
+                llbc_ast::Rvalue::UnaryOp(llbc_ast::UnOp::Cast(llbc_ast::CastKind::FnPtr(..)), _),
+            ) => panic!("unexpected cast"),
+            llbc_ast::RawStatement::Call(call) => calls.push(call),
+            _ => (),
+        }
+    }
+    assert_eq!(calls.len(), 1);
+    let llbc_ast::FnOperand::Regular(fn_ptr) = &calls[0].func else {
+        panic!()
+    };
+    assert!(is_incr(&fn_ptr.generics.types[0]));
+    assert_eq!(calls[0].args.len(), 1);
+    let llbc_ast::Operand::Const(cv) = &calls[0].args[0] else {
+        panic!("unexpected argument: {:?}", calls[0].args[0])
+    };
+    assert!(is_incr(&cv.ty));
+    Ok(())
+}
+
+#[test]
+fn trait_impl_kinds() -> Result<(), Box<dyn Error>> {
+    let crate_data = translate(
+        "
+        trait Trait {}
+        trait Other {}
+        #[derive(Clone)]
+        struct Foo;
+        impl<T: Clone> Trait for T {}
+        impl Other for Foo {}
+        ",
+    )?;
+    let impl_kind = |trait_name: &str| {
+        let trait_id = crate_data
+            .trait_decls
+            .iter()
+            .find(|d| repr_name(&d.name) == trait_name)
+            .unwrap()
+            .def_id;
+        &crate_data
+            .trait_impls
+            .iter()
+            .find(|i| i.impl_trait.trait_id == trait_id)
+            .unwrap()
+            .impl_kind
+    };
+    assert_eq!(
+        impl_kind("core::clone::Clone"),
+        &TraitImplKind::Derived("Clone".to_string())
+    );
+    assert_eq!(impl_kind("test_crate::Trait"), &TraitImplKind::Blanket);
+    assert_eq!(impl_kind("test_crate::Other"), &TraitImplKind::Regular);
+    Ok(())
+}
+
+#[test]
+fn specialization() -> Result<(), Box<dyn Error>> {
+    let crate_data = translate(
+        "
+        #![feature(min_specialization)]
+        trait Name {
+            fn name(&self) -> u32;
+        }
+        impl<T> Name for T {
+            default fn name(&self) -> u32 {
+                0
+            }
+        }
+        impl Name for u32 {
+            fn name(&self) -> u32 {
+                1
+            }
+        }
+        fn f<T>(x: &T) -> u32 {
+            x.name()
+        }
+        ",
+    )?;
+    let blanket_impl = &crate_data.trait_impls[0];
+    let u32_impl = &crate_data.trait_impls[1];
+    assert_eq!(blanket_impl.default_items.len(), 1);
+    assert_eq!(blanket_impl.default_items[0].0, "name");
+    assert!(u32_impl.default_items.is_empty());
+
+    // The method call may use the specializing impl
+    let f = crate_data
+        .functions
+        .iter()
+        .find(|f| repr_name(&f.name) == "test_crate::f")
+        .unwrap();
+    let calls: Vec<_> = f
+        .body
+        .as_ref()
+        .unwrap()
+        .body
+        .statements
+        .iter()
+        .filter_map(|st| match &st.content {
+            llbc_ast::RawStatement::Call(llbc_ast::Call {
+                func: llbc_ast::FnOperand::Regular(fn_ptr),
+                ..
+            }) => Some(&fn_ptr.func),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(calls.len(), 1);
+    let FunIdOrTraitMethodRef::Trait(trait_ref, _, _) = calls[0] else { panic!() };
+    assert_eq!(
+        trait_ref.trait_id,
+        TraitInstanceId::Specializable {
+            impl_id: blanket_impl.def_id,
+            specializing_impls: vec![u32_impl.def_id],
+        }
+    );
+    Ok(())
+}
+
+#[test]
+fn auto_traits() -> Result<(), Box<dyn Error>> {
+    let crate_data = translate(
+        "
+        #![feature(negative_impls)]
+        use std::rc::Rc;
+        struct A(u32);
+        struct B<T>(T);
+        struct C(Rc<u32>);
+        struct D;
+        impl !Sync for D {}
+        ",
+    )?;
+    let auto_traits = |name: &str| {
+        &crate_data
+            .types
+            .iter()
+            .find(|d| repr_name(&d.name) == name)
+            .unwrap()
+            .auto_traits
+    };
+    assert_eq!(auto_traits("test_crate::A").send, AutoTraitImpl::Always);
+    assert_eq!(auto_traits("test_crate::B").send, AutoTraitImpl::IfParams);
+    assert_eq!(auto_traits("test_crate::C").send, AutoTraitImpl::Never);
+    assert_eq!(auto_traits("test_crate::C").unpin, AutoTraitImpl::Always);
+    assert_eq!(auto_traits("test_crate::D").send, AutoTraitImpl::Always);
+    assert_eq!(auto_traits("test_crate::D").sync, AutoTraitImpl::Never);
+
+    // The negative impl
+    assert_eq!(crate_data.trait_impls.len(), 1);
+    let negative_impl = &crate_data.trait_impls[0];
+    assert!(negative_impl.is_negative);
+    assert!(negative_impl.parent_trait_refs.is_empty());
+    Ok(())
+}
+
+#[test]
+fn const_trait_impls() -> Result<(), Box<dyn Error>> {
+    let crate_data = translate(
+        "
+        #![feature(const_trait_impl)]
+        #[const_trait]
+        trait Trait {
+            fn f() -> u32;
+        }
+        struct S;
+        impl const Trait for S {
+            fn f() -> u32 { 0 }
+        }
+        const fn const_bound<T: ~const Trait>() -> u32 { T::f() }
+        fn regular_bound<T: Trait>() -> u32 { T::f() }
+        const fn both_bounds<T: Trait + ~const Trait>() -> u32 { T::f() }
+        ",
+    )?;
+    assert_eq!(crate_data.trait_impls.len(), 1);
+    assert!(crate_data.trait_impls[0].is_const);
+
+    let find_fun = |name: &str| {
+        crate_data
+            .functions
+            .iter()
+            .find(|f| repr_name(&f.name) == name)
+            .unwrap()
+    };
+    let clauses = |name: &str| &find_fun(name).signature.generics.trait_clauses;
+    assert!(clauses("test_crate::const_bound")[0].is_const);
+    assert!(!clauses("test_crate::regular_bound")[0].is_const);
+
+    // In a const context, the call uses the `~const` bound
+    let both_bounds = clauses("test_crate::both_bounds");
+    assert!(!both_bounds[0].is_const);
+    assert!(both_bounds[1].is_const);
+    let call = find_fun("test_crate::both_bounds")
+        .body
+        .as_ref()
+        .unwrap()
+        .body
+        .statements
+        .iter()
+        .find_map(|st| match &st.content {
+            llbc_ast::RawStatement::Call(llbc_ast::Call {
+                func: llbc_ast::FnOperand::Regular(fn_ptr),
+                ..
+            }) => Some(&fn_ptr.func),
+            _ => None,
+        })
+        .unwrap();
+    let FunIdOrTraitMethodRef::Trait(trait_ref, _, _) = call else {
+        panic!()
+    };
+    assert_eq!(
+        trait_ref.trait_id,
+        TraitInstanceId::Clause(both_bounds[1].clause_id)
+    );
+    Ok(())
+}
+
+#[test]
+fn const_fns_and_unsafe_blocks() -> Result<(), Box<dyn Error>> {
+    let crate_data = translate(
+        "
+        const fn zero() -> u32 { 0 }
+        fn read(x: *const u32) -> u32 {
+            let y = unsafe { *x };
+            y + 1
+        }
+        ",
+    )?;
+    let find_fun = |name: &str| {
+        crate_data
+            .functions
+            .iter()
+            .find(|f| repr_name(&f.name) == name)
+            .unwrap()
+    };
+    assert!(find_fun("test_crate::zero").signature.is_const);
+    let read = find_fun("test_crate::read");
+    assert!(!read.signature.is_const);
+
+    // The read of the raw pointer is in an unsafe block, the addition is not
+    let statements = &read.body.as_ref().unwrap().body.statements;
+    let deref_in_unsafe = statements.iter().any(|st| {
+        matches!(&st.content, llbc_ast::RawStatement::Assign(_, llbc_ast::Rvalue::Use(op))
+            if matches!(op, llbc_ast::Operand::Copy(p) if !p.projection.is_empty()))
+            && st.meta.in_unsafe_block
+    });
+    assert!(deref_in_unsafe);
+    let add_outside_unsafe = statements.iter().any(|st| {
+        matches!(
+            &st.content,
+            llbc_ast::RawStatement::Assign(_, llbc_ast::Rvalue::BinaryOp(..))
+        ) && !st.meta.in_unsafe_block
+    });
+    assert!(add_outside_unsafe);
+    Ok(())
+}
+
+#[test]
+fn promoted_constants() -> Result<(), Box<dyn Error>> {
+    let options = CliOpts {
+        mir_promoted: true,
+        ..Default::default()
+    };
+    let crate_data = translate_with_options(
+        "
+        fn f() -> &'static u32 {
+            &3
+        }
+        fn g() -> (&'static u32, &'static u32) {
+            (&4, &5)
+        }
+        ",
+        options,
+    )?;
+    let f = &crate_data.functions[0];
+    assert_eq!(crate_data.globals.len(), 3);
+    let promoted = crate_data
+        .globals
+        .iter()
+        .find(|g| repr_name(&g.name) == "test_crate::f::promoted")
+        .unwrap();
+    assert!(promoted.body.is_some());
+    let info = promoted.promoted.as_ref().unwrap();
+    assert_eq!(info.parent, AnyTransId::Fun(f.def_id));
+    assert_eq!(info.index.index(), 0);
+
+    // The bodies refer to their promoted constants by their indices
+    fn promoted_refs(value: &serde_json::Value, refs: &mut Vec<u64>) {
+        match value {
+            serde_json::Value::Array(values) => values.iter().for_each(|v| promoted_refs(v, refs)),
+            serde_json::Value::Object(fields) => {
+                if let Some(serde_json::Value::Array(args)) = fields.get("Promoted") {
+                    refs.push(args[0].as_u64().unwrap())
+                }
+                fields.values().for_each(|v| promoted_refs(v, refs))
+            }
+            _ => (),
+        }
+    }
+    for fun in &crate_data.functions {
+        let body = fun.body.as_ref().unwrap();
+        let mut refs = Vec::new();
+        promoted_refs(&serde_json::to_value(&body.body)?, &mut refs);
+        refs.sort();
+        let expected: Vec<u64> = (0..body.promoted.len() as u64).collect();
+        assert_eq!(refs, expected);
+        for (index, id) in body.promoted.iter().enumerate() {
+            let global = crate_data.globals.iter().find(|g| g.def_id == *id).unwrap();
+            let info = global.promoted.as_ref().unwrap();
+            assert_eq!(info.parent, AnyTransId::Fun(fun.def_id));
+            assert_eq!(info.index.index(), index);
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn static_flags() -> Result<(), Box<dyn Error>> {
+    let crate_data = translate(
+        "
+        #![feature(thread_local)]
+        use std::sync::atomic::AtomicUsize;
+        const CONST: u32 = 0;
+        static mut MUTABLE: u32 = 0;
+        static ATOMIC: AtomicUsize = AtomicUsize::new(0);
+        #[thread_local]
+        static THREAD_LOCAL: u32 = 0;
+        fn incr() {
+            unsafe { MUTABLE += 1 }
+        }
+        ",
+    )?;
+    let find_global = |name: &str| {
+        crate_data
+            .globals
+            .iter()
+            .find(|g| repr_name(&g.name) == name)
+            .unwrap()
+    };
+    let flags = |name: &str| {
+        let global = find_global(name);
+        (
+            global.is_mutable,
+            global.is_thread_local,
+            global.is_interior_mutable,
+        )
+    };
+    assert_eq!(flags("test_crate::CONST"), (false, false, false));
+    assert_eq!(flags("test_crate::MUTABLE"), (true, false, false));
+    assert_eq!(flags("test_crate::ATOMIC"), (false, false, true));
+    assert_eq!(flags("test_crate::THREAD_LOCAL"), (false, true, false));
+
+    // The mutable static is accessed through a raw pointer
+    let mutable = find_global("test_crate::MUTABLE");
+    let incr = crate_data
+        .functions
+        .iter()
+        .find(|f| repr_name(&f.name) == "test_crate::incr")
+        .unwrap();
+    let statements = &incr.body.as_ref().unwrap().body.statements;
+    assert!(statements.iter().any(|st| matches!(
+        &st.content,
+        llbc_ast::RawStatement::Assign(_, llbc_ast::Rvalue::Use(llbc_ast::Operand::Const(cv)))
+            if matches!(cv.value, RawConstantExpr::MutStatic(id) if id == mutable.def_id)
+    )));
+    Ok(())
+}
+
+#[test]
+fn global_init_deps() -> Result<(), Box<dyn Error>> {
+    let crate_data = translate(
+        "
+        static A: u32 = 0;
+        static B: &u32 = &A;
+        static F: fn() -> u32 = read_f;
+        fn read_f() -> u32 {
+            let _f = F;
+            *B
+        }
+        ",
+    )?;
+    let find_global = |name: &str| {
+        crate_data
+            .globals
+            .iter()
+            .find(|g| repr_name(&g.name) == name)
+            .unwrap()
+    };
+    let a = find_global("test_crate::A");
+    let b = find_global("test_crate::B");
+    let f = find_global("test_crate::F");
+    assert!(a.init_deps.is_empty());
+    assert_eq!(b.init_deps, vec![a.def_id]);
+    // The dependencies through the functions are taken into account
+    assert_eq!(
+        f.init_deps.iter().copied().sorted().collect_vec(),
+        vec![b.def_id, f.def_id].into_iter().sorted().collect_vec()
+    );
+
+    // The globals come after the globals they depend on, and the cyclic
+    // static is in a recursive group
+    let group_of = |id| {
+        crate_data
+            .declarations
+            .iter()
+            .position(|g| match g {
+                DeclarationGroup::Global(GDeclarationGroup::NonRec(gid)) => *gid == id,
+                DeclarationGroup::Global(GDeclarationGroup::Rec(gids)) => gids.contains(&id),
+                _ => false,
+            })
+            .unwrap()
+    };
+    assert!(group_of(a.def_id) < group_of(b.def_id));
+    assert!(group_of(b.def_id) < group_of(f.def_id));
+    assert!(matches!(
+        crate_data.declarations[group_of(f.def_id)],
+        DeclarationGroup::Global(GDeclarationGroup::Rec(_))
+    ));
+    Ok(())
+}
+
+#[test]
+fn interp_exec() -> Result<(), Box<dyn Error>> {
+    let crate_data = translate(
+        "
+        trait Shape {
+            fn area(&self) -> u32;
+            fn double_area(&self) -> u32 {
+                2 * self.area()
+            }
+        }
+        struct Rect { w: u32, h: u32 }
+        impl Shape for Rect {
+            fn area(&self) -> u32 { self.w * self.h }
+        }
+        enum Tree { Leaf, Node(Box<Tree>, u32, Box<Tree>) }
+        fn sum(t: &Tree) -> u32 {
+            match t {
+                Tree::Leaf => 0,
+                Tree::Node(l, x, r) => sum(l) + *x + sum(r),
+            }
+        }
+        fn fact(n: u64) -> u64 {
+            if n == 0 { 1 } else { n * fact(n - 1) }
+        }
+        fn arrays(i: usize) -> u32 {
+            let mut a = [1u32; 4];
+            a[2] = 5;
+            let s: &[u32] = &a;
+            let mut total = 0;
+            let mut j = 0;
+            while j < 4 {
+                total = total + s[j];
+                j = j + 1;
+            }
+            total + s[i]
+        }
+        fn shapes() -> (u32, u32) {
+            let r = Rect { w: 2, h: 3 };
+            (r.area(), r.double_area())
+        }
+        fn trees() -> u32 {
+            let leaf = || Box::new(Tree::Leaf);
+            sum(&Tree::Node(Box::new(Tree::Node(leaf(), 1, leaf())), 2, leaf()))
+        }
+        fn add(x: u8, y: u8) -> u8 {
+            x + y
+        }
+        ",
+    )?;
+    use charon_lib::interp::{exec, ExecError};
+    let run = |name: &str, args: &[&str]| {
+        let args = args.iter().map(|s| s.to_string()).collect_vec();
+        exec(&crate_data, &format!("test_crate::{name}"), &args)
+    };
+    assert_eq!(run("fact", &["10"])?, "3628800");
+    assert_eq!(run("arrays", &["2"])?, "13");
+    assert_eq!(run("shapes", &[])?, "(6, 12)");
+    assert_eq!(run("trees", &[])?, "3");
+    assert_eq!(run("add", &["200", "55"])?, "255");
+
+    // The failures of the dynamic checks are panics
+    assert!(matches!(
+        run("add", &["200", "56"]),
+        Err(ExecError::Panic(_))
+    ));
+    assert!(matches!(run("arrays", &["4"]), Err(ExecError::Panic(_))));
+    Ok(())
+}
+
+#[test]
+fn standalone_discriminant_reads() -> Result<(), Box<dyn Error>> {
+    let crate_data = translate(
+        "
+        enum E { A = 3, B = 7, C = -2 }
+        fn to_int(b: bool) -> isize {
+            let e = if b { E::B } else { E::C };
+            e as isize
+        }
+        fn first() -> i32 {
+            E::A as i32
+        }
+        ",
+    )?;
+    // The discriminant reads which are not followed by a switch become matches
+    let to_int = crate_data
+        .functions
+        .iter()
+        .find(|f| repr_name(&f.name) == "test_crate::to_int")
+        .unwrap();
+    let statements = &to_int.body.as_ref().unwrap().body.statements;
+    assert!(statements.iter().any(|st| matches!(
+        &st.content,
+        llbc_ast::RawStatement::Switch(llbc_ast::Switch::Match(_, targets, None))
+            if targets.len() == 3
+    )));
+
+    use charon_lib::interp::exec;
+    let run = |name: &str, args: &[&str]| {
+        let args = args.iter().map(|s| s.to_string()).collect_vec();
+        exec(&crate_data, &format!("test_crate::{name}"), &args)
+    };
+    assert_eq!(run("to_int", &["true"])?, "7");
+    assert_eq!(run("to_int", &["false"])?, "-2");
+    assert_eq!(run("first", &[])?, "3");
+    Ok(())
+}
+
+#[test]
+fn switch_over_discriminant_casts() -> Result<(), Box<dyn Error>> {
+    let crate_data = translate(
+        "
+        #[repr(u8)]
+        enum Color { Red = 1, Green = 2, Blue = 4, Black = 255 }
+        fn code(c: Color) -> u32 {
+            match c as u32 {
+                1 => 10,
+                2 | 4 => 20,
+                3 => 40,
+                _ => 30,
+            }
+        }
+        fn code_of(i: u8) -> u32 {
+            let c = if i == 0 { Color::Red } else if i == 1 { Color::Blue } else { Color::Black };
+            code(c)
+        }
+        ",
+    )?;
+    // The switch over the cast of the discriminant becomes a match over the variants
+    let code = crate_data
+        .functions
+        .iter()
+        .find(|f| repr_name(&f.name) == "test_crate::code")
+        .unwrap();
+    let statements = &code.body.as_ref().unwrap().body.statements;
+    let targets = statements
+        .iter()
+        .find_map(|st| match &st.content {
+            llbc_ast::RawStatement::Switch(llbc_ast::Switch::Match(_, targets, Some(_))) => {
+                Some(targets)
+            }
+            _ => None,
+        })
+        .unwrap();
+    let variants = targets
+        .iter()
+        .map(|(ids, _)| ids.iter().map(|id| id.index()).collect_vec())
+        .collect_vec();
+    assert_eq!(variants, vec![vec![0], vec![1, 2]]);
+
+    use charon_lib::interp::exec;
+    let run = |i: &str| exec(&crate_data, "test_crate::code_of", &[i.to_string()]);
+    assert_eq!(run("0")?, "10");
+    assert_eq!(run("1")?, "20");
+    assert_eq!(run("2")?, "30");
+    Ok(())
+}
+
+#[test]
+fn enum_discriminants() -> Result<(), Box<dyn Error>> {
+    let crate_data = translate(
+        "
+        enum Default { A, B = 10, C }
+        #[repr(i8)]
+        enum Signed { A = -1, B = 5, C }
+        #[repr(u16)]
+        enum Unsigned { A = 300, B }
+        ",
+    )?;
+    let discriminants = |name: &str| {
+        let decl = crate_data
+            .types
+            .iter()
+            .find(|d| repr_name(&d.name) == format!("test_crate::{name}"))
+            .unwrap();
+        let TypeDeclKind::Enum(variants, discr_ty, _) = &decl.kind else {
+            panic!()
+        };
+        let discrs = variants.iter().map(|v| v.discriminant).collect_vec();
+        (*discr_ty, discrs)
+    };
+    use ScalarValue::*;
+    assert_eq!(
+        discriminants("Default"),
+        (IntegerTy::Isize, vec![Isize(0), Isize(10), Isize(11)])
+    );
+    assert_eq!(
+        discriminants("Signed"),
+        (IntegerTy::I8, vec![I8(-1), I8(5), I8(6)])
+    );
+    assert_eq!(
+        discriminants("Unsigned"),
+        (IntegerTy::U16, vec![U16(300), U16(301)])
+    );
+    Ok(())
+}
+
+#[test]
+fn enum_layouts() -> Result<(), Box<dyn Error>> {
+    let crate_data = translate(
+        "
+        enum Single { A(u32) }
+        enum Tagged { A(u32), B(u32) }
+        enum Ptr<'a> { Null, Ref(u64, &'a u32) }
+        struct Wrapper<'a>(u64, &'a u32);
+        enum Nested<'a> { A(Wrapper<'a>), B }
+        enum Generic<T> { A, B(T) }
+        ",
+    )?;
+    let layout = |name: &str| {
+        let decl = crate_data
+            .types
+            .iter()
+            .find(|d| repr_name(&d.name) == format!("test_crate::{name}"))
+            .unwrap();
+        let TypeDeclKind::Enum(_, _, layout) = &decl.kind else {
+            panic!()
+        };
+        layout.clone()
+    };
+    assert!(matches!(layout("Single"), Some(EnumLayout::Single)));
+    assert!(matches!(layout("Tagged"), Some(EnumLayout::Tagged)));
+    // `Null` is represented by the null pointer
+    let Some(EnumLayout::Niche {
+        untagged_variant,
+        field,
+    }) = layout("Ptr")
+    else {
+        panic!()
+    };
+    assert_eq!((untagged_variant.index(), field.index()), (1, 1));
+    // The niche is in the reference inside the wrapper
+    let Some(EnumLayout::Niche {
+        untagged_variant,
+        field,
+    }) = layout("Nested")
+    else {
+        panic!()
+    };
+    assert_eq!((untagged_variant.index(), field.index()), (0, 0));
+    // The layout depends on the type parameter
+    assert!(layout("Generic").is_none());
+    Ok(())
+}
+
+#[test]
+fn subslice_patterns() -> Result<(), Box<dyn Error>> {
+    let crate_data = translate(
+        "
+        fn tail(s: &[u32]) -> &[u32] {
+            match s {
+                [_, rest @ ..] => rest,
+                [] => s,
+            }
+        }
+        fn middle(a: &[u32; 5]) -> &[u32; 2] {
+            let [_, m @ .., _, _] = a;
+            m
+        }
+        ",
+    )?;
+    let body = |name: &str| {
+        let f = crate_data
+            .functions
+            .iter()
+            .find(|f| repr_name(&f.name) == format!("test_crate::{name}"))
+            .unwrap();
+        format!("{:?}", f.body.as_ref().unwrap().body)
+    };
+    // The subslices of slices are counted from the end, not those of arrays
+    assert!(body("tail").contains("Subslice { from: 1, to: 0, from_end: true }"));
+    assert!(body("middle").contains("Subslice { from: 1, to: 3, from_end: false }"));
+    Ok(())
+}
+
+#[test]
+fn nullary_ops() -> Result<(), Box<dyn Error>> {
+    let crate_data = translate(
+        "
+        #![feature(offset_of)]
+        struct Inner { a: u8, b: (u16, u32) }
+        struct Outer { x: u64, inner: Inner }
+        fn offset() -> usize {
+            core::mem::offset_of!(Outer, inner.b.1)
+        }
+        ",
+    )?;
+    let inner_id = crate_data
+        .types
+        .iter()
+        .find(|d| repr_name(&d.name) == "test_crate::Inner")
+        .unwrap()
+        .def_id;
+    let f = &crate_data.functions[0];
+    let statements = &f.body.as_ref().unwrap().body.statements;
+    let path = statements
+        .iter()
+        .find_map(|st| match &st.content {
+            llbc_ast::RawStatement::Assign(
+                _,
+                llbc_ast::Rvalue::NullaryOp(llbc_ast::NullOp::OffsetOf(path), _),
+            ) => Some(path),
+            _ => None,
+        })
+        .unwrap();
+    // The path goes through the fields of the structures and of the tuple
+    let path: Vec<_> = path
+        .iter()
+        .map(|(kind, field)| (*kind, field.index()))
+        .collect();
+    assert_eq!(path.len(), 3);
+    assert_eq!(path[1], (FieldProjKind::Adt(inner_id, None), 1));
+    assert_eq!(path[2], (FieldProjKind::Tuple(2), 1));
+    Ok(())
+}
+
+#[test]
+fn pointer_arithmetic() -> Result<(), Box<dyn Error>> {
+    let crate_data = translate(
+        "
+        unsafe fn offset(p: *const u32, n: usize) -> *const u32 {
+            p.add(n)
+        }
+        fn wrapping(p: *mut u32) -> *mut u32 {
+            p.wrapping_offset(-1)
+        }
+        unsafe fn diff(p: *const u32, q: *const u32) -> isize {
+            p.offset_from(q)
+        }
+        fn same(p: *const u32, q: *const u32) -> bool {
+            core::ptr::eq(p, q)
+        }
+        ",
+    )?;
+    let body = |name: &str| {
+        let f = crate_data
+            .functions
+            .iter()
+            .find(|f| repr_name(&f.name) == format!("test_crate::{name}"))
+            .unwrap();
+        format!("{:?}", f.body.as_ref().unwrap().body)
+    };
+    // The calls are translated to binary operations
+    assert!(body("offset").contains("BinaryOp(Offset"));
+    assert!(body("wrapping").contains("BinaryOp(WrappingOffset"));
+    assert!(body("diff").contains("BinaryOp(PtrDiff"));
+    assert!(body("same").contains("BinaryOp(Eq"));
+    assert!(!body("offset").contains("Call"));
+    Ok(())
+}
+
+#[test]
+fn memory_copies() -> Result<(), Box<dyn Error>> {
+    let crate_data = translate(
+        "
+        unsafe fn copy(src: *const u32, dst: *mut u32, n: usize) {
+            core::ptr::copy_nonoverlapping(src, dst, n)
+        }
+        unsafe fn copy_from(src: *const u32, dst: *mut u32, n: usize) {
+            dst.copy_from(src, n)
+        }
+        unsafe fn zero(dst: *mut u32, n: usize) {
+            core::ptr::write_bytes(dst, 0, n)
+        }
+        ",
+    )?;
+    let body = |name: &str| {
+        let f = crate_data
+            .functions
+            .iter()
+            .find(|f| repr_name(&f.name) == format!("test_crate::{name}"))
+            .unwrap();
+        format!("{:?}", f.body.as_ref().unwrap().body)
+    };
+    assert!(body("copy").contains("nonoverlapping: true"));
+    assert!(body("copy_from").contains("nonoverlapping: false"));
+    assert!(body("zero").contains("WriteBytes(WriteBytes"));
+    assert!(!body("copy").contains("Call"));
+    Ok(())
+}
+
+#[test]
+fn kept_dynamic_checks() -> Result<(), Box<dyn Error>> {
+    let code = "
+        fn get(a: [u32; 4], i: usize) -> u32 {
+            a[i]
+        }
+        fn div(x: u32, y: u32) -> u32 {
+            x / y
+        }
+        ";
+    let body = |crate_data: &GCrateData<llbc_ast::FunDecl, llbc_ast::GlobalDecl>, name: &str| {
+        let f = crate_data
+            .functions
+            .iter()
+            .find(|f| repr_name(&f.name) == format!("test_crate::{name}"))
+            .unwrap();
+        format!("{:?}", f.body.as_ref().unwrap().body)
+    };
+    // By default, the checks are removed
+    let crate_data = translate(code)?;
+    assert!(!body(&crate_data, "get").contains("Assert"));
+    assert!(!body(&crate_data, "div").contains("Assert"));
+
+    // Keep the bounds checks only
+    let options = CliOpts {
+        keep_bounds_checks: true,
+        ..Default::default()
+    };
+    let crate_data = translate_with_options(code, options)?;
+    assert!(body(&crate_data, "get").contains("kind: Bounds"));
+    assert!(!body(&crate_data, "div").contains("Assert"));
+
+    // Keep the division by zero checks only
+    let options = CliOpts {
+        keep_div_checks: true,
+        ..Default::default()
+    };
+    let crate_data = translate_with_options(code, options)?;
+    assert!(!body(&crate_data, "get").contains("Assert"));
+    assert!(body(&crate_data, "div").contains("kind: DivisionByZero"));
+    Ok(())
+}
+
+#[test]
+fn arithmetic_with_overflow_behavior() -> Result<(), Box<dyn Error>> {
+    let crate_data = translate(
+        "
+        fn checked(x: u32, y: u32) -> Option<u32> {
+            x.checked_add(y)
+        }
+        fn wrapping(x: i8, y: i8) -> i8 {
+            x.wrapping_mul(y)
+        }
+        fn overflowing(x: u64, y: u64) -> (u64, bool) {
+            x.overflowing_sub(y)
+        }
+        fn saturating(x: usize, y: usize) -> usize {
+            x.saturating_add(y)
+        }
+        ",
+    )?;
+    let body = |name: &str| {
+        let f = crate_data
+            .functions
+            .iter()
+            .find(|f| repr_name(&f.name) == format!("test_crate::{name}"))
+            .unwrap();
+        format!("{:?}", f.body.as_ref().unwrap().body)
+    };
+    // The calls are translated to binary operations
+    assert!(body("checked").contains("BinaryOp(CheckedAdd"));
+    assert!(body("wrapping").contains("BinaryOp(WrappingMul"));
+    assert!(body("overflowing").contains("BinaryOp(OverflowingSub"));
+    assert!(body("saturating").contains("BinaryOp(SaturatingAdd"));
+    assert!(!body("checked").contains("Call"));
+    Ok(())
+}
+
+#[test]
+fn int128_constants() -> Result<(), Box<dyn Error>> {
+    let crate_data = translate(
+        "
+        fn switch(x: u128) -> u8 {
+            match x {
+                18446744073709551616 => 0,
+                340282366920938463463374607431768211455 => 1,
+                _ => 2,
+            }
+        }
+        fn add(x: i128) -> i128 {
+            x + -170141183460469231731687303715884105728
+        }
+        ",
+    )?;
+    let body = |name: &str| {
+        let f = crate_data
+            .functions
+            .iter()
+            .find(|f| repr_name(&f.name) == format!("test_crate::{name}"))
+            .unwrap();
+        f.body.as_ref().unwrap()
+    };
+    // The constants which don't fit in 64 bits are not truncated
+    let switch = format!("{:?}", body("switch").body);
+    assert!(switch.contains("U128(18446744073709551616)"));
+    assert!(switch.contains("U128(340282366920938463463374607431768211455)"));
+    let add = format!("{:?}", body("add").body);
+    assert!(add.contains("I128(-170141183460469231731687303715884105728)"));
+    // They are serialized as strings
+    let switch = serde_json::to_string(body("switch"))?;
+    assert!(switch.contains(r#"{"U128":"340282366920938463463374607431768211455"}"#));
+    let add = serde_json::to_string(body("add"))?;
+    assert!(add.contains(r#"{"I128":"-170141183460469231731687303715884105728"}"#));
+    Ok(())
+}
+
+#[test]
+fn kept_storage_statements() -> Result<(), Box<dyn Error>> {
+    let code = "
+        fn incr(x: u32) -> u32 {
+            let y = x + 1;
+            y * 2
+        }
+        ";
+    let body = |crate_data: &GCrateData<llbc_ast::FunDecl, llbc_ast::GlobalDecl>| {
+        let f = crate_data
+            .functions
+            .iter()
+            .find(|f| repr_name(&f.name) == "test_crate::incr")
+            .unwrap();
+        format!("{:?}", f.body.as_ref().unwrap().body)
+    };
+    // By default, the `StorageLive` are ignored and the `StorageDead` are drops
+    let crate_data = translate(code)?;
+    assert!(!body(&crate_data).contains("StorageLive"));
+    assert!(!body(&crate_data).contains("StorageDead"));
+    assert!(body(&crate_data).contains("Drop"));
+
+    let options = CliOpts {
+        keep_storage_statements: true,
+        ..Default::default()
+    };
+    let crate_data = translate_with_options(code, options)?;
+    assert!(body(&crate_data).contains("StorageLive"));
+    assert!(body(&crate_data).contains("StorageDead"));
+    Ok(())
+}
+
+#[test]
+fn var_debug_info() -> Result<(), Box<dyn Error>> {
+    let crate_data = translate(
+        "
+        fn add(x: u32) -> u32 {
+            let f = |y: u32| x + y;
+            f(1)
+        }
+        ",
+    )?;
+    let add = crate_data
+        .functions
+        .iter()
+        .find(|f| repr_name(&f.name) == "test_crate::add")
+        .unwrap();
+    let locals = &add.body.as_ref().unwrap().locals;
+    let f = locals
+        .iter()
+        .find(|v| v.name.as_deref() == Some("f"))
+        .unwrap();
+    assert_eq!(f.debug_info.len(), 1);
+    assert_eq!(f.debug_info[0].name, "f");
+    assert!(f.debug_info[0].projection.is_empty());
+
+    // The captured variable is a part of the state of the closure
+    let closure = crate_data
+        .functions
+        .iter()
+        .find(|f| f.signature.closure_info.is_some())
+        .unwrap();
+    // The state is the first input
+    let state = closure.body.as_ref().unwrap().locals.iter().nth(1).unwrap();
+    let x = state.debug_info.iter().find(|i| i.name == "x").unwrap();
+    assert!(!x.projection.is_empty());
+    Ok(())
+}
+
+#[test]
+fn body_scopes() -> Result<(), Box<dyn Error>> {
+    let crate_data = translate(
+        "
+        fn f(x: u32) -> u32 {
+            let y = x + 1;
+            {
+                let z = y * 2;
+                z + y
+            }
+        }
+        ",
+    )?;
+    let f = crate_data
+        .functions
+        .iter()
+        .find(|f| repr_name(&f.name) == "test_crate::f")
+        .unwrap();
+    let body = f.body.as_ref().unwrap();
+    let scopes: Vec<&Scope> = body.scopes.iter().collect();
+    // The root scope contains the inputs
+    assert!(scopes[0].parent.is_none());
+    let local_scope = |name: &str| {
+        let var = body
+            .locals
+            .iter()
+            .find(|v| v.name.as_deref() == Some(name))
+            .unwrap();
+        scopes
+            .iter()
+            .find(|s| s.locals.contains(&var.index))
+            .unwrap()
+    };
+    assert_eq!(local_scope("x").index, scopes[0].index);
+    // The scope of `z` is nested in the scope of `y`
+    let y_scope = local_scope("y").index;
+    let mut scope = local_scope("z");
+    while scope.index != y_scope {
+        scope = scopes[scope.parent.unwrap().index()];
+    }
+    Ok(())
+}
+
+#[test]
+fn reconstructed_expressions() -> Result<(), Box<dyn Error>> {
+    let code = "
+        fn f(b: u32, c: u32) -> u32 {
+            (b + c) * 2
+        }
+        ";
+    let body = |crate_data: &GCrateData<llbc_ast::FunDecl, llbc_ast::GlobalDecl>| {
+        let f = crate_data
+            .functions
+            .iter()
+            .find(|f| repr_name(&f.name) == "test_crate::f")
+            .unwrap();
+        f.body.as_ref().unwrap().clone()
+    };
+    let crate_data = translate(code)?;
+    let default_body = body(&crate_data);
+    assert!(!format!("{:?}", default_body.body).contains("Expr("));
+
+    let options = CliOpts {
+        reconstruct_expressions: true,
+        ..Default::default()
+    };
+    let crate_data = translate_with_options(code, options)?;
+    let reconstructed_body = body(&crate_data);
+    // The temporary storing `b + c` is inlined in the multiplication
+    assert!(format!("{:?}", reconstructed_body.body).contains("Expr("));
+    assert!(reconstructed_body.locals.len() < default_body.locals.len());
+    Ok(())
+}
+
+#[test]
+fn propagated_constants() -> Result<(), Box<dyn Error>> {
+    let code = "
+        fn f(x: u32) -> u32 {
+            let a = 3;
+            let b = a * 2;
+            if b > 4 { x + b } else { x }
+        }
+        ";
+    let body = |crate_data: &GCrateData<llbc_ast::FunDecl, llbc_ast::GlobalDecl>| {
+        let f = crate_data
+            .functions
+            .iter()
+            .find(|f| repr_name(&f.name) == "test_crate::f")
+            .unwrap();
+        f.body.as_ref().unwrap().clone()
+    };
+    let crate_data = translate(code)?;
+    let default_body = body(&crate_data);
+    assert!(format!("{:?}", default_body.body).contains("Switch("));
+
+    let options = CliOpts {
+        propagate_constants: true,
+        ..Default::default()
+    };
+    let crate_data = translate_with_options(code, options)?;
+    let propagated_body = body(&crate_data);
+    // `b` is the constant 6, and the condition is always true: only the
+    // addition remains
+    let propagated = format!("{:?}", propagated_body.body);
+    assert!(!propagated.contains("Switch("));
+    assert!(propagated.contains("U32(6)"));
+    assert!(propagated_body.locals.len() < default_body.locals.len());
+    Ok(())
+}
+
+#[test]
+fn dead_branches() -> Result<(), Box<dyn Error>> {
+    let crate_data = translate(
+        "
+        fn f(x: u32) -> u32 {
+            if cfg!(target_pointer_width = \"8\") {
+                x + 1
+            } else {
+                x
+            }
+        }
+        ",
+    )?;
+    let f = crate_data
+        .functions
+        .iter()
+        .find(|f| repr_name(&f.name) == "test_crate::f")
+        .unwrap();
+    // The condition is always false: only the `else` branch remains
+    let body = format!("{:?}", f.body.as_ref().unwrap().body);
+    assert!(!body.contains("Switch("));
+    assert!(!body.contains("Add"));
+    Ok(())
+}
+
+#[test]
+fn redundant_copies() -> Result<(), Box<dyn Error>> {
+    let code = "
+        fn incr(x: &mut u32) {
+            *x += 1;
+        }
+        fn f(x: &mut u32) {
+            incr(x)
+        }
+        ";
+    // The variable given to `incr`
+    let call_arg = |crate_data: &GCrateData<llbc_ast::FunDecl, llbc_ast::GlobalDecl>| {
+        let f = crate_data
+            .functions
+            .iter()
+            .find(|f| repr_name(&f.name) == "test_crate::f")
+            .unwrap();
+        f.body
+            .as_ref()
+            .unwrap()
+            .body
+            .statements
+            .iter()
+            .find_map(|st| match &st.content {
+                llbc_ast::RawStatement::Call(call) => match &call.args[0] {
+                    llbc_ast::Operand::Move(p) if p.projection.is_empty() => Some(p.var_id.index()),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .unwrap()
+    };
+    // By default, we remove the reborrow `tmp := &mut *x` and give `x` to `incr`
+    let crate_data = translate(code)?;
+    assert_eq!(call_arg(&crate_data), 1);
+
+    let options = CliOpts {
+        keep_redundant_copies: true,
+        ..Default::default()
+    };
+    let crate_data = translate_with_options(code, options)?;
+    assert!(call_arg(&crate_data) > 1);
+    Ok(())
+}
+
+#[test]
+fn unit_locals() -> Result<(), Box<dyn Error>> {
+    let crate_data = translate(
+        "
+        fn g() {}
+        fn f() {
+            let x = g();
+            x
+        }
+        fn h() {
+            let a = ();
+            let _b = a;
+        }
+        ",
+    )?;
+    let find_body = |name: &str| {
+        crate_data
+            .functions
+            .iter()
+            .find(|f| repr_name(&f.name) == name)
+            .unwrap()
+            .body
+            .as_ref()
+            .unwrap()
+    };
+    // The result of `g` is not copied to the return value anymore
+    let f = find_body("test_crate::f");
+    assert!(!f.body.statements.iter().any(|st| matches!(
+        &st.content,
+        llbc_ast::RawStatement::Assign(_, llbc_ast::Rvalue::Use(_))
+    )));
+    assert!(f
+        .body
+        .statements
+        .iter()
+        .any(|st| matches!(&st.content, llbc_ast::RawStatement::Call(_))));
+    // Only the return value remains
+    let h = find_body("test_crate::h");
+    assert_eq!(h.locals.len(), 1);
+    Ok(())
+}
+
+#[test]
+fn inlined_functions() -> Result<(), Box<dyn Error>> {
+    let code = "
+        fn incr<T: Copy>(x: T, f: fn(T) -> T) -> T {
+            f(x)
+        }
+        fn double(x: u32) -> u32 {
+            if x > 100 {
+                return x;
+            }
+            2 * x
+        }
+        #[inline(never)]
+        fn id(x: u32) -> u32 {
+            x
+        }
+        fn f(x: u32) -> u32 {
+            id(double(incr(x, id)))
+        }
+        ";
+    // The functions called by `f`
+    let calls = |crate_data: &GCrateData<llbc_ast::FunDecl, llbc_ast::GlobalDecl>| {
+        let f = crate_data
+            .functions
+            .iter()
+            .find(|f| repr_name(&f.name) == "test_crate::f")
+            .unwrap();
+        f.body
+            .as_ref()
+            .unwrap()
+            .body
+            .statements
+            .iter()
+            .filter_map(|st| match &st.content {
+                llbc_ast::RawStatement::Call(llbc_ast::Call {
+                    func: llbc_ast::FnOperand::Regular(fn_ptr),
+                    ..
+                }) => match &fn_ptr.func {
+                    FunIdOrTraitMethodRef::Fun(FunId::Regular(id)) => {
+                        let callee = crate_data.functions.iter().find(|f| f.def_id == *id)?;
+                        Some(repr_name(&callee.name))
+                    }
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+    };
+    let crate_data = translate(code)?;
+    assert_eq!(
+        calls(&crate_data),
+        ["test_crate::incr", "test_crate::double", "test_crate::id"]
+    );
+
+    let options = CliOpts {
+        inline_threshold: Some(20),
+        ..Default::default()
+    };
+    let crate_data = translate_with_options(code, options)?;
+    // `id` is marked `#[inline(never)]`: we only inline `incr` and `double`
+    // (which returns early)
+    assert_eq!(calls(&crate_data), ["test_crate::id"]);
+    Ok(())
+}
+
+#[test]
+fn tail_calls_and_recursion() -> Result<(), Box<dyn Error>> {
+    let crate_data = translate(
+        "
+        fn even(n: u32) -> bool {
+            if n == 0 { true } else { odd(n - 1) }
+        }
+        fn odd(n: u32) -> bool {
+            if n == 0 { false } else { even(n - 1) }
+        }
+        fn fact(n: u32) -> u32 {
+            if n == 0 { 1 } else { n * fact(n - 1) }
+        }
+        fn f(n: u32) -> u32 {
+            fact(n)
+        }
+        ",
+    )?;
+    let find_fun = |name: &str| {
+        crate_data
+            .functions
+            .iter()
+            .find(|f| repr_name(&f.name) == name)
+            .unwrap()
+    };
+    // Whether the calls of a function are in tail position
+    let tail_calls = |name: &str| {
+        let mut body = find_fun(name).body.clone().unwrap().body;
+        let mut tails = Vec::new();
+        body.transform(&mut |st| {
+            if let llbc_ast::RawStatement::Call(call) = &st.content {
+                tails.push(call.is_tail);
+            }
+            None
+        });
+        tails
+    };
+    let (even, odd) = (find_fun("test_crate::even"), find_fun("test_crate::odd"));
+    let fact = find_fun("test_crate::fact");
+    let mut group = even.rec_group.clone().unwrap();
+    let mut expected = vec![even.def_id, odd.def_id];
+    group.sort();
+    expected.sort();
+    assert_eq!(group, expected);
+    assert_eq!(fact.rec_group, Some(vec![fact.def_id]));
+    assert_eq!(find_fun("test_crate::f").rec_group, None);
+
+    assert_eq!(tail_calls("test_crate::even"), [true]);
+    assert_eq!(tail_calls("test_crate::fact"), [false]);
+    assert_eq!(tail_calls("test_crate::f"), [true]);
+    Ok(())
+}
+
+#[test]
+fn loop_info() -> Result<(), Box<dyn Error>> {
+    let crate_data = translate(
+        "
+        fn f(n: u32) -> u32 {
+            let mut i = 0;
+            let mut s = 0;
+            'outer: loop {
+                let mut j = 0;
+                while j < n {
+                    if s > 100 {
+                        break 'outer;
+                    }
+                    s += j;
+                    j += 1;
+                }
+                i += 1;
+                if i > n {
+                    break;
+                }
+            }
+            s
+        }
+        ",
+    )?;
+    // Collect the information of the loops, in the order in which they appear
+    fn collect(b: &llbc_ast::Block, loops: &mut Vec<llbc_ast::LoopInfo>) {
+        for st in &b.statements {
+            match &st.content {
+                llbc_ast::RawStatement::Switch(switch) => {
+                    for b in switch.get_targets() {
+                        collect(b, loops)
+                    }
+                }
+                llbc_ast::RawStatement::Loop(body, info) => {
+                    loops.push(info.clone());
+                    collect(body, loops)
+                }
+                _ => (),
+            }
+        }
+    }
+    let f = crate_data
+        .functions
+        .iter()
+        .find(|f| repr_name(&f.name) == "test_crate::f")
+        .unwrap();
+    let mut loops = Vec::new();
+    collect(&f.body.as_ref().unwrap().body, &mut loops);
+    let [outer, inner] = loops.as_slice() else { panic!() };
+    assert_eq!((outer.id.index(), outer.depth), (0, 0));
+    assert_eq!((inner.id.index(), inner.depth), (1, 1));
+    // The `break 'outer` exits the two loops
+    assert!(outer.breaks.iter().any(|jump| jump.distance == 1));
+    assert!(!inner.breaks.is_empty());
+    assert!(inner.breaks.iter().all(|jump| jump.distance == 0));
+    Ok(())
+}
+
+#[test]
+fn loop_labels() -> Result<(), Box<dyn Error>> {
+    let crate_data = translate(
+        "
+        fn f(v: &[u32]) -> u32 {
+            let mut s = 0;
+            let mut i = 0;
+            'outer: while i < v.len() {
+                let mut j = i;
+                loop {
+                    if v[j] == 0 {
+                        continue 'outer;
+                    }
+                    if v[j] > 10 {
+                        break 'outer;
+                    }
+                    s += v[j];
+                    j += 1;
+                    if j >= v.len() {
+                        break;
+                    }
+                }
+                i += 1;
+            }
+            s
+        }
+        ",
+    )?;
+    // Collect the labels of the loops and the labels given by the breaks and
+    // continues, in the order in which they appear
+    fn collect(b: &llbc_ast::Block, labels: &mut Vec<String>, jumps: &mut Vec<String>) {
+        for st in &b.statements {
+            match &st.content {
+                llbc_ast::RawStatement::Switch(switch) => {
+                    for b in switch.get_targets() {
+                        collect(b, labels, jumps)
+                    }
+                }
+                llbc_ast::RawStatement::Loop(body, info) => {
+                    labels.push(info.label.clone());
+                    collect(body, labels, jumps)
+                }
+                llbc_ast::RawStatement::Break(_, label)
+                | llbc_ast::RawStatement::Continue(_, label) => jumps.push(label.clone().unwrap()),
+                _ => (),
+            }
+        }
+    }
+    let f = crate_data
+        .functions
+        .iter()
+        .find(|f| repr_name(&f.name) == "test_crate::f")
+        .unwrap();
+    let (mut labels, mut jumps) = (Vec::new(), Vec::new());
+    collect(&f.body.as_ref().unwrap().body, &mut labels, &mut jumps);
+    assert_eq!(labels, ["l0", "l1"]);
+    // The labeled break and continue target the outer loop
+    assert!(jumps.iter().any(|label| label == "l0"));
+    assert!(jumps.iter().any(|label| label == "l1"));
+    Ok(())
+}
+
+#[test]
+fn ullbc_output() -> Result<(), Box<dyn Error>> {
+    use charon_lib::{export, ullbc_ast};
+    let options = CliOpts {
+        ullbc: true,
+        ..Default::default()
+    };
+    let callback = run_charon(
+        "
+        fn fact(n: u32) -> u32 {
+            if n == 0 { 1 } else { n * fact(n - 1) }
+        }
+        fn f(n: u32) -> u32 {
+            fact(n)
+        }
+        fn get(a: [u32; 4], i: usize) -> u32 {
+            a[i]
+        }
+        ",
+        options,
+        &[],
+    )?;
+    let export::CrateData::ULLBC(crate_data) = callback.crate_data.unwrap() else {
+        panic!("expected ullbc data, got llbc instead")
+    };
+    let find_fun = |name: &str| {
+        crate_data
+            .functions
+            .iter()
+            .find(|f| repr_name(&f.name) == name)
+            .unwrap()
+    };
+    // Whether the calls of a function are in tail position
+    let tail_calls = |name: &str| -> Vec<bool> {
+        let body = &find_fun(name).body.as_ref().unwrap().body;
+        body.iter()
+            .filter_map(|block| match &block.terminator.content {
+                ullbc_ast::RawTerminator::Call { call, .. } => Some(call.is_tail),
+                _ => None,
+            })
+            .collect()
+    };
+    // The ULLBC gives the same information as the LLBC
+    let fact = find_fun("test_crate::fact");
+    assert_eq!(fact.rec_group, Some(vec![fact.def_id]));
+    assert_eq!(tail_calls("test_crate::fact"), [false]);
+    assert_eq!(tail_calls("test_crate::f"), [true]);
+
+    // The ULLBC has its own format version, and keeps the constructs that the
+    // LLBC desugars
+    let json = serde_json::to_value(&crate_data)?;
+    assert_eq!(json["format_version"], export::ULLBC_FORMAT_VERSION);
+    let get = serde_json::to_string(&find_fun("test_crate::get").body)?;
+    assert!(get.contains("\"Index\""));
+    assert!(get.contains("\"Assert\""));
+    Ok(())
+}
+
+#[test]
+fn profile() -> Result<(), Box<dyn Error>> {
+    let options = CliOpts {
+        profile: true,
+        ..Default::default()
+    };
+    let callback = run_charon(
+        "
+        fn f(x: u32) -> u32 { x + 1 }
+        fn g() -> u32 { f(0) }
+        ",
+        options,
+        &[],
+    )?;
+    let profile = callback.profile.unwrap();
+    let phases: Vec<&str> = profile.phases.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(phases[0], "exploration of the crate");
+    assert!(phases.contains(&"translation of the items"));
+    assert!(phases.contains(&"ullbc_to_llbc"));
+    assert!(phases.contains(&"remove_unused_locals"));
+    // The time spent translating every item
+    assert!(profile.items.iter().any(|item| item.name == "f"));
+    let report = profile.to_string();
+    assert!(report.contains("The slowest items to translate:"));
+    Ok(())
+}
+
+#[test]
+fn profile_trait_resolution_cache() -> Result<(), Box<dyn Error>> {
+    let options = CliOpts {
+        profile: true,
+        ..Default::default()
+    };
+    let callback = run_charon(
+        "
+        trait Tr { fn m(&self) -> u32; }
+        struct S<T>(T);
+        impl<T: Tr> S<T> {
+            fn a(&self) -> u32 { self.0.m() }
+            fn b(&self) -> u32 { self.0.m() + self.0.m() }
+        }
+        ",
+        options,
+        &[],
+    )?;
+    let profile = callback.profile.unwrap();
+    let find_cache = |name: &str| {
+        profile
+            .caches
+            .iter()
+            .find(|cache| cache.name == name)
+            .unwrap()
+    };
+    // We resolve `T: Tr` once, then find it in the cache
+    let cache = find_cache("trait resolution");
+    assert!(cache.misses > 0);
+    assert!(cache.hits > 0);
+    // We translate the reference to `T: Tr` once per item: the second call in
+    // `b` finds it in the cache
+    let cache = find_cache("trait references");
+    assert!(cache.misses > 0);
+    assert!(cache.hits > 0);
+    assert!(profile.to_string().contains("trait resolution: "));
+    Ok(())
+}
+
+#[test]
+fn string_table() -> Result<(), Box<dyn Error>> {
+    use serde_json::{json, Value};
+    let crate_data = translate(
+        "
+        struct Foo { x: u32 }
+        enum E { A(Foo), B, C { y: u32 } }
+        mod m { pub struct G; }
+        ",
+    )?;
+    let tmp_dir = tempfile::TempDir::new()?;
+    let file = tmp_dir.path().join("test_crate.llbc");
+    crate_data.serialize_to_file(&file)?;
+    let mut krate: Value = serde_json::from_slice(&std::fs::read(file)?)?;
+
+    // Every string appears once in the table, and the symbols refer to it
+    let strings: Vec<&str> = krate["strings"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| s.as_str().unwrap())
+        .collect();
+    assert_eq!(strings.iter().unique().count(), strings.len());
+    for s in ["test_crate", "Foo", "x", "E", "A", "B", "C", "y", "m", "G"] {
+        assert!(strings.contains(&s));
+    }
+    let crate_name = &krate["types"][0]["name"][0]["Ident"][0];
+    assert_eq!(strings[crate_name.as_u64().unwrap() as usize], "test_crate");
+
+    charon_lib::diff::resolve_symbols(&mut krate);
+    assert!(krate.get("strings").is_none());
+    let foo = krate["types"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|d| d["name"][1] == json!({ "Ident": ["Foo", 0] }))
+        .unwrap();
+    assert_eq!(foo["kind"]["Struct"][0]["name"], "x");
+    // We found all the symbols: the crate is the same as the crate serialized
+    // without the string table
+    let mut expected = serde_json::to_value(&crate_data)?;
+    expected.as_object_mut().unwrap().remove("strings");
+    assert_eq!(krate, expected);
+    Ok(())
+}
+
+#[test]
+fn extract_external_bodies() -> Result<(), Box<dyn Error>> {
+    let code = "
+        fn f(x: Option<u32>) -> bool {
+            x.is_none()
+        }
+        ";
+    let has_body = |crate_data: &GCrateData<llbc_ast::FunDecl, llbc_ast::GlobalDecl>,
+                    name: &str| {
+        crate_data
+            .functions
+            .iter()
+            .find(|f| repr_name(&f.name) == name)
+            .unwrap()
+            .body
+            .is_some()
+    };
+    let translate_with_patterns = |patterns: &[&str]| {
+        let options = CliOpts {
+            extract_external_bodies: patterns.iter().map(|p| p.to_string()).collect(),
+            ..Default::default()
+        };
+        translate_with_options(code, options)
+    };
+
+    // `is_none` calls `is_some`, whose body we only extract if it matches
+    let crate_data = translate_with_patterns(&["core::option::Option::is_none"])?;
+    assert!(has_body(&crate_data, "core::option::<impl>::is_none"));
+    assert!(!has_body(&crate_data, "core::option::<impl>::is_some"));
+
+    let crate_data = translate_with_patterns(&["core::option::*"])?;
+    assert!(has_body(&crate_data, "core::option::<impl>::is_none"));
+    assert!(has_body(&crate_data, "core::option::<impl>::is_some"));
+
+    let crate_data = translate_with_patterns(&[])?;
+    assert!(!has_body(&crate_data, "core::option::<impl>::is_none"));
+    Ok(())
+}
+
+#[test]
+fn builtin_models() -> Result<(), Box<dyn Error>> {
+    let code = "
+        fn f(x: Option<u32>, v: &Vec<u32>, s: &[u32]) -> (Option<u32>, usize, Option<&u32>) {
+            (x.map(|y| y + 1), v.len(), s.get(0))
+        }
+        ";
+    let body = |crate_data: &GCrateData<llbc_ast::FunDecl, llbc_ast::GlobalDecl>, name: &str| {
+        crate_data
+            .functions
+            .iter()
+            .find(|f| repr_name(&f.name) == name)
+            .unwrap()
+            .body
+            .as_ref()
+            .map(|body| format!("{:?}", body.body))
+    };
+
+    // By default, the external functions are opaque
+    let crate_data = translate(code)?;
+    assert!(body(&crate_data, "core::option::<impl>::map").is_none());
+    assert!(body(&crate_data, "alloc::vec::<impl>::len").is_none());
+    assert!(body(&crate_data, "core::slice::<impl>::get").is_none());
+
+    let options = CliOpts {
+        use_builtin_models: true,
+        ..Default::default()
+    };
+    let crate_data = translate_with_options(code, options)?;
+    let map = body(&crate_data, "core::option::<impl>::map").unwrap();
+    assert!(map.contains("call_once"));
+    let len = body(&crate_data, "alloc::vec::<impl>::len").unwrap();
+    assert!(len.contains("Len"));
+    // `Vec::len` calls `Vec::as_slice`, which remains opaque
+    assert!(body(&crate_data, "alloc::vec::<impl>::as_slice").is_none());
+    let get = body(&crate_data, "core::slice::<impl>::get").unwrap();
+    assert!(get.contains("\"get\""));
+    Ok(())
+}
+
+#[test]
+fn no_std() -> Result<(), Box<dyn Error>> {
+    let crate_data = translate(
+        "
+        #![no_std]
+        extern crate alloc;
+        use alloc::boxed::Box;
+        fn f(x: u32) -> Box<u32> {
+            if x == 0 {
+                panic!(\"zero: {}\", x)
+            }
+            Box::new(x)
+        }
+        ",
+    )?;
+    let f = crate_data
+        .functions
+        .iter()
+        .find(|f| repr_name(&f.name) == "test_crate::f")
+        .unwrap();
+    let body = format!("{:?}", f.body.as_ref().unwrap().body);
+    // The call to `core::panicking::panic_fmt` is a panic
+    assert!(body.contains("Panic"));
+    assert!(crate_data
+        .functions
+        .iter()
+        .all(|f| repr_name(&f.name) != "core::panicking::panic_fmt"));
+    // `Box` is an assumed type
+    assert!(matches!(
+        f.signature.output.kind(),
+        TyKind::Adt(TypeId::Assumed(_), _)
+    ));
+    Ok(())
+}
+
+#[test]
+fn cargo_profile() -> Result<(), Box<dyn Error>> {
+    let crate_data = translate("fn f() {}")?;
+    assert_eq!(crate_data.cargo_profile, "release");
+
+    let options = CliOpts {
+        cargo_profile: Some("dev".to_string()),
+        ..Default::default()
+    };
+    let crate_data = translate_with_options("fn f() {}", options)?;
+    assert_eq!(crate_data.cargo_profile, "dev");
+    Ok(())
+}
+
+#[test]
+fn custom_llbc_pass() -> Result<(), Box<dyn Error>> {
+    use charon_lib::export;
+    use charon_lib::llbc_ast::{RawStatement, Statement};
+    use charon_lib::transform::llbc_passes::LlbcPass;
+    use charon_lib::translate_ctx::TransformCtx;
+
+    /// Replace the bodies of the functions named `f` with a panic
+    struct PanicPass;
+    impl LlbcPass for PanicPass {
+        fn name(&self) -> &str {
+            "panic_pass"
+        }
+        fn transform_body(
+            &mut self,
+            _: &mut TransformCtx,
+            name: &Name,
+            body: &mut llbc_ast::ExprBody,
+        ) {
+            if repr_name(name) == "test_crate::f" {
+                body.body.statements = vec![Statement {
+                    meta: body.body.meta,
+                    content: RawStatement::Panic,
+                }];
+            }
+        }
+    }
+
+    let tmp_dir = tempfile::TempDir::new()?;
+    let file_path = tmp_dir.path().join("test_crate.rs");
+    std::fs::write(&file_path, "fn f() -> u32 { 0 } fn g() -> u32 { 1 }")?;
+    let mut callback = CharonCallbacks::new(Default::default());
+    callback.register_llbc_pass(Box::new(PanicPass));
+    callback.run_compiler(vec![file_path.to_string_lossy().into_owned()])?;
+    assert_eq!(callback.error_count, 0);
+    let export::CrateData::LLBC(crate_data) = callback.crate_data.unwrap() else {
+        panic!("expected llbc data, got ullbc instead")
+    };
+    let statements = |name: &str| {
+        let f = crate_data
+            .functions
+            .iter()
+            .find(|f| repr_name(&f.name) == name)
+            .unwrap();
+        f.body.as_ref().unwrap().body.statements.clone()
+    };
+    let f_statements = statements("test_crate::f");
+    assert_eq!(f_statements.len(), 1);
+    assert!(matches!(f_statements[0].content, RawStatement::Panic));
+    assert!(statements("test_crate::g").len() > 1);
+    Ok(())
+}
+
+#[test]
+fn custom_intrinsics() -> Result<(), Box<dyn Error>> {
+    use charon_lib::export;
+    use charon_lib::gast::IntrinsicKind;
+
+    let tmp_dir = tempfile::TempDir::new()?;
+    let file_path = tmp_dir.path().join("test_crate.rs");
+    std::fs::write(
+        &file_path,
+        "
+        fn assume(_: bool) {}
+        fn nondet() -> u32 { 0 }
+        fn f(x: u32) -> u32 {
+            assume(x > 0);
+            x + nondet()
+        }
+        ",
+    )?;
+    let mut callback = CharonCallbacks::new(Default::default());
+    callback.register_intrinsic("test_crate::assume", IntrinsicKind::Assume);
+    callback.register_intrinsic("test_crate::nondet", IntrinsicKind::Havoc);
+    callback.run_compiler(vec![file_path.to_string_lossy().into_owned()])?;
+    assert_eq!(callback.error_count, 0);
+    let export::CrateData::LLBC(crate_data) = callback.crate_data.unwrap() else {
+        panic!("expected llbc data, got ullbc instead")
+    };
+    let f = crate_data
+        .functions
+        .iter()
+        .find(|f| repr_name(&f.name) == "test_crate::f")
+        .unwrap();
+    let body = format!("{:?}", f.body.as_ref().unwrap().body);
+    assert!(body.contains("Intrinsic(Intrinsic { kind: Assume"));
+    assert!(body.contains("Intrinsic(Intrinsic { kind: Havoc"));
+    assert!(!body.contains("Call"));
+    Ok(())
+}
+
+#[test]
+fn verifier_primitives() -> Result<(), Box<dyn Error>> {
+    use charon_lib::gast::{Intrinsic, IntrinsicKind};
+    use charon_lib::llbc_ast::RawStatement;
+
+    let options = CliOpts {
+        verifier_assume: vec!["test_crate::verifier::assume".to_string()],
+        verifier_assert: vec!["test_crate::verifier::assert".to_string()],
+        verifier_nondet: vec!["test_crate::verifier::nondet".to_string()],
+        ..Default::default()
+    };
+    let crate_data = translate_with_options(
+        "
+        mod verifier {
+            pub fn assume(_: bool) {}
+            pub fn assert(_: bool, _: &str) {}
+            pub fn nondet<T: Default>() -> T { T::default() }
+        }
+        fn f() -> u32 {
+            let x: u32 = verifier::nondet();
+            verifier::assume(x < 10);
+            verifier::assert(x < 11, \"x is small\");
+            x
+        }
+        ",
+        options,
+    )?;
+    let f = crate_data
+        .functions
+        .iter()
+        .find(|f| repr_name(&f.name) == "test_crate::f")
+        .unwrap();
+    let body = &f.body.as_ref().unwrap().body;
+    assert!(!format!("{body:?}").contains("Call"));
+    // The built-ins are translated to intrinsics, and we keep the message of
+    // the assertion (and not in the arguments)
+    let intrinsics: Vec<&Intrinsic> = body
+        .statements
+        .iter()
+        .filter_map(|st| match &st.content {
+            RawStatement::Intrinsic(intrinsic) => Some(intrinsic),
+            _ => None,
+        })
+        .collect();
+    let kinds: Vec<&IntrinsicKind> = intrinsics.iter().map(|i| &i.kind).collect();
+    assert_eq!(
+        kinds,
+        [
+            &IntrinsicKind::Havoc,
+            &IntrinsicKind::Assume,
+            &IntrinsicKind::Assert
+        ]
+    );
+    let assert = intrinsics[2];
+    assert_eq!(assert.args.len(), 1);
+    assert_eq!(assert.message.as_deref(), Some("x is small"));
+    Ok(())
+}
+
+#[test]
+fn harnesses() -> Result<(), Box<dyn Error>> {
+    let options = CliOpts {
+        harness_attributes: vec!["verifier::harness".to_string()],
+        ..Default::default()
+    };
+    let crate_data = translate_with_options(
+        "
+        #![feature(register_tool)]
+        #![register_tool(verifier)]
+        fn f(x: u32) -> u32 { x }
+        #[verifier::harness(unwind = 3)]
+        fn check_f() { f(0); }
+        ",
+        options,
+    )?;
+    assert_eq!(crate_data.harnesses.len(), 1);
+    let harness = &crate_data.harnesses[0];
+    let f = crate_data
+        .functions
+        .iter()
+        .find(|f| f.def_id == harness.fun_id)
+        .unwrap();
+    assert_eq!(repr_name(&f.name), "test_crate::check_f");
+    assert_eq!(harness.attribute, "verifier::harness");
+    assert_eq!(harness.args, vec!["unwind = 3".to_string()]);
+    Ok(())
+}
+
+#[test]
+fn ghost_code() -> Result<(), Box<dyn Error>> {
+    let crate_data = translate(
+        "
+        macro_rules! ghost { ($($t:tt)*) => {{ $($t)* }} }
+        #[charon::ghost]
+        fn lemma(_: u32) {}
+        fn spec(_: u32) {}
+        fn g(x: u32) -> u32 { x }
+        fn f(x: u32) -> u32 {
+            lemma(x);
+            ghost! { spec(x); }
+            g(x)
+        }
+        ",
+    )?;
+    let lemma = crate_data
+        .functions
+        .iter()
+        .find(|f| repr_name(&f.name) == "test_crate::lemma")
+        .unwrap();
+    assert_eq!(crate_data.ghost_functions, vec![lemma.def_id]);
+    let f = crate_data
+        .functions
+        .iter()
+        .find(|f| repr_name(&f.name) == "test_crate::f")
+        .unwrap();
+    let body = format!("{:?}", f.body.as_ref().unwrap().body);
+    assert_eq!(body.matches("is_ghost: true").count(), 2);
+    assert_eq!(body.matches("is_ghost: false").count(), 1);
+    Ok(())
+}
+
+#[test]
+fn method_resolution() -> Result<(), Box<dyn Error>> {
+    let crate_data = translate(
+        "
+        trait Greet {
+            fn hello(&self) -> u32 { 0 }
+            fn name(&self) -> u32;
+        }
+        struct Foo;
+        impl Foo {
+            fn inherent(&self) -> u32 { 1 }
+        }
+        impl Greet for Foo {
+            fn name(&self) -> u32 { 2 }
+        }
+        fn f(x: &Foo) -> (u32, u32, u32) {
+            (x.inherent(), x.hello(), x.name())
+        }
+        fn g<T: Greet>(x: &T) -> u32 {
+            x.name()
+        }
+        ",
+    )?;
+    let resolutions = |name: &str| -> Vec<String> {
+        let f = crate_data
+            .functions
+            .iter()
+            .find(|f| repr_name(&f.name) == name)
+            .unwrap();
+        f.body
+            .as_ref()
+            .unwrap()
+            .body
+            .statements
+            .iter()
+            .filter_map(|st| match &st.content {
+                llbc_ast::RawStatement::Call(call) => Some(format!("{:?}", call.resolution)),
+                _ => None,
+            })
+            .collect()
+    };
+    let impl_id = crate_data.trait_impls[0].def_id;
+    assert_eq!(
+        resolutions("test_crate::f"),
+        vec![
+            "Some(Inherent)".to_string(),
+            format!("Some(DefaultMethod({impl_id:?}))"),
+            format!("Some(TraitImpl({impl_id:?}))"),
+        ]
+    );
+    assert_eq!(resolutions("test_crate::g"), vec!["Some(Clause)"]);
+    Ok(())
+}
+
+#[test]
+fn simplify_trait_refs() -> Result<(), Box<dyn Error>> {
+    let options = CliOpts {
+        inline_threshold: Some(10),
+        ..Default::default()
+    };
+    let crate_data = translate_with_options(
+        "
+        trait Foo { fn foo(&self) -> u32; }
+        trait Bar: Foo {}
+        struct S;
+        impl Foo for S { fn foo(&self) -> u32 { 0 } }
+        impl Bar for S {}
+        fn g<T: Bar>(x: &T) -> u32 { x.foo() }
+        fn f(x: &S) -> u32 { g(x) }
+        ",
+        options,
+    )?;
+    let f = crate_data
+        .functions
+        .iter()
+        .find(|f| repr_name(&f.name) == "test_crate::f")
+        .unwrap();
+    let foo = crate_data
+        .trait_decls
+        .iter()
+        .find(|d| repr_name(&d.name) == "test_crate::Foo")
+        .unwrap();
+    let foo_impl = crate_data
+        .trait_impls
+        .iter()
+        .find(|imp| imp.impl_trait.trait_id == foo.def_id)
+        .unwrap();
+    // The call to `foo` inlined from `g` refers to the parent clause of `impl Bar for S`
+    let body = format!("{:?}", f.body.as_ref().unwrap().body);
+    assert!(!body.contains("ParentClause"));
+    assert!(body.contains(&format!("TraitImpl({:?})", foo_impl.def_id)));
+    Ok(())
+}
+
+#[test]
+fn solve_trait_obligations() -> Result<(), Box<dyn Error>> {
+    let options = CliOpts {
+        report: Some("report.json".into()),
+        ..Default::default()
+    };
+    let callback = run_charon(
+        "
+        trait Bar {}
+        trait Foo<U: Bar> { fn foo(&self, x: U); }
+        struct S;
+        impl Bar for S {}
+        fn f<T: Foo<U>, U: Bar>(x: &T, y: U) { x.foo(y) }
+        fn g<T: Foo<S>>(x: &T) { f(x, S) }
+        ",
+        options,
+        &[],
+    )?;
+    let report = callback.report.unwrap();
+    assert!(report.unsolved_obligations.is_empty());
+    let charon_lib::export::CrateData::LLBC(crate_data) = callback.crate_data.unwrap() else {
+        panic!("expected llbc data, got ullbc instead")
+    };
+    let functions = format!("{:?}", crate_data.functions);
+    assert!(!functions.contains("Unsolved"));
+    assert!(!functions.contains("Unknown"));
+    Ok(())
+}
+
+#[test]
+fn unsolved_trait_obligations() -> Result<(), Box<dyn Error>> {
+    let options = CliOpts {
+        report: Some("report.json".into()),
+        errors_as_warnings: true,
+        ..Default::default()
+    };
+    // We don't solve the obligations modulo the equality constraints: we fail
+    // to prove `<T as Tr>::A: Bar` from `T: Tr<A = U>` and `U: Bar`, in the
+    // where clause of the struct and in the supertrait of the trait.
+    let callback = run_charon_allowing_errors(
+        "
+        trait Bar {}
+        trait Tr { type A; }
+        trait Foo<U: Bar> {}
+        struct S<T: Tr<A = U>, U: Bar, V: Foo<T::A>>(T, U, V);
+        trait Baz<T: Tr<A = U>, U: Bar>: Foo<T::A> {}
+        ",
+        options,
+        &[],
+    )?;
+    assert!(callback.error_count > 0);
+    let report = callback.report.unwrap();
+    let items = report
+        .unsolved_obligations
+        .iter()
+        .map(|obligation| obligation.item.as_str())
+        .collect_vec();
+    assert!(items.contains(&"test_crate::S"));
+    assert!(items.contains(&"test_crate::Baz"));
+    // We replaced the obligations: we never export `Unsolved`
+    let charon_lib::export::CrateData::LLBC(crate_data) = callback.crate_data.unwrap() else {
+        panic!("expected llbc data, got ullbc instead")
+    };
+    let decls = format!("{:?} {:?}", crate_data.types, crate_data.trait_decls);
+    assert!(!decls.contains("Unsolved"));
+    assert!(decls.contains("Could not solve the trait obligation"));
+    Ok(())
+}
+
+#[test]
+fn clause_envs() -> Result<(), Box<dyn Error>> {
+    let crate_data = translate(
+        "
+        trait Foo {}
+        trait Bar: Foo { type Item: Foo; }
+        fn f<T: Bar>(_x: T) {}
+        ",
+    )?;
+    let f = crate_data
+        .functions
+        .iter()
+        .find(|f| repr_name(&f.name) == "test_crate::f")
+        .unwrap();
+    let foo = crate_data
+        .trait_decls
+        .iter()
+        .find(|d| repr_name(&d.name) == "test_crate::Foo")
+        .unwrap();
+    let env = crate_data
+        .clause_envs
+        .iter()
+        .find(|env| env.item == AnyTransId::Fun(f.def_id))
+        .unwrap();
+    let clauses = &env.clauses;
+    // The local clause `T: Bar`, then its parent clause and its item clause
+    assert_eq!(clauses.len(), 3);
+    let TraitInstanceId::Clause(clause_id) = clauses[0].trait_id else {
+        panic!("expected a local clause")
+    };
+    let TraitInstanceId::ParentClause(parent, _, _) = &clauses[1].trait_id else {
+        panic!("expected a parent clause")
+    };
+    assert_eq!(**parent, TraitInstanceId::Clause(clause_id));
+    assert_eq!(clauses[1].trait_decl_ref.trait_id, foo.def_id);
+    let TraitInstanceId::ItemClause(parent, _, _, _) = &clauses[2].trait_id else {
+        panic!("expected an item clause")
+    };
+    assert_eq!(**parent, TraitInstanceId::Clause(clause_id));
+    assert_eq!(clauses[2].trait_decl_ref.trait_id, foo.def_id);
+    Ok(())
+}
+
+#[test]
+fn normalize_trait_types() -> Result<(), Box<dyn Error>> {
+    let code = "
+        use std::ops::Add;
+        trait Foo { type Item; fn get(&self) -> Self::Item; }
+        impl Foo for u8 { type Item = u16; fn get(&self) -> u16 { 0 } }
+        fn f<T: Add<Output = u32>>(x: T, y: T) -> T::Output { x + y }
+        fn g(x: u8) -> <u8 as Foo>::Item { x.get() }
+        ";
+    let output = |crate_data: &GCrateData<llbc_ast::FunDecl, llbc_ast::GlobalDecl>, name: &str| {
+        let f = crate_data
+            .functions
+            .iter()
+            .find(|f| repr_name(&f.name) == name)
+            .unwrap();
+        f.signature.output.clone()
+    };
+    let crate_data = translate(code)?;
+    // Normalized with the constraint
+    let f_output = output(&crate_data, "test_crate::f");
+    assert!(matches!(f_output.kind(), TyKind::Literal(_)));
+    // Normalized with the implementation
+    let g_output = output(&crate_data, "test_crate::g");
+    assert!(matches!(g_output.kind(), TyKind::Literal(_)));
+
+    let options = CliOpts {
+        no_normalize_trait_types: true,
+        ..Default::default()
+    };
+    let crate_data = translate_with_options(code, options)?;
+    let f_output = output(&crate_data, "test_crate::f");
+    assert!(matches!(f_output.kind(), TyKind::TraitType(..)));
+    Ok(())
+}
+
+#[test]
+fn generic_param_defaults() -> Result<(), Box<dyn Error>> {
+    let crate_data = translate(
+        "
+        struct Foo<T = u32, const N: usize = 4>([T; N]);
+        fn f<T>(x: T) -> T { x }
+        ",
+    )?;
+    let generics = &crate_data.types[0].generics;
+    let default = generics.types[0].default.as_ref().unwrap();
+    assert!(matches!(default.kind(), TyKind::Literal(_)));
+    assert!(matches!(
+        generics.const_generics[0].default,
+        Some(ConstGeneric::Value(_))
+    ));
+    // The functions can't have defaults
+    assert!(crate_data.functions[0].signature.generics.types[0]
+        .default
+        .is_none());
+    Ok(())
+}
+
+#[test]
+fn phantom_data() -> Result<(), Box<dyn Error>> {
+    let code = "
+        use std::marker::PhantomData;
+        struct Foo<'a, T> { x: u32, unit: (), phantom: PhantomData<&'a T>, y: u32 }
+        fn new<'a, T>() -> Foo<'a, T> { Foo { x: 0, unit: (), phantom: PhantomData, y: 1 } }
+        fn get<'a, T>(foo: &Foo<'a, T>) -> u32 { foo.y }
+        ";
+    let crate_data = translate(code)?;
+    let foo = crate_data
+        .types
+        .iter()
+        .find(|d| repr_name(&d.name) == "test_crate::Foo")
+        .unwrap();
+    let TypeDeclKind::Struct(fields) = &foo.kind else {
+        panic!()
+    };
+    let flags = fields
+        .iter()
+        .map(|f| (f.is_zst, f.is_phantom_data))
+        .collect_vec();
+    assert_eq!(
+        flags,
+        vec![(false, false), (true, false), (true, true), (false, false)]
+    );
+    assert_eq!(
+        foo.variances.regions.iter().copied().collect_vec(),
+        vec![Variance::Covariant]
+    );
+    assert_eq!(
+        foo.variances.types.iter().copied().collect_vec(),
+        vec![Variance::Covariant]
+    );
+
+    let options = CliOpts {
+        remove_phantom_data: true,
+        validate: true,
+        ..Default::default()
+    };
+    let crate_data = translate_with_options(code, options)?;
+    let foo = crate_data
+        .types
+        .iter()
+        .find(|d| repr_name(&d.name) == "test_crate::Foo")
+        .unwrap();
+    let TypeDeclKind::Struct(fields) = &foo.kind else {
+        panic!()
+    };
+    let names = fields
+        .iter()
+        .map(|f| f.name.as_ref().unwrap().to_string())
+        .collect_vec();
+    assert_eq!(names, ["x", "unit", "y"]);
+    // The variance of the parameters is preserved
+    assert_eq!(foo.variances.types.len(), 1);
+    Ok(())
+}
+
+#[test]
+fn phantom_data_closure_captures() -> Result<(), Box<dyn Error>> {
+    let code = "
+        use std::marker::PhantomData;
+        struct Foo<T> { x: u32, phantom: PhantomData<T>, y: u32 }
+        fn get<T>(foo: Foo<T>) -> u32 {
+            let y = || foo.y;
+            y()
+        }
+        ";
+    let options = CliOpts {
+        remove_phantom_data: true,
+        validate: true,
+        ..Default::default()
+    };
+    let crate_data = translate_with_options(code, options)?;
+    let foo = crate_data
+        .types
+        .iter()
+        .find(|d| repr_name(&d.name) == "test_crate::Foo")
+        .unwrap();
+    let TypeDeclKind::Struct(fields) = &foo.kind else {
+        panic!()
+    };
+    let names = fields
+        .iter()
+        .map(|f| f.name.as_ref().unwrap().to_string())
+        .collect_vec();
+    assert_eq!(names, ["x", "y"]);
+    // The closure captures `foo.y`, which is now the field 1 of `Foo`
+    let info = crate_data
+        .functions
+        .iter()
+        .find_map(|f| f.signature.closure_info.as_ref())
+        .unwrap();
+    assert_eq!(info.captures.len(), 1);
+    let capture = &info.captures[0];
+    let get = crate_data
+        .functions
+        .iter()
+        .find(|f| repr_name(&f.name) == "test_crate::get")
+        .unwrap();
+    let locals = &get.body.as_ref().unwrap().locals;
+    assert_eq!(locals[capture.var_id].name.as_deref(), Some("foo"));
+    assert_eq!(
+        capture.projection,
+        vec![CaptureProjection::Field(FieldId::Id::new(1))]
+    );
+    assert_eq!(capture.field_adts, vec![Some((foo.def_id, None))]);
+    Ok(())
+}
+
+#[test]
+fn type_aliases() -> Result<(), Box<dyn Error>> {
+    let code = "
+        type Res<T> = Result<T, u8>;
+        struct Foo { x: Res<u32>, y: u32 }
+        fn f(x: Res<u32>, y: u32) -> Res<u32> { x }
+        ";
+    // The type aliases are not translated by default
+    let crate_data = translate(code)?;
+    assert!(crate_data.type_aliases.is_empty());
+
+    let options = CliOpts {
+        type_aliases: true,
+        ..Default::default()
+    };
+    let crate_data = translate_with_options(code, options)?;
+    assert_eq!(crate_data.type_aliases.len(), 1);
+    let alias = &crate_data.type_aliases[0];
+    assert_eq!(repr_name(&alias.name), "test_crate::Res");
+    assert_eq!(alias.generics.types.len(), 1);
+
+    let foo = crate_data
+        .types
+        .iter()
+        .find(|d| repr_name(&d.name) == "test_crate::Foo")
+        .unwrap();
+    let TypeDeclKind::Struct(fields) = &foo.kind else {
+        panic!()
+    };
+    let field_alias = fields[0].alias.as_ref().unwrap();
+    assert_eq!(field_alias.alias_id, alias.def_id);
+    assert!(fields[1].alias.is_none());
+
+    let f = &crate_data.functions[0];
+    assert_eq!(f.signature.input_aliases.len(), 2);
+    assert_eq!(f.signature.input_aliases[0].as_ref(), Some(field_alias));
+    assert!(f.signature.input_aliases[1].is_none());
+    assert_eq!(f.signature.output_alias.as_ref(), Some(field_alias));
+    Ok(())
+}
+
+#[test]
+fn recursive_types() -> Result<(), Box<dyn Error>> {
+    let crate_data = translate(
+        "
+        struct List { x: u32, next: Option<Box<List>> }
+        enum Tree { Leaf, Node(Forest) }
+        struct Forest { trees: Vec<Tree> }
+        struct Pair { x: u32, y: u32 }
+        ",
+    )?;
+    let find_type = |name: &str| {
+        crate_data
+            .types
+            .iter()
+            .find(|d| repr_name(&d.name) == name)
+            .unwrap()
+    };
+    let list = find_type("test_crate::List");
+    assert_eq!(list.rec_group, Some(vec![list.def_id]));
+    let tree = find_type("test_crate::Tree");
+    let forest = find_type("test_crate::Forest");
+    let mut group = tree.rec_group.clone().unwrap();
+    let mut expected = vec![tree.def_id, forest.def_id];
+    group.sort();
+    expected.sort();
+    assert_eq!(group, expected);
+    assert_eq!(forest.rec_group.as_ref().map(Vec::len), Some(2));
+    assert_eq!(find_type("test_crate::Pair").rec_group, None);
+    Ok(())
+}
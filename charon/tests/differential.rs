@@ -0,0 +1,306 @@
+//! Differential tests for the LLBC. Each `<file>.rs` file in `differential/` is a library: we
+//! translate it to LLBC, and compile it natively. Then, we run the functions which take integers,
+//! booleans or characters on generated inputs, both with the LLBC interpreter (see
+//! [charon_lib::interp]) and natively, and report the inputs on which the results differ. This
+//! catches the translation bugs, in particular in the micro-passes.
+//!
+//! The native code is compiled with the overflow checks, because the arithmetic overflows are
+//! panics in the LLBC. The results are compared through their `Debug` representation: the types
+//! returned by the tested functions must implement `Debug`.
+#![feature(rustc_private)]
+
+use anyhow::{anyhow, bail};
+use libtest_mimic::{Outcome, Test};
+use std::{
+    error::Error,
+    fs::read_to_string,
+    path::{Path, PathBuf},
+    process::Command,
+};
+use walkdir::{DirEntry, WalkDir};
+
+use charon_lib::{
+    cli_options::CliOpts,
+    driver::CharonCallbacks,
+    export::CrateData,
+    interp::{self, ExecError, LlbcCrate},
+    names::PathElem,
+    types::{LiteralTy, TyKind},
+    values::ScalarValue,
+};
+
+static TESTS_DIR: &str = "tests/differential";
+
+/// The number of inputs on which we run every function.
+const NUM_INPUTS: usize = 32;
+
+/// The maximal number of divergences we report for a file.
+const MAX_REPORTED: usize = 10;
+
+/// A function we test.
+struct TestedFun {
+    name: String,
+    inputs: Vec<LiteralTy>,
+}
+
+/// The result of a function call.
+#[derive(Debug, PartialEq, Eq)]
+enum RunResult {
+    /// The returned value, printed with `Debug`.
+    Value(String),
+    Panic,
+    /// The interpreter detected an undefined behavior.
+    Error(String),
+}
+
+/// A small pseudo-random generator (xorshift), so that the inputs are the same from one run to
+/// the other.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
+
+/// Generate an argument of the given type. We favor the small values and the bounds of the
+/// integer types, which are the most likely to exhibit the bugs.
+fn generate_arg(rng: &mut Rng, ty: &LiteralTy) -> String {
+    match ty {
+        LiteralTy::Bool => (rng.next() % 2 == 0).to_string(),
+        LiteralTy::Char => {
+            if rng.next() % 2 == 0 {
+                char::from(b' ' + (rng.next() % 95) as u8).to_string()
+            } else {
+                // The arguments of a process can't contain `\0`
+                let c = (rng.next() % 0x10FFFF) as u32 + 1;
+                char::from_u32(c).unwrap_or('a').to_string()
+            }
+        }
+        LiteralTy::Integer(int_ty) => {
+            let bits = int_ty.size() * 8;
+            let v: u128 = match rng.next() % 4 {
+                // Small values (possibly negative)
+                0 | 1 => ((rng.next() % 17) as i128 - 4) as u128,
+                // The bounds: `-1` or `MAX` (for the unsigned integers), `MIN` and `MAX`
+                2 => match rng.next() % 3 {
+                    0 => u128::MAX,
+                    1 => 1 << (bits - 1),
+                    _ => (1 << (bits - 1)) - 1,
+                },
+                _ => ((rng.next() as u128) << 64) | rng.next() as u128,
+            };
+            // Truncate the value
+            let v = ScalarValue::from_le_bytes(*int_ty, v.to_le_bytes());
+            if int_ty.is_signed() {
+                v.as_int().unwrap().to_string()
+            } else {
+                v.as_uint().unwrap().to_string()
+            }
+        }
+    }
+}
+
+/// The functions of the crate we can test: the top-level functions without generics, whose
+/// arguments are integers, booleans or characters.
+fn tested_funs(krate: &LlbcCrate) -> Vec<TestedFun> {
+    krate
+        .functions
+        .iter()
+        .filter(|f| f.body.is_some() && !f.signature.is_closure)
+        .filter(|f| f.signature.generics.types.is_empty())
+        .filter(|f| f.signature.generics.const_generics.is_empty())
+        .filter_map(|f| {
+            let [PathElem::Ident(_, _), PathElem::Ident(name, _)] = f.name.name.as_slice() else {
+                return None;
+            };
+            let inputs = f
+                .signature
+                .inputs
+                .iter()
+                .map(|ty| match ty.kind() {
+                    TyKind::Literal(lit_ty) => Some(*lit_ty),
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>()?;
+            Some(TestedFun {
+                name: name.to_string(),
+                inputs,
+            })
+        })
+        .collect()
+}
+
+fn translate(input_path: &Path) -> anyhow::Result<LlbcCrate> {
+    let options = CliOpts {
+        no_serialize: true,
+        ..CliOpts::default()
+    };
+    let mut callback = CharonCallbacks::new(options);
+    let args = vec![
+        input_path.to_string_lossy().into_owned(),
+        "--crate-name=test_crate".to_string(),
+        "--crate-type=lib".to_string(),
+        "--edition=2021".to_string(),
+    ];
+    callback
+        .run_compiler(args)
+        .map_err(|e| anyhow!("The extraction failed: {e}"))?;
+    if callback.error_count > 0 {
+        bail!("The extraction generated {} errors", callback.error_count)
+    }
+    match callback.crate_data {
+        Some(CrateData::LLBC(krate)) => Ok(krate),
+        _ => bail!("expected llbc data"),
+    }
+}
+
+/// Compile the file natively, together with a `main` function which calls the function given
+/// as first argument on the other arguments, and prints the result.
+fn compile_native(input_path: &Path, funs: &[TestedFun], dir: &Path) -> anyhow::Result<PathBuf> {
+    let mut code = read_to_string(input_path)?;
+    code += "\nfn main() {\n";
+    code += "    let args: Vec<String> = std::env::args().skip(1).collect();\n";
+    code += "    match args[0].as_str() {\n";
+    for f in funs {
+        let args = f
+            .inputs
+            .iter()
+            .enumerate()
+            .map(|(i, ty)| format!("args[{}].parse::<{ty}>().unwrap()", i + 1))
+            .collect::<Vec<_>>()
+            .join(", ");
+        code += &format!(
+            "        \"{}\" => println!(\"{{:?}}\", {}({args})),\n",
+            f.name, f.name
+        );
+    }
+    code += "        _ => unreachable!(),\n    }\n}\n";
+
+    let main_path = dir.join("main.rs");
+    let exe_path = dir.join("main");
+    std::fs::write(&main_path, code)?;
+    let output = Command::new("rustc")
+        .arg("--edition=2021")
+        .arg("-O")
+        .arg("-Coverflow-checks=on")
+        .arg("-Awarnings")
+        .arg("-o")
+        .arg(&exe_path)
+        .arg(&main_path)
+        .output()?;
+    if !output.status.success() {
+        bail!(
+            "The native compilation failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        )
+    }
+    Ok(exe_path)
+}
+
+/// Run the native code. Returns `None` if the process was killed (by a stack overflow for
+/// instance).
+fn run_native(exe_path: &Path, name: &str, args: &[String]) -> anyhow::Result<Option<RunResult>> {
+    let output = Command::new(exe_path).arg(name).args(args).output()?;
+    Ok(match output.status.code() {
+        Some(0) => {
+            let stdout = String::from_utf8(output.stdout)?;
+            Some(RunResult::Value(stdout.trim_end().to_string()))
+        }
+        Some(101) => Some(RunResult::Panic),
+        _ => None,
+    })
+}
+
+/// Run the LLBC interpreter. Returns `None` if the interpreter doesn't support the function
+/// (because it calls an external function for instance).
+fn run_llbc(krate: &LlbcCrate, name: &str, args: &[String]) -> Option<RunResult> {
+    match interp::exec(krate, &format!("test_crate::{name}"), args) {
+        Ok(value) => Some(RunResult::Value(value)),
+        Err(ExecError::Panic(_)) => Some(RunResult::Panic),
+        Err(ExecError::UndefinedBehavior(msg)) => Some(RunResult::Error(msg)),
+        Err(ExecError::Unsupported(_)) => None,
+    }
+}
+
+fn perform_test(input_path: &Path) -> anyhow::Result<()> {
+    let krate = translate(input_path)?;
+    let funs = tested_funs(&krate);
+    if funs.is_empty() {
+        bail!("No function to test")
+    }
+    let tmp_dir = tempfile::TempDir::new()?;
+    let exe_path = compile_native(input_path, &funs, tmp_dir.path())?;
+
+    let mut divergences = Vec::new();
+    let mut num_runs = 0;
+    for f in &funs {
+        let mut rng = Rng(0x9E3779B97F4A7C15);
+        for _ in 0..NUM_INPUTS {
+            let args: Vec<String> = f.inputs.iter().map(|ty| generate_arg(&mut rng, ty)).collect();
+            let (Some(native), Some(llbc)) = (
+                run_native(&exe_path, &f.name, &args)?,
+                run_llbc(&krate, &f.name, &args),
+            ) else {
+                continue;
+            };
+            num_runs += 1;
+            if native != llbc {
+                divergences.push(format!(
+                    "{}({}): native: {native:?}, llbc: {llbc:?}",
+                    f.name,
+                    args.join(", ")
+                ));
+            }
+        }
+    }
+    if num_runs == 0 {
+        bail!("The interpreter couldn't execute any of the functions")
+    }
+    if !divergences.is_empty() {
+        let num = divergences.len();
+        divergences.truncate(MAX_REPORTED);
+        bail!(
+            "Found {num} divergences between the LLBC and the native code:\n{}",
+            divergences.join("\n")
+        )
+    }
+    Ok(())
+}
+
+#[test]
+fn differential() -> Result<(), Box<dyn Error>> {
+    let root: PathBuf = TESTS_DIR.into();
+    let file_filter = |e: &DirEntry| e.file_name().to_str().is_some_and(|s| s.ends_with(".rs"));
+    let tests: Vec<_> = WalkDir::new(root)
+        .min_depth(1)
+        .into_iter()
+        .filter_map(|entry| match entry {
+            Ok(entry) if !file_filter(&entry) => None,
+            res => Some(res),
+        })
+        .map(|entry| {
+            let path = entry?.into_path();
+            let name = path.strip_prefix(TESTS_DIR)?.to_string_lossy().into_owned();
+            anyhow::Result::Ok(Test {
+                name,
+                kind: "".into(),
+                is_ignored: false,
+                is_bench: false,
+                data: path,
+            })
+        })
+        .collect::<anyhow::Result<_>>()?;
+
+    let args = libtest_mimic::Arguments::from_args();
+    libtest_mimic::run_tests(&args, tests, move |t| match perform_test(&t.data) {
+        Ok(()) => Outcome::Passed,
+        Err(err) => Outcome::Failed {
+            msg: Some(err.to_string()),
+        },
+    })
+    .exit()
+}
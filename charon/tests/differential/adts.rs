@@ -0,0 +1,139 @@
+//! The structures, the enumerations, the arrays, the boxes, the traits and
+//! the closures.
+#[derive(Debug, Clone, Copy)]
+pub struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[derive(Debug)]
+pub enum Shape {
+    Circle(u32),
+    Rect { w: u32, h: u32 },
+    Empty,
+}
+
+#[derive(Debug)]
+pub struct Wrapper(u8, bool);
+
+pub enum List {
+    Nil,
+    Cons(u32, Box<List>),
+}
+
+pub trait Area {
+    fn area(&self) -> u32;
+    fn double_area(&self) -> u32 {
+        2 * self.area()
+    }
+}
+
+impl Area for Shape {
+    fn area(&self) -> u32 {
+        match self {
+            Shape::Circle(r) => 3 * *r * *r,
+            Shape::Rect { w, h } => *w * *h,
+            Shape::Empty => 0,
+        }
+    }
+}
+
+impl Area for Point {
+    fn area(&self) -> u32 {
+        0
+    }
+    fn double_area(&self) -> u32 {
+        1
+    }
+}
+
+fn total_area<T: Area>(x: &T, y: &T) -> u32 {
+    x.double_area() + y.area()
+}
+
+pub fn make_point(x: i32, y: i32) -> Point {
+    let mut p = Point { x, y: 0 };
+    p.y = y - x;
+    p
+}
+
+pub fn swap_point(x: i32, y: i32) -> Point {
+    let p = Point { x, y };
+    let q = &mut Point { x: 0, y: 0 };
+    q.x = p.y;
+    q.y = p.x;
+    *q
+}
+
+pub fn make_shape(k: u8, n: u32) -> Shape {
+    match k % 3 {
+        0 => Shape::Circle(n),
+        1 => Shape::Rect { w: n, h: n / 2 },
+        _ => Shape::Empty,
+    }
+}
+
+pub fn shape_areas(k: u8, n: u32) -> (u32, u32) {
+    let s = make_shape(k, n);
+    let t = make_shape(k + 1, n / 3);
+    (total_area(&s, &t), total_area(&make_point(1, 2), &make_point(3, 4)))
+}
+
+pub fn wrapper(x: u8, b: bool) -> Wrapper {
+    let mut w = Wrapper(x, b);
+    if b {
+        w.0 = w.0 / 2;
+    }
+    w
+}
+
+pub fn arrays(i: usize, x: u16) -> [u16; 4] {
+    let mut a = [x; 4];
+    a[1] = 7;
+    a[i] = a[i] + 1;
+    a
+}
+
+pub fn slices(i: usize) -> u32 {
+    let a = [1u32, 2, 3, 4, 5];
+    let s: &[u32] = &a;
+    let mut total = 0;
+    let mut j = 0;
+    while j < 5 {
+        total += s[j];
+        j += 1;
+    }
+    total * s[i]
+}
+
+fn list_sum(l: &List) -> u32 {
+    match l {
+        List::Nil => 0,
+        List::Cons(x, tl) => *x + list_sum(tl),
+    }
+}
+
+pub fn boxes(n: u8, x: u32) -> u32 {
+    let mut l = List::Nil;
+    let mut i = 0;
+    while i < n % 20 {
+        l = List::Cons(x, Box::new(l));
+        i += 1;
+    }
+    list_sum(&l)
+}
+
+pub fn boxed_value(x: i64) -> Box<i64> {
+    let mut b = Box::new(x);
+    *b = *b / 2;
+    b
+}
+
+pub fn closures(x: u32, y: u32) -> u32 {
+    let add = |a: u32| a + y;
+    let mut count = 0;
+    let mut incr = |n: u32| count += n;
+    incr(add(x));
+    incr(1);
+    count
+}
@@ -0,0 +1,72 @@
+//! The arithmetic operations, the comparisons and the casts.
+pub fn add_u8(x: u8, y: u8) -> u8 {
+    x + y
+}
+
+pub fn sub_i16(x: i16, y: i16) -> i16 {
+    x - y
+}
+
+pub fn mul_u32(x: u32, y: u32) -> u32 {
+    x * y
+}
+
+pub fn div_rem_i32(x: i32, y: i32) -> (i32, i32) {
+    (x / y, x % y)
+}
+
+pub fn div_rem_usize(x: usize, y: usize) -> (usize, usize) {
+    (x / y, x % y)
+}
+
+pub fn neg_i64(x: i64) -> i64 {
+    -x
+}
+
+pub fn bits_u16(x: u16, y: u16) -> (u16, u16, u16, u16) {
+    (x & y, x | y, x ^ y, !x)
+}
+
+pub fn not_i8(x: i8) -> i8 {
+    !x
+}
+
+pub fn shifts_u32(x: u32, y: u32) -> (u32, u32) {
+    (x << y, x >> y)
+}
+
+pub fn shifts_i8(x: i8, y: u8) -> (i8, i8) {
+    (x << y, x >> y)
+}
+
+pub fn casts_i32(x: i32) -> (u8, i8, u64, i64, u128, isize) {
+    (x as u8, x as i8, x as u64, x as i64, x as u128, x as isize)
+}
+
+pub fn casts_u64(x: u64) -> (u16, i16, i32, u128, i128, bool) {
+    (x as u16, x as i16, x as i32, x as u128, x as i128, x > 1000)
+}
+
+pub fn compare_i128(x: i128, y: i128) -> (bool, bool, bool, bool, bool, bool) {
+    (x == y, x != y, x < y, x <= y, x > y, x >= y)
+}
+
+pub fn bools(a: bool, b: bool) -> (bool, bool, bool, bool, bool) {
+    (a & b, a | b, a ^ b, !a, a && !b || b)
+}
+
+pub fn classify(c: char) -> u8 {
+    match c {
+        'a'..='z' => 0,
+        'A'..='Z' => 1,
+        '0'..='9' => 2,
+        ' ' | '\t' => 3,
+        _ => 4,
+    }
+}
+
+pub fn mix(x: u16, y: i64, z: u8) -> i64 {
+    let a = (x as i64) * 3 - y;
+    let b = a / ((z as i64) + 1);
+    b % 1000 + (z >> 2) as i64
+}
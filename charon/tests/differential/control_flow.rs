@@ -0,0 +1,117 @@
+//! The loops, the matches, the early returns and the recursive functions.
+pub fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        let t = a % b;
+        a = b;
+        b = t;
+    }
+    a
+}
+
+pub fn fib(n: u8) -> u64 {
+    let mut a = 0u64;
+    let mut b = 1u64;
+    let mut i = 0;
+    while i < n {
+        let c = a + b;
+        a = b;
+        b = c;
+        i += 1;
+    }
+    a
+}
+
+pub fn fact(n: u32) -> u32 {
+    if n == 0 {
+        1
+    } else {
+        n * fact(n - 1)
+    }
+}
+
+pub fn is_even(n: u16) -> bool {
+    if n == 0 {
+        true
+    } else {
+        is_odd(n - 1)
+    }
+}
+
+pub fn is_odd(n: u16) -> bool {
+    if n == 0 {
+        false
+    } else {
+        is_even(n - 1)
+    }
+}
+
+pub fn collatz_steps(mut n: u32) -> u32 {
+    let mut steps = 0;
+    while n > 1 && steps < 200 {
+        if n % 2 == 0 {
+            n /= 2;
+        } else {
+            n = 3 * n + 1;
+        }
+        steps += 1;
+    }
+    steps
+}
+
+pub fn nested_loops(n: u8, m: u8) -> u32 {
+    let mut total = 0u32;
+    let mut i = 0;
+    'outer: loop {
+        if i >= n {
+            break;
+        }
+        i += 1;
+        let mut j = 0;
+        loop {
+            j += 1;
+            if j > m {
+                continue 'outer;
+            }
+            if i * j > 50 {
+                break 'outer;
+            }
+            if (i + j) % 3 == 0 {
+                continue;
+            }
+            total += (i as u32) * (j as u32);
+        }
+    }
+    total
+}
+
+pub fn early_return(x: i32) -> i32 {
+    if x < -100 {
+        return -1;
+    }
+    let mut y = x;
+    while y > 10 {
+        if y % 7 == 0 {
+            return y;
+        }
+        y -= 3;
+    }
+    y * 2
+}
+
+pub fn match_int(x: u8) -> u8 {
+    match x {
+        0 => 10,
+        1 | 2 | 3 => 20,
+        4..=100 => x / 2,
+        _ => 255 - x,
+    }
+}
+
+pub fn match_signed(x: i8) -> i8 {
+    match x {
+        -128 => 0,
+        -5..=-1 => 1,
+        0 => 2,
+        _ => x / 3,
+    }
+}
@@ -0,0 +1,88 @@
+//! The 128-bit integers: the arithmetic, the casts, the constants which don't fit in 64 bits,
+//! the switches on such constants and the discriminants of the enumerations.
+pub fn arith_u128(x: u128, y: u128) -> (u128, u128, u128) {
+    (x + y, x * y, x - y)
+}
+
+pub fn div_rem_i128(x: i128, y: i128) -> (i128, i128) {
+    (x / y, x % y)
+}
+
+pub fn neg_i128(x: i128) -> i128 {
+    -x
+}
+
+pub fn bits_u128(x: u128, y: u128) -> (u128, u128, u128, u128) {
+    (x & y, x | y, x ^ y, !x)
+}
+
+pub fn shifts_i128(x: i128, y: u32) -> (i128, i128) {
+    (x << y, x >> y)
+}
+
+pub fn casts_u128(x: u128) -> (u8, i64, u64, i128, usize) {
+    (x as u8, x as i64, x as u64, x as i128, x as usize)
+}
+
+pub fn casts_i128(x: i128) -> (i8, u32, i64, u128, isize) {
+    (x as i8, x as u32, x as i64, x as u128, x as isize)
+}
+
+pub fn big_constants(x: u128, y: i128) -> (u128, i128, bool) {
+    let a = x ^ 0xFFFF_0000_FFFF_0000_1234_5678_9ABC_DEF0;
+    let b = y.wrapping_add(-170141183460469231731687303715884105728);
+    (a, b, x > 18446744073709551616)
+}
+
+pub fn switch_u128(x: u128) -> u8 {
+    match x {
+        0 => 0,
+        18446744073709551615 => 1,
+        18446744073709551616 => 2,
+        340282366920938463463374607431768211455 => 3,
+        _ => 4,
+    }
+}
+
+pub fn switch_i128(x: i128) -> u8 {
+    match x {
+        -170141183460469231731687303715884105728 => 0,
+        -1 => 1,
+        170141183460469231731687303715884105727 => 2,
+        _ => 3,
+    }
+}
+
+pub fn overflow_ops(x: u128, y: i128) -> (u128, bool, i128, u128) {
+    let (a, b) = x.overflowing_mul(3);
+    (
+        a,
+        b,
+        y.saturating_sub(7),
+        x.wrapping_add(340282366920938463463374607431768211455),
+    )
+}
+
+#[repr(u128)]
+pub enum BigDiscr {
+    A = 1,
+    B = 18446744073709551616,
+    C = 340282366920938463463374607431768211455,
+}
+
+#[repr(i128)]
+pub enum SignedBigDiscr {
+    A = -170141183460469231731687303715884105728,
+    B = -1,
+    C = 170141183460469231731687303715884105727,
+}
+
+pub fn discriminants(x: u8) -> (u128, i128, i8) {
+    let (a, b) = match x % 3 {
+        0 => (BigDiscr::A, SignedBigDiscr::A),
+        1 => (BigDiscr::B, SignedBigDiscr::B),
+        _ => (BigDiscr::C, SignedBigDiscr::C),
+    };
+    let c = BigDiscr::B as i8;
+    (a as u128, b as i128, c)
+}
@@ -0,0 +1,670 @@
+//! Fuzzing of the micro-passes. We translate a small crate to ULLBC, replace the bodies of its
+//! functions `f0`, `f1`, etc. with randomly generated bodies, then run the whole pipeline of
+//! micro-passes (see [charon_lib::driver::transform]) with `--validate`: we check that the bodies
+//! are well-formed after every pass (see [charon_lib::validate]), and that no pass panics.
+//!
+//! The generated bodies are well-typed, and their control-flow is structured (sequences,
+//! conditionals, switches, loops, early returns), like the bodies the compiler generates. They
+//! also respect the invariants the micro-passes rely on: for instance, the switches over the
+//! discriminants of the enumerations immediately follow the discriminant reads. The failures are
+//! reported with the seed which generated the bodies.
+#![feature(rustc_private)]
+
+extern crate rustc_driver;
+extern crate rustc_interface;
+extern crate rustc_middle;
+extern crate rustc_session;
+
+use itertools::Itertools;
+use rustc_driver::{Callbacks, Compilation};
+use rustc_interface::{interface::Compiler, Queries};
+use rustc_middle::ty::TyCtxt;
+use rustc_session::Session;
+use std::any::Any;
+use std::collections::HashSet;
+use std::error::Error;
+use std::panic::{self, AssertUnwindSafe};
+
+use charon_lib::{
+    cli_options::CliOpts,
+    driver,
+    get_mir::MirLevel,
+    meta::Meta,
+    names::PathElem,
+    reorder_decls,
+    transform::{insert_closure_shims, update_closure_signatures},
+    translate_crate_to_ullbc,
+    translate_ctx::{CrateInfo, TranslatedCrate},
+    types::*,
+    ullbc_ast::*,
+    values::*,
+};
+
+/// The crate whose functions we fuzz. The generated bodies have the signatures of the functions
+/// `f0`, `f1`, etc., and use the declarations of the crate.
+static FUZZED_CRATE: &str = "
+pub struct S {
+    pub x: u32,
+    pub b: bool,
+}
+
+pub enum E {
+    A(u32),
+    B(bool, u32),
+    C,
+}
+
+pub fn callee(x: u32, b: bool) -> u32 {
+    if b { x } else { 0 }
+}
+
+pub fn f0(x: u32, y: u32, b: bool, s: S, e: E) -> u32 { 0 }
+pub fn f1(x: u32, y: u32, b: bool, s: S, e: E) -> u32 { 0 }
+pub fn f2(x: u32, y: u32, b: bool, s: S, e: E) -> u32 { 0 }
+pub fn f3(x: u32, y: u32, b: bool, s: S, e: E) -> u32 { 0 }
+";
+
+/// The number of seeds we try.
+const NUM_SEEDS: u64 = 256;
+
+/// The number of control-flow constructs and calls we generate in a body.
+const FUEL: usize = 24;
+
+/// The maximal number of failures we report.
+const MAX_REPORTED: usize = 10;
+
+/// A small pseudo-random generator (xorshift), so that the failures can be reproduced.
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        // The state must not be 0
+        Rng((seed + 1).wrapping_mul(0x9E3779B97F4A7C15))
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn choose<'a, T>(&mut self, elems: &'a [T]) -> &'a T {
+        &elems[self.below(elems.len())]
+    }
+}
+
+/// The declarations of the fuzzed crate the generated bodies use.
+struct Env {
+    u32_ty: Ty,
+    bool_ty: Ty,
+    /// The type of the discriminants of `E`.
+    isize_ty: Ty,
+    s_ty: Ty,
+    e_ty: Ty,
+    s_id: TypeDeclId::Id,
+    e_id: TypeDeclId::Id,
+    callee: FunDeclId::Id,
+    /// The functions whose bodies we generate.
+    fuzzed: Vec<FunDeclId::Id>,
+}
+
+impl Env {
+    fn new(krate: &TranslatedCrate) -> Self {
+        let fun_name = |f: &FunDecl| match f.name.name.last() {
+            Some(PathElem::Ident(name, _)) => name.to_string(),
+            _ => String::new(),
+        };
+        let callee = krate
+            .fun_decls
+            .iter()
+            .find(|f| fun_name(f) == "callee")
+            .unwrap()
+            .def_id;
+        let fuzzed: Vec<_> = krate
+            .fun_decls
+            .iter()
+            .filter(|f| fun_name(f).starts_with('f'))
+            .map(|f| f.def_id)
+            .collect();
+        let inputs = &krate.fun_decls.get(fuzzed[0]).unwrap().signature.inputs;
+        let adt_id = |ty: &Ty| match ty.kind() {
+            TyKind::Adt(TypeId::Adt(id), _) => *id,
+            _ => unreachable!(),
+        };
+        Env {
+            u32_ty: inputs[0].clone(),
+            bool_ty: inputs[2].clone(),
+            isize_ty: TyKind::Literal(LiteralTy::Integer(IntegerTy::Isize)).into_ty(),
+            s_ty: inputs[3].clone(),
+            e_ty: inputs[4].clone(),
+            s_id: adt_id(&inputs[3]),
+            e_id: adt_id(&inputs[4]),
+            callee,
+            fuzzed,
+        }
+    }
+}
+
+/// The variables of the generated bodies: the return value, the arguments `x`, `y`, `b`, `s`
+/// and `e`, then the temporaries.
+const RET: usize = 0;
+const ARG_S: usize = 4;
+const ARG_E: usize = 5;
+
+/// The variants of `E`, with the types of their fields (`true` for `bool`, `false` for `u32`).
+const E_VARIANTS: [&[bool]; 3] = [&[false], &[true, false], &[]];
+
+/// The place `var.field` (`(var as variant).field` if the variant is given).
+fn field(var: usize, ty_id: TypeDeclId::Id, variant: Option<usize>, field: usize) -> Place {
+    let kind = FieldProjKind::Adt(ty_id, variant.map(VariantId::Id::new));
+    Place {
+        var_id: VarId::Id::new(var),
+        projection: vec![ProjectionElem::Field(kind, FieldId::Id::new(field))],
+    }
+}
+
+/// Generate a body. We generate the blocks backwards: every construct is given the block it
+/// continues with.
+struct Generator<'a> {
+    env: &'a Env,
+    rng: Rng,
+    meta: Meta,
+    locals: VarId::Vector<Var>,
+    blocks: BlockId::Vector<BlockData>,
+    /// The number of constructs we can still generate.
+    fuel: usize,
+}
+
+impl<'a> Generator<'a> {
+    fn new(env: &'a Env, rng: Rng, meta: Meta) -> Self {
+        let mut gen = Generator {
+            env,
+            rng,
+            meta,
+            locals: VarId::Vector::new(),
+            blocks: BlockId::Vector::new(),
+            fuel: FUEL,
+        };
+        for (name, ty) in [
+            (None, &env.u32_ty),
+            (Some("x"), &env.u32_ty),
+            (Some("y"), &env.u32_ty),
+            (Some("b"), &env.bool_ty),
+            (Some("s"), &env.s_ty),
+            (Some("e"), &env.e_ty),
+        ] {
+            gen.locals.push_with(|index| Var {
+                index,
+                name: name.map(str::to_string),
+                ty: ty.clone(),
+                debug_info: Vec::new(),
+            });
+        }
+        gen
+    }
+
+    fn fresh_var(&mut self, ty: &Ty) -> VarId::Id {
+        self.locals.push_with(|index| Var {
+            index,
+            name: None,
+            ty: ty.clone(),
+            debug_info: Vec::new(),
+        })
+    }
+
+    /// A variable of the given type: either an existing one, or a fresh one.
+    fn var(&mut self, ty: &Ty) -> VarId::Id {
+        let vars: Vec<VarId::Id> = self
+            .locals
+            .iter()
+            .filter(|v| v.index.index() != RET && &v.ty == ty)
+            .map(|v| v.index)
+            .collect();
+        if vars.is_empty() || self.rng.below(4) == 0 {
+            self.fresh_var(ty)
+        } else {
+            *self.rng.choose(&vars)
+        }
+    }
+
+    /// A place of type `u32` (`bool` if `is_bool`).
+    fn place(&mut self, is_bool: bool) -> Place {
+        let (s_id, e_id) = (self.env.s_id, self.env.e_id);
+        match self.rng.below(6) {
+            0 => field(ARG_S, s_id, None, if is_bool { 1 } else { 0 }),
+            // The fields of the variants of `E`
+            1 if is_bool => field(ARG_E, e_id, Some(1), 0),
+            1 => {
+                let (variant, field_id) = *self.rng.choose(&[(0, 0), (1, 1)]);
+                field(ARG_E, e_id, Some(variant), field_id)
+            }
+            _ => {
+                let ty = if is_bool {
+                    self.env.bool_ty.clone()
+                } else {
+                    self.env.u32_ty.clone()
+                };
+                Place::new(self.var(&ty))
+            }
+        }
+    }
+
+    /// An operand of type `u32` (`bool` if `is_bool`).
+    fn operand(&mut self, is_bool: bool) -> Operand {
+        if self.rng.below(3) == 0 {
+            let (value, ty) = if is_bool {
+                (
+                    Literal::Bool(self.rng.below(2) == 0),
+                    self.env.bool_ty.clone(),
+                )
+            } else {
+                let v = *self.rng.choose(&[0, 1, 2, 7, u32::MAX]);
+                (
+                    Literal::Scalar(ScalarValue::U32(v)),
+                    self.env.u32_ty.clone(),
+                )
+            };
+            Operand::Const(ConstantExpr {
+                value: RawConstantExpr::Literal(value),
+                ty,
+            })
+        } else {
+            Operand::Copy(self.place(is_bool))
+        }
+    }
+
+    fn statement(&self, content: RawStatement) -> Statement {
+        Statement::new(self.meta, content)
+    }
+
+    /// A simple statement: an assignment, or a `StorageDead`.
+    fn simple_statement(&mut self) -> Statement {
+        let content = match self.rng.below(9) {
+            0 => {
+                let binop = *self.rng.choose(&[
+                    BinOp::Add,
+                    BinOp::Sub,
+                    BinOp::Mul,
+                    BinOp::BitAnd,
+                    BinOp::BitOr,
+                    BinOp::BitXor,
+                ]);
+                let rv = Rvalue::BinaryOp(binop, self.operand(false), self.operand(false));
+                RawStatement::Assign(self.place(false), rv)
+            }
+            1 => {
+                let binop = *self.rng.choose(&[
+                    BinOp::Eq,
+                    BinOp::Ne,
+                    BinOp::Lt,
+                    BinOp::Le,
+                    BinOp::Gt,
+                    BinOp::Ge,
+                ]);
+                let rv = Rvalue::BinaryOp(binop, self.operand(false), self.operand(false));
+                RawStatement::Assign(self.place(true), rv)
+            }
+            2 => {
+                let rv = Rvalue::UnaryOp(UnOp::Not, self.operand(true));
+                RawStatement::Assign(self.place(true), rv)
+            }
+            3 => {
+                let is_bool = self.rng.below(2) == 0;
+                let rv = Rvalue::Use(self.operand(is_bool));
+                RawStatement::Assign(self.place(is_bool), rv)
+            }
+            4 => {
+                let kind =
+                    AggregateKind::Adt(TypeId::Adt(self.env.s_id), None, GenericArgs::empty());
+                let rv = Rvalue::Aggregate(kind, vec![self.operand(false), self.operand(true)]);
+                let s_ty = self.env.s_ty.clone();
+                RawStatement::Assign(Place::new(self.var(&s_ty)), rv)
+            }
+            5 => {
+                let variant = self.rng.below(E_VARIANTS.len());
+                let kind = AggregateKind::Adt(
+                    TypeId::Adt(self.env.e_id),
+                    Some(VariantId::Id::new(variant)),
+                    GenericArgs::empty(),
+                );
+                let ops = E_VARIANTS[variant]
+                    .iter()
+                    .map(|is_bool| self.operand(*is_bool))
+                    .collect();
+                let e_ty = self.env.e_ty.clone();
+                RawStatement::Assign(Place::new(self.var(&e_ty)), Rvalue::Aggregate(kind, ops))
+            }
+            6 => {
+                let u32_ty = self.env.u32_ty.clone();
+                RawStatement::StorageDead(self.fresh_var(&u32_ty))
+            }
+            // A discriminant read which is not followed by a switch
+            7 => {
+                let e_ty = self.env.e_ty.clone();
+                let rv = Rvalue::Discriminant(Place::new(self.var(&e_ty)), self.env.e_id);
+                let isize_ty = self.env.isize_ty.clone();
+                RawStatement::Assign(Place::new(self.var(&isize_ty)), rv)
+            }
+            _ => RawStatement::FakeRead(self.place(false)),
+        };
+        self.statement(content)
+    }
+
+    fn new_block(&mut self) -> BlockId::Id {
+        let terminator = Terminator::new(self.meta, RawTerminator::Unreachable);
+        self.blocks.push(BlockData {
+            statements: Vec::new(),
+            terminator,
+        })
+    }
+
+    fn set_block(&mut self, id: BlockId::Id, statements: Vec<Statement>, content: RawTerminator) {
+        self.blocks[id] = BlockData {
+            statements,
+            terminator: Terminator::new(self.meta, content),
+        }
+    }
+
+    /// Generate a block which evaluates some statements and a construct, then continues with
+    /// `next` (unless the construct exits the function).
+    fn code(&mut self, next: BlockId::Id) -> BlockId::Id {
+        let block = self.new_block();
+        let mut statements: Vec<Statement> = (0..self.rng.below(4))
+            .map(|_| self.simple_statement())
+            .collect();
+        let kind = if self.fuel == 0 {
+            0
+        } else {
+            self.fuel -= 1;
+            self.rng.below(10)
+        };
+        let terminator = match kind {
+            // A sequence
+            1 | 2 => {
+                let next = self.code(next);
+                RawTerminator::Goto {
+                    target: self.code(next),
+                }
+            }
+            // A conditional
+            3 => {
+                let discr = self.operand(true);
+                let targets = SwitchTargets::If(self.code(next), self.code(next));
+                RawTerminator::Switch { discr, targets }
+            }
+            // A switch over an integer
+            4 => {
+                let discr = Operand::Copy(self.place(false));
+                let mut values: Vec<u32> = (0..1 + self.rng.below(3))
+                    .map(|_| self.rng.below(4) as u32)
+                    .collect();
+                values.sort();
+                values.dedup();
+                let targets = values
+                    .into_iter()
+                    .map(|v| (ScalarValue::U32(v), self.code(next)))
+                    .collect();
+                let otherwise = self.code(next);
+                let targets = SwitchTargets::SwitchInt(IntegerTy::U32, targets, otherwise);
+                RawTerminator::Switch { discr, targets }
+            }
+            // A match over an enumeration: we read the discriminant, then switch over it
+            5 => {
+                let isize_ty = self.env.isize_ty.clone();
+                let discr = self.fresh_var(&isize_ty);
+                let e_ty = self.env.e_ty.clone();
+                let scrutinee = Place::new(self.var(&e_ty));
+                let rv = Rvalue::Discriminant(scrutinee, self.env.e_id);
+                statements.push(self.statement(RawStatement::Assign(Place::new(discr), rv)));
+                let variants: Vec<usize> = (0..E_VARIANTS.len())
+                    .filter(|_| self.rng.below(3) != 0)
+                    .collect();
+                let otherwise = if variants.len() == E_VARIANTS.len() {
+                    let unreachable = self.new_block();
+                    self.set_block(unreachable, Vec::new(), RawTerminator::Unreachable);
+                    unreachable
+                } else {
+                    self.code(next)
+                };
+                let targets = variants
+                    .into_iter()
+                    .map(|v| (ScalarValue::Isize(v as i64), self.code(next)))
+                    .collect();
+                let targets = SwitchTargets::SwitchInt(IntegerTy::Isize, targets, otherwise);
+                RawTerminator::Switch {
+                    discr: Operand::Move(Place::new(discr)),
+                    targets,
+                }
+            }
+            // A `while` loop: the header evaluates the condition
+            6 => {
+                let header = self.new_block();
+                let body = self.code(header);
+                let header_statements = vec![self.simple_statement()];
+                let discr = self.operand(true);
+                let targets = SwitchTargets::If(body, next);
+                self.set_block(
+                    header,
+                    header_statements,
+                    RawTerminator::Switch { discr, targets },
+                );
+                RawTerminator::Goto { target: header }
+            }
+            // A call
+            7 => {
+                let func = FnOperand::Regular(FnPtr {
+                    func: FunIdOrTraitMethodRef::Fun(FunId::Regular(self.env.callee)),
+                    generics: GenericArgs::empty(),
+                });
+                let call = Call {
+                    func,
+                    args: vec![self.operand(false), self.operand(true)],
+                    dest: self.place(false),
+                    receiver_adjustments: Vec::new(),
+                    resolution: None,
+                    is_tail: false,
+                    is_ghost: false,
+                };
+                RawTerminator::Call {
+                    call,
+                    target: self.code(next),
+                }
+            }
+            // A drop
+            8 => {
+                let ty = if self.rng.below(2) == 0 {
+                    self.env.s_ty.clone()
+                } else {
+                    self.env.e_ty.clone()
+                };
+                RawTerminator::Drop {
+                    place: Place::new(self.var(&ty)),
+                    target: self.code(next),
+                }
+            }
+            // An early exit
+            9 => {
+                if self.rng.below(2) == 0 {
+                    RawTerminator::Panic
+                } else {
+                    let rv = Rvalue::Use(self.operand(false));
+                    let ret = Place::new(VarId::Id::new(RET));
+                    statements.push(self.statement(RawStatement::Assign(ret, rv)));
+                    RawTerminator::Return
+                }
+            }
+            _ => RawTerminator::Goto { target: next },
+        };
+        self.set_block(block, statements, terminator);
+        block
+    }
+
+    /// Generate the blocks of a body.
+    fn body(mut self) -> (VarId::Vector<Var>, BlockId::Vector<BlockData>) {
+        // The entry block must be the first block: we generate it last
+        let entry = self.new_block();
+        assert_eq!(entry, START_BLOCK_ID);
+        let ret_block = self.new_block();
+        let rv = Rvalue::Use(self.operand(false));
+        let ret = self.statement(RawStatement::Assign(Place::new(VarId::Id::new(RET)), rv));
+        self.set_block(ret_block, vec![ret], RawTerminator::Return);
+        let start = self.code(ret_block);
+        self.set_block(entry, Vec::new(), RawTerminator::Goto { target: start });
+        (self.locals, self.blocks)
+    }
+}
+
+/// The declarations the micro-passes modify, which we restore before fuzzing with a new seed.
+struct SavedDecls {
+    type_decls: TypeDecls,
+    fun_decls: FunDecls,
+    global_decls: GlobalDecls,
+    trait_decls: TraitDecls,
+    trait_impls: TraitImpls,
+}
+
+impl SavedDecls {
+    fn save(krate: &TranslatedCrate) -> Self {
+        SavedDecls {
+            type_decls: krate.type_decls.clone(),
+            fun_decls: krate.fun_decls.clone(),
+            global_decls: krate.global_decls.clone(),
+            trait_decls: krate.trait_decls.clone(),
+            trait_impls: krate.trait_impls.clone(),
+        }
+    }
+
+    fn restore(&self, krate: &mut TranslatedCrate) {
+        krate.type_decls = self.type_decls.clone();
+        krate.fun_decls = self.fun_decls.clone();
+        krate.global_decls = self.global_decls.clone();
+        krate.trait_decls = self.trait_decls.clone();
+        krate.trait_impls = self.trait_impls.clone();
+    }
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(msg) => *msg,
+        Err(payload) => match payload.downcast::<&str>() {
+            Ok(msg) => msg.to_string(),
+            Err(_) => "unknown panic".to_string(),
+        },
+    }
+}
+
+/// Fuzz the micro-passes, and return the failures.
+fn fuzz(session: &Session, tcx: TyCtxt) -> Vec<String> {
+    let options = CliOpts {
+        validate: true,
+        ..CliOpts::default()
+    };
+    let crate_info = CrateInfo {
+        crate_name: "fuzz_crate".to_string(),
+        opaque_mods: HashSet::new(),
+    };
+    let mut ctx = translate_crate_to_ullbc::translate(
+        crate_info,
+        &options,
+        &[],
+        session,
+        tcx,
+        MirLevel::Built,
+    )
+    .expect("The translation failed");
+    update_closure_signatures::transform(&mut ctx.translated);
+    insert_closure_shims::transform(&mut ctx);
+    reorder_decls::reorder_declarations(&mut ctx);
+    assert_eq!(ctx.errors.error_count, 0);
+
+    let env = Env::new(&ctx.translated);
+    let saved = SavedDecls::save(&ctx.translated);
+    let mut failures = Vec::new();
+    for seed in 0..NUM_SEEDS {
+        saved.restore(&mut ctx.translated);
+        ctx.errors.def_id = None;
+        ctx.errors.decls_with_errors.clear();
+        let error_count = ctx.errors.error_count;
+
+        let mut rng = Rng::new(seed);
+        for id in &env.fuzzed {
+            let body = ctx
+                .translated
+                .fun_decls
+                .get_mut(*id)
+                .unwrap()
+                .body
+                .as_mut()
+                .unwrap();
+            let generator = Generator::new(&env, Rng(rng.next()), body.meta);
+            (body.locals, body.body) = generator.body();
+        }
+
+        let res = panic::catch_unwind(AssertUnwindSafe(|| {
+            driver::transform(&mut ctx, &options, None, &mut []);
+        }));
+        match res {
+            Err(payload) => failures.push(format!(
+                "seed {seed}: a pass panicked: {}",
+                panic_message(payload)
+            )),
+            Ok(()) if ctx.errors.error_count > error_count => failures.push(format!(
+                "seed {seed}:\n{}",
+                ctx.errors.decls_with_errors.values().flatten().join("\n")
+            )),
+            Ok(()) => (),
+        }
+    }
+    failures
+}
+
+struct FuzzCallbacks {
+    failures: Vec<String>,
+}
+
+impl Callbacks for FuzzCallbacks {
+    fn after_parsing<'tcx>(&mut self, c: &Compiler, queries: &'tcx Queries<'tcx>) -> Compilation {
+        queries.global_ctxt().unwrap().get_mut().enter(|tcx| {
+            self.failures = fuzz(c.session(), tcx);
+        });
+        Compilation::Stop
+    }
+}
+
+#[test]
+fn fuzz_micro_passes() -> Result<(), Box<dyn Error>> {
+    let tmp_dir = tempfile::TempDir::new()?;
+    let file_path = tmp_dir.path().join("fuzz_crate.rs");
+    std::fs::write(&file_path, FUZZED_CRATE)?;
+
+    let args = vec![
+        // The first argument is the name of the executable
+        "__CHARON_FUZZ__".to_string(),
+        file_path.to_string_lossy().into_owned(),
+        "--crate-name=fuzz_crate".to_string(),
+        "--crate-type=lib".to_string(),
+        "--edition=2021".to_string(),
+        "-Awarnings".to_string(),
+    ];
+    let mut callbacks = FuzzCallbacks {
+        failures: Vec::new(),
+    };
+    // The compilation fails if the validation reported errors: we report them below
+    let _ = rustc_driver::RunCompiler::new(&args, &mut callbacks).run();
+
+    let mut failures = callbacks.failures;
+    if !failures.is_empty() {
+        let num = failures.len();
+        failures.truncate(MAX_REPORTED);
+        panic!(
+            "The micro-passes failed on {num} seeds:\n{}",
+            failures.join("\n")
+        )
+    }
+    Ok(())
+}
@@ -0,0 +1,8 @@
+//@ charon-args=--cfg my_cfg
+//@ no-check-output
+// The `--cfg` options are given to rustc.
+#[cfg(not(my_cfg))]
+compile_error!("`my_cfg` should be set");
+
+#[cfg(my_cfg)]
+fn foo() {}
@@ -0,0 +1,6 @@
+//@ charon-args=--extract-opaque-bodies
+
+// The optimized MIR of `is_some` has a discriminant read not followed by a SwitchInt.
+fn my_is_some<T>(opt: Option<T>) -> bool {
+    opt.is_some()
+}
@@ -0,0 +1,146 @@
+//! Tests which call `charon` on a cargo workspace. Cargo calls the driver on
+//! the build scripts and the proc-macro crates of the workspace, which we
+//! compile without extracting them (see [charon_lib::driver::is_build_crate]).
+use assert_cmd::prelude::CommandCargoExt;
+use std::error::Error;
+use std::path::Path;
+use std::process::{Command, Output};
+
+/// Create a workspace with a proc-macro crate (`macros`, whose library is
+/// `macros_lib`) and a crate with a build script (`main`), which uses the
+/// macros.
+fn create_workspace(dir: &Path, macros_lib: &str) -> Result<(), Box<dyn Error>> {
+    let write = |path: &str, content: &str| -> Result<(), Box<dyn Error>> {
+        let path = dir.join(path);
+        std::fs::create_dir_all(path.parent().unwrap())?;
+        std::fs::write(path, content)?;
+        Ok(())
+    };
+    write(
+        "Cargo.toml",
+        r#"
+        [workspace]
+        members = ["macros", "main"]
+        "#,
+    )?;
+    write(
+        "macros/Cargo.toml",
+        r#"
+        [package]
+        name = "macros"
+        version = "0.1.0"
+        edition = "2021"
+
+        [lib]
+        proc-macro = true
+        "#,
+    )?;
+    write("macros/src/lib.rs", macros_lib)?;
+    write(
+        "main/Cargo.toml",
+        r#"
+        [package]
+        name = "main"
+        version = "0.1.0"
+        edition = "2021"
+
+        [dependencies]
+        macros = { path = "../macros" }
+        "#,
+    )?;
+    write(
+        "main/build.rs",
+        r#"
+        fn main() {
+            println!("cargo:rustc-cfg=has_build_script");
+        }
+        "#,
+    )?;
+    write(
+        "main/src/lib.rs",
+        r#"
+        #[cfg(has_build_script)]
+        pub fn answer() -> u32 {
+            macros::answer!()
+        }
+        "#,
+    )?;
+    Ok(())
+}
+
+/// Call `charon` on the `main` crate of a workspace (see [create_workspace]).
+fn run_charon(dir: &Path) -> Result<Output, Box<dyn Error>> {
+    let output = Command::cargo_bin("charon")?
+        .current_dir(dir.join("main"))
+        .arg("--dest")
+        .arg(dir.join("llbc"))
+        .output()?;
+    Ok(output)
+}
+
+#[test]
+fn build_crates() -> Result<(), Box<dyn Error>> {
+    use serde_json::Value;
+    let tmp_dir = tempfile::TempDir::new()?;
+    let dir = tmp_dir.path();
+    create_workspace(
+        dir,
+        r#"
+        use proc_macro::TokenStream;
+
+        #[proc_macro]
+        pub fn answer(_: TokenStream) -> TokenStream {
+            "42u32".parse().unwrap()
+        }
+        "#,
+    )?;
+    let output = run_charon(dir)?;
+    let stderr = String::from_utf8(output.stderr)?;
+    assert!(output.status.success(), "{stderr}");
+
+    // We only extracted the `main` crate, with the configuration options set
+    // by its build script, and the code generated by the macros
+    let outputs: Vec<_> = std::fs::read_dir(dir.join("llbc"))?
+        .map(|entry| entry.unwrap().file_name())
+        .collect();
+    assert_eq!(outputs, ["main.llbc"]);
+    let mut krate: Value = serde_json::from_slice(&std::fs::read(dir.join("llbc/main.llbc"))?)?;
+    charon_lib::diff::resolve_symbols(&mut krate);
+    let names: Vec<&Value> = krate["functions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|decl| &decl["name"][1]["Ident"][0])
+        .collect();
+    assert_eq!(names, ["answer"]);
+    Ok(())
+}
+
+#[test]
+fn build_crate_errors() -> Result<(), Box<dyn Error>> {
+    let tmp_dir = tempfile::TempDir::new()?;
+    let dir = tmp_dir.path();
+    create_workspace(
+        dir,
+        r#"
+        use proc_macro::TokenStream;
+
+        fn f() -> u32 { "f" }
+        fn g() -> u32 { "g" }
+
+        #[proc_macro]
+        pub fn answer(_: TokenStream) -> TokenStream {
+            format!("{}u32", f() + g()).parse().unwrap()
+        }
+        "#,
+    )?;
+    let output = run_charon(dir)?;
+    let stderr = String::from_utf8(output.stderr)?;
+    assert!(!output.status.success(), "{stderr}");
+    // We report the errors of the proc-macro crate
+    assert!(
+        stderr.contains("Compilation encountered 2 errors"),
+        "{stderr}"
+    );
+    Ok(())
+}
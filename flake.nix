@@ -26,24 +26,32 @@
 
         rustToolchain = pkgs.rust-bin.fromRustupToolchainFile ./rust-toolchain.template;
         craneLib = (crane.mkLib pkgs).overrideToolchain rustToolchain;
-        charon =
+        mk-charon = cargoExtraArgs:
           let
             # Clean up the source directory.
             sourceFilter = path: type:
             (craneLib.filterCargoSources path type)
               || (pkgs.lib.hasPrefix (toString ./charon/tests) path)
+              || (pkgs.lib.hasPrefix (toString ./charon/proto) path)
               || (path == toString ./charon/rust-toolchain);
             cleanedUpSrc = pkgs.lib.cleanSourceWith {
               src = ./charon;
               filter = sourceFilter;
             };
-            cargoArtifacts = craneLib.buildDepsOnly { src = cleanedUpSrc; };
+            cargoArtifacts = craneLib.buildDepsOnly {
+              src = cleanedUpSrc;
+              inherit cargoExtraArgs;
+            };
           in craneLib.buildPackage {
             src = cleanedUpSrc;
-            inherit cargoArtifacts;
+            inherit cargoArtifacts cargoExtraArgs;
             # Check the `ui_llbc` files are correct instead of overwriting them.
             cargoTestCommand = "IN_CI=1 cargo test --profile release";
           };
+        charon = mk-charon "--locked";
+        # Charon with the protobuf export (see `--format protobuf`): the tests
+        # check in particular that `proto/numbering.txt` is up to date.
+        charon-protobuf = mk-charon "--locked --features protobuf";
         tests =
           let cargoArtifacts = craneLib.buildDepsOnly { src = ./tests; };
           in craneLib.buildPackage {
@@ -191,6 +199,9 @@
               mkdir -p tests/serialized
               cp ${tests}/llbc/* tests/serialized
               cp ${tests-polonius}/llbc/* tests/serialized
+              # Check that the generated OCaml definitions are up to date
+              ${charon}/bin/charon --generate-ml _generated
+              diff -r generated _generated
             '' else
               "";
             propagatedBuildInputs = with ocamlPackages; [
@@ -229,6 +240,6 @@
             self.packages.${system}.charon-ml
           ];
         };
-        checks = { inherit tests tests-polonius charon-ml-tests; };
+        checks = { inherit tests tests-polonius charon-ml-tests charon-protobuf; };
       });
 }
@@ -0,0 +1,17 @@
+//! This file is extracted as a standalone file, with `charon standalone.rs`
+//! (see `test-standalone` in the Makefile). It only compiles with the edition
+//! and the configuration options given on the command line.
+
+#[cfg(not(feature = "extra"))]
+compile_error!("this file must be extracted with `--cfg 'feature=\"extra\"'`");
+
+/// `async` is a keyword since the 2018 edition: this only compiles with
+/// `--edition 2015`
+pub fn async(x: u32) -> u32 {
+    x + 1
+}
+
+#[cfg(feature = "extra")]
+pub fn extra(x: u32) -> u32 {
+    async(x) * 2
+}